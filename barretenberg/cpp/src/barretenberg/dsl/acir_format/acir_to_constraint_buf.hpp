@@ -367,6 +367,8 @@ AcirFormat circuit_buf_to_acir_format(std::vector<uint8_t> const& buf)
                         throw_or_abort("unitialized MemoryOp");
                     }
                     handle_memory_op(arg, block->second);
+                } else if constexpr (std::is_same_v<T, Circuit::Opcode::Call>) {
+                    throw_or_abort("calls to other circuits are not supported");
                 }
             },
             gate.value);
@@ -644,6 +644,14 @@ struct BrilligOpcode {
     static BrilligOpcode bincodeDeserialize(std::vector<uint8_t>);
 };
 
+struct BrilligBytecode {
+    std::vector<Circuit::BrilligOpcode> bytecode;
+
+    friend bool operator==(const BrilligBytecode&, const BrilligBytecode&);
+    std::vector<uint8_t> bincodeSerialize() const;
+    static BrilligBytecode bincodeDeserialize(std::vector<uint8_t>);
+};
+
 struct Witness {
     uint32_t value;
 
@@ -1126,7 +1134,29 @@ struct Opcode {
         static MemoryInit bincodeDeserialize(std::vector<uint8_t>);
     };
 
-    std::variant<AssertZero, BlackBoxFuncCall, Directive, Brillig, MemoryOp, MemoryInit> value;
+    struct Call {
+        uint32_t id;
+        std::vector<Circuit::Witness> inputs;
+        std::vector<Circuit::Witness> outputs;
+        std::optional<Circuit::Expression> predicate;
+
+        friend bool operator==(const Call&, const Call&);
+        std::vector<uint8_t> bincodeSerialize() const;
+        static Call bincodeDeserialize(std::vector<uint8_t>);
+    };
+
+    struct BrilligCall {
+        uint32_t id;
+        std::vector<Circuit::BrilligInputs> inputs;
+        std::vector<Circuit::BrilligOutputs> outputs;
+        std::optional<Circuit::Expression> predicate;
+
+        friend bool operator==(const BrilligCall&, const BrilligCall&);
+        std::vector<uint8_t> bincodeSerialize() const;
+        static BrilligCall bincodeDeserialize(std::vector<uint8_t>);
+    };
+
+    std::variant<AssertZero, BlackBoxFuncCall, Directive, Brillig, MemoryOp, MemoryInit, Call, BrilligCall> value;
 
     friend bool operator==(const Opcode&, const Opcode&);
     std::vector<uint8_t> bincodeSerialize() const;
@@ -1199,6 +1229,7 @@ struct Circuit {
     PublicInputs return_values;
     std::vector<std::tuple<OpcodeLocation, std::string>> assert_messages;
     bool recursive;
+    std::vector<BrilligBytecode> brillig_bytecode;
 
     friend bool operator==(const Circuit&, const Circuit&);
     std::vector<uint8_t> bincodeSerialize() const;
@@ -4835,6 +4866,56 @@ Circuit::Brillig serde::Deserializable<Circuit::Brillig>::deserialize(Deserializ
 
 namespace Circuit {
 
+inline bool operator==(const BrilligBytecode& lhs, const BrilligBytecode& rhs)
+{
+    if (!(lhs.bytecode == rhs.bytecode)) {
+        return false;
+    }
+    return true;
+}
+
+inline std::vector<uint8_t> BrilligBytecode::bincodeSerialize() const
+{
+    auto serializer = serde::BincodeSerializer();
+    serde::Serializable<BrilligBytecode>::serialize(*this, serializer);
+    return std::move(serializer).bytes();
+}
+
+inline BrilligBytecode BrilligBytecode::bincodeDeserialize(std::vector<uint8_t> input)
+{
+    auto deserializer = serde::BincodeDeserializer(input);
+    auto value = serde::Deserializable<BrilligBytecode>::deserialize(deserializer);
+    if (deserializer.get_buffer_offset() < input.size()) {
+        throw_or_abort("Some input bytes were not read");
+    }
+    return value;
+}
+
+} // end of namespace Circuit
+
+template <>
+template <typename Serializer>
+void serde::Serializable<Circuit::BrilligBytecode>::serialize(const Circuit::BrilligBytecode& obj,
+                                                              Serializer& serializer)
+{
+    serializer.increase_container_depth();
+    serde::Serializable<decltype(obj.bytecode)>::serialize(obj.bytecode, serializer);
+    serializer.decrease_container_depth();
+}
+
+template <>
+template <typename Deserializer>
+Circuit::BrilligBytecode serde::Deserializable<Circuit::BrilligBytecode>::deserialize(Deserializer& deserializer)
+{
+    deserializer.increase_container_depth();
+    Circuit::BrilligBytecode obj;
+    obj.bytecode = serde::Deserializable<decltype(obj.bytecode)>::deserialize(deserializer);
+    deserializer.decrease_container_depth();
+    return obj;
+}
+
+namespace Circuit {
+
 inline bool operator==(const BrilligInputs& lhs, const BrilligInputs& rhs)
 {
     if (!(lhs.value == rhs.value)) {
@@ -6145,6 +6226,9 @@ inline bool operator==(const Circuit& lhs, const Circuit& rhs)
     if (!(lhs.recursive == rhs.recursive)) {
         return false;
     }
+    if (!(lhs.brillig_bytecode == rhs.brillig_bytecode)) {
+        return false;
+    }
     return true;
 }
 
@@ -6180,6 +6264,7 @@ void serde::Serializable<Circuit::Circuit>::serialize(const Circuit::Circuit& ob
     serde::Serializable<decltype(obj.return_values)>::serialize(obj.return_values, serializer);
     serde::Serializable<decltype(obj.assert_messages)>::serialize(obj.assert_messages, serializer);
     serde::Serializable<decltype(obj.recursive)>::serialize(obj.recursive, serializer);
+    serde::Serializable<decltype(obj.brillig_bytecode)>::serialize(obj.brillig_bytecode, serializer);
     serializer.decrease_container_depth();
 }
 
@@ -6197,6 +6282,7 @@ Circuit::Circuit serde::Deserializable<Circuit::Circuit>::deserialize(Deserializ
     obj.return_values = serde::Deserializable<decltype(obj.return_values)>::deserialize(deserializer);
     obj.assert_messages = serde::Deserializable<decltype(obj.assert_messages)>::deserialize(deserializer);
     obj.recursive = serde::Deserializable<decltype(obj.recursive)>::deserialize(deserializer);
+    obj.brillig_bytecode = serde::Deserializable<decltype(obj.brillig_bytecode)>::deserialize(deserializer);
     deserializer.decrease_container_depth();
     return obj;
 }
@@ -7368,6 +7454,128 @@ Circuit::Opcode::MemoryInit serde::Deserializable<Circuit::Opcode::MemoryInit>::
 
 namespace Circuit {
 
+inline bool operator==(const Opcode::Call& lhs, const Opcode::Call& rhs)
+{
+    if (!(lhs.id == rhs.id)) {
+        return false;
+    }
+    if (!(lhs.inputs == rhs.inputs)) {
+        return false;
+    }
+    if (!(lhs.outputs == rhs.outputs)) {
+        return false;
+    }
+    if (!(lhs.predicate == rhs.predicate)) {
+        return false;
+    }
+    return true;
+}
+
+inline std::vector<uint8_t> Opcode::Call::bincodeSerialize() const
+{
+    auto serializer = serde::BincodeSerializer();
+    serde::Serializable<Opcode::Call>::serialize(*this, serializer);
+    return std::move(serializer).bytes();
+}
+
+inline Opcode::Call Opcode::Call::bincodeDeserialize(std::vector<uint8_t> input)
+{
+    auto deserializer = serde::BincodeDeserializer(input);
+    auto value = serde::Deserializable<Opcode::Call>::deserialize(deserializer);
+    if (deserializer.get_buffer_offset() < input.size()) {
+        throw_or_abort("Some input bytes were not read");
+    }
+    return value;
+}
+
+} // end of namespace Circuit
+
+template <>
+template <typename Serializer>
+void serde::Serializable<Circuit::Opcode::Call>::serialize(const Circuit::Opcode::Call& obj, Serializer& serializer)
+{
+    serde::Serializable<decltype(obj.id)>::serialize(obj.id, serializer);
+    serde::Serializable<decltype(obj.inputs)>::serialize(obj.inputs, serializer);
+    serde::Serializable<decltype(obj.outputs)>::serialize(obj.outputs, serializer);
+    serde::Serializable<decltype(obj.predicate)>::serialize(obj.predicate, serializer);
+}
+
+template <>
+template <typename Deserializer>
+Circuit::Opcode::Call serde::Deserializable<Circuit::Opcode::Call>::deserialize(Deserializer& deserializer)
+{
+    Circuit::Opcode::Call obj;
+    obj.id = serde::Deserializable<decltype(obj.id)>::deserialize(deserializer);
+    obj.inputs = serde::Deserializable<decltype(obj.inputs)>::deserialize(deserializer);
+    obj.outputs = serde::Deserializable<decltype(obj.outputs)>::deserialize(deserializer);
+    obj.predicate = serde::Deserializable<decltype(obj.predicate)>::deserialize(deserializer);
+    return obj;
+}
+
+namespace Circuit {
+
+inline bool operator==(const Opcode::BrilligCall& lhs, const Opcode::BrilligCall& rhs)
+{
+    if (!(lhs.id == rhs.id)) {
+        return false;
+    }
+    if (!(lhs.inputs == rhs.inputs)) {
+        return false;
+    }
+    if (!(lhs.outputs == rhs.outputs)) {
+        return false;
+    }
+    if (!(lhs.predicate == rhs.predicate)) {
+        return false;
+    }
+    return true;
+}
+
+inline std::vector<uint8_t> Opcode::BrilligCall::bincodeSerialize() const
+{
+    auto serializer = serde::BincodeSerializer();
+    serde::Serializable<Opcode::BrilligCall>::serialize(*this, serializer);
+    return std::move(serializer).bytes();
+}
+
+inline Opcode::BrilligCall Opcode::BrilligCall::bincodeDeserialize(std::vector<uint8_t> input)
+{
+    auto deserializer = serde::BincodeDeserializer(input);
+    auto value = serde::Deserializable<Opcode::BrilligCall>::deserialize(deserializer);
+    if (deserializer.get_buffer_offset() < input.size()) {
+        throw_or_abort("Some input bytes were not read");
+    }
+    return value;
+}
+
+} // end of namespace Circuit
+
+template <>
+template <typename Serializer>
+void serde::Serializable<Circuit::Opcode::BrilligCall>::serialize(const Circuit::Opcode::BrilligCall& obj,
+                                                                  Serializer& serializer)
+{
+    serde::Serializable<decltype(obj.id)>::serialize(obj.id, serializer);
+    serde::Serializable<decltype(obj.inputs)>::serialize(obj.inputs, serializer);
+    serde::Serializable<decltype(obj.outputs)>::serialize(obj.outputs, serializer);
+    serde::Serializable<decltype(obj.predicate)>::serialize(obj.predicate, serializer);
+}
+
+template <>
+template <typename Deserializer>
+Circuit::Opcode::BrilligCall serde::Deserializable<Circuit::Opcode::BrilligCall>::deserialize(
+    Deserializer& deserializer)
+{
+    Circuit::Opcode::BrilligCall obj;
+    obj.id = serde::Deserializable<decltype(obj.id)>::deserialize(deserializer);
+    obj.inputs = serde::Deserializable<decltype(obj.inputs)>::deserialize(deserializer);
+    obj.outputs = serde::Deserializable<decltype(obj.outputs)>::deserialize(deserializer);
+    obj.predicate = serde::Deserializable<decltype(obj.predicate)>::deserialize(deserializer);
+    return obj;
+}
+
+namespace Circuit {
+
 inline bool operator==(const OpcodeLocation& lhs, const OpcodeLocation& rhs)
 {
     if (!(lhs.value == rhs.value)) {
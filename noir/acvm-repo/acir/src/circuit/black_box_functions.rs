@@ -63,6 +63,21 @@ pub enum BlackBoxFunc {
     Poseidon2Permutation,
     /// SHA256 compression function
     Sha256Compression,
+    /// Calculates the sum of multiple scalar multiplications over the embedded curve on which
+    /// [`FieldElement`][acir_field::FieldElement] is defined.
+    MultiScalarMul,
+    /// Encrypts the inputs with AES-128 in CBC mode, after padding them according to PKCS#7.
+    AES128Encrypt,
+    /// Calculates the SHA512 hash of the inputs.
+    Sha512,
+    /// Permutation function of the original Poseidon
+    PoseidonPermutation,
+    /// Verifies an Ed25519 signature, as specified by RFC 8032.
+    Ed25519,
+    /// Adds two points of the G1 group of the BLS12-381 curve.
+    Bls12381G1Add,
+    /// Multiplies a point of the G1 group of the BLS12-381 curve by a scalar.
+    Bls12381G1Mul,
 }
 
 impl std::fmt::Display for BlackBoxFunc {
@@ -98,6 +113,13 @@ impl BlackBoxFunc {
             BlackBoxFunc::BigIntToLeBytes => "bigint_to_le_bytes",
             BlackBoxFunc::Poseidon2Permutation => "poseidon2_permutation",
             BlackBoxFunc::Sha256Compression => "sha256_compression",
+            BlackBoxFunc::MultiScalarMul => "multi_scalar_mul",
+            BlackBoxFunc::AES128Encrypt => "aes128_encrypt",
+            BlackBoxFunc::Sha512 => "sha512",
+            BlackBoxFunc::PoseidonPermutation => "poseidon_permutation",
+            BlackBoxFunc::Ed25519 => "ed25519",
+            BlackBoxFunc::Bls12381G1Add => "bls12_381_g1_add",
+            BlackBoxFunc::Bls12381G1Mul => "bls12_381_g1_mul",
         }
     }
 
@@ -127,6 +149,13 @@ impl BlackBoxFunc {
             "bigint_to_le_bytes" => Some(BlackBoxFunc::BigIntToLeBytes),
             "poseidon2_permutation" => Some(BlackBoxFunc::Poseidon2Permutation),
             "sha256_compression" => Some(BlackBoxFunc::Sha256Compression),
+            "multi_scalar_mul" => Some(BlackBoxFunc::MultiScalarMul),
+            "aes128_encrypt" => Some(BlackBoxFunc::AES128Encrypt),
+            "sha512" => Some(BlackBoxFunc::Sha512),
+            "poseidon_permutation" => Some(BlackBoxFunc::PoseidonPermutation),
+            "ed25519" => Some(BlackBoxFunc::Ed25519),
+            "bls12_381_g1_add" => Some(BlackBoxFunc::Bls12381G1Add),
+            "bls12_381_g1_mul" => Some(BlackBoxFunc::Bls12381G1Mul),
             _ => None,
         }
     }
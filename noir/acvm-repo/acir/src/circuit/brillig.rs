@@ -29,3 +29,10 @@ pub struct Brillig {
     /// Predicate of the Brillig execution - indicates if it should be skipped
     pub predicate: Option<Expression>,
 }
+
+/// The bytecode of a Brillig program, stored once in a [`Circuit`][super::Circuit] and executed
+/// by each [`Opcode::BrilligCall`][super::Opcode::BrilligCall] which refers to it.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize, Debug, Default)]
+pub struct BrilligBytecode {
+    pub bytecode: Vec<BrilligOpcode>,
+}
@@ -3,7 +3,9 @@ pub mod brillig;
 pub mod directives;
 pub mod opcodes;
 
-use crate::native_types::Witness;
+use self::brillig::BrilligBytecode;
+use crate::brillig::Opcode as BrilligOpcode;
+use crate::native_types::{Witness, WitnessMap};
 pub use opcodes::Opcode;
 use thiserror::Error;
 
@@ -23,7 +25,7 @@ use std::collections::BTreeSet;
 /// Bounded Expressions are useful if you are eventually going to pass the ACIR
 /// into a proving system which supports PLONK, where arithmetic expressions have a
 /// finite fan-in.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
 pub enum ExpressionWidth {
     #[default]
     Unbounded,
@@ -56,22 +58,104 @@ pub struct Circuit {
     // Note: This should be a BTreeMap, but serde-reflect is creating invalid
     // c++ code at the moment when it is, due to OpcodeLocation needing a comparison
     // implementation which is never generated.
-    pub assert_messages: Vec<(OpcodeLocation, String)>,
+    pub assert_messages: Vec<(OpcodeLocation, AssertionPayload)>,
 
     /// States whether the backend should use a SNARK recursion friendly prover.
     /// If implemented by a backend, this means that proofs generated with this circuit
     /// will be friendly for recursively verifying inside of another SNARK.
     pub recursive: bool,
+
+    /// The distinct Brillig bytecode executed by the [`Opcode::BrilligCall`]s of the circuit,
+    /// which refer to it by index.
+    pub brillig_bytecode: Vec<BrilligBytecode>,
 }
 
 impl Circuit {
+    /// Returns the Brillig bytecode executed by `opcode`, if it is a [`Opcode::Brillig`] or an
+    /// [`Opcode::BrilligCall`] of this circuit.
+    ///
+    /// Returns `None` for an [`Opcode::BrilligCall`] whose id is not in `brillig_bytecode`.
+    pub fn brillig_bytecode_of<'a>(&'a self, opcode: &'a Opcode) -> Option<&'a [BrilligOpcode]> {
+        match opcode {
+            Opcode::Brillig(brillig) => Some(&brillig.bytecode),
+            Opcode::BrilligCall { id, .. } => {
+                self.brillig_bytecode.get(*id as usize).map(|brillig| &brillig.bytecode[..])
+            }
+            _ => None,
+        }
+    }
+
     /// Returns the assert message associated with the provided [`OpcodeLocation`].
     /// Returns `None` if no such assert message exists.
-    pub fn get_assert_message(&self, opcode_location: OpcodeLocation) -> Option<&str> {
+    pub fn get_assert_message(&self, opcode_location: OpcodeLocation) -> Option<&AssertionPayload> {
         self.assert_messages
             .iter()
             .find(|(loc, _)| *loc == opcode_location)
-            .map(|(_, message)| message.as_str())
+            .map(|(_, message)| message)
+    }
+}
+
+/// The message of a failed assertion, which may refer to the values of witnesses at the time
+/// of the failure, e.g. `expected _3 got _7`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssertionPayload {
+    pub parts: Vec<AssertionPayloadPart>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AssertionPayloadPart {
+    Text(String),
+    Witness(Witness),
+}
+
+impl AssertionPayload {
+    /// Returns the witnesses whose values are part of the message, in order.
+    pub fn witnesses(&self) -> impl Iterator<Item = Witness> + '_ {
+        self.parts.iter().filter_map(|part| match part {
+            AssertionPayloadPart::Text(_) => None,
+            AssertionPayloadPart::Witness(witness) => Some(*witness),
+        })
+    }
+
+    /// Returns the message with the value of each witness taken from `witness_map`.
+    /// Witnesses which have not been solved are shown as in [`Display`][std::fmt::Display].
+    pub fn render(&self, witness_map: &WitnessMap) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                AssertionPayloadPart::Witness(witness) => match witness_map.get(witness) {
+                    Some(value) => value.to_string(),
+                    None => format!("_{}", witness.witness_index()),
+                },
+                AssertionPayloadPart::Text(text) => text.clone(),
+            })
+            .collect()
+    }
+}
+
+impl From<String> for AssertionPayload {
+    fn from(message: String) -> Self {
+        AssertionPayload { parts: vec![AssertionPayloadPart::Text(message)] }
+    }
+}
+
+impl From<&str> for AssertionPayload {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+impl std::fmt::Display for AssertionPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for part in &self.parts {
+            match part {
+                AssertionPayloadPart::Text(text) => write!(f, "{text}")?,
+                AssertionPayloadPart::Witness(witness) => {
+                    write!(f, "_{}", witness.witness_index())?;
+                }
+            }
+        }
+        Ok(())
     }
 }
 
@@ -256,9 +340,12 @@ mod tests {
 
     use super::{
         opcodes::{BlackBoxFuncCall, FunctionInput},
-        Circuit, Compression, Opcode, PublicInputs,
+        AssertionPayload, AssertionPayloadPart, Circuit, Compression, Opcode, PublicInputs,
+    };
+    use crate::{
+        circuit::ExpressionWidth,
+        native_types::{Witness, WitnessMap},
     };
-    use crate::{circuit::ExpressionWidth, native_types::Witness};
     use acir_field::FieldElement;
 
     fn and_opcode() -> Opcode {
@@ -343,6 +430,7 @@ mod tests {
             return_values: PublicInputs(BTreeSet::from_iter(vec![Witness(4), Witness(12)])),
             assert_messages: Default::default(),
             recursive: false,
+            brillig_bytecode: Vec::new(),
         };
 
         fn read_write(circuit: Circuit) -> (Circuit, Circuit) {
@@ -375,6 +463,7 @@ mod tests {
             return_values: PublicInputs(BTreeSet::from_iter(vec![Witness(2)])),
             assert_messages: Default::default(),
             recursive: false,
+            brillig_bytecode: Vec::new(),
         };
 
         let json = serde_json::to_string_pretty(&circuit).unwrap();
@@ -383,6 +472,23 @@ mod tests {
         assert_eq!(circuit, deserialized);
     }
 
+    #[test]
+    fn renders_assertion_payload_with_solved_witnesses() {
+        let payload = AssertionPayload {
+            parts: vec![
+                AssertionPayloadPart::Text("expected ".to_string()),
+                AssertionPayloadPart::Witness(Witness(3)),
+                AssertionPayloadPart::Text(" got ".to_string()),
+                AssertionPayloadPart::Witness(Witness(7)),
+            ],
+        };
+        let mut witness_map = WitnessMap::new();
+        witness_map.insert(Witness(3), FieldElement::from(5u128));
+
+        assert_eq!(payload.render(&witness_map), "expected 5 got _7");
+        assert_eq!(payload.to_string(), "expected _3 got _7");
+    }
+
     #[test]
     fn does_not_panic_on_invalid_circuit() {
         use std::io::Write;
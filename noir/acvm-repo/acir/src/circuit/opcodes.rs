@@ -1,4 +1,7 @@
-use super::{brillig::Brillig, directives::Directive};
+use super::{
+    brillig::{Brillig, BrilligInputs, BrilligOutputs},
+    directives::Directive,
+};
 use crate::native_types::{Expression, Witness};
 use serde::{Deserialize, Serialize};
 
@@ -28,6 +31,33 @@ pub enum Opcode {
         block_id: BlockId,
         init: Vec<Witness>,
     },
+    /// Calls another circuit of the program, constraining `outputs` to be its return values when
+    /// its parameters are assigned `inputs`.
+    ///
+    /// The parameters of the called circuit are the witnesses `0..inputs.len()`, and `outputs`
+    /// correspond to its return values in increasing order of their witness indices.
+    Call {
+        /// Index of the circuit to call within the program.
+        id: u32,
+        inputs: Vec<Witness>,
+        outputs: Vec<Witness>,
+        /// Predicate of the call - indicates if it should be skipped, in which case its outputs
+        /// are zero
+        predicate: Option<Expression>,
+    },
+    /// Executes the Brillig bytecode stored in the circuit at index `id`, as a [`Brillig`]
+    /// opcode with the same inputs, outputs and predicate would.
+    ///
+    /// This allows the opcodes which execute the same bytecode to share a single copy of it.
+    BrilligCall {
+        /// Index of the bytecode within the [`brillig_bytecode`][super::Circuit::brillig_bytecode]
+        /// of the circuit.
+        id: u32,
+        inputs: Vec<BrilligInputs>,
+        outputs: Vec<BrilligOutputs>,
+        /// Predicate of the Brillig execution - indicates if it should be skipped
+        predicate: Option<Expression>,
+    },
 }
 
 impl std::fmt::Display for Opcode {
@@ -99,6 +129,22 @@ impl std::fmt::Display for Opcode {
                 write!(f, "INIT ")?;
                 write!(f, "(id: {}, len: {}) ", block_id.0, init.len())
             }
+            Opcode::Call { id, inputs, outputs, predicate } => {
+                write!(f, "CALL func {id}: ")?;
+                if let Some(pred) = predicate {
+                    writeln!(f, "PREDICATE = {pred}")?;
+                }
+                writeln!(f, "inputs: {inputs:?}")?;
+                write!(f, "outputs: {outputs:?}")
+            }
+            Opcode::BrilligCall { id, inputs, outputs, predicate } => {
+                write!(f, "BRILLIG CALL func {id}: ")?;
+                if let Some(pred) = predicate {
+                    writeln!(f, "PREDICATE = {pred}")?;
+                }
+                writeln!(f, "inputs: {inputs:?}")?;
+                write!(f, "outputs: {outputs:?}")
+            }
         }
     }
 }
@@ -170,6 +170,78 @@ pub enum BlackBoxFuncCall {
         /// Output of the compression, represented by 8 u32s
         outputs: Vec<Witness>,
     },
+    /// Computes the sum of the scalar multiplications of the given points over the embedded curve
+    ///
+    /// # Arguments
+    ///
+    /// * `points` - the points, each represented by its x and y coordinates
+    /// * `scalars` - the scalar of each point, represented by its low and high 128 bit limbs
+    /// * `outputs` - the x and y coordinates of the resulting point
+    MultiScalarMul {
+        points: Vec<FunctionInput>,
+        scalars: Vec<FunctionInput>,
+        outputs: (Witness, Witness),
+    },
+    /// Encrypts the input message with AES-128 in CBC mode
+    ///
+    /// # Arguments
+    ///
+    /// * `inputs` - the bytes of the message, which are padded according to PKCS#7
+    /// * `iv` - the 16 bytes of the initialization vector
+    /// * `key` - the 16 bytes of the key
+    /// * `outputs` - the bytes of the ciphertext, one block longer than the message rounded down
+    ///   to a whole number of blocks
+    AES128Encrypt {
+        inputs: Vec<FunctionInput>,
+        iv: Vec<FunctionInput>,
+        key: Vec<FunctionInput>,
+        outputs: Vec<Witness>,
+    },
+    Sha512 {
+        inputs: Vec<FunctionInput>,
+        outputs: Vec<Witness>,
+    },
+    /// Applies the permutation function of the original Poseidon to the given state,
+    /// outputting the permuted state.
+    PoseidonPermutation {
+        /// Input state for the permutation of Poseidon
+        inputs: Vec<FunctionInput>,
+        /// Permuted state
+        outputs: Vec<Witness>,
+        /// State length `t` (in number of field elements)
+        /// It is the length of inputs and outputs vectors
+        len: u32,
+        /// Number of state elements absorbed per permutation, the remaining `len - rate`
+        /// elements being the capacity. Together with `len`, it selects the round constants.
+        rate: u32,
+    },
+    Ed25519 {
+        /// The 32 byte encoding of the public key
+        public_key: Vec<FunctionInput>,
+        /// The 32 byte encoding of the point `R`, followed by the 32 byte scalar `S`
+        signature: Vec<FunctionInput>,
+        /// The message, which is hashed along with `R` and the public key by the verifier
+        message: Vec<FunctionInput>,
+        output: Witness,
+    },
+    /// Adds two points of the G1 group of BLS12-381. Points are encoded as the 48 byte big
+    /// endian encodings of their affine `x` and `y` coordinates, with the point at infinity
+    /// encoded as zero bytes.
+    Bls12381G1Add {
+        input1: Vec<FunctionInput>,
+        input2: Vec<FunctionInput>,
+        /// The 96 byte encoding of the sum
+        outputs: Vec<Witness>,
+    },
+    /// Multiplies a point of the G1 group of BLS12-381, encoded as for `Bls12381G1Add`, by a
+    /// scalar.
+    Bls12381G1Mul {
+        point: Vec<FunctionInput>,
+        /// The 32 byte big endian encoding of the scalar
+        scalar: Vec<FunctionInput>,
+        /// The 96 byte encoding of the product
+        outputs: Vec<Witness>,
+    },
 }
 
 impl BlackBoxFuncCall {
@@ -200,6 +272,13 @@ impl BlackBoxFuncCall {
             BlackBoxFuncCall::BigIntToLeBytes { .. } => BlackBoxFunc::BigIntToLeBytes,
             BlackBoxFuncCall::Poseidon2Permutation { .. } => BlackBoxFunc::Poseidon2Permutation,
             BlackBoxFuncCall::Sha256Compression { .. } => BlackBoxFunc::Sha256Compression,
+            BlackBoxFuncCall::MultiScalarMul { .. } => BlackBoxFunc::MultiScalarMul,
+            BlackBoxFuncCall::AES128Encrypt { .. } => BlackBoxFunc::AES128Encrypt,
+            BlackBoxFuncCall::Sha512 { .. } => BlackBoxFunc::Sha512,
+            BlackBoxFuncCall::PoseidonPermutation { .. } => BlackBoxFunc::PoseidonPermutation,
+            BlackBoxFuncCall::Ed25519 { .. } => BlackBoxFunc::Ed25519,
+            BlackBoxFuncCall::Bls12381G1Add { .. } => BlackBoxFunc::Bls12381G1Add,
+            BlackBoxFuncCall::Bls12381G1Mul { .. } => BlackBoxFunc::Bls12381G1Mul,
         }
     }
 
@@ -210,6 +289,8 @@ impl BlackBoxFuncCall {
     pub fn get_inputs_vec(&self) -> Vec<FunctionInput> {
         match self {
             BlackBoxFuncCall::SHA256 { inputs, .. }
+            | BlackBoxFuncCall::Sha512 { inputs, .. }
+            | BlackBoxFuncCall::PoseidonPermutation { inputs, .. }
             | BlackBoxFuncCall::Blake2s { inputs, .. }
             | BlackBoxFuncCall::Blake3 { inputs, .. }
             | BlackBoxFuncCall::Keccak256 { inputs, .. }
@@ -228,6 +309,19 @@ impl BlackBoxFuncCall {
             | BlackBoxFuncCall::BigIntDiv { .. }
             | BlackBoxFuncCall::BigIntToLeBytes { .. } => Vec::new(),
             BlackBoxFuncCall::FixedBaseScalarMul { low, high, .. } => vec![*low, *high],
+            BlackBoxFuncCall::AES128Encrypt { inputs, iv, key, .. } => {
+                let mut all_inputs = Vec::with_capacity(inputs.len() + iv.len() + key.len());
+                all_inputs.extend(inputs.iter().copied());
+                all_inputs.extend(iv.iter().copied());
+                all_inputs.extend(key.iter().copied());
+                all_inputs
+            }
+            BlackBoxFuncCall::MultiScalarMul { points, scalars, .. } => {
+                let mut inputs = Vec::with_capacity(points.len() + scalars.len());
+                inputs.extend(points.iter().copied());
+                inputs.extend(scalars.iter().copied());
+                inputs
+            }
             BlackBoxFuncCall::EmbeddedCurveAdd {
                 input1_x, input1_y, input2_x, input2_y, ..
             } => vec![*input1_x, *input1_y, *input2_x, *input2_y],
@@ -265,6 +359,21 @@ impl BlackBoxFuncCall {
                 inputs.extend(hashed_message.iter().copied());
                 inputs
             }
+            BlackBoxFuncCall::Ed25519 { public_key, signature, message, .. } => {
+                let mut inputs =
+                    Vec::with_capacity(public_key.len() + signature.len() + message.len());
+                inputs.extend(public_key.iter().copied());
+                inputs.extend(signature.iter().copied());
+                inputs.extend(message.iter().copied());
+                inputs
+            }
+            BlackBoxFuncCall::Bls12381G1Add { input1: lhs, input2: rhs, .. }
+            | BlackBoxFuncCall::Bls12381G1Mul { point: lhs, scalar: rhs, .. } => {
+                let mut inputs = Vec::with_capacity(lhs.len() + rhs.len());
+                inputs.extend(lhs.iter().copied());
+                inputs.extend(rhs.iter().copied());
+                inputs
+            }
             BlackBoxFuncCall::EcdsaSecp256r1 {
                 public_key_x,
                 public_key_y,
@@ -308,20 +417,27 @@ impl BlackBoxFuncCall {
     pub fn get_outputs_vec(&self) -> Vec<Witness> {
         match self {
             BlackBoxFuncCall::SHA256 { outputs, .. }
+            | BlackBoxFuncCall::Sha512 { outputs, .. }
+            | BlackBoxFuncCall::PoseidonPermutation { outputs, .. }
             | BlackBoxFuncCall::Blake2s { outputs, .. }
             | BlackBoxFuncCall::Blake3 { outputs, .. }
             | BlackBoxFuncCall::Keccak256 { outputs, .. }
             | BlackBoxFuncCall::Keccakf1600 { outputs, .. }
             | BlackBoxFuncCall::Keccak256VariableLength { outputs, .. }
             | BlackBoxFuncCall::Poseidon2Permutation { outputs, .. }
-            | BlackBoxFuncCall::Sha256Compression { outputs, .. } => outputs.to_vec(),
+            | BlackBoxFuncCall::Sha256Compression { outputs, .. }
+            | BlackBoxFuncCall::AES128Encrypt { outputs, .. }
+            | BlackBoxFuncCall::Bls12381G1Add { outputs, .. }
+            | BlackBoxFuncCall::Bls12381G1Mul { outputs, .. } => outputs.to_vec(),
             BlackBoxFuncCall::AND { output, .. }
             | BlackBoxFuncCall::XOR { output, .. }
             | BlackBoxFuncCall::SchnorrVerify { output, .. }
             | BlackBoxFuncCall::EcdsaSecp256k1 { output, .. }
             | BlackBoxFuncCall::PedersenHash { output, .. }
-            | BlackBoxFuncCall::EcdsaSecp256r1 { output, .. } => vec![*output],
+            | BlackBoxFuncCall::EcdsaSecp256r1 { output, .. }
+            | BlackBoxFuncCall::Ed25519 { output, .. } => vec![*output],
             BlackBoxFuncCall::FixedBaseScalarMul { outputs, .. }
+            | BlackBoxFuncCall::MultiScalarMul { outputs, .. }
             | BlackBoxFuncCall::PedersenCommitment { outputs, .. }
             | BlackBoxFuncCall::EmbeddedCurveAdd { outputs, .. } => vec![outputs.0, outputs.1],
             BlackBoxFuncCall::RANGE { .. }
@@ -42,7 +42,7 @@ mod reflection {
             brillig::{BrilligInputs, BrilligOutputs},
             directives::Directive,
             opcodes::BlackBoxFuncCall,
-            Circuit, ExpressionWidth, Opcode, OpcodeLocation,
+            AssertionPayloadPart, Circuit, ExpressionWidth, Opcode, OpcodeLocation,
         },
         native_types::{Witness, WitnessMap},
     };
@@ -63,6 +63,7 @@ mod reflection {
         tracer.trace_simple_type::<ExpressionWidth>().unwrap();
         tracer.trace_simple_type::<Opcode>().unwrap();
         tracer.trace_simple_type::<OpcodeLocation>().unwrap();
+        tracer.trace_simple_type::<AssertionPayloadPart>().unwrap();
         tracer.trace_simple_type::<BinaryFieldOp>().unwrap();
         tracer.trace_simple_type::<BlackBoxFuncCall>().unwrap();
         tracer.trace_simple_type::<BrilligInputs>().unwrap();
@@ -45,12 +45,12 @@ fn addition_circuit() {
     let bytes = Circuit::serialize_circuit(&circuit);
 
     let expected_serialization: Vec<u8> = vec![
-        31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 173, 208, 49, 14, 192, 32, 8, 5, 80, 212, 30, 8, 4, 20,
-        182, 94, 165, 166, 122, 255, 35, 52, 77, 28, 76, 58, 214, 191, 124, 166, 23, 242, 15, 0, 8,
-        240, 77, 154, 125, 206, 198, 127, 161, 176, 209, 138, 139, 197, 88, 68, 122, 205, 157, 152,
-        46, 204, 222, 76, 81, 180, 21, 35, 35, 53, 189, 179, 49, 119, 19, 171, 222, 188, 162, 147,
-        112, 167, 161, 206, 99, 98, 105, 223, 95, 248, 26, 113, 90, 97, 185, 97, 217, 56, 173, 35,
-        63, 243, 81, 87, 163, 125, 1, 0, 0,
+        31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 173, 144, 65, 14, 128, 32, 12, 4, 139, 248, 160, 150,
+        182, 208, 222, 252, 138, 68, 248, 255, 19, 140, 9, 7, 18, 189, 201, 92, 118, 79, 147, 205,
+        238, 0, 16, 224, 77, 28, 121, 140, 196, 127, 80, 88, 232, 218, 38, 23, 99, 22, 105, 37, 53,
+        98, 58, 49, 121, 53, 69, 209, 154, 141, 140, 212, 244, 74, 198, 220, 76, 172, 120, 245,
+        130, 78, 194, 141, 186, 58, 247, 33, 139, 235, 118, 225, 227, 216, 134, 43, 76, 29, 166,
+        143, 35, 124, 112, 3, 202, 243, 104, 183, 133, 1, 0, 0,
     ];
 
     assert_eq!(bytes, expected_serialization)
@@ -75,9 +75,9 @@ fn fixed_base_scalar_mul_circuit() {
     let bytes = Circuit::serialize_circuit(&circuit);
 
     let expected_serialization: Vec<u8> = vec![
-        31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 77, 138, 91, 10, 0, 32, 16, 2, 109, 171, 175, 46, 221,
-        209, 247, 229, 130, 130, 140, 200, 92, 0, 11, 157, 228, 35, 127, 212, 200, 29, 61, 116, 76,
-        220, 217, 250, 171, 91, 113, 160, 66, 104, 242, 97, 0, 0, 0,
+        31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 85, 138, 81, 10, 0, 48, 8, 66, 93, 219, 190, 118, 233,
+        29, 189, 34, 3, 19, 228, 137, 188, 11, 96, 161, 146, 124, 228, 143, 26, 185, 163, 135, 142,
+        137, 219, 91, 127, 117, 71, 28, 191, 210, 168, 202, 105, 0, 0, 0,
     ];
 
     assert_eq!(bytes, expected_serialization)
@@ -102,9 +102,9 @@ fn pedersen_circuit() {
     let bytes = Circuit::serialize_circuit(&circuit);
 
     let expected_serialization: Vec<u8> = vec![
-        31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 93, 74, 135, 9, 0, 48, 8, 75, 171, 224, 255, 15, 139,
-        27, 196, 64, 200, 100, 0, 15, 133, 80, 57, 89, 219, 127, 39, 173, 126, 235, 236, 247, 151,
-        48, 224, 71, 90, 33, 97, 0, 0, 0,
+        31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 93, 74, 11, 10, 0, 80, 4, 219, 123, 148, 251, 95, 88, 8,
+        133, 213, 218, 151, 1, 60, 36, 66, 229, 100, 45, 255, 157, 52, 250, 169, 189, 223, 223,
+        130, 1, 218, 156, 234, 54, 105, 0, 0, 0,
     ];
 
     assert_eq!(bytes, expected_serialization)
@@ -143,22 +143,22 @@ fn schnorr_verify_circuit() {
     let bytes = Circuit::serialize_circuit(&circuit);
 
     let expected_serialization: Vec<u8> = vec![
-        31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 77, 210, 7, 74, 3, 1, 20, 69, 209, 177, 247, 222, 123,
-        239, 189, 119, 141, 93, 99, 220, 133, 251, 95, 130, 152, 103, 78, 32, 3, 195, 33, 4, 66,
-        248, 239, 254, 20, 69, 209, 84, 212, 158, 216, 206, 223, 234, 219, 204, 146, 239, 91, 170,
-        111, 103, 245, 109, 101, 27, 219, 217, 193, 250, 219, 197, 110, 246, 176, 151, 125, 236,
-        231, 0, 7, 57, 196, 97, 142, 112, 148, 99, 28, 231, 4, 39, 57, 197, 105, 206, 112, 150,
-        115, 156, 231, 2, 23, 185, 196, 101, 174, 112, 149, 107, 92, 231, 6, 55, 185, 197, 109,
-        238, 112, 151, 123, 220, 231, 1, 15, 121, 196, 99, 158, 240, 148, 103, 60, 231, 5, 47, 121,
-        197, 107, 222, 240, 150, 119, 188, 231, 3, 75, 124, 228, 83, 195, 142, 121, 158, 125, 126,
-        225, 43, 223, 248, 206, 15, 126, 178, 204, 47, 86, 248, 237, 119, 43, 76, 127, 105, 47,
-        189, 165, 181, 116, 150, 198, 234, 125, 117, 249, 47, 233, 41, 45, 165, 163, 52, 148, 126,
-        210, 78, 186, 73, 51, 233, 37, 173, 164, 147, 52, 146, 62, 210, 70, 186, 72, 19, 233, 33,
-        45, 164, 131, 52, 144, 253, 23, 139, 218, 238, 217, 60, 123, 103, 235, 236, 156, 141, 179,
-        239, 166, 93, 183, 237, 185, 107, 199, 125, 251, 29, 218, 237, 216, 94, 167, 118, 58, 183,
-        207, 165, 93, 174, 237, 113, 107, 135, 123, 247, 47, 185, 251, 147, 59, 191, 184, 239, 155,
-        187, 126, 184, 103, 217, 29, 235, 55, 171, 223, 173, 104, 184, 231, 255, 243, 7, 236, 52,
-        239, 128, 225, 3, 0, 0,
+        31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 85, 210, 7, 74, 3, 1, 20, 69, 209, 177, 247, 222, 123,
+        239, 189, 119, 141, 93, 99, 220, 133, 251, 95, 130, 152, 71, 78, 32, 14, 12, 135, 16, 8,
+        225, 191, 251, 83, 20, 69, 83, 81, 123, 98, 59, 127, 171, 111, 51, 75, 190, 111, 169, 190,
+        157, 213, 183, 149, 109, 108, 103, 7, 235, 111, 23, 187, 217, 195, 94, 246, 177, 159, 3,
+        28, 228, 16, 135, 57, 194, 81, 142, 113, 156, 19, 156, 228, 20, 167, 57, 195, 89, 206, 113,
+        158, 11, 92, 228, 18, 151, 185, 194, 85, 174, 113, 157, 27, 220, 228, 22, 183, 185, 195,
+        93, 238, 113, 159, 7, 60, 228, 17, 143, 121, 194, 83, 158, 241, 156, 23, 188, 228, 21, 175,
+        121, 195, 91, 222, 241, 158, 15, 44, 241, 145, 79, 13, 59, 230, 121, 246, 249, 133, 175,
+        124, 227, 59, 63, 248, 201, 50, 191, 88, 225, 183, 223, 173, 48, 253, 165, 189, 244, 150,
+        214, 210, 89, 26, 171, 247, 213, 229, 191, 164, 167, 180, 148, 142, 210, 80, 250, 73, 59,
+        233, 38, 205, 164, 151, 180, 146, 78, 210, 72, 250, 72, 27, 233, 34, 77, 164, 135, 180,
+        144, 14, 210, 64, 246, 95, 44, 106, 187, 103, 243, 236, 157, 173, 179, 115, 54, 206, 190,
+        155, 118, 221, 182, 231, 174, 29, 247, 237, 119, 104, 183, 99, 123, 157, 218, 233, 220, 62,
+        151, 118, 185, 182, 199, 173, 29, 238, 221, 191, 228, 238, 79, 238, 252, 226, 190, 111,
+        238, 250, 225, 158, 101, 119, 172, 223, 172, 126, 183, 162, 225, 158, 255, 158, 63, 195,
+        119, 133, 187, 233, 3, 0, 0,
     ];
 
     assert_eq!(bytes, expected_serialization)
@@ -206,11 +206,11 @@ fn simple_brillig_foreign_call() {
     let bytes = Circuit::serialize_circuit(&circuit);
 
     let expected_serialization: Vec<u8> = vec![
-        31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 173, 143, 177, 10, 192, 32, 16, 67, 227, 21, 74, 233,
-        212, 79, 177, 127, 208, 159, 233, 224, 226, 32, 226, 247, 139, 168, 16, 68, 93, 244, 45,
-        119, 228, 142, 144, 92, 0, 20, 50, 7, 237, 76, 213, 190, 50, 245, 26, 175, 218, 231, 165,
-        57, 175, 148, 14, 137, 179, 147, 191, 114, 211, 221, 216, 240, 59, 63, 107, 221, 115, 104,
-        181, 103, 244, 43, 36, 10, 38, 68, 108, 25, 253, 238, 136, 1, 0, 0,
+        31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 173, 143, 65, 10, 192, 32, 16, 3, 227, 22, 74, 233, 169,
+        79, 177, 63, 232, 103, 122, 232, 165, 7, 17, 223, 47, 162, 66, 16, 21, 65, 231, 178, 75,
+        118, 9, 201, 1, 64, 33, 178, 209, 206, 100, 237, 73, 83, 207, 113, 171, 117, 94, 154, 243,
+        74, 234, 16, 216, 43, 249, 51, 39, 221, 191, 223, 189, 198, 246, 90, 215, 28, 74, 237, 106,
+        253, 10, 137, 130, 1, 60, 249, 192, 200, 46, 144, 1, 0, 0,
     ];
 
     assert_eq!(bytes, expected_serialization)
@@ -305,15 +305,15 @@ fn complex_brillig_foreign_call() {
     let bytes = Circuit::serialize_circuit(&circuit);
 
     let expected_serialization: Vec<u8> = vec![
-        31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 213, 84, 75, 10, 132, 48, 12, 125, 177, 163, 35, 179,
-        154, 35, 8, 51, 7, 232, 204, 9, 188, 139, 184, 83, 116, 233, 241, 173, 152, 98, 12, 213,
-        141, 21, 244, 65, 232, 39, 175, 233, 35, 73, 155, 3, 32, 204, 48, 206, 18, 158, 19, 175,
-        37, 60, 175, 228, 209, 30, 195, 143, 226, 197, 178, 103, 105, 76, 110, 160, 209, 156, 160,
-        209, 247, 195, 69, 235, 29, 179, 46, 81, 243, 103, 2, 239, 231, 225, 44, 117, 150, 241,
-        250, 201, 99, 206, 251, 96, 95, 161, 242, 14, 193, 243, 40, 162, 105, 253, 219, 12, 75, 47,
-        146, 186, 251, 37, 116, 86, 93, 219, 55, 245, 96, 20, 85, 75, 253, 136, 249, 87, 249, 105,
-        231, 220, 4, 249, 237, 132, 56, 20, 224, 109, 113, 223, 88, 82, 153, 34, 64, 34, 14, 164,
-        69, 172, 48, 2, 23, 243, 6, 31, 25, 5, 0, 0,
+        31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 213, 84, 219, 10, 131, 48, 12, 61, 177, 211, 201, 158,
+        246, 9, 194, 246, 1, 221, 190, 96, 255, 50, 124, 83, 244, 209, 207, 183, 98, 138, 33, 68,
+        95, 172, 160, 7, 66, 47, 57, 77, 15, 73, 218, 18, 0, 97, 134, 11, 150, 241, 156, 120, 45,
+        17, 121, 63, 30, 253, 62, 124, 40, 93, 44, 127, 148, 198, 236, 2, 26, 221, 1, 26, 99, 63,
+        156, 180, 222, 41, 235, 146, 52, 127, 206, 120, 63, 183, 96, 121, 176, 130, 215, 119, 30,
+        75, 222, 7, 251, 42, 149, 119, 8, 94, 68, 149, 76, 235, 215, 23, 88, 122, 145, 212, 221,
+        15, 161, 243, 223, 181, 125, 83, 15, 78, 81, 181, 212, 151, 152, 191, 149, 159, 54, 206,
+        77, 144, 223, 142, 197, 33, 131, 183, 198, 125, 98, 73, 101, 14, 131, 68, 28, 72, 139, 48,
+        49, 2, 81, 36, 35, 114, 33, 5, 0, 0,
     ];
 
     assert_eq!(bytes, expected_serialization)
@@ -345,10 +345,10 @@ fn memory_op_circuit() {
     let bytes = Circuit::serialize_circuit(&circuit);
 
     let expected_serialization: Vec<u8> = vec![
-        31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 213, 145, 187, 17, 0, 32, 8, 67, 195, 111, 31, 220, 192,
-        253, 167, 178, 144, 2, 239, 236, 132, 194, 52, 129, 230, 93, 8, 6, 64, 176, 101, 225, 28,
-        78, 49, 43, 238, 154, 225, 254, 166, 209, 205, 165, 98, 174, 212, 177, 188, 187, 92, 255,
-        173, 92, 173, 190, 93, 82, 80, 78, 123, 14, 127, 60, 97, 1, 210, 144, 46, 242, 19, 3, 0, 0,
+        31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 213, 145, 169, 21, 0, 48, 8, 67, 195, 181, 15, 221, 160,
+        251, 79, 85, 81, 4, 2, 87, 16, 141, 9, 152, 255, 66, 48, 0, 130, 43, 11, 231, 112, 138, 89,
+        81, 107, 135, 251, 155, 214, 52, 151, 154, 185, 210, 199, 242, 233, 114, 253, 183, 114,
+        181, 251, 118, 73, 65, 57, 237, 57, 124, 249, 132, 3, 79, 191, 128, 193, 27, 3, 0, 0,
     ];
 
     assert_eq!(bytes, expected_serialization)
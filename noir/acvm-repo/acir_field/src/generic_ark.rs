@@ -255,6 +255,11 @@ impl<F: PrimeField> FieldElement<F> {
         self.0.inverse_in_place().map(|f| FieldElement(*f))
     }
 
+    /// Returns a square root of `self`, or `None` if `self` is not a quadratic residue.
+    pub fn sqrt(&self) -> Option<Self> {
+        self.0.sqrt().map(FieldElement)
+    }
+
     pub fn from_repr(field: F) -> Self {
         Self(field)
     }
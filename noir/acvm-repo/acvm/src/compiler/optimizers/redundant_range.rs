@@ -174,6 +174,7 @@ mod tests {
             return_values: PublicInputs::default(),
             assert_messages: Default::default(),
             recursive: false,
+            brillig_bytecode: Vec::new(),
         }
     }
 
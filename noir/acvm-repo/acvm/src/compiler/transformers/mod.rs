@@ -1,5 +1,9 @@
 use acir::{
-    circuit::{brillig::BrilligOutputs, directives::Directive, Circuit, ExpressionWidth, Opcode},
+    circuit::{
+        brillig::{Brillig, BrilligOutputs},
+        directives::Directive,
+        Circuit, ExpressionWidth, Opcode,
+    },
     native_types::{Expression, Witness},
     FieldElement,
 };
@@ -136,8 +140,9 @@ pub(super) fn transform_internal(
                 new_acir_opcode_positions.push(acir_opcode_positions[index]);
                 transformed_opcodes.push(opcode);
             }
-            Opcode::Brillig(ref brillig) => {
-                for output in &brillig.outputs {
+            Opcode::Brillig(Brillig { ref outputs, .. })
+            | Opcode::BrilligCall { ref outputs, .. } => {
+                for output in outputs {
                     match output {
                         BrilligOutputs::Simple(w) => transformer.mark_solvable(*w),
                         BrilligOutputs::Array(v) => {
@@ -150,6 +155,13 @@ pub(super) fn transform_internal(
                 new_acir_opcode_positions.push(acir_opcode_positions[index]);
                 transformed_opcodes.push(opcode);
             }
+            Opcode::Call { ref outputs, .. } => {
+                for witness in outputs {
+                    transformer.mark_solvable(*witness);
+                }
+                new_acir_opcode_positions.push(acir_opcode_positions[index]);
+                transformed_opcodes.push(opcode);
+            }
         }
     }
 
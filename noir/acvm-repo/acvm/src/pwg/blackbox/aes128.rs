@@ -0,0 +1,62 @@
+use acir::{
+    circuit::opcodes::FunctionInput,
+    native_types::{Witness, WitnessMap},
+    BlackBoxFunc, FieldElement,
+};
+use acvm_blackbox_solver::aes128_encrypt;
+
+use crate::pwg::{insert_value, witness_to_value};
+use crate::OpcodeResolutionError;
+
+/// Attempts to solve an AES-128 encryption opcode.
+/// If successful, `initial_witness` will be mutated to contain the new witness assignment.
+pub(super) fn solve_aes128_encryption_opcode(
+    initial_witness: &mut WitnessMap,
+    inputs: &[FunctionInput],
+    iv: &[FunctionInput],
+    key: &[FunctionInput],
+    outputs: &[Witness],
+) -> Result<(), OpcodeResolutionError> {
+    let plaintext = read_bytes(initial_witness, inputs)?;
+    let iv = read_block(initial_witness, iv, "iv")?;
+    let key = read_block(initial_witness, key, "key")?;
+
+    let ciphertext = aes128_encrypt(&plaintext, &iv, &key)?;
+    if ciphertext.len() != outputs.len() {
+        return Err(OpcodeResolutionError::BlackBoxFunctionFailed(
+            BlackBoxFunc::AES128Encrypt,
+            format!("Expected {} outputs but encountered {}", ciphertext.len(), outputs.len()),
+        ));
+    }
+
+    for (output_witness, value) in outputs.iter().zip(ciphertext) {
+        insert_value(output_witness, FieldElement::from(value as u128), initial_witness)?;
+    }
+
+    Ok(())
+}
+
+/// Reads one byte from each of the given inputs.
+fn read_bytes(
+    initial_witness: &WitnessMap,
+    inputs: &[FunctionInput],
+) -> Result<Vec<u8>, OpcodeResolutionError> {
+    inputs
+        .iter()
+        .map(|input| Ok(witness_to_value(initial_witness, input.witness)?.to_u128() as u8))
+        .collect()
+}
+
+/// Reads the 16 bytes of the initialization vector or the key.
+fn read_block(
+    initial_witness: &WitnessMap,
+    inputs: &[FunctionInput],
+    name: &str,
+) -> Result<[u8; 16], OpcodeResolutionError> {
+    read_bytes(initial_witness, inputs)?.try_into().map_err(|bytes: Vec<u8>| {
+        OpcodeResolutionError::BlackBoxFunctionFailed(
+            BlackBoxFunc::AES128Encrypt,
+            format!("Expected a {name} of 16 bytes but encountered {}", bytes.len()),
+        )
+    })
+}
@@ -0,0 +1,76 @@
+use acir::{
+    circuit::opcodes::FunctionInput,
+    native_types::{Witness, WitnessMap},
+    BlackBoxFunc, FieldElement,
+};
+use acvm_blackbox_solver::{bls12_381_g1_add, bls12_381_g1_mul};
+
+use crate::pwg::{insert_value, witness_to_value};
+use crate::OpcodeResolutionError;
+
+/// Attempts to solve a BLS12-381 G1 addition opcode.
+/// If successful, `initial_witness` will be mutated to contain the new witness assignment.
+pub(super) fn solve_bls12_381_g1_add(
+    initial_witness: &mut WitnessMap,
+    input1: &[FunctionInput],
+    input2: &[FunctionInput],
+    outputs: &[Witness],
+) -> Result<(), OpcodeResolutionError> {
+    let func = BlackBoxFunc::Bls12381G1Add;
+    let lhs = read_bytes(initial_witness, input1, func, "first point")?;
+    let rhs = read_bytes(initial_witness, input2, func, "second point")?;
+    let sum = bls12_381_g1_add(&lhs, &rhs)?;
+    write_point(initial_witness, outputs, func, sum)
+}
+
+/// Attempts to solve a BLS12-381 G1 scalar multiplication opcode.
+/// If successful, `initial_witness` will be mutated to contain the new witness assignment.
+pub(super) fn solve_bls12_381_g1_mul(
+    initial_witness: &mut WitnessMap,
+    point: &[FunctionInput],
+    scalar: &[FunctionInput],
+    outputs: &[Witness],
+) -> Result<(), OpcodeResolutionError> {
+    let func = BlackBoxFunc::Bls12381G1Mul;
+    let point = read_bytes(initial_witness, point, func, "point")?;
+    let scalar = read_bytes(initial_witness, scalar, func, "scalar")?;
+    let product = bls12_381_g1_mul(&point, &scalar)?;
+    write_point(initial_witness, outputs, func, product)
+}
+
+/// Reads one byte from each of the given inputs, which must be `N` of them.
+fn read_bytes<const N: usize>(
+    initial_witness: &WitnessMap,
+    inputs: &[FunctionInput],
+    func: BlackBoxFunc,
+    name: &str,
+) -> Result<[u8; N], OpcodeResolutionError> {
+    let bytes = inputs
+        .iter()
+        .map(|input| Ok(witness_to_value(initial_witness, input.witness)?.to_u128() as u8))
+        .collect::<Result<Vec<u8>, OpcodeResolutionError>>()?;
+    bytes.try_into().map_err(|bytes: Vec<u8>| {
+        OpcodeResolutionError::BlackBoxFunctionFailed(
+            func,
+            format!("Expected a {name} of {N} bytes but encountered {}", bytes.len()),
+        )
+    })
+}
+
+fn write_point(
+    initial_witness: &mut WitnessMap,
+    outputs: &[Witness],
+    func: BlackBoxFunc,
+    point: [u8; 96],
+) -> Result<(), OpcodeResolutionError> {
+    if outputs.len() != point.len() {
+        return Err(OpcodeResolutionError::BlackBoxFunctionFailed(
+            func,
+            format!("Expected {} outputs but encountered {}", point.len(), outputs.len()),
+        ));
+    }
+    for (output_witness, byte) in outputs.iter().zip(point) {
+        insert_value(output_witness, FieldElement::from(byte as u128), initial_witness)?;
+    }
+    Ok(())
+}
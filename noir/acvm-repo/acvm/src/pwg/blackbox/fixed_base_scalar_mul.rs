@@ -24,6 +24,28 @@ pub(super) fn fixed_base_scalar_mul(
     Ok(())
 }
 
+pub(super) fn multi_scalar_mul(
+    backend: &impl BlackBoxFunctionSolver,
+    initial_witness: &mut WitnessMap,
+    points: &[FunctionInput],
+    scalars: &[FunctionInput],
+    outputs: (Witness, Witness),
+) -> Result<(), OpcodeResolutionError> {
+    let points: Result<Vec<_>, _> =
+        points.iter().map(|input| witness_to_value(initial_witness, input.witness)).collect();
+    let points: Vec<_> = points?.into_iter().cloned().collect();
+    let scalars: Result<Vec<_>, _> =
+        scalars.iter().map(|input| witness_to_value(initial_witness, input.witness)).collect();
+    let scalars: Vec<_> = scalars?.into_iter().cloned().collect();
+
+    let (res_x, res_y) = backend.multi_scalar_mul(&points, &scalars)?;
+
+    insert_value(&outputs.0, res_x, initial_witness)?;
+    insert_value(&outputs.1, res_y, initial_witness)?;
+
+    Ok(())
+}
+
 pub(super) fn embedded_curve_add(
     backend: &impl BlackBoxFunctionSolver,
     initial_witness: &mut WitnessMap,
@@ -3,7 +3,7 @@ use acir::{
     native_types::{Witness, WitnessMap},
     BlackBoxFunc, FieldElement,
 };
-use acvm_blackbox_solver::BlackBoxResolutionError;
+use acvm_blackbox_solver::{sha512, BlackBoxResolutionError};
 
 use crate::pwg::{insert_value, witness_to_value};
 use crate::OpcodeResolutionError;
@@ -32,6 +32,27 @@ pub(super) fn solve_generic_256_hash_opcode(
     Ok(())
 }
 
+/// Attempts to solve a SHA512 hash function opcode.
+/// If successful, `initial_witness` will be mutated to contain the new witness assignment.
+pub(super) fn solve_sha512_opcode(
+    initial_witness: &mut WitnessMap,
+    inputs: &[FunctionInput],
+    outputs: &[Witness],
+) -> Result<(), OpcodeResolutionError> {
+    let message_input = get_hash_input(initial_witness, inputs, None)?;
+    let digest: [u8; 64] = sha512(&message_input)?;
+
+    let outputs: [Witness; 64] = outputs.try_into().map_err(|_| {
+        OpcodeResolutionError::BlackBoxFunctionFailed(
+            BlackBoxFunc::Sha512,
+            format!("Expected 64 outputs but encountered {}", outputs.len()),
+        )
+    })?;
+    write_digest_to_outputs(initial_witness, outputs, digest)?;
+
+    Ok(())
+}
+
 /// Reads the hash function input from a [`WitnessMap`].
 fn get_hash_input(
     initial_witness: &WitnessMap,
@@ -71,10 +92,10 @@ fn get_hash_input(
 }
 
 /// Writes a `digest` to the [`WitnessMap`] at witness indices `outputs`.
-fn write_digest_to_outputs(
+fn write_digest_to_outputs<const N: usize>(
     initial_witness: &mut WitnessMap,
-    outputs: [Witness; 32],
-    digest: [u8; 32],
+    outputs: [Witness; N],
+    digest: [u8; N],
 ) -> Result<(), OpcodeResolutionError> {
     for (output_witness, value) in outputs.iter().zip(digest.into_iter()) {
         insert_value(
@@ -10,22 +10,29 @@ use self::{bigint::BigIntSolver, pedersen::pedersen_hash};
 use super::{insert_value, OpcodeNotSolvable, OpcodeResolutionError};
 use crate::{pwg::witness_to_value, BlackBoxFunctionSolver};
 
+mod aes128;
 pub(crate) mod bigint;
+mod bls12_381;
 mod fixed_base_scalar_mul;
 mod hash;
 mod logic;
 mod pedersen;
+mod poseidon;
 mod range;
 mod signature;
 
-use fixed_base_scalar_mul::{embedded_curve_add, fixed_base_scalar_mul};
+use aes128::solve_aes128_encryption_opcode;
+use bls12_381::{solve_bls12_381_g1_add, solve_bls12_381_g1_mul};
+use fixed_base_scalar_mul::{embedded_curve_add, fixed_base_scalar_mul, multi_scalar_mul};
 // Hash functions should eventually be exposed for external consumers.
-use hash::solve_generic_256_hash_opcode;
+use hash::{solve_generic_256_hash_opcode, solve_sha512_opcode};
 use logic::{and, xor};
 use pedersen::pedersen;
+use poseidon::poseidon_permutation;
 use range::solve_range_opcode;
 use signature::{
     ecdsa::{secp256k1_prehashed, secp256r1_prehashed},
+    ed25519::verify_ed25519_signature,
     schnorr::schnorr_verify,
 };
 
@@ -179,6 +186,15 @@ pub(crate) fn solve(
         BlackBoxFuncCall::FixedBaseScalarMul { low, high, outputs } => {
             fixed_base_scalar_mul(backend, initial_witness, *low, *high, *outputs)
         }
+        BlackBoxFuncCall::MultiScalarMul { points, scalars, outputs } => {
+            multi_scalar_mul(backend, initial_witness, points, scalars, *outputs)
+        }
+        BlackBoxFuncCall::AES128Encrypt { inputs, iv, key, outputs } => {
+            solve_aes128_encryption_opcode(initial_witness, inputs, iv, key, outputs)
+        }
+        BlackBoxFuncCall::Sha512 { inputs, outputs } => {
+            solve_sha512_opcode(initial_witness, inputs, outputs)
+        }
         BlackBoxFuncCall::EmbeddedCurveAdd { input1_x, input1_y, input2_x, input2_y, outputs } => {
             embedded_curve_add(
                 backend,
@@ -205,6 +221,18 @@ pub(crate) fn solve(
             bigint_solver.bigint_to_bytes(*input, outputs, initial_witness)
         }
         BlackBoxFuncCall::Poseidon2Permutation { .. } => todo!(),
+        BlackBoxFuncCall::PoseidonPermutation { inputs, outputs, len, rate } => {
+            poseidon_permutation(backend, initial_witness, inputs, outputs, *len, *rate)
+        }
+        BlackBoxFuncCall::Ed25519 { public_key, signature, message, output } => {
+            verify_ed25519_signature(initial_witness, public_key, signature, message, *output)
+        }
+        BlackBoxFuncCall::Bls12381G1Add { input1, input2, outputs } => {
+            solve_bls12_381_g1_add(initial_witness, input1, input2, outputs)
+        }
+        BlackBoxFuncCall::Bls12381G1Mul { point, scalar, outputs } => {
+            solve_bls12_381_g1_mul(initial_witness, point, scalar, outputs)
+        }
         BlackBoxFuncCall::Sha256Compression { .. } => todo!(),
     }
 }
@@ -0,0 +1,42 @@
+use acir::{
+    circuit::opcodes::FunctionInput,
+    native_types::{Witness, WitnessMap},
+    BlackBoxFunc,
+};
+
+use crate::{
+    pwg::{insert_value, witness_to_value, OpcodeResolutionError},
+    BlackBoxFunctionSolver,
+};
+
+pub(super) fn poseidon_permutation(
+    backend: &impl BlackBoxFunctionSolver,
+    initial_witness: &mut WitnessMap,
+    inputs: &[FunctionInput],
+    outputs: &[Witness],
+    len: u32,
+    rate: u32,
+) -> Result<(), OpcodeResolutionError> {
+    if inputs.len() != len as usize || outputs.len() != len as usize {
+        return Err(OpcodeResolutionError::BlackBoxFunctionFailed(
+            BlackBoxFunc::PoseidonPermutation,
+            format!(
+                "the state length is {len}, but {} inputs and {} outputs were provided",
+                inputs.len(),
+                outputs.len()
+            ),
+        ));
+    }
+
+    let state: Result<Vec<_>, _> =
+        inputs.iter().map(|input| witness_to_value(initial_witness, input.witness)).collect();
+    let state: Vec<_> = state?.into_iter().cloned().collect();
+
+    let permuted_state = backend.poseidon_permutation(&state, rate)?;
+
+    for (output, value) in outputs.iter().zip(permuted_state) {
+        insert_value(output, value, initial_witness)?;
+    }
+
+    Ok(())
+}
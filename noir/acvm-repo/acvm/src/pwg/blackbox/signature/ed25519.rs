@@ -0,0 +1,42 @@
+use acir::{
+    circuit::opcodes::FunctionInput,
+    native_types::{Witness, WitnessMap},
+    BlackBoxFunc, FieldElement,
+};
+use acvm_blackbox_solver::ed25519_verify;
+
+use crate::{pwg::insert_value, OpcodeResolutionError};
+
+use super::to_u8_vec;
+
+pub(crate) fn verify_ed25519_signature(
+    initial_witness: &mut WitnessMap,
+    public_key_inputs: &[FunctionInput],
+    signature_inputs: &[FunctionInput],
+    message_inputs: &[FunctionInput],
+    output: Witness,
+) -> Result<(), OpcodeResolutionError> {
+    let message = to_u8_vec(initial_witness, message_inputs)?;
+
+    // These errors should never be emitted in practice as they would imply malformed ACIR generation.
+    let public_key: [u8; 32] =
+        to_u8_vec(initial_witness, public_key_inputs)?.try_into().map_err(|_| {
+            OpcodeResolutionError::BlackBoxFunctionFailed(
+                BlackBoxFunc::Ed25519,
+                format!("expected public key size 32 but received {}", public_key_inputs.len()),
+            )
+        })?;
+
+    let signature: [u8; 64] =
+        to_u8_vec(initial_witness, signature_inputs)?.try_into().map_err(|_| {
+            OpcodeResolutionError::BlackBoxFunctionFailed(
+                BlackBoxFunc::Ed25519,
+                format!("expected signature size 64 but received {}", signature_inputs.len()),
+            )
+        })?;
+
+    let is_valid = ed25519_verify(&message, &public_key, &signature)?;
+
+    insert_value(&output, FieldElement::from(is_valid), initial_witness)?;
+    Ok(())
+}
@@ -18,4 +18,5 @@ fn to_u8_vec(
 }
 
 pub(super) mod ecdsa;
+pub(super) mod ed25519;
 pub(super) mod schnorr;
@@ -1,19 +1,22 @@
 use std::collections::HashMap;
 
 use acir::{
-    brillig::{ForeignCallParam, ForeignCallResult, Value},
+    brillig::{ForeignCallParam, ForeignCallResult, Opcode as BrilligOpcode, Value},
     circuit::{
-        brillig::{Brillig, BrilligInputs, BrilligOutputs},
+        brillig::{BrilligBytecode, BrilligInputs, BrilligOutputs},
         opcodes::BlockId,
-        OpcodeLocation,
+        Opcode, OpcodeLocation,
     },
-    native_types::WitnessMap,
+    native_types::{Expression, WitnessMap},
     FieldElement,
 };
 use acvm_blackbox_solver::BlackBoxFunctionSolver;
 use brillig_vm::{VMStatus, VM};
 
-use crate::{pwg::OpcodeNotSolvable, OpcodeResolutionError};
+use crate::{
+    pwg::{ErrorLocation, OpcodeNotSolvable},
+    OpcodeResolutionError,
+};
 
 use super::{get_value, insert_value, memory_op::MemoryOpSolver};
 
@@ -24,31 +27,73 @@ pub enum BrilligSolverStatus {
     ForeignCallWait(ForeignCallWaitInfo),
 }
 
+/// The parts of an [`Opcode::Brillig`] or [`Opcode::BrilligCall`] needed to execute it.
+pub(super) struct BrilligCall<'b> {
+    inputs: &'b [BrilligInputs],
+    outputs: &'b [BrilligOutputs],
+    predicate: &'b Option<Expression>,
+    bytecode: &'b [BrilligOpcode],
+}
+
+impl<'b> BrilligCall<'b> {
+    /// Returns the call made by `opcode` if it executes Brillig bytecode, looking up the bytecode
+    /// of an [`Opcode::BrilligCall`] in `brillig_bytecode`.
+    ///
+    /// Fails if `brillig_bytecode` has no bytecode for the id of an [`Opcode::BrilligCall`].
+    pub(super) fn from_opcode(
+        opcode: &'b Opcode,
+        brillig_bytecode: &'b [BrilligBytecode],
+    ) -> Result<Option<Self>, OpcodeResolutionError> {
+        match opcode {
+            Opcode::Brillig(brillig) => Ok(Some(BrilligCall {
+                inputs: &brillig.inputs,
+                outputs: &brillig.outputs,
+                predicate: &brillig.predicate,
+                bytecode: &brillig.bytecode,
+            })),
+            Opcode::BrilligCall { id, inputs, outputs, predicate } => {
+                let Some(brillig) = brillig_bytecode.get(*id as usize) else {
+                    return Err(OpcodeResolutionError::UnknownBrilligBytecode {
+                        opcode_location: ErrorLocation::Unresolved,
+                        id: *id,
+                    });
+                };
+                Ok(Some(BrilligCall { inputs, outputs, predicate, bytecode: &brillig.bytecode }))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
 pub struct BrilligSolver<'b, B: BlackBoxFunctionSolver> {
     vm: VM<'b, B>,
     acir_index: usize,
+    /// The maximum number of opcodes which may be executed before the VM is considered stuck.
+    step_limit: Option<usize>,
+    /// The number of opcodes executed so far.
+    steps: usize,
 }
 
 impl<'b, B: BlackBoxFunctionSolver> BrilligSolver<'b, B> {
     /// Evaluates if the Brillig block should be skipped entirely
     pub(super) fn should_skip(
         witness: &WitnessMap,
-        brillig: &Brillig,
+        brillig: &BrilligCall,
     ) -> Result<bool, OpcodeResolutionError> {
         // If the predicate is `None`, the block should never be skipped
         // If the predicate is `Some` but we cannot find a value, then we return stalled
-        match &brillig.predicate {
+        match brillig.predicate {
             Some(pred) => Ok(get_value(pred, witness)?.is_zero()),
             None => Ok(false),
         }
     }
 
-    /// Assigns the zero value to all outputs of the given [`Brillig`] bytecode.
+    /// Assigns the zero value to all outputs of the given Brillig call.
     pub(super) fn zero_out_brillig_outputs(
         initial_witness: &mut WitnessMap,
-        brillig: &Brillig,
+        brillig: &BrilligCall,
     ) -> Result<(), OpcodeResolutionError> {
-        for output in &brillig.outputs {
+        for output in brillig.outputs {
             match output {
                 BrilligOutputs::Simple(witness) => {
                     insert_value(witness, FieldElement::zero(), initial_witness)?;
@@ -68,7 +113,7 @@ impl<'b, B: BlackBoxFunctionSolver> BrilligSolver<'b, B> {
     pub(super) fn new(
         initial_witness: &WitnessMap,
         memory: &HashMap<BlockId, MemoryOpSolver>,
-        brillig: &'b Brillig,
+        brillig: &BrilligCall<'b>,
         bb_solver: &'b B,
         acir_index: usize,
     ) -> Result<Self, OpcodeResolutionError> {
@@ -78,7 +123,7 @@ impl<'b, B: BlackBoxFunctionSolver> BrilligSolver<'b, B> {
         // Iterate over each input and evaluate the expression(s) associated with it.
         // Push the results into memory.
         // If a certain expression is not solvable, we stall the ACVM and do not proceed with Brillig VM execution.
-        for input in &brillig.inputs {
+        for input in brillig.inputs {
             match input {
                 BrilligInputs::Single(expr) => match get_value(expr, initial_witness) {
                     Ok(value) => calldata.push(value.into()),
@@ -118,8 +163,14 @@ impl<'b, B: BlackBoxFunctionSolver> BrilligSolver<'b, B> {
 
         // Instantiate a Brillig VM given the solved calldata
         // along with the Brillig bytecode.
-        let vm = VM::new(calldata, &brillig.bytecode, vec![], bb_solver);
-        Ok(Self { vm, acir_index })
+        let vm = VM::new(calldata, brillig.bytecode, vec![], bb_solver);
+        Ok(Self { vm, acir_index, step_limit: None, steps: 0 })
+    }
+
+    /// Fails execution once more than `step_limit` opcodes have been executed.
+    pub(super) fn with_step_limit(mut self, step_limit: Option<usize>) -> Self {
+        self.step_limit = step_limit;
+        self
     }
 
     pub fn get_memory(&self) -> &[Value] {
@@ -131,11 +182,28 @@ impl<'b, B: BlackBoxFunctionSolver> BrilligSolver<'b, B> {
     }
 
     pub(super) fn solve(&mut self) -> Result<BrilligSolverStatus, OpcodeResolutionError> {
-        let status = self.vm.process_opcodes();
-        self.handle_vm_status(status)
+        if self.step_limit.is_none() {
+            let status = self.vm.process_opcodes();
+            return self.handle_vm_status(status);
+        }
+        loop {
+            match self.step()? {
+                BrilligSolverStatus::InProgress => (),
+                status => return Ok(status),
+            }
+        }
     }
 
     pub fn step(&mut self) -> Result<BrilligSolverStatus, OpcodeResolutionError> {
+        if let Some(step_limit) = self.step_limit {
+            if self.steps >= step_limit {
+                return Err(OpcodeResolutionError::BrilligStepLimitExceeded {
+                    step_limit,
+                    call_stack: self.opcode_locations(self.vm.call_stack()),
+                });
+            }
+        }
+        self.steps += 1;
         let status = self.vm.process_opcode();
         self.handle_vm_status(status)
     }
@@ -158,13 +226,7 @@ impl<'b, B: BlackBoxFunctionSolver> BrilligSolver<'b, B> {
             VMStatus::Failure { message, call_stack } => {
                 Err(OpcodeResolutionError::BrilligFunctionFailed {
                     message,
-                    call_stack: call_stack
-                        .iter()
-                        .map(|brillig_index| OpcodeLocation::Brillig {
-                            acir_index: self.acir_index,
-                            brillig_index: *brillig_index,
-                        })
-                        .collect(),
+                    call_stack: self.opcode_locations(call_stack),
                 })
             }
             VMStatus::ForeignCallWait { function, inputs } => {
@@ -173,10 +235,20 @@ impl<'b, B: BlackBoxFunctionSolver> BrilligSolver<'b, B> {
         }
     }
 
+    fn opcode_locations(&self, call_stack: Vec<usize>) -> Vec<OpcodeLocation> {
+        call_stack
+            .into_iter()
+            .map(|brillig_index| OpcodeLocation::Brillig {
+                acir_index: self.acir_index,
+                brillig_index,
+            })
+            .collect()
+    }
+
     pub(super) fn finalize(
         self,
         witness: &mut WitnessMap,
-        brillig: &Brillig,
+        brillig: &BrilligCall,
     ) -> Result<(), OpcodeResolutionError> {
         // Finish the Brillig execution by writing the outputs to the witness map
         let vm_status = self.vm.get_status();
@@ -194,7 +266,7 @@ impl<'b, B: BlackBoxFunctionSolver> BrilligSolver<'b, B> {
         witness_map: &mut WitnessMap,
         return_data_offset: usize,
         return_data_size: usize,
-        brillig: &Brillig,
+        brillig: &BrilligCall,
     ) -> Result<(), OpcodeResolutionError> {
         // Write VM execution results into the witness map
         let memory = self.vm.get_memory();
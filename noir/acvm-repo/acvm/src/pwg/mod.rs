@@ -1,18 +1,18 @@
 // Re-usable methods that backends can use to implement their PWG
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use acir::{
     brillig::ForeignCallResult,
-    circuit::{opcodes::BlockId, Opcode, OpcodeLocation},
+    circuit::{brillig::BrilligBytecode, opcodes::BlockId, Opcode, OpcodeLocation},
     native_types::{Expression, Witness, WitnessMap},
     BlackBoxFunc, FieldElement,
 };
 use acvm_blackbox_solver::BlackBoxResolutionError;
 
 use self::{
-    arithmetic::ExpressionSolver, blackbox::bigint::BigIntSolver, directives::solve_directives,
-    memory_op::MemoryOpSolver,
+    arithmetic::ExpressionSolver, blackbox::bigint::BigIntSolver, brillig::BrilligCall,
+    directives::solve_directives, memory_op::MemoryOpSolver,
 };
 use crate::BlackBoxFunctionSolver;
 
@@ -49,6 +49,13 @@ pub enum ACVMStatus {
     ///
     /// Once this is done, the ACVM can be restarted to solve the remaining opcodes.
     RequiresForeignCall(ForeignCallWaitInfo),
+
+    /// The ACVM has encountered a [call][acir::circuit::Opcode::Call] to another circuit of the
+    /// program, which must be executed outside of the ACVM. The values it returns must be passed
+    /// back to the ACVM using [`ACVM::resolve_pending_acir_call`].
+    ///
+    /// Once this is done, the ACVM can be restarted to solve the remaining opcodes.
+    RequiresAcirCall(AcirCallWaitInfo),
 }
 
 impl std::fmt::Display for ACVMStatus {
@@ -58,10 +65,20 @@ impl std::fmt::Display for ACVMStatus {
             ACVMStatus::InProgress => write!(f, "In progress"),
             ACVMStatus::Failure(_) => write!(f, "Execution failure"),
             ACVMStatus::RequiresForeignCall(_) => write!(f, "Waiting on foreign call"),
+            ACVMStatus::RequiresAcirCall(_) => write!(f, "Waiting on acir call"),
         }
     }
 }
 
+/// A call to another circuit of the program which the ACVM is waiting on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AcirCallWaitInfo {
+    /// Index of the circuit to call within the program.
+    pub id: u32,
+    /// The values of the inputs of the call, assigned to the parameters of the called circuit.
+    pub initial_witness: WitnessMap,
+}
+
 pub enum StepResult<'a, B: BlackBoxFunctionSolver> {
     Status(ACVMStatus),
     IntoBrillig(BrilligSolver<'a, B>),
@@ -117,6 +134,12 @@ pub enum OpcodeResolutionError {
     BlackBoxFunctionFailed(BlackBoxFunc, String),
     #[error("Failed to solve brillig function, reason: {message}")]
     BrilligFunctionFailed { message: String, call_stack: Vec<OpcodeLocation> },
+    #[error("Brillig function did not finish after executing {step_limit} opcodes")]
+    BrilligStepLimitExceeded { step_limit: usize, call_stack: Vec<OpcodeLocation> },
+    #[error("Called circuit returned {results_size} values instead of {outputs_size}")]
+    AcirCallOutputsMismatch { opcode_location: ErrorLocation, results_size: u32, outputs_size: u32 },
+    #[error("Brillig call refers to bytecode {id}, which the circuit does not have")]
+    UnknownBrilligBytecode { opcode_location: ErrorLocation, id: u32 },
 }
 
 impl From<BlackBoxResolutionError> for OpcodeResolutionError {
@@ -141,16 +164,34 @@ pub struct ACVM<'a, B: BlackBoxFunctionSolver> {
 
     /// A list of opcodes which are to be executed by the ACVM.
     opcodes: &'a [Opcode],
+    /// The Brillig bytecode which the [`Opcode::BrilligCall`]s in `opcodes` refer to.
+    brillig_bytecode: &'a [BrilligBytecode],
     /// Index of the next opcode to be executed.
     instruction_pointer: usize,
 
     witness_map: WitnessMap,
 
     brillig_solver: Option<BrilligSolver<'a, B>>,
+
+    /// The maximum number of Brillig opcodes which may be executed by each Brillig opcode,
+    /// keyed by the index of the Brillig opcode.
+    brillig_step_limits: BTreeMap<usize, usize>,
+
+    /// The values returned by the pending call to another circuit, once it has been resolved.
+    acir_call_results: Option<Vec<FieldElement>>,
 }
 
 impl<'a, B: BlackBoxFunctionSolver> ACVM<'a, B> {
-    pub fn new(backend: &'a B, opcodes: &'a [Opcode], initial_witness: WitnessMap) -> Self {
+    /// Creates an ACVM solving `opcodes`, whose [`Opcode::BrilligCall`]s execute the bytecode in
+    /// `brillig_bytecode`, usually the
+    /// [`brillig_bytecode`][acir::circuit::Circuit::brillig_bytecode] of the circuit which the
+    /// opcodes belong to.
+    pub fn new(
+        backend: &'a B,
+        opcodes: &'a [Opcode],
+        initial_witness: WitnessMap,
+        brillig_bytecode: &'a [BrilligBytecode],
+    ) -> Self {
         let status = if opcodes.is_empty() { ACVMStatus::Solved } else { ACVMStatus::InProgress };
         ACVM {
             status,
@@ -158,12 +199,22 @@ impl<'a, B: BlackBoxFunctionSolver> ACVM<'a, B> {
             block_solvers: HashMap::default(),
             bigint_solver: BigIntSolver::default(),
             opcodes,
+            brillig_bytecode,
             instruction_pointer: 0,
             witness_map: initial_witness,
             brillig_solver: None,
+            brillig_step_limits: BTreeMap::new(),
+            acir_call_results: None,
         }
     }
 
+    /// Fails the execution of each Brillig opcode listed in `brillig_step_limits` once it has
+    /// executed more than the given number of Brillig opcodes, rather than letting it run forever.
+    pub fn with_brillig_step_limits(mut self, brillig_step_limits: BTreeMap<usize, usize>) -> Self {
+        self.brillig_step_limits = brillig_step_limits;
+        self
+    }
+
     /// Returns a reference to the current state of the ACVM's [`WitnessMap`].
     ///
     /// Once execution has completed, the witness map can be extracted using [`ACVM::finalize`]
@@ -244,12 +295,33 @@ impl<'a, B: BlackBoxFunctionSolver> ACVM<'a, B> {
         self.status(ACVMStatus::InProgress);
     }
 
+    /// Sets the status of the VM to `RequiresAcirCall`.
+    /// Indicating that the VM is now waiting for a call to another circuit to be resolved.
+    fn wait_for_acir_call(&mut self, acir_call: AcirCallWaitInfo) -> ACVMStatus {
+        self.status(ACVMStatus::RequiresAcirCall(acir_call))
+    }
+
+    /// Resolves the pending call to another circuit with the values it returned, in increasing
+    /// order of the witness indices of its return values.
+    ///
+    /// The ACVM can then be restarted to solve the remaining ACIR opcodes.
+    pub fn resolve_pending_acir_call(&mut self, call_results: Vec<FieldElement>) {
+        if !matches!(self.status, ACVMStatus::RequiresAcirCall(_)) {
+            panic!("ACVM is not expecting an acir call response as no call was made");
+        }
+        self.acir_call_results = Some(call_results);
+
+        // Now that the call has been resolved then we can resume execution.
+        self.status(ACVMStatus::InProgress);
+    }
+
     /// Executes the ACVM's circuit until execution halts.
     ///
     /// Execution can halt due to three reasons:
     /// 1. All opcodes have been executed successfully.
     /// 2. The circuit has been found to be unsatisfiable.
     /// 2. A Brillig [foreign call][`ForeignCallWaitInfo`] has been encountered and must be resolved.
+    /// 3. A [call][`AcirCallWaitInfo`] to another circuit has been encountered and must be resolved.
     pub fn solve(&mut self) -> ACVMStatus {
         while self.status == ACVMStatus::InProgress {
             self.solve_opcode();
@@ -277,10 +349,14 @@ impl<'a, B: BlackBoxFunctionSolver> ACVM<'a, B> {
                 let solver = self.block_solvers.entry(*block_id).or_default();
                 solver.solve_memory_op(op, &mut self.witness_map, predicate)
             }
-            Opcode::Brillig(_) => match self.solve_brillig_opcode() {
+            Opcode::Brillig(_) | Opcode::BrilligCall { .. } => match self.solve_brillig_opcode() {
                 Ok(Some(foreign_call)) => return self.wait_for_foreign_call(foreign_call),
                 res => res.map(|_| ()),
             },
+            Opcode::Call { .. } => match self.solve_call_opcode() {
+                Ok(Some(acir_call)) => return self.wait_for_acir_call(acir_call),
+                res => res.map(|_| ()),
+            },
         };
         self.handle_opcode_resolution(resolution)
     }
@@ -309,6 +385,14 @@ impl<'a, B: BlackBoxFunctionSolver> ACVM<'a, B> {
                     }
                     | OpcodeResolutionError::UnsatisfiedConstrain {
                         opcode_location: opcode_index,
+                    }
+                    | OpcodeResolutionError::AcirCallOutputsMismatch {
+                        opcode_location: opcode_index,
+                        ..
+                    }
+                    | OpcodeResolutionError::UnknownBrilligBytecode {
+                        opcode_location: opcode_index,
+                        ..
                     } => {
                         *opcode_index = ErrorLocation::Resolved(OpcodeLocation::Acir(
                             self.instruction_pointer(),
@@ -325,13 +409,13 @@ impl<'a, B: BlackBoxFunctionSolver> ACVM<'a, B> {
     fn solve_brillig_opcode(
         &mut self,
     ) -> Result<Option<ForeignCallWaitInfo>, OpcodeResolutionError> {
-        let Opcode::Brillig(brillig) = &self.opcodes[self.instruction_pointer] else {
+        let Some(brillig) = self.current_brillig_call()? else {
             unreachable!("Not executing a Brillig opcode");
         };
 
         let witness = &mut self.witness_map;
-        if BrilligSolver::<B>::should_skip(witness, brillig)? {
-            return BrilligSolver::<B>::zero_out_brillig_outputs(witness, brillig).map(|_| None);
+        if BrilligSolver::<B>::should_skip(witness, &brillig)? {
+            return BrilligSolver::<B>::zero_out_brillig_outputs(witness, &brillig).map(|_| None);
         }
 
         // If we're resuming execution after resolving a foreign call then
@@ -341,10 +425,11 @@ impl<'a, B: BlackBoxFunctionSolver> ACVM<'a, B> {
             None => BrilligSolver::new(
                 witness,
                 &self.block_solvers,
-                brillig,
+                &brillig,
                 self.backend,
                 self.instruction_pointer,
-            )?,
+            )?
+            .with_step_limit(self.brillig_step_limits.get(&self.instruction_pointer).copied()),
         };
         match solver.solve()? {
             BrilligSolverStatus::ForeignCallWait(foreign_call) => {
@@ -357,35 +442,78 @@ impl<'a, B: BlackBoxFunctionSolver> ACVM<'a, B> {
             }
             BrilligSolverStatus::Finished => {
                 // Write execution outputs
-                solver.finalize(witness, brillig)?;
+                solver.finalize(witness, &brillig)?;
                 Ok(None)
             }
         }
     }
 
+    fn solve_call_opcode(&mut self) -> Result<Option<AcirCallWaitInfo>, OpcodeResolutionError> {
+        let Opcode::Call { id, inputs, outputs, predicate } =
+            &self.opcodes[self.instruction_pointer]
+        else {
+            unreachable!("Not executing a Call opcode");
+        };
+
+        let witness = &mut self.witness_map;
+        if let Some(predicate) = predicate {
+            if get_value(predicate, witness)?.is_zero() {
+                for output in outputs {
+                    insert_value(output, FieldElement::zero(), witness)?;
+                }
+                return Ok(None);
+            }
+        }
+
+        // If we're resuming execution after resolving the call then its results are waiting.
+        if let Some(call_results) = self.acir_call_results.take() {
+            if call_results.len() != outputs.len() {
+                return Err(OpcodeResolutionError::AcirCallOutputsMismatch {
+                    opcode_location: ErrorLocation::Unresolved,
+                    results_size: call_results.len() as u32,
+                    outputs_size: outputs.len() as u32,
+                });
+            }
+            for (output, value) in outputs.iter().zip(call_results) {
+                insert_value(output, value, witness)?;
+            }
+            return Ok(None);
+        }
+
+        let mut initial_witness = WitnessMap::default();
+        for (index, input) in inputs.iter().enumerate() {
+            initial_witness.insert(Witness(index as u32), *witness_to_value(witness, *input)?);
+        }
+        Ok(Some(AcirCallWaitInfo { id: *id, initial_witness }))
+    }
+
     pub fn step_into_brillig_opcode(&mut self) -> StepResult<'a, B> {
-        let Opcode::Brillig(brillig) = &self.opcodes[self.instruction_pointer] else {
-            return StepResult::Status(self.solve_opcode());
+        let brillig = match self.current_brillig_call() {
+            Ok(Some(brillig)) => brillig,
+            Ok(None) => return StepResult::Status(self.solve_opcode()),
+            Err(err) => return StepResult::Status(self.handle_opcode_resolution(Err(err))),
         };
 
         let witness = &mut self.witness_map;
-        let should_skip = match BrilligSolver::<B>::should_skip(witness, brillig) {
+        let should_skip = match BrilligSolver::<B>::should_skip(witness, &brillig) {
             Ok(result) => result,
             Err(err) => return StepResult::Status(self.handle_opcode_resolution(Err(err))),
         };
 
         if should_skip {
-            let resolution = BrilligSolver::<B>::zero_out_brillig_outputs(witness, brillig);
+            let resolution = BrilligSolver::<B>::zero_out_brillig_outputs(witness, &brillig);
             return StepResult::Status(self.handle_opcode_resolution(resolution));
         }
 
+        let step_limit = self.brillig_step_limits.get(&self.instruction_pointer).copied();
         let solver = BrilligSolver::new(
             witness,
             &self.block_solvers,
-            brillig,
+            &brillig,
             self.backend,
             self.instruction_pointer,
-        );
+        )
+        .map(|solver| solver.with_step_limit(step_limit));
         match solver {
             Ok(solver) => StepResult::IntoBrillig(solver),
             Err(..) => StepResult::Status(self.handle_opcode_resolution(solver.map(|_| ()))),
@@ -393,12 +521,17 @@ impl<'a, B: BlackBoxFunctionSolver> ACVM<'a, B> {
     }
 
     pub fn finish_brillig_with_solver(&mut self, solver: BrilligSolver<'a, B>) -> ACVMStatus {
-        if !matches!(&self.opcodes[self.instruction_pointer], Opcode::Brillig(..)) {
+        if !matches!(self.current_brillig_call(), Ok(Some(_))) {
             unreachable!("Not executing a Brillig opcode");
         }
         self.brillig_solver = Some(solver);
         self.solve_opcode()
     }
+
+    /// Returns the Brillig call made by the opcode at the instruction pointer, if it makes one.
+    fn current_brillig_call(&self) -> Result<Option<BrilligCall<'a>>, OpcodeResolutionError> {
+        BrilligCall::from_opcode(&self.opcodes[self.instruction_pointer], self.brillig_bytecode)
+    }
 }
 
 // Returns the concrete value for a particular witness
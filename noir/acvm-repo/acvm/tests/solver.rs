@@ -3,7 +3,7 @@ use std::collections::BTreeMap;
 use acir::{
     brillig::{BinaryFieldOp, MemoryAddress, Opcode as BrilligOpcode, Value, ValueOrArray},
     circuit::{
-        brillig::{Brillig, BrilligInputs, BrilligOutputs},
+        brillig::{Brillig, BrilligBytecode, BrilligInputs, BrilligOutputs},
         opcodes::{BlockId, MemOp},
         Opcode, OpcodeLocation,
     },
@@ -11,7 +11,9 @@ use acir::{
     FieldElement,
 };
 
-use acvm::pwg::{ACVMStatus, ErrorLocation, ForeignCallWaitInfo, OpcodeResolutionError, ACVM};
+use acvm::pwg::{
+    ACVMStatus, AcirCallWaitInfo, ErrorLocation, ForeignCallWaitInfo, OpcodeResolutionError, ACVM,
+};
 use acvm_blackbox_solver::StubbedBlackBoxSolver;
 use brillig_vm::brillig::HeapValueType;
 
@@ -105,7 +107,7 @@ fn inversion_brillig_oracle_equivalence() {
     ])
     .into();
 
-    let mut acvm = ACVM::new(&StubbedBlackBoxSolver, &opcodes, witness_assignments);
+    let mut acvm = ACVM::new(&StubbedBlackBoxSolver, &opcodes, witness_assignments, &[]);
     // use the partial witness generation solver with our acir program
     let solver_status = acvm.solve();
 
@@ -243,7 +245,7 @@ fn double_inversion_brillig_oracle() {
     ])
     .into();
 
-    let mut acvm = ACVM::new(&StubbedBlackBoxSolver, &opcodes, witness_assignments);
+    let mut acvm = ACVM::new(&StubbedBlackBoxSolver, &opcodes, witness_assignments, &[]);
 
     // use the partial witness generation solver with our acir program
     let solver_status = acvm.solve();
@@ -374,7 +376,7 @@ fn oracle_dependent_execution() {
     let witness_assignments =
         BTreeMap::from([(w_x, FieldElement::from(2u128)), (w_y, FieldElement::from(2u128))]).into();
 
-    let mut acvm = ACVM::new(&StubbedBlackBoxSolver, &opcodes, witness_assignments);
+    let mut acvm = ACVM::new(&StubbedBlackBoxSolver, &opcodes, witness_assignments, &[]);
 
     // use the partial witness generation solver with our acir program
     let solver_status = acvm.solve();
@@ -480,7 +482,7 @@ fn brillig_oracle_predicate() {
     ])
     .into();
 
-    let mut acvm = ACVM::new(&StubbedBlackBoxSolver, &opcodes, witness_assignments);
+    let mut acvm = ACVM::new(&StubbedBlackBoxSolver, &opcodes, witness_assignments, &[]);
     let solver_status = acvm.solve();
     assert_eq!(solver_status, ACVMStatus::Solved, "should be fully solved");
 
@@ -514,7 +516,7 @@ fn unsatisfied_opcode_resolved() {
     values.insert(d, FieldElement::from(2_i128));
 
     let opcodes = vec![Opcode::AssertZero(opcode_a)];
-    let mut acvm = ACVM::new(&StubbedBlackBoxSolver, &opcodes, values);
+    let mut acvm = ACVM::new(&StubbedBlackBoxSolver, &opcodes, values, &[]);
     let solver_status = acvm.solve();
     assert_eq!(
         solver_status,
@@ -597,7 +599,7 @@ fn unsatisfied_opcode_resolved_brillig() {
 
     let opcodes = vec![brillig_opcode, Opcode::AssertZero(opcode_a)];
 
-    let mut acvm = ACVM::new(&StubbedBlackBoxSolver, &opcodes, values);
+    let mut acvm = ACVM::new(&StubbedBlackBoxSolver, &opcodes, values, &[]);
     let solver_status = acvm.solve();
     assert_eq!(
         solver_status,
@@ -609,6 +611,136 @@ fn unsatisfied_opcode_resolved_brillig() {
     );
 }
 
+#[test]
+fn brillig_step_limit_aborts_non_terminating_execution() {
+    // A Brillig function which loops forever.
+    let brillig_opcode = Opcode::Brillig(Brillig {
+        inputs: vec![],
+        outputs: vec![],
+        bytecode: vec![BrilligOpcode::Jump { location: 0 }],
+        predicate: None,
+    });
+    let opcodes = vec![brillig_opcode];
+
+    let mut acvm = ACVM::new(&StubbedBlackBoxSolver, &opcodes, WitnessMap::new(), &[])
+        .with_brillig_step_limits(BTreeMap::from([(0, 10)]));
+    let solver_status = acvm.solve();
+    assert_eq!(
+        solver_status,
+        ACVMStatus::Failure(OpcodeResolutionError::BrilligStepLimitExceeded {
+            step_limit: 10,
+            call_stack: vec![OpcodeLocation::Brillig { acir_index: 0, brillig_index: 0 }]
+        }),
+        "Execution should be aborted once the step limit is reached"
+    );
+}
+
+#[test]
+fn brillig_calls_execute_shared_bytecode() {
+    // Returns the sum of its two inputs.
+    let brillig_bytecode = vec![BrilligBytecode {
+        bytecode: vec![
+            BrilligOpcode::CalldataCopy {
+                destination_address: MemoryAddress(0),
+                size: 2,
+                offset: 0,
+            },
+            BrilligOpcode::BinaryFieldOp {
+                destination: MemoryAddress(0),
+                op: BinaryFieldOp::Add,
+                lhs: MemoryAddress(0),
+                rhs: MemoryAddress(1),
+            },
+            BrilligOpcode::Stop { return_data_offset: 0, return_data_size: 1 },
+        ],
+    }];
+    let brillig_call = |lhs: Witness, rhs: Witness, output: Witness| Opcode::BrilligCall {
+        id: 0,
+        inputs: vec![BrilligInputs::Single(lhs.into()), BrilligInputs::Single(rhs.into())],
+        outputs: vec![BrilligOutputs::Simple(output)],
+        predicate: None,
+    };
+    let opcodes = vec![
+        brillig_call(Witness(1), Witness(2), Witness(3)),
+        brillig_call(Witness(3), Witness(3), Witness(4)),
+    ];
+    let initial_witness = WitnessMap::from(BTreeMap::from_iter([
+        (Witness(1), FieldElement::from(2u128)),
+        (Witness(2), FieldElement::from(3u128)),
+    ]));
+
+    let mut acvm = ACVM::new(&StubbedBlackBoxSolver, &opcodes, initial_witness, &brillig_bytecode);
+    assert_eq!(acvm.solve(), ACVMStatus::Solved);
+    let witness_map = acvm.finalize();
+    assert_eq!(witness_map[&Witness(3)], FieldElement::from(5u128));
+    assert_eq!(witness_map[&Witness(4)], FieldElement::from(10u128));
+}
+
+#[test]
+fn brillig_calls_to_unknown_bytecode_fail() {
+    let opcodes =
+        vec![Opcode::BrilligCall { id: 1, inputs: vec![], outputs: vec![], predicate: None }];
+
+    let mut acvm = ACVM::new(&StubbedBlackBoxSolver, &opcodes, WitnessMap::new(), &[]);
+    assert_eq!(
+        acvm.solve(),
+        ACVMStatus::Failure(OpcodeResolutionError::UnknownBrilligBytecode {
+            opcode_location: ErrorLocation::Resolved(OpcodeLocation::Acir(0)),
+            id: 1,
+        })
+    );
+}
+
+#[test]
+fn acir_calls_wait_for_their_results() {
+    let call = Opcode::Call {
+        id: 1,
+        inputs: vec![Witness(1), Witness(2)],
+        outputs: vec![Witness(3)],
+        predicate: None,
+    };
+    let opcodes = vec![call];
+    let initial_witness = WitnessMap::from(BTreeMap::from_iter([
+        (Witness(1), FieldElement::from(2u128)),
+        (Witness(2), FieldElement::from(3u128)),
+    ]));
+
+    let mut acvm = ACVM::new(&StubbedBlackBoxSolver, &opcodes, initial_witness, &[]);
+    let solver_status = acvm.solve();
+    // The inputs of the call are assigned to the parameters of the called circuit.
+    let call_initial_witness = WitnessMap::from(BTreeMap::from_iter([
+        (Witness(0), FieldElement::from(2u128)),
+        (Witness(1), FieldElement::from(3u128)),
+    ]));
+    assert_eq!(
+        solver_status,
+        ACVMStatus::RequiresAcirCall(AcirCallWaitInfo {
+            id: 1,
+            initial_witness: call_initial_witness
+        })
+    );
+
+    acvm.resolve_pending_acir_call(vec![FieldElement::from(6u128)]);
+    assert_eq!(acvm.solve(), ACVMStatus::Solved);
+    assert_eq!(acvm.finalize()[&Witness(3)], FieldElement::from(6u128));
+}
+
+#[test]
+fn acir_calls_with_a_false_predicate_are_skipped() {
+    let call = Opcode::Call {
+        id: 1,
+        inputs: vec![Witness(1)],
+        outputs: vec![Witness(2)],
+        predicate: Some(Expression::zero()),
+    };
+    let opcodes = vec![call];
+
+    // The inputs need not be known as the call is not made.
+    let mut acvm = ACVM::new(&StubbedBlackBoxSolver, &opcodes, WitnessMap::new(), &[]);
+    assert_eq!(acvm.solve(), ACVMStatus::Solved);
+    assert_eq!(acvm.finalize()[&Witness(2)], FieldElement::zero());
+}
+
 #[test]
 fn memory_operations() {
     let initial_witness = WitnessMap::from(BTreeMap::from_iter([
@@ -641,7 +773,7 @@ fn memory_operations() {
 
     let opcodes = vec![init, read_op, expression];
 
-    let mut acvm = ACVM::new(&StubbedBlackBoxSolver, &opcodes, initial_witness);
+    let mut acvm = ACVM::new(&StubbedBlackBoxSolver, &opcodes, initial_witness, &[]);
     let solver_status = acvm.solve();
     assert_eq!(solver_status, ACVMStatus::Solved);
     let witness_map = acvm.finalize();
@@ -64,7 +64,8 @@ pub async fn execute_circuit_with_black_box_solver(
     let circuit: Circuit = Circuit::deserialize_circuit(&circuit)
         .map_err(|_| JsExecutionError::new("Failed to deserialize circuit. This is likely due to differing serialization formats between ACVM_JS and your compiler".to_string(), None))?;
 
-    let mut acvm = ACVM::new(&solver.0, &circuit.opcodes, initial_witness.into());
+    let mut acvm =
+        ACVM::new(&solver.0, &circuit.opcodes, initial_witness.into(), &circuit.brillig_bytecode);
 
     loop {
         let solver_status = acvm.solve();
@@ -90,11 +91,16 @@ pub async fn execute_circuit_with_black_box_solver(
                             call_stack.last().expect("Brillig error call stacks cannot be empty");
                         (circuit.get_assert_message(*failing_opcode), Some(call_stack.clone()))
                     }
+                    OpcodeResolutionError::BrilligStepLimitExceeded { call_stack, .. } => {
+                        (None, Some(call_stack.clone()))
+                    }
                     _ => (None, None),
                 };
 
                 let error_string = match &assert_message {
-                    Some(assert_message) => format!("Assertion failed: {}", assert_message),
+                    Some(assert_message) => {
+                        format!("Assertion failed: {}", assert_message.render(acvm.witness_map()))
+                    }
                     None => error.to_string(),
                 };
 
@@ -105,6 +111,10 @@ pub async fn execute_circuit_with_black_box_solver(
 
                 acvm.resolve_pending_foreign_call(result);
             }
+            ACVMStatus::RequiresAcirCall(_) => {
+                let error = "Calls to other circuits are not supported".to_string();
+                return Err(JsExecutionError::new(error.into(), None).into());
+            }
         }
     }
 
@@ -2,11 +2,11 @@ import { WitnessMap } from '@noir-lang/acvm_js';
 
 // See `addition_circuit` integration test in `acir/tests/test_program_serialization.rs`.
 export const bytecode = Uint8Array.from([
-  31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 173, 208, 49, 14, 192, 32, 8, 5, 80, 212, 30, 8, 4, 20, 182, 94, 165, 166, 122,
-  255, 35, 52, 77, 28, 76, 58, 214, 191, 124, 166, 23, 242, 15, 0, 8, 240, 77, 154, 125, 206, 198, 127, 161, 176, 209,
-  138, 139, 197, 88, 68, 122, 205, 157, 152, 46, 204, 222, 76, 81, 180, 21, 35, 35, 53, 189, 179, 49, 119, 19, 171, 222,
-  188, 162, 147, 112, 167, 161, 206, 99, 98, 105, 223, 95, 248, 26, 113, 90, 97, 185, 97, 217, 56, 173, 35, 63, 243, 81,
-  87, 163, 125, 1, 0, 0,
+  31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 173, 144, 65, 14, 128, 32, 12, 4, 139, 248, 160, 150, 182, 208, 222, 252, 138, 68,
+  248, 255, 19, 140, 9, 7, 18, 189, 201, 92, 118, 79, 147, 205, 238, 0, 16, 224, 77, 28, 121, 140, 196, 127, 80, 88,
+  232, 218, 38, 23, 99, 22, 105, 37, 53, 98, 58, 49, 121, 53, 69, 209, 154, 141, 140, 212, 244, 74, 198, 220, 76, 172,
+  120, 245, 130, 78, 194, 141, 186, 58, 247, 33, 139, 235, 118, 225, 227, 216, 134, 43, 76, 29, 166, 143, 35, 124, 112,
+  3, 202, 243, 104, 183, 133, 1, 0, 0,
 ]);
 
 export const initialWitnessMap: WitnessMap = new Map([
@@ -2,13 +2,13 @@ import { WitnessMap } from '@noir-lang/acvm_js';
 
 // See `complex_brillig_foreign_call` integration test in `acir/tests/test_program_serialization.rs`.
 export const bytecode = Uint8Array.from([
-  31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 213, 84, 75, 10, 132, 48, 12, 125, 177, 163, 35, 179, 154, 35, 8, 51, 7, 232, 204,
-  9, 188, 139, 184, 83, 116, 233, 241, 173, 152, 98, 12, 213, 141, 21, 244, 65, 232, 39, 175, 233, 35, 73, 155, 3, 32,
-  204, 48, 206, 18, 158, 19, 175, 37, 60, 175, 228, 209, 30, 195, 143, 226, 197, 178, 103, 105, 76, 110, 160, 209, 156,
-  160, 209, 247, 195, 69, 235, 29, 179, 46, 81, 243, 103, 2, 239, 231, 225, 44, 117, 150, 241, 250, 201, 99, 206, 251,
-  96, 95, 161, 242, 14, 193, 243, 40, 162, 105, 253, 219, 12, 75, 47, 146, 186, 251, 37, 116, 86, 93, 219, 55, 245, 96,
-  20, 85, 75, 253, 136, 249, 87, 249, 105, 231, 220, 4, 249, 237, 132, 56, 20, 224, 109, 113, 223, 88, 82, 153, 34, 64,
-  34, 14, 164, 69, 172, 48, 2, 23, 243, 6, 31, 25, 5, 0, 0,
+  31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 213, 84, 219, 10, 131, 48, 12, 61, 177, 211, 201, 158, 246, 9, 194, 246, 1, 221,
+  190, 96, 255, 50, 124, 83, 244, 209, 207, 183, 98, 138, 33, 68, 95, 172, 160, 7, 66, 47, 57, 77, 15, 73, 218, 18, 0,
+  97, 134, 11, 150, 241, 156, 120, 45, 17, 121, 63, 30, 253, 62, 124, 40, 93, 44, 127, 148, 198, 236, 2, 26, 221, 1, 26,
+  99, 63, 156, 180, 222, 41, 235, 146, 52, 127, 206, 120, 63, 183, 96, 121, 176, 130, 215, 119, 30, 75, 222, 7, 251, 42,
+  149, 119, 8, 94, 68, 149, 76, 235, 215, 23, 88, 122, 145, 212, 221, 15, 161, 243, 223, 181, 125, 83, 15, 78, 81, 181,
+  212, 151, 152, 191, 149, 159, 54, 206, 77, 144, 223, 142, 197, 33, 131, 183, 198, 125, 98, 73, 101, 14, 131, 68, 28,
+  72, 139, 48, 49, 2, 81, 36, 35, 114, 33, 5, 0, 0,
 ]);
 export const initialWitnessMap: WitnessMap = new Map([
   [1, '0x0000000000000000000000000000000000000000000000000000000000000001'],
@@ -2,10 +2,10 @@ import { WitnessMap } from '@noir-lang/acvm_js';
 
 // See `simple_brillig_foreign_call` integration test in `acir/tests/test_program_serialization.rs`.
 export const bytecode = Uint8Array.from([
-  31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 173, 143, 177, 10, 192, 32, 16, 67, 227, 21, 74, 233, 212, 79, 177, 127, 208, 159,
-  233, 224, 226, 32, 226, 247, 139, 168, 16, 68, 93, 244, 45, 119, 228, 142, 144, 92, 0, 20, 50, 7, 237, 76, 213, 190,
-  50, 245, 26, 175, 218, 231, 165, 57, 175, 148, 14, 137, 179, 147, 191, 114, 211, 221, 216, 240, 59, 63, 107, 221, 115,
-  104, 181, 103, 244, 43, 36, 10, 38, 68, 108, 25, 253, 238, 136, 1, 0, 0,
+  31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 173, 143, 65, 10, 192, 32, 16, 3, 227, 22, 74, 233, 169, 79, 177, 63, 232, 103,
+  122, 232, 165, 7, 17, 223, 47, 162, 66, 16, 21, 65, 231, 178, 75, 118, 9, 201, 1, 64, 33, 178, 209, 206, 100, 237, 73,
+  83, 207, 113, 171, 117, 94, 154, 243, 74, 234, 16, 216, 43, 249, 51, 39, 221, 191, 223, 189, 198, 246, 90, 215, 28,
+  74, 237, 106, 253, 10, 137, 130, 1, 60, 249, 192, 200, 46, 144, 1, 0, 0,
 ]);
 export const initialWitnessMap: WitnessMap = new Map([
   [1, '0x0000000000000000000000000000000000000000000000000000000000000005'],
@@ -1,9 +1,9 @@
 // See `memory_op_circuit` integration test in `acir/tests/test_program_serialization.rs`.
 export const bytecode = Uint8Array.from([
-  31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 213, 145, 187, 17, 0, 32, 8, 67, 195, 111, 31, 220, 192, 253, 167, 178, 144, 2,
-  239, 236, 132, 194, 52, 129, 230, 93, 8, 6, 64, 176, 101, 225, 28, 78, 49, 43, 238, 154, 225, 254, 166, 209, 205, 165,
-  98, 174, 212, 177, 188, 187, 92, 255, 173, 92, 173, 190, 93, 82, 80, 78, 123, 14, 127, 60, 97, 1, 210, 144, 46, 242,
-  19, 3, 0, 0,
+  31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 213, 145, 169, 21, 0, 48, 8, 67, 195, 181, 15, 221, 160, 251, 79, 85, 81, 4, 2, 87,
+  16, 141, 9, 152, 255, 66, 48, 0, 130, 43, 11, 231, 112, 138, 89, 81, 107, 135, 251, 155, 214, 52, 151, 154, 185, 210,
+  199, 242, 233, 114, 253, 183, 114, 181, 251, 118, 73, 65, 57, 237, 57, 124, 249, 132, 3, 79, 191, 128, 193, 27, 3, 0,
+  0,
 ]);
 
 export const initialWitnessMap = new Map([
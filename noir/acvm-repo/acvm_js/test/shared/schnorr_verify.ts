@@ -1,17 +1,17 @@
 // See `schnorr_verify_circuit` integration test in `acir/tests/test_program_serialization.rs`.
 export const bytecode = Uint8Array.from([
-  31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 77, 210, 7, 74, 3, 1, 20, 69, 209, 177, 247, 222, 123, 239, 189, 119, 141, 93, 99,
-  220, 133, 251, 95, 130, 152, 103, 78, 32, 3, 195, 33, 4, 66, 248, 239, 254, 20, 69, 209, 84, 212, 158, 216, 206, 223,
-  234, 219, 204, 146, 239, 91, 170, 111, 103, 245, 109, 101, 27, 219, 217, 193, 250, 219, 197, 110, 246, 176, 151, 125,
-  236, 231, 0, 7, 57, 196, 97, 142, 112, 148, 99, 28, 231, 4, 39, 57, 197, 105, 206, 112, 150, 115, 156, 231, 2, 23,
-  185, 196, 101, 174, 112, 149, 107, 92, 231, 6, 55, 185, 197, 109, 238, 112, 151, 123, 220, 231, 1, 15, 121, 196, 99,
-  158, 240, 148, 103, 60, 231, 5, 47, 121, 197, 107, 222, 240, 150, 119, 188, 231, 3, 75, 124, 228, 83, 195, 142, 121,
-  158, 125, 126, 225, 43, 223, 248, 206, 15, 126, 178, 204, 47, 86, 248, 237, 119, 43, 76, 127, 105, 47, 189, 165, 181,
-  116, 150, 198, 234, 125, 117, 249, 47, 233, 41, 45, 165, 163, 52, 148, 126, 210, 78, 186, 73, 51, 233, 37, 173, 164,
-  147, 52, 146, 62, 210, 70, 186, 72, 19, 233, 33, 45, 164, 131, 52, 144, 253, 23, 139, 218, 238, 217, 60, 123, 103,
-  235, 236, 156, 141, 179, 239, 166, 93, 183, 237, 185, 107, 199, 125, 251, 29, 218, 237, 216, 94, 167, 118, 58, 183,
-  207, 165, 93, 174, 237, 113, 107, 135, 123, 247, 47, 185, 251, 147, 59, 191, 184, 239, 155, 187, 126, 184, 103, 217,
-  29, 235, 55, 171, 223, 173, 104, 184, 231, 255, 243, 7, 236, 52, 239, 128, 225, 3, 0, 0,
+  31, 139, 8, 0, 0, 0, 0, 0, 0, 255, 85, 210, 7, 74, 3, 1, 20, 69, 209, 177, 247, 222, 123, 239, 189, 119, 141, 93, 99,
+  220, 133, 251, 95, 130, 152, 71, 78, 32, 14, 12, 135, 16, 8, 225, 191, 251, 83, 20, 69, 83, 81, 123, 98, 59, 127, 171,
+  111, 51, 75, 190, 111, 169, 190, 157, 213, 183, 149, 109, 108, 103, 7, 235, 111, 23, 187, 217, 195, 94, 246, 177, 159,
+  3, 28, 228, 16, 135, 57, 194, 81, 142, 113, 156, 19, 156, 228, 20, 167, 57, 195, 89, 206, 113, 158, 11, 92, 228, 18,
+  151, 185, 194, 85, 174, 113, 157, 27, 220, 228, 22, 183, 185, 195, 93, 238, 113, 159, 7, 60, 228, 17, 143, 121, 194,
+  83, 158, 241, 156, 23, 188, 228, 21, 175, 121, 195, 91, 222, 241, 158, 15, 44, 241, 145, 79, 13, 59, 230, 121, 246,
+  249, 133, 175, 124, 227, 59, 63, 248, 201, 50, 191, 88, 225, 183, 223, 173, 48, 253, 165, 189, 244, 150, 214, 210, 89,
+  26, 171, 247, 213, 229, 191, 164, 167, 180, 148, 142, 210, 80, 250, 73, 59, 233, 38, 205, 164, 151, 180, 146, 78, 210,
+  72, 250, 72, 27, 233, 34, 77, 164, 135, 180, 144, 14, 210, 64, 246, 95, 44, 106, 187, 103, 243, 236, 157, 173, 179,
+  115, 54, 206, 190, 155, 118, 221, 182, 231, 174, 29, 247, 237, 119, 104, 183, 99, 123, 157, 218, 233, 220, 62, 151,
+  118, 185, 182, 199, 173, 29, 238, 221, 191, 228, 238, 79, 238, 252, 226, 190, 111, 238, 250, 225, 158, 101, 119, 172,
+  223, 172, 126, 183, 162, 225, 158, 255, 158, 63, 195, 119, 133, 187, 233, 3, 0, 0,
 ]);
 
 export const initialWitnessMap = new Map([
@@ -0,0 +1,57 @@
+//! AES-128 encryption in CBC mode with PKCS#7 padding, as specified by FIPS 197 and NIST SP 800-38A.
+
+use aes::cipher::{block_padding::Pkcs7, BlockEncryptMut, KeyIvInit};
+
+type Aes128CbcEnc = cbc::Encryptor<aes::Aes128>;
+
+/// Encrypts `inputs` with AES-128 in CBC mode, after padding them to a whole number of blocks
+/// according to PKCS#7.
+///
+/// The ciphertext is always longer than `inputs`, as a full block of padding is added when their
+/// length is already a multiple of the block size.
+pub(crate) fn encrypt_cbc(inputs: &[u8], iv: &[u8; 16], key: &[u8; 16]) -> Vec<u8> {
+    Aes128CbcEnc::new(key.into(), iv.into()).encrypt_padded_vec_mut::<Pkcs7>(inputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::encrypt_cbc;
+
+    fn from_hex<const N: usize>(hex: &str) -> [u8; N] {
+        let bytes: Vec<u8> = (0..hex.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&hex[index..index + 2], 16).unwrap())
+            .collect();
+        bytes.try_into().unwrap()
+    }
+
+    #[test]
+    fn encrypts_fips_197_example_block() {
+        // With an all-zero IV, the first block of the ciphertext is the block cipher's output.
+        let key = from_hex("000102030405060708090a0b0c0d0e0f");
+        let block: [u8; 16] = from_hex("00112233445566778899aabbccddeeff");
+        let ciphertext = encrypt_cbc(&block, &[0; 16], &key);
+        assert_eq!(ciphertext[..16], from_hex::<16>("69c4e0d86a7b0430d8cdb78070b4c55a"));
+    }
+
+    #[test]
+    fn encrypts_sp_800_38a_cbc_example() {
+        let key = from_hex("2b7e151628aed2a6abf7158809cf4f3c");
+        let iv = from_hex("000102030405060708090a0b0c0d0e0f");
+        let inputs: [u8; 32] =
+            from_hex("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51");
+
+        let ciphertext = encrypt_cbc(&inputs, &iv, &key);
+        // A whole block of padding follows the two blocks of the example.
+        assert_eq!(ciphertext.len(), 48);
+        let expected: [u8; 32] =
+            from_hex("7649abac8119b246cee98e9b12e9197d5086cb9b507219ee95db113a917678b2");
+        assert_eq!(ciphertext[..32], expected);
+    }
+
+    #[test]
+    fn pads_partial_blocks() {
+        let ciphertext = encrypt_cbc(&[0; 20], &[0; 16], &[0; 16]);
+        assert_eq!(ciphertext.len(), 32);
+    }
+}
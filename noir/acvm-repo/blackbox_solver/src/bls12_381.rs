@@ -0,0 +1,116 @@
+//! Arithmetic on the group G1 of the BLS12-381 curve `y^2 = x^3 + 4`.
+//!
+//! Points are encoded as the 48 byte big endian encodings of their affine `x` and `y`
+//! coordinates, with the point at infinity encoded as zero bytes, as in EIP-2537.
+
+use ark_bls12_381::{Fq, G1Affine};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, BigInteger384, PrimeField};
+
+/// The number of bytes of an encoded point.
+pub(crate) const POINT_BYTES: usize = 96;
+
+/// The number of bytes of an encoded coordinate.
+const COORDINATE_BYTES: usize = 48;
+
+/// Adds the points of G1 encoded by `lhs` and `rhs`.
+pub(crate) fn g1_add(
+    lhs: &[u8; POINT_BYTES],
+    rhs: &[u8; POINT_BYTES],
+) -> Result<[u8; POINT_BYTES], String> {
+    let lhs = decode(lhs).ok_or("the first point is not on the curve")?;
+    let rhs = decode(rhs).ok_or("the second point is not on the curve")?;
+    Ok(encode((lhs + rhs).into_affine()))
+}
+
+/// Multiplies the point of G1 encoded by `point` by the big endian integer `scalar`.
+pub(crate) fn g1_mul(
+    point: &[u8; POINT_BYTES],
+    scalar: &[u8; 32],
+) -> Result<[u8; POINT_BYTES], String> {
+    let point = decode(point).ok_or("the point is not on the curve")?;
+    // Points outside of G1 are on the curve but have a cofactor component, which scalars of G1
+    // are not meant to act on.
+    if !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err("the point is not in the prime order subgroup".to_string());
+    }
+    let mut limbs = [0; 4];
+    for (limb, bytes) in limbs.iter_mut().zip(scalar.rchunks(8)) {
+        *limb = u64::from_be_bytes(bytes.try_into().expect("chunks are 8 bytes long"));
+    }
+    Ok(encode(point.mul_bigint(limbs).into_affine()))
+}
+
+/// Decodes a point of the curve. Returns `None` if a coordinate is not reduced or if the point is
+/// not on the curve.
+fn decode(bytes: &[u8; POINT_BYTES]) -> Option<G1Affine> {
+    if bytes.iter().all(|byte| *byte == 0) {
+        return Some(G1Affine::identity());
+    }
+    let (x, y) = bytes.split_at(COORDINATE_BYTES);
+    let point = G1Affine::new_unchecked(decode_coordinate(x)?, decode_coordinate(y)?);
+    point.is_on_curve().then_some(point)
+}
+
+fn decode_coordinate(bytes: &[u8]) -> Option<Fq> {
+    let mut limbs = [0; 6];
+    for (limb, bytes) in limbs.iter_mut().zip(bytes.rchunks(8)) {
+        *limb = u64::from_be_bytes(bytes.try_into().expect("chunks are 8 bytes long"));
+    }
+    Fq::from_bigint(BigInteger384::new(limbs))
+}
+
+fn encode(point: G1Affine) -> [u8; POINT_BYTES] {
+    let mut bytes = [0; POINT_BYTES];
+    if let Some((x, y)) = point.xy() {
+        bytes[..COORDINATE_BYTES].copy_from_slice(&x.into_bigint().to_bytes_be());
+        bytes[COORDINATE_BYTES..].copy_from_slice(&y.into_bigint().to_bytes_be());
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{g1_add, g1_mul, POINT_BYTES};
+
+    const GENERATOR: &str = "17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb08b3f481e3aaa0f1a09e30ed741d8ae4fcf5e095d5d00af600db18cb2c04b3edd03cc744a2888ae40caa232946c5e7e1";
+
+    fn from_hex<const N: usize>(hex: &str) -> [u8; N] {
+        let mut bytes = [0; N];
+        for (byte, digits) in bytes.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            let digits = std::str::from_utf8(digits).unwrap();
+            *byte = u8::from_str_radix(digits, 16).unwrap();
+        }
+        bytes
+    }
+
+    #[test]
+    fn adds_points() {
+        let generator = from_hex(GENERATOR);
+        let doubled = from_hex::<POINT_BYTES>("0572cbea904d67468808c8eb50a9450c9721db309128012543902d0ac358a62ae28f75bb8f1c7c42c39a8c5529bf0f4e166a9d8cabc673a322fda673779d8e3822ba3ecb8670e461f73bb9021d5fd76a4c56d9d4cd16bd1bba86881979749d28");
+        assert_eq!(g1_add(&generator, &generator), Ok(doubled));
+        assert_eq!(g1_add(&generator, &[0; POINT_BYTES]), Ok(generator));
+
+        let minus_one =
+            from_hex("73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000000");
+        let negated = g1_mul(&generator, &minus_one).unwrap();
+        assert_eq!(g1_add(&generator, &negated), Ok([0; POINT_BYTES]));
+    }
+
+    #[test]
+    fn multiplies_points() {
+        let generator = from_hex(GENERATOR);
+        let scalar = from_hex("2a7b1c4e5f3d9e8a0b6c1d2e3f405162738495a6b7c8d9eaf0123456789abcde");
+        let expected = from_hex::<POINT_BYTES>("0474a40af29bb282858c8d31adcd9ac943f234bb9fc61a039d593e1c8f80768845a60cc19937b9a401f2a8fa08bae2760c7428abc0780c0eb36e568f090291814a01aaf00d15f72a3436913fb8aed20aa1586035e4e4b1864b5c440595c7c81c");
+        assert_eq!(g1_mul(&generator, &scalar), Ok(expected));
+        assert_eq!(g1_mul(&generator, &[0; 32]), Ok([0; POINT_BYTES]));
+    }
+
+    #[test]
+    fn rejects_points_off_the_curve() {
+        let mut point: [u8; POINT_BYTES] = from_hex(GENERATOR);
+        point[POINT_BYTES - 1] ^= 1;
+        assert!(g1_add(&point, &point).is_err());
+        assert!(g1_mul(&point, &[1; 32]).is_err());
+    }
+}
@@ -29,6 +29,11 @@ pub trait BlackBoxFunctionSolver {
         low: &FieldElement,
         high: &FieldElement,
     ) -> Result<(FieldElement, FieldElement), BlackBoxResolutionError>;
+    fn multi_scalar_mul(
+        &self,
+        points: &[FieldElement],
+        scalars: &[FieldElement],
+    ) -> Result<(FieldElement, FieldElement), BlackBoxResolutionError>;
     fn ec_add(
         &self,
         input1_x: &FieldElement,
@@ -36,6 +41,11 @@ pub trait BlackBoxFunctionSolver {
         input2_x: &FieldElement,
         input2_y: &FieldElement,
     ) -> Result<(FieldElement, FieldElement), BlackBoxResolutionError>;
+    fn poseidon_permutation(
+        &self,
+        inputs: &[FieldElement],
+        rate: u32,
+    ) -> Result<Vec<FieldElement>, BlackBoxResolutionError>;
 }
 
 pub struct StubbedBlackBoxSolver;
@@ -80,6 +90,13 @@ impl BlackBoxFunctionSolver for StubbedBlackBoxSolver {
     ) -> Result<(FieldElement, FieldElement), BlackBoxResolutionError> {
         Err(Self::fail(BlackBoxFunc::FixedBaseScalarMul))
     }
+    fn multi_scalar_mul(
+        &self,
+        _points: &[FieldElement],
+        _scalars: &[FieldElement],
+    ) -> Result<(FieldElement, FieldElement), BlackBoxResolutionError> {
+        Err(Self::fail(BlackBoxFunc::MultiScalarMul))
+    }
     fn ec_add(
         &self,
         _input1_x: &FieldElement,
@@ -89,4 +106,11 @@ impl BlackBoxFunctionSolver for StubbedBlackBoxSolver {
     ) -> Result<(FieldElement, FieldElement), BlackBoxResolutionError> {
         Err(Self::fail(BlackBoxFunc::EmbeddedCurveAdd))
     }
+    fn poseidon_permutation(
+        &self,
+        _inputs: &[FieldElement],
+        _rate: u32,
+    ) -> Result<Vec<FieldElement>, BlackBoxResolutionError> {
+        Err(Self::fail(BlackBoxFunc::PoseidonPermutation))
+    }
 }
@@ -0,0 +1,70 @@
+//! Ed25519 signature verification, as specified by RFC 8032.
+
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+
+/// Verifies the Ed25519 `signature` of `message` under `public_key`.
+///
+/// Returns false if either the public key or the first half of the signature is not the
+/// encoding of a point, or if the second half of the signature is not reduced.
+pub(crate) fn verify_signature(
+    message: &[u8],
+    public_key: &[u8; 32],
+    signature: &[u8; 64],
+) -> bool {
+    let Ok(public_key) = VerifyingKey::from_bytes(public_key) else {
+        return false;
+    };
+    // Verification rejects an unreduced `s`, and compares the encoding of `R` against the one
+    // it recomputes, which is never the encoding of something other than a point.
+    public_key.verify(message, &Signature::from_bytes(signature)).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::verify_signature;
+
+    /// The order of the prime order subgroup generated by the base point, in little endian.
+    const ORDER: [u8; 32] = [
+        0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde,
+        0x14, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x10,
+    ];
+
+    // Test 2 of section 7.1 of RFC 8032.
+    const PUBLIC_KEY: [u8; 32] = [
+        0x3d, 0x40, 0x17, 0xc3, 0xe8, 0x43, 0x89, 0x5a, 0x92, 0xb7, 0x0a, 0xa7, 0x4d, 0x1b, 0x7e,
+        0xbc, 0x9c, 0x98, 0x2c, 0xcf, 0x2e, 0xc4, 0x96, 0x8c, 0xc0, 0xcd, 0x55, 0xf1, 0x2a, 0xf4,
+        0x66, 0x0c,
+    ];
+    const MESSAGE: [u8; 1] = [0x72];
+    const SIGNATURE: [u8; 64] = [
+        0x92, 0xa0, 0x09, 0xa9, 0xf0, 0xd4, 0xca, 0xb8, 0x72, 0x0e, 0x82, 0x0b, 0x5f, 0x64, 0x25,
+        0x40, 0xa2, 0xb2, 0x7b, 0x54, 0x16, 0x50, 0x3f, 0x8f, 0xb3, 0x76, 0x22, 0x23, 0xeb, 0xdb,
+        0x69, 0xda, 0x08, 0x5a, 0xc1, 0xe4, 0x3e, 0x15, 0x99, 0x6e, 0x45, 0x8f, 0x36, 0x13, 0xd0,
+        0xf1, 0x1d, 0x8c, 0x38, 0x7b, 0x2e, 0xae, 0xb4, 0x30, 0x2a, 0xee, 0xb0, 0x0d, 0x29, 0x16,
+        0x12, 0xbb, 0x0c, 0x00,
+    ];
+
+    #[test]
+    fn verifies_valid_signature() {
+        assert!(verify_signature(&MESSAGE, &PUBLIC_KEY, &SIGNATURE));
+    }
+
+    #[test]
+    fn rejects_signature_of_other_message() {
+        assert!(!verify_signature(&[0x73], &PUBLIC_KEY, &SIGNATURE));
+    }
+
+    #[test]
+    fn rejects_unreduced_signature() {
+        // Adding the group order to `s` yields an equivalent but malleated signature.
+        let mut signature = SIGNATURE;
+        let mut carry = 0;
+        for (byte, order_byte) in signature[32..].iter_mut().zip(ORDER) {
+            let sum = u16::from(*byte) + u16::from(order_byte) + carry;
+            *byte = sum as u8;
+            carry = sum >> 8;
+        }
+        assert!(!verify_signature(&MESSAGE, &PUBLIC_KEY, &signature));
+    }
+}
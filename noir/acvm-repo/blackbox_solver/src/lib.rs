@@ -10,11 +10,14 @@
 use acir::BlackBoxFunc;
 use blake2::digest::generic_array::GenericArray;
 use blake2::{Blake2s256, Digest};
-use sha2::Sha256;
+use sha2::{Sha256, Sha512};
 use sha3::Keccak256;
 use thiserror::Error;
 
+mod aes128;
+mod bls12_381;
 mod curve_specific_solver;
+mod ed25519;
 
 pub use curve_specific_solver::{BlackBoxFunctionSolver, StubbedBlackBoxSolver};
 
@@ -24,11 +27,25 @@ pub enum BlackBoxResolutionError {
     Failed(BlackBoxFunc, String),
 }
 
+pub fn aes128_encrypt(
+    inputs: &[u8],
+    iv: &[u8; 16],
+    key: &[u8; 16],
+) -> Result<Vec<u8>, BlackBoxResolutionError> {
+    Ok(aes128::encrypt_cbc(inputs, iv, key))
+}
+
 pub fn sha256(inputs: &[u8]) -> Result<[u8; 32], BlackBoxResolutionError> {
     generic_hash_256::<Sha256>(inputs)
         .map_err(|err| BlackBoxResolutionError::Failed(BlackBoxFunc::SHA256, err))
 }
 
+pub fn sha512(inputs: &[u8]) -> Result<[u8; 64], BlackBoxResolutionError> {
+    let mut digest = [0; 64];
+    digest.copy_from_slice(&Sha512::digest(inputs));
+    Ok(digest)
+}
+
 pub fn blake2s(inputs: &[u8]) -> Result<[u8; 32], BlackBoxResolutionError> {
     generic_hash_256::<Blake2s256>(inputs)
         .map_err(|err| BlackBoxResolutionError::Failed(BlackBoxFunc::Blake2s, err))
@@ -70,6 +87,30 @@ pub fn ecdsa_secp256r1_verify(
     Ok(verify_secp256r1_ecdsa_signature(hashed_msg, public_key_x, public_key_y, signature))
 }
 
+pub fn ed25519_verify(
+    message: &[u8],
+    public_key: &[u8; 32],
+    signature: &[u8; 64],
+) -> Result<bool, BlackBoxResolutionError> {
+    Ok(ed25519::verify_signature(message, public_key, signature))
+}
+
+pub fn bls12_381_g1_add(
+    lhs: &[u8; 96],
+    rhs: &[u8; 96],
+) -> Result<[u8; 96], BlackBoxResolutionError> {
+    bls12_381::g1_add(lhs, rhs)
+        .map_err(|err| BlackBoxResolutionError::Failed(BlackBoxFunc::Bls12381G1Add, err))
+}
+
+pub fn bls12_381_g1_mul(
+    point: &[u8; 96],
+    scalar: &[u8; 32],
+) -> Result<[u8; 96], BlackBoxResolutionError> {
+    bls12_381::g1_mul(point, scalar)
+        .map_err(|err| BlackBoxResolutionError::Failed(BlackBoxFunc::Bls12381G1Mul, err))
+}
+
 /// Does a generic hash of the inputs returning the resulting 32 bytes separately.
 fn generic_hash_256<D: Digest>(message: &[u8]) -> Result<[u8; 32], String> {
     let output_bytes: [u8; 32] =
@@ -286,6 +327,25 @@ mod keccakf1600_tests {
     }
 }
 
+#[cfg(test)]
+mod sha512_tests {
+    use crate::sha512;
+
+    #[test]
+    fn hashes_abc() {
+        // Test vector from FIPS 180-2, Appendix C.1
+        let expected = [
+            0xdd, 0xaf, 0x35, 0xa1, 0x93, 0x61, 0x7a, 0xba, 0xcc, 0x41, 0x73, 0x49, 0xae, 0x20,
+            0x41, 0x31, 0x12, 0xe6, 0xfa, 0x4e, 0x89, 0xa9, 0x7e, 0xa2, 0x0a, 0x9e, 0xee, 0xe6,
+            0x4b, 0x55, 0xd3, 0x9a, 0x21, 0x92, 0x99, 0x2a, 0x27, 0x4f, 0xc1, 0xa8, 0x36, 0xba,
+            0x3c, 0x23, 0xa3, 0xfe, 0xeb, 0xbd, 0x45, 0x4d, 0x44, 0x23, 0x64, 0x3c, 0xe8, 0x0e,
+            0x2a, 0x9a, 0xc9, 0x4f, 0xa5, 0x4c, 0xa4, 0x9f,
+        ];
+
+        assert_eq!(sha512(b"abc").unwrap(), expected);
+    }
+}
+
 #[cfg(test)]
 mod secp256k1_tests {
     use super::verify_secp256k1_ecdsa_signature;
@@ -10,16 +10,81 @@ pub fn fixed_base_scalar_mul(
     low: &FieldElement,
     high: &FieldElement,
 ) -> Result<(FieldElement, FieldElement), BlackBoxResolutionError> {
+    let grumpkin_integer = grumpkin_scalar(low, high, BlackBoxFunc::FixedBaseScalarMul)?;
+
+    let result = grumpkin::SWAffine::from(
+        grumpkin::SWAffine::generator().mul_bigint(grumpkin_integer.to_u64_digits()),
+    );
+    if let Some((res_x, res_y)) = result.xy() {
+        Ok((FieldElement::from_repr(*res_x), FieldElement::from_repr(*res_y)))
+    } else {
+        Ok((FieldElement::zero(), FieldElement::zero()))
+    }
+}
+
+/// Computes the sum of the scalar multiplications of `points` by `scalars`, where each point is
+/// given by its x and y coordinates and each scalar by its low and high 128 bit limbs.
+///
+/// As in the results of [`fixed_base_scalar_mul`], `(0, 0)` represents the point at infinity.
+pub fn multi_scalar_mul(
+    points: &[FieldElement],
+    scalars: &[FieldElement],
+) -> Result<(FieldElement, FieldElement), BlackBoxResolutionError> {
+    if points.len() % 2 != 0 || points.len() != scalars.len() {
+        return Err(BlackBoxResolutionError::Failed(
+            BlackBoxFunc::MultiScalarMul,
+            format!(
+                "Expected two limbs of a scalar for each point, but got {} coordinates and {} limbs",
+                points.len(),
+                scalars.len()
+            ),
+        ));
+    }
+
+    let mut output_point = grumpkin::SWAffine::zero();
+    for (point, scalar) in points.chunks_exact(2).zip(scalars.chunks_exact(2)) {
+        let grumpkin_integer =
+            grumpkin_scalar(&scalar[0], &scalar[1], BlackBoxFunc::MultiScalarMul)?;
+        let (x, y) = (point[0], point[1]);
+        if x.is_zero() && y.is_zero() {
+            continue;
+        }
+
+        let point = grumpkin::SWAffine::new_unchecked(x.into_repr(), y.into_repr());
+        if !point.is_on_curve() {
+            return Err(BlackBoxResolutionError::Failed(
+                BlackBoxFunc::MultiScalarMul,
+                format!("Point ({}, {}) is not on curve", x.to_hex(), y.to_hex()),
+            ));
+        }
+        let product = grumpkin::SWAffine::from(point.mul_bigint(grumpkin_integer.to_u64_digits()));
+        output_point = grumpkin::SWAffine::from(output_point + product);
+    }
+
+    if let Some((res_x, res_y)) = output_point.xy() {
+        Ok((FieldElement::from_repr(*res_x), FieldElement::from_repr(*res_y)))
+    } else {
+        Ok((FieldElement::zero(), FieldElement::zero()))
+    }
+}
+
+/// Returns the grumpkin scalar with the given 128 bit limbs, failing as `func` if either limb or
+/// the scalar itself is out of range.
+fn grumpkin_scalar(
+    low: &FieldElement,
+    high: &FieldElement,
+    func: BlackBoxFunc,
+) -> Result<BigUint, BlackBoxResolutionError> {
     let low: u128 = low.try_into_u128().ok_or_else(|| {
         BlackBoxResolutionError::Failed(
-            BlackBoxFunc::FixedBaseScalarMul,
+            func,
             format!("Limb {} is not less than 2^128", low.to_hex()),
         )
     })?;
 
     let high: u128 = high.try_into_u128().ok_or_else(|| {
         BlackBoxResolutionError::Failed(
-            BlackBoxFunc::FixedBaseScalarMul,
+            func,
             format!("Limb {} is not less than 2^128", high.to_hex()),
         )
     })?;
@@ -32,19 +97,11 @@ pub fn fixed_base_scalar_mul(
 
     if grumpkin_integer >= grumpkin::FrConfig::MODULUS.into() {
         return Err(BlackBoxResolutionError::Failed(
-            BlackBoxFunc::FixedBaseScalarMul,
+            func,
             format!("{} is not a valid grumpkin scalar", grumpkin_integer.to_str_radix(16)),
         ));
     }
-
-    let result = grumpkin::SWAffine::from(
-        grumpkin::SWAffine::generator().mul_bigint(grumpkin_integer.to_u64_digits()),
-    );
-    if let Some((res_x, res_y)) = result.xy() {
-        Ok((FieldElement::from_repr(*res_x), FieldElement::from_repr(*res_y)))
-    } else {
-        Ok((FieldElement::zero(), FieldElement::zero()))
-    }
+    Ok(grumpkin_integer)
 }
 
 pub fn embedded_curve_add(
@@ -98,6 +155,31 @@ mod grumpkin_fixed_base_scalar_mul {
         Ok(())
     }
 
+    #[test]
+    fn multi_scalar_mul_matches_fixed_base_scalar_mul() -> Result<(), BlackBoxResolutionError> {
+        let zero = FieldElement::zero();
+        let (gen_x, gen_y) = fixed_base_scalar_mul(&FieldElement::one(), &zero)?;
+        let points = [gen_x, gen_y, gen_x, gen_y, zero, zero];
+        let scalars =
+            [FieldElement::one(), zero, FieldElement::from(2u128), zero, FieldElement::one(), zero];
+
+        let res = multi_scalar_mul(&points, &scalars)?;
+        assert_eq!(res, fixed_base_scalar_mul(&FieldElement::from(3u128), &zero)?);
+        Ok(())
+    }
+
+    #[test]
+    fn multi_scalar_mul_rejects_points_off_curve() {
+        let points = [FieldElement::one(), FieldElement::one()];
+        let scalars = [FieldElement::one(), FieldElement::zero()];
+
+        let res = multi_scalar_mul(&points, &scalars);
+        assert!(matches!(
+            res,
+            Err(BlackBoxResolutionError::Failed(BlackBoxFunc::MultiScalarMul, _))
+        ));
+    }
+
     #[test]
     fn rejects_invalid_limbs() {
         let max_limb = FieldElement::from(u128::MAX);
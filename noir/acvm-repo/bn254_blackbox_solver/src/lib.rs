@@ -8,7 +8,7 @@ use acvm_blackbox_solver::{BlackBoxFunctionSolver, BlackBoxResolutionError};
 mod fixed_base_scalar_mul;
 mod wasm;
 
-pub use fixed_base_scalar_mul::{embedded_curve_add, fixed_base_scalar_mul};
+pub use fixed_base_scalar_mul::{embedded_curve_add, fixed_base_scalar_mul, multi_scalar_mul};
 use wasm::Barretenberg;
 
 use self::wasm::{Pedersen, SchnorrSig};
@@ -88,6 +88,14 @@ impl BlackBoxFunctionSolver for Bn254BlackBoxSolver {
         fixed_base_scalar_mul(low, high)
     }
 
+    fn multi_scalar_mul(
+        &self,
+        points: &[FieldElement],
+        scalars: &[FieldElement],
+    ) -> Result<(FieldElement, FieldElement), BlackBoxResolutionError> {
+        multi_scalar_mul(points, scalars)
+    }
+
     fn ec_add(
         &self,
         input1_x: &FieldElement,
@@ -97,4 +105,15 @@ impl BlackBoxFunctionSolver for Bn254BlackBoxSolver {
     ) -> Result<(FieldElement, FieldElement), BlackBoxResolutionError> {
         embedded_curve_add(*input1_x, *input1_y, *input2_x, *input2_y)
     }
+
+    fn poseidon_permutation(
+        &self,
+        _inputs: &[FieldElement],
+        _rate: u32,
+    ) -> Result<Vec<FieldElement>, BlackBoxResolutionError> {
+        Err(BlackBoxResolutionError::Failed(
+            BlackBoxFunc::PoseidonPermutation,
+            "the Poseidon permutation is not exposed by the Barretenberg wasm".to_string(),
+        ))
+    }
 }
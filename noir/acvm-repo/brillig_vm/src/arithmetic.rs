@@ -4,7 +4,7 @@ use num_bigint::{BigInt, BigUint};
 use num_traits::{One, ToPrimitive, Zero};
 
 /// Evaluate a binary operation on two FieldElements and return the result as a FieldElement.
-pub(crate) fn evaluate_binary_field_op(
+pub fn evaluate_binary_field_op(
     op: &BinaryFieldOp,
     a: FieldElement,
     b: FieldElement,
@@ -19,6 +19,22 @@ pub(crate) fn evaluate_binary_field_op(
     }
 }
 
+/// Evaluate a binary operation on two unsigned integers with a given bit size, held in FieldElements,
+/// and return the result as a FieldElement.
+pub fn evaluate_binary_int_op(
+    op: &BinaryIntOp,
+    a: FieldElement,
+    b: FieldElement,
+    bit_size: u32,
+) -> Result<FieldElement, String> {
+    // Convert to big integers
+    let a = BigUint::from_bytes_be(&a.to_be_bytes());
+    let b = BigUint::from_bytes_be(&b.to_be_bytes());
+    let result = evaluate_binary_bigint_op(op, a, b, bit_size)?;
+    // Convert back to field element
+    Ok(FieldElement::from_be_bytes_reduce(&result.to_bytes_be()))
+}
+
 /// Evaluate a binary operation on two unsigned big integers with a given bit size and return the result as a big integer.
 pub(crate) fn evaluate_binary_bigint_op(
     op: &BinaryIntOp,
@@ -24,7 +24,7 @@ mod black_box;
 mod memory;
 
 use acvm_blackbox_solver::{BlackBoxFunctionSolver, BlackBoxResolutionError};
-use arithmetic::{evaluate_binary_bigint_op, evaluate_binary_field_op};
+pub use arithmetic::{evaluate_binary_field_op, evaluate_binary_int_op};
 use black_box::evaluate_black_box;
 
 pub use memory::Memory;
@@ -139,14 +139,19 @@ impl<'a, B: BlackBoxFunctionSolver> VM<'a, B> {
         self.status(VMStatus::InProgress);
     }
 
+    /// Returns the return locations of the calls being executed, followed by the program counter.
+    pub fn call_stack(&self) -> ErrorCallStack {
+        let mut call_stack: Vec<_> = self.call_stack.iter().map(|value| value.to_usize()).collect();
+        call_stack.push(self.program_counter);
+        call_stack
+    }
+
     /// Sets the current status of the VM to `fail`.
     /// Indicating that the VM encountered a `Trap` Opcode
     /// or an invalid state.
     fn fail(&mut self, message: String) -> VMStatus {
-        let mut error_stack: Vec<_> =
-            self.call_stack.iter().map(|value| value.to_usize()).collect();
-        error_stack.push(self.program_counter);
-        self.status(VMStatus::Failure { call_stack: error_stack, message });
+        let call_stack = self.call_stack();
+        self.status(VMStatus::Failure { call_stack, message });
         self.status.clone()
     }
 
@@ -498,13 +503,9 @@ impl<'a, B: BlackBoxFunctionSolver> VM<'a, B> {
         let lhs_value = self.memory.read(lhs);
         let rhs_value = self.memory.read(rhs);
 
-        // Convert to big integers
-        let lhs_big = BigUint::from_bytes_be(&lhs_value.to_field().to_be_bytes());
-        let rhs_big = BigUint::from_bytes_be(&rhs_value.to_field().to_be_bytes());
-        let result_value = evaluate_binary_bigint_op(&op, lhs_big, rhs_big, bit_size)?;
-        // Convert back to field element
-        self.memory
-            .write(result, FieldElement::from_be_bytes_reduce(&result_value.to_bytes_be()).into());
+        let result_value =
+            evaluate_binary_int_op(&op, lhs_value.to_field(), rhs_value.to_field(), bit_size)?;
+        self.memory.write(result, result_value.into());
         Ok(())
     }
 
@@ -549,6 +550,13 @@ impl BlackBoxFunctionSolver for DummyBlackBoxSolver {
     ) -> Result<(FieldElement, FieldElement), BlackBoxResolutionError> {
         Ok((4_u128.into(), 5_u128.into()))
     }
+    fn multi_scalar_mul(
+        &self,
+        _points: &[FieldElement],
+        _scalars: &[FieldElement],
+    ) -> Result<(FieldElement, FieldElement), BlackBoxResolutionError> {
+        Ok((7_u128.into(), 8_u128.into()))
+    }
     fn ec_add(
         &self,
         _input1_x: &FieldElement,
@@ -558,6 +566,13 @@ impl BlackBoxFunctionSolver for DummyBlackBoxSolver {
     ) -> Result<(FieldElement, FieldElement), BlackBoxResolutionError> {
         Ok((5_u128.into(), 6_u128.into()))
     }
+    fn poseidon_permutation(
+        &self,
+        inputs: &[FieldElement],
+        _rate: u32,
+    ) -> Result<Vec<FieldElement>, BlackBoxResolutionError> {
+        Ok(inputs.to_vec())
+    }
 }
 
 #[cfg(test)]
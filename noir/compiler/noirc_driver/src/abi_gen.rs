@@ -1,8 +1,12 @@
 use std::collections::BTreeMap;
 
-use acvm::acir::native_types::Witness;
+use acvm::acir::{circuit::Circuit, native_types::Witness};
 use iter_extended::{btree_map, vecmap};
-use noirc_abi::{Abi, AbiParameter, AbiReturnType, AbiType};
+use noirc_abi::{
+    Abi, AbiParameter, AbiReturnType, AbiType, PublicInput, PublicInputTable, PublicInputValue,
+    MAIN_RETURN_NAME,
+};
+use noirc_evaluator::{public_input_layout::PublicInputElements, ssa::InputParameter};
 use noirc_frontend::{
     hir::Context,
     hir_def::{function::Param, stmt::HirPattern},
@@ -11,22 +15,44 @@ use noirc_frontend::{
 };
 use std::ops::Range;
 
-/// Arranges a function signature and a generated circuit's return witnesses into a
-/// `noirc_abi::Abi`.
+/// Arranges a function signature and a generated circuit's input parameters and return witnesses
+/// into a `noirc_abi::Abi`.
 pub(super) fn gen_abi(
     context: &Context,
     func_id: &FuncId,
-    input_witnesses: Vec<Witness>,
+    input_parameters: Vec<InputParameter>,
     return_witnesses: Vec<Witness>,
     return_visibility: Visibility,
 ) -> Abi {
     let (parameters, return_type) = compute_function_abi(context, func_id);
-    let param_witnesses = param_witnesses_from_abi_param(&parameters, input_witnesses);
+    let param_witnesses = param_witnesses_from_abi_param(&parameters, &input_parameters);
     let return_type = return_type
         .map(|typ| AbiReturnType { abi_type: typ, visibility: return_visibility.into() });
     Abi { parameters, return_type, param_witnesses, return_witnesses }
 }
 
+/// Describes each public input of `circuit` in terms of the values of `abi` which it holds.
+pub(super) fn gen_public_input_table(
+    abi: &Abi,
+    circuit: &Circuit,
+    elements: &PublicInputElements,
+) -> PublicInputTable {
+    let mut values = vecmap(&abi.parameters, |param| param.typ.flattened_elements(&param.name));
+    if let Some(return_type) = &abi.return_type {
+        values.push(return_type.abi_type.flattened_elements(MAIN_RETURN_NAME));
+    }
+
+    let public_inputs = circuit.public_inputs().0.into_iter().enumerate();
+    PublicInputTable(vecmap(public_inputs, |(position, witness)| {
+        let elements = elements.get(&witness).map_or(&[][..], Vec::as_slice);
+        let values = vecmap(elements, |element| {
+            let (path, encoding) = values[element.value][element.element].clone();
+            PublicInputValue { path, encoding, bit_offset: element.bit_offset }
+        });
+        PublicInput { position, values }
+    }))
+}
+
 pub(super) fn compute_function_abi(
     context: &Context,
     func_id: &FuncId,
@@ -60,25 +86,16 @@ fn into_abi_params(context: &Context, params: Vec<Param>) -> Vec<AbiParameter> {
     })
 }
 
-// Takes each abi parameter and shallowly maps to the expected witness range in which the
-// parameter's constituent values live.
+// Takes each abi parameter and shallowly maps to the witness ranges in which the input parameter
+// of the circuit at the same position keeps its constituent values.
 fn param_witnesses_from_abi_param(
     abi_params: &[AbiParameter],
-    input_witnesses: Vec<Witness>,
+    input_parameters: &[InputParameter],
 ) -> BTreeMap<String, Vec<Range<Witness>>> {
-    let mut idx = 0_usize;
-    if input_witnesses.is_empty() {
-        return BTreeMap::new();
-    }
-
-    btree_map(abi_params, |param| {
-        let num_field_elements_needed = param.typ.field_count() as usize;
-        let param_witnesses = &input_witnesses[idx..idx + num_field_elements_needed];
-
+    btree_map(abi_params.iter().zip(input_parameters), |(param, input_parameter)| {
         // It's likely that `param_witnesses` will consist of mostly incrementing witness indices.
         // We then want to collapse these into `Range`s to save space.
-        let param_witnesses = collapse_ranges(param_witnesses);
-        idx += num_field_elements_needed;
+        let param_witnesses = collapse_ranges(&input_parameter.witnesses);
         (param.name.clone(), param_witnesses)
     })
 }
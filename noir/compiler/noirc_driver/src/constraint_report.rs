@@ -0,0 +1,166 @@
+use std::{collections::BTreeMap, path::Path, str::FromStr};
+
+use acvm::acir::circuit::Circuit;
+use fm::{FileId, FileManager};
+use noirc_errors::{debug_info::DebugInfo, Location, Span};
+use noirc_evaluator::{
+    annotated_acir::AnnotatedCircuit,
+    attribution::{
+        attribute_opcodes, folded_stacks, profile_folded_stacks, ConstraintReport, SourceLine,
+        SourceResolver,
+    },
+    brillig_disassembly::BrilligDisassembly,
+    cost_model::CostModel,
+};
+use noirc_frontend::hir::Context;
+
+use crate::CompiledProgram;
+
+/// How the opcodes attributed to the source of a program are written out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConstraintReportFormat {
+    /// A [`ConstraintReport`] of the opcodes attributed to each line and function.
+    #[default]
+    Json,
+    /// The call stacks of the opcodes in the folded stack format read by flamegraph tools.
+    Folded,
+}
+
+impl FromStr for ConstraintReportFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format {
+            "json" => Ok(ConstraintReportFormat::Json),
+            "folded" => Ok(ConstraintReportFormat::Folded),
+            _ => Err(format!(
+                "unknown constraint report format `{format}`, expected `json` or `folded`"
+            )),
+        }
+    }
+}
+
+/// Resolves locations against the files and functions known to a compilation [`Context`].
+struct ContextSources<'a> {
+    file_manager: &'a FileManager,
+    /// The body of each function with one, along with its name.
+    function_bodies: Vec<(FileId, Span, String)>,
+}
+
+impl<'a> ContextSources<'a> {
+    fn new(context: &'a Context) -> Self {
+        let interner = &context.def_interner;
+        let function_bodies = interner
+            .function_ids()
+            .filter(|func_id| interner.function_meta(func_id).has_body)
+            .map(|func_id| {
+                let body = interner.expr_location(interner.function(&func_id).as_expr());
+                (body.file, body.span, interner.function_name(&func_id).to_string())
+            })
+            .collect();
+        ContextSources { file_manager: &context.file_manager, function_bodies }
+    }
+}
+
+impl SourceResolver for ContextSources<'_> {
+    fn line(&self, location: Location) -> Option<SourceLine> {
+        let path = self.file_manager.path(location.file)?;
+        let source = self.file_manager.fetch_file(location.file)?;
+        let preceding = source.get(..location.span.start() as usize)?;
+        let line = preceding.matches('\n').count() as u32 + 1;
+        Some(SourceLine { file: path.display().to_string(), line })
+    }
+
+    fn function(&self, location: Location) -> Option<String> {
+        self.function_bodies
+            .iter()
+            .filter(|(file, body, _)| {
+                *file == location.file
+                    && body.start() <= location.span.start()
+                    && location.span.end() <= body.end()
+            })
+            .min_by_key(|(_, body, _)| body.end() - body.start())
+            .map(|(_, _, name)| name.clone())
+    }
+
+    fn snippet(&self, location: Location) -> Option<String> {
+        let source = self.file_manager.fetch_file(location.file)?;
+        let snippet = source.get(location.span.start() as usize..location.span.end() as usize)?;
+        // Only the first line is kept, as the spans of blocks can cover whole functions.
+        let mut lines = snippet.trim().lines();
+        let first_line = lines.next()?;
+        Some(if lines.next().is_some() {
+            format!("{first_line} ...")
+        } else {
+            first_line.to_string()
+        })
+    }
+}
+
+/// Returns the number of opcodes of `program` attributed to each line and function of the
+/// source files of `context`.
+pub fn constraint_report(context: &Context, program: &CompiledProgram) -> ConstraintReport {
+    attribute_opcodes(&program.circuit, &program.debug, &ContextSources::new(context))
+}
+
+/// Returns the call stacks of the opcodes of `program` in the folded stack format, weighted by
+/// their estimated cost under `cost_model` or by their number if it is not given.
+pub fn constraint_flamegraph(
+    context: &Context,
+    program: &CompiledProgram,
+    cost_model: Option<&dyn CostModel>,
+) -> String {
+    folded_stacks(&program.circuit, &program.debug, &ContextSources::new(context), cost_model)
+}
+
+/// Returns `circuit` listed with the source code which each of its opcodes was generated for,
+/// as located by `debug`.
+pub fn annotated_acir(context: &Context, circuit: &Circuit, debug: &DebugInfo) -> String {
+    AnnotatedCircuit { circuit, debug, sources: &ContextSources::new(context) }.to_string()
+}
+
+/// Returns the Brillig bytecode of `circuit` disassembled, with the source code which each of its
+/// opcodes was generated for, as located by `debug`.
+pub fn brillig_disassembly(context: &Context, circuit: &Circuit, debug: &DebugInfo) -> String {
+    BrilligDisassembly { circuit, debug, sources: &ContextSources::new(context) }.to_string()
+}
+
+/// Returns the call stacks of the blocks of unconstrained code of `program` in the folded stack
+/// format, weighted by `counts`, the number of times each of their profiling counters was reached
+/// while executing it.
+pub fn brillig_profile(
+    context: &Context,
+    program: &CompiledProgram,
+    counts: &BTreeMap<usize, u64>,
+) -> String {
+    let mut counters = program.debug.brillig_counters.clone();
+    for function in &program.acir_functions {
+        counters.extend(function.debug.brillig_counters.clone());
+    }
+    profile_folded_stacks(&counters, counts, &ContextSources::new(context))
+}
+
+/// Writes the opcodes of `program`, which was compiled from `function_name`, attributed to the
+/// source in `format` to `<function_name>.constraints.json` or `<function_name>.folded` in
+/// `directory`.
+pub(crate) fn write_constraint_report(
+    context: &Context,
+    program: &CompiledProgram,
+    function_name: &str,
+    directory: &Path,
+    format: ConstraintReportFormat,
+    cost_model: Option<&dyn CostModel>,
+) -> std::io::Result<()> {
+    std::fs::create_dir_all(directory)?;
+    match format {
+        ConstraintReportFormat::Json => {
+            let report = constraint_report(context, program);
+            let path = directory.join(format!("{function_name}.constraints.json"));
+            std::fs::write(path, serde_json::to_string_pretty(&report)?)
+        }
+        ConstraintReportFormat::Folded => {
+            let folded = constraint_flamegraph(context, program, cost_model);
+            std::fs::write(directory.join(format!("{function_name}.folded")), folded)
+        }
+    }
+}
@@ -4,32 +4,48 @@
 #![warn(clippy::semicolon_if_nothing_returned)]
 
 use acvm::acir::circuit::ExpressionWidth;
-use clap::Args;
+use clap::{builder::PossibleValuesParser, Args};
 use fm::{FileId, FileManager};
 use iter_extended::vecmap;
 use noirc_abi::{AbiParameter, AbiType, ContractEvent};
 use noirc_errors::{CustomDiagnostic, FileDiagnostic};
+use noirc_evaluator::cost_model::{self, CostModel, DefaultCostModel};
 use noirc_evaluator::create_circuit;
-use noirc_evaluator::errors::RuntimeError;
+use noirc_evaluator::errors::{InternalError, RuntimeError};
+use noirc_evaluator::public_input_layout::PublicInputLayout;
+use noirc_evaluator::slice::SliceTarget;
+use noirc_evaluator::ssa::{
+    custom_passes::CustomSsaPasses, CircuitOptions, CompiledCircuit, SsaDump, SSA_PASS_NAMES,
+};
 use noirc_frontend::graph::{CrateId, CrateName};
 use noirc_frontend::hir::def_map::{Contract, CrateDefMap};
 use noirc_frontend::hir::Context;
 use noirc_frontend::macros_api::MacroProcessor;
 use noirc_frontend::monomorphization::monomorphize;
 use noirc_frontend::node_interner::FuncId;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tracing::info;
 
 mod abi_gen;
+mod constraint_report;
 mod contract;
 mod debug;
 mod program;
 mod stdlib;
 
+use constraint_report::write_constraint_report;
 use debug::filter_relevant_files;
 
+pub use constraint_report::{
+    annotated_acir, brillig_disassembly, brillig_profile, constraint_flamegraph, constraint_report,
+    ConstraintReportFormat,
+};
 pub use contract::{CompiledContract, ContractFunction, ContractFunctionType};
 pub use debug::DebugFile;
+pub use noirc_evaluator::acir_json::AcirJson;
+pub use noirc_evaluator::plonkish::{circuit_to_plonkish, PlonkishError};
+pub use noirc_evaluator::smt_lib::circuit_to_smt_lib;
 pub use program::CompiledProgram;
 
 const STD_CRATE_NAME: &str = "std";
@@ -57,6 +73,26 @@ pub struct CompileOptions {
     #[arg(long, hide = true)]
     pub show_ssa: bool,
 
+    /// Write the SSA of each function to a file after each of the given passes
+    #[arg(long, value_delimiter = ',', value_parser = PossibleValuesParser::new(SSA_PASS_NAMES))]
+    pub dump_ssa_after: Vec<String>,
+
+    /// Directory to write the SSA selected by `--dump-ssa-after` to [default: target/ssa]
+    #[arg(long)]
+    pub dump_ssa_dir: Option<PathBuf>,
+
+    /// Display the number of iterations, loop-carried values and unrolled size of each loop
+    #[arg(long)]
+    pub show_loop_report: bool,
+
+    /// Report range constraints which can never be satisfied as warnings rather than errors
+    #[arg(long)]
+    pub warn_failing_range_constraints: bool,
+
+    /// Warn about constraints which are not connected to any input or return value of the program
+    #[arg(long)]
+    pub warn_disconnected_components: bool,
+
     #[arg(long, hide = true)]
     pub show_brillig: bool,
 
@@ -64,6 +100,15 @@ pub struct CompileOptions {
     #[arg(long)]
     pub print_acir: bool,
 
+    /// Display the ACIR for compiled circuit with the source code and assert message of each opcode
+    #[arg(long)]
+    pub print_annotated_acir: bool,
+
+    /// Display the disassembled Brillig bytecode of the compiled circuit with the source code and
+    /// assert message of each opcode
+    #[arg(long)]
+    pub print_brillig: bool,
+
     /// Treat all warnings as errors
     #[arg(long, conflicts_with = "silence_warnings")]
     pub deny_warnings: bool,
@@ -83,6 +128,171 @@ pub struct CompileOptions {
     /// Outputs the monomorphized IR to stdout for debugging
     #[arg(long, hide = true)]
     pub show_monomorphized: bool,
+
+    /// Path to a JSON file describing the costs of the backend's opcodes, used to choose how
+    /// operations are lowered into ACIR, or `barretenberg` to use its built-in costs and also
+    /// emit hints about the layout of its gates
+    #[arg(long, value_parser = cost_model::parse_cost_model)]
+    pub cost_model: Option<Arc<dyn CostModel>>,
+
+    /// Warn about expressions which need more than this many intermediate witnesses
+    /// to keep the degree of their multiplications at most 2
+    #[arg(long)]
+    pub max_reduction_witnesses: Option<usize>,
+
+    /// Abort a call to an unconstrained function once it has executed this many Brillig opcodes,
+    /// or fewer if a smaller bound can be derived from its loops
+    #[arg(long)]
+    pub max_brillig_steps: Option<usize>,
+
+    /// Unroll the loops of unconstrained functions which have constant bounds and unroll into at
+    /// most this many SSA instructions, trading a larger bytecode for not running the loop counter
+    #[arg(long)]
+    pub brillig_unroll_threshold: Option<usize>,
+
+    /// Fail calls to unconstrained functions which nest more than this many calls, reporting the
+    /// call stack rather than letting runaway recursion exhaust the memory of the VM
+    #[arg(long)]
+    pub brillig_max_call_depth: Option<usize>,
+
+    /// Fail calls to unconstrained functions which use more than this many memory slots, reporting
+    /// the call stack rather than letting the VM run out of memory
+    #[arg(long)]
+    pub brillig_max_memory: Option<usize>,
+
+    /// Count the executions of each block of unconstrained code through the `profile_block`
+    /// oracle, so that the hot paths of unconstrained functions can be profiled
+    #[arg(long)]
+    pub profile_brillig: bool,
+
+    /// Reuse the results of earlier calls with the same inputs to this oracle within an execution
+    /// of unconstrained code, instead of calling the foreign call resolver again. Only meant for
+    /// oracles whose results only depend on their inputs
+    #[arg(long = "memoize-oracle")]
+    pub memoized_oracles: Vec<String>,
+
+    /// Make the unsigned integer arithmetic of unconstrained code fail where it overflows, as it
+    /// does in constrained code, instead of wrapping around
+    #[arg(long)]
+    pub check_brillig_overflows: bool,
+
+    /// Path to a JSON file giving the order of the circuit's public inputs in terms of the ABI,
+    /// optionally packing several small values into a single public input
+    #[arg(long, value_parser = parse_public_input_layout)]
+    pub public_input_layout: Option<PublicInputLayout>,
+
+    /// Return repeated values of a `distinct` return value of `main` in a single witness, so that
+    /// backends only expose each of them once as a public input. The ABI still maps each element
+    /// of the return value to the witness holding it
+    #[arg(long)]
+    pub dedup_return_witnesses: bool,
+
+    /// Only keep the constraints needed to check the assertions with this message, or to compute
+    /// the return value if given `return`. The sliced circuit is weaker than the program and is
+    /// only meant for quickly proving the selected parts while working on them
+    #[arg(long = "slice")]
+    pub slice_targets: Vec<SliceTarget>,
+
+    /// Write the number of ACIR opcodes attributed to each line and function of Noir source to
+    /// `<function>.constraints.json` in this directory, for each compiled program or function
+    #[arg(long)]
+    pub constraint_report_dir: Option<PathBuf>,
+
+    /// Format of the reports written to `--constraint-report-dir`: `json`, or `folded` to write
+    /// the call stacks of the opcodes to `<function>.folded` for flamegraph tools, weighted by
+    /// their estimated cost if a `--cost-model` is given
+    #[arg(long, default_value = "json")]
+    pub constraint_report_format: ConstraintReportFormat,
+
+    /// Also write the constraints of each compiled program as SMT-LIB to `<package>.smt2`, so that
+    /// they can be checked with formal verification tools
+    #[arg(long)]
+    pub emit_smt_lib: bool,
+
+    /// Also write the opcodes, witnesses, source locations, assert messages and warnings of each
+    /// compiled program as JSON to `<package>.acir.json`, for tools which read circuits without
+    /// depending on the compiler
+    #[arg(long)]
+    pub emit_acir_json: bool,
+
+    /// Also write the constraints of each compiled program as a PLONKish table of selector,
+    /// advice and instance columns with copy constraints to `<package>.plonkish.json`, for proving
+    /// with halo2 backends. Requires an expression width of 3
+    #[arg(long)]
+    pub emit_plonkish: bool,
+
+    /// Compile each program or function twice and fail unless both compilations produce the same
+    /// ACIR, ignoring any cached artifact, so that builds can be reproduced for audits
+    #[arg(long)]
+    pub deterministic: bool,
+
+    /// SSA passes registered by the application embedding the compiler, run around the
+    /// built-in passes they are placed at
+    #[arg(skip)]
+    pub ssa_passes: CustomSsaPasses,
+}
+
+impl CompileOptions {
+    /// The cost model of the targeted backend, or the [`DefaultCostModel`] if none was given.
+    pub fn cost_model(&self) -> Arc<dyn CostModel> {
+        self.cost_model.clone().unwrap_or_else(|| Arc::new(DefaultCostModel))
+    }
+
+    /// The options which change the compiled circuit, rather than what is printed or written
+    /// while compiling it.
+    fn codegen_options(&self) -> CodegenOptions<'_> {
+        CodegenOptions {
+            expression_width: self.expression_width.unwrap_or(ExpressionWidth::Unbounded),
+            cost_model: HashedCostModel(self.cost_model()),
+            max_reduction_witnesses: self.max_reduction_witnesses,
+            max_brillig_steps: self.max_brillig_steps,
+            public_input_layout: self.public_input_layout.as_ref(),
+            brillig_unroll_threshold: self.brillig_unroll_threshold,
+            brillig_max_call_depth: self.brillig_max_call_depth,
+            brillig_max_memory: self.brillig_max_memory,
+            profile_brillig: self.profile_brillig,
+            memoized_oracles: &self.memoized_oracles,
+            check_brillig_overflows: self.check_brillig_overflows,
+            warn_failing_range_constraints: self.warn_failing_range_constraints,
+            slice_targets: &self.slice_targets,
+            warn_disconnected_components: self.warn_disconnected_components,
+            dedup_return_witnesses: self.dedup_return_witnesses,
+        }
+    }
+}
+
+/// The [`CompileOptions`] which change the compiled circuit. They are hashed together with the
+/// program, so that a cached artifact is only reused if it was compiled with the same options.
+#[derive(Hash)]
+struct CodegenOptions<'a> {
+    expression_width: ExpressionWidth,
+    cost_model: HashedCostModel,
+    max_reduction_witnesses: Option<usize>,
+    max_brillig_steps: Option<usize>,
+    public_input_layout: Option<&'a PublicInputLayout>,
+    brillig_unroll_threshold: Option<usize>,
+    brillig_max_call_depth: Option<usize>,
+    brillig_max_memory: Option<usize>,
+    profile_brillig: bool,
+    memoized_oracles: &'a [String],
+    check_brillig_overflows: bool,
+    warn_failing_range_constraints: bool,
+    slice_targets: &'a [SliceTarget],
+    warn_disconnected_components: bool,
+    dedup_return_witnesses: bool,
+}
+
+/// A cost model hashed by its [`CostModel::cache_key`].
+struct HashedCostModel(Arc<dyn CostModel>);
+
+impl std::hash::Hash for HashedCostModel {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.cache_key().hash(state);
+    }
+}
+
+fn parse_public_input_layout(input: &str) -> Result<PublicInputLayout, std::io::Error> {
+    PublicInputLayout::from_file(Path::new(input))
 }
 
 fn parse_expression_width(input: &str) -> Result<ExpressionWidth, std::io::Error> {
@@ -255,6 +465,20 @@ pub fn compile_main(
     if options.print_acir {
         println!("Compiled ACIR for main (unoptimized):");
         println!("{}", compiled_program.circuit);
+        for function in &compiled_program.acir_functions {
+            println!("Compiled ACIR for {} (unoptimized):", function.name);
+            println!("{}", function.circuit);
+        }
+    }
+    if options.print_annotated_acir {
+        println!("Annotated ACIR for main:");
+        println!("{}", annotated_acir(context, &compiled_program.circuit, &compiled_program.debug));
+    }
+    if options.print_brillig {
+        println!("Brillig for main:");
+        let listing =
+            brillig_disassembly(context, &compiled_program.circuit, &compiled_program.debug);
+        println!("{listing}");
     }
 
     Ok((compiled_program, warnings))
@@ -309,6 +533,28 @@ pub fn compile_contract(
                 println!("{}", contract_function.bytecode);
             }
         }
+        if options.print_annotated_acir {
+            for contract_function in &compiled_contract.functions {
+                println!(
+                    "Annotated ACIR for {}::{}:",
+                    compiled_contract.name, contract_function.name
+                );
+                let listing =
+                    annotated_acir(context, &contract_function.bytecode, &contract_function.debug);
+                println!("{listing}");
+            }
+        }
+        if options.print_brillig {
+            for contract_function in &compiled_contract.functions {
+                println!("Brillig for {}::{}:", compiled_contract.name, contract_function.name);
+                let listing = brillig_disassembly(
+                    context,
+                    &contract_function.bytecode,
+                    &contract_function.debug,
+                );
+                println!("{listing}");
+            }
+        }
         // errors here is either empty or contains only warnings
         Ok((compiled_contract, errors))
     }
@@ -354,6 +600,14 @@ fn compile_contract_inner(
             }
         };
         warnings.extend(function.warnings);
+        if !function.acir_functions.is_empty() {
+            let err = CustomDiagnostic::from_message(&format!(
+                "contract function `{name}` cannot call `#[fold]` or `#[no_inline]` functions"
+            ))
+            .in_file(FileId::default());
+            errors.push(err);
+            continue;
+        }
         let modifiers = context.def_interner.function_modifiers(&function_id);
         let func_type = modifiers
             .contract_function_type
@@ -409,7 +663,8 @@ pub fn compile_no_check(
 ) -> Result<CompiledProgram, RuntimeError> {
     let program = monomorphize(main_function, &context.def_interner);
 
-    let hash = fxhash::hash64(&program);
+    let codegen_options = options.codegen_options();
+    let hash = fxhash::hash64(&(&program, &codegen_options));
     let hashes_match = cached_program.as_ref().map_or(false, |program| program.hash == hash);
     if options.show_monomorphized {
         println!("{program}");
@@ -417,22 +672,117 @@ pub fn compile_no_check(
 
     // If user has specified that they want to see intermediate steps printed then we should
     // force compilation even if the program hasn't changed.
-    let force_compile =
-        force_compile || options.print_acir || options.show_brillig || options.show_ssa;
+    let force_compile = force_compile
+        || options.print_acir
+        || options.print_annotated_acir
+        || options.print_brillig
+        || options.show_brillig
+        || options.show_ssa
+        || options.show_loop_report
+        || !options.dump_ssa_after.is_empty()
+        || !options.ssa_passes.is_empty()
+        || options.constraint_report_dir.is_some()
+        || options.deterministic;
 
     if !force_compile && hashes_match {
         info!("Program matches existing artifact, returning early");
         return Ok(cached_program.expect("cache must exist for hashes to match"));
     }
     let visibility = program.return_visibility;
-    let (circuit, debug, input_witnesses, return_witnesses, warnings) =
-        create_circuit(program, options.show_ssa, options.show_brillig)?;
+    let CodegenOptions {
+        expression_width,
+        cost_model: HashedCostModel(cost_model),
+        max_reduction_witnesses,
+        max_brillig_steps,
+        public_input_layout,
+        brillig_unroll_threshold,
+        brillig_max_call_depth,
+        brillig_max_memory,
+        profile_brillig,
+        memoized_oracles,
+        check_brillig_overflows,
+        warn_failing_range_constraints,
+        slice_targets,
+        warn_disconnected_components,
+        dedup_return_witnesses,
+    } = codegen_options;
+    let ssa_dump = (!options.dump_ssa_after.is_empty()).then(|| SsaDump {
+        passes: options.dump_ssa_after.clone(),
+        directory: options.dump_ssa_dir.clone().unwrap_or_else(|| PathBuf::from("target/ssa")),
+    });
+    let circuit_options = CircuitOptions {
+        enable_ssa_logging: options.show_ssa,
+        enable_brillig_logging: options.show_brillig,
+        cost_model,
+        expression_width,
+        max_reduction_witnesses,
+        max_brillig_steps,
+        public_input_layout,
+        ssa_dump: ssa_dump.as_ref(),
+        collect_loop_reports: options.show_loop_report,
+        brillig_unroll_threshold,
+        brillig_max_call_depth,
+        brillig_max_memory,
+        profile_brillig,
+        memoized_oracles,
+        check_brillig_overflows,
+        warn_failing_range_constraints,
+        slice_targets,
+        warn_disconnected_components,
+        dedup_return_witnesses,
+        custom_passes: &options.ssa_passes,
+    };
+    let reproduction = options.deterministic.then(|| program.clone());
+    let CompiledCircuit {
+        circuit,
+        debug,
+        input_parameters,
+        return_witnesses,
+        warnings,
+        pass_watermarks,
+        brillig_sizes,
+        public_input_elements,
+        return_groups,
+        acir_functions,
+        loop_reports,
+    } = create_circuit(program, &circuit_options)?;
+    if options.show_loop_report {
+        println!("Loops before unrolling:");
+        for report in loop_reports {
+            println!("{report}");
+        }
+    }
+    if let Some(program) = reproduction {
+        // Only the first compilation prints or dumps the intermediate steps.
+        let reproduction_options = CircuitOptions {
+            enable_ssa_logging: false,
+            enable_brillig_logging: false,
+            ssa_dump: None,
+            collect_loop_reports: false,
+            ..circuit_options
+        };
+        let reproduced = create_circuit(program, &reproduction_options)?;
+        let function_circuits = acir_functions.iter().map(|function| &function.circuit);
+        let reproduced_functions = reproduced.acir_functions.iter();
+        if reproduced.circuit != circuit
+            || reproduced.input_parameters != input_parameters
+            || !reproduced_functions.map(|function| &function.circuit).eq(function_circuits)
+        {
+            let message = "Compiling the program twice produced different ACIR".to_string();
+            return Err(InternalError::General { message, call_stack: Default::default() }.into());
+        }
+    }
 
     let abi =
-        abi_gen::gen_abi(context, &main_function, input_witnesses, return_witnesses, visibility);
-    let file_map = filter_relevant_files(&[debug.clone()], &context.file_manager);
-
-    Ok(CompiledProgram {
+        abi_gen::gen_abi(context, &main_function, input_parameters, return_witnesses, visibility);
+    let public_inputs = abi_gen::gen_public_input_table(&abi, &circuit, &public_input_elements);
+    let debug_infos: Vec<_> = std::iter::once(&debug)
+        .chain(acir_functions.iter().map(|function| &function.debug))
+        .cloned()
+        .collect();
+    let file_map = filter_relevant_files(&debug_infos, &context.file_manager);
+
+    let program = CompiledProgram {
         hash,
         circuit,
         debug,
@@ -440,5 +790,28 @@ pub fn compile_no_check(
         file_map,
         noir_version: NOIR_ARTIFACT_VERSION_STRING.to_string(),
         warnings,
-    })
+        pass_watermarks,
+        brillig_sizes,
+        public_inputs,
+        return_groups,
+        gate_hints: Vec::new(),
+        acir_functions,
+    };
+    if let Some(directory) = &options.constraint_report_dir {
+        let function_name = context.function_name(&main_function);
+        write_constraint_report(
+            context,
+            &program,
+            function_name,
+            directory,
+            options.constraint_report_format,
+            options.cost_model.as_deref(),
+        )
+        .map_err(|error| RuntimeError::WriteOutput {
+            path: directory.display().to_string(),
+            message: error.to_string(),
+            call_stack: Default::default(),
+        })?;
+    }
+    Ok(program)
 }
@@ -3,8 +3,14 @@ use std::collections::BTreeMap;
 use acvm::acir::circuit::Circuit;
 use fm::FileId;
 
+use iter_extended::vecmap;
+use noirc_abi::PublicInputTable;
 use noirc_errors::debug_info::DebugInfo;
-use noirc_evaluator::errors::SsaReport;
+use noirc_evaluator::{
+    errors::SsaReport,
+    gate_hints::GateHint,
+    ssa::{AcirFunction, BrilligFunctionSize, PassWatermark, ReturnGroup},
+};
 use serde::{Deserialize, Serialize};
 
 use super::debug::DebugFile;
@@ -27,4 +33,44 @@ pub struct CompiledProgram {
     pub debug: DebugInfo,
     pub file_map: BTreeMap<FileId, DebugFile>,
     pub warnings: Vec<SsaReport>,
+    /// Size of the program after each compilation pass.
+    #[serde(default)]
+    pub pass_watermarks: Vec<PassWatermark>,
+    /// Size of the bytecode of each unconstrained function.
+    #[serde(default)]
+    pub brillig_sizes: Vec<BrilligFunctionSize>,
+    /// The values of the ABI making up each public input.
+    #[serde(default)]
+    pub public_inputs: PublicInputTable,
+    /// The witnesses of each field of the return value of `main`, if it is a struct or tuple.
+    #[serde(default)]
+    pub return_groups: Vec<ReturnGroup>,
+    /// Hints about the layout of the gates of the circuit, once it has been transformed for the
+    /// backend.
+    #[serde(default)]
+    pub gate_hints: Vec<GateHint>,
+    /// The circuits of the `#[fold]` or `#[no_inline]` functions which `Call` opcodes with ids from
+    /// `1` refer to.
+    #[serde(default)]
+    pub acir_functions: Vec<AcirFunction>,
+}
+
+impl CompiledProgram {
+    /// Returns the SHA-256 hash of the circuit and ABI of the program, in hex, along with the
+    /// circuits of its `#[fold]` or `#[no_inline]` functions if it has any.
+    ///
+    /// The debug information and source files are left out, as they hold the paths which the
+    /// program was compiled from, so that the hash only depends on the source code and compiler
+    /// version. Builds can then be checked to be reproducible, and verifier keys pinned to it.
+    pub fn artifact_hash(&self) -> String {
+        let function_circuits = vecmap(&self.acir_functions, |function| &function.circuit);
+        let bytes = if function_circuits.is_empty() {
+            serde_json::to_vec(&(&self.circuit, &self.abi))
+        } else {
+            serde_json::to_vec(&(&self.circuit, &self.abi, function_circuits))
+        }
+        .expect("circuits and ABIs can always be serialized");
+        let digest = acvm::blackbox_solver::sha256(&bytes).expect("sha256 cannot fail");
+        digest.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
 }
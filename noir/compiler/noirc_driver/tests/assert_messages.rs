@@ -0,0 +1,36 @@
+use std::path::Path;
+
+use acvm::acir::native_types::Witness;
+use noirc_driver::{compile_main, file_manager_with_stdlib, prepare_crate, CompileOptions};
+use noirc_frontend::hir::{def_map::parse_file, Context};
+
+#[test]
+fn format_strings_in_assert_messages_refer_to_witnesses() {
+    let source = r#"
+fn main(x: Field, y: pub Field) {
+    assert(x == y, f"mismatch: {x} vs {y}");
+}"#;
+    let root = Path::new("");
+    let file_name = Path::new("main.nr");
+    let mut file_manager = file_manager_with_stdlib(root);
+    file_manager.add_file_with_source(file_name, source.to_owned()).expect(
+        "Adding source buffer to file manager should never fail when file manager is empty",
+    );
+    let parsed_files = file_manager
+        .as_file_map()
+        .all_file_ids()
+        .map(|&file_id| (file_id, parse_file(&file_manager, file_id)))
+        .collect();
+
+    let mut context = Context::new(file_manager, parsed_files);
+    let root_crate_id = prepare_crate(&mut context, file_name);
+
+    let (program, _) = compile_main(&mut context, root_crate_id, &CompileOptions::default(), None)
+        .expect("program should compile");
+
+    let [(_, message)] = program.circuit.assert_messages.as_slice() else {
+        panic!("expected a single assertion message");
+    };
+    assert_eq!(message.witnesses().collect::<Vec<_>>(), vec![Witness(0), Witness(1)]);
+    assert_eq!(message.to_string(), "mismatch: _0 vs _1");
+}
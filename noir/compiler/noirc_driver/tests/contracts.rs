@@ -40,3 +40,45 @@ contract Bar {}";
 
     Ok(())
 }
+
+#[test]
+fn reject_contract_functions_calling_no_inline_functions() {
+    let source = "
+contract Foo {
+    fn double(x: Field) -> pub Field {
+        crate::add(x, x)
+    }
+}
+
+#[no_inline]
+fn add(x: Field, y: Field) -> Field {
+    x + y
+}";
+
+    let root = Path::new("");
+    let file_name = Path::new("main.nr");
+    let mut file_manager = file_manager_with_stdlib(root);
+    file_manager.add_file_with_source(file_name, source.to_owned()).expect(
+        "Adding source buffer to file manager should never fail when file manager is empty",
+    );
+    let parsed_files = file_manager
+        .as_file_map()
+        .all_file_ids()
+        .map(|&file_id| (file_id, parse_file(&file_manager, file_id)))
+        .collect();
+
+    let mut context = Context::new(file_manager, parsed_files);
+    let root_crate_id = prepare_crate(&mut context, file_name);
+
+    let errors =
+        noirc_driver::compile_contract(&mut context, root_crate_id, &CompileOptions::default())
+            .unwrap_err();
+
+    assert_eq!(
+        errors,
+        vec![CustomDiagnostic::from_message(
+            "contract function `double` cannot call `#[fold]` or `#[no_inline]` functions"
+        )
+        .in_file(FileId::default())],
+    );
+}
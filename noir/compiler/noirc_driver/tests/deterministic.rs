@@ -0,0 +1,44 @@
+use std::path::Path;
+
+use noirc_driver::{compile_main, file_manager_with_stdlib, prepare_crate, CompileOptions};
+use noirc_frontend::hir::{def_map::parse_file, Context};
+
+fn compile_to_hash(source: &str) -> String {
+    let root = Path::new("");
+    let file_name = Path::new("main.nr");
+    let mut file_manager = file_manager_with_stdlib(root);
+    file_manager.add_file_with_source(file_name, source.to_owned()).expect(
+        "Adding source buffer to file manager should never fail when file manager is empty",
+    );
+    let parsed_files = file_manager
+        .as_file_map()
+        .all_file_ids()
+        .map(|&file_id| (file_id, parse_file(&file_manager, file_id)))
+        .collect();
+
+    let mut context = Context::new(file_manager, parsed_files);
+    let root_crate_id = prepare_crate(&mut context, file_name);
+
+    let options = CompileOptions { deterministic: true, ..CompileOptions::default() };
+    let (program, _) =
+        compile_main(&mut context, root_crate_id, &options, None).expect("program should compile");
+    program.artifact_hash()
+}
+
+#[test]
+fn deterministic_compilations_have_the_same_artifact_hash() {
+    let source = "
+fn main(x: Field, y: pub [Field; 2], z: u8) -> pub Field {
+    let mut sum = 0;
+    for i in 0..2 {
+        if z > i {
+            sum += y[i] * x;
+        }
+    }
+    sum
+}";
+
+    let hash = compile_to_hash(source);
+    assert_eq!(hash.len(), 64);
+    assert_eq!(compile_to_hash(source), hash);
+}
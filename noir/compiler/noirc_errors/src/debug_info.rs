@@ -1,4 +1,5 @@
 use acvm::acir::circuit::OpcodeLocation;
+use acvm::acir::native_types::Witness;
 use acvm::compiler::AcirTransformationMap;
 
 use base64::Engine;
@@ -14,6 +15,7 @@ use std::collections::HashMap;
 use std::io::Read;
 use std::io::Write;
 use std::mem;
+use std::ops::Range;
 
 use crate::Location;
 use serde::{
@@ -24,10 +26,196 @@ use serde::{
 #[derive(Default, Debug, Clone, Deserialize, Serialize)]
 pub struct DebugInfo {
     /// Map opcode index of an ACIR circuit into the source code location
+    ///
+    /// The call stacks of inlined functions share most of their frames, so they are serialized
+    /// as [`CompressedLocations`]. Artifacts which map each opcode to its full call stack are
+    /// still accepted.
+    #[serde(serialize_with = "serialize_locations", deserialize_with = "deserialize_locations")]
+    pub locations: BTreeMap<OpcodeLocation, Vec<Location>>,
+    /// Map opcode index of a Brillig opcode in an ACIR circuit into the maximum number of
+    /// Brillig opcodes which it may execute before it is considered to be stuck.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub brillig_step_bounds: BTreeMap<usize, usize>,
+    /// Map each witness which holds the value of a variable of the source code, or of an element
+    /// or field of one, into the name of that variable, e.g. `x`, `xs[2]` or `point.1`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub witness_names: BTreeMap<Witness, String>,
+    /// Map opcode index of a Brillig opcode in an ACIR circuit into the variables of the source
+    /// code which its registers hold, and the Brillig opcodes during which they hold them.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub brillig_variables: BTreeMap<usize, Vec<BrilligVariableInfo>>,
+    /// Map the id of each counter of profiled Brillig code into the call stack of the block of
+    /// code whose executions it counts.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub brillig_counters: BTreeMap<usize, Vec<Location>>,
+}
+
+/// A variable of the source code which is held in the registers of a Brillig program while the
+/// opcodes at the positions in `opcodes` are executed.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct BrilligVariableInfo {
+    pub name: String,
+    pub opcodes: Range<usize>,
+    pub value: BrilligValueInfo,
+}
+
+/// Where the value of a [`BrilligVariableInfo`] is held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum BrilligValueInfo {
+    /// The value is held in a register.
+    Register(usize),
+    /// The value is an array of `size` elements, starting at the memory address held in the
+    /// `pointer` register.
+    Array { pointer: usize, size: usize },
+    /// The value is a vector, whose elements start at the memory address held in the `pointer`
+    /// register, and whose length is held in the `size` register.
+    Vector { pointer: usize, size: usize },
+}
+
+/// A call stack interned in a [`CallStackTree`], which it must be resolved by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+pub struct CallStackId(u32);
+
+impl CallStackId {
+    /// The empty call stack, which every tree holds.
+    pub fn root() -> Self {
+        CallStackId(0)
+    }
+}
+
+/// Interns call stacks as the paths from the root of a tree of locations, with the outermost
+/// location first, so that call stacks which share their outer frames only store them once.
+///
+/// The tree is serialized as the list of its frames.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[serde(from = "Vec<(CallStackId, Location)>")]
+pub struct CallStackTree {
+    /// The parent and innermost location of each call stack other than the root, which is
+    /// `frames[id - 1]`.
+    frames: Vec<(CallStackId, Location)>,
+    children: HashMap<(CallStackId, Location), CallStackId>,
+}
+
+impl From<Vec<(CallStackId, Location)>> for CallStackTree {
+    fn from(frames: Vec<(CallStackId, Location)>) -> Self {
+        let children = frames
+            .iter()
+            .enumerate()
+            .map(|(index, frame)| (*frame, CallStackId(index as u32 + 1)))
+            .collect();
+        CallStackTree { frames, children }
+    }
+}
+
+impl Serialize for CallStackTree {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.frames.serialize(s)
+    }
+}
+
+impl CallStackTree {
+    pub fn intern(&mut self, call_stack: &[Location]) -> CallStackId {
+        call_stack.iter().fold(CallStackId::root(), |parent, location| {
+            *self.children.entry((parent, *location)).or_insert_with(|| {
+                self.frames.push((parent, *location));
+                CallStackId(self.frames.len() as u32)
+            })
+        })
+    }
+
+    /// Returns the call stack which `id` was interned from, or `None` if it is not in the tree.
+    pub fn resolve(&self, mut id: CallStackId) -> Option<Vec<Location>> {
+        let mut call_stack = Vec::new();
+        while id != CallStackId::root() {
+            let (parent, location) = *self.frames.get(id.0 as usize - 1)?;
+            // Parents are always interned before their children, which rules out cycles.
+            if parent >= id {
+                return None;
+            }
+            call_stack.push(location);
+            id = parent;
+        }
+        call_stack.reverse();
+        Some(call_stack)
+    }
+
+    /// Number of distinct frames held by the tree.
+    pub fn num_frames(&self) -> usize {
+        self.frames.len()
+    }
+}
+
+/// The source code locations of the opcodes of a circuit, with their call stacks interned in a
+/// [`CallStackTree`].
+#[serde_as]
+#[derive(Default, Debug, Clone, Deserialize, Serialize)]
+pub struct CompressedLocations {
+    pub call_stacks: CallStackTree,
     /// Serde does not support mapping keys being enums for json, so we indicate
     /// that they should be serialized to/from strings.
     #[serde_as(as = "BTreeMap<DisplayFromStr, _>")]
-    pub locations: BTreeMap<OpcodeLocation, Vec<Location>>,
+    pub opcodes: BTreeMap<OpcodeLocation, CallStackId>,
+}
+
+impl CompressedLocations {
+    pub fn new(locations: &BTreeMap<OpcodeLocation, Vec<Location>>) -> Self {
+        let mut call_stacks = CallStackTree::default();
+        let opcodes = locations
+            .iter()
+            .map(|(opcode_location, call_stack)| (*opcode_location, call_stacks.intern(call_stack)))
+            .collect();
+        CompressedLocations { call_stacks, opcodes }
+    }
+
+    /// Returns the full call stack of the opcode at `opcode_location`.
+    pub fn call_stack(&self, opcode_location: &OpcodeLocation) -> Option<Vec<Location>> {
+        self.call_stacks.resolve(*self.opcodes.get(opcode_location)?)
+    }
+
+    /// Returns the full call stack of every opcode, or `None` if one refers to a call stack
+    /// which is not in the tree.
+    pub fn decompress(&self) -> Option<BTreeMap<OpcodeLocation, Vec<Location>>> {
+        self.opcodes
+            .iter()
+            .map(|(opcode_location, id)| Some((*opcode_location, self.call_stacks.resolve(*id)?)))
+            .collect()
+    }
+}
+
+/// The two forms in which [`DebugInfo::locations`] can be serialized.
+#[serde_as]
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SerializedLocations {
+    Compressed(CompressedLocations),
+    Full(#[serde_as(as = "BTreeMap<DisplayFromStr, _>")] BTreeMap<OpcodeLocation, Vec<Location>>),
+}
+
+fn serialize_locations<S>(
+    locations: &BTreeMap<OpcodeLocation, Vec<Location>>,
+    s: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    CompressedLocations::new(locations).serialize(s)
+}
+
+fn deserialize_locations<'de, D>(
+    deserializer: D,
+) -> Result<BTreeMap<OpcodeLocation, Vec<Location>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match SerializedLocations::deserialize(deserializer)? {
+        SerializedLocations::Compressed(locations) => locations
+            .decompress()
+            .ok_or_else(|| D::Error::custom("opcode location refers to a missing call stack")),
+        SerializedLocations::Full(locations) => Ok(locations),
+    }
 }
 
 /// Holds OpCodes Counts for Acir and Brillig Opcodes
@@ -40,10 +228,16 @@ pub struct OpCodesCount {
 
 impl DebugInfo {
     pub fn new(locations: BTreeMap<OpcodeLocation, Vec<Location>>) -> Self {
-        DebugInfo { locations }
+        DebugInfo {
+            locations,
+            brillig_step_bounds: BTreeMap::new(),
+            witness_names: BTreeMap::new(),
+            brillig_variables: BTreeMap::new(),
+            brillig_counters: BTreeMap::new(),
+        }
     }
 
-    /// Updates the locations map when the [`Circuit`][acvm::acir::circuit::Circuit] is modified.
+    /// Updates the locations, Brillig step bound and Brillig variable maps when the [`Circuit`][acvm::acir::circuit::Circuit] is modified.
     ///
     /// The [`OpcodeLocation`]s are generated with the ACIR, but passing the ACIR through a transformation step
     /// renders the old `OpcodeLocation`s invalid. The AcirTransformationMap is able to map the old `OpcodeLocation` to the new ones.
@@ -57,12 +251,40 @@ impl DebugInfo {
                 self.locations.insert(new_opcode_location, source_locations.clone());
             });
         }
+
+        let old_step_bounds = mem::take(&mut self.brillig_step_bounds);
+        for (old_acir_index, step_bound) in old_step_bounds {
+            update_map.new_locations(OpcodeLocation::Acir(old_acir_index)).for_each(
+                |new_opcode_location| {
+                    if let OpcodeLocation::Acir(new_acir_index) = new_opcode_location {
+                        self.brillig_step_bounds.insert(new_acir_index, step_bound);
+                    }
+                },
+            );
+        }
+
+        let old_variables = mem::take(&mut self.brillig_variables);
+        for (old_acir_index, variables) in old_variables {
+            update_map.new_locations(OpcodeLocation::Acir(old_acir_index)).for_each(
+                |new_opcode_location| {
+                    if let OpcodeLocation::Acir(new_acir_index) = new_opcode_location {
+                        self.brillig_variables.insert(new_acir_index, variables.clone());
+                    }
+                },
+            );
+        }
     }
 
     pub fn opcode_location(&self, loc: &OpcodeLocation) -> Option<Vec<Location>> {
         self.locations.get(loc).cloned()
     }
 
+    /// Returns the locations of the opcodes with their call stacks interned, in the form in
+    /// which they are serialized.
+    pub fn compressed_locations(&self) -> CompressedLocations {
+        CompressedLocations::new(&self.locations)
+    }
+
     pub fn count_span_opcodes(&self) -> HashMap<Location, OpCodesCount> {
         let mut accumulator: HashMap<Location, Vec<&OpcodeLocation>> = HashMap::new();
 
@@ -133,3 +355,49 @@ impl DebugInfo {
         serde_json::from_str(&json_str).map_err(D::Error::custom)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use acvm::acir::circuit::OpcodeLocation;
+
+    use super::{CallStackId, CompressedLocations, DebugInfo};
+    use crate::{Location, Span};
+
+    fn at(position: u32) -> Location {
+        Location::new(Span::single_char(position), Default::default())
+    }
+
+    #[test]
+    fn call_stacks_share_their_outer_frames() {
+        let locations = BTreeMap::from([
+            (OpcodeLocation::Acir(0), vec![at(1), at(2), at(3)]),
+            (OpcodeLocation::Acir(1), vec![at(1), at(2), at(4)]),
+            (OpcodeLocation::Brillig { acir_index: 2, brillig_index: 5 }, vec![at(1)]),
+        ]);
+        let compressed = CompressedLocations::new(&locations);
+        assert_eq!(compressed.call_stacks.num_frames(), 4);
+        assert_eq!(
+            compressed.call_stack(&OpcodeLocation::Acir(1)),
+            Some(vec![at(1), at(2), at(4)])
+        );
+        assert_eq!(compressed.call_stacks.resolve(CallStackId::root()), Some(Vec::new()));
+        assert_eq!(compressed.decompress(), Some(locations.clone()));
+
+        let debug_info = DebugInfo::new(locations.clone());
+        let json = serde_json::to_string(&debug_info).unwrap();
+        let round_tripped: DebugInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(round_tripped.locations, locations);
+    }
+
+    #[test]
+    fn full_call_stacks_are_still_read() {
+        let location = serde_json::to_string(&at(1)).unwrap();
+        let json = format!(r#"{{ "locations": {{ "0": [{location}], "1.2": [] }} }}"#);
+        let debug_info: DebugInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(debug_info.opcode_location(&OpcodeLocation::Acir(0)), Some(vec![at(1)]));
+        let brillig_location = OpcodeLocation::Brillig { acir_index: 1, brillig_index: 2 };
+        assert_eq!(debug_info.opcode_location(&brillig_location), Some(Vec::new()));
+    }
+}
@@ -0,0 +1,184 @@
+//! A JSON representation of a compiled circuit for tools outside of the compiler, such as circuit
+//! analyzers, linters and visualizers, which should not need to link against `acvm` to read the
+//! base64-encoded bytecode of a program artifact.
+//!
+//! The schema is versioned by [`ACIR_JSON_VERSION`], which is bumped whenever a field is removed
+//! or changes meaning. Each opcode is given both as text, in the format of `--print-acir`, and in
+//! the serialization of `acvm` itself, which carries every detail of the opcode but follows its
+//! internal representation and so may change between compiler versions.
+use acvm::acir::{
+    circuit::{Circuit, Opcode, OpcodeLocation},
+    native_types::Witness,
+};
+use noirc_errors::{debug_info::DebugInfo, Location};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::SsaReport;
+
+/// Version of the schema of [`AcirJson`].
+pub const ACIR_JSON_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AcirJson {
+    pub version: u32,
+    /// The largest index of a witness in the circuit.
+    pub current_witness_index: u32,
+    pub private_parameters: Vec<u32>,
+    pub public_parameters: Vec<u32>,
+    pub return_values: Vec<u32>,
+    pub opcodes: Vec<OpcodeJson>,
+    pub warnings: Vec<WarningJson>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpcodeJson {
+    pub index: usize,
+    pub kind: OpcodeKind,
+    pub text: String,
+    pub opcode: Opcode,
+    /// The source locations which the opcode was generated for, outermost call first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub call_stack: Vec<SourceSpan>,
+    /// The message of the assertion which fails if the opcode is not satisfied, with the indices
+    /// of any witnesses whose values are part of it written as `_<index>`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assert_message: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OpcodeKind {
+    AssertZero,
+    BlackBoxFuncCall,
+    Directive,
+    Brillig,
+    MemoryOp,
+    MemoryInit,
+    Call,
+}
+
+/// A range of bytes of a source file, identified by its index in the file map of the program
+/// artifact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceSpan {
+    pub file: usize,
+    pub start: u32,
+    pub end: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WarningJson {
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub call_stack: Vec<SourceSpan>,
+}
+
+impl AcirJson {
+    /// Describes `circuit`, whose opcodes are located by `debug` and which was compiled with
+    /// `warnings`.
+    pub fn new(circuit: &Circuit, debug: &DebugInfo, warnings: &[SsaReport]) -> Self {
+        let opcodes = circuit
+            .opcodes
+            .iter()
+            .enumerate()
+            .map(|(index, opcode)| {
+                let location = OpcodeLocation::Acir(index);
+                OpcodeJson {
+                    index,
+                    kind: OpcodeKind::of(opcode),
+                    text: opcode.to_string(),
+                    opcode: opcode.clone(),
+                    call_stack: debug.locations.get(&location).map_or_else(Vec::new, source_spans),
+                    assert_message: circuit
+                        .get_assert_message(location)
+                        .map(|message| message.to_string()),
+                }
+            })
+            .collect();
+        let warnings = warnings
+            .iter()
+            .map(|SsaReport::Warning(warning)| WarningJson {
+                message: warning.to_string(),
+                call_stack: source_spans(warning.call_stack()),
+            })
+            .collect();
+
+        AcirJson {
+            version: ACIR_JSON_VERSION,
+            current_witness_index: circuit.current_witness_index,
+            private_parameters: circuit
+                .private_parameters
+                .iter()
+                .map(Witness::witness_index)
+                .collect(),
+            public_parameters: circuit.public_parameters.indices(),
+            return_values: circuit.return_values.indices(),
+            opcodes,
+            warnings,
+        }
+    }
+}
+
+impl OpcodeKind {
+    fn of(opcode: &Opcode) -> Self {
+        match opcode {
+            Opcode::AssertZero(_) => OpcodeKind::AssertZero,
+            Opcode::BlackBoxFuncCall(_) => OpcodeKind::BlackBoxFuncCall,
+            Opcode::Directive(_) => OpcodeKind::Directive,
+            Opcode::Brillig(_) | Opcode::BrilligCall { .. } => OpcodeKind::Brillig,
+            Opcode::MemoryOp { .. } => OpcodeKind::MemoryOp,
+            Opcode::MemoryInit { .. } => OpcodeKind::MemoryInit,
+            Opcode::Call { .. } => OpcodeKind::Call,
+        }
+    }
+}
+
+fn source_spans<'a>(call_stack: impl IntoIterator<Item = &'a Location>) -> Vec<SourceSpan> {
+    call_stack
+        .into_iter()
+        .map(|location| SourceSpan {
+            file: location.file.as_usize(),
+            start: location.span.start(),
+            end: location.span.end(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use acvm::acir::{
+        circuit::{opcodes::Opcode, Circuit, OpcodeLocation, PublicInputs},
+        native_types::{Expression, Witness},
+    };
+    use noirc_errors::{debug_info::DebugInfo, Location, Span};
+
+    use super::AcirJson;
+
+    #[test]
+    fn opcodes_carry_their_call_stack_and_assert_message() {
+        let location = Location::new(Span::inclusive(3, 7), Default::default());
+        let circuit = Circuit {
+            current_witness_index: 2,
+            opcodes: vec![Opcode::AssertZero(Expression::default()); 2],
+            private_parameters: [Witness(1)].into(),
+            return_values: PublicInputs([Witness(2)].into()),
+            assert_messages: vec![(OpcodeLocation::Acir(1), "failed".into())],
+            ..Circuit::default()
+        };
+        let debug = DebugInfo::new(BTreeMap::from([(OpcodeLocation::Acir(1), vec![location])]));
+
+        let json = serde_json::to_value(AcirJson::new(&circuit, &debug, &[])).unwrap();
+        assert_eq!(json["private_parameters"], serde_json::json!([1]));
+        assert_eq!(json["return_values"], serde_json::json!([2]));
+        assert_eq!(json["opcodes"][0]["kind"], "assert_zero");
+        assert_eq!(json["opcodes"][0]["text"], "EXPR [ 0 ]");
+        assert!(json["opcodes"][0].get("call_stack").is_none());
+        assert_eq!(
+            json["opcodes"][1]["call_stack"],
+            serde_json::json!([{ "file": 0, "start": 3, "end": 8 }])
+        );
+        assert_eq!(json["opcodes"][1]["assert_message"], "failed");
+    }
+}
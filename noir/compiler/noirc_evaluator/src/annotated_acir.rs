@@ -0,0 +1,158 @@
+//! A human-readable listing of a circuit for auditing it, in which the opcodes are interleaved
+//! with the source code they were generated for.
+//!
+//! Consecutive opcodes generated at the same call stack are listed under a single header, which
+//! gives the line of each frame, innermost first, and the source code of the innermost one:
+//!
+//! ```text
+//! // src/main.nr:4 in main
+//! //     assert(x * x == y, "not a square");
+//!     3: EXPR [ (1, _1, _1) (-1, _2) 0 ]
+//!        // assert message: not a square
+//! ```
+use std::fmt::{Display, Formatter, Result};
+
+use acvm::acir::circuit::{Circuit, OpcodeLocation, PublicInputs};
+use noirc_errors::{debug_info::DebugInfo, Location};
+
+use crate::attribution::SourceResolver;
+
+/// A circuit displayed with the call stacks of `debug`, as resolved by `sources`.
+pub struct AnnotatedCircuit<'a, R> {
+    pub circuit: &'a Circuit,
+    pub debug: &'a DebugInfo,
+    pub sources: &'a R,
+}
+
+impl<R: SourceResolver> Display for AnnotatedCircuit<'_, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let indices = |witnesses: &PublicInputs| {
+            let indices: Vec<_> = witnesses.indices().iter().map(u32::to_string).collect();
+            indices.join(", ")
+        };
+        writeln!(f, "current witness index : {}", self.circuit.current_witness_index)?;
+        writeln!(f, "public parameters indices : [{}]", indices(&self.circuit.public_parameters))?;
+        writeln!(f, "return value indices : [{}]", indices(&self.circuit.return_values))?;
+
+        let mut previous_call_stack = None;
+        for (index, opcode) in self.circuit.opcodes.iter().enumerate() {
+            let location = OpcodeLocation::Acir(index);
+            let call_stack = self.debug.locations.get(&location);
+            if previous_call_stack != Some(call_stack) {
+                writeln!(f)?;
+                write_call_stack(f, self.sources, call_stack.map_or(&[][..], Vec::as_slice))?;
+                previous_call_stack = Some(call_stack);
+            }
+            writeln!(f, "{index:>5}: {opcode}")?;
+            if let Some(message) = self.circuit.get_assert_message(location) {
+                writeln!(f, "       // assert message: {message}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes the header listing `call_stack`, under which the opcodes emitted at it are listed.
+pub(crate) fn write_call_stack(
+    f: &mut Formatter<'_>,
+    sources: &impl SourceResolver,
+    call_stack: &[Location],
+) -> Result {
+    let Some((innermost, callers)) = call_stack.split_last() else {
+        return writeln!(f, "// no source location");
+    };
+    writeln!(f, "// {}", describe_frame(sources, *innermost))?;
+    for caller in callers.iter().rev() {
+        writeln!(f, "//   called from {}", describe_frame(sources, *caller))?;
+    }
+    if let Some(snippet) = sources.snippet(*innermost) {
+        for line in snippet.lines() {
+            writeln!(f, "//     {line}")?;
+        }
+    }
+    Ok(())
+}
+
+fn describe_frame(sources: &impl SourceResolver, location: Location) -> String {
+    let mut frame = match sources.line(location) {
+        Some(line) => format!("{}:{}", line.file, line.line),
+        None => "unknown location".to_string(),
+    };
+    if let Some(function) = sources.function(location) {
+        frame.push_str(&format!(" in {function}"));
+    }
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use acvm::acir::{
+        circuit::{opcodes::Opcode, Circuit, OpcodeLocation},
+        native_types::Expression,
+    };
+    use noirc_errors::{debug_info::DebugInfo, Location, Span};
+
+    use super::AnnotatedCircuit;
+    use crate::attribution::{SourceLine, SourceResolver};
+
+    /// Locations start at their line, in `main` below line 10 and in `helper` from there on.
+    struct Lines;
+
+    impl SourceResolver for Lines {
+        fn line(&self, location: Location) -> Option<SourceLine> {
+            Some(SourceLine { file: "main.nr".to_string(), line: location.span.start() })
+        }
+
+        fn function(&self, location: Location) -> Option<String> {
+            let name = if location.span.start() < 10 { "main" } else { "helper" };
+            Some(name.to_string())
+        }
+
+        fn snippet(&self, location: Location) -> Option<String> {
+            Some(format!("line {}", location.span.start()))
+        }
+    }
+
+    fn at_line(line: u32) -> Location {
+        Location::new(Span::single_char(line), Default::default())
+    }
+
+    #[test]
+    fn opcodes_are_listed_under_their_call_stacks() {
+        let locations = BTreeMap::from([
+            (OpcodeLocation::Acir(0), vec![at_line(2), at_line(11)]),
+            (OpcodeLocation::Acir(1), vec![at_line(2), at_line(11)]),
+            (OpcodeLocation::Acir(2), vec![at_line(3)]),
+        ]);
+        let circuit = Circuit {
+            opcodes: vec![Opcode::AssertZero(Expression::default()); 4],
+            assert_messages: vec![(OpcodeLocation::Acir(2), "failed".into())],
+            ..Circuit::default()
+        };
+
+        let debug = DebugInfo::new(locations);
+        let listing = AnnotatedCircuit { circuit: &circuit, debug: &debug, sources: &Lines };
+        let expected = "\
+current witness index : 0
+public parameters indices : []
+return value indices : []
+
+// main.nr:11 in helper
+//   called from main.nr:2 in main
+//     line 11
+    0: EXPR [ 0 ]
+    1: EXPR [ 0 ]
+
+// main.nr:3 in main
+//     line 3
+    2: EXPR [ 0 ]
+       // assert message: failed
+
+// no source location
+    3: EXPR [ 0 ]
+";
+        assert_eq!(listing.to_string(), expected);
+    }
+}
@@ -0,0 +1,336 @@
+//! Attributes the opcodes of a circuit to the lines and functions of Noir source which they were
+//! generated for, so that developers can see which parts of their program dominate its size.
+//!
+//! Each ACIR opcode is located by the call stack at which it was emitted. The innermost frame of
+//! the call stack is the line which the opcode was generated for, while the outer frames are the
+//! calls which led to it. An opcode therefore counts towards the own opcodes of its innermost line
+//! and function, and towards the inclusive opcodes of every line and function on its call stack.
+//!
+//! The call stacks can also be written out in the folded stack format read by flamegraph tools
+//! such as `inferno`, to show where the size of a circuit comes from across inlined calls. The
+//! blocks of profiled Brillig code are written out in the same format, weighted by the number of
+//! times they were executed, to show where unconstrained functions spend their time.
+use std::collections::{BTreeMap, BTreeSet};
+
+use acvm::acir::circuit::{Circuit, OpcodeLocation};
+use noirc_errors::{debug_info::DebugInfo, Location};
+use serde::{Deserialize, Serialize};
+
+use crate::cost_model::CostModel;
+
+/// Resolves the source locations found in call stacks.
+pub trait SourceResolver {
+    /// The line at which `location` starts, if its file is known.
+    fn line(&self, location: Location) -> Option<SourceLine>;
+
+    /// The name of the function whose body contains `location`, if any.
+    fn function(&self, location: Location) -> Option<String>;
+
+    /// The source code at `location`, if it is known.
+    fn snippet(&self, _location: Location) -> Option<String> {
+        None
+    }
+}
+
+/// A line of a source file, counted from 1.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct SourceLine {
+    pub file: String,
+    pub line: u32,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpcodeCounts {
+    /// Number of opcodes generated for the line or function itself.
+    pub opcodes: usize,
+    /// Number of opcodes generated for the line or function itself, or for the calls it makes.
+    pub inclusive_opcodes: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineAttribution {
+    #[serde(flatten)]
+    pub line: SourceLine,
+    /// The function which the line belongs to, if it is known.
+    pub function: Option<String>,
+    #[serde(flatten)]
+    pub counts: OpcodeCounts,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FunctionAttribution {
+    pub function: String,
+    pub file: String,
+    #[serde(flatten)]
+    pub counts: OpcodeCounts,
+}
+
+/// The number of ACIR opcodes attributed to each line and function of a program.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConstraintReport {
+    pub total_opcodes: usize,
+    /// Opcodes which have no known source location, e.g. those generated for the program's
+    /// parameters and return values.
+    pub unattributed_opcodes: usize,
+    /// The lines which opcodes were attributed to, with those generating the most opcodes first.
+    pub lines: Vec<LineAttribution>,
+    /// The functions which opcodes were attributed to, with those generating the most opcodes,
+    /// including through their calls, first.
+    pub functions: Vec<FunctionAttribution>,
+}
+
+/// Attributes the opcodes of `circuit` to the source lines and functions of the call stacks in
+/// `debug`, as resolved by `sources`.
+pub fn attribute_opcodes(
+    circuit: &Circuit,
+    debug: &DebugInfo,
+    sources: &impl SourceResolver,
+) -> ConstraintReport {
+    let mut lines: BTreeMap<SourceLine, (Option<String>, OpcodeCounts)> = BTreeMap::new();
+    let mut functions: BTreeMap<(String, String), OpcodeCounts> = BTreeMap::new();
+    let mut attributed_opcodes = 0;
+
+    for (opcode_location, call_stack) in &debug.locations {
+        if !matches!(opcode_location, OpcodeLocation::Acir(_)) {
+            continue;
+        }
+        let frames: Vec<_> = call_stack
+            .iter()
+            .filter_map(|location| {
+                let line = sources.line(*location)?;
+                Some((line, sources.function(*location)))
+            })
+            .collect();
+        let Some((innermost_line, innermost_function)) = frames.last() else {
+            continue;
+        };
+        attributed_opcodes += 1;
+
+        // Recursive calls put a line or function on the call stack more than once, but the
+        // opcode is only generated for it once.
+        let mut seen_lines = BTreeSet::new();
+        let mut seen_functions = BTreeSet::new();
+        for (line, function) in &frames {
+            let function_key = function.clone().map(|function| (function, line.file.clone()));
+            if seen_lines.insert(line) {
+                let (line_function, counts) = lines
+                    .entry(line.clone())
+                    .or_insert_with(|| (function.clone(), Default::default()));
+                if line_function.is_none() {
+                    *line_function = function.clone();
+                }
+                counts.inclusive_opcodes += 1;
+            }
+            if let Some(function_key) = function_key {
+                if seen_functions.insert(function_key.clone()) {
+                    functions.entry(function_key).or_default().inclusive_opcodes += 1;
+                }
+            }
+        }
+        if let Some((_, counts)) = lines.get_mut(innermost_line) {
+            counts.opcodes += 1;
+        }
+        if let Some(function) = innermost_function {
+            if let Some(counts) =
+                functions.get_mut(&(function.clone(), innermost_line.file.clone()))
+            {
+                counts.opcodes += 1;
+            }
+        }
+    }
+
+    let mut lines: Vec<_> = lines
+        .into_iter()
+        .map(|(line, (function, counts))| LineAttribution { line, function, counts })
+        .collect();
+    lines.sort_by(|a, b| {
+        (b.counts.opcodes, b.counts.inclusive_opcodes)
+            .cmp(&(a.counts.opcodes, a.counts.inclusive_opcodes))
+            .then_with(|| a.line.cmp(&b.line))
+    });
+    let mut functions: Vec<_> = functions
+        .into_iter()
+        .map(|((function, file), counts)| FunctionAttribution { function, file, counts })
+        .collect();
+    functions.sort_by(|a, b| {
+        (b.counts.inclusive_opcodes, b.counts.opcodes)
+            .cmp(&(a.counts.inclusive_opcodes, a.counts.opcodes))
+            .then_with(|| (&a.file, &a.function).cmp(&(&b.file, &b.function)))
+    });
+
+    ConstraintReport {
+        total_opcodes: circuit.opcodes.len(),
+        unattributed_opcodes: circuit.opcodes.len().saturating_sub(attributed_opcodes),
+        lines,
+        functions,
+    }
+}
+
+/// Returns the call stacks of the opcodes of `circuit` in the folded stack format, with one line
+/// per distinct call stack followed by its weight.
+///
+/// Each opcode weighs its estimated cost under `cost_model` if one is given, or one otherwise.
+/// Frames are named after their function and line, and opcodes without a known location are
+/// put under a single `unknown` frame.
+pub fn folded_stacks(
+    circuit: &Circuit,
+    debug: &DebugInfo,
+    sources: &impl SourceResolver,
+    cost_model: Option<&dyn CostModel>,
+) -> String {
+    let mut stacks: BTreeMap<String, u64> = BTreeMap::new();
+    for (index, opcode) in circuit.opcodes.iter().enumerate() {
+        let call_stack = debug.locations.get(&OpcodeLocation::Acir(index));
+        let stack = fold_call_stack(call_stack.map_or(&[][..], Vec::as_slice), sources);
+        let weight = cost_model.map_or(1, |cost_model| cost_model.opcode_cost(opcode));
+        *stacks.entry(stack).or_default() += weight;
+    }
+
+    stacks.into_iter().map(|(stack, weight)| format!("{stack} {weight}\n")).collect()
+}
+
+/// Returns the call stacks of the blocks of profiled Brillig code in the folded stack format,
+/// weighted by the number of times they were executed.
+///
+/// `counters` maps the id of each profiling counter into the call stack of the block it counts,
+/// as in [`DebugInfo::brillig_counters`], and `counts` maps it into the number of times it was
+/// reached. Frames are named as in [`folded_stacks`].
+pub fn profile_folded_stacks(
+    counters: &BTreeMap<usize, Vec<Location>>,
+    counts: &BTreeMap<usize, u64>,
+    sources: &impl SourceResolver,
+) -> String {
+    let mut stacks: BTreeMap<String, u64> = BTreeMap::new();
+    for (counter, count) in counts {
+        let call_stack = counters.get(counter).map_or(&[][..], Vec::as_slice);
+        *stacks.entry(fold_call_stack(call_stack, sources)).or_default() += count;
+    }
+
+    stacks.into_iter().map(|(stack, count)| format!("{stack} {count}\n")).collect()
+}
+
+/// Names the frames of `call_stack` after their function and line, separated by `;`, or returns
+/// `unknown` if none of them can be located.
+fn fold_call_stack(call_stack: &[Location], sources: &impl SourceResolver) -> String {
+    let frames: Vec<_> = call_stack
+        .iter()
+        .filter_map(|location| {
+            let line = sources.line(*location)?;
+            let function = sources.function(*location).unwrap_or_else(|| "?".to_string());
+            Some(format!("{function} ({}:{})", line.file, line.line))
+        })
+        .collect();
+    if frames.is_empty() {
+        "unknown".to_string()
+    } else {
+        frames.join(";")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use acvm::acir::{
+        circuit::{opcodes::Opcode, Circuit, OpcodeLocation},
+        native_types::Expression,
+    };
+    use noirc_errors::{debug_info::DebugInfo, Location, Span};
+
+    use super::{
+        attribute_opcodes, folded_stacks, profile_folded_stacks, OpcodeCounts, SourceLine,
+        SourceResolver,
+    };
+
+    /// Locations start at their line, and lines from 10 onwards belong to `helper`.
+    struct Lines;
+
+    impl SourceResolver for Lines {
+        fn line(&self, location: Location) -> Option<SourceLine> {
+            Some(SourceLine { file: "main.nr".to_string(), line: location.span.start() })
+        }
+
+        fn function(&self, location: Location) -> Option<String> {
+            let name = if location.span.start() < 10 { "main" } else { "helper" };
+            Some(name.to_string())
+        }
+    }
+
+    fn at_line(line: u32) -> Location {
+        Location::new(Span::single_char(line), Default::default())
+    }
+
+    #[test]
+    fn attributes_opcodes_to_every_frame_of_their_call_stack() {
+        // main calls `helper` on line 2, which emits an opcode on line 11 and calls itself on
+        // line 12 to emit another opcode on line 11.
+        let locations = BTreeMap::from([
+            (OpcodeLocation::Acir(0), vec![at_line(1)]),
+            (OpcodeLocation::Acir(1), vec![at_line(2), at_line(11)]),
+            (OpcodeLocation::Acir(2), vec![at_line(2), at_line(12), at_line(11)]),
+            (OpcodeLocation::Brillig { acir_index: 3, brillig_index: 0 }, vec![at_line(1)]),
+        ]);
+        let circuit = Circuit {
+            opcodes: vec![Opcode::AssertZero(Expression::default()); 5],
+            ..Circuit::default()
+        };
+
+        let report = attribute_opcodes(&circuit, &DebugInfo::new(locations), &Lines);
+        assert_eq!(report.total_opcodes, 5);
+        assert_eq!(report.unattributed_opcodes, 2);
+
+        let lines: Vec<_> = report.lines.iter().map(|line| (line.line.line, line.counts)).collect();
+        let counts = |opcodes, inclusive_opcodes| OpcodeCounts { opcodes, inclusive_opcodes };
+        assert_eq!(
+            lines,
+            [(11, counts(2, 2)), (1, counts(1, 1)), (2, counts(0, 2)), (12, counts(0, 1))]
+        );
+
+        let functions: Vec<_> = report
+            .functions
+            .iter()
+            .map(|function| (function.function.as_str(), function.counts))
+            .collect();
+        assert_eq!(functions, [("main", counts(1, 3)), ("helper", counts(2, 2))]);
+    }
+
+    #[test]
+    fn folds_identical_call_stacks() {
+        let locations = BTreeMap::from([
+            (OpcodeLocation::Acir(0), vec![at_line(2), at_line(11)]),
+            (OpcodeLocation::Acir(1), vec![at_line(1)]),
+            (OpcodeLocation::Acir(2), vec![at_line(2), at_line(11)]),
+        ]);
+        let circuit = Circuit {
+            opcodes: vec![Opcode::AssertZero(Expression::default()); 4],
+            ..Circuit::default()
+        };
+
+        let folded = folded_stacks(&circuit, &DebugInfo::new(locations), &Lines, None);
+        assert_eq!(
+            folded,
+            "main (main.nr:1) 1\n\
+             main (main.nr:2);helper (main.nr:11) 2\n\
+             unknown 1\n"
+        );
+    }
+
+    #[test]
+    fn weighs_profiled_blocks_by_their_executions() {
+        let counters = BTreeMap::from([
+            (0, vec![at_line(1)]),
+            (1, vec![at_line(2), at_line(11)]),
+            (2, vec![at_line(3), at_line(11)]),
+        ]);
+        // Counter 2 was never reached, and counter 3 belongs to no known block.
+        let counts = BTreeMap::from([(0, 1), (1, 40), (3, 2)]);
+
+        let folded = profile_folded_stacks(&counters, &counts, &Lines);
+        assert_eq!(
+            folded,
+            "main (main.nr:1) 1\n\
+             main (main.nr:2);helper (main.nr:11) 40\n\
+             unknown 2\n"
+        );
+    }
+}
@@ -1,26 +1,60 @@
+pub(crate) mod brillig_bigint;
 pub(crate) mod brillig_black_box;
 pub(crate) mod brillig_block;
 pub(crate) mod brillig_block_variables;
 pub(crate) mod brillig_directive;
 pub(crate) mod brillig_fn;
 pub(crate) mod brillig_slice_ops;
+pub(crate) mod step_bound;
 mod variable_liveness;
 
 use self::{brillig_block::BrilligBlock, brillig_fn::FunctionContext};
-use super::brillig_ir::{artifact::BrilligArtifact, BrilligContext};
-use crate::ssa::ir::function::Function;
+use super::{
+    brillig_ir::{artifact::BrilligArtifact, BrilligContext},
+    BrilligOptions,
+};
+use crate::spans;
+use crate::{errors::RuntimeError, ssa::ir::function::Function};
 
-/// Converting an SSA function into Brillig bytecode.
-pub(crate) fn convert_ssa_function(func: &Function, enable_debug_trace: bool) -> BrilligArtifact {
-    let mut brillig_context = BrilligContext::new(enable_debug_trace);
+/// Converting an SSA function into Brillig bytecode, which checks the limits of the `options` on
+/// entry.
+///
+/// If `first_profile_counter` is given, each block counts its executions under counters numbered
+/// from it.
+pub(crate) fn convert_ssa_function(
+    func: &Function,
+    options: &BrilligOptions,
+    first_profile_counter: Option<usize>,
+) -> Result<BrilligArtifact, RuntimeError> {
+    let _span = tracing::debug_span!(spans::BRILLIG_FUNCTION, function = func.name()).entered();
+
+    let mut brillig_context = BrilligContext::new(options.enable_debug_trace);
+    brillig_context.set_limits(options.limits);
+    if let Some(first_profile_counter) = first_profile_counter {
+        brillig_context.enable_profiling(first_profile_counter);
+    }
+    brillig_context.memoize_foreign_calls(options.memoized_foreign_calls.clone());
+    if options.check_overflows {
+        brillig_context.enable_overflow_checks();
+    }
 
     let mut function_context = FunctionContext::new(func, &mut brillig_context);
+    let parameter_registers = func
+        .parameters()
+        .iter()
+        .map(|parameter| {
+            function_context.ssa_value_allocations[parameter].extract_registers().len()
+        })
+        .sum();
+    brillig_context.use_calling_convention_registers(parameter_registers);
 
     brillig_context.enter_context(FunctionContext::function_id_to_function_label(func.id()));
+    brillig_context.check_limits_on_entry();
 
     for block in function_context.blocks.clone() {
-        BrilligBlock::compile(&mut function_context, &mut brillig_context, block, &func.dfg);
+        BrilligBlock::compile(&mut function_context, &mut brillig_context, block, &func.dfg)?;
     }
 
-    brillig_context.artifact()
+    brillig_context.reallocate_registers();
+    Ok(brillig_context.artifact())
 }
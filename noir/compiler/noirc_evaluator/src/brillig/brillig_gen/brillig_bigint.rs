@@ -0,0 +1,280 @@
+//! The procedures which the big integer black box functions of unconstrained code are lowered to.
+//!
+//! Instead of the ids of the ACVM solver, the `pointer` and `modulus` fields of a big integer hold
+//! the addresses of arrays with the limbs of its value and of its modulus respectively, laid out as
+//! described in [`crate::brillig::brillig_ir::bigint`]. Each procedure is generated once per
+//! program and linked like a function, taking its arguments and returning its results in the same
+//! registers.
+use acvm::acir::BlackBoxFunc;
+
+use crate::brillig::brillig_ir::{
+    artifact::{BrilligArtifact, Label},
+    bigint::{BigIntOp, BIGINT_LIMBS},
+    BrilligContext, BrilligLimits,
+};
+
+/// A procedure of the big integer library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum BigIntProcedure {
+    /// Takes the pointers and lengths of the vectors of the little-endian bytes of a value and of
+    /// a modulus, and returns the big integer holding the value modulo the modulus.
+    FromLeBytes,
+    /// Takes a big integer and returns the length and pointer of the vector of its little-endian
+    /// bytes.
+    ToLeBytes,
+    /// Takes two big integers and returns the result of the operation between them.
+    Op(BigIntOp),
+}
+
+impl BigIntProcedure {
+    const ALL: [BigIntProcedure; 6] = [
+        BigIntProcedure::FromLeBytes,
+        BigIntProcedure::ToLeBytes,
+        BigIntProcedure::Op(BigIntOp::Add),
+        BigIntProcedure::Op(BigIntOp::Sub),
+        BigIntProcedure::Op(BigIntOp::Mul),
+        BigIntProcedure::Op(BigIntOp::Div),
+    ];
+
+    /// Returns the procedure which calls to the black box function are lowered to, if any.
+    pub(crate) fn for_black_box(func: BlackBoxFunc) -> Option<Self> {
+        match func {
+            BlackBoxFunc::BigIntFromLeBytes => Some(BigIntProcedure::FromLeBytes),
+            BlackBoxFunc::BigIntToLeBytes => Some(BigIntProcedure::ToLeBytes),
+            BlackBoxFunc::BigIntAdd => Some(BigIntProcedure::Op(BigIntOp::Add)),
+            BlackBoxFunc::BigIntSub => Some(BigIntProcedure::Op(BigIntOp::Sub)),
+            BlackBoxFunc::BigIntMul => Some(BigIntProcedure::Op(BigIntOp::Mul)),
+            BlackBoxFunc::BigIntDiv => Some(BigIntProcedure::Op(BigIntOp::Div)),
+            _ => None,
+        }
+    }
+
+    /// Returns the label which calls to the procedure jump to.
+    pub(crate) fn label(self) -> Label {
+        let name = match self {
+            BigIntProcedure::FromLeBytes => "from_le_bytes",
+            BigIntProcedure::ToLeBytes => "to_le_bytes",
+            BigIntProcedure::Op(BigIntOp::Add) => "add",
+            BigIntProcedure::Op(BigIntOp::Sub) => "sub",
+            BigIntProcedure::Op(BigIntOp::Mul) => "mul",
+            BigIntProcedure::Op(BigIntOp::Div) => "div",
+        };
+        format!("bigint_{name}")
+    }
+
+    /// Returns the procedure with the given label, if any.
+    pub(crate) fn from_label(label: &str) -> Option<Self> {
+        BigIntProcedure::ALL.into_iter().find(|procedure| procedure.label() == label)
+    }
+
+    /// Generates the bytecode of the procedure, which checks the `limits` on entry.
+    pub(crate) fn generate(
+        self,
+        enable_debug_trace: bool,
+        limits: BrilligLimits,
+    ) -> BrilligArtifact {
+        let mut context = BrilligContext::new(enable_debug_trace);
+        context.set_limits(limits);
+
+        // The first registers allocated are those of the calling convention.
+        let arguments = [(); 4].map(|_| context.allocate_register());
+        let argument_count = match self {
+            BigIntProcedure::ToLeBytes => 2,
+            BigIntProcedure::FromLeBytes | BigIntProcedure::Op(_) => 4,
+        };
+        context.use_calling_convention_registers(argument_count);
+        context.enter_context(self.label());
+        context.check_limits_on_entry();
+
+        let returns = match self {
+            BigIntProcedure::FromLeBytes => {
+                let [bytes_pointer, bytes_len, modulus_bytes_pointer, modulus_bytes_len] =
+                    arguments;
+                let result = context.allocate_register();
+                let modulus = context.allocate_register();
+                context.allocate_fixed_length_array(result, BIGINT_LIMBS);
+                context.allocate_fixed_length_array(modulus, BIGINT_LIMBS);
+                context.bigint_from_le_bytes_instruction(
+                    (bytes_pointer, bytes_len),
+                    (modulus_bytes_pointer, modulus_bytes_len),
+                    result,
+                    modulus,
+                );
+                [result, modulus]
+            }
+            BigIntProcedure::ToLeBytes => {
+                let [value, modulus, ..] = arguments;
+                let bytes_pointer = context.allocate_register();
+                let bytes_len = context.allocate_register();
+                context.bigint_to_le_bytes_instruction(value, modulus, bytes_pointer, bytes_len);
+                [bytes_len, bytes_pointer]
+            }
+            BigIntProcedure::Op(op) => {
+                let [lhs, lhs_modulus, rhs, rhs_modulus] = arguments;
+                let result = context.allocate_register();
+                context.allocate_fixed_length_array(result, BIGINT_LIMBS);
+                context.bigint_op_instruction(op, (lhs, lhs_modulus), (rhs, rhs_modulus), result);
+                [result, lhs_modulus]
+            }
+        };
+        context.return_instruction(&returns);
+
+        context.reallocate_registers();
+        context.artifact()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::brillig::{MemoryAddress, Opcode as BrilligOpcode, Value};
+    use acvm::brillig_vm::{VMStatus, VM};
+    use acvm::FieldElement;
+    use iter_extended::vecmap;
+    use num_bigint::BigUint;
+
+    use crate::brillig::brillig_ir::{
+        bigint::{BigIntOp, BIGINT_MAX_BYTES},
+        brillig_variable::BrilligVariable,
+        tests::{create_context, DummyBlackBoxSolver},
+        BrilligContext, BrilligLimits, ReservedRegisters, BRILLIG_MEMORY_ADDRESSING_BIT_SIZE,
+        MAX_STACK_SIZE,
+    };
+
+    use super::BigIntProcedure;
+
+    /// Calls the procedure, saving the `live` variables across the call and adding its two results
+    /// to them.
+    fn call_procedure(
+        context: &mut BrilligContext,
+        procedure: BigIntProcedure,
+        arguments: &[MemoryAddress],
+        live: &mut Vec<BrilligVariable>,
+    ) -> [MemoryAddress; 2] {
+        let saved_registers = context.pre_call_save_registers_prep_args(arguments, live);
+        context.add_external_call_instruction(procedure.label());
+        let results = [(); 2].map(|_| context.allocate_register());
+        context.post_call_prep_returns_load_registers(&results, &saved_registers);
+        live.extend(results.map(BrilligVariable::Simple));
+        results
+    }
+
+    /// Returns the little-endian bytes of `lhs op rhs`, where the operands are converted from
+    /// bytes modulo `modulus` and the result back to bytes with the procedures.
+    fn evaluate(op: BigIntOp, lhs: &BigUint, rhs: &BigUint, modulus: &BigUint) -> Vec<u8> {
+        let inputs = [lhs, rhs, modulus].map(BigUint::to_bytes_le);
+        let mut calldata = Vec::new();
+        for bytes in &inputs {
+            calldata.extend(bytes.iter().map(|byte| Value::from(*byte as usize)));
+            calldata.resize(calldata.len() + BIGINT_MAX_BYTES - bytes.len(), Value::from(0_usize));
+        }
+
+        let mut context = create_context();
+        context.push_opcode(BrilligOpcode::Const {
+            destination: ReservedRegisters::stack_pointer(),
+            value: (MAX_STACK_SIZE + calldata.len()).into(),
+            bit_size: BRILLIG_MEMORY_ADDRESSING_BIT_SIZE,
+        });
+        context.push_opcode(BrilligOpcode::CalldataCopy {
+            destination_address: MemoryAddress(MAX_STACK_SIZE),
+            size: calldata.len(),
+            offset: 0,
+        });
+        let mut live = Vec::new();
+        let [lhs_bytes, rhs_bytes, modulus_bytes] = [0, 1, 2].map(|index| {
+            let offset = MAX_STACK_SIZE + index * BIGINT_MAX_BYTES;
+            let pointer = context.make_usize_constant(offset.into());
+            let len = context.make_usize_constant(inputs[index].len().into());
+            live.extend([BrilligVariable::Simple(pointer), BrilligVariable::Simple(len)]);
+            [pointer, len]
+        });
+
+        let from_le_bytes = BigIntProcedure::FromLeBytes;
+        let [lhs, modulus] = call_procedure(
+            &mut context,
+            from_le_bytes,
+            &[lhs_bytes, modulus_bytes].concat(),
+            &mut live,
+        );
+        let [rhs, rhs_modulus] = call_procedure(
+            &mut context,
+            from_le_bytes,
+            &[rhs_bytes, modulus_bytes].concat(),
+            &mut live,
+        );
+        let [result, _] = call_procedure(
+            &mut context,
+            BigIntProcedure::Op(op),
+            &[lhs, modulus, rhs, rhs_modulus],
+            &mut live,
+        );
+        let [bytes_len, bytes_pointer] =
+            call_procedure(&mut context, BigIntProcedure::ToLeBytes, &[result, modulus], &mut live);
+        context.stop_instruction();
+
+        let mut artifact = context.artifact();
+        for procedure in [from_le_bytes, BigIntProcedure::Op(op), BigIntProcedure::ToLeBytes] {
+            artifact.link_with(&procedure.generate(false, BrilligLimits::default()));
+        }
+        let bytecode = artifact.finish().byte_code;
+
+        let mut vm = VM::new(calldata, &bytecode, vec![], &DummyBlackBoxSolver);
+        let status = vm.process_opcodes();
+        assert!(matches!(status, VMStatus::Finished { .. }), "VM did not finish: {status:?}");
+        let memory = vm.get_memory();
+        let bytes_len = memory[bytes_len.to_usize()].to_usize();
+        let bytes_pointer = memory[bytes_pointer.to_usize()].to_usize();
+        vecmap(&memory[bytes_pointer..bytes_pointer + bytes_len], |byte| byte.to_usize() as u8)
+    }
+
+    /// Returns the little-endian bytes of `value`, as many as those of `modulus - 1`.
+    fn expected_bytes(value: BigUint, modulus: &BigUint) -> Vec<u8> {
+        let len = (modulus - 1_u32).to_bytes_le().len();
+        let mut bytes = value.to_bytes_le();
+        bytes.resize(len, 0);
+        bytes
+    }
+
+    #[test]
+    fn computes_modular_arithmetic() {
+        let secp256k1_fq = BigUint::parse_bytes(
+            b"fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+            16,
+        )
+        .unwrap();
+        let moduli = [
+            FieldElement::modulus(),
+            secp256k1_fq,
+            (BigUint::from(1_u32) << 127) - 1_u32,
+            (BigUint::from(1_u32) << 61) - 1_u32,
+            BigUint::from(101_u32),
+        ];
+        for modulus in &moduli {
+            let values = [
+                BigUint::from(0_u32),
+                modulus / 3_u32,
+                modulus - 1_u32,
+                // Values are reduced when converted from bytes.
+                (BigUint::from(1_u32) << 256) - 1_u32,
+            ];
+            for lhs in &values {
+                for rhs in &values {
+                    let (a, b) = (lhs % modulus, rhs % modulus);
+                    let inverse = b.modpow(&(modulus - 2_u32), modulus);
+                    let expected = [
+                        (BigIntOp::Add, (&a + &b) % modulus),
+                        (BigIntOp::Sub, (&a + modulus - &b) % modulus),
+                        (BigIntOp::Mul, (&a * &b) % modulus),
+                        (BigIntOp::Div, (&a * inverse) % modulus),
+                    ];
+                    for (op, expected) in expected {
+                        assert_eq!(
+                            evaluate(op, lhs, rhs, modulus),
+                            expected_bytes(expected, modulus),
+                            "{lhs} {op:?} {rhs} modulo {modulus}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
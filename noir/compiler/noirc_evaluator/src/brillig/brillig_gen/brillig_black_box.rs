@@ -1,19 +1,24 @@
 use acvm::acir::{brillig::BlackBoxOp, BlackBoxFunc};
 
-use crate::brillig::brillig_ir::{
-    brillig_variable::{BrilligVariable, BrilligVector},
-    BrilligContext,
+use crate::{
+    brillig::brillig_ir::{
+        brillig_variable::{BrilligVariable, BrilligVector},
+        BrilligContext,
+    },
+    errors::RuntimeError,
 };
 
 /// Transforms SSA's black box function calls into the corresponding brillig instructions
 /// Extracting arguments and results from the SSA function call
 /// And making any necessary type conversions to adapt noir's blackbox calls to brillig's
+///
+/// Returns an error for the black box functions which the Brillig VM does not implement.
 pub(crate) fn convert_black_box_call(
     brillig_context: &mut BrilligContext,
     bb_func: &BlackBoxFunc,
     function_arguments: &[BrilligVariable],
     function_results: &[BrilligVariable],
-) {
+) -> Result<(), RuntimeError> {
     match bb_func {
         BlackBoxFunc::SHA256 => {
             if let ([message], [BrilligVariable::BrilligArray(result_array)]) =
@@ -224,110 +229,27 @@ pub(crate) fn convert_black_box_call(
         BlackBoxFunc::RANGE => unreachable!(
             "ICE: `BlackBoxFunc::RANGE` calls should be transformed into a `Instruction::Cast`"
         ),
-        BlackBoxFunc::RecursiveAggregation => unimplemented!(
-            "ICE: `BlackBoxFunc::RecursiveAggregation` is not implemented by the Brillig VM"
-        ),
-        BlackBoxFunc::BigIntAdd => {
-            if let (
-                [BrilligVariable::Simple(lhs), BrilligVariable::Simple(rhs)],
-                [BrilligVariable::Simple(output)],
-            ) = (function_arguments, function_results)
-            {
-                brillig_context.black_box_op_instruction(BlackBoxOp::BigIntAdd {
-                    lhs: *lhs,
-                    rhs: *rhs,
-                    output: *output,
-                });
-            } else {
-                unreachable!(
-                    "ICE: EmbeddedCurveAdd expects two register arguments and one array result"
-                )
-            }
-        }
-        BlackBoxFunc::BigIntSub => {
-            if let (
-                [BrilligVariable::Simple(lhs), BrilligVariable::Simple(rhs)],
-                [BrilligVariable::Simple(output)],
-            ) = (function_arguments, function_results)
-            {
-                brillig_context.black_box_op_instruction(BlackBoxOp::BigIntSub {
-                    lhs: *lhs,
-                    rhs: *rhs,
-                    output: *output,
-                });
-            } else {
-                unreachable!(
-                    "ICE: EmbeddedCurveAdd expects two register arguments and one array result"
-                )
-            }
-        }
-        BlackBoxFunc::BigIntMul => {
-            if let (
-                [BrilligVariable::Simple(lhs), BrilligVariable::Simple(rhs)],
-                [BrilligVariable::Simple(output)],
-            ) = (function_arguments, function_results)
-            {
-                brillig_context.black_box_op_instruction(BlackBoxOp::BigIntMul {
-                    lhs: *lhs,
-                    rhs: *rhs,
-                    output: *output,
-                });
-            } else {
-                unreachable!(
-                    "ICE: EmbeddedCurveAdd expects two register arguments and one array result"
-                )
-            }
-        }
-        BlackBoxFunc::BigIntDiv => {
-            if let (
-                [BrilligVariable::Simple(lhs), BrilligVariable::Simple(rhs)],
-                [BrilligVariable::Simple(output)],
-            ) = (function_arguments, function_results)
-            {
-                brillig_context.black_box_op_instruction(BlackBoxOp::BigIntDiv {
-                    lhs: *lhs,
-                    rhs: *rhs,
-                    output: *output,
-                });
-            } else {
-                unreachable!(
-                    "ICE: EmbeddedCurveAdd expects two register arguments and one array result"
-                )
-            }
-        }
-        BlackBoxFunc::BigIntFromLeBytes => {
-            if let ([inputs, modulus], [BrilligVariable::Simple(output)]) =
-                (function_arguments, function_results)
-            {
-                let inputs_vector = convert_array_or_vector(brillig_context, inputs, bb_func);
-                let modulus_vector = convert_array_or_vector(brillig_context, modulus, bb_func);
-                brillig_context.black_box_op_instruction(BlackBoxOp::BigIntFromLeBytes {
-                    inputs: inputs_vector.to_heap_vector(),
-                    modulus: modulus_vector.to_heap_vector(),
-                    output: *output,
-                });
-            } else {
-                unreachable!(
-                    "ICE: EmbeddedCurveAdd expects two register arguments and one array result"
-                )
-            }
-        }
-        BlackBoxFunc::BigIntToLeBytes => {
-            if let (
-                [BrilligVariable::Simple(input)],
-                [BrilligVariable::BrilligVector(result_vector)],
-            ) = (function_arguments, function_results)
-            {
-                brillig_context.black_box_op_instruction(BlackBoxOp::BigIntToLeBytes {
-                    input: *input,
-                    output: result_vector.to_heap_vector(),
-                });
-            } else {
-                unreachable!(
-                    "ICE: EmbeddedCurveAdd expects two register arguments and one array result"
-                )
-            }
+        BlackBoxFunc::RecursiveAggregation
+        | BlackBoxFunc::MultiScalarMul
+        | BlackBoxFunc::AES128Encrypt
+        | BlackBoxFunc::Sha512
+        | BlackBoxFunc::PoseidonPermutation
+        | BlackBoxFunc::Ed25519
+        | BlackBoxFunc::Bls12381G1Add
+        | BlackBoxFunc::Bls12381G1Mul => {
+            return Err(RuntimeError::UnsupportedBrilligBlackBox {
+                name: *bb_func,
+                call_stack: brillig_context.call_stack().clone(),
+            });
         }
+        BlackBoxFunc::BigIntAdd
+        | BlackBoxFunc::BigIntSub
+        | BlackBoxFunc::BigIntMul
+        | BlackBoxFunc::BigIntDiv
+        | BlackBoxFunc::BigIntFromLeBytes
+        | BlackBoxFunc::BigIntToLeBytes => unreachable!(
+            "ICE: `BlackBoxFunc::{bb_func:?}` is lowered to a procedure of the big integer library"
+        ),
         BlackBoxFunc::Poseidon2Permutation => {
             if let (
                 [message, BrilligVariable::Simple(state_len)],
@@ -360,6 +282,7 @@ pub(crate) fn convert_black_box_call(
             }
         }
     }
+    Ok(())
 }
 
 fn convert_array_or_vector(
@@ -2,8 +2,9 @@ use crate::brillig::brillig_ir::brillig_variable::{
     type_to_heap_value_type, BrilligArray, BrilligVariable, BrilligVector,
 };
 use crate::brillig::brillig_ir::{
-    BrilligBinaryOp, BrilligContext, BRILLIG_INTEGER_ARITHMETIC_BIT_SIZE,
+    artifact::OpcodeLocation, BrilligBinaryOp, BrilligContext, BRILLIG_INTEGER_ARITHMETIC_BIT_SIZE,
 };
+use crate::errors::RuntimeError;
 use crate::ssa::ir::dfg::CallStack;
 use crate::ssa::ir::{
     basic_block::{BasicBlock, BasicBlockId},
@@ -16,16 +17,22 @@ use crate::ssa::ir::{
     value::{Value, ValueId},
 };
 use acvm::acir::brillig::{BinaryFieldOp, BinaryIntOp, MemoryAddress, ValueOrArray};
+use acvm::acir::BlackBoxFunc;
 use acvm::brillig_vm::brillig::HeapVector;
 use acvm::FieldElement;
 use fxhash::{FxHashMap as HashMap, FxHashSet as HashSet};
 use iter_extended::vecmap;
 use num_bigint::BigUint;
 
+use super::brillig_bigint::BigIntProcedure;
 use super::brillig_black_box::convert_black_box_call;
 use super::brillig_block_variables::BlockVariables;
 use super::brillig_fn::{get_bit_size_from_ssa_type, FunctionContext};
 
+/// The minimum number of consecutive identical items of an array literal which are written by a
+/// loop rather than one at a time.
+const MIN_FILLED_RUN: usize = 8;
+
 /// Generate the compilation artifacts for compiling a function into brillig bytecode.
 pub(crate) struct BrilligBlock<'block> {
     pub(crate) function_context: &'block mut FunctionContext,
@@ -37,6 +44,9 @@ pub(crate) struct BrilligBlock<'block> {
     pub(crate) variables: BlockVariables,
     /// For each instruction, the set of values that are not used anymore after it.
     pub(crate) last_uses: HashMap<InstructionId, HashSet<ValueId>>,
+    /// The values named after a variable of the source code whose registers hold it, along with
+    /// the position of the first opcode at which they do.
+    pub(crate) debug_variables: HashMap<ValueId, OpcodeLocation>,
 }
 
 impl<'block> BrilligBlock<'block> {
@@ -46,10 +56,14 @@ impl<'block> BrilligBlock<'block> {
         brillig_context: &'block mut BrilligContext,
         block_id: BasicBlockId,
         dfg: &DataFlowGraph,
-    ) {
+    ) -> Result<(), RuntimeError> {
         let live_in = function_context.liveness.get_live_in(&block_id);
-        let variables =
-            BlockVariables::new(live_in.clone(), function_context.all_block_parameters());
+        let live_in = live_in
+            .iter()
+            .filter(|value| !function_context.folded_values.contains_key(value))
+            .copied()
+            .collect();
+        let variables = BlockVariables::new(live_in, function_context.all_block_parameters());
 
         brillig_context.set_allocated_registers(
             variables
@@ -60,13 +74,19 @@ impl<'block> BrilligBlock<'block> {
         );
         let last_uses = function_context.liveness.get_last_uses(&block_id).clone();
 
-        let mut brillig_block =
-            BrilligBlock { function_context, block_id, brillig_context, variables, last_uses };
+        let mut brillig_block = BrilligBlock {
+            function_context,
+            block_id,
+            brillig_context,
+            variables,
+            last_uses,
+            debug_variables: HashMap::default(),
+        };
 
-        brillig_block.convert_block(dfg);
+        brillig_block.convert_block(dfg)
     }
 
-    fn convert_block(&mut self, dfg: &DataFlowGraph) {
+    fn convert_block(&mut self, dfg: &DataFlowGraph) -> Result<(), RuntimeError> {
         // Add a label for this block
         let block_label = self.create_block_label_for_current_function(self.block_id);
         self.brillig_context.enter_context(block_label);
@@ -74,10 +94,16 @@ impl<'block> BrilligBlock<'block> {
         // Convert the block parameters
         let block = &dfg[self.block_id];
         self.convert_block_params(block, dfg);
+        self.brillig_context.count_executions(Self::block_call_stack(block, dfg));
+
+        let live_in = self.function_context.liveness.get_live_in(&self.block_id).clone();
+        for value in live_in.into_iter().chain(block.parameters().iter().copied()) {
+            self.open_debug_variable(value, dfg);
+        }
 
         // Convert all of the instructions into the block
         for instruction_id in block.instructions() {
-            self.convert_ssa_instruction(*instruction_id, dfg);
+            self.convert_ssa_instruction(*instruction_id, dfg)?;
         }
 
         // Process the block's terminator instruction
@@ -85,6 +111,51 @@ impl<'block> BrilligBlock<'block> {
             block.terminator().expect("block is expected to be constructed");
 
         self.convert_ssa_terminator(terminator_instruction, dfg);
+
+        let mut debug_variables: Vec<_> = self.debug_variables.keys().copied().collect();
+        debug_variables.sort();
+        for value in debug_variables {
+            self.close_debug_variable(value, dfg);
+        }
+        Ok(())
+    }
+
+    /// Returns the call stack of the first instruction of `block`, or of its terminator if it has
+    /// no instructions, which its executions are attributed to when profiling.
+    fn block_call_stack(block: &BasicBlock, dfg: &DataFlowGraph) -> CallStack {
+        if let Some(instruction_id) = block.instructions().first() {
+            return dfg.get_call_stack(*instruction_id);
+        }
+        match block.terminator() {
+            Some(
+                TerminatorInstruction::Jmp { call_stack, .. }
+                | TerminatorInstruction::Return { call_stack, .. },
+            ) => call_stack.clone(),
+            _ => CallStack::new(),
+        }
+    }
+
+    /// Starts recording that the registers of `value` hold the variable of the source code it is
+    /// named after, if it has a name and is held in registers.
+    fn open_debug_variable(&mut self, value: ValueId, dfg: &DataFlowGraph) {
+        let value = dfg.resolve(value);
+        if dfg.value_name(value).is_some()
+            && self.function_context.ssa_value_allocations.contains_key(&value)
+        {
+            let start = self.brillig_context.next_opcode_position();
+            self.debug_variables.entry(value).or_insert(start);
+        }
+    }
+
+    /// Records the opcodes emitted since the registers of `value` started holding the variable of
+    /// the source code it is named after.
+    fn close_debug_variable(&mut self, value: ValueId, dfg: &DataFlowGraph) {
+        let value = dfg.resolve(value);
+        if let Some(start) = self.debug_variables.remove(&value) {
+            let name = dfg.value_name(value).expect("ICE: debug variable without a name");
+            let variable = self.function_context.ssa_value_allocations[&value];
+            self.brillig_context.add_debug_variable(name.to_string(), start, variable);
+        }
     }
 
     /// Creates a unique global label for a block.
@@ -101,7 +172,7 @@ impl<'block> BrilligBlock<'block> {
     /// for us to create a unique label across functions and blocks.
     ///
     /// This is so that during linking there are no duplicates or labels being overwritten.
-    fn create_block_label(function_id: FunctionId, block_id: BasicBlockId) -> String {
+    pub(crate) fn create_block_label(function_id: FunctionId, block_id: BasicBlockId) -> String {
         format!("{function_id}-{block_id}")
     }
 
@@ -188,16 +259,19 @@ impl<'block> BrilligBlock<'block> {
                     pointer: source_pointer,
                     size: source_size,
                     rc: source_rc,
+                    capacity: source_capacity,
                 }),
                 BrilligVariable::BrilligVector(BrilligVector {
                     pointer: destination_pointer,
                     size: destination_size,
                     rc: destination_rc,
+                    capacity: destination_capacity,
                 }),
             ) => {
                 self.brillig_context.mov_instruction(destination_pointer, source_pointer);
                 self.brillig_context.mov_instruction(destination_size, source_size);
                 self.brillig_context.mov_instruction(destination_rc, source_rc);
+                self.brillig_context.mov_instruction(destination_capacity, source_capacity);
             }
             (_, _) => {
                 unreachable!("ICE: Cannot pass value from {:?} to {:?}", source, destination);
@@ -234,19 +308,38 @@ impl<'block> BrilligBlock<'block> {
     }
 
     /// Converts an SSA instruction into a sequence of Brillig opcodes.
-    fn convert_ssa_instruction(&mut self, instruction_id: InstructionId, dfg: &DataFlowGraph) {
+    fn convert_ssa_instruction(
+        &mut self,
+        instruction_id: InstructionId,
+        dfg: &DataFlowGraph,
+    ) -> Result<(), RuntimeError> {
         let instruction = &dfg[instruction_id];
         self.brillig_context.set_call_stack(dfg.get_call_stack(instruction_id));
 
         match instruction {
             Instruction::Binary(binary) => {
-                let result_register = self.variables.define_register_variable(
-                    self.function_context,
-                    self.brillig_context,
-                    dfg.instruction_results(instruction_id)[0],
-                    dfg,
-                );
-                self.convert_ssa_binary(binary, dfg, result_register);
+                let result = dfg.instruction_results(instruction_id)[0];
+                let overflow_check = self.function_context.overflow_checks.get(&instruction_id);
+                // Folding would wrap the result around without checking it.
+                let folded_value = match overflow_check {
+                    Some(_) => None,
+                    None => self.fold_ssa_binary(binary, dfg),
+                };
+                if let Some(value) = folded_value {
+                    self.function_context.folded_values.insert(result, value);
+                } else {
+                    let overflow_message = overflow_check.cloned();
+                    let result_register = self.variables.define_register_variable(
+                        self.function_context,
+                        self.brillig_context,
+                        result,
+                        dfg,
+                    );
+                    self.convert_ssa_binary(binary, dfg, result_register);
+                    if let Some(message) = overflow_message {
+                        self.check_binary_overflow(binary, dfg, result_register, message);
+                    }
+                }
             }
             Instruction::Constrain(lhs, rhs, assert_message) => {
                 let condition = self.brillig_context.allocate_register();
@@ -257,7 +350,11 @@ impl<'block> BrilligBlock<'block> {
                     condition,
                 );
 
-                self.brillig_context.constrain_instruction(condition, assert_message.clone());
+                // Brillig cannot fill in the placeholders of a format string, so they are shown
+                // as written.
+                let assert_message =
+                    assert_message.as_ref().map(|message| message.template().to_owned());
+                self.brillig_context.constrain_instruction(condition, assert_message);
                 self.brillig_context.deallocate_register(condition);
             }
             Instruction::Allocate => {
@@ -328,20 +425,39 @@ impl<'block> BrilligBlock<'block> {
                         let value_type = dfg.type_of_value(*value_id);
                         type_to_heap_value_type(&value_type)
                     });
-                    let output_registers = vecmap(result_ids, |value_id| {
-                        self.allocate_external_call_result(*value_id, dfg).to_register_or_memory()
+                    let output_variables = vecmap(result_ids, |value_id| {
+                        self.allocate_external_call_result(*value_id, dfg)
                     });
+                    let output_registers =
+                        vecmap(&output_variables, |variable| variable.to_register_or_memory());
                     let output_value_types = vecmap(result_ids, |value_id| {
                         let value_type = dfg.type_of_value(*value_id);
                         type_to_heap_value_type(&value_type)
                     });
-                    self.brillig_context.foreign_call_instruction(
-                        func_name.to_owned(),
-                        &input_registers,
-                        &input_value_types,
-                        &output_registers,
-                        &output_value_types,
-                    );
+                    // Calls are only memoized if their inputs and outputs have a fixed size
+                    let cache_id =
+                        self.brillig_context.foreign_call_cache_id(func_name).filter(|_| {
+                            BrilligContext::is_memoizable_foreign_call(&input_value_types)
+                                && BrilligContext::is_memoizable_foreign_call(&output_value_types)
+                        });
+                    if let Some(cache_id) = cache_id {
+                        self.brillig_context.memoized_foreign_call_instruction(
+                            func_name.to_owned(),
+                            cache_id,
+                            &input_registers,
+                            &input_value_types,
+                            &output_registers,
+                            &output_value_types,
+                        );
+                    } else {
+                        self.brillig_context.foreign_call_instruction(
+                            func_name.to_owned(),
+                            &input_registers,
+                            &input_value_types,
+                            &output_registers,
+                            &output_value_types,
+                        );
+                    }
 
                     for (i, output_register) in output_registers.iter().enumerate() {
                         if let ValueOrArray::HeapVector(HeapVector { size, .. }) = output_register {
@@ -349,6 +465,10 @@ impl<'block> BrilligBlock<'block> {
                             // dynamic memory returned from other external calls
                             self.brillig_context.update_stack_pointer(*size);
 
+                            // The returned vector fills the memory it was given
+                            let vector = output_variables[i].extract_vector();
+                            self.brillig_context.mov_instruction(vector.capacity, *size);
+
                             // Update the dynamic slice length maintained in SSA
                             if let ValueOrArray::MemoryAddress(len_index) = output_registers[i - 1]
                             {
@@ -360,7 +480,7 @@ impl<'block> BrilligBlock<'block> {
                                     element_size,
                                 );
                             } else {
-                                unreachable!("ICE: a vector must be preceded by a register containing its length");
+                                unreachable!("ICE: a vector must be preceded by its length");
                             }
                         }
                         // Single values and allocation of fixed sized arrays has already been handled
@@ -370,6 +490,11 @@ impl<'block> BrilligBlock<'block> {
                 Value::Function(func_id) => {
                     self.convert_ssa_function_call(*func_id, arguments, dfg, instruction_id);
                 }
+                Value::Intrinsic(Intrinsic::BlackBox(bb_func))
+                    if BigIntProcedure::for_black_box(*bb_func).is_some() =>
+                {
+                    self.convert_bigint_call(*bb_func, arguments, dfg, instruction_id);
+                }
                 Value::Intrinsic(Intrinsic::BlackBox(bb_func)) => {
                     // Slices are represented as a tuple of (length, slice contents).
                     // We must check the inputs to determine if there are slices
@@ -403,7 +528,7 @@ impl<'block> BrilligBlock<'block> {
                         bb_func,
                         &function_arguments,
                         &function_results,
-                    );
+                    )?;
                 }
                 Value::Intrinsic(Intrinsic::ArrayLen) => {
                     let result_register = self.variables.define_register_variable(
@@ -518,6 +643,62 @@ impl<'block> BrilligBlock<'block> {
 
                     self.brillig_context.deallocate_register(radix);
                 }
+                Value::Intrinsic(Intrinsic::FieldSqrt) => {
+                    let source = self.convert_ssa_register_value(arguments[0], dfg);
+                    let results = dfg.instruction_results(instruction_id);
+                    let is_square = self.variables.define_register_variable(
+                        self.function_context,
+                        self.brillig_context,
+                        results[0],
+                        dfg,
+                    );
+                    let root = self.variables.define_register_variable(
+                        self.function_context,
+                        self.brillig_context,
+                        results[1],
+                        dfg,
+                    );
+                    self.brillig_context.field_sqrt_instruction(source, is_square, root);
+                }
+                Value::Intrinsic(Intrinsic::FieldPow) => {
+                    let base = self.convert_ssa_register_value(arguments[0], dfg);
+                    let exponent = self.convert_ssa_register_value(arguments[1], dfg);
+                    let max_bits = dfg
+                        .get_numeric_constant(arguments[2])
+                        .expect("ICE: field_pow expects a constant bound on the exponent");
+                    let result = self.variables.define_register_variable(
+                        self.function_context,
+                        self.brillig_context,
+                        dfg.instruction_results(instruction_id)[0],
+                        dfg,
+                    );
+                    self.brillig_context.field_pow_bounded_instruction(
+                        base,
+                        exponent,
+                        max_bits.to_u128() as u32,
+                        result,
+                    );
+                }
+                Value::Intrinsic(intrinsic @ (Intrinsic::RotateLeft | Intrinsic::RotateRight)) => {
+                    let value = self.convert_ssa_register_value(arguments[0], dfg);
+                    let amount = self.convert_ssa_register_value(arguments[1], dfg);
+                    let result = self.variables.define_register_variable(
+                        self.function_context,
+                        self.brillig_context,
+                        dfg.instruction_results(instruction_id)[0],
+                        dfg,
+                    );
+                    self.brillig_context.rotate_instruction(
+                        result,
+                        value,
+                        amount,
+                        get_bit_size_from_ssa_type(&dfg.type_of_value(arguments[1])),
+                        get_bit_size_from_ssa_type(&dfg.type_of_value(arguments[0])),
+                        *intrinsic == Intrinsic::RotateLeft,
+                    );
+                }
+                // Witnesses only exist in ACIR.
+                Value::Intrinsic(Intrinsic::AsWitness) => (),
                 _ => {
                     unreachable!("unsupported function call type {:?}", dfg[*func])
                 }
@@ -627,12 +808,18 @@ impl<'block> BrilligBlock<'block> {
         let dead_variables = self
             .last_uses
             .get(&instruction_id)
-            .expect("Last uses for instruction should have been computed");
+            .expect("Last uses for instruction should have been computed")
+            .clone();
 
         for dead_variable in dead_variables {
-            self.variables.remove_variable(dead_variable);
+            self.variables.remove_variable(&dead_variable);
+            self.close_debug_variable(dead_variable, dfg);
+        }
+        for result in dfg.instruction_results(instruction_id) {
+            self.open_debug_variable(*result, dfg);
         }
         self.brillig_context.set_call_stack(CallStack::new());
+        Ok(())
     }
 
     fn convert_ssa_function_call(
@@ -694,6 +881,85 @@ impl<'block> BrilligBlock<'block> {
             .post_call_prep_returns_load_registers(&returned_registers, &saved_registers);
     }
 
+    /// Converts a call to a big integer black box function into a call to the procedure of the big
+    /// integer library which implements it.
+    fn convert_bigint_call(
+        &mut self,
+        bb_func: BlackBoxFunc,
+        arguments: &[ValueId],
+        dfg: &DataFlowGraph,
+        instruction_id: InstructionId,
+    ) {
+        let procedure = BigIntProcedure::for_black_box(bb_func)
+            .expect("ICE: not a big integer black box function");
+
+        // Slices are represented as a tuple of (length, slice contents), and the procedures take
+        // the pointer and length of each vector or array argument.
+        let mut argument_registers = Vec::new();
+        let mut array_sizes = Vec::new();
+        for (i, argument) in arguments.iter().enumerate() {
+            let is_slice_length = matches!(dfg.type_of_value(*argument), Type::Numeric(_))
+                && arguments
+                    .get(i + 1)
+                    .map_or(false, |next| matches!(dfg.type_of_value(*next), Type::Slice(_)));
+            if is_slice_length {
+                continue;
+            }
+            match self.convert_ssa_value(*argument, dfg) {
+                BrilligVariable::Simple(register) => argument_registers.push(register),
+                BrilligVariable::BrilligArray(BrilligArray { pointer, size, .. }) => {
+                    let size = self.brillig_context.make_usize_constant(size.into());
+                    argument_registers.extend([pointer, size]);
+                    array_sizes.push(size);
+                }
+                BrilligVariable::BrilligVector(BrilligVector { pointer, size, .. }) => {
+                    argument_registers.extend([pointer, size]);
+                }
+            }
+        }
+
+        let variables_to_save = self.variables.get_available_variables(self.function_context);
+        let saved_registers = self
+            .brillig_context
+            .pre_call_save_registers_prep_args(&argument_registers, &variables_to_save);
+        for size in array_sizes {
+            self.brillig_context.deallocate_register(size);
+        }
+        self.variables.dump_constants();
+        self.brillig_context.add_external_call_instruction(procedure.label());
+
+        // A vector is returned as its pointer, after a result holding its length.
+        let result_ids = dfg.instruction_results(instruction_id);
+        let results = vecmap(result_ids, |result_id| {
+            self.variables.define_variable(
+                self.function_context,
+                self.brillig_context,
+                *result_id,
+                dfg,
+            )
+        });
+        let returned_registers = vecmap(&results, |result| match result {
+            BrilligVariable::Simple(register) => *register,
+            BrilligVariable::BrilligVector(vector) => vector.pointer,
+            BrilligVariable::BrilligArray(_) => {
+                unreachable!("ICE: big integer procedures do not return arrays")
+            }
+        });
+        self.brillig_context
+            .post_call_prep_returns_load_registers(&returned_registers, &saved_registers);
+
+        for (i, result) in results.iter().enumerate() {
+            if let BrilligVariable::BrilligVector(vector) = result {
+                let BrilligVariable::Simple(len) = results[i - 1] else {
+                    unreachable!("ICE: a vector must be preceded by its length");
+                };
+                self.brillig_context.mov_instruction(vector.size, len);
+                self.brillig_context.mov_instruction(vector.capacity, len);
+                self.brillig_context.usize_const(vector.rc, 1_usize.into());
+            }
+        }
+    }
+
     fn validate_array_index(
         &mut self,
         array_variable: BrilligVariable,
@@ -771,7 +1037,7 @@ impl<'block> BrilligBlock<'block> {
                 self.brillig_context.usize_const(source_size_register, size.into());
                 (pointer, source_size_register)
             }
-            BrilligVariable::BrilligVector(BrilligVector { size, pointer, rc: _ }) => {
+            BrilligVariable::BrilligVector(BrilligVector { size, pointer, .. }) => {
                 let source_size_register = self.brillig_context.allocate_register();
                 self.brillig_context.mov_instruction(source_size_register, size);
                 (pointer, source_size_register)
@@ -779,6 +1045,18 @@ impl<'block> BrilligBlock<'block> {
             _ => unreachable!("ICE: array set on non-array"),
         };
 
+        // A slice mutated in place keeps its spare capacity, while a copy has none.
+        let capacities = match (source_variable, destination_variable) {
+            (
+                BrilligVariable::BrilligVector(BrilligVector { capacity: source_capacity, .. }),
+                BrilligVariable::BrilligVector(BrilligVector {
+                    capacity: destination_capacity,
+                    ..
+                }),
+            ) => Some((source_capacity, destination_capacity)),
+            _ => None,
+        };
+
         let one = self.brillig_context.make_usize_constant(1_usize.into());
         let condition = self.brillig_context.allocate_register();
 
@@ -793,6 +1071,9 @@ impl<'block> BrilligBlock<'block> {
             if cond {
                 // Reference count is 1, we can mutate the array directly
                 ctx.mov_instruction(destination_pointer, source_pointer);
+                if let Some((source_capacity, destination_capacity)) = capacities {
+                    ctx.mov_instruction(destination_capacity, source_capacity);
+                }
             } else {
                 // First issue a array copy to the destination
                 ctx.allocate_array_instruction(destination_pointer, source_size_as_register);
@@ -802,6 +1083,9 @@ impl<'block> BrilligBlock<'block> {
                     destination_pointer,
                     source_size_as_register,
                 );
+                if let Some((_, destination_capacity)) = capacities {
+                    ctx.mov_instruction(destination_capacity, source_size_as_register);
+                }
             }
         });
 
@@ -838,21 +1122,29 @@ impl<'block> BrilligBlock<'block> {
             BrilligVariable::Simple(value_register) => {
                 ctx.array_set(destination_pointer, index_register, value_register);
             }
-            BrilligVariable::BrilligArray(_) => {
-                let reference: MemoryAddress = ctx.allocate_register();
-                ctx.allocate_array_reference_instruction(reference);
-                ctx.store_variable_instruction(reference, value_variable);
+            BrilligVariable::BrilligArray(_) | BrilligVariable::BrilligVector(_) => {
+                let reference = Self::store_variable_in_reference_with_ctx(ctx, value_variable);
                 ctx.array_set(destination_pointer, index_register, reference);
                 ctx.deallocate_register(reference);
             }
+        }
+    }
+
+    /// Stores an array or a vector behind a new reference, whose address is returned.
+    fn store_variable_in_reference_with_ctx(
+        ctx: &mut BrilligContext,
+        value_variable: BrilligVariable,
+    ) -> MemoryAddress {
+        let reference = ctx.allocate_register();
+        match value_variable {
+            BrilligVariable::BrilligArray(_) => ctx.allocate_array_reference_instruction(reference),
             BrilligVariable::BrilligVector(_) => {
-                let reference = ctx.allocate_register();
                 ctx.allocate_vector_reference_instruction(reference);
-                ctx.store_variable_instruction(reference, value_variable);
-                ctx.array_set(destination_pointer, index_register, reference);
-                ctx.deallocate_register(reference);
             }
+            BrilligVariable::Simple(_) => unreachable!("ICE: simple values are stored directly"),
         }
+        ctx.store_variable_instruction(reference, value_variable);
+        reference
     }
 
     pub(crate) fn store_variable_in_array(
@@ -882,6 +1174,12 @@ impl<'block> BrilligBlock<'block> {
         let source_variable = self.convert_ssa_value(slice_id, dfg);
         let source_vector = self.convert_array_or_vector_to_vector(source_variable);
 
+        // The memory of the source slice can be reused for the result if the slice is not used
+        // afterwards, unless it is also one of the items, which would then reference itself.
+        let resolved_slice_id = dfg.resolve(slice_id);
+        let reuse_source = self.last_uses[&instruction_id].contains(&resolved_slice_id)
+            && !arguments[2..].iter().any(|arg| dfg.resolve(*arg) == resolved_slice_id);
+
         let results = dfg.instruction_results(instruction_id);
         match intrinsic {
             Value::Intrinsic(Intrinsic::SlicePushBack) => {
@@ -909,7 +1207,12 @@ impl<'block> BrilligBlock<'block> {
 
                 self.update_slice_length(target_len, arguments[0], dfg, BinaryIntOp::Add);
 
-                self.slice_push_back_operation(target_vector, source_vector, &item_values);
+                self.slice_push_back_operation(
+                    target_vector,
+                    source_vector,
+                    &item_values,
+                    reuse_source,
+                );
             }
             Value::Intrinsic(Intrinsic::SlicePushFront) => {
                 let target_len = match self.variables.define_variable(
@@ -935,7 +1238,12 @@ impl<'block> BrilligBlock<'block> {
 
                 self.update_slice_length(target_len, arguments[0], dfg, BinaryIntOp::Add);
 
-                self.slice_push_front_operation(target_vector, source_vector, &item_values);
+                self.slice_push_front_operation(
+                    target_vector,
+                    source_vector,
+                    &item_values,
+                    reuse_source,
+                );
             }
             Value::Intrinsic(Intrinsic::SlicePopBack) => {
                 let target_len = match self.variables.define_variable(
@@ -968,7 +1276,12 @@ impl<'block> BrilligBlock<'block> {
 
                 self.update_slice_length(target_len, arguments[0], dfg, BinaryIntOp::Sub);
 
-                self.slice_pop_back_operation(target_vector, source_vector, &pop_variables);
+                self.slice_pop_back_operation(
+                    target_vector,
+                    source_vector,
+                    &pop_variables,
+                    reuse_source,
+                );
             }
             Value::Intrinsic(Intrinsic::SlicePopFront) => {
                 let target_len = match self.variables.define_variable(
@@ -1000,7 +1313,12 @@ impl<'block> BrilligBlock<'block> {
 
                 self.update_slice_length(target_len, arguments[0], dfg, BinaryIntOp::Sub);
 
-                self.slice_pop_front_operation(target_vector, source_vector, &pop_variables);
+                self.slice_pop_front_operation(
+                    target_vector,
+                    source_vector,
+                    &pop_variables,
+                    reuse_source,
+                );
             }
             Value::Intrinsic(Intrinsic::SliceInsert) => {
                 let target_len = match self.variables.define_variable(
@@ -1042,7 +1360,13 @@ impl<'block> BrilligBlock<'block> {
 
                 self.update_slice_length(target_len, arguments[0], dfg, BinaryIntOp::Add);
 
-                self.slice_insert_operation(target_vector, source_vector, converted_index, &items);
+                self.slice_insert_operation(
+                    target_vector,
+                    source_vector,
+                    converted_index,
+                    &items,
+                    reuse_source,
+                );
                 self.brillig_context.deallocate_register(converted_index);
             }
             Value::Intrinsic(Intrinsic::SliceRemove) => {
@@ -1094,6 +1418,7 @@ impl<'block> BrilligBlock<'block> {
                     source_vector,
                     converted_index,
                     &removed_items,
+                    reuse_source,
                 );
 
                 self.brillig_context.deallocate_register(converted_index);
@@ -1152,6 +1477,99 @@ impl<'block> BrilligBlock<'block> {
         self.brillig_context.binary_instruction(left, right, result_register, brillig_binary_op);
     }
 
+    /// Traps with `message` if the unsigned addition, subtraction or multiplication whose result
+    /// is in `result_register` wrapped around.
+    fn check_binary_overflow(
+        &mut self,
+        binary: &Binary,
+        dfg: &DataFlowGraph,
+        result_register: MemoryAddress,
+        message: Option<String>,
+    ) {
+        let bit_size = get_bit_size_from_ssa_type(&dfg.type_of_value(binary.lhs));
+        let int_op = |op| BrilligBinaryOp::Integer { op, bit_size };
+        let left = self.convert_ssa_register_value(binary.lhs, dfg);
+        let right = self.convert_ssa_register_value(binary.rhs, dfg);
+        let condition = self.brillig_context.allocate_register();
+
+        // A sum wraps around to less than its operands, and a difference when the right operand is
+        // greater than the left one.
+        match binary.operator {
+            BinaryOp::Add => self.brillig_context.binary_instruction(
+                left,
+                result_register,
+                condition,
+                int_op(BinaryIntOp::LessThanEquals),
+            ),
+            BinaryOp::Sub => self.brillig_context.binary_instruction(
+                right,
+                left,
+                condition,
+                int_op(BinaryIntOp::LessThanEquals),
+            ),
+            // A product which did not wrap around divides back into the left operand, unless the
+            // right one is zero.
+            BinaryOp::Mul => {
+                let is_zero = self.brillig_context.allocate_register();
+                let zero = self.brillig_context.make_constant(0_u128.into(), bit_size);
+                self.brillig_context.binary_instruction(
+                    right,
+                    zero,
+                    is_zero,
+                    int_op(BinaryIntOp::Equals),
+                );
+                let divisor = self.brillig_context.allocate_register();
+                self.brillig_context.binary_instruction(
+                    right,
+                    is_zero,
+                    divisor,
+                    int_op(BinaryIntOp::Add),
+                );
+                self.brillig_context.binary_instruction(
+                    result_register,
+                    divisor,
+                    condition,
+                    int_op(BinaryIntOp::UnsignedDiv),
+                );
+                self.brillig_context.binary_instruction(
+                    condition,
+                    left,
+                    condition,
+                    int_op(BinaryIntOp::Equals),
+                );
+                self.brillig_context.binary_instruction(
+                    condition,
+                    is_zero,
+                    condition,
+                    BrilligBinaryOp::Integer { op: BinaryIntOp::Or, bit_size: 1 },
+                );
+                for register in [is_zero, zero, divisor] {
+                    self.brillig_context.deallocate_register(register);
+                }
+            }
+            _ => unreachable!("ICE: overflow check of {}", binary.operator),
+        }
+        self.brillig_context.constrain_instruction(condition, message);
+        self.brillig_context.deallocate_register(condition);
+    }
+
+    /// Returns the result of the Binary instruction if both of its operands are known at compile
+    /// time and the operation doesn't fail.
+    fn fold_ssa_binary(&self, binary: &Binary, dfg: &DataFlowGraph) -> Option<FieldElement> {
+        let lhs = self.get_constant_value(binary.lhs, dfg)?;
+        let rhs = self.get_constant_value(binary.rhs, dfg)?;
+        let binary_type =
+            type_of_binary_operation(dfg[binary.lhs].get_type(), dfg[binary.rhs].get_type());
+        convert_ssa_binary_op_to_brillig_binary_op(binary.operator, &binary_type).evaluate(lhs, rhs)
+    }
+
+    /// Returns the value of an SSA `ValueId` if it is a numeric constant or was folded into one.
+    fn get_constant_value(&self, value_id: ValueId, dfg: &DataFlowGraph) -> Option<FieldElement> {
+        let value_id = dfg.resolve(value_id);
+        dfg.get_numeric_constant(value_id)
+            .or_else(|| self.function_context.folded_values.get(&value_id).copied())
+    }
+
     /// Converts an SSA `ValueId` into a `RegisterOrMemory`. Initializes if necessary.
     fn convert_ssa_value(&mut self, value_id: ValueId, dfg: &DataFlowGraph) -> BrilligVariable {
         let value_id = dfg.resolve(value_id);
@@ -1159,27 +1577,20 @@ impl<'block> BrilligBlock<'block> {
 
         match value {
             Value::Param { .. } | Value::Instruction { .. } => {
+                if let Some(constant) = self.function_context.folded_values.get(&value_id) {
+                    return self.convert_numeric_constant(
+                        value_id,
+                        *constant,
+                        value.get_type(),
+                        dfg,
+                    );
+                }
                 // All block parameters and instruction results should have already been
                 // converted to registers so we fetch from the cache.
                 self.variables.get_allocation(self.function_context, value_id, dfg)
             }
             Value::NumericConstant { constant, typ } => {
-                // Constants might have been converted previously or not, so we get or create and
-                // (re)initialize the value inside.
-                if let Some(variable) = self.variables.get_constant(value_id, dfg) {
-                    variable
-                } else {
-                    let new_variable =
-                        self.variables.allocate_constant(self.brillig_context, value_id, dfg);
-                    let register_index = new_variable.extract_register();
-
-                    self.brillig_context.const_instruction(
-                        register_index,
-                        (*constant).into(),
-                        get_bit_size_from_ssa_type(typ),
-                    );
-                    new_variable
-                }
+                self.convert_numeric_constant(value_id, *constant, typ, dfg)
             }
             Value::Array { array, .. } => {
                 if let Some(variable) = self.variables.get_constant(value_id, dfg) {
@@ -1199,6 +1610,7 @@ impl<'block> BrilligBlock<'block> {
                         }
                         BrilligVariable::BrilligVector(vector) => {
                             self.brillig_context.usize_const(vector.size, array.len().into());
+                            self.brillig_context.mov_instruction(vector.capacity, vector.size);
                             self.brillig_context
                                 .allocate_array_instruction(vector.pointer, vector.size);
                             self.brillig_context.usize_const(vector.rc, 1_usize.into());
@@ -1210,25 +1622,7 @@ impl<'block> BrilligBlock<'block> {
                         ),
                     };
 
-                    // Write the items
-
-                    // Allocate a register for the iterator
-                    let iterator_register =
-                        self.brillig_context.make_usize_constant(0_usize.into());
-
-                    for element_id in array.iter() {
-                        let element_variable = self.convert_ssa_value(*element_id, dfg);
-                        // Store the item in memory
-                        self.store_variable_in_array(pointer, iterator_register, element_variable);
-                        // Increment the iterator
-                        self.brillig_context.usize_op_in_place(
-                            iterator_register,
-                            BinaryIntOp::Add,
-                            1,
-                        );
-                    }
-
-                    self.brillig_context.deallocate_register(iterator_register);
+                    self.initialize_array_items(pointer, array, dfg);
 
                     new_variable
                 }
@@ -1239,6 +1633,77 @@ impl<'block> BrilligBlock<'block> {
         }
     }
 
+    /// Writes the values of `items` to the array pointed by `pointer`, filling runs of at least
+    /// [`MIN_FILLED_RUN`] identical values with a loop.
+    fn initialize_array_items(
+        &mut self,
+        pointer: MemoryAddress,
+        items: &im::Vector<ValueId>,
+        dfg: &DataFlowGraph,
+    ) {
+        let address = self.brillig_context.allocate_register();
+        self.brillig_context.mov_instruction(address, pointer);
+        let one = self.brillig_context.make_usize_constant(1_usize.into());
+
+        let mut index = 0;
+        while index < items.len() {
+            let item = items[index];
+            let run_length = items.iter().skip(index).take_while(|other| **other == item).count();
+            let variable = self.convert_ssa_value(item, dfg);
+            match variable {
+                BrilligVariable::Simple(value) if run_length >= MIN_FILLED_RUN => {
+                    let len = self.brillig_context.make_usize_constant(run_length.into());
+                    self.brillig_context.fill_array_instruction(address, len, value);
+                    self.brillig_context.memory_op(address, len, address, BinaryIntOp::Add);
+                    self.brillig_context.deallocate_register(len);
+                    index += run_length;
+                }
+                BrilligVariable::Simple(value) => {
+                    self.brillig_context.store_instruction(address, value);
+                    self.brillig_context.memory_op(address, one, address, BinaryIntOp::Add);
+                    index += 1;
+                }
+                BrilligVariable::BrilligArray(_) | BrilligVariable::BrilligVector(_) => {
+                    let reference =
+                        Self::store_variable_in_reference_with_ctx(self.brillig_context, variable);
+                    self.brillig_context.store_instruction(address, reference);
+                    self.brillig_context.deallocate_register(reference);
+                    self.brillig_context.memory_op(address, one, address, BinaryIntOp::Add);
+                    index += 1;
+                }
+            }
+        }
+
+        self.brillig_context.deallocate_register(address);
+        self.brillig_context.deallocate_register(one);
+    }
+
+    /// Converts a numeric constant, or a value folded into one, into a register holding it.
+    fn convert_numeric_constant(
+        &mut self,
+        value_id: ValueId,
+        constant: FieldElement,
+        typ: &Type,
+        dfg: &DataFlowGraph,
+    ) -> BrilligVariable {
+        // Constants might have been converted previously or not, so we get or create and
+        // (re)initialize the value inside.
+        if let Some(variable) = self.variables.get_constant(value_id, dfg) {
+            variable
+        } else {
+            let new_variable =
+                self.variables.allocate_constant(self.brillig_context, value_id, dfg);
+            let register_index = new_variable.extract_register();
+
+            self.brillig_context.const_instruction(
+                register_index,
+                constant.into(),
+                get_bit_size_from_ssa_type(typ),
+            );
+            new_variable
+        }
+    }
+
     /// Converts an SSA `ValueId` into a `MemoryAddress`. Initializes if necessary.
     fn convert_ssa_register_value(
         &mut self,
@@ -1430,3 +1895,256 @@ pub(crate) fn convert_ssa_binary_op_to_brillig_binary_op(
         None => binary_op_to_field_op(ssa_op),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use acvm::acir::brillig::{Opcode as BrilligOpcode, Value};
+    use acvm::acir::BlackBoxFunc;
+    use acvm::brillig_vm::{VMStatus, VM};
+
+    use crate::brillig::brillig_gen::brillig_fn::FunctionContext;
+    use crate::brillig::brillig_ir::{
+        artifact::BrilligParameter, tests::DummyBlackBoxSolver, BrilligContext, BrilligLimits,
+    };
+    use crate::brillig::BrilligOptions;
+    use crate::errors::RuntimeError;
+    use crate::ssa::{
+        function_builder::FunctionBuilder,
+        ir::{
+            function::RuntimeType,
+            instruction::{BinaryOp, Intrinsic},
+            map::Id,
+            types::Type,
+        },
+    };
+
+    #[test]
+    fn folds_binary_operations_on_constants() {
+        // fn main f0 {
+        //   b0():
+        //     v2 = add u32 2, u32 3
+        //     v4 = mul v2, u32 4
+        //     v6 = div v4, u32 0
+        //     return v4, v6
+        // }
+        let mut builder =
+            FunctionBuilder::new("main".to_string(), Id::test_new(0), RuntimeType::Brillig);
+        let two = builder.numeric_constant(2u128, Type::unsigned(32));
+        let three = builder.numeric_constant(3u128, Type::unsigned(32));
+        let v2 = builder.insert_binary(two, BinaryOp::Add, three);
+        let four = builder.numeric_constant(4u128, Type::unsigned(32));
+        let v4 = builder.insert_binary(v2, BinaryOp::Mul, four);
+        let zero = builder.numeric_constant(0u128, Type::unsigned(32));
+        // Dividing by zero fails at runtime, so it must not be folded.
+        let v6 = builder.insert_binary(v4, BinaryOp::Div, zero);
+        builder.terminate_with_return(vec![v4, v6]);
+        let ssa = builder.finish();
+
+        let byte_code = &ssa.to_brillig(&BrilligOptions::default()).unwrap()[ssa.main_id].byte_code;
+        let binary_ops: Vec<_> = byte_code
+            .iter()
+            .filter(|opcode| {
+                matches!(
+                    opcode,
+                    BrilligOpcode::BinaryIntOp { .. } | BrilligOpcode::BinaryFieldOp { .. }
+                )
+            })
+            .collect();
+        assert_eq!(binary_ops.len(), 1, "only the division should be computed at runtime");
+        assert!(byte_code.iter().any(|opcode| matches!(
+            opcode,
+            BrilligOpcode::Const { value, .. } if *value == 20_u128.into()
+        )));
+    }
+
+    #[test]
+    fn records_the_registers_of_named_values_while_they_are_live() {
+        // brillig fn main f0 {
+        //   b0(v0: u32):              // x
+        //     v1 = add v0, v0         // y
+        //     v2 = mul v1, v1
+        //     return v2
+        // }
+        let mut builder =
+            FunctionBuilder::new("main".to_string(), Id::test_new(0), RuntimeType::Brillig);
+        let v0 = builder.add_parameter(Type::unsigned(32));
+        builder.set_value_name(v0, "x".to_string());
+        let v1 = builder.insert_binary(v0, BinaryOp::Add, v0);
+        builder.set_value_name(v1, "y".to_string());
+        let v2 = builder.insert_binary(v1, BinaryOp::Mul, v1);
+        builder.terminate_with_return(vec![v2]);
+        let ssa = builder.finish();
+
+        let brillig = ssa.to_brillig(&BrilligOptions::default()).unwrap();
+        let artifact = &brillig[ssa.main_id];
+        let [x, y] = &artifact.variables[..] else {
+            panic!("expected `x` and `y` to be recorded, got {:?}", artifact.variables);
+        };
+        assert_eq!((x.name.as_str(), y.name.as_str()), ("x", "y"));
+        // `x` is last used by the addition which defines `y`.
+        assert_eq!(x.opcodes.end, y.opcodes.start);
+        let y_register = y.variable.extract_register();
+        assert!(artifact.byte_code[y.opcodes.clone()].iter().any(|opcode| matches!(
+            opcode,
+            BrilligOpcode::BinaryIntOp { lhs, rhs, .. } if *lhs == y_register && *rhs == y_register
+        )));
+    }
+
+    #[test]
+    fn reports_the_opcodes_of_each_function_by_kind() {
+        // brillig fn main f0 {
+        //   b0(v0: u32):
+        //     v1 = add v0, v0
+        //     v2 = mul v1, v1
+        //     return v2
+        // }
+        let mut builder =
+            FunctionBuilder::new("main".to_string(), Id::test_new(0), RuntimeType::Brillig);
+        let v0 = builder.add_parameter(Type::unsigned(32));
+        let v1 = builder.insert_binary(v0, BinaryOp::Add, v0);
+        let v2 = builder.insert_binary(v1, BinaryOp::Mul, v1);
+        builder.terminate_with_return(vec![v2]);
+        let ssa = builder.finish();
+
+        let brillig = ssa.to_brillig(&BrilligOptions::default()).unwrap();
+        let [size] = &brillig.function_sizes(&ssa)[..] else {
+            panic!("expected the size of `main` only");
+        };
+        assert_eq!(size.name, "main");
+        assert_eq!(size.opcodes, brillig[ssa.main_id].byte_code.len());
+        assert_eq!(size.opcode_counts.values().sum::<usize>(), size.opcodes);
+        assert!(size.opcode_counts["BINARY_INT_OP"] >= 2);
+        assert_eq!(size.opcode_counts["STOP"], 1);
+    }
+
+    /// Runs a function which calls itself forever with the given limits, returning the message of
+    /// the opcode it traps at and the depth of the call stack when it does.
+    fn run_runaway_recursion(limits: BrilligLimits) -> (String, usize) {
+        // brillig fn main f0 {
+        //   b0(v0: u32):
+        //     call f0(v0)
+        //     return
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".to_string(), main_id, RuntimeType::Brillig);
+        let v0 = builder.add_parameter(Type::unsigned(32));
+        let main = builder.import_function(main_id);
+        builder.insert_call(main, vec![v0], vec![]);
+        builder.terminate_with_return(vec![]);
+        let ssa = builder.finish();
+
+        let brillig =
+            ssa.to_brillig(&BrilligOptions { limits, ..BrilligOptions::default() }).unwrap();
+        let mut entry_point = BrilligContext::new_entry_point_artifact(
+            vec![BrilligParameter::Simple(32)],
+            vec![],
+            FunctionContext::function_id_to_function_label(ssa.main_id),
+        );
+        entry_point.link_with(&brillig[ssa.main_id]);
+        let generated_brillig = entry_point.finish();
+
+        let calldata = vec![Value::from(0_u128)];
+        let mut vm = VM::new(calldata, &generated_brillig.byte_code, vec![], &DummyBlackBoxSolver);
+        let VMStatus::Failure { call_stack, .. } = vm.process_opcodes() else {
+            panic!("the recursion should trap");
+        };
+        let message = generated_brillig.assert_messages[call_stack.last().unwrap()].clone();
+        (message, call_stack.len())
+    }
+
+    #[test]
+    fn runaway_recursion_traps_at_the_limits() {
+        let limits = BrilligLimits { max_call_depth: Some(10), max_memory: None };
+        let (message, depth) = run_runaway_recursion(limits);
+        assert_eq!(message, "Exceeded the maximum call depth of 10");
+        // The entry point calls `main`, which calls itself until its 11th call traps.
+        assert_eq!(depth, 12);
+
+        let limits = BrilligLimits { max_call_depth: None, max_memory: Some(2048) };
+        let (message, _) = run_runaway_recursion(limits);
+        assert_eq!(message, "Exceeded the maximum memory size of 2048");
+    }
+
+    #[test]
+    fn rejects_black_box_functions_missing_from_the_vm() {
+        // brillig fn main f0 {
+        //   b0(v0: [Field; 2], v1: [Field; 2]):
+        //     v3 = call multi_scalar_mul(v0, v1)
+        //     return v3
+        // }
+        let mut builder =
+            FunctionBuilder::new("main".to_string(), Id::test_new(0), RuntimeType::Brillig);
+        let v0 = builder.add_parameter(Type::Array(Rc::new(vec![Type::field()]), 2));
+        let v1 = builder.add_parameter(Type::Array(Rc::new(vec![Type::field()]), 2));
+        let multi_scalar_mul =
+            builder.import_intrinsic_id(Intrinsic::BlackBox(BlackBoxFunc::MultiScalarMul));
+        let output_type = Type::Array(Rc::new(vec![Type::field()]), 3);
+        let v3 = builder.insert_call(multi_scalar_mul, vec![v0, v1], vec![output_type])[0];
+        builder.terminate_with_return(vec![v3]);
+        let ssa = builder.finish();
+
+        let result = ssa.to_brillig(&BrilligOptions::default());
+        assert!(matches!(
+            result,
+            Err(RuntimeError::UnsupportedBrilligBlackBox {
+                name: BlackBoxFunc::MultiScalarMul,
+                ..
+            })
+        ));
+    }
+
+    /// Runs a function applying `operator` to two `u8` arguments and range checking its result,
+    /// as constrained code checks for overflows, returning the message of the opcode it traps at
+    /// if it does.
+    fn run_checked_binary(operator: BinaryOp, lhs: u128, rhs: u128) -> Option<String> {
+        // brillig fn main f0 {
+        //   b0(v0: u8, v1: u8):
+        //     v2 = operator v0, v1
+        //     range_check v2 to 8 bits, "overflow"
+        //     return v2
+        // }
+        let mut builder =
+            FunctionBuilder::new("main".to_string(), Id::test_new(0), RuntimeType::Brillig);
+        let v0 = builder.add_parameter(Type::unsigned(8));
+        let v1 = builder.add_parameter(Type::unsigned(8));
+        let v2 = builder.insert_binary(v0, operator, v1);
+        builder.insert_range_check(v2, 8, Some("overflow".to_string()));
+        builder.terminate_with_return(vec![v2]);
+        let ssa = builder.finish();
+
+        let brillig = ssa
+            .to_brillig(&BrilligOptions { check_overflows: true, ..BrilligOptions::default() })
+            .unwrap();
+        let mut entry_point = BrilligContext::new_entry_point_artifact(
+            vec![BrilligParameter::Simple(8), BrilligParameter::Simple(8)],
+            vec![BrilligParameter::Simple(8)],
+            FunctionContext::function_id_to_function_label(ssa.main_id),
+        );
+        entry_point.link_with(&brillig[ssa.main_id]);
+        let generated_brillig = entry_point.finish();
+
+        let calldata = vec![Value::from(lhs), Value::from(rhs)];
+        let mut vm = VM::new(calldata, &generated_brillig.byte_code, vec![], &DummyBlackBoxSolver);
+        match vm.process_opcodes() {
+            VMStatus::Finished { .. } => None,
+            VMStatus::Failure { call_stack, .. } => {
+                Some(generated_brillig.assert_messages[call_stack.last().unwrap()].clone())
+            }
+            status => panic!("unexpected status {status:?}"),
+        }
+    }
+
+    #[test]
+    fn checked_operations_trap_when_they_wrap_around() {
+        let overflow = Some("overflow".to_string());
+        assert_eq!(run_checked_binary(BinaryOp::Add, 100, 155), None);
+        assert_eq!(run_checked_binary(BinaryOp::Add, 100, 156), overflow);
+        assert_eq!(run_checked_binary(BinaryOp::Sub, 3, 3), None);
+        assert_eq!(run_checked_binary(BinaryOp::Sub, 3, 4), overflow);
+        assert_eq!(run_checked_binary(BinaryOp::Mul, 15, 17), None);
+        assert_eq!(run_checked_binary(BinaryOp::Mul, 200, 0), None);
+        assert_eq!(run_checked_binary(BinaryOp::Mul, 16, 16), overflow);
+    }
+}
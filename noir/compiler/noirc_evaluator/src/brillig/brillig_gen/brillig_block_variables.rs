@@ -31,13 +31,16 @@ impl BlockVariables {
         }
     }
 
-    /// Returns all non-constant variables that have not been removed at this point.
+    /// Returns all non-constant variables that have not been removed at this point, in order of
+    /// their value id so that the registers saved around calls do not depend on hashing order.
     pub(crate) fn get_available_variables(
         &self,
         function_context: &FunctionContext,
     ) -> Vec<BrilligVariable> {
-        self.available_variables
-            .iter()
+        let mut available_variables: Vec<_> = self.available_variables.iter().collect();
+        available_variables.sort();
+        available_variables
+            .into_iter()
             .map(|value_id| {
                 function_context
                     .ssa_value_allocations
@@ -192,11 +195,13 @@ pub(crate) fn allocate_value(
             let pointer_register = brillig_context.allocate_register();
             let size_register = brillig_context.allocate_register();
             let rc_register = brillig_context.allocate_register();
+            let capacity_register = brillig_context.allocate_register();
 
             BrilligVariable::BrilligVector(BrilligVector {
                 pointer: pointer_register,
                 size: size_register,
                 rc: rc_register,
+                capacity: capacity_register,
             })
         }
         Type::Function => {
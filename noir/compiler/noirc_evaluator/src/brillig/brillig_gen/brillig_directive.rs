@@ -3,7 +3,21 @@ use acvm::{
     FieldElement,
 };
 
-use crate::brillig::brillig_ir::artifact::GeneratedBrillig;
+use crate::brillig::brillig_ir::{
+    artifact::GeneratedBrillig, switch_count, BrilligContext, ReservedRegisters, MAX_STACK_SIZE,
+};
+
+/// Wraps `byte_code` which never jumps backwards, so that each of its opcodes is executed at most once.
+fn forward_only(byte_code: Vec<BrilligOpcode>) -> GeneratedBrillig {
+    GeneratedBrillig {
+        step_bound: Some(byte_code.len()),
+        byte_code,
+        assert_messages: Default::default(),
+        locations: Default::default(),
+        variables: Vec::new(),
+        profile_counters: Default::default(),
+    }
+}
 
 /// Generates brillig bytecode which computes the inverse of its input if not null, and zero else.
 pub(crate) fn directive_invert() -> GeneratedBrillig {
@@ -19,29 +33,79 @@ pub(crate) fn directive_invert() -> GeneratedBrillig {
     // Location of the stop opcode
     let stop_location = 3;
 
-    GeneratedBrillig {
-        byte_code: vec![
-            BrilligOpcode::CalldataCopy { destination_address: input, size: 1, offset: 0 },
-            // If the input is zero, then we jump to the stop opcode
-            BrilligOpcode::JumpIfNot { condition: input, location: stop_location },
-            // Put value one in register (1)
-            BrilligOpcode::Const {
-                destination: one_const,
-                value: Value::from(1_usize),
-                bit_size: FieldElement::max_num_bits(),
-            },
-            // Divide 1 by the input, and set the result of the division into register (0)
-            BrilligOpcode::BinaryFieldOp {
-                op: BinaryFieldOp::Div,
-                lhs: one_const,
-                rhs: input,
-                destination: input,
-            },
-            BrilligOpcode::Stop { return_data_offset: 0, return_data_size: 1 },
-        ],
-        assert_messages: Default::default(),
-        locations: Default::default(),
+    forward_only(vec![
+        BrilligOpcode::CalldataCopy { destination_address: input, size: 1, offset: 0 },
+        // If the input is zero, then we jump to the stop opcode
+        BrilligOpcode::JumpIfNot { condition: input, location: stop_location },
+        // Put value one in register (1)
+        BrilligOpcode::Const {
+            destination: one_const,
+            value: Value::from(1_usize),
+            bit_size: FieldElement::max_num_bits(),
+        },
+        // Divide 1 by the input, and set the result of the division into register (0)
+        BrilligOpcode::BinaryFieldOp {
+            op: BinaryFieldOp::Div,
+            lhs: one_const,
+            rhs: input,
+            destination: input,
+        },
+        BrilligOpcode::Stop { return_data_offset: 0, return_data_size: 1 },
+    ])
+}
+
+/// Generates brillig bytecode which computes the inverses of its `len` inputs, and zero for the
+/// inputs which are null.
+///
+/// Montgomery's trick is used so that only the product of the non-null inputs is inverted. The
+/// inverse of each input is derived from it and from the products of the inputs before it.
+pub(crate) fn directive_invert_batch(len: usize) -> GeneratedBrillig {
+    // The inputs are copied into registers (0) to (len - 1), where their inverses are stored too.
+    let input: fn(usize) -> MemoryAddress = MemoryAddress::from;
+    // The product of the non-null inputs up to each input.
+    let prefix = |index: usize| MemoryAddress::from(len + index);
+    let is_zero = |index: usize| MemoryAddress::from(2 * len + index);
+    let zero_const = MemoryAddress::from(3 * len);
+    let one_const = MemoryAddress::from(3 * len + 1);
+    // The inverse of the product of the inputs which are not inverted yet.
+    let inverse = MemoryAddress::from(3 * len + 2);
+    let input_inverse = MemoryAddress::from(3 * len + 3);
+    let bit_size = FieldElement::max_num_bits();
+    let field_op =
+        |op, lhs, rhs, destination| BrilligOpcode::BinaryFieldOp { op, lhs, rhs, destination };
+
+    let mut byte_code = vec![
+        BrilligOpcode::CalldataCopy { destination_address: input(0), size: len, offset: 0 },
+        BrilligOpcode::Const { destination: zero_const, value: Value::from(0_usize), bit_size },
+        BrilligOpcode::Const { destination: one_const, value: Value::from(1_usize), bit_size },
+    ];
+    for index in 0..len {
+        // A null input is replaced with one, so that it does not cancel the product.
+        byte_code.push(field_op(BinaryFieldOp::Equals, input(index), zero_const, is_zero(index)));
+        byte_code.push(field_op(BinaryFieldOp::Add, input(index), is_zero(index), input(index)));
+        byte_code.push(match index {
+            0 => BrilligOpcode::Mov { destination: prefix(0), source: input(0) },
+            _ => field_op(BinaryFieldOp::Mul, prefix(index - 1), input(index), prefix(index)),
+        });
     }
+    if len > 0 {
+        byte_code.push(field_op(BinaryFieldOp::Div, one_const, prefix(len - 1), inverse));
+    }
+    for index in (0..len).rev() {
+        // 1 / input = (1 / prefix(index)) * prefix(index - 1)
+        if index == 0 {
+            byte_code.push(BrilligOpcode::Mov { destination: input_inverse, source: inverse });
+        } else {
+            byte_code.push(field_op(BinaryFieldOp::Mul, inverse, prefix(index - 1), input_inverse));
+            byte_code.push(field_op(BinaryFieldOp::Mul, inverse, input(index), inverse));
+        }
+        // Null inputs have a null inverse.
+        byte_code.push(field_op(BinaryFieldOp::Sub, one_const, is_zero(index), is_zero(index)));
+        byte_code.push(field_op(BinaryFieldOp::Mul, input_inverse, is_zero(index), input(index)));
+    }
+    byte_code.push(BrilligOpcode::Stop { return_data_offset: 0, return_data_size: len });
+
+    forward_only(byte_code)
 }
 
 /// Generates brillig bytecode which computes `a / b` and returns the quotient and remainder.
@@ -56,45 +120,263 @@ pub(crate) fn directive_invert() -> GeneratedBrillig {
 pub(crate) fn directive_quotient(bit_size: u32) -> GeneratedBrillig {
     // `a` is (0) (i.e register index 0)
     // `b` is (1)
-    GeneratedBrillig {
-        byte_code: vec![
-            BrilligOpcode::CalldataCopy {
-                destination_address: MemoryAddress::from(0),
-                size: 2,
-                offset: 0,
-            },
-            //q = a/b is set into register (2)
-            BrilligOpcode::BinaryIntOp {
-                op: BinaryIntOp::UnsignedDiv,
-                lhs: MemoryAddress::from(0),
-                rhs: MemoryAddress::from(1),
-                destination: MemoryAddress::from(2),
-                bit_size,
-            },
-            //(1)= q*b
-            BrilligOpcode::BinaryIntOp {
-                op: BinaryIntOp::Mul,
-                lhs: MemoryAddress::from(2),
-                rhs: MemoryAddress::from(1),
-                destination: MemoryAddress::from(1),
-                bit_size,
-            },
-            //(1) = a-q*b
-            BrilligOpcode::BinaryIntOp {
-                op: BinaryIntOp::Sub,
-                lhs: MemoryAddress::from(0),
-                rhs: MemoryAddress::from(1),
-                destination: MemoryAddress::from(1),
+    forward_only(vec![
+        BrilligOpcode::CalldataCopy {
+            destination_address: MemoryAddress::from(0),
+            size: 2,
+            offset: 0,
+        },
+        //q = a/b is set into register (2)
+        BrilligOpcode::BinaryIntOp {
+            op: BinaryIntOp::UnsignedDiv,
+            lhs: MemoryAddress::from(0),
+            rhs: MemoryAddress::from(1),
+            destination: MemoryAddress::from(2),
+            bit_size,
+        },
+        //(1)= q*b
+        BrilligOpcode::BinaryIntOp {
+            op: BinaryIntOp::Mul,
+            lhs: MemoryAddress::from(2),
+            rhs: MemoryAddress::from(1),
+            destination: MemoryAddress::from(1),
+            bit_size,
+        },
+        //(1) = a-q*b
+        BrilligOpcode::BinaryIntOp {
+            op: BinaryIntOp::Sub,
+            lhs: MemoryAddress::from(0),
+            rhs: MemoryAddress::from(1),
+            destination: MemoryAddress::from(1),
+            bit_size,
+        },
+        //(0) = q
+        BrilligOpcode::Mov { destination: MemoryAddress::from(0), source: MemoryAddress::from(2) },
+        BrilligOpcode::Stop { return_data_offset: 0, return_data_size: 2 },
+    ])
+}
+
+/// Generates brillig bytecode which decomposes its input into `limb_count` little-endian limbs of
+/// the given `radix`.
+///
+/// Limbs past the most significant digit of the input are zero, and digits past the last limb are
+/// dropped, which the caller must rule out by constraining the limbs to recompose to the input.
+pub(crate) fn directive_to_radix(radix: u32, limb_count: usize) -> GeneratedBrillig {
+    let mut context = BrilligContext::new(false);
+    context.enter_context("to_radix");
+
+    // The limbs are written past the registers, where they are returned from.
+    let limbs_address = MAX_STACK_SIZE;
+    let value = context.allocate_register();
+    context.push_opcode(BrilligOpcode::CalldataCopy {
+        destination_address: value,
+        size: 1,
+        offset: 0,
+    });
+    let radix = context.make_constant((radix as usize).into(), FieldElement::max_num_bits());
+    // The decomposition is unrolled, as the number of limbs is known.
+    for limb in 0..limb_count {
+        context.radix_limb_instruction(MemoryAddress::from(limbs_address + limb), value, radix);
+    }
+    context.push_opcode(BrilligOpcode::Stop {
+        return_data_offset: limbs_address,
+        return_data_size: limb_count,
+    });
+
+    // The unrolled decomposition never jumps.
+    let artifact = context.artifact();
+    let step_bound = artifact.max_steps(0..artifact.index_of_next_opcode(), 0);
+    let mut generated_brillig = artifact.finish();
+    generated_brillig.step_bound = step_bound;
+    generated_brillig
+}
+
+/// Generates brillig bytecode which sorts its `len` inputs of `bit_size` bits in increasing order.
+///
+/// The inputs are sorted in place using a fully unrolled bubble sort.
+pub(crate) fn directive_sort(len: usize, bit_size: u32) -> GeneratedBrillig {
+    // The inputs are copied into registers (0) to (len - 1).
+    let is_out_of_order = MemoryAddress::from(len);
+    let temp = MemoryAddress::from(len + 1);
+
+    let mut byte_code = vec![BrilligOpcode::CalldataCopy {
+        destination_address: MemoryAddress::from(0),
+        size: len,
+        offset: 0,
+    }];
+    for sorted_count in 0..len {
+        for index in 1..len - sorted_count {
+            let lhs = MemoryAddress::from(index - 1);
+            let rhs = MemoryAddress::from(index);
+            byte_code.push(BrilligOpcode::BinaryIntOp {
+                op: BinaryIntOp::LessThan,
+                lhs: rhs,
+                rhs: lhs,
+                destination: is_out_of_order,
                 bit_size,
-            },
-            //(0) = q
-            BrilligOpcode::Mov {
-                destination: MemoryAddress::from(0),
-                source: MemoryAddress::from(2),
-            },
-            BrilligOpcode::Stop { return_data_offset: 0, return_data_size: 2 },
-        ],
-        assert_messages: Default::default(),
-        locations: Default::default(),
+            });
+            // Skip over the swap below if the pair is already in order
+            let swap_end = byte_code.len() + 4;
+            byte_code
+                .push(BrilligOpcode::JumpIfNot { condition: is_out_of_order, location: swap_end });
+            byte_code.push(BrilligOpcode::Mov { destination: temp, source: lhs });
+            byte_code.push(BrilligOpcode::Mov { destination: lhs, source: rhs });
+            byte_code.push(BrilligOpcode::Mov { destination: rhs, source: temp });
+        }
+    }
+    byte_code.push(BrilligOpcode::Stop { return_data_offset: 0, return_data_size: len });
+
+    forward_only(byte_code)
+}
+
+/// Generates brillig bytecode which returns the control bits of the switches of the sorting
+/// network over its `len` inputs which sorts them in increasing order.
+pub(crate) fn directive_permutation_routing(len: usize) -> GeneratedBrillig {
+    let mut context = BrilligContext::new(false);
+    context.enter_context("permutation_routing");
+
+    // The inputs are copied past the registers, followed by the bits, followed by the heap.
+    let num_bits = switch_count(len);
+    let values_address = MAX_STACK_SIZE;
+    let bits_address = MAX_STACK_SIZE + len;
+    context.usize_const(ReservedRegisters::stack_pointer(), (bits_address + num_bits).into());
+    context.push_opcode(BrilligOpcode::CalldataCopy {
+        destination_address: MemoryAddress::from(values_address),
+        size: len,
+        offset: 0,
+    });
+    let values = context.make_usize_constant(values_address.into());
+    let bits = context.make_usize_constant(bits_address.into());
+    context.permutation_routing_instruction(values, len, bits);
+    context.push_opcode(BrilligOpcode::Stop {
+        return_data_offset: bits_address,
+        return_data_size: num_bits,
+    });
+
+    // No loop of the routing iterates more than once per input.
+    let artifact = context.artifact();
+    let step_bound = artifact.max_steps(0..artifact.index_of_next_opcode(), len);
+    let mut generated_brillig = artifact.finish();
+    generated_brillig.step_bound = step_bound;
+    generated_brillig
+}
+
+/// Generates brillig bytecode which returns whether its input is a square, along with a square
+/// root of it or, if it is not a square, of its product with a fixed quadratic non-residue.
+pub(crate) fn directive_field_sqrt() -> GeneratedBrillig {
+    let mut context = BrilligContext::new(false);
+    context.enter_context("field_sqrt");
+
+    let input = context.allocate_register();
+    context.push_opcode(BrilligOpcode::CalldataCopy {
+        destination_address: input,
+        size: 1,
+        offset: 0,
+    });
+    let is_square = context.allocate_register();
+    let root = context.allocate_register();
+    context.field_sqrt_instruction(input, is_square, root);
+    context.mov_instruction(MemoryAddress::from(0), is_square);
+    context.mov_instruction(MemoryAddress::from(1), root);
+    context.push_opcode(BrilligOpcode::Stop { return_data_offset: 0, return_data_size: 2 });
+
+    // Every loop of the algorithm has a known bound.
+    let artifact = context.artifact();
+    let step_bound = artifact.max_steps(0..artifact.index_of_next_opcode(), 0);
+    let mut generated_brillig = artifact.finish();
+    generated_brillig.step_bound = step_bound;
+    generated_brillig
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::brillig::Opcode as BrilligOpcode;
+    use acvm::brillig_vm::{VMStatus, VM};
+    use acvm::FieldElement;
+
+    use crate::brillig::brillig_ir::brillig_variable::BrilligVector;
+    use crate::brillig::brillig_ir::tests::{
+        create_and_run_vm, create_context, DummyBlackBoxSolver,
+    };
+    use crate::brillig::brillig_ir::{ReservedRegisters, MAX_STACK_SIZE};
+
+    use super::{directive_invert_batch, directive_to_radix};
+
+    fn run_to_radix(value: u128, radix: u32, limb_count: usize) -> Vec<FieldElement> {
+        let directive = directive_to_radix(radix, limb_count);
+        let value = FieldElement::from(value);
+        let mut vm =
+            VM::new(vec![value.into()], &directive.byte_code, vec![], &DummyBlackBoxSolver);
+        let VMStatus::Finished { return_data_offset, return_data_size } = vm.process_opcodes()
+        else {
+            panic!("VM should have finished")
+        };
+        vm.get_memory()[return_data_offset..return_data_offset + return_data_size]
+            .iter()
+            .map(|limb| limb.to_field())
+            .collect()
+    }
+
+    #[test]
+    fn decomposes_into_little_endian_limbs() {
+        let expected = [4_u128, 3, 2, 1].map(FieldElement::from);
+        assert_eq!(run_to_radix(0x0102_0304, 256, 4), expected);
+
+        let expected = [5_u128, 4, 3, 0].map(FieldElement::from);
+        assert_eq!(run_to_radix(345, 10, 4), expected);
+    }
+
+    #[test]
+    fn unconstrained_decompositions_match_the_directive() {
+        let (value, radix, limb_count) = (0x0fed_cba9_8765_4321_u128, 7_usize, 24);
+
+        let mut context = create_context();
+        context.usize_const(ReservedRegisters::stack_pointer(), MAX_STACK_SIZE.into());
+        let source = context.allocate_register();
+        context.push_opcode(BrilligOpcode::CalldataCopy {
+            destination_address: source,
+            size: 1,
+            offset: 0,
+        });
+        let vector = BrilligVector {
+            pointer: context.allocate_register(),
+            size: context.allocate_register(),
+            rc: context.allocate_register(),
+            capacity: context.allocate_register(),
+        };
+        let radix_register = context.make_constant(radix.into(), FieldElement::max_num_bits());
+        let limb_count_register = context.make_usize_constant(limb_count.into());
+        context.radix_instruction(source, vector, radix_register, limb_count_register, false);
+        context.push_opcode(BrilligOpcode::Stop {
+            return_data_offset: MAX_STACK_SIZE,
+            return_data_size: limb_count,
+        });
+        let bytecode = context.artifact().finish().byte_code;
+
+        let (vm, return_data_offset, return_data_size) =
+            create_and_run_vm(vec![FieldElement::from(value).into()], &bytecode);
+        let limbs: Vec<_> = vm.get_memory()
+            [return_data_offset..return_data_offset + return_data_size]
+            .iter()
+            .map(|limb| limb.to_field())
+            .collect();
+        assert_eq!(limbs, run_to_radix(value, radix as u32, limb_count));
+    }
+
+    #[test]
+    fn inverts_each_input_of_a_batch() {
+        let inputs = [2_i128, 0, 5, -1, 7].map(FieldElement::from);
+        let directive = directive_invert_batch(inputs.len());
+        let calldata = inputs.iter().map(|input| (*input).into()).collect();
+        let mut vm = VM::new(calldata, &directive.byte_code, vec![], &DummyBlackBoxSolver);
+        let VMStatus::Finished { return_data_offset, return_data_size } = vm.process_opcodes()
+        else {
+            panic!("VM should have finished")
+        };
+        assert_eq!(return_data_size, inputs.len());
+        let inverses = &vm.get_memory()[return_data_offset..return_data_offset + return_data_size];
+        for (input, inverse) in inputs.iter().zip(inverses) {
+            assert_eq!(inverse.to_field(), input.inverse());
+        }
     }
 }
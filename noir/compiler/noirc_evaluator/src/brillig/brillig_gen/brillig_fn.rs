@@ -10,9 +10,10 @@ use crate::{
     ssa::ir::{
         basic_block::BasicBlockId,
         function::{Function, FunctionId},
+        instruction::{Binary, BinaryOp, Instruction, InstructionId},
         post_order::PostOrder,
         types::{NumericType, Type},
-        value::ValueId,
+        value::{Value, ValueId},
     },
 };
 use fxhash::{FxHashMap as HashMap, FxHashSet as HashSet};
@@ -29,6 +30,11 @@ pub(crate) struct FunctionContext {
     pub(crate) blocks: Vec<BasicBlockId>,
     /// Liveness information for each variable in the function.
     pub(crate) liveness: VariableLiveness,
+    /// The values of the instruction results which were computed at compile time, so that no
+    /// register is allocated for them and they are initialized as constants wherever they are used.
+    pub(crate) folded_values: HashMap<ValueId, FieldElement>,
+    /// The binary instructions which trap when they overflow, with the message to trap with.
+    pub(crate) overflow_checks: HashMap<InstructionId, Option<String>>,
 }
 
 impl FunctionContext {
@@ -53,12 +59,20 @@ impl FunctionContext {
             block_parameters.insert(block_id, parameters);
         }
 
+        let overflow_checks = if brillig_context.checks_overflows() {
+            find_overflow_checks(function)
+        } else {
+            HashMap::default()
+        };
+
         Self {
             function_id: id,
             ssa_value_allocations: ssa_variable_to_register_or_memory,
             block_parameters,
             blocks: reverse_post_order,
             liveness: VariableLiveness::from_function(function),
+            folded_values: HashMap::default(),
+            overflow_checks,
         }
     }
 
@@ -124,3 +138,36 @@ pub(crate) fn get_bit_size_from_ssa_type(typ: &Type) -> u32 {
         _ => unreachable!("ICE bitwise not on a non numeric type"),
     }
 }
+
+/// Returns the unsigned additions, subtractions and multiplications whose results are range
+/// checked to their bit size, along with the message of the range check.
+///
+/// Brillig reduces the results of these operations modulo their bit size, so that the range
+/// checks always pass even when the unreduced results of constrained code would fail them.
+fn find_overflow_checks(function: &Function) -> HashMap<InstructionId, Option<String>> {
+    let dfg = &function.dfg;
+    let mut overflow_checks = HashMap::default();
+    for block in function.reachable_blocks() {
+        for instruction_id in dfg[block].instructions() {
+            let Instruction::RangeCheck { value, max_bit_size, assert_message } =
+                &dfg[*instruction_id]
+            else {
+                continue;
+            };
+            let Value::Instruction { instruction, .. } = &dfg[dfg.resolve(*value)] else {
+                continue;
+            };
+            let Instruction::Binary(Binary {
+                operator: BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul,
+                ..
+            }) = &dfg[*instruction]
+            else {
+                continue;
+            };
+            if dfg.type_of_value(*value) == Type::unsigned(*max_bit_size) {
+                overflow_checks.insert(*instruction, assert_message.clone());
+            }
+        }
+    }
+    overflow_checks
+}
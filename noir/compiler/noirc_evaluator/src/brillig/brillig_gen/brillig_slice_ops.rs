@@ -1,6 +1,16 @@
+//! The slice operations of unconstrained code.
+//!
+//! An operation writes the target vector over the source one when it is allowed to reuse it, the
+//! source being dead after the operation, and at runtime nothing else references the source and it
+//! has the capacity for the target. Otherwise the target is a new allocation, with twice the
+//! capacity it needs if it grows, so that a slice built by successive pushes is only copied a
+//! logarithmic number of times.
 use acvm::brillig_vm::brillig::{BinaryIntOp, MemoryAddress};
 
-use crate::brillig::brillig_ir::brillig_variable::{BrilligVariable, BrilligVector};
+use crate::brillig::brillig_ir::{
+    brillig_variable::{BrilligVariable, BrilligVector},
+    BrilligContext,
+};
 
 use super::brillig_block::BrilligBlock;
 
@@ -10,23 +20,22 @@ impl<'block> BrilligBlock<'block> {
         target_vector: BrilligVector,
         source_vector: BrilligVector,
         variables_to_insert: &[BrilligVariable],
+        reuse_source: bool,
     ) {
-        // First we need to allocate the target vector incrementing the size by variables_to_insert.len()
+        // First we need to compute the size of the target vector incrementing the size by variables_to_insert.len()
         self.brillig_context.usize_op(
             source_vector.size,
             target_vector.size,
             BinaryIntOp::Add,
             variables_to_insert.len(),
         );
-        self.brillig_context.allocate_array_instruction(target_vector.pointer, target_vector.size);
-        // We initialize the RC of the target vector to 1
-        self.brillig_context.usize_const(target_vector.rc, 1_usize.into());
-
-        // Now we copy the source vector into the target vector
-        self.brillig_context.copy_array_instruction(
-            source_vector.pointer,
-            target_vector.pointer,
+        // Then either reuse the source vector or copy it into a larger one
+        self.reuse_or_reallocate_vector(
+            target_vector,
+            source_vector,
             source_vector.size,
+            true,
+            reuse_source,
         );
 
         for (index, variable) in variables_to_insert.iter().enumerate() {
@@ -47,42 +56,18 @@ impl<'block> BrilligBlock<'block> {
         target_vector: BrilligVector,
         source_vector: BrilligVector,
         variables_to_insert: &[BrilligVariable],
+        reuse_source: bool,
     ) {
-        // First we need to allocate the target vector incrementing the size by variables_to_insert.len()
-        self.brillig_context.usize_op(
-            source_vector.size,
-            target_vector.size,
-            BinaryIntOp::Add,
-            variables_to_insert.len(),
-        );
-        self.brillig_context.allocate_array_instruction(target_vector.pointer, target_vector.size);
-        // We initialize the RC of the target vector to 1
-        self.brillig_context.usize_const(target_vector.rc, 1_usize.into());
-
-        // Now we offset the target pointer by variables_to_insert.len()
-        let destination_copy_pointer = self.brillig_context.allocate_register();
-        self.brillig_context.usize_op(
-            target_vector.pointer,
-            destination_copy_pointer,
-            BinaryIntOp::Add,
-            variables_to_insert.len(),
-        );
-
-        // Now we copy the source vector into the target vector starting at index variables_to_insert.len()
-        self.brillig_context.copy_array_instruction(
-            source_vector.pointer,
-            destination_copy_pointer,
-            source_vector.size,
+        // Pushing to the front is inserting at index zero
+        let index = self.brillig_context.make_usize_constant(0_usize.into());
+        self.slice_insert_operation(
+            target_vector,
+            source_vector,
+            index,
+            variables_to_insert,
+            reuse_source,
         );
-
-        // Then we write the items to insert at the start
-        for (index, variable) in variables_to_insert.iter().enumerate() {
-            let target_index = self.brillig_context.make_usize_constant(index.into());
-            self.store_variable_in_array(target_vector.pointer, target_index, *variable);
-            self.brillig_context.deallocate_register(target_index);
-        }
-
-        self.brillig_context.deallocate_register(destination_copy_pointer);
+        self.brillig_context.deallocate_register(index);
     }
 
     pub(crate) fn slice_pop_front_operation(
@@ -90,17 +75,15 @@ impl<'block> BrilligBlock<'block> {
         target_vector: BrilligVector,
         source_vector: BrilligVector,
         removed_items: &[BrilligVariable],
+        reuse_source: bool,
     ) {
-        // First we need to allocate the target vector decrementing the size by removed_items.len()
+        // First we need to compute the size of the target vector decrementing the size by removed_items.len()
         self.brillig_context.usize_op(
             source_vector.size,
             target_vector.size,
             BinaryIntOp::Sub,
             removed_items.len(),
         );
-        self.brillig_context.allocate_array_instruction(target_vector.pointer, target_vector.size);
-        // We initialize the RC of the target vector to 1
-        self.brillig_context.usize_const(target_vector.rc, 1_usize.into());
 
         // Now we offset the source pointer by removed_items.len()
         let source_copy_pointer = self.brillig_context.allocate_register();
@@ -111,12 +94,37 @@ impl<'block> BrilligBlock<'block> {
             removed_items.len(),
         );
 
-        // Now we copy the source vector starting at index removed_items.len() into the target vector
-        self.brillig_context.copy_array_instruction(
-            source_copy_pointer,
-            target_vector.pointer,
-            target_vector.size,
-        );
+        // The target vector either starts after the removed items of the source vector, or is a
+        // copy of the source vector starting at index removed_items.len()
+        let reallocate = |ctx: &mut BrilligContext| {
+            ctx.allocate_array_instruction(target_vector.pointer, target_vector.size);
+            ctx.mov_instruction(target_vector.capacity, target_vector.size);
+            ctx.copy_array_instruction(
+                source_copy_pointer,
+                target_vector.pointer,
+                target_vector.size,
+            );
+        };
+        if reuse_source {
+            let condition = self.reuse_condition(source_vector, None);
+            self.brillig_context.branch_instruction(condition, |ctx, reuse| {
+                if reuse {
+                    ctx.mov_instruction(target_vector.pointer, source_copy_pointer);
+                    ctx.usize_op(
+                        source_vector.capacity,
+                        target_vector.capacity,
+                        BinaryIntOp::Sub,
+                        removed_items.len(),
+                    );
+                } else {
+                    reallocate(ctx);
+                }
+            });
+            self.brillig_context.deallocate_register(condition);
+        } else {
+            reallocate(self.brillig_context);
+        }
+        self.brillig_context.usize_const(target_vector.rc, 1_usize.into());
 
         for (index, variable) in removed_items.iter().enumerate() {
             let target_index = self.brillig_context.make_usize_constant(index.into());
@@ -132,25 +140,25 @@ impl<'block> BrilligBlock<'block> {
         target_vector: BrilligVector,
         source_vector: BrilligVector,
         removed_items: &[BrilligVariable],
+        reuse_source: bool,
     ) {
-        // First we need to allocate the target vector decrementing the size by removed_items.len()
+        // First we need to compute the size of the target vector decrementing the size by removed_items.len()
         self.brillig_context.usize_op(
             source_vector.size,
             target_vector.size,
             BinaryIntOp::Sub,
             removed_items.len(),
         );
-        self.brillig_context.allocate_array_instruction(target_vector.pointer, target_vector.size);
-        // We initialize the RC of the target vector to 1
-        self.brillig_context.usize_const(target_vector.rc, 1_usize.into());
-
-        // Now we copy all elements except the last items into the target vector
-        self.brillig_context.copy_array_instruction(
-            source_vector.pointer,
-            target_vector.pointer,
+        // Then either reuse the source vector or copy all elements except the last items
+        self.reuse_or_reallocate_vector(
+            target_vector,
+            source_vector,
             target_vector.size,
+            false,
+            reuse_source,
         );
 
+        // The removed items are left untouched in the source vector either way
         for (index, variable) in removed_items.iter().enumerate() {
             let target_index = self.brillig_context.make_usize_constant(index.into());
             self.brillig_context.memory_op(
@@ -170,24 +178,18 @@ impl<'block> BrilligBlock<'block> {
         source_vector: BrilligVector,
         index: MemoryAddress,
         items: &[BrilligVariable],
+        reuse_source: bool,
     ) {
-        // First we need to allocate the target vector incrementing the size by items.len()
+        // First we need to compute the size of the target vector incrementing the size by items.len()
         self.brillig_context.usize_op(
             source_vector.size,
             target_vector.size,
             BinaryIntOp::Add,
             items.len(),
         );
-        self.brillig_context.allocate_array_instruction(target_vector.pointer, target_vector.size);
-        // We initialize the RC of the target vector to 1
-        self.brillig_context.usize_const(target_vector.rc, 1_usize.into());
-
-        // Copy the elements to the left of the index
-        self.brillig_context.copy_array_instruction(
-            source_vector.pointer,
-            target_vector.pointer,
-            index,
-        );
+        // Then either reuse the source vector or copy the elements to the left of the index into a
+        // larger one
+        self.reuse_or_reallocate_vector(target_vector, source_vector, index, true, reuse_source);
 
         // Compute the source pointer just at the index
         let source_pointer_at_index = self.brillig_context.allocate_register();
@@ -216,8 +218,9 @@ impl<'block> BrilligBlock<'block> {
         let item_count = self.brillig_context.allocate_register();
         self.brillig_context.memory_op(source_vector.size, index, item_count, BinaryIntOp::Sub);
 
-        // Copy the elements to the right of the index
-        self.brillig_context.copy_array_instruction(
+        // Copy the elements to the right of the index, which are shifted in place when the source
+        // vector is reused
+        self.brillig_context.copy_array_backward_instruction(
             source_pointer_at_index,
             target_pointer_after_index,
             item_count,
@@ -242,24 +245,26 @@ impl<'block> BrilligBlock<'block> {
         source_vector: BrilligVector,
         index: MemoryAddress,
         removed_items: &[BrilligVariable],
+        reuse_source: bool,
     ) {
-        // First we need to allocate the target vector decrementing the size by removed_items.len()
+        // Get the removed items first, as they can be overwritten when the source vector is reused
+        for (subitem_index, variable) in removed_items.iter().enumerate() {
+            let target_index = self.brillig_context.make_usize_constant(subitem_index.into());
+            self.brillig_context.memory_op(target_index, index, target_index, BinaryIntOp::Add);
+            self.retrieve_variable_from_array(source_vector.pointer, target_index, *variable);
+            self.brillig_context.deallocate_register(target_index);
+        }
+
+        // Then we need to compute the size of the target vector decrementing the size by removed_items.len()
         self.brillig_context.usize_op(
             source_vector.size,
             target_vector.size,
             BinaryIntOp::Sub,
             removed_items.len(),
         );
-        self.brillig_context.allocate_array_instruction(target_vector.pointer, target_vector.size);
-        // We initialize the RC of the target vector to 1
-        self.brillig_context.usize_const(target_vector.rc, 1_usize.into());
-
-        // Copy the elements to the left of the index
-        self.brillig_context.copy_array_instruction(
-            source_vector.pointer,
-            target_vector.pointer,
-            index,
-        );
+        // Then either reuse the source vector or copy the elements to the left of the index into a
+        // new one
+        self.reuse_or_reallocate_vector(target_vector, source_vector, index, false, reuse_source);
 
         // Compute the source pointer after the removed items
         let source_pointer_after_index = self.brillig_context.allocate_register();
@@ -289,26 +294,82 @@ impl<'block> BrilligBlock<'block> {
         self.brillig_context.memory_op(source_vector.size, index, item_count, BinaryIntOp::Sub);
         self.brillig_context.usize_op_in_place(item_count, BinaryIntOp::Sub, removed_items.len());
 
-        // Copy the elements to the right of the index
+        // Copy the elements to the right of the index, which are shifted in place when the source
+        // vector is reused
         self.brillig_context.copy_array_instruction(
             source_pointer_after_index,
             target_pointer_at_index,
             item_count,
         );
 
-        // Get the removed items
-        for (subitem_index, variable) in removed_items.iter().enumerate() {
-            let target_index = self.brillig_context.make_usize_constant(subitem_index.into());
-            self.brillig_context.memory_op(target_index, index, target_index, BinaryIntOp::Add);
-            self.retrieve_variable_from_array(source_vector.pointer, target_index, *variable);
-            self.brillig_context.deallocate_register(target_index);
-        }
-
         self.brillig_context.deallocate_register(source_pointer_after_index);
         self.brillig_context.deallocate_register(target_pointer_at_index);
         self.brillig_context.deallocate_register(item_count);
     }
 
+    /// Sets the pointer, capacity and reference count of the target vector, whose size is already
+    /// set. If `reuse_source` and the source vector can be reused at runtime, the target vector
+    /// takes over its memory. Otherwise it is allocated, with twice its size as capacity if it
+    /// `grows`, and the first `prefix_size` elements of the source vector are copied into it.
+    fn reuse_or_reallocate_vector(
+        &mut self,
+        target_vector: BrilligVector,
+        source_vector: BrilligVector,
+        prefix_size: MemoryAddress,
+        grows: bool,
+        reuse_source: bool,
+    ) {
+        let reallocate = |ctx: &mut BrilligContext| {
+            if grows {
+                ctx.usize_op(target_vector.size, target_vector.capacity, BinaryIntOp::Mul, 2);
+            } else {
+                ctx.mov_instruction(target_vector.capacity, target_vector.size);
+            }
+            ctx.allocate_array_instruction(target_vector.pointer, target_vector.capacity);
+            ctx.copy_array_instruction(source_vector.pointer, target_vector.pointer, prefix_size);
+        };
+        if reuse_source {
+            let required_capacity = grows.then_some(target_vector.size);
+            let condition = self.reuse_condition(source_vector, required_capacity);
+            self.brillig_context.branch_instruction(condition, |ctx, reuse| {
+                if reuse {
+                    ctx.mov_instruction(target_vector.pointer, source_vector.pointer);
+                    ctx.mov_instruction(target_vector.capacity, source_vector.capacity);
+                } else {
+                    reallocate(ctx);
+                }
+            });
+            self.brillig_context.deallocate_register(condition);
+        } else {
+            reallocate(self.brillig_context);
+        }
+        // We initialize the RC of the target vector to 1
+        self.brillig_context.usize_const(target_vector.rc, 1_usize.into());
+    }
+
+    /// Returns a register which is set if the source vector is only referenced once, and has at
+    /// least the `required_capacity` if any.
+    fn reuse_condition(
+        &mut self,
+        source_vector: BrilligVector,
+        required_capacity: Option<MemoryAddress>,
+    ) -> MemoryAddress {
+        let condition = self.brillig_context.allocate_register();
+        self.brillig_context.usize_op(source_vector.rc, condition, BinaryIntOp::Equals, 1);
+        if let Some(required_capacity) = required_capacity {
+            let fits = self.brillig_context.allocate_register();
+            self.brillig_context.memory_op(
+                required_capacity,
+                source_vector.capacity,
+                fits,
+                BinaryIntOp::LessThanEquals,
+            );
+            self.brillig_context.memory_op(condition, fits, condition, BinaryIntOp::And);
+            self.brillig_context.deallocate_register(fits);
+        }
+        condition
+    }
+
     pub(crate) fn convert_array_or_vector_to_vector(
         &mut self,
         source_variable: BrilligVariable,
@@ -327,7 +388,7 @@ impl<'block> BrilligBlock<'block> {
 mod tests {
     use std::vec;
 
-    use acvm::acir::brillig::Value;
+    use acvm::acir::brillig::{BinaryIntOp, Value};
 
     use crate::brillig::brillig_gen::brillig_block::BrilligBlock;
     use crate::brillig::brillig_gen::brillig_block_variables::BlockVariables;
@@ -366,6 +427,7 @@ mod tests {
             brillig_context,
             variables,
             last_uses: Default::default(),
+            debug_variables: Default::default(),
         }
     }
 
@@ -376,6 +438,7 @@ mod tests {
             array: Vec<Value>,
             item_to_push: Value,
             expected_return: Vec<Value>,
+            reuse_source: bool,
         ) {
             let arguments = vec![
                 BrilligParameter::Array(
@@ -407,6 +470,7 @@ mod tests {
                 pointer: context.allocate_register(),
                 size: context.allocate_register(),
                 rc: context.allocate_register(),
+                capacity: context.allocate_register(),
             };
 
             let mut block = create_brillig_block(&mut function_context, &mut context);
@@ -416,12 +480,14 @@ mod tests {
                     target_vector,
                     source_vector,
                     &[BrilligVariable::Simple(item_to_insert)],
+                    reuse_source,
                 );
             } else {
                 block.slice_push_front_operation(
                     target_vector,
                     source_vector,
                     &[BrilligVariable::Simple(item_to_insert)],
+                    reuse_source,
                 );
             }
 
@@ -437,30 +503,129 @@ mod tests {
             );
         }
 
-        test_case_push(
-            true,
-            vec![Value::from(1_usize), Value::from(2_usize), Value::from(3_usize)],
-            Value::from(27_usize),
-            vec![
-                Value::from(1_usize),
-                Value::from(2_usize),
-                Value::from(3_usize),
+        for reuse_source in [false, true] {
+            test_case_push(
+                true,
+                vec![Value::from(1_usize), Value::from(2_usize), Value::from(3_usize)],
                 Value::from(27_usize),
-            ],
-        );
-        test_case_push(true, vec![], Value::from(27_usize), vec![Value::from(27_usize)]);
-        test_case_push(
-            false,
-            vec![Value::from(1_usize), Value::from(2_usize), Value::from(3_usize)],
-            Value::from(27_usize),
-            vec![
+                vec![
+                    Value::from(1_usize),
+                    Value::from(2_usize),
+                    Value::from(3_usize),
+                    Value::from(27_usize),
+                ],
+                reuse_source,
+            );
+            test_case_push(
+                true,
+                vec![],
                 Value::from(27_usize),
-                Value::from(1_usize),
-                Value::from(2_usize),
-                Value::from(3_usize),
-            ],
+                vec![Value::from(27_usize)],
+                reuse_source,
+            );
+            test_case_push(
+                false,
+                vec![Value::from(1_usize), Value::from(2_usize), Value::from(3_usize)],
+                Value::from(27_usize),
+                vec![
+                    Value::from(27_usize),
+                    Value::from(1_usize),
+                    Value::from(2_usize),
+                    Value::from(3_usize),
+                ],
+                reuse_source,
+            );
+            test_case_push(
+                false,
+                vec![],
+                Value::from(27_usize),
+                vec![Value::from(27_usize)],
+                reuse_source,
+            );
+        }
+    }
+
+    #[test]
+    fn test_slice_grows_in_place() {
+        let array = vec![Value::from(1_usize), Value::from(2_usize), Value::from(3_usize)];
+        let arguments = vec![BrilligParameter::Array(
+            vec![BrilligParameter::Simple(BRILLIG_MEMORY_ADDRESSING_BIT_SIZE)],
+            array.len(),
+        )];
+        let returns = vec![
+            BrilligParameter::Array(
+                vec![BrilligParameter::Simple(BRILLIG_MEMORY_ADDRESSING_BIT_SIZE)],
+                array.len() + 3,
+            ),
+            BrilligParameter::Simple(BRILLIG_MEMORY_ADDRESSING_BIT_SIZE),
+        ];
+
+        let (_, mut function_context, mut context) = create_test_environment();
+
+        let array_variable = BrilligArray {
+            pointer: context.allocate_register(),
+            size: array.len(),
+            rc: context.allocate_register(),
+        };
+        let source_vector = context.array_to_vector(&array_variable);
+        let vectors = [(); 3].map(|_| BrilligVector {
+            pointer: context.allocate_register(),
+            size: context.allocate_register(),
+            rc: context.allocate_register(),
+            capacity: context.allocate_register(),
+        });
+        let items = [0_usize, 4, 9].map(|item| context.make_usize_constant(item.into()));
+        let index = context.make_usize_constant(2_usize.into());
+
+        let mut block = create_brillig_block(&mut function_context, &mut context);
+
+        // The first push copies the array into a vector with spare capacity, which the next
+        // operations shift and write in place.
+        block.slice_push_back_operation(
+            vectors[0],
+            source_vector,
+            &[BrilligVariable::Simple(items[1])],
+            true,
+        );
+        block.slice_push_front_operation(
+            vectors[1],
+            vectors[0],
+            &[BrilligVariable::Simple(items[0])],
+            true,
+        );
+        block.slice_insert_operation(
+            vectors[2],
+            vectors[1],
+            index,
+            &[BrilligVariable::Simple(items[2])],
+            true,
+        );
+
+        let same_pointer = context.allocate_register();
+        context.memory_op(
+            vectors[0].pointer,
+            vectors[2].pointer,
+            same_pointer,
+            BinaryIntOp::Equals,
+        );
+        context.return_instruction(&[vectors[2].pointer, vectors[2].rc, same_pointer]);
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(array, &bytecode);
+        let expected_return = vec![
+            Value::from(0_usize),
+            Value::from(1_usize),
+            Value::from(9_usize),
+            Value::from(2_usize),
+            Value::from(3_usize),
+            Value::from(4_usize),
+            Value::from(1_usize),
+        ];
+        assert_eq!(return_data_size, expected_return.len());
+        assert_eq!(
+            vm.get_memory()[return_data_offset..(return_data_offset + expected_return.len())],
+            expected_return
         );
-        test_case_push(false, vec![], Value::from(27_usize), vec![Value::from(27_usize)]);
     }
 
     #[test]
@@ -470,6 +635,7 @@ mod tests {
             array: Vec<Value>,
             expected_return_array: Vec<Value>,
             expected_return_item: Value,
+            reuse_source: bool,
         ) {
             let arguments = vec![BrilligParameter::Array(
                 vec![BrilligParameter::Simple(BRILLIG_MEMORY_ADDRESSING_BIT_SIZE)],
@@ -500,6 +666,7 @@ mod tests {
                 pointer: context.allocate_register(),
                 size: context.allocate_register(),
                 rc: context.allocate_register(),
+                capacity: context.allocate_register(),
             };
             let removed_item = context.allocate_register();
 
@@ -510,12 +677,14 @@ mod tests {
                     target_vector,
                     source_vector,
                     &[BrilligVariable::Simple(removed_item)],
+                    reuse_source,
                 );
             } else {
                 block.slice_pop_front_operation(
                     target_vector,
                     source_vector,
                     &[BrilligVariable::Simple(removed_item)],
+                    reuse_source,
                 );
             }
 
@@ -534,19 +703,29 @@ mod tests {
             );
         }
 
-        test_case_pop(
-            true,
-            vec![Value::from(1_usize), Value::from(2_usize), Value::from(3_usize)],
-            vec![Value::from(1_usize), Value::from(2_usize)],
-            Value::from(3_usize),
-        );
-        test_case_pop(true, vec![Value::from(1_usize)], vec![], Value::from(1_usize));
-        test_case_pop(
-            false,
-            vec![Value::from(1_usize), Value::from(2_usize), Value::from(3_usize)],
-            vec![Value::from(2_usize), Value::from(3_usize)],
-            Value::from(1_usize),
-        );
+        for reuse_source in [false, true] {
+            test_case_pop(
+                true,
+                vec![Value::from(1_usize), Value::from(2_usize), Value::from(3_usize)],
+                vec![Value::from(1_usize), Value::from(2_usize)],
+                Value::from(3_usize),
+                reuse_source,
+            );
+            test_case_pop(
+                true,
+                vec![Value::from(1_usize)],
+                vec![],
+                Value::from(1_usize),
+                reuse_source,
+            );
+            test_case_pop(
+                false,
+                vec![Value::from(1_usize), Value::from(2_usize), Value::from(3_usize)],
+                vec![Value::from(2_usize), Value::from(3_usize)],
+                Value::from(1_usize),
+                reuse_source,
+            );
+        }
     }
 
     #[test]
@@ -556,6 +735,7 @@ mod tests {
             item: Value,
             index: Value,
             expected_return: Vec<Value>,
+            reuse_source: bool,
         ) {
             let arguments = vec![
                 BrilligParameter::Array(
@@ -589,6 +769,7 @@ mod tests {
                 pointer: context.allocate_register(),
                 size: context.allocate_register(),
                 rc: context.allocate_register(),
+                capacity: context.allocate_register(),
             };
 
             let mut block = create_brillig_block(&mut function_context, &mut context);
@@ -598,6 +779,7 @@ mod tests {
                 source_vector,
                 index_to_insert,
                 &[BrilligVariable::Simple(item_to_insert)],
+                reuse_source,
             );
 
             context.return_instruction(&[target_vector.pointer, target_vector.rc]);
@@ -613,57 +795,64 @@ mod tests {
             );
         }
 
-        test_case_insert(
-            vec![Value::from(1_usize), Value::from(2_usize), Value::from(3_usize)],
-            Value::from(27_usize),
-            Value::from(1_usize),
-            vec![
-                Value::from(1_usize),
+        for reuse_source in [false, true] {
+            test_case_insert(
+                vec![Value::from(1_usize), Value::from(2_usize), Value::from(3_usize)],
                 Value::from(27_usize),
-                Value::from(2_usize),
-                Value::from(3_usize),
-            ],
-        );
+                Value::from(1_usize),
+                vec![
+                    Value::from(1_usize),
+                    Value::from(27_usize),
+                    Value::from(2_usize),
+                    Value::from(3_usize),
+                ],
+                reuse_source,
+            );
 
-        test_case_insert(
-            vec![Value::from(1_usize), Value::from(2_usize), Value::from(3_usize)],
-            Value::from(27_usize),
-            Value::from(0_usize),
-            vec![
+            test_case_insert(
+                vec![Value::from(1_usize), Value::from(2_usize), Value::from(3_usize)],
                 Value::from(27_usize),
-                Value::from(1_usize),
-                Value::from(2_usize),
-                Value::from(3_usize),
-            ],
-        );
-        test_case_insert(
-            vec![Value::from(1_usize), Value::from(2_usize), Value::from(3_usize)],
-            Value::from(27_usize),
-            Value::from(2_usize),
-            vec![
-                Value::from(1_usize),
-                Value::from(2_usize),
+                Value::from(0_usize),
+                vec![
+                    Value::from(27_usize),
+                    Value::from(1_usize),
+                    Value::from(2_usize),
+                    Value::from(3_usize),
+                ],
+                reuse_source,
+            );
+            test_case_insert(
+                vec![Value::from(1_usize), Value::from(2_usize), Value::from(3_usize)],
                 Value::from(27_usize),
-                Value::from(3_usize),
-            ],
-        );
-        test_case_insert(
-            vec![Value::from(1_usize), Value::from(2_usize), Value::from(3_usize)],
-            Value::from(27_usize),
-            Value::from(3_usize),
-            vec![
-                Value::from(1_usize),
                 Value::from(2_usize),
+                vec![
+                    Value::from(1_usize),
+                    Value::from(2_usize),
+                    Value::from(27_usize),
+                    Value::from(3_usize),
+                ],
+                reuse_source,
+            );
+            test_case_insert(
+                vec![Value::from(1_usize), Value::from(2_usize), Value::from(3_usize)],
+                Value::from(27_usize),
                 Value::from(3_usize),
+                vec![
+                    Value::from(1_usize),
+                    Value::from(2_usize),
+                    Value::from(3_usize),
+                    Value::from(27_usize),
+                ],
+                reuse_source,
+            );
+            test_case_insert(
+                vec![],
                 Value::from(27_usize),
-            ],
-        );
-        test_case_insert(
-            vec![],
-            Value::from(27_usize),
-            Value::from(0_usize),
-            vec![Value::from(27_usize)],
-        );
+                Value::from(0_usize),
+                vec![Value::from(27_usize)],
+                reuse_source,
+            );
+        }
     }
 
     #[test]
@@ -673,6 +862,7 @@ mod tests {
             index: Value,
             expected_array: Vec<Value>,
             expected_removed_item: Value,
+            reuse_source: bool,
         ) {
             let arguments = vec![
                 BrilligParameter::Array(
@@ -707,6 +897,7 @@ mod tests {
                 pointer: context.allocate_register(),
                 size: context.allocate_register(),
                 rc: context.allocate_register(),
+                capacity: context.allocate_register(),
             };
             let removed_item = context.allocate_register();
 
@@ -717,6 +908,7 @@ mod tests {
                 source_vector,
                 index_to_insert,
                 &[BrilligVariable::Simple(removed_item)],
+                reuse_source,
             );
 
             context.return_instruction(&[target_vector.pointer, target_vector.size, removed_item]);
@@ -736,31 +928,37 @@ mod tests {
             );
         }
 
-        test_case_remove(
-            vec![Value::from(1_usize), Value::from(2_usize), Value::from(3_usize)],
-            Value::from(0_usize),
-            vec![Value::from(2_usize), Value::from(3_usize)],
-            Value::from(1_usize),
-        );
+        for reuse_source in [false, true] {
+            test_case_remove(
+                vec![Value::from(1_usize), Value::from(2_usize), Value::from(3_usize)],
+                Value::from(0_usize),
+                vec![Value::from(2_usize), Value::from(3_usize)],
+                Value::from(1_usize),
+                reuse_source,
+            );
 
-        test_case_remove(
-            vec![Value::from(1_usize), Value::from(2_usize), Value::from(3_usize)],
-            Value::from(1_usize),
-            vec![Value::from(1_usize), Value::from(3_usize)],
-            Value::from(2_usize),
-        );
+            test_case_remove(
+                vec![Value::from(1_usize), Value::from(2_usize), Value::from(3_usize)],
+                Value::from(1_usize),
+                vec![Value::from(1_usize), Value::from(3_usize)],
+                Value::from(2_usize),
+                reuse_source,
+            );
 
-        test_case_remove(
-            vec![Value::from(1_usize), Value::from(2_usize), Value::from(3_usize)],
-            Value::from(2_usize),
-            vec![Value::from(1_usize), Value::from(2_usize)],
-            Value::from(3_usize),
-        );
-        test_case_remove(
-            vec![Value::from(1_usize)],
-            Value::from(0_usize),
-            vec![],
-            Value::from(1_usize),
-        );
+            test_case_remove(
+                vec![Value::from(1_usize), Value::from(2_usize), Value::from(3_usize)],
+                Value::from(2_usize),
+                vec![Value::from(1_usize), Value::from(2_usize)],
+                Value::from(3_usize),
+                reuse_source,
+            );
+            test_case_remove(
+                vec![Value::from(1_usize)],
+                Value::from(0_usize),
+                vec![],
+                Value::from(1_usize),
+                reuse_source,
+            );
+        }
     }
 }
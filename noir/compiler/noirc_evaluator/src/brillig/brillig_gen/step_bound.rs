@@ -0,0 +1,325 @@
+//! This module computes an upper bound on the number of opcodes executed by a call to each
+//! Brillig function, so that execution which runs past it can be aborted instead of hanging
+//! witness generation.
+//!
+//! The bound of a function is the sum over its blocks of the number of opcodes emitted for the
+//! block multiplied by the number of times the block can be executed, which follows from the
+//! trip counts of the loops containing it. A loop is only bounded when it counts up in steps of
+//! one between constants, as `for` loops over constant ranges do. Loops emitted while lowering a
+//! single instruction (e.g. to copy an array) iterate at most once per element of the largest
+//! array in the function.
+//!
+//! No bound is derived for functions which contain any other loop, use slices (whose lengths are
+//! only known at runtime), make memoized foreign calls (which search a cache growing with each
+//! call) or are recursive, nor for the functions calling them.
+use fxhash::{FxHashMap as HashMap, FxHashSet as HashSet};
+
+use crate::{
+    brillig::Brillig,
+    ssa::{
+        ir::{
+            basic_block::BasicBlockId,
+            cfg::ControlFlowGraph,
+            dfg::DataFlowGraph,
+            function::{Function, FunctionId},
+            instruction::{Binary, BinaryOp, Instruction, Intrinsic, TerminatorInstruction},
+            post_order::PostOrder,
+            types::{NumericType, Type},
+            value::{Value, ValueId},
+        },
+        ssa_gen::Ssa,
+    },
+};
+
+use super::{
+    brillig_bigint::BigIntProcedure,
+    brillig_block::BrilligBlock,
+    variable_liveness::{compute_loop_body, find_back_edges, BackEdge},
+};
+
+/// Returns an upper bound on the number of opcodes executed by a call to each Brillig function,
+/// for the functions where one could be derived.
+pub(crate) fn compute_step_bounds(
+    ssa: &Ssa,
+    brillig: &Brillig,
+) -> std::collections::HashMap<FunctionId, usize> {
+    let mut context =
+        StepBounds { ssa, brillig, bounds: HashMap::default(), in_progress: HashSet::default() };
+    for function_id in brillig.ssa_function_to_brillig.keys() {
+        context.function_bound(*function_id);
+    }
+    context.bounds.into_iter().filter_map(|(id, bound)| Some((id, bound?))).collect()
+}
+
+struct StepBounds<'a> {
+    ssa: &'a Ssa,
+    brillig: &'a Brillig,
+    bounds: HashMap<FunctionId, Option<usize>>,
+    /// Functions whose bound is being computed, used to detect recursion.
+    in_progress: HashSet<FunctionId>,
+}
+
+impl StepBounds<'_> {
+    fn function_bound(&mut self, function_id: FunctionId) -> Option<usize> {
+        if let Some(bound) = self.bounds.get(&function_id) {
+            return *bound;
+        }
+        if !self.in_progress.insert(function_id) {
+            return None;
+        }
+        let bound = self.compute_function_bound(function_id);
+        self.in_progress.remove(&function_id);
+        self.bounds.insert(function_id, bound);
+        bound
+    }
+
+    fn compute_function_bound(&mut self, function_id: FunctionId) -> Option<usize> {
+        let function = self.ssa.functions.get(&function_id)?;
+        let artifact = self.brillig.ssa_function_to_brillig.get(&function_id)?;
+        let max_loop_iterations = max_array_size(&function.dfg)?;
+        let executions = block_executions(function)?;
+
+        let mut block_starts = executions
+            .keys()
+            .map(|block| {
+                let label = BrilligBlock::create_block_label(function_id, *block);
+                Some((artifact.label_position(&label)?, *block))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        block_starts.sort();
+
+        // Any code emitted before the first block is executed once.
+        let function_end = artifact.index_of_next_opcode();
+        let first_block_start = block_starts.first().map_or(function_end, |(start, _)| *start);
+        let mut bound = artifact.max_steps(0..first_block_start, max_loop_iterations)?;
+
+        for (index, (start, block)) in block_starts.iter().enumerate() {
+            let end = block_starts.get(index + 1).map_or(function_end, |(end, _)| *end);
+            let mut block_bound = artifact.max_steps(*start..end, max_loop_iterations)?;
+            for instruction in function.dfg[*block].instructions() {
+                if let Instruction::Call { func, .. } = &function.dfg[*instruction] {
+                    let callee_bound = match &function.dfg[*func] {
+                        Value::Function(callee) => self.function_bound(*callee)?,
+                        Value::ForeignFunction(name)
+                            if self.brillig.memoizes_foreign_call(name) =>
+                        {
+                            return None;
+                        }
+                        Value::Intrinsic(Intrinsic::BlackBox(func)) => {
+                            let Some(procedure) = BigIntProcedure::for_black_box(*func) else {
+                                continue;
+                            };
+                            self.brillig.bigint_procedure_step_bound(procedure)?
+                        }
+                        _ => continue,
+                    };
+                    block_bound = block_bound.checked_add(callee_bound)?;
+                }
+            }
+            bound = bound.checked_add(block_bound.checked_mul(executions[block])?)?;
+        }
+        Some(bound)
+    }
+}
+
+/// Returns the flattened size of the largest array in the function, which bounds the number of
+/// iterations of the loops emitted to lower a single instruction.
+fn max_array_size(dfg: &DataFlowGraph) -> Option<usize> {
+    let mut max_size = 0;
+    for (value, _) in dfg.values_iter() {
+        let typ = dfg.type_of_value(value);
+        if typ.contains_slice_element() {
+            return None;
+        }
+        if let Type::Array(..) = typ {
+            max_size = max_size.max(typ.flattened_size());
+        }
+    }
+    Some(max_size)
+}
+
+/// Returns the maximum number of times each reachable block is executed per call to `function`.
+fn block_executions(function: &Function) -> Option<HashMap<BasicBlockId, usize>> {
+    let cfg = ControlFlowGraph::with_function(function);
+    let post_order = PostOrder::with_function(function);
+
+    let mut executions: HashMap<_, _> =
+        function.reachable_blocks().into_iter().map(|block| (block, 1_usize)).collect();
+    let mut headers = HashSet::default();
+    for back_edge in find_back_edges(function, &cfg, &post_order) {
+        // A loop which can be restarted from several blocks is not a `for` loop.
+        if !headers.insert(back_edge.header) {
+            return None;
+        }
+        let body = compute_loop_body(back_edge, &cfg);
+        let trip_count = trip_count(function, &cfg, back_edge, &body)?;
+        for block in body {
+            // The header checks the loop condition once more than the body is executed.
+            let factor =
+                if block == back_edge.header { trip_count.checked_add(1)? } else { trip_count };
+            let block_executions = executions.get_mut(&block)?;
+            *block_executions = block_executions.checked_mul(factor)?;
+        }
+    }
+    Some(executions)
+}
+
+/// Returns the number of iterations of the loop closed by `back_edge`, if it is of the form
+///
+/// ```text
+/// pre_header():
+///   jmp header(start)
+/// header(i):
+///   v = lt i, end
+///   jmpif v then: body, else: exit
+/// back_edge_start():
+///   next = add i, 1
+///   jmp header(next)
+/// ```
+///
+/// where `start` and `end` are constants.
+fn trip_count(
+    function: &Function,
+    cfg: &ControlFlowGraph,
+    back_edge: BackEdge,
+    body: &HashSet<BasicBlockId>,
+) -> Option<usize> {
+    let dfg = &function.dfg;
+    let header = &dfg[back_edge.header];
+
+    let Some(TerminatorInstruction::JmpIf { condition, then_destination, else_destination }) =
+        header.terminator()
+    else {
+        return None;
+    };
+    if !body.contains(then_destination) || body.contains(else_destination) {
+        return None;
+    }
+    let Value::Instruction { instruction, .. } = &dfg[dfg.resolve(*condition)] else {
+        return None;
+    };
+    let Instruction::Binary(Binary { lhs: induction_variable, rhs: end, operator: BinaryOp::Lt }) =
+        &dfg[*instruction]
+    else {
+        return None;
+    };
+    // Signed comparisons do not order values the same way as their field elements.
+    if !matches!(
+        dfg.type_of_value(*induction_variable),
+        Type::Numeric(NumericType::Unsigned { .. } | NumericType::NativeField)
+    ) {
+        return None;
+    }
+    let position = header.parameters().iter().position(|param| param == induction_variable)?;
+
+    let mut pre_headers =
+        cfg.predecessors(back_edge.header).filter(|block| *block != back_edge.start);
+    let (Some(pre_header), None) = (pre_headers.next(), pre_headers.next()) else {
+        return None;
+    };
+    let start = dfg.get_numeric_constant(jump_argument(dfg, pre_header, position)?)?;
+    let end = dfg.get_numeric_constant(*end)?;
+
+    let next = jump_argument(dfg, back_edge.start, position)?;
+    if !is_increment_of(dfg, next, *induction_variable) {
+        return None;
+    }
+
+    let trip_count = end.to_u128().saturating_sub(start.to_u128());
+    usize::try_from(trip_count).ok()
+}
+
+/// Returns the `position`th argument passed by the jump terminating `block`.
+fn jump_argument(dfg: &DataFlowGraph, block: BasicBlockId, position: usize) -> Option<ValueId> {
+    match dfg[block].terminator() {
+        Some(TerminatorInstruction::Jmp { arguments, .. }) => arguments.get(position).copied(),
+        _ => None,
+    }
+}
+
+/// Returns whether `value` is `add variable, 1`.
+fn is_increment_of(dfg: &DataFlowGraph, value: ValueId, variable: ValueId) -> bool {
+    let Value::Instruction { instruction, .. } = &dfg[dfg.resolve(value)] else {
+        return false;
+    };
+    let Instruction::Binary(Binary { lhs, rhs, operator: BinaryOp::Add }) = &dfg[*instruction]
+    else {
+        return false;
+    };
+    let is_one = |value: &ValueId| dfg.get_numeric_constant(*value).map_or(false, |c| c.is_one());
+    (*lhs == variable && is_one(rhs)) || (*rhs == variable && is_one(lhs))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::brillig::BrilligOptions;
+    use crate::ssa::{
+        function_builder::FunctionBuilder,
+        ir::{function::RuntimeType, instruction::BinaryOp, map::Id, types::Type},
+        ssa_gen::Ssa,
+    };
+
+    /// Builds a function which counts from zero up to `end`, or up to its parameter if `end` is
+    /// not given.
+    fn counting_loop(end: Option<u128>) -> Ssa {
+        // fn main f0 {
+        //   b0(v0: u32):
+        //     jmp b1(u32 0)
+        //   b1(v1: u32):
+        //     v2 = lt v1, end (or v0)
+        //     jmpif v2 then: b2, else: b3
+        //   b2():
+        //     v3 = add v1, u32 1
+        //     jmp b1(v3)
+        //   b3():
+        //     return
+        // }
+        let mut builder =
+            FunctionBuilder::new("main".to_string(), Id::test_new(0), RuntimeType::Brillig);
+        let v0 = builder.add_parameter(Type::unsigned(32));
+        let b1 = builder.insert_block();
+        let b2 = builder.insert_block();
+        let b3 = builder.insert_block();
+
+        let end = match end {
+            Some(end) => builder.numeric_constant(end, Type::unsigned(32)),
+            None => v0,
+        };
+        let zero = builder.numeric_constant(0u128, Type::unsigned(32));
+        builder.terminate_with_jmp(b1, vec![zero]);
+
+        builder.switch_to_block(b1);
+        let v1 = builder.add_block_parameter(b1, Type::unsigned(32));
+        let v2 = builder.insert_binary(v1, BinaryOp::Lt, end);
+        builder.terminate_with_jmpif(v2, b2, b3);
+
+        builder.switch_to_block(b2);
+        let one = builder.numeric_constant(1u128, Type::unsigned(32));
+        let v3 = builder.insert_binary(v1, BinaryOp::Add, one);
+        builder.terminate_with_jmp(b1, vec![v3]);
+
+        builder.switch_to_block(b3);
+        builder.terminate_with_return(vec![]);
+
+        builder.finish()
+    }
+
+    fn step_bound(ssa: &Ssa) -> Option<usize> {
+        ssa.to_brillig(&BrilligOptions::default()).unwrap().step_bound(ssa.main_id)
+    }
+
+    #[test]
+    fn bounds_grow_with_the_trip_count() {
+        let ten = step_bound(&counting_loop(Some(10)))
+            .expect("a loop over a constant range should be bounded");
+        let twenty = step_bound(&counting_loop(Some(20)))
+            .expect("a loop over a constant range should be bounded");
+        // Each iteration executes at least the comparison, the increment and both jumps.
+        assert!(twenty >= ten + 10 * 4);
+    }
+
+    #[test]
+    fn loops_with_runtime_bounds_are_unbounded() {
+        assert_eq!(step_bound(&counting_loop(None)), None);
+    }
+}
@@ -15,12 +15,12 @@ use fxhash::{FxHashMap as HashMap, FxHashSet as HashSet};
 
 /// A back edge is an edge from a node to one of its ancestors. It denotes a loop in the CFG.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct BackEdge {
-    header: BasicBlockId,
-    start: BasicBlockId,
+pub(super) struct BackEdge {
+    pub(super) header: BasicBlockId,
+    pub(super) start: BasicBlockId,
 }
 
-fn find_back_edges(
+pub(super) fn find_back_edges(
     func: &Function,
     cfg: &ControlFlowGraph,
     post_order: &PostOrder,
@@ -41,6 +41,26 @@ fn find_back_edges(
     back_edges
 }
 
+/// Returns the blocks of the loop closed by `edge`, including its header.
+pub(super) fn compute_loop_body(edge: BackEdge, cfg: &ControlFlowGraph) -> HashSet<BasicBlockId> {
+    let mut loop_blocks = HashSet::default();
+    loop_blocks.insert(edge.header);
+    loop_blocks.insert(edge.start);
+
+    let mut stack = vec![edge.start];
+
+    while let Some(block) = stack.pop() {
+        for predecessor in cfg.predecessors(block) {
+            if !loop_blocks.contains(&predecessor) {
+                loop_blocks.insert(predecessor);
+                stack.push(predecessor);
+            }
+        }
+    }
+
+    loop_blocks
+}
+
 /// Collects the underlying variables inside a value id. It might be more than one, for example in constant arrays that are constructed with multiple vars.
 fn collect_variables_of_value(value_id: ValueId, dfg: &DataFlowGraph) -> Vec<ValueId> {
     let value_id = dfg.resolve(value_id);
@@ -229,7 +249,7 @@ impl VariableLiveness {
             .expect("Live ins should have been calculated")
             .clone();
 
-        let body = self.compute_loop_body(back_edge);
+        let body = compute_loop_body(back_edge, &self.cfg);
         for body_block_id in body {
             self.live_in
                 .get_mut(&body_block_id)
@@ -238,25 +258,6 @@ impl VariableLiveness {
         }
     }
 
-    fn compute_loop_body(&self, edge: BackEdge) -> HashSet<BasicBlockId> {
-        let mut loop_blocks = HashSet::default();
-        loop_blocks.insert(edge.header);
-        loop_blocks.insert(edge.start);
-
-        let mut stack = vec![edge.start];
-
-        while let Some(block) = stack.pop() {
-            for predecessor in self.cfg.predecessors(block) {
-                if !loop_blocks.contains(&predecessor) {
-                    loop_blocks.insert(predecessor);
-                    stack.push(predecessor);
-                }
-            }
-        }
-
-        loop_blocks
-    }
-
     fn compute_last_uses(&mut self, func: &Function) {
         for block_id in func.reachable_blocks() {
             let block = &func.dfg[block_id];
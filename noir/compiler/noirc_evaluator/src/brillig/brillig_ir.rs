@@ -5,16 +5,24 @@
 //! ssa types and types in this module.
 //! A similar paradigm can be seen with the `acir_ir` module.
 pub(crate) mod artifact;
+pub(crate) mod bigint;
 pub(crate) mod brillig_variable;
 pub(crate) mod debug_show;
 pub(crate) mod registers;
 
+mod bulk_memory;
+mod dead_code_elimination;
 mod entry_point;
+mod field_pow;
+mod field_sqrt;
+mod foreign_call_cache;
+mod permutation_routing;
+mod register_allocation;
 
-use crate::ssa::ir::dfg::CallStack;
+use crate::{brillig::BRILLIG_PROFILE_ORACLE, spans, ssa::ir::dfg::CallStack};
 
 use self::{
-    artifact::{BrilligArtifact, UnresolvedJumpLocation},
+    artifact::{BrilligArtifact, OpcodeLocation, UnresolvedJumpLocation},
     brillig_variable::{BrilligArray, BrilligVariable, BrilligVector},
     registers::BrilligRegistersContext,
 };
@@ -23,11 +31,14 @@ use acvm::{
         BinaryFieldOp, BinaryIntOp, BlackBoxOp, MemoryAddress, Opcode as BrilligOpcode, Value,
         ValueOrArray,
     },
-    brillig_vm::brillig::HeapValueType,
+    brillig_vm::{brillig::HeapValueType, evaluate_binary_field_op, evaluate_binary_int_op},
     FieldElement,
 };
 use debug_show::DebugShow;
 
+pub(crate) use entry_point::MAX_STACK_SIZE;
+pub(crate) use permutation_routing::switch_count;
+
 /// Integer arithmetic in Brillig is limited to 127 bit
 /// integers.
 ///
@@ -50,6 +61,10 @@ pub(crate) enum ReservedRegisters {
     StackPointer = 0,
     /// This register stores the previous stack pointer. The registers of the caller are stored here.
     PreviousStackPointer = 1,
+    /// This register stores the number of nested calls between functions, if it is limited.
+    CallDepth = 2,
+    /// This register points to the cached results of memoized foreign calls, if any.
+    ForeignCallCache = 3,
 }
 
 impl ReservedRegisters {
@@ -57,7 +72,7 @@ impl ReservedRegisters {
     ///
     /// This is used to offset the general registers
     /// which should not overwrite the special register
-    const NUM_RESERVED_REGISTERS: usize = 2;
+    const NUM_RESERVED_REGISTERS: usize = 4;
 
     /// Returns the length of the reserved registers
     pub(crate) fn len() -> usize {
@@ -74,12 +89,33 @@ impl ReservedRegisters {
         MemoryAddress::from(ReservedRegisters::PreviousStackPointer as usize)
     }
 
+    /// Returns the call depth register. This is used to limit the nesting of function calls.
+    pub(crate) fn call_depth() -> MemoryAddress {
+        MemoryAddress::from(ReservedRegisters::CallDepth as usize)
+    }
+
+    /// Returns the foreign call cache register. This is used to memoize foreign calls.
+    pub(crate) fn foreign_call_cache() -> MemoryAddress {
+        MemoryAddress::from(ReservedRegisters::ForeignCallCache as usize)
+    }
+
     /// Returns a user defined (non-reserved) register index.
     fn user_register_index(index: usize) -> MemoryAddress {
         MemoryAddress::from(index + ReservedRegisters::len())
     }
 }
 
+/// Limits on the resources used by the Brillig VM, which the generated bytecode checks on entering
+/// each function so that exceeding them traps with a message instead of exhausting the memory of
+/// the VM.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct BrilligLimits {
+    /// The maximum number of nested calls between functions.
+    pub(crate) max_call_depth: Option<usize>,
+    /// The maximum number of memory slots in use, including the registers.
+    pub(crate) max_memory: Option<usize>,
+}
+
 /// Brillig context object that is used while constructing the
 /// Brillig bytecode.
 pub(crate) struct BrilligContext {
@@ -95,6 +131,18 @@ pub(crate) struct BrilligContext {
     next_section: usize,
     /// IR printer
     debug_show: DebugShow,
+    /// The number of user registers through which the arguments and return values of the function
+    /// and of its calls are passed.
+    calling_convention_registers: usize,
+    /// The limits checked by the functions being generated.
+    limits: BrilligLimits,
+    /// The id of the next profiling counter, if the executions of the blocks are counted.
+    next_profile_counter: Option<usize>,
+    /// The foreign functions whose calls are memoized, in the order of their cache ids.
+    memoized_foreign_calls: Vec<String>,
+    /// Whether the integer operations which overflow checks are inserted for in constrained code
+    /// trap when they wrap around.
+    check_overflows: bool,
 }
 
 impl BrilligContext {
@@ -107,15 +155,84 @@ impl BrilligContext {
             section_label: 0,
             next_section: 1,
             debug_show: DebugShow::new(enable_debug_trace),
+            calling_convention_registers: 0,
+            limits: BrilligLimits::default(),
+            next_profile_counter: None,
+            memoized_foreign_calls: Vec::new(),
+            check_overflows: false,
         }
     }
 
+    /// Sets the limits checked by [`Self::check_limits_on_entry`] and [`Self::return_instruction`].
+    pub(crate) fn set_limits(&mut self, limits: BrilligLimits) {
+        self.limits = limits;
+    }
+
+    /// Makes [`Self::count_executions`] count executions under counters numbered from
+    /// `first_counter`.
+    pub(crate) fn enable_profiling(&mut self, first_counter: usize) {
+        self.next_profile_counter = Some(first_counter);
+    }
+
+    /// Memoizes the calls to the foreign functions named `memoized_foreign_calls`, whose results
+    /// are then cached under the index of their name.
+    pub(crate) fn memoize_foreign_calls(&mut self, memoized_foreign_calls: Vec<String>) {
+        self.memoized_foreign_calls = memoized_foreign_calls;
+    }
+
+    /// Makes the unsigned additions, subtractions and multiplications whose results are range
+    /// checked trap when they wrap around, as their results would fail the range check in
+    /// constrained code.
+    pub(crate) fn enable_overflow_checks(&mut self) {
+        self.check_overflows = true;
+    }
+
+    /// Returns whether [`Self::enable_overflow_checks`] was called.
+    pub(crate) fn checks_overflows(&self) -> bool {
+        self.check_overflows
+    }
+
+    /// Returns the id under which the results of calls to the foreign function are cached, if
+    /// they are memoized.
+    pub(crate) fn foreign_call_cache_id(&self, func_name: &str) -> Option<usize> {
+        self.memoized_foreign_calls.iter().position(|name| name == func_name)
+    }
+
+    /// Counts each execution of the opcodes emitted next under a new counter, attributed to
+    /// `call_stack`, by calling the [`BRILLIG_PROFILE_ORACLE`] with the id of the counter.
+    ///
+    /// Nothing is emitted unless profiling is enabled.
+    pub(crate) fn count_executions(&mut self, call_stack: CallStack) {
+        let Some(counter) = self.next_profile_counter else {
+            return;
+        };
+        self.next_profile_counter = Some(counter + 1);
+        self.obj.profile_counters.insert(counter, call_stack.clone());
+
+        self.set_call_stack(call_stack);
+        let counter_register = self.make_usize_constant(counter.into());
+        self.foreign_call_instruction(
+            BRILLIG_PROFILE_ORACLE.to_string(),
+            &[ValueOrArray::MemoryAddress(counter_register)],
+            &[HeapValueType::Simple],
+            &[],
+            &[],
+        );
+        self.deallocate_register(counter_register);
+        self.set_call_stack(CallStack::new());
+    }
+
     pub(crate) fn set_allocated_registers(&mut self, allocated_registers: Vec<MemoryAddress>) {
         self.registers = BrilligRegistersContext::from_preallocated_registers(allocated_registers);
     }
 
     /// Adds a brillig instruction to the brillig byte code
     pub(crate) fn push_opcode(&mut self, opcode: BrilligOpcode) {
+        tracing::trace!(
+            name: spans::BRILLIG_OPCODE,
+            index = self.obj.index_of_next_opcode(),
+            opcode = ?opcode
+        );
         self.obj.push_opcode(opcode);
     }
 
@@ -124,6 +241,18 @@ impl BrilligContext {
         self.obj
     }
 
+    /// Records that the first `count` user registers are used to pass arguments or return values,
+    /// so that [`Self::reallocate_registers`] leaves them in place.
+    pub(crate) fn use_calling_convention_registers(&mut self, count: usize) {
+        self.calling_convention_registers = self.calling_convention_registers.max(count);
+    }
+
+    /// Reassigns the registers used by the bytecode of the function emitted so far based on their
+    /// liveness, removing the moves which become redundant.
+    pub(crate) fn reallocate_registers(&mut self) {
+        register_allocation::allocate_registers(&mut self.obj, self.calling_convention_registers);
+    }
+
     /// Allocates an array of size `size` and stores the pointer to the array
     /// in `pointer_register`
     pub(crate) fn allocate_fixed_length_array(
@@ -147,6 +276,7 @@ impl BrilligContext {
         self.debug_show.allocate_array_instruction(pointer_register, size_register);
         self.set_array_pointer(pointer_register);
         self.update_stack_pointer(size_register);
+        self.check_memory_limit();
     }
 
     pub(crate) fn set_array_pointer(&mut self, pointer_register: MemoryAddress) {
@@ -257,30 +387,6 @@ impl BrilligContext {
         self.deallocate_register(index_of_element_in_memory);
     }
 
-    /// Copies the values of an array pointed by source with length stored in `num_elements_register`
-    /// Into the array pointed by destination
-    pub(crate) fn copy_array_instruction(
-        &mut self,
-        source_pointer: MemoryAddress,
-        destination_pointer: MemoryAddress,
-        num_elements_register: MemoryAddress,
-    ) {
-        self.debug_show.copy_array_instruction(
-            source_pointer,
-            destination_pointer,
-            num_elements_register,
-        );
-
-        let value_register = self.allocate_register();
-
-        self.loop_instruction(num_elements_register, |ctx, iterator| {
-            ctx.array_get(source_pointer, iterator, value_register);
-            ctx.array_set(destination_pointer, iterator, value_register);
-        });
-
-        self.deallocate_register(value_register);
-    }
-
     /// This instruction will issue a loop that will iterate iteration_count times
     /// The body of the loop should be issued by the caller in the on_iteration closure.
     pub(crate) fn loop_instruction<F>(&mut self, iteration_count: MemoryAddress, on_iteration: F)
@@ -291,6 +397,7 @@ impl BrilligContext {
 
         let (loop_section, loop_label) = self.reserve_next_section_label();
         self.enter_section(loop_section);
+        let loop_start = self.obj.index_of_next_opcode();
 
         // Loop body
 
@@ -315,6 +422,7 @@ impl BrilligContext {
         self.usize_op_in_place(iterator_register, BinaryIntOp::Add, 1);
 
         self.jump_instruction(loop_label);
+        self.obj.add_loop(loop_start..self.obj.index_of_next_opcode());
 
         // Exit the loop
         self.enter_section(exit_loop_section);
@@ -447,6 +555,43 @@ impl BrilligContext {
         self.enter_section(next_section);
     }
 
+    /// Emits the checks made on entering a function, which trap if the call exceeds the limits.
+    ///
+    /// The call depth is counted up here and back down by [`Self::return_instruction`]. Each call
+    /// saves at least one register in memory, so that limiting the memory also limits recursion.
+    pub(crate) fn check_limits_on_entry(&mut self) {
+        if let Some(max_call_depth) = self.limits.max_call_depth {
+            let call_depth = ReservedRegisters::call_depth();
+            self.usize_op_in_place(call_depth, BinaryIntOp::Add, 1);
+            let within_limit = self.allocate_register();
+            self.usize_op(call_depth, within_limit, BinaryIntOp::LessThanEquals, max_call_depth);
+            self.constrain_instruction(
+                within_limit,
+                Some(format!("Exceeded the maximum call depth of {max_call_depth}")),
+            );
+            self.deallocate_register(within_limit);
+        }
+        self.check_memory_limit();
+    }
+
+    /// Emits a check that the memory in use, which ends at the stack pointer, is within the limit.
+    fn check_memory_limit(&mut self) {
+        if let Some(max_memory) = self.limits.max_memory {
+            let within_limit = self.allocate_register();
+            self.usize_op(
+                ReservedRegisters::stack_pointer(),
+                within_limit,
+                BinaryIntOp::LessThanEquals,
+                max_memory,
+            );
+            self.constrain_instruction(
+                within_limit,
+                Some(format!("Exceeded the maximum memory size of {max_memory}")),
+            );
+            self.deallocate_register(within_limit);
+        }
+    }
+
     /// Processes a return instruction.
     ///
     /// For Brillig, the return is implicit, since there is no explicit return instruction.
@@ -458,6 +603,10 @@ impl BrilligContext {
     /// the VM.
     pub(crate) fn return_instruction(&mut self, return_registers: &[MemoryAddress]) {
         self.debug_show.return_instruction(return_registers);
+        if self.limits.max_call_depth.is_some() {
+            self.usize_op_in_place(ReservedRegisters::call_depth(), BinaryIntOp::Sub, 1);
+        }
+        self.use_calling_convention_registers(return_registers.len());
         let mut sources = Vec::with_capacity(return_registers.len());
         let mut destinations = Vec::with_capacity(return_registers.len());
 
@@ -636,7 +785,7 @@ impl BrilligContext {
                 self.load_instruction(rc, rc_pointer);
                 self.deallocate_register(rc_pointer);
             }
-            BrilligVariable::BrilligVector(BrilligVector { pointer, size, rc }) => {
+            BrilligVariable::BrilligVector(BrilligVector { pointer, size, rc, capacity }) => {
                 self.load_instruction(pointer, variable_pointer);
 
                 let size_pointer = self.allocate_register();
@@ -652,6 +801,13 @@ impl BrilligContext {
 
                 self.load_instruction(rc, rc_pointer);
                 self.deallocate_register(rc_pointer);
+
+                let capacity_pointer = self.allocate_register();
+                self.mov_instruction(capacity_pointer, variable_pointer);
+                self.usize_op_in_place(capacity_pointer, BinaryIntOp::Add, 3_usize);
+
+                self.load_instruction(capacity, capacity_pointer);
+                self.deallocate_register(capacity_pointer);
             }
         }
     }
@@ -685,7 +841,7 @@ impl BrilligContext {
                 self.store_instruction(rc_pointer, rc);
                 self.deallocate_register(rc_pointer);
             }
-            BrilligVariable::BrilligVector(BrilligVector { pointer, size, rc }) => {
+            BrilligVariable::BrilligVector(BrilligVector { pointer, size, rc, capacity }) => {
                 self.store_instruction(variable_pointer, pointer);
 
                 let size_pointer = self.allocate_register();
@@ -698,8 +854,14 @@ impl BrilligContext {
                 self.usize_op_in_place(rc_pointer, BinaryIntOp::Add, 2_usize);
                 self.store_instruction(rc_pointer, rc);
 
+                let capacity_pointer: MemoryAddress = self.allocate_register();
+                self.mov_instruction(capacity_pointer, variable_pointer);
+                self.usize_op_in_place(capacity_pointer, BinaryIntOp::Add, 3_usize);
+                self.store_instruction(capacity_pointer, capacity);
+
                 self.deallocate_register(size_pointer);
                 self.deallocate_register(rc_pointer);
+                self.deallocate_register(capacity_pointer);
             }
         }
     }
@@ -816,6 +978,64 @@ impl BrilligContext {
         self.deallocate_register(scratch_register_j);
     }
 
+    /// Rotates the bits of the `bit_size`-bit integer in `value` by `amount`, which holds a
+    /// `amount_bit_size`-bit integer, to the left if `left` is true and to the right otherwise.
+    ///
+    /// This is done by using the following formula, where `k = amount % bit_size`:
+    ///
+    /// rotate_left(x, k) = (x << k) | (x >> (bit_size - k))
+    ///
+    /// Both shifts are reduced modulo `2^bit_size`, so that `k == 0` leaves `x` unchanged.
+    pub(crate) fn rotate_instruction(
+        &mut self,
+        result: MemoryAddress,
+        value: MemoryAddress,
+        amount: MemoryAddress,
+        amount_bit_size: u32,
+        bit_size: u32,
+        left: bool,
+    ) {
+        let bit_size_register = self.make_constant(Value::from(bit_size as u128), amount_bit_size);
+        let reduced_amount = self.allocate_register();
+        self.modulo_instruction(reduced_amount, amount, bit_size_register, amount_bit_size, false);
+        let complement = self.allocate_register();
+        self.binary_instruction(
+            bit_size_register,
+            reduced_amount,
+            complement,
+            BrilligBinaryOp::Integer { op: BinaryIntOp::Sub, bit_size: amount_bit_size },
+        );
+
+        let (first_shift, second_shift) = if left {
+            (BinaryIntOp::Shl, BinaryIntOp::Shr)
+        } else {
+            (BinaryIntOp::Shr, BinaryIntOp::Shl)
+        };
+        let shifted = self.allocate_register();
+        self.binary_instruction(
+            value,
+            reduced_amount,
+            shifted,
+            BrilligBinaryOp::Integer { op: first_shift, bit_size },
+        );
+        self.binary_instruction(
+            value,
+            complement,
+            result,
+            BrilligBinaryOp::Integer { op: second_shift, bit_size },
+        );
+        self.binary_instruction(
+            shifted,
+            result,
+            result,
+            BrilligBinaryOp::Integer { op: BinaryIntOp::Or, bit_size },
+        );
+
+        for register in [bit_size_register, reduced_amount, complement, shifted] {
+            self.deallocate_register(register);
+        }
+    }
+
     /// Adds a unresolved external `Call` instruction to the bytecode.
     /// This calls into another function compiled into this brillig artifact.
     pub(crate) fn add_external_call_instruction<T: ToString>(&mut self, func_label: T) {
@@ -922,6 +1142,7 @@ impl BrilligContext {
     ) -> Vec<MemoryAddress> {
         // Save all the registers we have used to the stack.
         let saved_registers = self.save_registers_of_vars(variables_to_save);
+        self.use_calling_convention_registers(arguments.len());
 
         // Move argument values to the front of the registers
         //
@@ -943,6 +1164,7 @@ impl BrilligContext {
     ) {
         // Allocate our result registers and write into them
         // We assume the return values of our call are held in 0..num results register indices
+        self.use_calling_convention_registers(result_registers.len());
         let (sources, destinations) = result_registers
             .iter()
             .enumerate()
@@ -959,9 +1181,15 @@ impl BrilligContext {
     }
 
     /// Utility method to transform a HeapArray to a HeapVector by making a runtime constant with the size.
+    /// The array has no room for more items, so its capacity is its size.
     pub(crate) fn array_to_vector(&mut self, array: &BrilligArray) -> BrilligVector {
         let size_register = self.make_usize_constant(array.size.into());
-        BrilligVector { size: size_register, pointer: array.pointer, rc: array.rc }
+        BrilligVector {
+            size: size_register,
+            pointer: array.pointer,
+            rc: array.rc,
+            capacity: size_register,
+        }
     }
 
     /// Issues a blackbox operation.
@@ -970,8 +1198,44 @@ impl BrilligContext {
         self.push_opcode(BrilligOpcode::BlackBox(op));
     }
 
-    /// Issues a to_radix instruction. This instruction will write the modulus of the source register
-    /// And the radix register limb_count times to the target vector.
+    /// Splits the least significant limb of `value` in the given `radix` off into the `limb`
+    /// register, leaving the remaining limbs in `value`.
+    ///
+    /// Every radix decomposition, in unconstrained code and in the directive solving the limbs of
+    /// constrained code, is made of these steps.
+    pub(crate) fn radix_limb_instruction(
+        &mut self,
+        limb: MemoryAddress,
+        value: MemoryAddress,
+        radix: MemoryAddress,
+    ) {
+        let bit_size = FieldElement::max_num_bits();
+        let quotient = self.allocate_register();
+        // limb = value - (value / radix) * radix
+        self.binary_instruction(
+            value,
+            radix,
+            quotient,
+            BrilligBinaryOp::Integer { op: BinaryIntOp::UnsignedDiv, bit_size },
+        );
+        self.binary_instruction(
+            quotient,
+            radix,
+            limb,
+            BrilligBinaryOp::Integer { op: BinaryIntOp::Mul, bit_size },
+        );
+        self.binary_instruction(
+            value,
+            limb,
+            limb,
+            BrilligBinaryOp::Integer { op: BinaryIntOp::Sub, bit_size },
+        );
+        self.mov_instruction(value, quotient);
+        self.deallocate_register(quotient);
+    }
+
+    /// Issues a to_radix instruction. This instruction will write the limbs of the source register
+    /// in the given radix limb_count times to the target vector.
     pub(crate) fn radix_instruction(
         &mut self,
         source: MemoryAddress,
@@ -981,40 +1245,25 @@ impl BrilligContext {
         big_endian: bool,
     ) {
         self.mov_instruction(target_vector.size, limb_count);
+        self.mov_instruction(target_vector.capacity, limb_count);
         self.usize_const(target_vector.rc, 1_usize.into());
         self.allocate_array_instruction(target_vector.pointer, target_vector.size);
 
         let shifted_register = self.allocate_register();
         self.mov_instruction(shifted_register, source);
 
-        let modulus_register: MemoryAddress = self.allocate_register();
+        let limb_register: MemoryAddress = self.allocate_register();
 
         self.loop_instruction(target_vector.size, |ctx, iterator_register| {
-            // Compute the modulus
-            ctx.modulo_instruction(
-                modulus_register,
-                shifted_register,
-                radix,
-                FieldElement::max_num_bits(),
-                false,
-            );
+            // Split the next limb off the field
+            ctx.radix_limb_instruction(limb_register, shifted_register, radix);
             // Write it
-            ctx.array_set(target_vector.pointer, iterator_register, modulus_register);
-            // Integer div the field
-            ctx.binary_instruction(
-                shifted_register,
-                radix,
-                shifted_register,
-                BrilligBinaryOp::Integer {
-                    op: BinaryIntOp::UnsignedDiv,
-                    bit_size: FieldElement::max_num_bits(),
-                },
-            );
+            ctx.array_set(target_vector.pointer, iterator_register, limb_register);
         });
 
         // Deallocate our temporary registers
         self.deallocate_register(shifted_register);
-        self.deallocate_register(modulus_register);
+        self.deallocate_register(limb_register);
 
         if big_endian {
             self.reverse_vector_in_place_instruction(target_vector);
@@ -1061,6 +1310,28 @@ impl BrilligContext {
     pub(crate) fn set_call_stack(&mut self, call_stack: CallStack) {
         self.obj.set_call_stack(call_stack);
     }
+
+    /// Returns the call stack that the next pushed opcodes will be associated with.
+    pub(crate) fn call_stack(&self) -> &CallStack {
+        self.obj.call_stack()
+    }
+
+    /// Returns the position of the next opcode to be emitted.
+    pub(crate) fn next_opcode_position(&self) -> OpcodeLocation {
+        self.obj.index_of_next_opcode()
+    }
+
+    /// Records that `variable` has held the variable `name` of the source code from the opcode at
+    /// position `start` up to the opcodes emitted so far.
+    pub(crate) fn add_debug_variable(
+        &mut self,
+        name: String,
+        start: OpcodeLocation,
+        variable: BrilligVariable,
+    ) {
+        let end = self.obj.index_of_next_opcode();
+        self.obj.add_variable(name, start..end, variable);
+    }
 }
 
 /// Type to encapsulate the binary operation types in Brillig
@@ -1073,6 +1344,30 @@ pub(crate) enum BrilligBinaryOp {
     Modulo { is_signed_integer: bool, bit_size: u32 },
 }
 
+impl BrilligBinaryOp {
+    /// Returns the result of the operation on `lhs` and `rhs`, exactly as the opcodes emitted for
+    /// it would compute it, or `None` if they would fail.
+    pub(crate) fn evaluate(&self, lhs: FieldElement, rhs: FieldElement) -> Option<FieldElement> {
+        match self {
+            BrilligBinaryOp::Field { op } => Some(evaluate_binary_field_op(op, lhs, rhs)),
+            BrilligBinaryOp::Integer { op, bit_size } => {
+                evaluate_binary_int_op(op, lhs, rhs, *bit_size).ok()
+            }
+            BrilligBinaryOp::Modulo { is_signed_integer, bit_size } => {
+                let division = if *is_signed_integer {
+                    BinaryIntOp::SignedDiv
+                } else {
+                    BinaryIntOp::UnsignedDiv
+                };
+                let quotient = evaluate_binary_int_op(&division, lhs, rhs, *bit_size).ok()?;
+                let product =
+                    evaluate_binary_int_op(&BinaryIntOp::Mul, quotient, rhs, *bit_size).ok()?;
+                evaluate_binary_int_op(&BinaryIntOp::Sub, lhs, product, *bit_size).ok()
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
     use std::vec;
@@ -1124,6 +1419,14 @@ pub(crate) mod tests {
             Ok((4_u128.into(), 5_u128.into()))
         }
 
+        fn multi_scalar_mul(
+            &self,
+            _points: &[FieldElement],
+            _scalars: &[FieldElement],
+        ) -> Result<(FieldElement, FieldElement), BlackBoxResolutionError> {
+            panic!("Path not trodden by this test")
+        }
+
         fn ec_add(
             &self,
             _input1_x: &FieldElement,
@@ -1133,6 +1436,14 @@ pub(crate) mod tests {
         ) -> Result<(FieldElement, FieldElement), BlackBoxResolutionError> {
             panic!("Path not trodden by this test")
         }
+
+        fn poseidon_permutation(
+            &self,
+            _inputs: &[FieldElement],
+            _rate: u32,
+        ) -> Result<Vec<FieldElement>, BlackBoxResolutionError> {
+            panic!("Path not trodden by this test")
+        }
     }
 
     pub(crate) fn create_context() -> BrilligContext {
@@ -1221,4 +1532,34 @@ pub(crate) mod tests {
         let status = vm.process_opcodes();
         assert_eq!(status, VMStatus::Finished { return_data_offset: 0, return_data_size: 0 });
     }
+
+    #[test]
+    fn rotates_integers_by_amounts_known_at_runtime() {
+        let rotate = |value: u128, amount: u128, left: bool| {
+            let mut context = create_context();
+            let value_register = context.allocate_register();
+            let amount_register = context.allocate_register();
+            context.push_opcode(BrilligOpcode::CalldataCopy {
+                destination_address: value_register,
+                size: 2,
+                offset: 0,
+            });
+            let result = context.allocate_register();
+            context.rotate_instruction(result, value_register, amount_register, 32, 8, left);
+            context.mov_instruction(MemoryAddress::from(0), result);
+            context.push_opcode(BrilligOpcode::Stop { return_data_offset: 0, return_data_size: 1 });
+            let bytecode = context.artifact().finish().byte_code;
+
+            let calldata =
+                vec![FieldElement::from(value).into(), FieldElement::from(amount).into()];
+            let (vm, return_data_offset, _) = create_and_run_vm(calldata, &bytecode);
+            vm.get_memory()[return_data_offset].to_field()
+        };
+
+        assert_eq!(rotate(0b1001_0110, 3, true), FieldElement::from(0b1011_0100_u128));
+        assert_eq!(rotate(0b1001_0110, 3, false), FieldElement::from(0b1101_0010_u128));
+        // Rotations are taken modulo the bit size of the value.
+        assert_eq!(rotate(0b1001_0110, 11, true), FieldElement::from(0b1011_0100_u128));
+        assert_eq!(rotate(0b1001_0110, 8, false), FieldElement::from(0b1001_0110_u128));
+    }
 }
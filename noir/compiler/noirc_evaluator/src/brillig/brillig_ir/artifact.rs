@@ -1,8 +1,14 @@
 use acvm::acir::brillig::Opcode as BrilligOpcode;
-use std::collections::{BTreeMap, HashMap};
+use noirc_errors::debug_info::{BrilligValueInfo, BrilligVariableInfo};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ops::Range,
+};
 
 use crate::ssa::ir::dfg::CallStack;
 
+use super::brillig_variable::BrilligVariable;
+
 /// Represents a parameter or a return value of a function.
 #[derive(Debug, Clone)]
 pub(crate) enum BrilligParameter {
@@ -21,6 +27,40 @@ pub(crate) struct GeneratedBrillig {
     pub(crate) byte_code: Vec<BrilligOpcode>,
     pub(crate) locations: BTreeMap<OpcodeLocation, CallStack>,
     pub(crate) assert_messages: BTreeMap<OpcodeLocation, String>,
+    /// Upper bound on the number of opcodes executed before the bytecode stops,
+    /// if one could be derived.
+    pub(crate) step_bound: Option<usize>,
+    /// The variables of the source code held in registers, and the opcodes during which they are.
+    pub(crate) variables: Vec<DebugVariable>,
+    /// The call stack of the block of code whose executions each profiling counter counts, keyed
+    /// by the id of the counter.
+    pub(crate) profile_counters: BTreeMap<usize, CallStack>,
+}
+
+/// A variable of the source code, held in the registers of `variable` while the opcodes in
+/// `opcodes` are executed.
+#[derive(Debug, Clone)]
+pub(crate) struct DebugVariable {
+    pub(crate) name: String,
+    pub(crate) opcodes: Range<OpcodeLocation>,
+    pub(crate) variable: BrilligVariable,
+}
+
+impl DebugVariable {
+    /// Describes the registers of the variable as they are recorded in the debug info of a circuit.
+    pub(crate) fn to_debug_info(&self) -> BrilligVariableInfo {
+        let value = match self.variable {
+            BrilligVariable::Simple(register) => BrilligValueInfo::Register(register.to_usize()),
+            BrilligVariable::BrilligArray(array) => {
+                BrilligValueInfo::Array { pointer: array.pointer.to_usize(), size: array.size }
+            }
+            BrilligVariable::BrilligVector(vector) => BrilligValueInfo::Vector {
+                pointer: vector.pointer.to_usize(),
+                size: vector.size.to_usize(),
+            },
+        };
+        BrilligVariableInfo { name: self.name.clone(), opcodes: self.opcodes.clone(), value }
+    }
 }
 
 #[derive(Default, Debug, Clone)]
@@ -46,6 +86,15 @@ pub(crate) struct BrilligArtifact {
     locations: BTreeMap<OpcodeLocation, CallStack>,
     /// The current call stack. All opcodes that are pushed will be associated with this call stack.
     call_stack: CallStack,
+    /// The opcodes of each loop emitted while lowering a single instruction, e.g. to copy an array,
+    /// along with the maximum number of iterations of the loop if it is known when it is emitted.
+    /// Other loops iterate at most once per element of the arrays involved in the instruction.
+    loops: Vec<(Range<OpcodeLocation>, Option<usize>)>,
+    /// The variables of the source code held in registers, and the opcodes during which they are.
+    pub(crate) variables: Vec<DebugVariable>,
+    /// The call stack of the block of code whose executions each profiling counter counts, keyed
+    /// by the id of the counter.
+    pub(crate) profile_counters: BTreeMap<usize, CallStack>,
 }
 
 /// A pointer to a location in the opcode.
@@ -78,9 +127,17 @@ impl BrilligArtifact {
             byte_code: self.byte_code,
             locations: self.locations,
             assert_messages: self.assert_messages,
+            step_bound: None,
+            variables: self.variables,
+            profile_counters: self.profile_counters,
         }
     }
 
+    /// Returns the labels called by this artifact which are defined in other artifacts.
+    pub(crate) fn unresolved_function_calls(&self) -> impl Iterator<Item = &Label> {
+        self.unresolved_external_call_labels.iter().map(|(_, label)| label)
+    }
+
     /// Gets the first unresolved function call of this artifact.
     pub(crate) fn first_unresolved_function_call(&self) -> Option<Label> {
         self.unresolved_external_call_labels.first().map(|(_, label)| label.clone())
@@ -148,6 +205,19 @@ impl BrilligArtifact {
         for (position_in_bytecode, call_stack) in obj.locations.iter() {
             self.locations.insert(position_in_bytecode + offset, call_stack.clone());
         }
+
+        for (loop_, max_iterations) in &obj.loops {
+            self.loops.push((loop_.start + offset..loop_.end + offset, *max_iterations));
+        }
+
+        for variable in &obj.variables {
+            let opcodes = variable.opcodes.start + offset..variable.opcodes.end + offset;
+            self.variables.push(DebugVariable { opcodes, ..variable.clone() });
+        }
+
+        for (counter, call_stack) in &obj.profile_counters {
+            self.profile_counters.insert(*counter, call_stack.clone());
+        }
     }
 
     /// Adds a brillig instruction to the brillig byte code
@@ -204,6 +274,135 @@ impl BrilligArtifact {
         );
     }
 
+    /// Returns the position of the opcode labelled with `label`, if it is in this bytecode.
+    pub(crate) fn label_position(&self, label: &str) -> Option<OpcodeLocation> {
+        self.labels.get(label).copied()
+    }
+
+    /// Returns the position jumped to by each jump in the bytecode, keyed by the position of the
+    /// jump, or `None` if some jump lands on a label which is not defined in this bytecode.
+    pub(crate) fn jump_targets(&self) -> Option<HashMap<OpcodeLocation, OpcodeLocation>> {
+        self.unresolved_jumps
+            .iter()
+            .map(|(position, label)| Some((*position, self.label_position(label)?)))
+            .collect()
+    }
+
+    /// Removes the opcodes for which `keep` returns false, moving the labels, jumps, calls and
+    /// metadata of the remaining opcodes along with them.
+    pub(crate) fn retain_opcodes(&mut self, mut keep: impl FnMut(&BrilligOpcode) -> bool) {
+        // The position of each opcode once the others have been removed, followed by the new
+        // length of the bytecode.
+        let mut new_positions = Vec::with_capacity(self.byte_code.len() + 1);
+        let mut kept = Vec::with_capacity(self.byte_code.len());
+        let mut num_kept = 0;
+        for opcode in &self.byte_code {
+            new_positions.push(num_kept);
+            let keep_opcode = keep(opcode);
+            if keep_opcode {
+                num_kept += 1;
+            }
+            kept.push(keep_opcode);
+        }
+        new_positions.push(num_kept);
+
+        let mut kept_opcodes = kept.iter();
+        self.byte_code.retain(|_| *kept_opcodes.next().unwrap());
+
+        let move_positions = |positions: &mut Vec<(OpcodeLocation, Label)>| {
+            positions.retain(|(position, _)| kept[*position]);
+            for (position, _) in positions.iter_mut() {
+                *position = new_positions[*position];
+            }
+        };
+        move_positions(&mut self.unresolved_jumps);
+        move_positions(&mut self.unresolved_external_call_labels);
+
+        for position in self.labels.values_mut() {
+            *position = new_positions[*position];
+        }
+        self.assert_messages = std::mem::take(&mut self.assert_messages)
+            .into_iter()
+            .filter(|(position, _)| kept[*position])
+            .map(|(position, message)| (new_positions[position], message))
+            .collect();
+        self.locations = std::mem::take(&mut self.locations)
+            .into_iter()
+            .filter(|(position, _)| kept[*position])
+            .map(|(position, call_stack)| (new_positions[position], call_stack))
+            .collect();
+        for (loop_, _) in self.loops.iter_mut() {
+            *loop_ = new_positions[loop_.start]..new_positions[loop_.end];
+        }
+        move_variables(&mut self.variables, &new_positions);
+    }
+
+    /// Records that `variable` holds the variable `name` of the source code while the opcodes in
+    /// `opcodes` are executed.
+    pub(crate) fn add_variable(
+        &mut self,
+        name: String,
+        opcodes: Range<OpcodeLocation>,
+        variable: BrilligVariable,
+    ) {
+        if !opcodes.is_empty() {
+            self.variables.push(DebugVariable { name, opcodes, variable });
+        }
+    }
+
+    /// Records that the opcodes in `loop_` form a loop.
+    pub(crate) fn add_loop(&mut self, loop_: Range<OpcodeLocation>) {
+        self.loops.push((loop_, None));
+    }
+
+    /// Records that the opcodes in `loop_` form a loop which iterates at most `max_iterations` times.
+    pub(crate) fn add_bounded_loop(&mut self, loop_: Range<OpcodeLocation>, max_iterations: usize) {
+        self.loops.push((loop_, Some(max_iterations)));
+    }
+
+    /// Returns an upper bound on the number of opcodes executed by a single pass through the
+    /// opcodes in `range`, assuming each loop without a known bound iterates at most
+    /// `max_loop_iterations` times.
+    ///
+    /// Jumps out of `range` and the code executed by calls are not accounted for.
+    pub(crate) fn max_steps(
+        &self,
+        range: Range<OpcodeLocation>,
+        max_loop_iterations: usize,
+    ) -> Option<usize> {
+        let mut loops: Vec<_> = self
+            .loops
+            .iter()
+            .filter(|(loop_, _)| range.start <= loop_.start && loop_.end <= range.end)
+            .cloned()
+            .collect();
+        // Outer loops come before the loops nested within them.
+        loops.sort_by_key(|(loop_, _)| (loop_.start, std::cmp::Reverse(loop_.end)));
+        self.max_steps_in(range, &loops, max_loop_iterations)
+    }
+
+    fn max_steps_in(
+        &self,
+        range: Range<OpcodeLocation>,
+        loops: &[(Range<OpcodeLocation>, Option<usize>)],
+        max_loop_iterations: usize,
+    ) -> Option<usize> {
+        let mut steps = range.len();
+        let mut remaining = loops;
+        while let Some(((loop_, max_iterations), rest)) = remaining.split_first() {
+            let nested_count =
+                rest.iter().take_while(|(nested, _)| nested.end <= loop_.end).count();
+            let (nested, rest) = rest.split_at(nested_count);
+            // The loop condition is checked once more than the body is executed.
+            let iterations = max_iterations.unwrap_or(max_loop_iterations);
+            let loop_steps = self.max_steps_in(loop_.clone(), nested, max_loop_iterations)?;
+            steps = (steps - loop_.len())
+                .checked_add(loop_steps.checked_mul(iterations.checked_add(1)?)?)?;
+            remaining = rest;
+        }
+        Some(steps)
+    }
+
     /// Returns the index of the next opcode.
     ///
     /// This is useful for labelling regions of code
@@ -257,8 +456,22 @@ impl BrilligArtifact {
         self.call_stack = call_stack;
     }
 
+    pub(crate) fn call_stack(&self) -> &CallStack {
+        &self.call_stack
+    }
+
     pub(crate) fn add_assert_message_to_last_opcode(&mut self, message: String) {
         let position = self.index_of_next_opcode() - 1;
         self.assert_messages.insert(position, message);
     }
 }
+
+/// Moves the opcode ranges of `variables` to the `new_positions` of their opcodes once some opcodes
+/// have been removed, dropping the variables which are left without any opcode.
+fn move_variables(variables: &mut Vec<DebugVariable>, new_positions: &[OpcodeLocation]) {
+    for variable in variables.iter_mut() {
+        variable.opcodes =
+            new_positions[variable.opcodes.start]..new_positions[variable.opcodes.end];
+    }
+    variables.retain(|variable| !variable.opcodes.is_empty());
+}
@@ -0,0 +1,760 @@
+//! Modular arithmetic on the big integers of the standard library, held in memory as arrays of
+//! limbs.
+//!
+//! A big integer is held as [`BIGINT_LIMBS`] limbs of [`BIGINT_LIMB_BIT_SIZE`] bits, least
+//! significant first, and is always reduced modulo its modulus, which is held in the same way.
+//! The limbs are loaded into registers and combined as integers of [`WIDE_BIT_SIZE`] bits, which
+//! hold the product of two limbs along with the carries added to it. Apart from the square and
+//! multiply loop of divisions, the arithmetic is straight-line code.
+use acvm::acir::brillig::{BinaryIntOp, MemoryAddress};
+use iter_extended::vecmap;
+
+use super::{BrilligBinaryOp, BrilligContext};
+
+/// The number of limbs of a big integer.
+pub(crate) const BIGINT_LIMBS: usize = 4;
+/// The number of bits of each limb of a big integer.
+pub(crate) const BIGINT_LIMB_BIT_SIZE: u32 = 64;
+/// The maximum number of bytes of a big integer or of a modulus.
+pub(crate) const BIGINT_MAX_BYTES: usize = BIGINT_LIMBS * (BIGINT_LIMB_BIT_SIZE as usize / 8);
+/// The bit size of the integers which limbs are combined into. It also holds the three most
+/// significant limbs of a remainder, from which the digits of a quotient are estimated.
+const WIDE_BIT_SIZE: u32 = 3 * BIGINT_LIMB_BIT_SIZE;
+
+/// An operation between two big integers with the same modulus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum BigIntOp {
+    Add,
+    Sub,
+    Mul,
+    /// Multiplication by the inverse of the right-hand side, for a prime modulus.
+    Div,
+}
+
+/// The registers holding the constants used by the limb arithmetic.
+struct LimbConstants {
+    zero: MemoryAddress,
+    one: MemoryAddress,
+    /// `2^BIGINT_LIMB_BIT_SIZE`, the base of the limbs.
+    base: MemoryAddress,
+    /// `base - 1`, which masks the least significant limb of a wide integer.
+    mask: MemoryAddress,
+}
+
+impl LimbConstants {
+    fn new(context: &mut BrilligContext) -> Self {
+        let base = 1_u128 << BIGINT_LIMB_BIT_SIZE;
+        LimbConstants {
+            zero: context.make_constant(0_usize.into(), WIDE_BIT_SIZE),
+            one: context.make_constant(1_usize.into(), WIDE_BIT_SIZE),
+            base: context.make_constant(base.into(), WIDE_BIT_SIZE),
+            mask: context.make_constant((base - 1).into(), WIDE_BIT_SIZE),
+        }
+    }
+
+    fn deallocate(self, context: &mut BrilligContext) {
+        for register in [self.zero, self.one, self.base, self.mask] {
+            context.deallocate_register(register);
+        }
+    }
+}
+
+impl BrilligContext {
+    /// Writes the limbs of the big integer with the little-endian bytes in the vector at
+    /// `bytes_pointer`, reduced modulo the one with the little-endian bytes in the vector at
+    /// `modulus_bytes_pointer`, to the array at `result`, and those of the modulus to the array at
+    /// `modulus`.
+    ///
+    /// Traps if either has more than [`BIGINT_MAX_BYTES`] bytes or if the modulus is zero.
+    pub(crate) fn bigint_from_le_bytes_instruction(
+        &mut self,
+        (bytes_pointer, bytes_len): (MemoryAddress, MemoryAddress),
+        (modulus_bytes_pointer, modulus_bytes_len): (MemoryAddress, MemoryAddress),
+        result: MemoryAddress,
+        modulus: MemoryAddress,
+    ) {
+        let constants = LimbConstants::new(self);
+
+        let modulus_limbs =
+            self.load_le_bytes(modulus_bytes_pointer, modulus_bytes_len, &constants);
+        let any_limb = self.allocate_register();
+        self.mov_instruction(any_limb, constants.zero);
+        for limb in &modulus_limbs {
+            self.wide_op(any_limb, *limb, any_limb, BinaryIntOp::Or);
+        }
+        let is_nonzero = self.allocate_register();
+        self.wide_op(any_limb, constants.zero, is_nonzero, BinaryIntOp::Equals);
+        self.not_instruction(is_nonzero, 1, is_nonzero);
+        self.constrain_instruction(is_nonzero, Some("Big integer modulus must not be zero".into()));
+        self.store_limbs(modulus, &modulus_limbs);
+
+        // The value is less than `base^BIGINT_LIMBS`, so its quotient fits in as many limbs.
+        let mut value_limbs = self.load_le_bytes(bytes_pointer, bytes_len, &constants);
+        value_limbs.extend((0..BIGINT_LIMBS).map(|_| {
+            let limb = self.allocate_register();
+            self.mov_instruction(limb, constants.zero);
+            limb
+        }));
+        let remainder = vecmap(0..BIGINT_LIMBS, |_| self.allocate_register());
+        self.divmod_limbs(&value_limbs, &modulus_limbs, &remainder, &constants);
+        self.store_limbs(result, &remainder);
+
+        for register in [any_limb, is_nonzero].into_iter().chain(modulus_limbs).chain(value_limbs) {
+            self.deallocate_register(register);
+        }
+        self.deallocate_limbs(remainder);
+        constants.deallocate(self);
+    }
+
+    /// Allocates a vector for the little-endian bytes of the big integer whose limbs are in the
+    /// array at `value`, writing its pointer to `bytes_pointer` and its length to `bytes_len`.
+    ///
+    /// There are as many bytes as in the greatest value modulo the one whose limbs are in the array
+    /// at `modulus`, so at least one.
+    pub(crate) fn bigint_to_le_bytes_instruction(
+        &mut self,
+        value: MemoryAddress,
+        modulus: MemoryAddress,
+        bytes_pointer: MemoryAddress,
+        bytes_len: MemoryAddress,
+    ) {
+        let constants = LimbConstants::new(self);
+        let value_limbs = self.load_limbs(value);
+        let modulus_limbs = self.load_limbs(modulus);
+
+        let one_limbs = vecmap(0..BIGINT_LIMBS, |index| {
+            let limb = self.allocate_register();
+            let constant = if index == 0 { constants.one } else { constants.zero };
+            self.mov_instruction(limb, constant);
+            limb
+        });
+        let greatest_value = vecmap(0..BIGINT_LIMBS, |_| self.allocate_register());
+        let no_borrow = self.allocate_register();
+        self.mov_instruction(no_borrow, constants.one);
+        self.sub_limbs(&modulus_limbs, &one_limbs, no_borrow, &greatest_value, &constants);
+
+        // Count the bytes up to the most significant non-zero one, other than the first.
+        let byte = self.allocate_register();
+        let is_nonzero = self.allocate_register();
+        let found = self.allocate_register();
+        self.mov_instruction(found, constants.zero);
+        self.usize_const(bytes_len, 1_usize.into());
+        for index in (1..BIGINT_MAX_BYTES).rev() {
+            self.limbs_byte(&greatest_value, index, byte);
+            self.wide_op(byte, constants.zero, is_nonzero, BinaryIntOp::Equals);
+            self.not_instruction(is_nonzero, 1, is_nonzero);
+            self.wide_op(found, is_nonzero, found, BinaryIntOp::Or);
+            self.memory_op(bytes_len, found, bytes_len, BinaryIntOp::Add);
+        }
+        self.allocate_array_instruction(bytes_pointer, bytes_len);
+
+        let (end_section, end_label) = self.reserve_next_section_label();
+        let is_past_end = self.allocate_register();
+        let address = self.allocate_register();
+        for index in 0..BIGINT_MAX_BYTES {
+            if index > 0 {
+                self.usize_op(bytes_len, is_past_end, BinaryIntOp::LessThanEquals, index);
+                self.jump_if_instruction(is_past_end, end_label.clone());
+            }
+            self.limbs_byte(&value_limbs, index, byte);
+            self.usize_op(bytes_pointer, address, BinaryIntOp::Add, index);
+            self.store_instruction(address, byte);
+        }
+        self.jump_instruction(end_label);
+        self.enter_section(end_section);
+
+        for register in [no_borrow, byte, is_nonzero, found, is_past_end, address] {
+            self.deallocate_register(register);
+        }
+        for limbs in [value_limbs, modulus_limbs, one_limbs, greatest_value] {
+            self.deallocate_limbs(limbs);
+        }
+        constants.deallocate(self);
+    }
+
+    /// Writes the limbs of `lhs op rhs` to the array at `result`, where `lhs` and `rhs` are the
+    /// arrays holding the limbs of the operands, reduced modulo the limbs in the array at
+    /// `lhs_modulus`.
+    ///
+    /// Traps if the array at `rhs_modulus` holds a different modulus.
+    pub(crate) fn bigint_op_instruction(
+        &mut self,
+        op: BigIntOp,
+        (lhs, lhs_modulus): (MemoryAddress, MemoryAddress),
+        (rhs, rhs_modulus): (MemoryAddress, MemoryAddress),
+        result: MemoryAddress,
+    ) {
+        let constants = LimbConstants::new(self);
+        let modulus = self.load_limbs(lhs_modulus);
+        let rhs_modulus = self.load_limbs(rhs_modulus);
+        let is_same_modulus = self.allocate_register();
+        let is_same_limb = self.allocate_register();
+        self.mov_instruction(is_same_modulus, constants.one);
+        for (lhs_limb, rhs_limb) in modulus.iter().zip(&rhs_modulus) {
+            self.wide_op(*lhs_limb, *rhs_limb, is_same_limb, BinaryIntOp::Equals);
+            self.wide_op(is_same_modulus, is_same_limb, is_same_modulus, BinaryIntOp::Mul);
+        }
+        self.constrain_instruction(
+            is_same_modulus,
+            Some("Big integers must have the same modulus".into()),
+        );
+
+        let lhs = self.load_limbs(lhs);
+        let rhs = self.load_limbs(rhs);
+        let result_limbs = vecmap(0..BIGINT_LIMBS, |_| self.allocate_register());
+        match op {
+            BigIntOp::Add => self.mod_add_limbs(&lhs, &rhs, &modulus, &result_limbs, &constants),
+            BigIntOp::Sub => self.mod_sub_limbs(&lhs, &rhs, &modulus, &result_limbs, &constants),
+            BigIntOp::Mul => self.mod_mul_limbs(&lhs, &rhs, &modulus, &result_limbs, &constants),
+            BigIntOp::Div => {
+                // By Fermat's little theorem, rhs^(modulus - 2) is the inverse of rhs.
+                let two_limbs = vecmap(0..BIGINT_LIMBS, |index| {
+                    let limb = if index == 0 { 2_usize } else { 0 };
+                    self.make_constant(limb.into(), WIDE_BIT_SIZE)
+                });
+                let exponent = vecmap(0..BIGINT_LIMBS, |_| self.allocate_register());
+                let no_borrow = self.allocate_register();
+                self.mov_instruction(no_borrow, constants.one);
+                self.sub_limbs(&modulus, &two_limbs, no_borrow, &exponent, &constants);
+                let inverse = vecmap(0..BIGINT_LIMBS, |_| self.allocate_register());
+                self.mod_pow_limbs(&rhs, &exponent, &modulus, &inverse, &constants);
+                self.mod_mul_limbs(&lhs, &inverse, &modulus, &result_limbs, &constants);
+
+                self.deallocate_register(no_borrow);
+                for limbs in [two_limbs, exponent, inverse] {
+                    self.deallocate_limbs(limbs);
+                }
+            }
+        }
+        self.store_limbs(result, &result_limbs);
+
+        self.deallocate_register(is_same_modulus);
+        self.deallocate_register(is_same_limb);
+        for limbs in [modulus, rhs_modulus, lhs, rhs, result_limbs] {
+            self.deallocate_limbs(limbs);
+        }
+        constants.deallocate(self);
+    }
+
+    /// Loads the limbs of the array at `pointer` into new registers.
+    fn load_limbs(&mut self, pointer: MemoryAddress) -> Vec<MemoryAddress> {
+        let address = self.allocate_register();
+        let limbs = vecmap(0..BIGINT_LIMBS, |index| {
+            let limb = self.allocate_register();
+            self.usize_op(pointer, address, BinaryIntOp::Add, index);
+            self.load_instruction(limb, address);
+            limb
+        });
+        self.deallocate_register(address);
+        limbs
+    }
+
+    /// Stores `limbs` into the array at `pointer`.
+    fn store_limbs(&mut self, pointer: MemoryAddress, limbs: &[MemoryAddress]) {
+        let address = self.allocate_register();
+        for (index, limb) in limbs.iter().enumerate() {
+            self.usize_op(pointer, address, BinaryIntOp::Add, index);
+            self.store_instruction(address, *limb);
+        }
+        self.deallocate_register(address);
+    }
+
+    fn deallocate_limbs(&mut self, limbs: Vec<MemoryAddress>) {
+        for limb in limbs {
+            self.deallocate_register(limb);
+        }
+    }
+
+    /// Loads the limbs of the integer with the little-endian bytes in the vector at `pointer` of
+    /// length `len` into new registers, trapping if it has more than [`BIGINT_MAX_BYTES`] bytes.
+    fn load_le_bytes(
+        &mut self,
+        pointer: MemoryAddress,
+        len: MemoryAddress,
+        constants: &LimbConstants,
+    ) -> Vec<MemoryAddress> {
+        let fits = self.allocate_register();
+        self.usize_op(len, fits, BinaryIntOp::LessThanEquals, BIGINT_MAX_BYTES);
+        self.constrain_instruction(
+            fits,
+            Some(format!("Big integers must have at most {BIGINT_MAX_BYTES} bytes")),
+        );
+        let limbs = vecmap(0..BIGINT_LIMBS, |_| {
+            let limb = self.allocate_register();
+            self.mov_instruction(limb, constants.zero);
+            limb
+        });
+
+        let (end_section, end_label) = self.reserve_next_section_label();
+        let address = self.allocate_register();
+        let byte = self.allocate_register();
+        let bytes_per_limb = BIGINT_LIMB_BIT_SIZE as usize / 8;
+        for index in 0..BIGINT_MAX_BYTES {
+            self.usize_op(len, fits, BinaryIntOp::LessThanEquals, index);
+            self.jump_if_instruction(fits, end_label.clone());
+            self.usize_op(pointer, address, BinaryIntOp::Add, index);
+            self.load_instruction(byte, address);
+            let shift = 1_u128 << (8 * (index % bytes_per_limb));
+            let shift = self.make_constant(shift.into(), WIDE_BIT_SIZE);
+            self.wide_op(byte, shift, byte, BinaryIntOp::Mul);
+            let limb = limbs[index / bytes_per_limb];
+            self.wide_op(limb, byte, limb, BinaryIntOp::Add);
+            self.deallocate_register(shift);
+        }
+        self.jump_instruction(end_label);
+        self.enter_section(end_section);
+
+        for register in [fits, address, byte] {
+            self.deallocate_register(register);
+        }
+        limbs
+    }
+
+    /// Writes the byte at `index` of the integer with the given limbs to `byte`.
+    fn limbs_byte(&mut self, limbs: &[MemoryAddress], index: usize, byte: MemoryAddress) {
+        let bytes_per_limb = BIGINT_LIMB_BIT_SIZE as usize / 8;
+        let shift = 1_u128 << (8 * (index % bytes_per_limb));
+        let shift = self.make_constant(shift.into(), WIDE_BIT_SIZE);
+        let byte_mask = self.make_constant(0xff_usize.into(), WIDE_BIT_SIZE);
+        self.wide_op(limbs[index / bytes_per_limb], shift, byte, BinaryIntOp::UnsignedDiv);
+        self.wide_op(byte, byte_mask, byte, BinaryIntOp::And);
+        self.deallocate_register(shift);
+        self.deallocate_register(byte_mask);
+    }
+
+    fn wide_op(
+        &mut self,
+        lhs: MemoryAddress,
+        rhs: MemoryAddress,
+        result: MemoryAddress,
+        op: BinaryIntOp,
+    ) {
+        let operation = BrilligBinaryOp::Integer { op, bit_size: WIDE_BIT_SIZE };
+        self.binary_instruction(lhs, rhs, result, operation);
+    }
+
+    /// Splits the wide integer `value` into its least significant limb, written to `low`, and the
+    /// rest, written to `high`. `low` must not be `value`.
+    fn split_limb(
+        &mut self,
+        value: MemoryAddress,
+        low: MemoryAddress,
+        high: MemoryAddress,
+        constants: &LimbConstants,
+    ) {
+        self.wide_op(value, constants.mask, low, BinaryIntOp::And);
+        self.wide_op(value, constants.base, high, BinaryIntOp::UnsignedDiv);
+    }
+
+    /// Writes the limbs of `lhs + rhs + carry` to `sum`, leaving the carry out of the most
+    /// significant limb in `carry`.
+    fn add_limbs(
+        &mut self,
+        lhs: &[MemoryAddress],
+        rhs: &[MemoryAddress],
+        carry: MemoryAddress,
+        sum: &[MemoryAddress],
+        constants: &LimbConstants,
+    ) {
+        let total = self.allocate_register();
+        for ((lhs, rhs), sum) in lhs.iter().zip(rhs).zip(sum) {
+            self.wide_op(*lhs, *rhs, total, BinaryIntOp::Add);
+            self.wide_op(total, carry, total, BinaryIntOp::Add);
+            self.split_limb(total, *sum, carry, constants);
+        }
+        self.deallocate_register(total);
+    }
+
+    /// Writes the limbs of `lhs - rhs - (1 - no_borrow)` modulo `base^limbs` to `difference`,
+    /// leaving in `no_borrow` whether the difference is not negative.
+    fn sub_limbs(
+        &mut self,
+        lhs: &[MemoryAddress],
+        rhs: &[MemoryAddress],
+        no_borrow: MemoryAddress,
+        difference: &[MemoryAddress],
+        constants: &LimbConstants,
+    ) {
+        // Each limb is offset by `base`, whose limb above is taken back unless it is borrowed.
+        let total = self.allocate_register();
+        for ((lhs, rhs), difference) in lhs.iter().zip(rhs).zip(difference) {
+            self.wide_op(*lhs, constants.mask, total, BinaryIntOp::Add);
+            self.wide_op(total, *rhs, total, BinaryIntOp::Sub);
+            self.wide_op(total, no_borrow, total, BinaryIntOp::Add);
+            self.split_limb(total, *difference, no_borrow, constants);
+        }
+        self.deallocate_register(total);
+    }
+
+    /// Writes `if_true` to `result` if `condition` is one, or `if_false` if it is zero.
+    fn select_limbs(
+        &mut self,
+        condition: MemoryAddress,
+        if_true: &[MemoryAddress],
+        if_false: &[MemoryAddress],
+        result: &[MemoryAddress],
+    ) {
+        let difference = self.allocate_register();
+        for ((if_true, if_false), result) in if_true.iter().zip(if_false).zip(result) {
+            self.wide_op(*if_true, *if_false, difference, BinaryIntOp::Sub);
+            self.wide_op(difference, condition, difference, BinaryIntOp::Mul);
+            self.wide_op(*if_false, difference, *result, BinaryIntOp::Add);
+        }
+        self.deallocate_register(difference);
+    }
+
+    /// Returns new registers holding the limbs of `lhs * rhs`, twice as many as those of `lhs`.
+    fn mul_limbs(
+        &mut self,
+        lhs: &[MemoryAddress],
+        rhs: &[MemoryAddress],
+        constants: &LimbConstants,
+    ) -> Vec<MemoryAddress> {
+        let len = lhs.len();
+        let product = vecmap(0..2 * len, |_| self.allocate_register());
+        let column = self.allocate_register();
+        let term = self.allocate_register();
+        let carry = self.allocate_register();
+        self.mov_instruction(carry, constants.zero);
+        for index in 0..2 * len - 1 {
+            self.mov_instruction(column, carry);
+            for lhs_index in index.saturating_sub(len - 1)..=index.min(len - 1) {
+                self.wide_op(lhs[lhs_index], rhs[index - lhs_index], term, BinaryIntOp::Mul);
+                self.wide_op(column, term, column, BinaryIntOp::Add);
+            }
+            self.split_limb(column, product[index], carry, constants);
+        }
+        self.mov_instruction(product[2 * len - 1], carry);
+
+        for register in [column, term, carry] {
+            self.deallocate_register(register);
+        }
+        product
+    }
+
+    /// Writes `(lhs + rhs) % modulus` to `result`, for `lhs` and `rhs` less than `modulus`.
+    fn mod_add_limbs(
+        &mut self,
+        lhs: &[MemoryAddress],
+        rhs: &[MemoryAddress],
+        modulus: &[MemoryAddress],
+        result: &[MemoryAddress],
+        constants: &LimbConstants,
+    ) {
+        let carry = self.allocate_register();
+        self.mov_instruction(carry, constants.zero);
+        self.add_limbs(lhs, rhs, carry, result, constants);
+
+        // The sum is less than twice the modulus, so subtracting it once reduces the sum when it
+        // carries or when it does not borrow.
+        let reduced = vecmap(0..BIGINT_LIMBS, |_| self.allocate_register());
+        let no_borrow = self.allocate_register();
+        self.mov_instruction(no_borrow, constants.one);
+        self.sub_limbs(result, modulus, no_borrow, &reduced, constants);
+        self.wide_op(no_borrow, carry, no_borrow, BinaryIntOp::Add);
+        self.select_limbs(no_borrow, &reduced, result, result);
+
+        self.deallocate_register(carry);
+        self.deallocate_register(no_borrow);
+        self.deallocate_limbs(reduced);
+    }
+
+    /// Writes `(lhs - rhs) % modulus` to `result`, for `lhs` and `rhs` less than `modulus`.
+    fn mod_sub_limbs(
+        &mut self,
+        lhs: &[MemoryAddress],
+        rhs: &[MemoryAddress],
+        modulus: &[MemoryAddress],
+        result: &[MemoryAddress],
+        constants: &LimbConstants,
+    ) {
+        let no_borrow = self.allocate_register();
+        self.mov_instruction(no_borrow, constants.one);
+        self.sub_limbs(lhs, rhs, no_borrow, result, constants);
+
+        // A negative difference wraps around to itself plus the modulus once the carry is dropped.
+        let wrapped = vecmap(0..BIGINT_LIMBS, |_| self.allocate_register());
+        let carry = self.allocate_register();
+        self.mov_instruction(carry, constants.zero);
+        self.add_limbs(result, modulus, carry, &wrapped, constants);
+        self.select_limbs(no_borrow, result, &wrapped, result);
+
+        self.deallocate_register(no_borrow);
+        self.deallocate_register(carry);
+        self.deallocate_limbs(wrapped);
+    }
+
+    /// Writes `(lhs * rhs) % modulus` to `result`, for `lhs` and `rhs` less than `modulus`.
+    fn mod_mul_limbs(
+        &mut self,
+        lhs: &[MemoryAddress],
+        rhs: &[MemoryAddress],
+        modulus: &[MemoryAddress],
+        result: &[MemoryAddress],
+        constants: &LimbConstants,
+    ) {
+        let product = self.mul_limbs(lhs, rhs, constants);
+        self.divmod_limbs(&product, modulus, result, constants);
+        self.deallocate_limbs(product);
+    }
+
+    /// Writes `base^exponent % modulus` to `result`, for `base` less than `modulus`.
+    ///
+    /// The bits of the exponent are consumed from the most significant one, squaring the result
+    /// at each of them and multiplying it by the base at those which are set.
+    fn mod_pow_limbs(
+        &mut self,
+        base: &[MemoryAddress],
+        exponent: &[MemoryAddress],
+        modulus: &[MemoryAddress],
+        result: &[MemoryAddress],
+        constants: &LimbConstants,
+    ) {
+        let exponent_bits = BIGINT_LIMBS * BIGINT_LIMB_BIT_SIZE as usize;
+        let remaining = vecmap(exponent, |limb| {
+            let register = self.allocate_register();
+            self.mov_instruction(register, *limb);
+            register
+        });
+        for (index, limb) in result.iter().enumerate() {
+            self.mov_instruction(*limb, if index == 0 { constants.one } else { constants.zero });
+        }
+        let multiplied = vecmap(0..BIGINT_LIMBS, |_| self.allocate_register());
+        let top_bit =
+            self.make_constant((1_u128 << (BIGINT_LIMB_BIT_SIZE - 1)).into(), WIDE_BIT_SIZE);
+        let two = self.make_constant(2_usize.into(), WIDE_BIT_SIZE);
+        let bit = self.allocate_register();
+        let shifted = self.allocate_register();
+        let carry = self.allocate_register();
+        let step = self.make_usize_constant(0_usize.into());
+        let is_done = self.allocate_register();
+
+        let (loop_section, loop_label) = self.reserve_next_section_label();
+        let (exit_section, exit_label) = self.reserve_next_section_label();
+        self.enter_section(loop_section);
+        let loop_start = self.obj.index_of_next_opcode();
+        self.usize_op(step, is_done, BinaryIntOp::Equals, exponent_bits);
+        self.jump_if_instruction(is_done, exit_label);
+
+        let square = self.mul_limbs(result, result, constants);
+        self.divmod_limbs(&square, modulus, result, constants);
+        self.deallocate_limbs(square);
+
+        // Shift the most significant bit out of the exponent.
+        self.wide_op(remaining[BIGINT_LIMBS - 1], top_bit, bit, BinaryIntOp::UnsignedDiv);
+        self.mov_instruction(carry, constants.zero);
+        for limb in &remaining {
+            self.wide_op(*limb, two, shifted, BinaryIntOp::Mul);
+            self.wide_op(shifted, carry, shifted, BinaryIntOp::Add);
+            self.split_limb(shifted, *limb, carry, constants);
+        }
+
+        self.mod_mul_limbs(result, base, modulus, &multiplied, constants);
+        self.select_limbs(bit, &multiplied, result, result);
+
+        self.usize_op_in_place(step, BinaryIntOp::Add, 1);
+        self.jump_instruction(loop_label);
+        self.obj.add_bounded_loop(loop_start..self.obj.index_of_next_opcode(), exponent_bits);
+        self.enter_section(exit_section);
+
+        for register in [top_bit, two, bit, shifted, carry, step, is_done] {
+            self.deallocate_register(register);
+        }
+        self.deallocate_limbs(remaining);
+        self.deallocate_limbs(multiplied);
+    }
+
+    /// Writes the limbs of the remainder of `numerator` by `divisor` to `remainder`.
+    ///
+    /// The numerator has twice as many limbs as the divisor, which must not be zero, and the
+    /// quotient must fit in as many limbs as the divisor, as it does when the numerator is the
+    /// product of two integers less than the divisor.
+    ///
+    /// This is Knuth's long division (The Art of Computer Programming, algorithm 4.3.1 D): both
+    /// operands are shifted left until the most significant bit of the divisor is set, then each
+    /// digit of the quotient is estimated from the three most significant limbs of the remainder
+    /// and the two of the divisor, which overestimates it by at most one.
+    fn divmod_limbs(
+        &mut self,
+        numerator: &[MemoryAddress],
+        divisor: &[MemoryAddress],
+        remainder: &[MemoryAddress],
+        constants: &LimbConstants,
+    ) {
+        let len = divisor.len();
+        let temporary = self.allocate_register();
+        let term = self.allocate_register();
+
+        // The number of most significant limbs of the divisor which are zero, as a one-hot vector.
+        let limb_shift = self.allocate_register();
+        let is_leading_zero = self.allocate_register();
+        self.mov_instruction(limb_shift, constants.zero);
+        self.mov_instruction(is_leading_zero, constants.one);
+        for limb in divisor[1..].iter().rev() {
+            self.wide_op(*limb, constants.zero, temporary, BinaryIntOp::Equals);
+            self.wide_op(is_leading_zero, temporary, is_leading_zero, BinaryIntOp::Mul);
+            self.wide_op(limb_shift, is_leading_zero, limb_shift, BinaryIntOp::Add);
+        }
+        let is_limb_shift = vecmap(0..len, |shift| {
+            let register = self.allocate_register();
+            self.const_instruction(register, shift.into(), WIDE_BIT_SIZE);
+            self.wide_op(limb_shift, register, register, BinaryIntOp::Equals);
+            register
+        });
+
+        // The power of two which shifts the most significant non-zero limb of the divisor until
+        // its most significant bit is set, found by binary search.
+        let top_limb = self.allocate_register();
+        self.mov_instruction(top_limb, constants.zero);
+        for (shift, is_shift) in is_limb_shift.iter().enumerate() {
+            self.wide_op(*is_shift, divisor[len - 1 - shift], term, BinaryIntOp::Mul);
+            self.wide_op(top_limb, term, top_limb, BinaryIntOp::Add);
+        }
+        let bit_shift = self.allocate_register();
+        self.mov_instruction(bit_shift, constants.one);
+        let mut step = BIGINT_LIMB_BIT_SIZE / 2;
+        while step > 0 {
+            let bound =
+                self.make_constant((1_u128 << (BIGINT_LIMB_BIT_SIZE - step)).into(), WIDE_BIT_SIZE);
+            self.wide_op(top_limb, bound, temporary, BinaryIntOp::LessThan);
+            self.const_instruction(term, ((1_u128 << step) - 1).into(), WIDE_BIT_SIZE);
+            self.wide_op(term, temporary, term, BinaryIntOp::Mul);
+            self.wide_op(term, constants.one, term, BinaryIntOp::Add);
+            self.wide_op(top_limb, term, top_limb, BinaryIntOp::Mul);
+            self.wide_op(bit_shift, term, bit_shift, BinaryIntOp::Mul);
+            self.deallocate_register(bound);
+            step /= 2;
+        }
+
+        let shifted_divisor = self.shift_limbs_left(divisor, bit_shift, &is_limb_shift, constants);
+        // The quotient fits in `len` limbs, so the shifted numerator fits in `2 * len` limbs.
+        let shifted_numerator =
+            self.shift_limbs_left(numerator, bit_shift, &is_limb_shift, constants);
+
+        let divisor_top = self.allocate_register();
+        self.wide_op(shifted_divisor[len - 1], constants.base, divisor_top, BinaryIntOp::Mul);
+        self.wide_op(divisor_top, shifted_divisor[len - 2], divisor_top, BinaryIntOp::Add);
+        let digit = self.allocate_register();
+        let carry = self.allocate_register();
+        let no_borrow = self.allocate_register();
+        for index in (0..len).rev() {
+            let window = &shifted_numerator[index..=index + len];
+
+            // Estimate the digit from the three most significant limbs of the window, capped to
+            // the greatest digit.
+            self.wide_op(window[len], constants.base, digit, BinaryIntOp::Mul);
+            self.wide_op(digit, window[len - 1], digit, BinaryIntOp::Add);
+            self.wide_op(digit, constants.base, digit, BinaryIntOp::Mul);
+            self.wide_op(digit, window[len - 2], digit, BinaryIntOp::Add);
+            self.wide_op(digit, divisor_top, digit, BinaryIntOp::UnsignedDiv);
+            self.wide_op(digit, constants.base, temporary, BinaryIntOp::LessThan);
+            self.wide_op(digit, temporary, digit, BinaryIntOp::Add);
+            self.wide_op(digit, constants.one, digit, BinaryIntOp::Sub);
+
+            // window -= digit * divisor
+            self.mov_instruction(carry, constants.zero);
+            self.mov_instruction(no_borrow, constants.one);
+            for (limb, divisor_limb) in window.iter().zip(&shifted_divisor) {
+                self.wide_op(digit, *divisor_limb, temporary, BinaryIntOp::Mul);
+                self.wide_op(temporary, carry, temporary, BinaryIntOp::Add);
+                self.split_limb(temporary, term, carry, constants);
+                self.wide_op(*limb, constants.mask, temporary, BinaryIntOp::Add);
+                self.wide_op(temporary, term, temporary, BinaryIntOp::Sub);
+                self.wide_op(temporary, no_borrow, temporary, BinaryIntOp::Add);
+                self.split_limb(temporary, *limb, no_borrow, constants);
+            }
+            self.wide_op(window[len], constants.mask, temporary, BinaryIntOp::Add);
+            self.wide_op(temporary, carry, temporary, BinaryIntOp::Sub);
+            self.wide_op(temporary, no_borrow, temporary, BinaryIntOp::Add);
+            self.split_limb(temporary, window[len], no_borrow, constants);
+
+            // If the digit was overestimated, the window went negative: add the divisor back.
+            let borrow = no_borrow;
+            self.wide_op(constants.one, no_borrow, borrow, BinaryIntOp::Sub);
+            self.mov_instruction(carry, constants.zero);
+            for (limb, divisor_limb) in window.iter().zip(&shifted_divisor) {
+                self.wide_op(*divisor_limb, borrow, temporary, BinaryIntOp::Mul);
+                self.wide_op(temporary, *limb, temporary, BinaryIntOp::Add);
+                self.wide_op(temporary, carry, temporary, BinaryIntOp::Add);
+                self.split_limb(temporary, *limb, carry, constants);
+            }
+            self.wide_op(window[len], carry, window[len], BinaryIntOp::Add);
+            self.wide_op(window[len], constants.mask, window[len], BinaryIntOp::And);
+        }
+
+        // Shift the remainder back right, first by whole limbs then by bits.
+        let unshifted = vecmap(0..len, |index| {
+            let limb = self.allocate_register();
+            self.mov_instruction(limb, constants.zero);
+            for (shift, is_shift) in is_limb_shift.iter().enumerate().take(len - index) {
+                self.wide_op(*is_shift, shifted_numerator[index + shift], term, BinaryIntOp::Mul);
+                self.wide_op(limb, term, limb, BinaryIntOp::Add);
+            }
+            limb
+        });
+        let bit_unshift = self.allocate_register();
+        self.wide_op(constants.base, bit_shift, bit_unshift, BinaryIntOp::UnsignedDiv);
+        for index in 0..len {
+            self.wide_op(unshifted[index], bit_shift, remainder[index], BinaryIntOp::UnsignedDiv);
+            if index + 1 < len {
+                self.wide_op(unshifted[index + 1], bit_unshift, term, BinaryIntOp::Mul);
+                self.wide_op(term, constants.mask, term, BinaryIntOp::And);
+                self.wide_op(remainder[index], term, remainder[index], BinaryIntOp::Add);
+            }
+        }
+
+        for register in [
+            temporary,
+            term,
+            limb_shift,
+            is_leading_zero,
+            top_limb,
+            bit_shift,
+            divisor_top,
+            digit,
+            carry,
+            no_borrow,
+            bit_unshift,
+        ] {
+            self.deallocate_register(register);
+        }
+        for limbs in [is_limb_shift, shifted_divisor, shifted_numerator, unshifted] {
+            self.deallocate_limbs(limbs);
+        }
+    }
+
+    /// Returns new registers holding the limbs of `limbs * bit_shift * base^limb_shift`, where
+    /// `is_limb_shift` is one only at the index `limb_shift`, as many as the given limbs.
+    fn shift_limbs_left(
+        &mut self,
+        limbs: &[MemoryAddress],
+        bit_shift: MemoryAddress,
+        is_limb_shift: &[MemoryAddress],
+        constants: &LimbConstants,
+    ) -> Vec<MemoryAddress> {
+        let carry = self.allocate_register();
+        let product = self.allocate_register();
+        self.mov_instruction(carry, constants.zero);
+        let bit_shifted = vecmap(limbs, |limb| {
+            let shifted = self.allocate_register();
+            self.wide_op(*limb, bit_shift, product, BinaryIntOp::Mul);
+            self.wide_op(product, carry, product, BinaryIntOp::Add);
+            self.split_limb(product, shifted, carry, constants);
+            shifted
+        });
+        let shifted = vecmap(0..limbs.len(), |index| {
+            let limb = self.allocate_register();
+            self.mov_instruction(limb, constants.zero);
+            for (shift, is_shift) in is_limb_shift.iter().enumerate().take(index + 1) {
+                self.wide_op(*is_shift, bit_shifted[index - shift], product, BinaryIntOp::Mul);
+                self.wide_op(limb, product, limb, BinaryIntOp::Add);
+            }
+            limb
+        });
+
+        self.deallocate_register(carry);
+        self.deallocate_register(product);
+        self.deallocate_limbs(bit_shifted);
+        shifted
+    }
+}
@@ -33,6 +33,9 @@ pub(crate) struct BrilligVector {
     pub(crate) pointer: MemoryAddress,
     pub(crate) size: MemoryAddress,
     pub(crate) rc: MemoryAddress,
+    /// The number of items allocated at the pointer, at least the size, so that items can be
+    /// added in place.
+    pub(crate) capacity: MemoryAddress,
 }
 
 impl BrilligVector {
@@ -41,11 +44,11 @@ impl BrilligVector {
     }
 
     pub(crate) fn registers_count() -> usize {
-        3
+        4
     }
 
     pub(crate) fn extract_registers(self) -> Vec<MemoryAddress> {
-        vec![self.pointer, self.size, self.rc]
+        vec![self.pointer, self.size, self.rc, self.capacity]
     }
 }
 
@@ -0,0 +1,252 @@
+//! Copies, fills and comparisons of consecutive memory cells, e.g. to clone an array or initialize
+//! one.
+//!
+//! Brillig has no opcode for these, so they are emitted as loops. Each loop advances a pointer per
+//! array instead of indexing the arrays with a counter, so that an element costs a few opcodes on
+//! top of the loads and stores.
+use acvm::acir::brillig::{BinaryFieldOp, BinaryIntOp, MemoryAddress};
+use iter_extended::vecmap;
+
+use super::{BrilligBinaryOp, BrilligContext};
+
+impl BrilligContext {
+    /// Copies the values of an array pointed by source with length stored in `num_elements_register`
+    /// Into the array pointed by destination
+    pub(crate) fn copy_array_instruction(
+        &mut self,
+        source_pointer: MemoryAddress,
+        destination_pointer: MemoryAddress,
+        num_elements_register: MemoryAddress,
+    ) {
+        self.debug_show.copy_array_instruction(
+            source_pointer,
+            destination_pointer,
+            num_elements_register,
+        );
+
+        self.copy_elements(source_pointer, destination_pointer, num_elements_register, false);
+    }
+
+    /// Copies the values of an array pointed by source with length stored in `num_elements_register`
+    /// Into the array pointed by destination, starting with the last element. Unlike
+    /// [`Self::copy_array_instruction`], this is correct when the arrays overlap with the destination
+    /// after the source, e.g. to shift elements towards the end of an array.
+    pub(crate) fn copy_array_backward_instruction(
+        &mut self,
+        source_pointer: MemoryAddress,
+        destination_pointer: MemoryAddress,
+        num_elements_register: MemoryAddress,
+    ) {
+        self.debug_show.copy_array_backward_instruction(
+            source_pointer,
+            destination_pointer,
+            num_elements_register,
+        );
+
+        self.copy_elements(source_pointer, destination_pointer, num_elements_register, true);
+    }
+
+    /// Writes `value` to each of the `num_elements_register` elements of the array pointed by
+    /// `destination_pointer`.
+    pub(crate) fn fill_array_instruction(
+        &mut self,
+        destination_pointer: MemoryAddress,
+        num_elements_register: MemoryAddress,
+        value: MemoryAddress,
+    ) {
+        self.debug_show.fill_array_instruction(destination_pointer, num_elements_register, value);
+
+        self.pointer_loop_instruction(
+            num_elements_register,
+            &[destination_pointer],
+            false,
+            |ctx, addresses| {
+                ctx.store_instruction(addresses[0], value);
+            },
+        );
+    }
+
+    /// Sets `result` to whether the `num_elements_register` elements of the arrays pointed by
+    /// `lhs_pointer` and `rhs_pointer` are all equal.
+    pub(crate) fn compare_arrays_instruction(
+        &mut self,
+        lhs_pointer: MemoryAddress,
+        rhs_pointer: MemoryAddress,
+        num_elements_register: MemoryAddress,
+        result: MemoryAddress,
+    ) {
+        self.debug_show.compare_arrays_instruction(
+            lhs_pointer,
+            rhs_pointer,
+            num_elements_register,
+            result,
+        );
+
+        self.usize_const(result, 1_usize.into());
+        let lhs_value = self.allocate_register();
+        let rhs_value = self.allocate_register();
+        self.pointer_loop_instruction(
+            num_elements_register,
+            &[lhs_pointer, rhs_pointer],
+            false,
+            |ctx, addresses| {
+                ctx.load_instruction(lhs_value, addresses[0]);
+                ctx.load_instruction(rhs_value, addresses[1]);
+                ctx.binary_instruction(
+                    lhs_value,
+                    rhs_value,
+                    lhs_value,
+                    BrilligBinaryOp::Field { op: BinaryFieldOp::Equals },
+                );
+                ctx.memory_op(result, lhs_value, result, BinaryIntOp::And);
+            },
+        );
+        self.deallocate_register(lhs_value);
+        self.deallocate_register(rhs_value);
+    }
+
+    fn copy_elements(
+        &mut self,
+        source_pointer: MemoryAddress,
+        destination_pointer: MemoryAddress,
+        num_elements_register: MemoryAddress,
+        backward: bool,
+    ) {
+        let value_register = self.allocate_register();
+        self.pointer_loop_instruction(
+            num_elements_register,
+            &[source_pointer, destination_pointer],
+            backward,
+            |ctx, addresses| {
+                ctx.load_instruction(value_register, addresses[0]);
+                ctx.store_instruction(addresses[1], value_register);
+            },
+        );
+        self.deallocate_register(value_register);
+    }
+
+    /// Emits a loop over `num_elements_register` consecutive cells starting at each of `pointers`,
+    /// from the last cells to the first ones if `backward`. The body of the loop is issued by
+    /// `on_element`, given registers holding the address of the current cell of each.
+    fn pointer_loop_instruction<F>(
+        &mut self,
+        num_elements_register: MemoryAddress,
+        pointers: &[MemoryAddress],
+        backward: bool,
+        on_element: F,
+    ) where
+        F: FnOnce(&mut BrilligContext, &[MemoryAddress]),
+    {
+        let (exit_section, exit_label) = self.reserve_next_section_label();
+        let condition = self.allocate_register();
+        self.usize_op(num_elements_register, condition, BinaryIntOp::Equals, 0);
+        self.jump_if_instruction(condition, exit_label);
+
+        // Going backward, the addresses start past the arrays and are decremented before each
+        // element, until the first address is back at its pointer.
+        let addresses = vecmap(pointers, |pointer| {
+            let address = self.allocate_register();
+            if backward {
+                self.memory_op(*pointer, num_elements_register, address, BinaryIntOp::Add);
+            } else {
+                self.mov_instruction(address, *pointer);
+            }
+            address
+        });
+        let end = self.allocate_register();
+        if backward {
+            self.mov_instruction(end, pointers[0]);
+        } else {
+            self.memory_op(addresses[0], num_elements_register, end, BinaryIntOp::Add);
+        }
+        let one = self.make_usize_constant(1_usize.into());
+
+        // The loop is only entered with at least one element, so the condition is checked last.
+        let (loop_section, loop_label) = self.reserve_next_section_label();
+        self.enter_section(loop_section);
+        let loop_start = self.obj.index_of_next_opcode();
+        if backward {
+            for address in &addresses {
+                self.memory_op(*address, one, *address, BinaryIntOp::Sub);
+            }
+            on_element(self, &addresses);
+            self.memory_op(end, addresses[0], condition, BinaryIntOp::LessThan);
+        } else {
+            on_element(self, &addresses);
+            for address in &addresses {
+                self.memory_op(*address, one, *address, BinaryIntOp::Add);
+            }
+            self.memory_op(addresses[0], end, condition, BinaryIntOp::LessThan);
+        }
+        self.jump_if_instruction(condition, loop_label);
+        self.obj.add_loop(loop_start..self.obj.index_of_next_opcode());
+
+        self.enter_section(exit_section);
+
+        for register in addresses.into_iter().chain([condition, end, one]) {
+            self.deallocate_register(register);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::brillig_vm::brillig::{BinaryIntOp, Value};
+
+    use crate::brillig::brillig_ir::{
+        artifact::BrilligParameter,
+        brillig_variable::BrilligArray,
+        tests::{create_and_run_vm, create_context, create_entry_point_bytecode},
+    };
+
+    #[test]
+    fn copies_and_fills_arrays() {
+        let calldata = values(&[1, 2, 3, 4]);
+        let arguments = vec![BrilligParameter::Array(vec![BrilligParameter::Simple(32)], 4)];
+        let returns = vec![BrilligParameter::Array(vec![BrilligParameter::Simple(32)], 6)];
+
+        let mut context = create_context();
+        let source = context.allocate_register();
+        let _source_rc = context.allocate_register();
+
+        let result = BrilligArray {
+            pointer: context.allocate_register(),
+            size: 6,
+            rc: context.allocate_register(),
+        };
+        context.allocate_fixed_length_array(result.pointer, result.size);
+        context.usize_const(result.rc, 1_usize.into());
+
+        let len = context.make_usize_constant(result.size.into());
+        let seven = context.make_usize_constant(7_usize.into());
+        context.fill_array_instruction(result.pointer, len, seven);
+
+        // Copy the source into the middle of the result, and nothing over its start.
+        let zero = context.make_usize_constant(0_usize.into());
+        context.copy_array_instruction(source, result.pointer, zero);
+        let middle = context.allocate_register();
+        context.usize_op(result.pointer, middle, BinaryIntOp::Add, 1);
+        context.usize_const(len, 4_usize.into());
+        context.copy_array_instruction(source, middle, len);
+
+        // Shift the last three copied elements by one, over the last one.
+        let shifted = context.allocate_register();
+        context.usize_op(result.pointer, shifted, BinaryIntOp::Add, 2);
+        context.usize_const(len, 3_usize.into());
+        context.copy_array_backward_instruction(middle, shifted, len);
+
+        context.return_instruction(&result.extract_registers());
+
+        let bytecode = create_entry_point_bytecode(context, arguments, returns).byte_code;
+        let (vm, return_data_offset, return_data_size) = create_and_run_vm(calldata, &bytecode);
+        assert_eq!(return_data_size, 6);
+        assert_eq!(
+            vm.get_memory()[return_data_offset..return_data_offset + 6],
+            values(&[7, 1, 1, 2, 3, 7])
+        );
+    }
+
+    fn values(values: &[usize]) -> Vec<Value> {
+        values.iter().map(|value| Value::from(*value)).collect()
+    }
+}
@@ -0,0 +1,339 @@
+//! This module removes the opcodes of linked Brillig bytecode which can never be executed, or
+//! which only write registers that are never read afterwards.
+//!
+//! Registers are tracked individually, while memory past the registers is only accessed through
+//! pointers and is treated as a whole: writing to it is a side effect, as are foreign calls, black
+//! box functions, integer divisions (which fail on a zero divisor) and traps.
+//!
+//! The bytecode is analyzed as a single control flow graph. A call continues both at the function
+//! it calls and at the next opcode, and a return continues after any of the calls, which keeps every
+//! value that a function returns through its registers.
+use std::collections::BTreeSet;
+
+use acvm::acir::brillig::{BinaryIntOp, MemoryAddress, Opcode as BrilligOpcode};
+use iter_extended::vecmap;
+
+use super::{
+    artifact::{BrilligArtifact, OpcodeLocation},
+    register_allocation::for_each_register,
+    MAX_STACK_SIZE,
+};
+
+/// The registers read and written by an opcode.
+struct Accesses {
+    reads: Vec<MemoryAddress>,
+    writes: Vec<MemoryAddress>,
+    /// Whether the opcode does anything besides writing `writes`.
+    has_side_effects: bool,
+}
+
+impl Accesses {
+    fn new(opcode: &mut BrilligOpcode) -> Self {
+        let mut accesses = Accesses {
+            reads: Vec::new(),
+            writes: Vec::new(),
+            has_side_effects: match opcode {
+                BrilligOpcode::BinaryIntOp { op, .. } => {
+                    matches!(op, BinaryIntOp::SignedDiv | BinaryIntOp::UnsignedDiv)
+                }
+                BrilligOpcode::BinaryFieldOp { .. }
+                | BrilligOpcode::Cast { .. }
+                | BrilligOpcode::Mov { .. }
+                | BrilligOpcode::Load { .. }
+                | BrilligOpcode::Const { .. } => false,
+                _ => true,
+            },
+        };
+        match opcode {
+            BrilligOpcode::CalldataCopy { destination_address, size, .. } => {
+                let start = destination_address.to_usize();
+                accesses.writes.extend(registers_in(start..start + *size));
+            }
+            BrilligOpcode::Stop { return_data_offset, return_data_size } => {
+                let start = *return_data_offset;
+                accesses.reads.extend(registers_in(start..start + *return_data_size));
+            }
+            _ => for_each_register(opcode, &mut |address, access| {
+                if !is_register(*address) {
+                    accesses.has_side_effects |= access.writes();
+                    return;
+                }
+                if access.reads() {
+                    accesses.reads.push(*address);
+                }
+                if access.writes() {
+                    accesses.writes.push(*address);
+                }
+            }),
+        }
+        accesses
+    }
+
+    /// Updates `live`, the registers live after the opcode, to the registers live before it, and
+    /// returns whether the opcode has to be kept.
+    fn transfer(&self, live: &mut BTreeSet<MemoryAddress>) -> bool {
+        if !self.has_side_effects && self.writes.iter().all(|register| !live.contains(register)) {
+            return false;
+        }
+        for register in &self.writes {
+            live.remove(register);
+        }
+        live.extend(self.reads.iter().copied());
+        true
+    }
+}
+
+fn is_register(address: MemoryAddress) -> bool {
+    address.to_usize() < MAX_STACK_SIZE
+}
+
+fn registers_in(range: std::ops::Range<usize>) -> impl Iterator<Item = MemoryAddress> {
+    range.map(MemoryAddress::from).filter(|address| is_register(*address))
+}
+
+/// Returns where execution may continue after the opcode at `position`, with the return
+/// opcodes continuing at any of `return_sites`.
+fn successors(
+    byte_code: &[BrilligOpcode],
+    position: OpcodeLocation,
+    return_sites: &[OpcodeLocation],
+) -> Vec<OpcodeLocation> {
+    let next = position + 1;
+    let successors = match &byte_code[position] {
+        BrilligOpcode::Jump { location } => vec![*location],
+        BrilligOpcode::JumpIf { location, .. }
+        | BrilligOpcode::JumpIfNot { location, .. }
+        | BrilligOpcode::Call { location } => vec![*location, next],
+        BrilligOpcode::Return => return_sites.to_vec(),
+        BrilligOpcode::Stop { .. } | BrilligOpcode::Trap => Vec::new(),
+        _ => vec![next],
+    };
+    successors.into_iter().filter(|successor| *successor < byte_code.len()).collect()
+}
+
+impl BrilligArtifact {
+    /// Removes the opcodes which can never be executed, or which have no side effects and only
+    /// write registers that are never read afterwards.
+    ///
+    /// The bytecode must be fully linked, it is left as is otherwise.
+    pub(crate) fn eliminate_dead_code(&mut self) {
+        if self.byte_code.is_empty() || self.first_unresolved_function_call().is_some() {
+            return;
+        }
+        let Some(jump_targets) = self.jump_targets() else {
+            return;
+        };
+        // The analysis follows the jumps, so it runs over a copy of the bytecode where they are
+        // resolved.
+        let mut byte_code = self.byte_code.clone();
+        for (position, target) in jump_targets {
+            match &mut byte_code[position] {
+                BrilligOpcode::Jump { location }
+                | BrilligOpcode::JumpIf { location, .. }
+                | BrilligOpcode::JumpIfNot { location, .. }
+                | BrilligOpcode::Call { location } => *location = target,
+                _ => unreachable!(
+                    "all jump labels should point to a jump instruction in the bytecode"
+                ),
+            }
+        }
+
+        let accesses = vecmap(&mut byte_code, Accesses::new);
+        let return_sites: Vec<_> = byte_code
+            .iter()
+            .enumerate()
+            .filter(|(_, opcode)| matches!(opcode, BrilligOpcode::Call { .. }))
+            .map(|(position, _)| position + 1)
+            .collect();
+
+        // Blocks start at the beginning of the bytecode and after or at any change of control flow.
+        let mut starts = BTreeSet::from([0]);
+        for position in 0..byte_code.len() {
+            let successors = successors(&byte_code, position, &return_sites);
+            if successors != [position + 1] {
+                starts.insert(position + 1);
+                starts.extend(successors);
+            }
+        }
+        let starts: Vec<_> = starts.into_iter().filter(|start| *start < byte_code.len()).collect();
+        let blocks = vecmap(0..starts.len(), |index| {
+            starts[index]..starts.get(index + 1).copied().unwrap_or(byte_code.len())
+        });
+        let block_successors = vecmap(&blocks, |block| {
+            let successors = successors(&byte_code, block.end - 1, &return_sites);
+            vecmap(successors, |successor| starts.binary_search(&successor).unwrap())
+        });
+
+        let mut reachable = vec![false; blocks.len()];
+        let mut to_visit = vec![0];
+        while let Some(block) = to_visit.pop() {
+            if !std::mem::replace(&mut reachable[block], true) {
+                to_visit.extend(block_successors[block].iter().copied());
+            }
+        }
+
+        let live_before = |block: usize, mut live: BTreeSet<MemoryAddress>| {
+            for position in blocks[block].clone().rev() {
+                accesses[position].transfer(&mut live);
+            }
+            live
+        };
+        let live_after = |block: usize, live_in: &[BTreeSet<MemoryAddress>]| -> BTreeSet<_> {
+            let successors = block_successors[block].iter();
+            successors.flat_map(|successor| live_in[*successor].iter().copied()).collect()
+        };
+        let mut live_in = vec![BTreeSet::new(); blocks.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for block in (0..blocks.len()).rev().filter(|block| reachable[*block]) {
+                let block_live_in = live_before(block, live_after(block, &live_in));
+                if block_live_in != live_in[block] {
+                    live_in[block] = block_live_in;
+                    changed = true;
+                }
+            }
+        }
+
+        let mut keep = vec![false; byte_code.len()];
+        for block in (0..blocks.len()).filter(|block| reachable[*block]) {
+            let mut live = live_after(block, &live_in);
+            for position in blocks[block].clone().rev() {
+                keep[position] = accesses[position].transfer(&mut live);
+            }
+        }
+        if keep.iter().all(|keep| *keep) {
+            return;
+        }
+        let mut keep = keep.into_iter();
+        self.retain_opcodes(|_| keep.next().unwrap());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use acvm::acir::brillig::{BinaryFieldOp, BinaryIntOp, MemoryAddress, Opcode as BrilligOpcode};
+
+    use crate::brillig::brillig_ir::artifact::{BrilligArtifact, GeneratedBrillig};
+
+    /// Builds an artifact out of `byte_code`, where jumps land on labels named after the position
+    /// they jump to.
+    fn artifact_with_jumps(byte_code: Vec<BrilligOpcode>) -> BrilligArtifact {
+        let mut artifact = BrilligArtifact::default();
+        let mut targets = Vec::new();
+        for mut opcode in byte_code {
+            let target = match &mut opcode {
+                BrilligOpcode::Jump { location }
+                | BrilligOpcode::JumpIf { location, .. }
+                | BrilligOpcode::JumpIfNot { location, .. } => Some(std::mem::take(location)),
+                _ => None,
+            };
+            match target {
+                Some(target) => {
+                    targets.push(target);
+                    artifact.add_unresolved_jump(opcode, target.to_string());
+                }
+                None => artifact.push_opcode(opcode),
+            }
+        }
+        targets.sort_unstable();
+        targets.dedup();
+        for target in targets {
+            artifact.add_label_at_position(target.to_string(), target);
+        }
+        artifact
+    }
+
+    fn eliminate_dead_code(mut artifact: BrilligArtifact) -> GeneratedBrillig {
+        artifact.eliminate_dead_code();
+        artifact.finish()
+    }
+
+    fn add(destination: usize, lhs: usize, rhs: usize) -> BrilligOpcode {
+        BrilligOpcode::BinaryFieldOp {
+            destination: MemoryAddress(destination),
+            op: BinaryFieldOp::Add,
+            lhs: MemoryAddress(lhs),
+            rhs: MemoryAddress(rhs),
+        }
+    }
+
+    fn constant(destination: usize, value: u128) -> BrilligOpcode {
+        BrilligOpcode::Const {
+            destination: MemoryAddress(destination),
+            bit_size: 32,
+            value: value.into(),
+        }
+    }
+
+    #[test]
+    fn removes_unreachable_opcodes_and_unused_results() {
+        let byte_code = vec![
+            constant(0, 1),
+            // Only read to compute a value which is never read.
+            constant(1, 2),
+            add(2, 1, 1),
+            BrilligOpcode::Jump { location: 5 },
+            BrilligOpcode::Trap,
+            add(0, 0, 0),
+            BrilligOpcode::JumpIf { condition: MemoryAddress(0), location: 8 },
+            BrilligOpcode::Trap,
+            BrilligOpcode::Stop { return_data_offset: 0, return_data_size: 1 },
+        ];
+        let mut artifact = artifact_with_jumps(byte_code);
+        artifact.assert_messages = BTreeMap::from([(7, "unreachable".to_string())]);
+
+        let generated_brillig = eliminate_dead_code(artifact);
+        assert_eq!(
+            generated_brillig.byte_code,
+            vec![
+                constant(0, 1),
+                BrilligOpcode::Jump { location: 2 },
+                add(0, 0, 0),
+                BrilligOpcode::JumpIf { condition: MemoryAddress(0), location: 5 },
+                BrilligOpcode::Trap,
+                BrilligOpcode::Stop { return_data_offset: 0, return_data_size: 1 },
+            ]
+        );
+        assert_eq!(generated_brillig.assert_messages, BTreeMap::from([(4, "unreachable".into())]));
+    }
+
+    #[test]
+    fn keeps_opcodes_with_side_effects_and_values_returned_by_calls() {
+        let division = BrilligOpcode::BinaryIntOp {
+            destination: MemoryAddress(3),
+            op: BinaryIntOp::UnsignedDiv,
+            bit_size: 32,
+            lhs: MemoryAddress(0),
+            rhs: MemoryAddress(1),
+        };
+        let mut function = BrilligArtifact::default();
+        function.add_label_at_position("function".to_string(), 0);
+        function.push_opcode(constant(2, 7));
+        function.push_opcode(BrilligOpcode::Stop { return_data_offset: 0, return_data_size: 0 });
+
+        let mut artifact = BrilligArtifact::default();
+        artifact.add_unresolved_external_call(
+            BrilligOpcode::Call { location: 0 },
+            "function".to_string(),
+        );
+        // The result of the division is unused, but the division fails if `r1` is zero.
+        artifact.push_opcode(division.clone());
+        artifact.push_opcode(BrilligOpcode::Stop { return_data_offset: 2, return_data_size: 1 });
+        artifact.link_with(&function);
+
+        let generated_brillig = eliminate_dead_code(artifact);
+        assert_eq!(
+            generated_brillig.byte_code,
+            vec![
+                BrilligOpcode::Call { location: 3 },
+                division,
+                BrilligOpcode::Stop { return_data_offset: 2, return_data_size: 1 },
+                constant(2, 7),
+                BrilligOpcode::Return,
+            ]
+        );
+    }
+}
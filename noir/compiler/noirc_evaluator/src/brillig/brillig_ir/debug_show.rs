@@ -3,8 +3,8 @@
 use super::BrilligBinaryOp;
 use crate::brillig::brillig_ir::{ReservedRegisters, BRILLIG_MEMORY_ADDRESSING_BIT_SIZE};
 use acvm::acir::brillig::{
-    BinaryFieldOp, BinaryIntOp, BlackBoxOp, HeapArray, HeapVector, MemoryAddress, Value,
-    ValueOrArray,
+    BinaryFieldOp, BinaryIntOp, BlackBoxOp, HeapArray, HeapVector, MemoryAddress,
+    Opcode as BrilligOpcode, Value, ValueOrArray,
 };
 
 /// Trait for converting values into debug-friendly strings.
@@ -30,6 +30,10 @@ impl DebugToString for MemoryAddress {
             "Stack".into()
         } else if *self == ReservedRegisters::previous_stack_pointer() {
             "PrevStack".into()
+        } else if *self == ReservedRegisters::call_depth() {
+            "CallDepth".into()
+        } else if *self == ReservedRegisters::foreign_call_cache() {
+            "CallCache".into()
         } else {
             format!("R{}", self.to_usize())
         }
@@ -74,9 +78,8 @@ impl DebugToString for BinaryIntOp {
             BinaryIntOp::And => "&&".into(),
             BinaryIntOp::Or => "||".into(),
             BinaryIntOp::Xor => "^".into(),
-            BinaryIntOp::Shl | BinaryIntOp::Shr => {
-                unreachable!("bit shift should have been replaced")
-            }
+            BinaryIntOp::Shl => "<<".into(),
+            BinaryIntOp::Shr => ">>".into(),
         }
     }
 }
@@ -108,7 +111,12 @@ impl DebugToString for BrilligBinaryOp {
 
 impl DebugToString for Value {
     fn debug_to_string(&self) -> String {
-        self.to_usize().to_string()
+        let value = self.to_field();
+        if value.fits_in_u128() {
+            value.to_u128().to_string()
+        } else {
+            format!("0x{}", value.to_hex())
+        }
     }
 }
 
@@ -122,12 +130,251 @@ impl DebugToString for ValueOrArray {
     }
 }
 
+impl DebugToString for BlackBoxOp {
+    fn debug_to_string(&self) -> String {
+        match self {
+            BlackBoxOp::Sha256 { message, output } => {
+                format!("SHA256 {} -> {}", message.debug_to_string(), output.debug_to_string())
+            }
+            BlackBoxOp::Keccak256 { message, output } => {
+                format!("KECCAK256 {} -> {}", message.debug_to_string(), output.debug_to_string())
+            }
+            BlackBoxOp::Keccakf1600 { message, output } => {
+                format!("KECCAKF1600 {} -> {}", message.debug_to_string(), output.debug_to_string())
+            }
+            BlackBoxOp::Blake2s { message, output } => {
+                format!("BLAKE2S {} -> {}", message.debug_to_string(), output.debug_to_string())
+            }
+            BlackBoxOp::Blake3 { message, output } => {
+                format!("BLAKE3 {} -> {}", message.debug_to_string(), output.debug_to_string())
+            }
+            BlackBoxOp::EcdsaSecp256k1 {
+                hashed_msg,
+                public_key_x,
+                public_key_y,
+                signature,
+                result,
+            } => format!(
+                "ECDSA_SECP256K1 {} {} {} {} -> {}",
+                hashed_msg.debug_to_string(),
+                public_key_x.debug_to_string(),
+                public_key_y.debug_to_string(),
+                signature.debug_to_string(),
+                result.debug_to_string()
+            ),
+            BlackBoxOp::EcdsaSecp256r1 {
+                hashed_msg,
+                public_key_x,
+                public_key_y,
+                signature,
+                result,
+            } => format!(
+                "ECDSA_SECP256R1 {} {} {} {} -> {}",
+                hashed_msg.debug_to_string(),
+                public_key_x.debug_to_string(),
+                public_key_y.debug_to_string(),
+                signature.debug_to_string(),
+                result.debug_to_string()
+            ),
+            BlackBoxOp::FixedBaseScalarMul { low, high, result } => format!(
+                "FIXED_BASE_SCALAR_MUL {} {} -> {}",
+                low.debug_to_string(),
+                high.debug_to_string(),
+                result.debug_to_string()
+            ),
+            BlackBoxOp::EmbeddedCurveAdd { input1_x, input1_y, input2_x, input2_y, result } => {
+                format!(
+                    "EMBEDDED_CURVE_ADD ({} {}) ({} {}) -> {}",
+                    input1_x.debug_to_string(),
+                    input1_y.debug_to_string(),
+                    input2_x.debug_to_string(),
+                    input2_y.debug_to_string(),
+                    result.debug_to_string()
+                )
+            }
+            BlackBoxOp::PedersenCommitment { inputs, domain_separator, output } => format!(
+                "PEDERSEN {} {} -> {}",
+                inputs.debug_to_string(),
+                domain_separator.debug_to_string(),
+                output.debug_to_string()
+            ),
+            BlackBoxOp::PedersenHash { inputs, domain_separator, output } => format!(
+                "PEDERSEN_HASH {} {} -> {}",
+                inputs.debug_to_string(),
+                domain_separator.debug_to_string(),
+                output.debug_to_string()
+            ),
+            BlackBoxOp::SchnorrVerify {
+                public_key_x,
+                public_key_y,
+                message,
+                signature,
+                result,
+            } => format!(
+                "SCHNORR_VERIFY {} {} {} {} -> {}",
+                public_key_x.debug_to_string(),
+                public_key_y.debug_to_string(),
+                message.debug_to_string(),
+                signature.debug_to_string(),
+                result.debug_to_string()
+            ),
+            BlackBoxOp::BigIntAdd { lhs, rhs, output } => format!(
+                "BIGINT_ADD {} {} -> {}",
+                lhs.debug_to_string(),
+                rhs.debug_to_string(),
+                output.debug_to_string()
+            ),
+            BlackBoxOp::BigIntSub { lhs, rhs, output } => format!(
+                "BIGINT_NEG {} {} -> {}",
+                lhs.debug_to_string(),
+                rhs.debug_to_string(),
+                output.debug_to_string()
+            ),
+            BlackBoxOp::BigIntMul { lhs, rhs, output } => format!(
+                "BIGINT_MUL {} {} -> {}",
+                lhs.debug_to_string(),
+                rhs.debug_to_string(),
+                output.debug_to_string()
+            ),
+            BlackBoxOp::BigIntDiv { lhs, rhs, output } => format!(
+                "BIGINT_DIV {} {} -> {}",
+                lhs.debug_to_string(),
+                rhs.debug_to_string(),
+                output.debug_to_string()
+            ),
+            BlackBoxOp::BigIntFromLeBytes { inputs, modulus, output } => format!(
+                "BIGINT_FROM_LE_BYTES {} {} -> {}",
+                inputs.debug_to_string(),
+                modulus.debug_to_string(),
+                output.debug_to_string()
+            ),
+            BlackBoxOp::BigIntToLeBytes { input, output } => format!(
+                "BIGINT_TO_LE_BYTES {} -> {}",
+                input.debug_to_string(),
+                output.debug_to_string()
+            ),
+            BlackBoxOp::Poseidon2Permutation { message, output, len } => format!(
+                "POSEIDON2_PERMUTATION {} {} -> {}",
+                message.debug_to_string(),
+                len.debug_to_string(),
+                output.debug_to_string()
+            ),
+            BlackBoxOp::Sha256Compression { input, hash_values, output } => format!(
+                "SHA256COMPRESSION {} {} -> {}",
+                input.debug_to_string(),
+                hash_values.debug_to_string(),
+                output.debug_to_string()
+            ),
+        }
+    }
+}
+
 impl<T: DebugToString> DebugToString for [T] {
     fn debug_to_string(&self) -> String {
         self.iter().map(|x| x.debug_to_string()).collect::<Vec<String>>().join(", ")
     }
 }
 
+/// Returns the textual form of `opcode` in the disassembly of linked bytecode, in which the
+/// targets of jumps and calls are written as the label `L<index>` of the opcode they lead to.
+pub(crate) fn disassemble_opcode(opcode: &BrilligOpcode) -> String {
+    match opcode {
+        BrilligOpcode::BinaryFieldOp { destination, op, lhs, rhs } => format!(
+            "{} = {} {} {}",
+            destination.debug_to_string(),
+            lhs.debug_to_string(),
+            op.debug_to_string(),
+            rhs.debug_to_string()
+        ),
+        BrilligOpcode::BinaryIntOp { destination, op, bit_size, lhs, rhs } => format!(
+            "{} = {} i{}::{} {}",
+            destination.debug_to_string(),
+            lhs.debug_to_string(),
+            bit_size,
+            op.debug_to_string(),
+            rhs.debug_to_string()
+        ),
+        BrilligOpcode::Cast { destination, source, bit_size } => format!(
+            "CAST {}, {} as u{}",
+            destination.debug_to_string(),
+            source.debug_to_string(),
+            bit_size
+        ),
+        BrilligOpcode::JumpIfNot { condition, location } => {
+            format!("JUMP_IF_NOT {} TO L{location}", condition.debug_to_string())
+        }
+        BrilligOpcode::JumpIf { condition, location } => {
+            format!("JUMP_IF {} TO L{location}", condition.debug_to_string())
+        }
+        BrilligOpcode::Jump { location } => format!("JUMP_TO L{location}"),
+        BrilligOpcode::CalldataCopy { destination_address, size, offset } => format!(
+            "CALLDATA_COPY {}[0..{size}] = CALLDATA[{offset}..{}]",
+            destination_address.debug_to_string(),
+            offset + size
+        ),
+        BrilligOpcode::Call { location } => format!("CALL L{location}"),
+        BrilligOpcode::Const { destination, bit_size, value } => {
+            format!(
+                "CONST {}: u{bit_size} = {}",
+                destination.debug_to_string(),
+                value.debug_to_string()
+            )
+        }
+        BrilligOpcode::Return => "RETURN".into(),
+        BrilligOpcode::ForeignCall { function, destinations, inputs, .. } => format!(
+            "FOREIGN_CALL {function} ({}) => {}",
+            inputs.debug_to_string(),
+            destinations.debug_to_string()
+        ),
+        BrilligOpcode::Mov { destination, source } => {
+            format!("MOV {}, {}", destination.debug_to_string(), source.debug_to_string())
+        }
+        BrilligOpcode::Load { destination, source_pointer } => {
+            format!(
+                "LOAD {} = *{}",
+                destination.debug_to_string(),
+                source_pointer.debug_to_string()
+            )
+        }
+        BrilligOpcode::Store { destination_pointer, source } => {
+            format!(
+                "STORE *{} = {}",
+                destination_pointer.debug_to_string(),
+                source.debug_to_string()
+            )
+        }
+        BrilligOpcode::BlackBox(op) => op.debug_to_string(),
+        BrilligOpcode::Trap => "TRAP".into(),
+        BrilligOpcode::Stop { return_data_offset, return_data_size } => format!(
+            "STOP {}[0..{return_data_size}]",
+            MemoryAddress(*return_data_offset).debug_to_string()
+        ),
+    }
+}
+
+/// Returns the mnemonic of the kind of `opcode`, as it starts its disassembly where it has one.
+pub(crate) fn opcode_mnemonic(opcode: &BrilligOpcode) -> &'static str {
+    match opcode {
+        BrilligOpcode::BinaryFieldOp { .. } => "BINARY_FIELD_OP",
+        BrilligOpcode::BinaryIntOp { .. } => "BINARY_INT_OP",
+        BrilligOpcode::Cast { .. } => "CAST",
+        BrilligOpcode::JumpIfNot { .. } => "JUMP_IF_NOT",
+        BrilligOpcode::JumpIf { .. } => "JUMP_IF",
+        BrilligOpcode::Jump { .. } => "JUMP_TO",
+        BrilligOpcode::CalldataCopy { .. } => "CALLDATA_COPY",
+        BrilligOpcode::Call { .. } => "CALL",
+        BrilligOpcode::Const { .. } => "CONST",
+        BrilligOpcode::Return => "RETURN",
+        BrilligOpcode::ForeignCall { .. } => "FOREIGN_CALL",
+        BrilligOpcode::Mov { .. } => "MOV",
+        BrilligOpcode::Load { .. } => "LOAD",
+        BrilligOpcode::Store { .. } => "STORE",
+        BrilligOpcode::BlackBox(_) => "BLACK_BOX",
+        BrilligOpcode::Trap => "TRAP",
+        BrilligOpcode::Stop { .. } => "STOP",
+    }
+}
+
 macro_rules! debug_println {
     ( $enable_debug:expr, $literal:expr ) => {
         if $enable_debug {
@@ -314,6 +561,66 @@ impl DebugShow {
         );
     }
 
+    /// Debug function for copy_array_backward_instruction
+    pub(crate) fn copy_array_backward_instruction(
+        &self,
+        source: MemoryAddress,
+        destination: MemoryAddress,
+        num_elements_register: MemoryAddress,
+    ) {
+        debug_println!(
+            self.enable_debug_trace,
+            "  COPY_ARRAY_BACKWARD {} -> {} ({} ELEMENTS)",
+            source,
+            destination,
+            num_elements_register
+        );
+    }
+
+    /// Debug function for compare_arrays_instruction
+    pub(crate) fn compare_arrays_instruction(
+        &self,
+        lhs: MemoryAddress,
+        rhs: MemoryAddress,
+        num_elements_register: MemoryAddress,
+        result: MemoryAddress,
+    ) {
+        debug_println!(
+            self.enable_debug_trace,
+            "  {} = COMPARE_ARRAYS {} == {} ({} ELEMENTS)",
+            result,
+            lhs,
+            rhs,
+            num_elements_register
+        );
+    }
+
+    /// Debug function for memoized_foreign_call_instruction
+    pub(crate) fn memoized_foreign_call_instruction(&self, func_name: &str, cache_id: usize) {
+        debug_println!(
+            self.enable_debug_trace,
+            "  MEMOIZED_FOREIGN_CALL {} (CACHE ID {})",
+            func_name,
+            cache_id
+        );
+    }
+
+    /// Debug function for fill_array_instruction
+    pub(crate) fn fill_array_instruction(
+        &self,
+        destination: MemoryAddress,
+        num_elements_register: MemoryAddress,
+        value: MemoryAddress,
+    ) {
+        debug_println!(
+            self.enable_debug_trace,
+            "  FILL_ARRAY {} ({} ELEMENTS) = {}",
+            destination,
+            num_elements_register,
+            value
+        );
+    }
+
     /// Debug function for enter_context
     pub(crate) fn enter_context(&self, label: String) {
         if !label.ends_with("-b0") {
@@ -359,183 +666,7 @@ impl DebugShow {
 
     /// Debug function for black_box_op
     pub(crate) fn black_box_op_instruction(&self, op: &BlackBoxOp) {
-        match op {
-            BlackBoxOp::Sha256 { message, output } => {
-                debug_println!(self.enable_debug_trace, "  SHA256 {} -> {}", message, output);
-            }
-            BlackBoxOp::Keccak256 { message, output } => {
-                debug_println!(self.enable_debug_trace, "  KECCAK256 {} -> {}", message, output);
-            }
-            BlackBoxOp::Keccakf1600 { message, output } => {
-                debug_println!(self.enable_debug_trace, "  KECCAKF1600 {} -> {}", message, output);
-            }
-            BlackBoxOp::Blake2s { message, output } => {
-                debug_println!(self.enable_debug_trace, "  BLAKE2S {} -> {}", message, output);
-            }
-            BlackBoxOp::Blake3 { message, output } => {
-                debug_println!(self.enable_debug_trace, "  BLAKE3 {} -> {}", message, output);
-            }
-            BlackBoxOp::EcdsaSecp256k1 {
-                hashed_msg,
-                public_key_x,
-                public_key_y,
-                signature,
-                result,
-            } => {
-                debug_println!(
-                    self.enable_debug_trace,
-                    "  ECDSA_SECP256K1 {} {} {} {} -> {}",
-                    hashed_msg,
-                    public_key_x,
-                    public_key_y,
-                    signature,
-                    result
-                );
-            }
-            BlackBoxOp::EcdsaSecp256r1 {
-                hashed_msg,
-                public_key_x,
-                public_key_y,
-                signature,
-                result,
-            } => {
-                debug_println!(
-                    self.enable_debug_trace,
-                    "  ECDSA_SECP256R1 {} {} {} {} -> {}",
-                    hashed_msg,
-                    public_key_x,
-                    public_key_y,
-                    signature,
-                    result
-                );
-            }
-            BlackBoxOp::FixedBaseScalarMul { low, high, result } => {
-                debug_println!(
-                    self.enable_debug_trace,
-                    "  FIXED_BASE_SCALAR_MUL {} {} -> {}",
-                    low,
-                    high,
-                    result
-                );
-            }
-            BlackBoxOp::EmbeddedCurveAdd { input1_x, input1_y, input2_x, input2_y, result } => {
-                debug_println!(
-                    self.enable_debug_trace,
-                    "  EMBEDDED_CURVE_ADD ({} {}) ({} {}) -> {}",
-                    input1_x,
-                    input1_y,
-                    input2_x,
-                    input2_y,
-                    result
-                );
-            }
-            BlackBoxOp::PedersenCommitment { inputs, domain_separator, output } => {
-                debug_println!(
-                    self.enable_debug_trace,
-                    "  PEDERSEN {} {} -> {}",
-                    inputs,
-                    domain_separator,
-                    output
-                );
-            }
-            BlackBoxOp::PedersenHash { inputs, domain_separator, output } => {
-                debug_println!(
-                    self.enable_debug_trace,
-                    "  PEDERSEN_HASH {} {} -> {}",
-                    inputs,
-                    domain_separator,
-                    output
-                );
-            }
-            BlackBoxOp::SchnorrVerify {
-                public_key_x,
-                public_key_y,
-                message,
-                signature,
-                result,
-            } => {
-                debug_println!(
-                    self.enable_debug_trace,
-                    "  SCHNORR_VERIFY {} {} {} {} -> {}",
-                    public_key_x,
-                    public_key_y,
-                    message,
-                    signature,
-                    result
-                );
-            }
-            BlackBoxOp::BigIntAdd { lhs, rhs, output } => {
-                debug_println!(
-                    self.enable_debug_trace,
-                    "  BIGINT_ADD {} {} -> {}",
-                    lhs,
-                    rhs,
-                    output
-                );
-            }
-            BlackBoxOp::BigIntSub { lhs, rhs, output } => {
-                debug_println!(
-                    self.enable_debug_trace,
-                    "  BIGINT_NEG {} {} -> {}",
-                    lhs,
-                    rhs,
-                    output
-                );
-            }
-            BlackBoxOp::BigIntMul { lhs, rhs, output } => {
-                debug_println!(
-                    self.enable_debug_trace,
-                    "  BIGINT_MUL {} {} -> {}",
-                    lhs,
-                    rhs,
-                    output
-                );
-            }
-            BlackBoxOp::BigIntDiv { lhs, rhs, output } => {
-                debug_println!(
-                    self.enable_debug_trace,
-                    "  BIGINT_DIV {} {} -> {}",
-                    lhs,
-                    rhs,
-                    output
-                );
-            }
-            BlackBoxOp::BigIntFromLeBytes { inputs, modulus, output } => {
-                debug_println!(
-                    self.enable_debug_trace,
-                    "  BIGINT_FROM_LE_BYTES {} {} -> {}",
-                    inputs,
-                    modulus,
-                    output
-                );
-            }
-            BlackBoxOp::BigIntToLeBytes { input, output } => {
-                debug_println!(
-                    self.enable_debug_trace,
-                    "  BIGINT_TO_LE_BYTES {} -> {}",
-                    input,
-                    output
-                );
-            }
-            BlackBoxOp::Poseidon2Permutation { message, output, len } => {
-                debug_println!(
-                    self.enable_debug_trace,
-                    "  POSEIDON2_PERMUTATION {} {} -> {}",
-                    message,
-                    len,
-                    output
-                );
-            }
-            BlackBoxOp::Sha256Compression { input, hash_values, output } => {
-                debug_println!(
-                    self.enable_debug_trace,
-                    "  SHA256COMPRESSION {} {} -> {}",
-                    input,
-                    hash_values,
-                    output
-                );
-            }
-        }
+        debug_println!(self.enable_debug_trace, "  {}", op);
     }
 
     /// Debug function for cast_instruction
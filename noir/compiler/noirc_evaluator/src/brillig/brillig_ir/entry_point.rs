@@ -1,8 +1,6 @@
 use super::{
     artifact::{BrilligArtifact, BrilligParameter},
     brillig_variable::{BrilligArray, BrilligVariable},
-    debug_show::DebugShow,
-    registers::BrilligRegistersContext,
     BrilligContext, ReservedRegisters, BRILLIG_MEMORY_ADDRESSING_BIT_SIZE,
 };
 use acvm::{
@@ -19,14 +17,7 @@ impl BrilligContext {
         return_parameters: Vec<BrilligParameter>,
         target_function: T,
     ) -> BrilligArtifact {
-        let mut context = BrilligContext {
-            obj: BrilligArtifact::default(),
-            registers: BrilligRegistersContext::new(),
-            context_label: String::default(),
-            section_label: 0,
-            next_section: 1,
-            debug_show: DebugShow::new(false),
-        };
+        let mut context = BrilligContext::new(false);
 
         context.entry_point_instruction(&arguments, &return_parameters);
 
@@ -36,6 +27,16 @@ impl BrilligContext {
         context.artifact()
     }
 
+    /// Returns an upper bound on the number of iterations of any loop in the entry point for a
+    /// function with the given arguments and return values, as these only copy their elements.
+    pub(crate) fn entry_point_max_loop_iterations(
+        arguments: &[BrilligParameter],
+        return_parameters: &[BrilligParameter],
+    ) -> usize {
+        BrilligContext::flattened_tuple_size(arguments)
+            .max(BrilligContext::flattened_tuple_size(return_parameters))
+    }
+
     /// Adds the instructions needed to handle entry point parameters
     /// The runtime will leave the parameters in calldata.
     /// Arrays will be passed flattened.
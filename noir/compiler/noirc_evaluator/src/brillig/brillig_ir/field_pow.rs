@@ -0,0 +1,120 @@
+//! Powers of native field elements by exponents which are only known at runtime.
+use acvm::{
+    acir::brillig::{BinaryFieldOp, BinaryIntOp, MemoryAddress},
+    FieldElement,
+};
+
+use super::{BrilligBinaryOp, BrilligContext};
+
+impl BrilligContext {
+    /// Writes `base^exponent` to `result`, trapping if `exponent` does not fit in `max_bits` bits
+    /// as the constraints generated for it in ACIR would fail.
+    ///
+    /// The bits of the exponent are consumed from the least significant one, unrolled over
+    /// `max_bits` steps.
+    pub(crate) fn field_pow_bounded_instruction(
+        &mut self,
+        base: MemoryAddress,
+        exponent: MemoryAddress,
+        max_bits: u32,
+        result: MemoryAddress,
+    ) {
+        let field_bits = FieldElement::max_num_bits();
+        let field_op = |op| BrilligBinaryOp::Field { op };
+
+        let power = self.allocate_register();
+        self.mov_instruction(power, base);
+        let remaining = self.allocate_register();
+        self.mov_instruction(remaining, exponent);
+        let zero = self.make_constant(0_usize.into(), field_bits);
+        let one = self.make_constant(1_usize.into(), field_bits);
+        let two = self.make_constant(2_usize.into(), field_bits);
+        let quotient = self.allocate_register();
+        let bit = self.allocate_register();
+        let factor = self.allocate_register();
+
+        self.const_instruction(result, 1_usize.into(), field_bits);
+        for step in 0..max_bits {
+            // bit = remaining - 2 * (remaining / 2)
+            self.binary_instruction(
+                remaining,
+                two,
+                quotient,
+                BrilligBinaryOp::Integer { op: BinaryIntOp::UnsignedDiv, bit_size: field_bits },
+            );
+            self.binary_instruction(remaining, quotient, bit, field_op(BinaryFieldOp::Sub));
+            self.binary_instruction(bit, quotient, bit, field_op(BinaryFieldOp::Sub));
+            self.mov_instruction(remaining, quotient);
+
+            // result *= bit ? power : 1
+            self.binary_instruction(power, one, factor, field_op(BinaryFieldOp::Sub));
+            self.binary_instruction(factor, bit, factor, field_op(BinaryFieldOp::Mul));
+            self.binary_instruction(factor, one, factor, field_op(BinaryFieldOp::Add));
+            self.binary_instruction(result, factor, result, field_op(BinaryFieldOp::Mul));
+
+            if step + 1 < max_bits {
+                self.binary_instruction(power, power, power, field_op(BinaryFieldOp::Mul));
+            }
+        }
+
+        let is_consumed = self.allocate_register();
+        self.binary_instruction(remaining, zero, is_consumed, field_op(BinaryFieldOp::Equals));
+        self.constrain_instruction(
+            is_consumed,
+            Some(format!("exponent does not fit in {max_bits} bits")),
+        );
+
+        for register in [power, remaining, zero, one, two, quotient, bit, factor, is_consumed] {
+            self.deallocate_register(register);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::brillig::{MemoryAddress, Opcode as BrilligOpcode};
+    use acvm::brillig_vm::{VMStatus, VM};
+    use acvm::FieldElement;
+
+    use crate::brillig::brillig_ir::tests::{create_context, DummyBlackBoxSolver};
+
+    /// Runs `base^exponent` with exponents bounded to 8 bits.
+    fn run_pow(base: FieldElement, exponent: u128) -> Option<FieldElement> {
+        let mut context = create_context();
+        let base_register = context.allocate_register();
+        let exponent_register = context.allocate_register();
+        context.push_opcode(BrilligOpcode::CalldataCopy {
+            destination_address: base_register,
+            size: 2,
+            offset: 0,
+        });
+        let result = context.allocate_register();
+        context.field_pow_bounded_instruction(base_register, exponent_register, 8, result);
+        context.mov_instruction(MemoryAddress::from(0), result);
+        context.push_opcode(BrilligOpcode::Stop { return_data_offset: 0, return_data_size: 1 });
+        let bytecode = context.artifact().finish().byte_code;
+
+        let calldata = vec![base.into(), FieldElement::from(exponent).into()];
+        let mut vm = VM::new(calldata, &bytecode, vec![], &DummyBlackBoxSolver);
+        match vm.process_opcodes() {
+            VMStatus::Finished { return_data_offset, .. } => {
+                Some(vm.get_memory()[return_data_offset].to_field())
+            }
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn raises_to_exponents_within_the_bound() {
+        let base = FieldElement::from(3_u128);
+        for exponent in [0_u128, 1, 2, 5, 128, 255] {
+            let expected = base.pow(&FieldElement::from(exponent));
+            assert_eq!(run_pow(base, exponent), Some(expected));
+        }
+    }
+
+    #[test]
+    fn traps_on_exponents_beyond_the_bound() {
+        assert_eq!(run_pow(FieldElement::from(3_u128), 256), None);
+    }
+}
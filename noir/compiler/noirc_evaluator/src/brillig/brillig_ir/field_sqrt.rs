@@ -0,0 +1,230 @@
+//! Square roots in the native field, computed with the Tonelli-Shanks algorithm.
+use acvm::{
+    acir::brillig::{BinaryFieldOp, MemoryAddress},
+    FieldElement,
+};
+use num_bigint::BigUint;
+
+use crate::ssa::ir::instruction::quadratic_non_residue;
+
+use super::{BrilligBinaryOp, BrilligContext};
+
+/// The constants used by the Tonelli-Shanks algorithm, derived from the modulus `p` of the
+/// native field written as `p - 1 = q * 2^s` for an odd `q`.
+struct TonelliShanks {
+    /// `(q - 1) / 2`
+    half_odd_factor: BigUint,
+    /// `s`
+    two_adicity: usize,
+    non_residue: FieldElement,
+}
+
+impl TonelliShanks {
+    fn new() -> Self {
+        let p_minus_one = FieldElement::modulus() - 1_u32;
+        let two_adicity =
+            p_minus_one.trailing_zeros().expect("ICE: the field modulus must be odd") as usize;
+        let odd_factor = &p_minus_one >> two_adicity;
+        TonelliShanks {
+            half_odd_factor: odd_factor >> 1,
+            two_adicity,
+            non_residue: quadratic_non_residue(),
+        }
+    }
+
+    /// Returns `non_residue^exponent`.
+    fn non_residue_pow(&self, exponent: &BigUint) -> FieldElement {
+        self.non_residue.pow(&FieldElement::from_be_bytes_reduce(&exponent.to_bytes_be()))
+    }
+}
+
+impl BrilligContext {
+    /// Writes whether `source` is a square to `is_square`, along with a square root of `source`
+    /// to `root` if so or of `source * quadratic_non_residue()` otherwise.
+    pub(crate) fn field_sqrt_instruction(
+        &mut self,
+        source: MemoryAddress,
+        is_square: MemoryAddress,
+        root: MemoryAddress,
+    ) {
+        let constants = TonelliShanks::new();
+        let field_bits = FieldElement::max_num_bits();
+
+        let value = self.allocate_register();
+        self.mov_instruction(value, source);
+        let zero = self.make_constant(0_usize.into(), field_bits);
+        let one = self.make_constant(1_usize.into(), field_bits);
+
+        // Both the Legendre symbol of `value` and the initial guesses for its root are derived
+        // from u = value^((q - 1) / 2).
+        let u = self.allocate_register();
+        self.field_pow_instruction(u, value, &constants.half_odd_factor);
+
+        // The Legendre symbol value^((p - 1) / 2) = (u^2 * value)^(2^(s - 1)) is one for squares
+        // other than zero.
+        let t = self.allocate_register();
+        self.field_op_instruction(u, u, t, BinaryFieldOp::Mul);
+        self.field_op_instruction(t, value, t, BinaryFieldOp::Mul);
+        for _ in 1..constants.two_adicity {
+            self.field_op_instruction(t, t, t, BinaryFieldOp::Mul);
+        }
+        let is_zero = self.allocate_register();
+        self.field_op_instruction(value, zero, is_zero, BinaryFieldOp::Equals);
+        self.field_op_instruction(t, one, is_square, BinaryFieldOp::Equals);
+        self.field_op_instruction(is_square, is_zero, is_square, BinaryFieldOp::Add);
+
+        // The product of a non-square with a non-residue is a square, so take its root instead.
+        self.branch_instruction(is_square, |ctx, is_square| {
+            if !is_square {
+                let non_residue = ctx.make_constant(constants.non_residue.into(), field_bits);
+                ctx.field_op_instruction(value, non_residue, value, BinaryFieldOp::Mul);
+                ctx.const_instruction(
+                    non_residue,
+                    constants.non_residue_pow(&constants.half_odd_factor).into(),
+                    field_bits,
+                );
+                ctx.field_op_instruction(u, non_residue, u, BinaryFieldOp::Mul);
+                ctx.deallocate_register(non_residue);
+            }
+        });
+
+        // Start from root = value^((q + 1) / 2) and t = value^q, maintaining the invariants
+        // root^2 = value * t, t^(2^(m - 1)) = 1 and c^(2^(m - 1)) = -1.
+        self.field_op_instruction(u, value, root, BinaryFieldOp::Mul);
+        self.field_op_instruction(u, root, t, BinaryFieldOp::Mul);
+        let q = constants.half_odd_factor.clone() * 2_u32 + 1_u32;
+        let c = self.make_constant(constants.non_residue_pow(&q).into(), field_bits);
+        let m = self.make_constant(constants.two_adicity.into(), field_bits);
+
+        let (exit_section, exit_label) = self.reserve_next_section_label();
+        // Zero is its own root, and the loop below would never find an order for it.
+        self.jump_if_instruction(is_zero, exit_label.clone());
+
+        let condition = self.allocate_register();
+        let i = self.allocate_register();
+        let t_pow = self.allocate_register();
+        let b = self.allocate_register();
+        let k = self.allocate_register();
+
+        // Each iteration decreases m, so there are at most s of them.
+        let (loop_section, loop_label) = self.reserve_next_section_label();
+        self.enter_section(loop_section);
+        let loop_start = self.obj.index_of_next_opcode();
+        self.field_op_instruction(t, one, condition, BinaryFieldOp::Equals);
+        self.jump_if_instruction(condition, exit_label);
+
+        // Find the least i such that t^(2^i) = 1, which is less than m.
+        self.const_instruction(i, 1_usize.into(), field_bits);
+        self.field_op_instruction(t, t, t_pow, BinaryFieldOp::Mul);
+        let (find_section, find_label) = self.reserve_next_section_label();
+        let (found_section, found_label) = self.reserve_next_section_label();
+        self.enter_section(find_section);
+        let find_start = self.obj.index_of_next_opcode();
+        self.field_op_instruction(t_pow, one, condition, BinaryFieldOp::Equals);
+        self.jump_if_instruction(condition, found_label);
+        self.field_op_instruction(t_pow, t_pow, t_pow, BinaryFieldOp::Mul);
+        self.field_op_instruction(i, one, i, BinaryFieldOp::Add);
+        self.jump_instruction(find_label);
+        self.obj
+            .add_bounded_loop(find_start..self.obj.index_of_next_opcode(), constants.two_adicity);
+        self.enter_section(found_section);
+
+        // b = c^(2^(m - i - 1))
+        self.mov_instruction(b, c);
+        self.field_op_instruction(m, i, k, BinaryFieldOp::Sub);
+        self.field_op_instruction(k, one, k, BinaryFieldOp::Sub);
+        let (square_section, square_label) = self.reserve_next_section_label();
+        let (squared_section, squared_label) = self.reserve_next_section_label();
+        self.enter_section(square_section);
+        let square_start = self.obj.index_of_next_opcode();
+        self.field_op_instruction(k, zero, condition, BinaryFieldOp::Equals);
+        self.jump_if_instruction(condition, squared_label);
+        self.field_op_instruction(b, b, b, BinaryFieldOp::Mul);
+        self.field_op_instruction(k, one, k, BinaryFieldOp::Sub);
+        self.jump_instruction(square_label);
+        self.obj
+            .add_bounded_loop(square_start..self.obj.index_of_next_opcode(), constants.two_adicity);
+        self.enter_section(squared_section);
+
+        self.mov_instruction(m, i);
+        self.field_op_instruction(b, b, c, BinaryFieldOp::Mul);
+        self.field_op_instruction(t, c, t, BinaryFieldOp::Mul);
+        self.field_op_instruction(root, b, root, BinaryFieldOp::Mul);
+        self.jump_instruction(loop_label);
+        self.obj
+            .add_bounded_loop(loop_start..self.obj.index_of_next_opcode(), constants.two_adicity);
+
+        self.enter_section(exit_section);
+
+        for register in [value, zero, one, u, t, is_zero, c, m, condition, i, t_pow, b, k] {
+            self.deallocate_register(register);
+        }
+    }
+
+    /// Writes `base^exponent` to `result`, which must not be `base`.
+    fn field_pow_instruction(
+        &mut self,
+        result: MemoryAddress,
+        base: MemoryAddress,
+        exponent: &BigUint,
+    ) {
+        // Square and multiply, starting from the most significant bit of the exponent.
+        self.const_instruction(result, 1_usize.into(), FieldElement::max_num_bits());
+        for bit in (0..exponent.bits()).rev() {
+            self.field_op_instruction(result, result, result, BinaryFieldOp::Mul);
+            if exponent.bit(bit) {
+                self.field_op_instruction(result, base, result, BinaryFieldOp::Mul);
+            }
+        }
+    }
+
+    fn field_op_instruction(
+        &mut self,
+        lhs: MemoryAddress,
+        rhs: MemoryAddress,
+        result: MemoryAddress,
+        op: BinaryFieldOp,
+    ) {
+        self.binary_instruction(lhs, rhs, result, BrilligBinaryOp::Field { op });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::brillig_vm::{VMStatus, VM};
+    use acvm::FieldElement;
+
+    use crate::brillig::brillig_gen::brillig_directive::directive_field_sqrt;
+    use crate::brillig::brillig_ir::tests::DummyBlackBoxSolver;
+    use crate::ssa::ir::instruction::quadratic_non_residue;
+
+    #[test]
+    fn computes_roots_and_non_residue_certificates() {
+        let directive = directive_field_sqrt();
+        let step_bound = directive.step_bound.expect("all loops should be bounded");
+
+        for value in [0_i128, 1, 2, 4, 5, 7, 12345, -1, -3] {
+            let value = FieldElement::from(value);
+            let mut vm =
+                VM::new(vec![value.into()], &directive.byte_code, vec![], &DummyBlackBoxSolver);
+            let mut steps = 0;
+            while let VMStatus::InProgress = vm.process_opcode() {
+                steps += 1;
+            }
+            assert!(steps < step_bound);
+            let VMStatus::Finished { return_data_offset, return_data_size } = vm.get_status()
+            else {
+                panic!("VM should have finished")
+            };
+            assert_eq!(return_data_size, 2);
+            let outputs = &vm.get_memory()[return_data_offset..return_data_offset + 2];
+            let (is_square, root) = (outputs[0].to_field(), outputs[1].to_field());
+
+            let is_expected_square = value.sqrt().is_some();
+            assert_eq!(is_square, FieldElement::from(is_expected_square));
+            let expected_square =
+                if is_expected_square { value } else { value * quadratic_non_residue() };
+            assert_eq!(root * root, expected_square);
+        }
+    }
+}
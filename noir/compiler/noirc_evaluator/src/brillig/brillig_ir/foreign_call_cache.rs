@@ -0,0 +1,254 @@
+//! Memoization of foreign calls, so that a call repeated with the same inputs within an execution
+//! of the VM reuses the results of the first one instead of going back to the external resolver.
+//!
+//! The results are cached in a linked list of entries, the first of which is pointed by the
+//! [`ReservedRegisters::foreign_call_cache`] register, which is zero while the list is empty. Each
+//! entry is laid out as
+//!
+//! | next entry | cache id | input cells | output cells | inputs... | outputs... |
+//!
+//! where the cache id tells apart the functions called, and the inputs and outputs are flattened
+//! into consecutive cells. Only calls whose inputs and outputs are single values or arrays of them
+//! can be memoized, as the length of vectors is only known once the call is made.
+use acvm::acir::brillig::{BinaryIntOp, HeapArray, HeapValueType, MemoryAddress, ValueOrArray};
+
+use super::{BrilligContext, ReservedRegisters};
+
+/// The number of cells of an entry before its inputs.
+const HEADER_SIZE: usize = 4;
+
+impl BrilligContext {
+    /// Returns whether calls to a foreign function taking or returning values of these types can
+    /// be memoized.
+    pub(crate) fn is_memoizable_foreign_call(value_types: &[HeapValueType]) -> bool {
+        value_types.iter().all(|value_type| match value_type {
+            HeapValueType::Simple => true,
+            HeapValueType::Array { value_types, .. } => HeapValueType::all_simple(value_types),
+            HeapValueType::Vector { .. } => false,
+        })
+    }
+
+    /// Emits a call to the foreign function `func_name` which first looks for the results of an
+    /// earlier call with the same inputs in the entries cached under `cache_id`, and only calls
+    /// the function if there are none, adding an entry with its results.
+    ///
+    /// The values must be memoizable, as checked by [`Self::is_memoizable_foreign_call`].
+    pub(crate) fn memoized_foreign_call_instruction(
+        &mut self,
+        func_name: String,
+        cache_id: usize,
+        inputs: &[ValueOrArray],
+        input_value_types: &[HeapValueType],
+        outputs: &[ValueOrArray],
+        output_value_types: &[HeapValueType],
+    ) {
+        self.debug_show.memoized_foreign_call_instruction(&func_name, cache_id);
+
+        let input_cells = Self::flattened_cells(inputs);
+        let output_cells = Self::flattened_cells(outputs);
+        let (hit_section, hit_label) = self.reserve_next_section_label();
+        let (miss_section, miss_label) = self.reserve_next_section_label();
+        let (end_section, end_label) = self.reserve_next_section_label();
+
+        // The entry of the call is written past the allocated memory, which it is only added to if
+        // the call is made.
+        let entry = self.allocate_register();
+        self.set_array_pointer(entry);
+        let inputs_pointer = self.allocate_register();
+        self.usize_op(entry, inputs_pointer, BinaryIntOp::Add, HEADER_SIZE);
+        let cursor = self.allocate_register();
+        self.mov_instruction(cursor, inputs_pointer);
+        self.write_cells(cursor, inputs);
+
+        // Look for an entry with the same header and inputs.
+        let candidate = self.allocate_register();
+        self.mov_instruction(candidate, ReservedRegisters::foreign_call_cache());
+        let found = self.allocate_register();
+        let field = self.allocate_register();
+        let address = self.allocate_register();
+        let input_count = self.make_usize_constant(input_cells.into());
+
+        let (loop_section, loop_label) = self.reserve_next_section_label();
+        self.enter_section(loop_section);
+        let loop_start = self.obj.index_of_next_opcode();
+        self.usize_op(candidate, found, BinaryIntOp::Equals, 0);
+        self.jump_if_instruction(found, miss_label);
+
+        self.usize_op(candidate, address, BinaryIntOp::Add, HEADER_SIZE);
+        self.compare_arrays_instruction(address, inputs_pointer, input_count, found);
+        for (offset, value) in [cache_id, input_cells, output_cells].into_iter().enumerate() {
+            self.usize_op(candidate, address, BinaryIntOp::Add, offset + 1);
+            self.load_instruction(field, address);
+            self.usize_op(field, field, BinaryIntOp::Equals, value);
+            self.memory_op(found, field, found, BinaryIntOp::And);
+        }
+        self.jump_if_instruction(found, hit_label);
+
+        self.load_instruction(candidate, candidate);
+        self.jump_instruction(loop_label);
+        self.obj.add_loop(loop_start..self.obj.index_of_next_opcode());
+
+        // On a miss, make the call and cache its results in the entry, after the inputs.
+        self.enter_section(miss_section);
+        self.foreign_call_instruction(
+            func_name,
+            inputs,
+            input_value_types,
+            outputs,
+            output_value_types,
+        );
+        self.write_cells(cursor, outputs);
+
+        self.store_instruction(entry, ReservedRegisters::foreign_call_cache());
+        for (offset, value) in [cache_id, input_cells, output_cells].into_iter().enumerate() {
+            self.usize_op(entry, address, BinaryIntOp::Add, offset + 1);
+            self.usize_const(field, value.into());
+            self.store_instruction(address, field);
+        }
+        self.mov_instruction(ReservedRegisters::foreign_call_cache(), entry);
+        self.usize_const(field, (HEADER_SIZE + input_cells + output_cells).into());
+        self.update_stack_pointer(field);
+        self.jump_instruction(end_label);
+
+        // On a hit, read the results cached after the inputs of the entry found.
+        self.enter_section(hit_section);
+        self.usize_op(candidate, cursor, BinaryIntOp::Add, HEADER_SIZE + input_cells);
+        self.read_cells(cursor, outputs);
+
+        self.enter_section(end_section);
+
+        for register in
+            [entry, inputs_pointer, cursor, candidate, found, field, address, input_count]
+        {
+            self.deallocate_register(register);
+        }
+    }
+
+    /// Returns the number of cells which the values are flattened into.
+    fn flattened_cells(values: &[ValueOrArray]) -> usize {
+        values
+            .iter()
+            .map(|value| match value {
+                ValueOrArray::MemoryAddress(_) => 1,
+                ValueOrArray::HeapArray(HeapArray { size, .. }) => *size,
+                ValueOrArray::HeapVector(_) => unreachable!("ICE: vectors cannot be memoized"),
+            })
+            .sum()
+    }
+
+    /// Writes the values into consecutive cells from the address in `cursor`, which is advanced
+    /// past them.
+    fn write_cells(&mut self, cursor: MemoryAddress, values: &[ValueOrArray]) {
+        for value in values {
+            match value {
+                ValueOrArray::MemoryAddress(register) => {
+                    self.store_instruction(cursor, *register);
+                    self.usize_op_in_place(cursor, BinaryIntOp::Add, 1);
+                }
+                ValueOrArray::HeapArray(HeapArray { pointer, size }) => {
+                    let size_register = self.make_usize_constant((*size).into());
+                    self.copy_array_instruction(*pointer, cursor, size_register);
+                    self.memory_op(cursor, size_register, cursor, BinaryIntOp::Add);
+                    self.deallocate_register(size_register);
+                }
+                ValueOrArray::HeapVector(_) => unreachable!("ICE: vectors cannot be memoized"),
+            }
+        }
+    }
+
+    /// Reads the values from consecutive cells from the address in `cursor`, which is advanced
+    /// past them.
+    fn read_cells(&mut self, cursor: MemoryAddress, values: &[ValueOrArray]) {
+        for value in values {
+            match value {
+                ValueOrArray::MemoryAddress(register) => {
+                    self.load_instruction(*register, cursor);
+                    self.usize_op_in_place(cursor, BinaryIntOp::Add, 1);
+                }
+                ValueOrArray::HeapArray(HeapArray { pointer, size }) => {
+                    let size_register = self.make_usize_constant((*size).into());
+                    self.copy_array_instruction(cursor, *pointer, size_register);
+                    self.memory_op(cursor, size_register, cursor, BinaryIntOp::Add);
+                    self.deallocate_register(size_register);
+                }
+                ValueOrArray::HeapVector(_) => unreachable!("ICE: vectors cannot be memoized"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::brillig::{
+        ForeignCallParam, ForeignCallResult, HeapArray, HeapValueType, MemoryAddress,
+        Opcode as BrilligOpcode, Value, ValueOrArray,
+    };
+    use acvm::brillig_vm::{VMStatus, VM};
+
+    use crate::brillig::brillig_ir::{
+        tests::{create_context, DummyBlackBoxSolver},
+        BrilligContext, ReservedRegisters, BRILLIG_MEMORY_ADDRESSING_BIT_SIZE, MAX_STACK_SIZE,
+    };
+
+    /// Emits a memoized call to an oracle taking `x` and the array `[2, y]`, returning the register
+    /// holding its result.
+    fn call(context: &mut BrilligContext, cache_id: usize, x: usize, y: usize) -> MemoryAddress {
+        let x = context.make_usize_constant(x.into());
+        let array = context.allocate_register();
+        context.allocate_fixed_length_array(array, 2);
+        for (index, value) in [2, y].into_iter().enumerate() {
+            let index = context.make_usize_constant(index.into());
+            let value = context.make_usize_constant(value.into());
+            context.array_set(array, index, value);
+        }
+        let result = context.allocate_register();
+        context.memoized_foreign_call_instruction(
+            "oracle".to_string(),
+            cache_id,
+            &[
+                ValueOrArray::MemoryAddress(x),
+                ValueOrArray::HeapArray(HeapArray { pointer: array, size: 2 }),
+            ],
+            &[
+                HeapValueType::Simple,
+                HeapValueType::Array { value_types: vec![HeapValueType::Simple], size: 2 },
+            ],
+            &[ValueOrArray::MemoryAddress(result)],
+            &[HeapValueType::Simple],
+        );
+        result
+    }
+
+    #[test]
+    fn reuses_the_results_of_calls_with_the_same_inputs() {
+        let mut context = create_context();
+        context.push_opcode(BrilligOpcode::Const {
+            destination: ReservedRegisters::stack_pointer(),
+            value: MAX_STACK_SIZE.into(),
+            bit_size: BRILLIG_MEMORY_ADDRESSING_BIT_SIZE,
+        });
+        let results = [
+            call(&mut context, 0, 1, 3),
+            call(&mut context, 0, 1, 3),
+            call(&mut context, 0, 1, 4),
+            call(&mut context, 0, 1, 3),
+            call(&mut context, 1, 1, 3),
+            call(&mut context, 0, 1, 4),
+        ];
+        context.stop_instruction();
+        let bytecode = context.artifact().finish().byte_code;
+
+        // Only the calls whose inputs were not seen before are resolved.
+        let foreign_call_results = [6_usize, 7, 100]
+            .map(|value| ForeignCallResult {
+                values: vec![ForeignCallParam::Single(Value::from(value))],
+            })
+            .to_vec();
+        let mut vm = VM::new(vec![], &bytecode, foreign_call_results, &DummyBlackBoxSolver);
+        let status = vm.process_opcodes();
+        assert!(matches!(status, VMStatus::Finished { .. }), "VM did not finish: {status:?}");
+        let memory = vm.get_memory();
+        let values = results.map(|result| memory[result.to_usize()].to_usize());
+        assert_eq!(values, [6, 6, 7, 6, 100, 7]);
+    }
+}
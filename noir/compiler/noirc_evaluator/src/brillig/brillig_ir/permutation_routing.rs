@@ -0,0 +1,410 @@
+//! Control bits of the switches of the sorting networks built by `GeneratedAcir::permutation`.
+//!
+//! A network over `n` wires pairs up its inputs with a layer of switches, each of which sends one
+//! of its wires to an upper sub-network over `n / 2` wires and the other to a lower sub-network
+//! over the rest. A layer of switches then pairs up the outputs of the sub-networks. When `n` is
+//! odd, the last input and the last output bypass the switches through the lower sub-network.
+//! When it is even, the last two outputs bypass them from the upper and lower sub-networks.
+//!
+//! Routing a wire through a sub-network forces the other wire of each of its switches through the
+//! other one, so the wires form a path and cycles along which the sub-networks alternate. The path
+//! runs between the wires which bypass the switches, and the cycles can be routed either way.
+//!
+//! The bits of a network are those of its input switches, then those of its output switches, then
+//! those of its upper and lower sub-networks. A bit is set when its switch crosses its wires over.
+use acvm::{
+    acir::brillig::{BinaryFieldOp, BinaryIntOp, MemoryAddress},
+    FieldElement,
+};
+
+use super::{BrilligBinaryOp, BrilligContext};
+
+/// Marks a wire which has not been routed through either sub-network yet.
+const UNROUTED: usize = 2;
+
+/// Returns the number of switches of the network over `len` wires.
+pub(crate) fn switch_count(len: usize) -> usize {
+    if len <= 1 {
+        return 0;
+    }
+    let upper_len = len / 2;
+    upper_len + (len - 1) / 2 + switch_count(upper_len) + switch_count(len - upper_len)
+}
+
+impl BrilligContext {
+    /// Writes to the array pointed to by `bits` the control bits of the network over `len` wires
+    /// which sorts the array pointed to by `values` in increasing order.
+    pub(crate) fn permutation_routing_instruction(
+        &mut self,
+        values: MemoryAddress,
+        len: usize,
+        bits: MemoryAddress,
+    ) {
+        let destinations = self.allocate_register();
+        let sources = self.allocate_register();
+        self.allocate_fixed_length_array(destinations, len);
+        self.allocate_fixed_length_array(sources, len);
+        self.sorted_positions_instruction(values, len, destinations, sources);
+        self.route_network_instruction(len, destinations, sources, bits, 0);
+        self.deallocate_register(destinations);
+        self.deallocate_register(sources);
+    }
+
+    /// Writes to `destinations` the position of each of the `len` values once sorted, equal
+    /// values keeping their order, and to `sources` the position of each sorted value in `values`.
+    fn sorted_positions_instruction(
+        &mut self,
+        values: MemoryAddress,
+        len: usize,
+        destinations: MemoryAddress,
+        sources: MemoryAddress,
+    ) {
+        let len_register = self.make_usize_constant(len.into());
+        let value = self.allocate_register();
+        let other_value = self.allocate_register();
+        let position = self.allocate_register();
+        let condition = self.allocate_register();
+        let is_before = self.allocate_register();
+
+        self.loop_instruction(len_register, |ctx, index| {
+            // A value comes after the smaller values and the equal values before it.
+            ctx.array_get(values, index, value);
+            ctx.usize_const(position, 0_usize.into());
+            ctx.loop_instruction(len_register, |ctx, other_index| {
+                ctx.array_get(values, other_index, other_value);
+                ctx.binary_instruction(
+                    other_value,
+                    value,
+                    condition,
+                    BrilligBinaryOp::Integer {
+                        op: BinaryIntOp::LessThan,
+                        bit_size: FieldElement::max_num_bits(),
+                    },
+                );
+                ctx.memory_op(position, condition, position, BinaryIntOp::Add);
+                ctx.binary_instruction(
+                    other_value,
+                    value,
+                    condition,
+                    BrilligBinaryOp::Field { op: BinaryFieldOp::Equals },
+                );
+                ctx.memory_op(other_index, index, is_before, BinaryIntOp::LessThan);
+                ctx.memory_op(condition, is_before, condition, BinaryIntOp::Mul);
+                ctx.memory_op(position, condition, position, BinaryIntOp::Add);
+            });
+            ctx.array_set(destinations, index, position);
+            ctx.array_set(sources, position, index);
+        });
+
+        for register in [len_register, value, other_value, position, condition, is_before] {
+            self.deallocate_register(register);
+        }
+    }
+
+    /// Writes from position `offset` of `bits` the control bits of the network over `len` wires
+    /// which routes the wire at each position `i` to `destinations[i]`, where `sources` is the
+    /// inverse of `destinations`.
+    ///
+    /// Returns the number of bits of the network.
+    fn route_network_instruction(
+        &mut self,
+        len: usize,
+        destinations: MemoryAddress,
+        sources: MemoryAddress,
+        bits: MemoryAddress,
+        offset: usize,
+    ) -> usize {
+        if len <= 1 {
+            return 0;
+        }
+        let upper_len = len / 2;
+        let lower_len = len - upper_len;
+        let input_switches = upper_len;
+        let output_switches = (len - 1) / 2;
+
+        // The sub-network through which the wire at each input goes: 0 for the upper one and 1
+        // for the lower one.
+        let lanes = self.allocate_register();
+        self.allocate_fixed_length_array(lanes, len);
+        let len_register = self.make_usize_constant(len.into());
+        let unrouted = self.make_usize_constant(UNROUTED.into());
+        self.loop_instruction(len_register, |ctx, wire| ctx.array_set(lanes, wire, unrouted));
+
+        // The path starts from the wire which bypasses the switches through the lower sub-network.
+        let wire = self.allocate_register();
+        let lane = self.allocate_register();
+        self.usize_const(lane, 1_usize.into());
+        // It is the last input if that one bypasses the switches, and the last output otherwise.
+        let starts_through_output = len % 2 == 1;
+        self.usize_const(wire, (len - 1).into());
+        if !starts_through_output {
+            self.array_get(sources, wire, wire);
+        }
+        self.array_set(lanes, wire, lane);
+        self.route_alternating_instruction(
+            len,
+            destinations,
+            sources,
+            lanes,
+            wire,
+            starts_through_output,
+        );
+
+        // Each cycle left can go either way, so it starts through the upper sub-network.
+        let condition = self.allocate_register();
+        self.loop_instruction(len_register, |ctx, start| {
+            ctx.array_get(lanes, start, lane);
+            ctx.memory_op(lane, unrouted, condition, BinaryIntOp::Equals);
+            ctx.branch_instruction(condition, |ctx, is_unrouted| {
+                if is_unrouted {
+                    ctx.usize_const(lane, 0_usize.into());
+                    ctx.array_set(lanes, start, lane);
+                    ctx.mov_instruction(wire, start);
+                    ctx.route_alternating_instruction(
+                        len,
+                        destinations,
+                        sources,
+                        lanes,
+                        wire,
+                        true,
+                    );
+                }
+            });
+        });
+        self.deallocate_register(condition);
+        self.deallocate_register(unrouted);
+
+        // An input switch crosses over if its first wire goes through the lower sub-network, and
+        // an output switch if its first wire comes from it.
+        let switches = self.make_usize_constant(input_switches.into());
+        let position = self.allocate_register();
+        self.loop_instruction(switches, |ctx, switch| {
+            ctx.usize_op(switch, position, BinaryIntOp::Mul, 2);
+            ctx.array_get(lanes, position, lane);
+            ctx.usize_op(switch, position, BinaryIntOp::Add, offset);
+            ctx.array_set(bits, position, lane);
+        });
+        self.usize_const(switches, output_switches.into());
+        self.loop_instruction(switches, |ctx, switch| {
+            ctx.usize_op(switch, position, BinaryIntOp::Mul, 2);
+            ctx.array_get(sources, position, wire);
+            ctx.array_get(lanes, wire, lane);
+            ctx.usize_op(switch, position, BinaryIntOp::Add, offset + input_switches);
+            ctx.array_set(bits, position, lane);
+        });
+        self.deallocate_register(switches);
+
+        // A wire enters and leaves its sub-network at half its positions in this network.
+        let upper_destinations = self.allocate_register();
+        let upper_sources = self.allocate_register();
+        let lower_destinations = self.allocate_register();
+        let lower_sources = self.allocate_register();
+        self.allocate_fixed_length_array(upper_destinations, upper_len);
+        self.allocate_fixed_length_array(upper_sources, upper_len);
+        self.allocate_fixed_length_array(lower_destinations, lower_len);
+        self.allocate_fixed_length_array(lower_sources, lower_len);
+        let destination = self.allocate_register();
+        self.loop_instruction(len_register, |ctx, wire| {
+            ctx.array_get(destinations, wire, destination);
+            ctx.usize_op_in_place(destination, BinaryIntOp::UnsignedDiv, 2);
+            ctx.usize_op(wire, position, BinaryIntOp::UnsignedDiv, 2);
+            ctx.array_get(lanes, wire, lane);
+            ctx.branch_instruction(lane, |ctx, is_lower| {
+                let (sub_destinations, sub_sources) = if is_lower {
+                    (lower_destinations, lower_sources)
+                } else {
+                    (upper_destinations, upper_sources)
+                };
+                ctx.array_set(sub_destinations, position, destination);
+                ctx.array_set(sub_sources, destination, position);
+            });
+        });
+        for register in [lanes, len_register, wire, lane, position, destination] {
+            self.deallocate_register(register);
+        }
+
+        let mut count = input_switches + output_switches;
+        count += self.route_network_instruction(
+            upper_len,
+            upper_destinations,
+            upper_sources,
+            bits,
+            offset + count,
+        );
+        count += self.route_network_instruction(
+            lower_len,
+            lower_destinations,
+            lower_sources,
+            bits,
+            offset + count,
+        );
+        for register in [upper_destinations, upper_sources, lower_destinations, lower_sources] {
+            self.deallocate_register(register);
+        }
+        count
+    }
+
+    /// Routes the wires following `wire` through alternating sub-networks, until the end of its
+    /// path or cycle. `wire` must already be routed, and is overwritten.
+    ///
+    /// The wire following `wire` is the other wire of its output switch if `through_output`, and
+    /// of its input switch otherwise. The switches the wires are followed through alternate.
+    fn route_alternating_instruction(
+        &mut self,
+        len: usize,
+        destinations: MemoryAddress,
+        sources: MemoryAddress,
+        lanes: MemoryAddress,
+        wire: MemoryAddress,
+        through_output: bool,
+    ) {
+        let switched_inputs = self.make_usize_constant((2 * (len / 2)).into());
+        let switched_outputs = self.make_usize_constant((2 * ((len - 1) / 2)).into());
+        let one = self.make_usize_constant(1_usize.into());
+        let unrouted = self.make_usize_constant(UNROUTED.into());
+        let through_output = self.make_usize_constant(usize::from(through_output).into());
+        let next = self.allocate_register();
+        let lane = self.allocate_register();
+        let condition = self.allocate_register();
+
+        let (loop_section, loop_label) = self.reserve_next_section_label();
+        let (exit_section, exit_label) = self.reserve_next_section_label();
+        self.enter_section(loop_section);
+        let loop_start = self.obj.index_of_next_opcode();
+
+        // The path ends at the wires which bypass the switches.
+        self.branch_instruction(through_output, |ctx, through_output| {
+            if through_output {
+                ctx.array_get(destinations, wire, next);
+                ctx.memory_op(switched_outputs, next, condition, BinaryIntOp::LessThanEquals);
+                ctx.jump_if_instruction(condition, exit_label.clone());
+                ctx.memory_op(next, one, next, BinaryIntOp::Xor);
+                ctx.array_get(sources, next, next);
+            } else {
+                ctx.memory_op(switched_inputs, wire, condition, BinaryIntOp::LessThanEquals);
+                ctx.jump_if_instruction(condition, exit_label.clone());
+                ctx.memory_op(wire, one, next, BinaryIntOp::Xor);
+            }
+        });
+
+        // The cycle ends when it gets back to a routed wire.
+        self.array_get(lanes, next, lane);
+        self.memory_op(lane, unrouted, condition, BinaryIntOp::LessThan);
+        self.jump_if_instruction(condition, exit_label);
+
+        self.array_get(lanes, wire, lane);
+        self.memory_op(one, lane, lane, BinaryIntOp::Sub);
+        self.array_set(lanes, next, lane);
+        self.mov_instruction(wire, next);
+        self.memory_op(one, through_output, through_output, BinaryIntOp::Sub);
+        self.jump_instruction(loop_label);
+        self.obj.add_loop(loop_start..self.obj.index_of_next_opcode());
+
+        self.enter_section(exit_section);
+
+        for register in [
+            switched_inputs,
+            switched_outputs,
+            one,
+            unrouted,
+            through_output,
+            next,
+            lane,
+            condition,
+        ] {
+            self.deallocate_register(register);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::brillig_vm::{VMStatus, VM};
+    use acvm::FieldElement;
+
+    use crate::brillig::brillig_gen::brillig_directive::directive_permutation_routing;
+    use crate::brillig::brillig_ir::tests::DummyBlackBoxSolver;
+
+    use super::switch_count;
+
+    /// Routes `values` through the network over them as `GeneratedAcir::permutation_layer` does.
+    fn apply_network(values: &[u128], bits: &[bool]) -> Vec<u128> {
+        let len = values.len();
+        if len <= 1 {
+            return values.to_vec();
+        }
+        let upper_len = len / 2;
+        let output_switches = (len - 1) / 2;
+        let (mut upper, mut lower) = (Vec::new(), Vec::new());
+        for (switch, pair) in values.chunks_exact(2).enumerate() {
+            let (first, second) =
+                if bits[switch] { (pair[1], pair[0]) } else { (pair[0], pair[1]) };
+            upper.push(first);
+            lower.push(second);
+        }
+        if len % 2 == 1 {
+            lower.push(values[len - 1]);
+        }
+        let sub_bits = &bits[upper_len + output_switches..];
+        let upper_bits = switch_count(upper_len);
+        let upper = apply_network(&upper, &sub_bits[..upper_bits]);
+        let lower = apply_network(&lower, &sub_bits[upper_bits..]);
+
+        let mut outputs = Vec::new();
+        for switch in 0..output_switches {
+            let (first, second) = (upper[switch], lower[switch]);
+            let crosses_over = bits[upper_len + switch];
+            outputs.extend(if crosses_over { [second, first] } else { [first, second] });
+        }
+        if len % 2 == 0 {
+            outputs.push(upper[upper_len - 1]);
+        }
+        outputs.push(lower[lower.len() - 1]);
+        outputs
+    }
+
+    #[test]
+    fn routes_values_to_their_sorted_positions() {
+        for values in [
+            vec![1],
+            vec![2, 1],
+            vec![3, 1, 2],
+            vec![4, 4, 1, 3],
+            vec![5, 3, 8, 1, 9, 2, 7],
+            vec![10, 9, 8, 7, 6, 5, 4, 3, 2, 1],
+            vec![6, 1, 6, 2, 6, 3, 0, 0, 5, 7, 1],
+        ] {
+            let directive = directive_permutation_routing(values.len());
+            let step_bound = directive.step_bound.expect("all loops should be bounded");
+            let calldata = values.iter().map(|value| FieldElement::from(*value).into()).collect();
+            let mut vm = VM::new(calldata, &directive.byte_code, vec![], &DummyBlackBoxSolver);
+            let mut steps = 0;
+            while let VMStatus::InProgress = vm.process_opcode() {
+                steps += 1;
+            }
+            assert!(steps < step_bound);
+            let VMStatus::Finished { return_data_offset, return_data_size } = vm.get_status()
+            else {
+                panic!("VM should have finished")
+            };
+            assert_eq!(return_data_size, switch_count(values.len()));
+            let bits: Vec<bool> = vm.get_memory()
+                [return_data_offset..return_data_offset + return_data_size]
+                .iter()
+                .map(|bit| !bit.to_field().is_zero())
+                .collect();
+
+            let mut sorted = values.clone();
+            sorted.sort_unstable();
+            assert_eq!(apply_network(&values, &bits), sorted);
+        }
+    }
+
+    #[test]
+    fn counts_switches_of_each_level() {
+        for len in 1..50_usize {
+            let expected: u32 = (1..=len).map(|i| (i as f32).log2().ceil() as u32).sum();
+            assert_eq!(switch_count(len), expected as usize);
+        }
+    }
+}
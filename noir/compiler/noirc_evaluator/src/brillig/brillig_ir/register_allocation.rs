@@ -0,0 +1,632 @@
+//! This module reassigns the registers used by the bytecode of a function once it is complete.
+//!
+//! Brillig generation gives every value and temporary a register of its own, rarely frees them
+//! and moves values around liberally, e.g. through temporaries when passing arguments to a call.
+//! This pass computes which registers are live at each opcode, builds the graph of registers
+//! which are live at the same time and colors it greedily. Before coloring, the source and
+//! destination of each `mov` are merged whenever they don't interfere, so that the move turns into
+//! a move from a register to itself and can be removed.
+//!
+//! The reserved registers and the first user registers, through which arguments and return values
+//! are passed, keep their place. Other registers may still be assigned to them when they are free.
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    ops::Range,
+};
+
+use acvm::acir::brillig::{
+    BlackBoxOp, HeapArray, HeapVector, MemoryAddress, Opcode as BrilligOpcode, ValueOrArray,
+};
+use iter_extended::vecmap;
+
+use super::{
+    artifact::{BrilligArtifact, OpcodeLocation},
+    brillig_variable::BrilligVariable,
+    ReservedRegisters, MAX_STACK_SIZE,
+};
+
+/// How an opcode accesses a register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Access {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl Access {
+    pub(super) fn reads(self) -> bool {
+        matches!(self, Access::Read | Access::ReadWrite)
+    }
+
+    pub(super) fn writes(self) -> bool {
+        matches!(self, Access::Write | Access::ReadWrite)
+    }
+}
+
+/// Reassigns the registers used by `artifact`, the bytecode of a single function, and removes the
+/// moves whose source and destination end up in the same register.
+///
+/// The first `num_fixed_registers` user registers hold the arguments and return values of the
+/// function and of the functions it calls, so they are left in place.
+pub(crate) fn allocate_registers(artifact: &mut BrilligArtifact, num_fixed_registers: usize) {
+    // Bytecode which copies calldata or returns data from memory is not the bytecode of a function.
+    let is_function = artifact.byte_code.iter().all(|opcode| match opcode {
+        BrilligOpcode::CalldataCopy { .. } => false,
+        BrilligOpcode::Stop { return_data_size, .. } => *return_data_size == 0,
+        _ => true,
+    });
+    if !is_function {
+        return;
+    }
+    let Some(jump_targets) = artifact.jump_targets() else {
+        return;
+    };
+
+    let fixed_registers = (0..num_fixed_registers).map(ReservedRegisters::user_register_index);
+    let mut allocation = RegisterAllocation::new(fixed_registers.collect());
+    let accesses = vecmap(&mut artifact.byte_code, |opcode| allocation.accesses(opcode));
+    let blocks = Blocks::new(&artifact.byte_code, &jump_targets);
+    let live_out = blocks.live_out(&accesses);
+    allocation.build_interference_graph(&artifact.byte_code, &accesses, &blocks, live_out);
+    allocation.coalesce_moves();
+    let colors = allocation.color();
+
+    let recolor = |register: &mut MemoryAddress| {
+        if let Some(color) = colors.get(register) {
+            *register = *color;
+        }
+    };
+    for opcode in artifact.byte_code.iter_mut() {
+        for_each_register(opcode, &mut |register, _| recolor(register));
+    }
+    for debug_variable in artifact.variables.iter_mut() {
+        match &mut debug_variable.variable {
+            BrilligVariable::Simple(register) => recolor(register),
+            BrilligVariable::BrilligArray(array) => {
+                recolor(&mut array.pointer);
+                recolor(&mut array.rc);
+            }
+            BrilligVariable::BrilligVector(vector) => {
+                recolor(&mut vector.pointer);
+                recolor(&mut vector.size);
+                recolor(&mut vector.rc);
+                recolor(&mut vector.capacity);
+            }
+        }
+    }
+    artifact.retain_opcodes(|opcode| {
+        !matches!(opcode, BrilligOpcode::Mov { destination, source } if destination == source)
+    });
+}
+
+/// Returns whether `register` is a register which can be allocated, rather than a reserved
+/// register or a memory address past the registers.
+fn is_allocatable(register: MemoryAddress) -> bool {
+    (ReservedRegisters::len()..MAX_STACK_SIZE).contains(&register.to_usize())
+}
+
+struct RegisterAllocation {
+    fixed_registers: BTreeSet<MemoryAddress>,
+    /// The registers which are live at the same time as each register, or rather as each set of
+    /// registers merged together, keyed by the representative of the set.
+    interference: BTreeMap<MemoryAddress, BTreeSet<MemoryAddress>>,
+    /// The destination and source of each move between two registers.
+    moves: Vec<(MemoryAddress, MemoryAddress)>,
+    /// The register each merged register was merged into.
+    merged_into: BTreeMap<MemoryAddress, MemoryAddress>,
+}
+
+impl RegisterAllocation {
+    fn new(fixed_registers: BTreeSet<MemoryAddress>) -> Self {
+        Self {
+            fixed_registers,
+            interference: BTreeMap::new(),
+            moves: Vec::new(),
+            merged_into: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the allocatable registers accessed by `opcode`.
+    ///
+    /// Calls pass their arguments and results through the fixed registers and function returns
+    /// pass results through them, so they are considered to access all of them.
+    fn accesses(&self, opcode: &mut BrilligOpcode) -> Vec<(MemoryAddress, Access)> {
+        let fixed_registers =
+            |access| vecmap(&self.fixed_registers, |register| (*register, access));
+        match opcode {
+            BrilligOpcode::Call { .. } => fixed_registers(Access::ReadWrite),
+            BrilligOpcode::Stop { .. } | BrilligOpcode::Return => fixed_registers(Access::Read),
+            _ => {
+                let mut accesses = Vec::new();
+                for_each_register(opcode, &mut |register, access| {
+                    if is_allocatable(*register) {
+                        accesses.push((*register, access));
+                    }
+                });
+                accesses
+            }
+        }
+    }
+
+    fn build_interference_graph(
+        &mut self,
+        byte_code: &[BrilligOpcode],
+        accesses: &[Vec<(MemoryAddress, Access)>],
+        blocks: &Blocks,
+        live_out: Vec<BTreeSet<MemoryAddress>>,
+    ) {
+        for (block, mut live) in blocks.ranges.iter().zip(live_out) {
+            for position in block.clone().rev() {
+                for (register, _) in &accesses[position] {
+                    self.interference.entry(*register).or_default();
+                }
+                let opcode = &byte_code[position];
+                let reads = accesses[position].iter().filter(|(_, access)| access.reads());
+                let writes = accesses[position].iter().filter(|(_, access)| access.writes());
+                // A move doesn't make its destination interfere with its source, as both hold the
+                // same value afterwards.
+                let moved = match opcode {
+                    BrilligOpcode::Mov { destination, source }
+                        if is_allocatable(*destination) && is_allocatable(*source) =>
+                    {
+                        if destination != source {
+                            self.moves.push((*destination, *source));
+                        }
+                        Some(*source)
+                    }
+                    _ => None,
+                };
+                // Foreign calls and black box functions may write some of their results before
+                // they are done reading their inputs.
+                let reads_after_writes = matches!(
+                    opcode,
+                    BrilligOpcode::ForeignCall { .. } | BrilligOpcode::BlackBox(_)
+                );
+
+                for (written, _) in writes.clone() {
+                    for other in live.iter().chain(writes.clone().map(|(register, _)| register)) {
+                        if Some(*other) != moved {
+                            self.add_interference(*written, *other);
+                        }
+                    }
+                    if reads_after_writes {
+                        for (read, _) in reads.clone() {
+                            self.add_interference(*written, *read);
+                        }
+                    }
+                }
+                for (written, _) in writes {
+                    live.remove(written);
+                }
+                live.extend(reads.map(|(register, _)| *register));
+            }
+        }
+    }
+
+    fn add_interference(&mut self, register: MemoryAddress, other: MemoryAddress) {
+        let is_fixed = |register| self.fixed_registers.contains(&register);
+        if register == other || (is_fixed(register) && is_fixed(other)) {
+            return;
+        }
+        self.interference.entry(register).or_default().insert(other);
+        self.interference.entry(other).or_default().insert(register);
+    }
+
+    /// Returns the representative of the set of registers which `register` was merged into.
+    fn representative(&self, mut register: MemoryAddress) -> MemoryAddress {
+        while let Some(merged_into) = self.merged_into.get(&register) {
+            register = *merged_into;
+        }
+        register
+    }
+
+    /// Merges the source and destination of each move unless they interfere or are both fixed.
+    fn coalesce_moves(&mut self) {
+        for (destination, source) in std::mem::take(&mut self.moves) {
+            let destination = self.representative(destination);
+            let source = self.representative(source);
+            let interferes =
+                self.interference.get(&destination).map_or(false, |regs| regs.contains(&source));
+            let destination_is_fixed = self.fixed_registers.contains(&destination);
+            let source_is_fixed = self.fixed_registers.contains(&source);
+            if destination == source || interferes || (destination_is_fixed && source_is_fixed) {
+                continue;
+            }
+
+            // A fixed register stays the representative so that the merged registers keep its place.
+            let (kept, merged) =
+                if source_is_fixed { (source, destination) } else { (destination, source) };
+            self.merged_into.insert(merged, kept);
+            for other in self.interference.remove(&merged).unwrap_or_default() {
+                let other_interference = self.interference.entry(other).or_default();
+                other_interference.remove(&merged);
+                other_interference.insert(kept);
+                self.interference.entry(kept).or_default().insert(other);
+            }
+        }
+    }
+
+    /// Assigns each register the lowest register which doesn't interfere with it, and returns the
+    /// assignment of the registers which don't keep their place.
+    fn color(&self) -> BTreeMap<MemoryAddress, MemoryAddress> {
+        let mut colors: BTreeMap<MemoryAddress, MemoryAddress> = BTreeMap::new();
+        let registers: Vec<_> = self.interference.keys().chain(self.merged_into.keys()).collect();
+        for register in registers {
+            let representative = self.representative(*register);
+            if self.fixed_registers.contains(&representative) {
+                colors.insert(*register, representative);
+                continue;
+            }
+            if let Some(color) = colors.get(&representative) {
+                colors.insert(*register, *color);
+                continue;
+            }
+
+            let neighbors = self.interference.get(&representative);
+            let taken: BTreeSet<_> = neighbors
+                .into_iter()
+                .flatten()
+                .filter_map(|neighbor| {
+                    if self.fixed_registers.contains(neighbor) {
+                        Some(*neighbor)
+                    } else {
+                        colors.get(neighbor).copied()
+                    }
+                })
+                .collect();
+            let color = (ReservedRegisters::len()..)
+                .map(MemoryAddress::from)
+                .find(|color| !taken.contains(color))
+                .expect("there should be a free register");
+            assert!(color.to_usize() < MAX_STACK_SIZE, "Stack too deep");
+            colors.insert(representative, color);
+            colors.insert(*register, color);
+        }
+        colors
+    }
+}
+
+/// The basic blocks of the bytecode of a function.
+struct Blocks {
+    ranges: Vec<Range<OpcodeLocation>>,
+    successors: Vec<Vec<usize>>,
+}
+
+impl Blocks {
+    fn new(
+        byte_code: &[BrilligOpcode],
+        jump_targets: &HashMap<OpcodeLocation, OpcodeLocation>,
+    ) -> Self {
+        let mut starts: BTreeSet<OpcodeLocation> = jump_targets.values().copied().collect();
+        starts.insert(0);
+        for (position, opcode) in byte_code.iter().enumerate() {
+            if ends_block(opcode) {
+                starts.insert(position + 1);
+            }
+        }
+        let starts: Vec<_> = starts.into_iter().filter(|start| *start < byte_code.len()).collect();
+        let ranges: Vec<_> = starts
+            .iter()
+            .enumerate()
+            .map(|(index, start)| *start..starts.get(index + 1).copied().unwrap_or(byte_code.len()))
+            .collect();
+
+        let block_starting_at = |position: OpcodeLocation| starts.binary_search(&position).ok();
+        let successors = vecmap(&ranges, |block| {
+            let last = block.end - 1;
+            let falls_through = !matches!(
+                &byte_code[last],
+                BrilligOpcode::Jump { .. }
+                    | BrilligOpcode::Stop { .. }
+                    | BrilligOpcode::Return
+                    | BrilligOpcode::Trap
+            );
+            let jump_target = jump_targets.get(&last).and_then(|target| block_starting_at(*target));
+            let next = falls_through.then(|| block_starting_at(block.end)).flatten();
+            jump_target.into_iter().chain(next).collect()
+        });
+        Self { ranges, successors }
+    }
+
+    /// Returns the registers which are live at the end of each block.
+    fn live_out(&self, accesses: &[Vec<(MemoryAddress, Access)>]) -> Vec<BTreeSet<MemoryAddress>> {
+        // The registers read by each block before it writes them, and the registers it writes.
+        let (reads, writes): (Vec<_>, Vec<_>) = self
+            .ranges
+            .iter()
+            .map(|block| {
+                let mut reads = BTreeSet::new();
+                let mut writes = BTreeSet::new();
+                for position in block.clone().rev() {
+                    for (register, access) in &accesses[position] {
+                        if access.writes() {
+                            reads.remove(register);
+                            writes.insert(*register);
+                        }
+                    }
+                    for (register, access) in &accesses[position] {
+                        if access.reads() {
+                            reads.insert(*register);
+                        }
+                    }
+                }
+                (reads, writes)
+            })
+            .unzip();
+
+        let mut live_in: Vec<BTreeSet<MemoryAddress>> = reads.clone();
+        let mut live_out = vec![BTreeSet::new(); self.ranges.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for block in (0..self.ranges.len()).rev() {
+                let out: BTreeSet<_> = self.successors[block]
+                    .iter()
+                    .flat_map(|successor| live_in[*successor].iter().copied())
+                    .collect();
+                let mut block_live_in = reads[block].clone();
+                block_live_in.extend(out.difference(&writes[block]).copied());
+                if block_live_in != live_in[block] {
+                    live_in[block] = block_live_in;
+                    changed = true;
+                }
+                live_out[block] = out;
+            }
+        }
+        live_out
+    }
+}
+
+/// Returns whether execution doesn't always continue with the next opcode after `opcode`.
+fn ends_block(opcode: &BrilligOpcode) -> bool {
+    matches!(
+        opcode,
+        BrilligOpcode::Jump { .. }
+            | BrilligOpcode::JumpIf { .. }
+            | BrilligOpcode::JumpIfNot { .. }
+            | BrilligOpcode::Stop { .. }
+            | BrilligOpcode::Return
+            | BrilligOpcode::Trap
+    )
+}
+
+/// Calls `f` on each address accessed directly by `opcode`, other than the ranges of memory which
+/// `CalldataCopy` writes and `Stop` returns, and the registers through which calls pass values.
+pub(super) fn for_each_register(
+    opcode: &mut BrilligOpcode,
+    f: &mut impl FnMut(&mut MemoryAddress, Access),
+) {
+    match opcode {
+        BrilligOpcode::BinaryFieldOp { destination, lhs, rhs, .. }
+        | BrilligOpcode::BinaryIntOp { destination, lhs, rhs, .. } => {
+            f(lhs, Access::Read);
+            f(rhs, Access::Read);
+            f(destination, Access::Write);
+        }
+        BrilligOpcode::Cast { destination, source, .. }
+        | BrilligOpcode::Mov { destination, source }
+        | BrilligOpcode::Load { destination, source_pointer: source } => {
+            f(source, Access::Read);
+            f(destination, Access::Write);
+        }
+        BrilligOpcode::Store { destination_pointer, source } => {
+            f(destination_pointer, Access::Read);
+            f(source, Access::Read);
+        }
+        BrilligOpcode::JumpIfNot { condition, .. } | BrilligOpcode::JumpIf { condition, .. } => {
+            f(condition, Access::Read);
+        }
+        BrilligOpcode::Const { destination, .. } => f(destination, Access::Write),
+        BrilligOpcode::ForeignCall { destinations, inputs, .. } => {
+            for input in inputs {
+                match input {
+                    ValueOrArray::MemoryAddress(register) => f(register, Access::Read),
+                    ValueOrArray::HeapArray(array) => read_array(array, f),
+                    ValueOrArray::HeapVector(vector) => read_vector(vector, f),
+                }
+            }
+            for destination in destinations {
+                match destination {
+                    ValueOrArray::MemoryAddress(register) => f(register, Access::Write),
+                    ValueOrArray::HeapArray(array) => read_array(array, f),
+                    ValueOrArray::HeapVector(HeapVector { pointer, size }) => {
+                        f(pointer, Access::Read);
+                        f(size, Access::Write);
+                    }
+                }
+            }
+        }
+        BrilligOpcode::BlackBox(op) => for_each_black_box_register(op, f),
+        BrilligOpcode::Jump { .. }
+        | BrilligOpcode::CalldataCopy { .. }
+        | BrilligOpcode::Call { .. }
+        | BrilligOpcode::Return
+        | BrilligOpcode::Trap
+        | BrilligOpcode::Stop { .. } => {}
+    }
+}
+
+/// Calls `f` on each register accessed by a black box function. The arrays which the function
+/// outputs are written to the memory which their pointer registers point to.
+fn for_each_black_box_register(
+    op: &mut BlackBoxOp,
+    f: &mut impl FnMut(&mut MemoryAddress, Access),
+) {
+    match op {
+        BlackBoxOp::Sha256 { message, output }
+        | BlackBoxOp::Blake2s { message, output }
+        | BlackBoxOp::Blake3 { message, output }
+        | BlackBoxOp::Keccak256 { message, output }
+        | BlackBoxOp::Keccakf1600 { message, output } => {
+            read_vector(message, f);
+            read_array(output, f);
+        }
+        BlackBoxOp::EcdsaSecp256k1 {
+            hashed_msg,
+            public_key_x,
+            public_key_y,
+            signature,
+            result,
+        }
+        | BlackBoxOp::EcdsaSecp256r1 {
+            hashed_msg,
+            public_key_x,
+            public_key_y,
+            signature,
+            result,
+        } => {
+            read_vector(hashed_msg, f);
+            read_array(public_key_x, f);
+            read_array(public_key_y, f);
+            read_array(signature, f);
+            f(result, Access::Write);
+        }
+        BlackBoxOp::SchnorrVerify { public_key_x, public_key_y, message, signature, result } => {
+            f(public_key_x, Access::Read);
+            f(public_key_y, Access::Read);
+            read_vector(message, f);
+            read_vector(signature, f);
+            f(result, Access::Write);
+        }
+        BlackBoxOp::PedersenCommitment { inputs, domain_separator, output } => {
+            read_vector(inputs, f);
+            f(domain_separator, Access::Read);
+            read_array(output, f);
+        }
+        BlackBoxOp::PedersenHash { inputs, domain_separator, output } => {
+            read_vector(inputs, f);
+            f(domain_separator, Access::Read);
+            f(output, Access::Write);
+        }
+        BlackBoxOp::FixedBaseScalarMul { low, high, result } => {
+            f(low, Access::Read);
+            f(high, Access::Read);
+            read_array(result, f);
+        }
+        BlackBoxOp::EmbeddedCurveAdd { input1_x, input1_y, input2_x, input2_y, result } => {
+            f(input1_x, Access::Read);
+            f(input1_y, Access::Read);
+            f(input2_x, Access::Read);
+            f(input2_y, Access::Read);
+            read_array(result, f);
+        }
+        BlackBoxOp::BigIntAdd { lhs, rhs, output }
+        | BlackBoxOp::BigIntSub { lhs, rhs, output }
+        | BlackBoxOp::BigIntMul { lhs, rhs, output }
+        | BlackBoxOp::BigIntDiv { lhs, rhs, output } => {
+            f(lhs, Access::Read);
+            f(rhs, Access::Read);
+            f(output, Access::Write);
+        }
+        BlackBoxOp::BigIntFromLeBytes { inputs, modulus, output } => {
+            read_vector(inputs, f);
+            read_vector(modulus, f);
+            f(output, Access::Write);
+        }
+        BlackBoxOp::BigIntToLeBytes { input, output } => {
+            f(input, Access::Read);
+            f(&mut output.pointer, Access::Read);
+            f(&mut output.size, Access::ReadWrite);
+        }
+        BlackBoxOp::Poseidon2Permutation { message, output, len } => {
+            read_vector(message, f);
+            read_array(output, f);
+            f(len, Access::Read);
+        }
+        BlackBoxOp::Sha256Compression { input, hash_values, output } => {
+            read_vector(input, f);
+            read_vector(hash_values, f);
+            read_array(output, f);
+        }
+    }
+}
+
+fn read_array(array: &mut HeapArray, f: &mut impl FnMut(&mut MemoryAddress, Access)) {
+    f(&mut array.pointer, Access::Read);
+}
+
+fn read_vector(vector: &mut HeapVector, f: &mut impl FnMut(&mut MemoryAddress, Access)) {
+    f(&mut vector.pointer, Access::Read);
+    f(&mut vector.size, Access::Read);
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::brillig::{BinaryFieldOp, BinaryIntOp, Value};
+    use iter_extended::vecmap;
+
+    use crate::brillig::brillig_ir::{
+        artifact::BrilligParameter,
+        tests::{create_and_run_vm, create_context, create_entry_point_bytecode},
+        BrilligBinaryOp, BrilligContext,
+    };
+
+    /// Runs the function emitted by `build` with and without reallocating its registers, checks
+    /// that both return `expected` and returns the number of opcodes removed by the reallocation.
+    fn removed_opcodes(
+        build: impl Fn() -> BrilligContext,
+        calldata: Vec<Value>,
+        expected: Value,
+    ) -> usize {
+        let arguments = vec![BrilligParameter::Simple(32); calldata.len()];
+        let returns = vec![BrilligParameter::Simple(32)];
+        let lengths = vecmap([false, true], |reallocate| {
+            let mut context = build();
+            if reallocate {
+                context.reallocate_registers();
+            }
+            let bytecode =
+                create_entry_point_bytecode(context, arguments.clone(), returns.clone()).byte_code;
+            let (vm, return_data_offset, _) = create_and_run_vm(calldata.clone(), &bytecode);
+            assert_eq!(vm.get_memory()[return_data_offset], expected);
+            bytecode.len()
+        });
+        lengths[0] - lengths[1]
+    }
+
+    #[test]
+    fn moves_between_registers_which_do_not_interfere_are_removed() {
+        let build = || {
+            let mut context = create_context();
+            let a = context.allocate_register();
+            let b = context.allocate_register();
+            context.use_calling_convention_registers(2);
+
+            let copy = context.allocate_register();
+            context.mov_instruction(copy, a);
+            let sum = context.allocate_register();
+            context.binary_instruction(
+                copy,
+                b,
+                sum,
+                BrilligBinaryOp::Field { op: BinaryFieldOp::Add },
+            );
+            context.return_instruction(&[sum]);
+            context
+        };
+        let calldata = vec![Value::from(2_usize), Value::from(3_usize)];
+        // The copy of `a` and both moves through which `sum` is returned.
+        assert_eq!(removed_opcodes(build, calldata, Value::from(5_usize)), 3);
+    }
+
+    #[test]
+    fn registers_live_across_loop_iterations_are_kept_apart() {
+        let build = || {
+            let mut context = create_context();
+            let count = context.allocate_register();
+            context.use_calling_convention_registers(1);
+
+            let sum = context.make_usize_constant(0_usize.into());
+            context.loop_instruction(count, |context, iterator| {
+                let copy = context.allocate_register();
+                context.mov_instruction(copy, iterator);
+                context.memory_op(sum, copy, sum, BinaryIntOp::Add);
+                context.deallocate_register(copy);
+            });
+            context.return_instruction(&[sum]);
+            context
+        };
+        // Merging `sum` with the iterator or its copy would change the result.
+        removed_opcodes(build, vec![Value::from(4_usize)], Value::from(6_usize));
+    }
+}
@@ -2,14 +2,46 @@ pub(crate) mod brillig_gen;
 pub(crate) mod brillig_ir;
 
 use self::{
-    brillig_gen::{brillig_fn::FunctionContext, convert_ssa_function},
-    brillig_ir::artifact::{BrilligArtifact, Label},
+    brillig_gen::{
+        brillig_bigint::BigIntProcedure, brillig_fn::FunctionContext, convert_ssa_function,
+        step_bound::compute_step_bounds,
+    },
+    brillig_ir::{
+        artifact::{BrilligArtifact, Label},
+        debug_show::opcode_mnemonic,
+        BrilligLimits,
+    },
 };
+use crate::errors::RuntimeError;
 use crate::ssa::{
     ir::function::{Function, FunctionId, RuntimeType},
     ssa_gen::Ssa,
+    BrilligFunctionSize,
 };
-use std::collections::{BTreeSet, HashMap};
+use iter_extended::vecmap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// The oracle called with the id of a counter by Brillig code compiled with profiling enabled,
+/// each time the block of code which the counter belongs to is executed.
+pub const BRILLIG_PROFILE_ORACLE: &str = "profile_block";
+
+/// The options of the compilation of functions into brillig.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct BrilligOptions {
+    /// Whether the brillig opcodes are printed as they are generated.
+    pub(crate) enable_debug_trace: bool,
+    /// The limits which each function checks on entry.
+    pub(crate) limits: BrilligLimits,
+    /// Whether each block counts its executions through the [`BRILLIG_PROFILE_ORACLE`], under a
+    /// counter whose id is unique within the program.
+    pub(crate) enable_profiling: bool,
+    /// The foreign functions whose results are cached for the rest of the execution of the VM,
+    /// and reused by the calls with the same inputs.
+    pub(crate) memoized_foreign_calls: Vec<String>,
+    /// Whether unsigned arithmetic traps instead of wrapping around where constrained code checks
+    /// it for overflows.
+    pub(crate) check_overflows: bool,
+}
 
 /// Context structure for the brillig pass.
 /// It stores brillig-related data required for brillig generation.
@@ -17,17 +49,39 @@ use std::collections::{BTreeSet, HashMap};
 pub struct Brillig {
     /// Maps SSA function labels to their brillig artifact
     ssa_function_to_brillig: HashMap<FunctionId, BrilligArtifact>,
+    /// The procedures of the big integer library called by the compiled functions
+    bigint_procedures: HashMap<BigIntProcedure, BrilligArtifact>,
+    /// Upper bounds on the number of opcodes executed by a call to each function, where known.
+    step_bounds: HashMap<FunctionId, usize>,
+    /// The id of the next profiling counter, if the executions of each block are counted.
+    next_profile_counter: Option<usize>,
+    /// The options the functions are compiled with.
+    options: BrilligOptions,
 }
 
 impl Brillig {
     /// Compiles a function into brillig and store the compilation artifacts
-    pub(crate) fn compile(&mut self, func: &Function, enable_debug_trace: bool) {
-        let obj = convert_ssa_function(func, enable_debug_trace);
+    pub(crate) fn compile(&mut self, func: &Function) -> Result<(), RuntimeError> {
+        let obj = convert_ssa_function(func, &self.options, self.next_profile_counter)?;
+        if let Some(next_profile_counter) = &mut self.next_profile_counter {
+            *next_profile_counter += obj.profile_counters.len();
+        }
+        for label in obj.unresolved_function_calls() {
+            if let Some(procedure) = BigIntProcedure::from_label(label) {
+                self.bigint_procedures.entry(procedure).or_insert_with(|| {
+                    procedure.generate(self.options.enable_debug_trace, self.options.limits)
+                });
+            }
+        }
         self.ssa_function_to_brillig.insert(func.id(), obj);
+        Ok(())
     }
 
     /// Finds a brillig function artifact by its function label
     pub(crate) fn find_by_function_label(&self, function_label: Label) -> Option<&BrilligArtifact> {
+        if let Some(procedure) = BigIntProcedure::from_label(&function_label) {
+            return self.bigint_procedures.get(&procedure);
+        }
         self.ssa_function_to_brillig.iter().find_map(|(function_id, obj)| {
             if FunctionContext::function_id_to_function_label(*function_id) == function_label {
                 Some(obj)
@@ -36,6 +90,42 @@ impl Brillig {
             }
         })
     }
+
+    /// Returns whether calls to the foreign function may be memoized, in which case the number of
+    /// opcodes they execute depends on the number of results cached before them.
+    pub(crate) fn memoizes_foreign_call(&self, func_name: &str) -> bool {
+        self.options.memoized_foreign_calls.iter().any(|name| name == func_name)
+    }
+
+    /// Returns an upper bound on the number of opcodes executed by a call to the function,
+    /// if one could be derived.
+    pub(crate) fn step_bound(&self, function_id: FunctionId) -> Option<usize> {
+        self.step_bounds.get(&function_id).copied()
+    }
+
+    /// Returns the size of the bytecode of each compiled function, in the order of their ids.
+    pub(crate) fn function_sizes(&self, ssa: &Ssa) -> Vec<BrilligFunctionSize> {
+        let function_ids: BTreeSet<_> = self.ssa_function_to_brillig.keys().collect();
+        vecmap(function_ids, |function_id| {
+            let byte_code = &self.ssa_function_to_brillig[function_id].byte_code;
+            let mut opcode_counts = BTreeMap::new();
+            for opcode in byte_code {
+                *opcode_counts.entry(opcode_mnemonic(opcode).to_string()).or_default() += 1;
+            }
+            BrilligFunctionSize {
+                name: ssa.functions[function_id].name().to_string(),
+                opcodes: byte_code.len(),
+                opcode_counts,
+            }
+        })
+    }
+
+    /// Returns an upper bound on the number of opcodes executed by a call to the procedure of the
+    /// big integer library, if a compiled function calls it.
+    pub(crate) fn bigint_procedure_step_bound(&self, procedure: BigIntProcedure) -> Option<usize> {
+        let artifact = self.bigint_procedures.get(&procedure)?;
+        artifact.max_steps(0..artifact.index_of_next_opcode(), 0)
+    }
 }
 
 impl std::ops::Index<FunctionId> for Brillig {
@@ -47,7 +137,11 @@ impl std::ops::Index<FunctionId> for Brillig {
 
 impl Ssa {
     /// Compile to brillig brillig functions and ACIR functions reachable from them
-    pub(crate) fn to_brillig(&self, enable_debug_trace: bool) -> Brillig {
+    ///
+    /// Each function checks on entry that the limits of the `options` are not exceeded. If
+    /// `check_overflows` is set in the `options`, unsigned arithmetic traps instead of wrapping
+    /// around where constrained code would fail its overflow checks.
+    pub(crate) fn to_brillig(&self, options: &BrilligOptions) -> Result<Brillig, RuntimeError> {
         // Collect all the function ids that are reachable from brillig
         // That means all the functions marked as brillig and ACIR functions called by them
         let brillig_reachable_function_ids = self
@@ -56,12 +150,17 @@ impl Ssa {
             .filter_map(|(id, func)| (func.runtime() == RuntimeType::Brillig).then_some(*id))
             .collect::<BTreeSet<_>>();
 
-        let mut brillig = Brillig::default();
+        let mut brillig = Brillig {
+            next_profile_counter: options.enable_profiling.then_some(0),
+            options: options.clone(),
+            ..Brillig::default()
+        };
         for brillig_function_id in brillig_reachable_function_ids {
             let func = &self.functions[&brillig_function_id];
-            brillig.compile(func, enable_debug_trace);
+            brillig.compile(func)?;
         }
+        brillig.step_bounds = compute_step_bounds(self, &brillig);
 
-        brillig
+        Ok(brillig)
     }
 }
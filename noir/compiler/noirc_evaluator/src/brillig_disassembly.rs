@@ -0,0 +1,221 @@
+//! A human-readable disassembly of the Brillig bytecode of a circuit, for inspecting what its
+//! unconstrained functions compile to.
+//!
+//! Each Brillig program is listed under a header naming the ACIR opcodes which execute it. Its
+//! opcodes are numbered by their position in the bytecode, and the targets of jumps and calls are
+//! written as labels `L<position>`, which are also listed before the opcodes they lead to.
+//! Registers are written as `R<address>`, apart from the reserved `Stack`, `PrevStack` and
+//! `CallDepth` registers. As in [`annotated_acir`][crate::annotated_acir], consecutive opcodes
+//! generated at the same call stack are listed under a single header, and the assert message of
+//! an opcode follows it:
+//!
+//! ```text
+//! brillig 0, called by opcodes 2, 5
+//!
+//! // no source location
+//!     0: CONST Stack: u32 = 1027
+//!     1: CALL L3
+//!     2: STOP R1026[0..1]
+//!
+//! // src/main.nr:8 in is_even
+//! //     assert(x % 2 == 0, "odd");
+//! L3:
+//!     3: R6 = R4 i32::== R5
+//!     ...
+//!    10: TRAP
+//!        // assert message: odd
+//! ```
+//!
+//! Programs which are executed by several opcodes are listed with the call stacks and assert
+//! messages of the first of them.
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter, Result};
+
+use acvm::acir::{
+    brillig::Opcode as BrilligOpcode,
+    circuit::{opcodes::Opcode, Circuit, OpcodeLocation},
+};
+use noirc_errors::debug_info::DebugInfo;
+
+use crate::{
+    annotated_acir::write_call_stack, attribution::SourceResolver,
+    brillig::brillig_ir::debug_show::disassemble_opcode,
+};
+
+/// The Brillig bytecode of a circuit displayed with the call stacks of `debug`, as resolved by
+/// `sources`.
+pub struct BrilligDisassembly<'a, R> {
+    pub circuit: &'a Circuit,
+    pub debug: &'a DebugInfo,
+    pub sources: &'a R,
+}
+
+impl<R: SourceResolver> Display for BrilligDisassembly<'_, R> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let mut programs = Vec::new();
+        for (id, bytecode) in self.circuit.brillig_bytecode.iter().enumerate() {
+            let callers: Vec<_> = self
+                .circuit
+                .opcodes
+                .iter()
+                .enumerate()
+                .filter_map(|(index, opcode)| match opcode {
+                    Opcode::BrilligCall { id: call_id, .. } if *call_id as usize == id => {
+                        Some(index)
+                    }
+                    _ => None,
+                })
+                .collect();
+            let header = if callers.is_empty() {
+                format!("brillig {id}, not called")
+            } else {
+                let callers: Vec<_> = callers.iter().map(usize::to_string).collect();
+                format!("brillig {id}, called by opcodes {}", callers.join(", "))
+            };
+            programs.push((header, &bytecode.bytecode, callers.first().copied()));
+        }
+        for (index, opcode) in self.circuit.opcodes.iter().enumerate() {
+            if let Opcode::Brillig(brillig) = opcode {
+                programs.push((
+                    format!("brillig of opcode {index}"),
+                    &brillig.bytecode,
+                    Some(index),
+                ));
+            }
+        }
+
+        for (position, (header, bytecode, acir_index)) in programs.into_iter().enumerate() {
+            if position > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "{header}")?;
+            self.write_bytecode(f, bytecode, acir_index)?;
+        }
+        Ok(())
+    }
+}
+
+impl<R: SourceResolver> BrilligDisassembly<'_, R> {
+    /// Writes the opcodes of `bytecode`, with the call stacks and assert messages which the ACIR
+    /// opcode at `acir_index` has for them.
+    fn write_bytecode(
+        &self,
+        f: &mut Formatter<'_>,
+        bytecode: &[BrilligOpcode],
+        acir_index: Option<usize>,
+    ) -> Result {
+        let targets: BTreeSet<_> = bytecode
+            .iter()
+            .filter_map(|opcode| match opcode {
+                BrilligOpcode::Jump { location }
+                | BrilligOpcode::JumpIf { location, .. }
+                | BrilligOpcode::JumpIfNot { location, .. }
+                | BrilligOpcode::Call { location } => Some(*location),
+                _ => None,
+            })
+            .collect();
+
+        let mut previous_call_stack = None;
+        for (brillig_index, opcode) in bytecode.iter().enumerate() {
+            let location =
+                acir_index.map(|acir_index| OpcodeLocation::Brillig { acir_index, brillig_index });
+            let call_stack = location.and_then(|location| self.debug.locations.get(&location));
+            if previous_call_stack != Some(call_stack) {
+                writeln!(f)?;
+                write_call_stack(f, self.sources, call_stack.map_or(&[][..], Vec::as_slice))?;
+                previous_call_stack = Some(call_stack);
+            }
+            if targets.contains(&brillig_index) {
+                writeln!(f, "L{brillig_index}:")?;
+            }
+            writeln!(f, "{brillig_index:>5}: {}", disassemble_opcode(opcode))?;
+            if let Some(message) =
+                location.and_then(|location| self.circuit.get_assert_message(location))
+            {
+                writeln!(f, "       // assert message: {message}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use acvm::acir::{
+        brillig::{MemoryAddress, Opcode as BrilligOpcode},
+        circuit::{brillig::BrilligBytecode, opcodes::Opcode, Circuit, OpcodeLocation},
+    };
+    use noirc_errors::{debug_info::DebugInfo, Location, Span};
+
+    use super::BrilligDisassembly;
+    use crate::attribution::{SourceLine, SourceResolver};
+
+    struct Lines;
+
+    impl SourceResolver for Lines {
+        fn line(&self, location: Location) -> Option<SourceLine> {
+            Some(SourceLine { file: "main.nr".to_string(), line: location.span.start() })
+        }
+
+        fn function(&self, _location: Location) -> Option<String> {
+            Some("main".to_string())
+        }
+    }
+
+    fn at_line(line: u32) -> Location {
+        Location::new(Span::single_char(line), Default::default())
+    }
+
+    #[test]
+    fn bytecode_is_listed_with_labels_and_the_locations_of_its_first_caller() {
+        let bytecode = vec![
+            BrilligOpcode::Const {
+                destination: MemoryAddress(0),
+                bit_size: 32,
+                value: 1025_u128.into(),
+            },
+            BrilligOpcode::JumpIf { condition: MemoryAddress(4), location: 3 },
+            BrilligOpcode::Trap,
+            BrilligOpcode::Stop { return_data_offset: 1024, return_data_size: 1 },
+        ];
+        let brillig_call =
+            Opcode::BrilligCall { id: 0, inputs: Vec::new(), outputs: Vec::new(), predicate: None };
+        let brillig_location =
+            |acir_index, brillig_index| OpcodeLocation::Brillig { acir_index, brillig_index };
+        let circuit = Circuit {
+            opcodes: vec![brillig_call.clone(), brillig_call],
+            brillig_bytecode: vec![BrilligBytecode { bytecode }],
+            assert_messages: vec![
+                (brillig_location(0, 2), "failed".into()),
+                (brillig_location(1, 2), "failed again".into()),
+            ],
+            ..Circuit::default()
+        };
+        let locations = BTreeMap::from([
+            (brillig_location(0, 1), vec![at_line(3)]),
+            (brillig_location(0, 2), vec![at_line(3)]),
+            (brillig_location(1, 1), vec![at_line(7)]),
+        ]);
+
+        let debug = DebugInfo::new(locations);
+        let listing = BrilligDisassembly { circuit: &circuit, debug: &debug, sources: &Lines };
+        let expected = "\
+brillig 0, called by opcodes 0, 1
+
+// no source location
+    0: CONST Stack: u32 = 1025
+
+// main.nr:3 in main
+    1: JUMP_IF R4 TO L3
+    2: TRAP
+       // assert message: failed
+
+// no source location
+L3:
+    3: STOP R1024[0..1]
+";
+        assert_eq!(listing.to_string(), expected);
+    }
+}
@@ -0,0 +1,259 @@
+//! Backends differ in how expensive each ACIR opcode is to prove. A [`CostModel`] describes these
+//! costs so that ACIR generation can pick the cheapest way of lowering an operation for the
+//! backend being targeted, e.g. whether to use a `RANGE` black box function or a bit
+//! decomposition to range constrain a witness.
+use std::{collections::BTreeMap, fmt::Debug, path::Path, sync::Arc};
+
+use acvm::acir::{
+    circuit::{Circuit, Opcode},
+    BlackBoxFunc,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::gate_hints::{wide_arithmetic_gates, GateHint};
+
+/// The cost of proving each kind of ACIR opcode for a given backend.
+///
+/// Costs are in arbitrary units (e.g. gates) and only need to be consistent with each other.
+pub trait CostModel: Debug + Send + Sync {
+    /// Cost of a single `AssertZero` opcode.
+    fn assert_zero_cost(&self) -> u64;
+
+    /// Cost of a call to `func`, where `num_bits` is the bit size of its inputs.
+    fn black_box_cost(&self, func: BlackBoxFunc, num_bits: u32) -> u64;
+
+    /// Cost of a single read from or write to a memory block.
+    fn lookup_cost(&self) -> u64;
+
+    /// A key which identifies the costs of this model, differing between any two models which
+    /// may lower the same program differently. Compiled artifacts are only reused if they were
+    /// compiled with a model of the same key.
+    fn cache_key(&self) -> String;
+
+    /// Cost of an opcode which only solves for witnesses without constraining them,
+    /// i.e. a directive or a call to Brillig.
+    fn unconstrained_cost(&self) -> u64 {
+        0
+    }
+
+    /// Cost of `opcode`.
+    fn opcode_cost(&self, opcode: &Opcode) -> u64 {
+        match opcode {
+            Opcode::AssertZero(_) => self.assert_zero_cost(),
+            Opcode::BlackBoxFuncCall(call) => {
+                let num_bits = call.get_inputs_vec().first().map_or(0, |input| input.num_bits);
+                self.black_box_cost(call.get_black_box_func(), num_bits)
+            }
+            Opcode::MemoryOp { .. } => self.lookup_cost(),
+            Opcode::Directive(_)
+            | Opcode::Brillig(_)
+            | Opcode::BrilligCall { .. }
+            | Opcode::MemoryInit { .. } => self.unconstrained_cost(),
+            // The constraints of a call are those of the circuit being called, which are costed
+            // separately.
+            Opcode::Call { .. } => self.unconstrained_cost(),
+        }
+    }
+
+    /// Estimated cost of proving a circuit made of `opcodes`.
+    fn circuit_cost(&self, opcodes: &[Opcode]) -> u64 {
+        opcodes.iter().map(|opcode| self.opcode_cost(opcode)).sum()
+    }
+
+    /// Hints about how the backend may lay out the gates of the finished `circuit`, if it
+    /// accepts any.
+    fn gate_hints(&self, _circuit: &Circuit) -> Vec<GateHint> {
+        Vec::new()
+    }
+}
+
+/// Picks a cost model from a command line argument, which is either the name of a backend
+/// whose cost model is built into the compiler or the path to a JSON file.
+pub fn parse_cost_model(input: &str) -> std::io::Result<Arc<dyn CostModel>> {
+    match input {
+        "barretenberg" => Ok(Arc::new(BarretenbergCostModel)),
+        path => Ok(Arc::new(JsonCostModel::from_file(Path::new(path))?)),
+    }
+}
+
+/// The cost model used when the backend does not provide one.
+///
+/// Arithmetic opcodes, memory accesses and the black box functions which backends usually
+/// implement with lookup tables cost one unit each. Every other black box function is assumed to
+/// expand into many constraints.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DefaultCostModel;
+
+impl DefaultCostModel {
+    /// Cost of a black box function which is not implemented with lookup tables.
+    const GADGET_COST: u64 = 100;
+}
+
+impl CostModel for DefaultCostModel {
+    fn assert_zero_cost(&self) -> u64 {
+        1
+    }
+
+    fn black_box_cost(&self, func: BlackBoxFunc, _num_bits: u32) -> u64 {
+        match func {
+            BlackBoxFunc::RANGE | BlackBoxFunc::AND | BlackBoxFunc::XOR => 1,
+            _ => Self::GADGET_COST,
+        }
+    }
+
+    fn lookup_cost(&self) -> u64 {
+        1
+    }
+
+    fn cache_key(&self) -> String {
+        "default".to_owned()
+    }
+}
+
+/// The costs of Barretenberg, in gates of its width 4 arithmetization.
+///
+/// Range constraints and bitwise operations are implemented with lookup tables over 14 and 4
+/// bit limbs respectively, so they cost a gate per limb.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BarretenbergCostModel;
+
+impl CostModel for BarretenbergCostModel {
+    fn assert_zero_cost(&self) -> u64 {
+        1
+    }
+
+    fn black_box_cost(&self, func: BlackBoxFunc, num_bits: u32) -> u64 {
+        match func {
+            BlackBoxFunc::RANGE => u64::from((num_bits + 13) / 14).max(1),
+            BlackBoxFunc::AND | BlackBoxFunc::XOR => u64::from((num_bits + 3) / 4).max(1),
+            _ => DefaultCostModel.black_box_cost(func, num_bits),
+        }
+    }
+
+    fn lookup_cost(&self) -> u64 {
+        2
+    }
+
+    fn cache_key(&self) -> String {
+        "barretenberg".to_owned()
+    }
+
+    fn gate_hints(&self, circuit: &Circuit) -> Vec<GateHint> {
+        wide_arithmetic_gates(circuit)
+    }
+}
+
+/// A cost model which is read from a JSON file, for experimenting with the costs of a backend
+/// without having to rebuild the compiler.
+///
+/// Black box functions are keyed by their name, as in `{ "black_box": { "range": 2 } }`.
+/// Any cost which is not given is taken from the [`DefaultCostModel`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JsonCostModel {
+    pub assert_zero: u64,
+    pub lookup: u64,
+    pub unconstrained: u64,
+    /// Fixed cost of each black box function.
+    pub black_box: BTreeMap<String, u64>,
+    /// Cost of each black box function per bit of its inputs, added to its fixed cost.
+    pub black_box_per_bit: BTreeMap<String, u64>,
+}
+
+impl Default for JsonCostModel {
+    fn default() -> Self {
+        JsonCostModel {
+            assert_zero: DefaultCostModel.assert_zero_cost(),
+            lookup: DefaultCostModel.lookup_cost(),
+            unconstrained: DefaultCostModel.unconstrained_cost(),
+            black_box: BTreeMap::new(),
+            black_box_per_bit: BTreeMap::new(),
+        }
+    }
+}
+
+impl JsonCostModel {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn from_file(path: &Path) -> std::io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(Self::from_json(&json)?)
+    }
+}
+
+impl CostModel for JsonCostModel {
+    fn assert_zero_cost(&self) -> u64 {
+        self.assert_zero
+    }
+
+    fn black_box_cost(&self, func: BlackBoxFunc, num_bits: u32) -> u64 {
+        let fixed_cost = match self.black_box.get(func.name()) {
+            Some(cost) => *cost,
+            None => DefaultCostModel.black_box_cost(func, num_bits),
+        };
+        let cost_per_bit = self.black_box_per_bit.get(func.name()).copied().unwrap_or(0);
+        fixed_cost + cost_per_bit * num_bits as u64
+    }
+
+    fn lookup_cost(&self) -> u64 {
+        self.lookup
+    }
+
+    fn unconstrained_cost(&self) -> u64 {
+        self.unconstrained
+    }
+
+    fn cache_key(&self) -> String {
+        // Serialized costs are ordered by name, so equal models have equal keys.
+        serde_json::to_string(self).expect("JSON cost models can always be serialized")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::{
+        circuit::{
+            opcodes::{BlackBoxFuncCall, FunctionInput},
+            Opcode,
+        },
+        native_types::{Expression, Witness},
+        BlackBoxFunc,
+    };
+
+    use super::{BarretenbergCostModel, CostModel, JsonCostModel};
+
+    #[test]
+    fn missing_costs_fall_back_to_defaults() {
+        let model = JsonCostModel::from_json(
+            r#"{ "assert_zero": 2, "black_box": { "range": 3 }, "black_box_per_bit": { "and": 4 } }"#,
+        )
+        .unwrap();
+
+        assert_eq!(model.assert_zero_cost(), 2);
+        assert_eq!(model.lookup_cost(), 1);
+        assert_eq!(model.black_box_cost(BlackBoxFunc::RANGE, 32), 3);
+        assert_eq!(model.black_box_cost(BlackBoxFunc::AND, 8), 1 + 4 * 8);
+        assert_eq!(model.black_box_cost(BlackBoxFunc::SHA256, 8), 100);
+    }
+
+    #[test]
+    fn barretenberg_range_constraints_cost_a_gate_per_lookup() {
+        let model = BarretenbergCostModel;
+        assert_eq!(model.black_box_cost(BlackBoxFunc::RANGE, 1), 1);
+        assert_eq!(model.black_box_cost(BlackBoxFunc::RANGE, 14), 1);
+        assert_eq!(model.black_box_cost(BlackBoxFunc::RANGE, 64), 5);
+        assert_eq!(model.black_box_cost(BlackBoxFunc::XOR, 32), 8);
+    }
+
+    #[test]
+    fn circuits_cost_the_sum_of_their_opcodes() {
+        let input = FunctionInput { witness: Witness(1), num_bits: 64 };
+        let opcodes = [
+            Opcode::AssertZero(Expression::default()),
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { input }),
+        ];
+        assert_eq!(BarretenbergCostModel.circuit_cost(&opcodes), 1 + 5);
+    }
+}
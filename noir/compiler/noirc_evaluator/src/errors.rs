@@ -7,7 +7,10 @@
 //! An Error of the former is a user Error
 //!
 //! An Error of the latter is an error in the implementation of the compiler
-use acvm::{acir::native_types::Expression, FieldElement};
+use acvm::{
+    acir::{native_types::Expression, BlackBoxFunc},
+    FieldElement,
+};
 use iter_extended::vecmap;
 use noirc_errors::{CustomDiagnostic as Diagnostic, FileDiagnostic};
 use thiserror::Error;
@@ -46,6 +49,27 @@ pub enum RuntimeError {
     NestedSlice { call_stack: CallStack },
     #[error("Big Integer modulus do no match")]
     BigIntModulus { call_stack: CallStack },
+    #[error("Invalid public input layout: {message}")]
+    InvalidPublicInputLayout { message: String, call_stack: CallStack },
+    #[error("Failed to write to {path}: {message}")]
+    WriteOutput { path: String, message: String, call_stack: CallStack },
+    #[error("Cannot slice the circuit to `{target}`, as no assertion has this message")]
+    UnknownSliceTarget { target: String, call_stack: CallStack },
+    #[error("Value between {lower} and {upper} can never fit within {num_bits} bits")]
+    FailingRangeConstraint {
+        lower: Box<FieldElement>,
+        upper: Box<FieldElement>,
+        num_bits: u32,
+        call_stack: CallStack,
+    },
+    #[error("Black box function {name} is not supported in unconstrained functions")]
+    UnsupportedBrilligBlackBox { name: BlackBoxFunc, call_stack: CallStack },
+    #[error("Commitment cannot be computed at compile time, as {reason}")]
+    CommitmentNotPrecomputed { reason: String, call_stack: CallStack },
+    #[error("Black box function {name} takes {expected} inputs, but was called with {found}")]
+    BlackBoxInputCount { name: BlackBoxFunc, expected: usize, found: usize, call_stack: CallStack },
+    #[error("Black box function {name} returns {expected} outputs, but {found} were expected")]
+    BlackBoxOutputCount { name: BlackBoxFunc, expected: usize, found: usize, call_stack: CallStack },
 }
 
 // We avoid showing the actual lhs and rhs since most of the time they are just 0
@@ -75,6 +99,15 @@ impl From<SsaReport> for FileDiagnostic {
                     InternalWarning::VerifyProof { call_stack } => {
                         ("verify_proof(...) aggregates data for the verifier, the actual verification will be done when the full proof is verified using nargo verify. nargo prove may generate an invalid proof if bad data is used as input to verify_proof".to_string(), call_stack)
                     },
+                    InternalWarning::ReductionWitnesses { call_stack, .. } => {
+                        ("Each non-linear operand of a multiplication is assigned to a new witness. Consider passing repeated sub-expressions to std::as_witness so that they are only assigned once".to_string(), call_stack)
+                    },
+                    InternalWarning::FailingRangeConstraint { call_stack, .. } => {
+                        ("The range constraint of this value will fail whatever the inputs to the program are".to_string(), call_stack)
+                    },
+                    InternalWarning::DisconnectedComponent { call_stack, .. } => {
+                        ("These constraints involve none of the inputs or return values of the program, even through other constraints, so they do not restrict its behavior. This usually points to a result which is never used".to_string(), call_stack)
+                    },
                 };
                 let call_stack = vecmap(call_stack, |location| location);
                 let file_id = call_stack.last().map(|location| location.file).unwrap_or_default();
@@ -93,6 +126,29 @@ pub enum InternalWarning {
     ReturnConstant { call_stack: CallStack },
     #[error("Calling std::verify_proof(...) does not verify a proof")]
     VerifyProof { call_stack: CallStack },
+    #[error("Expression needs more than {max_reduction_witnesses} intermediate witnesses")]
+    ReductionWitnesses { max_reduction_witnesses: usize, call_stack: CallStack },
+    #[error("Value between {lower} and {upper} can never fit within {num_bits} bits")]
+    FailingRangeConstraint {
+        lower: FieldElement,
+        upper: FieldElement,
+        num_bits: u32,
+        call_stack: CallStack,
+    },
+    #[error("{opcodes} opcodes are not connected to any input or return value")]
+    DisconnectedComponent { opcodes: usize, call_stack: CallStack },
+}
+
+impl InternalWarning {
+    pub(crate) fn call_stack(&self) -> &CallStack {
+        match self {
+            InternalWarning::ReturnConstant { call_stack }
+            | InternalWarning::VerifyProof { call_stack }
+            | InternalWarning::ReductionWitnesses { call_stack, .. }
+            | InternalWarning::FailingRangeConstraint { call_stack, .. }
+            | InternalWarning::DisconnectedComponent { call_stack, .. } => call_stack,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Error)]
@@ -135,7 +191,15 @@ impl RuntimeError {
             | RuntimeError::IntegerOutOfBounds { call_stack, .. }
             | RuntimeError::UnsupportedIntegerSize { call_stack, .. }
             | RuntimeError::NestedSlice { call_stack, .. }
-            | RuntimeError::BigIntModulus { call_stack, .. } => call_stack,
+            | RuntimeError::BigIntModulus { call_stack, .. }
+            | RuntimeError::InvalidPublicInputLayout { call_stack, .. }
+            | RuntimeError::WriteOutput { call_stack, .. }
+            | RuntimeError::UnknownSliceTarget { call_stack, .. }
+            | RuntimeError::FailingRangeConstraint { call_stack, .. }
+            | RuntimeError::UnsupportedBrilligBlackBox { call_stack, .. }
+            | RuntimeError::CommitmentNotPrecomputed { call_stack, .. }
+            | RuntimeError::BlackBoxInputCount { call_stack, .. }
+            | RuntimeError::BlackBoxOutputCount { call_stack, .. } => call_stack,
         }
     }
 }
@@ -160,6 +224,19 @@ impl RuntimeError {
                     noirc_errors::Span::inclusive(0, 0)
                 )
             }
+            // Names in a layout which don't match a parameter of `main`, slice targets which don't
+            // match an assertion and outputs which can't be written have no location.
+            RuntimeError::InvalidPublicInputLayout { .. }
+            | RuntimeError::UnknownSliceTarget { .. }
+            | RuntimeError::WriteOutput { .. }
+                if self.call_stack().is_empty() =>
+            {
+                Diagnostic::simple_error(
+                    self.to_string(),
+                    String::new(),
+                    noirc_errors::Span::inclusive(0, 0),
+                )
+            }
             _ => {
                 let message = self.to_string();
                 let location =
@@ -0,0 +1,404 @@
+//! A builder API for emitting ACIR directly from Rust.
+//!
+//! Circuits which are not written in Noir (e.g. parts of the Aztec protocol circuits) can use
+//! [`AcirBuilder`] to allocate witnesses, emit constraints and call black box functions using
+//! the same primitives as the compiler itself. A builder can also be resumed from a
+//! Noir-compiled [`Circuit`] so that hand-written gadgets can be linked onto its inputs and
+//! outputs.
+//!
+//! This API is relied upon by other crates in the workspace, so changes to it should be kept
+//! backwards compatible where possible.
+use std::collections::{BTreeMap, BTreeSet};
+
+use acvm::{
+    acir::{
+        brillig::Opcode as BrilligOpcode,
+        circuit::{
+            brillig::{BrilligInputs, BrilligOutputs},
+            opcodes::FunctionInput,
+            Circuit, ExpressionWidth, PublicInputs,
+        },
+        native_types::{Expression, Witness},
+        BlackBoxFunc,
+    },
+    FieldElement,
+};
+use noirc_errors::{debug_info::DebugInfo, Location};
+
+use crate::{
+    brillig::brillig_ir::artifact::GeneratedBrillig, cost_model::CostModel, errors::RuntimeError,
+    opcode_sink::OpcodeSink, ssa::GeneratedAcir,
+};
+
+/// Incrementally builds an ACIR [`Circuit`].
+#[derive(Debug, Default)]
+pub struct AcirBuilder {
+    acir: GeneratedAcir,
+    public_parameters: BTreeSet<Witness>,
+    private_parameters: BTreeSet<Witness>,
+    recursive: bool,
+}
+
+impl AcirBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resumes building from an existing circuit.
+    ///
+    /// The opcodes, Brillig bytecode, parameters and return values of `circuit` are kept and any
+    /// witness allocated by the builder is guaranteed not to clash with a witness of `circuit`.
+    pub fn from_circuit(circuit: Circuit) -> Self {
+        let mut acir = GeneratedAcir::default();
+        acir.reserve_witnesses(Witness(circuit.current_witness_index));
+        for opcode in circuit.opcodes {
+            acir.push_opcode(opcode);
+        }
+        acir.assert_messages = circuit.assert_messages.into_iter().collect();
+        acir.brillig_bytecode = circuit.brillig_bytecode;
+        acir.return_witnesses = circuit.return_values.0.into_iter().collect();
+
+        Self {
+            acir,
+            public_parameters: circuit.public_parameters.0,
+            private_parameters: circuit.private_parameters,
+            recursive: circuit.recursive,
+        }
+    }
+
+    /// Declares a new private input to the circuit.
+    pub fn add_private_input(&mut self) -> Witness {
+        let witness = self.acir.next_witness_index();
+        self.acir.input_witnesses.push(witness);
+        self.private_parameters.insert(witness);
+        witness
+    }
+
+    /// Declares a new public input to the circuit.
+    pub fn add_public_input(&mut self) -> Witness {
+        let witness = self.acir.next_witness_index();
+        self.acir.input_witnesses.push(witness);
+        self.public_parameters.insert(witness);
+        witness
+    }
+
+    /// Adds `witness` to the values returned by the circuit.
+    pub fn add_return_value(&mut self, witness: Witness) {
+        self.acir.push_return_witness(witness);
+    }
+
+    /// Marks the circuit as one whose proofs are to be verified inside of another circuit.
+    pub fn set_recursive(&mut self, recursive: bool) {
+        self.recursive = recursive;
+    }
+
+    /// Allocates a fresh, unconstrained witness.
+    pub fn new_witness(&mut self) -> Witness {
+        self.acir.next_witness_index()
+    }
+
+    /// Returns a witness constrained to be equal to `expression`,
+    /// reusing `expression` itself if it is already a single witness.
+    pub fn witness_for(&mut self, expression: &Expression) -> Witness {
+        self.acir.get_or_create_witness(expression)
+    }
+
+    /// Returns `lhs * rhs`, introducing intermediate witnesses so that the result is at most
+    /// of degree 2.
+    pub fn mul(&mut self, lhs: &Expression, rhs: &Expression) -> Expression {
+        self.acir.mul_with_witness(lhs, rhs)
+    }
+
+    /// Returns a witness constrained to be `1` if `lhs == rhs` and `0` otherwise.
+    pub fn is_equal(&mut self, lhs: &Expression, rhs: &Expression) -> Witness {
+        self.acir.is_equal(lhs, rhs)
+    }
+
+    /// Constrains `expression` to be zero.
+    pub fn assert_zero(&mut self, expression: Expression) {
+        self.acir.assert_is_zero(expression);
+    }
+
+    /// Constrains `lhs` to be equal to `rhs`, reporting `message` if the constraint fails.
+    pub fn assert_eq(&mut self, lhs: &Expression, rhs: &Expression, message: Option<String>) {
+        self.acir.assert_is_zero(lhs - rhs);
+        if let Some(message) = message {
+            self.acir.set_last_assert_message(message.into());
+        }
+    }
+
+    /// Constrains `witness` to fit within `num_bits` bits.
+    pub fn range_constraint(
+        &mut self,
+        witness: Witness,
+        num_bits: u32,
+    ) -> Result<(), RuntimeError> {
+        self.acir.range_constraint(witness, num_bits)
+    }
+
+    /// Returns the big-endian packing of `bytes` into a field element, range constraining each
+    /// byte which is not constant and not already known to fit in a byte.
+    pub fn pack_bytes(&mut self, bytes: &[Expression]) -> Result<Expression, RuntimeError> {
+        self.acir.pack_bytes(bytes)
+    }
+
+    /// Returns the `num_bytes` big-endian bytes of `value`, which are known to fit in a byte
+    /// when later packed with [`pack_bytes`][Self::pack_bytes].
+    pub fn unpack_bytes(
+        &mut self,
+        value: &Expression,
+        num_bytes: u32,
+    ) -> Result<Vec<Expression>, RuntimeError> {
+        self.acir.unpack_bytes(value, num_bytes)
+    }
+
+    /// Calls the black box function `func` and returns its output witnesses.
+    pub fn call_black_box(
+        &mut self,
+        func: BlackBoxFunc,
+        inputs: &[Vec<FunctionInput>],
+        constant_inputs: Vec<FieldElement>,
+        output_count: usize,
+    ) -> Result<Vec<Witness>, RuntimeError> {
+        self.acir.call_black_box(func, inputs, constant_inputs, Vec::new(), output_count, None)
+    }
+
+    /// Adds a call to unconstrained `bytecode`, which is only executed if `predicate` is non-zero.
+    ///
+    /// The outputs are not constrained in any way, so callers must constrain them afterwards.
+    pub fn brillig(
+        &mut self,
+        predicate: Option<Expression>,
+        bytecode: Vec<BrilligOpcode>,
+        inputs: Vec<BrilligInputs>,
+        outputs: Vec<BrilligOutputs>,
+    ) {
+        let generated_brillig = GeneratedBrillig {
+            byte_code: bytecode,
+            locations: BTreeMap::new(),
+            assert_messages: BTreeMap::new(),
+            step_bound: None,
+            variables: Vec::new(),
+            profile_counters: BTreeMap::new(),
+        };
+        self.acir.brillig(predicate, generated_brillig, inputs, outputs);
+    }
+
+    /// Attributes every opcode emitted by `f` to `location`.
+    ///
+    /// Scopes can be nested, in which case opcodes are attributed to the full stack of locations.
+    pub fn scoped<T>(&mut self, location: Location, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.acir.call_stack.push_back(location);
+        let result = f(self);
+        self.acir.call_stack.pop_back();
+        result
+    }
+
+    /// Streams the opcodes emitted so far and from now on to `sink` rather than holding them in
+    /// memory, for circuits too large to build otherwise.
+    ///
+    /// Only the indices of the opcodes are kept, which their locations and assertion messages are
+    /// keyed by. The circuit returned by [`finish_streaming`][Self::finish_streaming] has no
+    /// opcodes of its own, and [`estimate_cost`][Self::estimate_cost] does not count the opcodes
+    /// which have been streamed.
+    pub fn stream_opcodes(&mut self, sink: Box<dyn OpcodeSink>) {
+        self.acir.set_opcode_sink(sink);
+    }
+
+    /// Estimated cost of proving the opcodes emitted so far with the backend of `cost_model`.
+    pub fn estimate_cost(&self, cost_model: &dyn CostModel) -> u64 {
+        self.acir.estimate_cost(cost_model)
+    }
+
+    /// Finishes building, returning the circuit along with the locations of its opcodes.
+    pub fn finish(mut self) -> (Circuit, DebugInfo) {
+        let current_witness_index = self.acir.current_witness_index().0;
+        let opcodes = self.acir.take_opcodes();
+        let GeneratedAcir {
+            return_witnesses,
+            locations,
+            call_stacks,
+            assert_messages,
+            brillig_bytecode,
+            ..
+        } = self.acir;
+
+        let circuit = Circuit {
+            current_witness_index,
+            expression_width: ExpressionWidth::Unbounded,
+            opcodes,
+            private_parameters: self.private_parameters,
+            public_parameters: PublicInputs(self.public_parameters),
+            return_values: PublicInputs(return_witnesses.into_iter().collect()),
+            assert_messages: assert_messages.into_iter().collect(),
+            recursive: self.recursive,
+            brillig_bytecode,
+        };
+
+        let locations = locations
+            .into_iter()
+            .map(|(index, call_stack)| {
+                (index, call_stacks.resolve(call_stack).into_iter().collect())
+            })
+            .collect();
+        (circuit, DebugInfo::new(locations))
+    }
+
+    /// Finishes building after [`stream_opcodes`][Self::stream_opcodes], flushing the sink.
+    ///
+    /// Returns the first error which the sink returned, if any.
+    pub fn finish_streaming(mut self) -> std::io::Result<(Circuit, DebugInfo)> {
+        self.acir.close_opcode_sink()?;
+        Ok(self.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::{
+        brillig::Opcode as BrilligOpcode,
+        circuit::{
+            brillig::{BrilligInputs, BrilligOutputs},
+            opcodes::Opcode,
+            OpcodeLocation,
+        },
+        native_types::Expression,
+    };
+    use noirc_errors::{Location, Span};
+
+    use super::AcirBuilder;
+    use crate::opcode_sink::{read_json_lines, JsonLinesSink, OpcodeSink};
+
+    #[test]
+    fn scopes_attribute_opcodes_to_locations() {
+        let outer = Location::new(Span::single_char(1), Default::default());
+        let inner = Location::new(Span::single_char(2), Default::default());
+
+        let mut builder = AcirBuilder::new();
+        let input = builder.add_private_input();
+        builder.scoped(outer, |builder| {
+            builder.assert_zero(input.into());
+            builder.scoped(inner, |builder| builder.assert_zero(input.into()));
+        });
+        builder.assert_zero(input.into());
+
+        let (circuit, debug_info) = builder.finish();
+        assert_eq!(circuit.opcodes.len(), 3);
+        assert_eq!(debug_info.locations[&OpcodeLocation::Acir(0)], vec![outer]);
+        assert_eq!(debug_info.locations[&OpcodeLocation::Acir(1)], vec![outer, inner]);
+        assert!(!debug_info.locations.contains_key(&OpcodeLocation::Acir(2)));
+    }
+
+    #[test]
+    fn resumed_builder_does_not_reuse_witnesses() {
+        let mut builder = AcirBuilder::new();
+        let public_input = builder.add_public_input();
+        let private_input = builder.add_private_input();
+        let sum = builder
+            .witness_for(&(&Expression::from(public_input) + &Expression::from(private_input)));
+        builder.add_return_value(sum);
+        let (circuit, _) = builder.finish();
+
+        let mut builder = AcirBuilder::from_circuit(circuit.clone());
+        let square = builder.mul(&sum.into(), &sum.into());
+        let square = builder.witness_for(&square);
+        assert!(square.witness_index() > circuit.current_witness_index);
+        builder.add_return_value(square);
+
+        let (linked, _) = builder.finish();
+        assert_eq!(linked.public_parameters, circuit.public_parameters);
+        assert_eq!(linked.private_parameters, circuit.private_parameters);
+        assert_eq!(linked.return_values.0.into_iter().collect::<Vec<_>>(), vec![sum, square]);
+        assert_eq!(&linked.opcodes[..circuit.opcodes.len()], &circuit.opcodes[..]);
+        assert!(matches!(linked.opcodes.last(), Some(Opcode::AssertZero(_))));
+        assert_eq!(linked.current_witness_index, square.witness_index());
+    }
+
+    #[test]
+    fn resumed_builder_keeps_brillig_bytecode() {
+        let stop = vec![BrilligOpcode::Stop { return_data_offset: 0, return_data_size: 0 }];
+        let trap = vec![BrilligOpcode::Trap];
+        let call = |builder: &mut AcirBuilder, bytecode: &Vec<BrilligOpcode>| {
+            let input = builder.add_private_input();
+            let output = builder.new_witness();
+            builder.brillig(
+                None,
+                bytecode.clone(),
+                vec![BrilligInputs::Single(input.into())],
+                vec![BrilligOutputs::Simple(output)],
+            );
+        };
+
+        let mut builder = AcirBuilder::new();
+        call(&mut builder, &stop);
+        let (circuit, _) = builder.finish();
+
+        let mut builder = AcirBuilder::from_circuit(circuit.clone());
+        call(&mut builder, &stop);
+        call(&mut builder, &trap);
+        let (linked, _) = builder.finish();
+
+        assert_eq!(&linked.brillig_bytecode[..1], &circuit.brillig_bytecode[..]);
+        let bytecode = linked.brillig_bytecode.iter().map(|brillig| &brillig.bytecode);
+        assert_eq!(bytecode.collect::<Vec<_>>(), vec![&stop, &trap]);
+        let ids: Vec<_> = linked
+            .opcodes
+            .iter()
+            .filter_map(|opcode| match opcode {
+                Opcode::BrilligCall { id, .. } => Some(*id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(ids, vec![0, 0, 1]);
+    }
+
+    /// Shares the bytes written by a [`JsonLinesSink`] with the test once the builder is done.
+    #[derive(Debug, Clone, Default)]
+    struct SharedBuffer(std::rc::Rc<std::cell::RefCell<Vec<u8>>>);
+
+    impl std::io::Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Builds the same circuit whether or not opcodes are streamed, attaching `sink` once some
+    /// opcodes have been emitted.
+    fn build(sink: Option<Box<dyn OpcodeSink>>) -> AcirBuilder {
+        let location = Location::new(Span::single_char(1), Default::default());
+        let mut builder = AcirBuilder::new();
+        let input = builder.add_private_input();
+        builder.assert_zero(input.into());
+        if let Some(sink) = sink {
+            builder.stream_opcodes(sink);
+        }
+        builder.scoped(location, |builder| {
+            let square = builder.mul(&input.into(), &input.into());
+            builder.assert_eq(&square, &input.into(), Some("not boolean".to_string()));
+        });
+        builder
+    }
+
+    #[test]
+    fn streamed_opcodes_match_opcodes_held_in_memory() {
+        let (circuit, debug_info) = build(None).finish();
+
+        let buffer = SharedBuffer::default();
+        let sink = JsonLinesSink::new(buffer.clone());
+        let (streamed_circuit, streamed_debug_info) =
+            build(Some(Box::new(sink))).finish_streaming().unwrap();
+
+        assert!(streamed_circuit.opcodes.is_empty());
+        let streamed = read_json_lines(&buffer.0.borrow()[..]).unwrap();
+        assert_eq!(streamed, circuit.opcodes);
+        assert!(!debug_info.locations.is_empty());
+        assert_eq!(streamed_debug_info.locations, debug_info.locations);
+        assert!(!circuit.assert_messages.is_empty());
+        assert_eq!(streamed_circuit.assert_messages, circuit.assert_messages);
+    }
+}
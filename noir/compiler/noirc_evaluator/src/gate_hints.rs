@@ -0,0 +1,133 @@
+//! Hints about how a backend may lay out the gates of a finished circuit, which it is free to
+//! ignore as they do not change the constraints.
+//!
+//! Backends with wide arithmetic gates split an `AssertZero` opcode which does not fit in a single
+//! gate into several, linked by intermediate witnesses. Each of these intermediate witnesses only
+//! passes a partial sum on to the next opcode, so a backend which lets a gate read a wire of the
+//! following row (such as Barretenberg) can lay the opcodes out as successive rows of one wide
+//! gate rather than as independent gates.
+use std::{collections::HashMap, ops::Range};
+
+use acvm::acir::{
+    circuit::{Circuit, Opcode},
+    native_types::Witness,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::ssa::rename_opcode_witnesses;
+
+/// A group of consecutive opcodes which the backend may lay out together.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GateHint {
+    /// The indices of the opcodes in the group.
+    pub opcodes: Range<usize>,
+    pub layout: GateLayout,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GateLayout {
+    /// `AssertZero` opcodes where each one shares an intermediate witness with the next, which
+    /// is not referenced anywhere else, so that they form a single wide arithmetic gate.
+    WideArithmetic,
+}
+
+/// Returns the groups of consecutive `AssertZero` opcodes of `circuit` which form a single wide
+/// arithmetic gate.
+pub fn wide_arithmetic_gates(circuit: &Circuit) -> Vec<GateHint> {
+    let mut occurrences: HashMap<Witness, usize> = HashMap::new();
+    for opcode in &circuit.opcodes {
+        rename_opcode_witnesses(&mut opcode.clone(), &mut |witness| {
+            *occurrences.entry(witness).or_default() += 1;
+            witness
+        });
+    }
+    let arguments = circuit.circuit_arguments();
+    let return_values = &circuit.return_values.0;
+    let is_intermediate = |witness: &Witness| {
+        occurrences[witness] == 2
+            && !arguments.contains(witness)
+            && !return_values.contains(witness)
+    };
+
+    let mut hints = Vec::new();
+    let mut start = 0;
+    for (index, pair) in circuit.opcodes.windows(2).enumerate() {
+        let linked = match pair {
+            [Opcode::AssertZero(current), Opcode::AssertZero(next)] => {
+                next.linear_combinations.iter().any(|(_, witness)| {
+                    is_intermediate(witness)
+                        && current.linear_combinations.iter().any(|(_, other)| other == witness)
+                })
+            }
+            _ => false,
+        };
+        if !linked {
+            push_group(&mut hints, start..index + 1);
+            start = index + 1;
+        }
+    }
+    push_group(&mut hints, start..circuit.opcodes.len());
+    hints
+}
+
+fn push_group(hints: &mut Vec<GateHint>, opcodes: Range<usize>) {
+    if opcodes.len() > 1 {
+        hints.push(GateHint { opcodes, layout: GateLayout::WideArithmetic });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use acvm::{
+        acir::{
+            circuit::{
+                opcodes::{BlackBoxFuncCall, FunctionInput},
+                Circuit, Opcode, PublicInputs,
+            },
+            native_types::{Expression, Witness},
+        },
+        FieldElement,
+    };
+
+    use super::{wide_arithmetic_gates, GateHint, GateLayout};
+
+    fn sum(witnesses: &[u32]) -> Opcode {
+        let mut expression = Expression::default();
+        for witness in witnesses {
+            expression.push_addition_term(FieldElement::one(), Witness(*witness));
+        }
+        Opcode::AssertZero(expression)
+    }
+
+    #[test]
+    fn opcodes_linked_by_intermediate_witnesses_are_grouped() {
+        let range = Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
+            input: FunctionInput { witness: Witness(9), num_bits: 8 },
+        });
+        let circuit = Circuit {
+            current_witness_index: 9,
+            opcodes: vec![
+                // `w4` and `w5` carry partial sums from one opcode to the next.
+                sum(&[0, 1, 2, 4]),
+                sum(&[4, 3, 5]),
+                sum(&[5, 6, 7]),
+                // `w7` is also a return value, so it does not link these opcodes, unlike `w8`.
+                sum(&[7, 8]),
+                // `w9` is referenced by a range constraint as well.
+                sum(&[8, 9]),
+                range,
+                sum(&[9, 1]),
+            ],
+            private_parameters: BTreeSet::from([Witness(0), Witness(1)]),
+            return_values: PublicInputs(BTreeSet::from([Witness(7)])),
+            ..Circuit::default()
+        };
+
+        let hints = wide_arithmetic_gates(&circuit);
+        let group = |opcodes| GateHint { opcodes, layout: GateLayout::WideArithmetic };
+        assert_eq!(hints, vec![group(0..3), group(3..5)]);
+    }
+}
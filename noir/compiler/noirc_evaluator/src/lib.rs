@@ -3,7 +3,19 @@
 #![warn(unreachable_pub)]
 #![warn(clippy::semicolon_if_nothing_returned)]
 
+pub mod acir_json;
+pub mod annotated_acir;
+pub mod attribution;
+pub mod brillig_disassembly;
+pub mod cost_model;
 pub mod errors;
+pub mod gate_hints;
+pub mod opcode_sink;
+pub mod plonkish;
+pub mod public_input_layout;
+pub mod slice;
+pub mod smt_lib;
+pub mod spans;
 
 // SSA code to create the SSA based IR
 // for functions and execute different optimizations.
@@ -11,4 +23,7 @@ pub mod ssa;
 
 pub mod brillig;
 
+// Builder API for emitting ACIR directly from Rust.
+pub mod gadgets;
+
 pub use ssa::create_circuit;
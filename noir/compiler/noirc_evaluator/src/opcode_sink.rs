@@ -0,0 +1,49 @@
+//! Sinks which the opcodes of a circuit can be streamed to as they are generated.
+//!
+//! Circuits of millions of opcodes may not fit in memory alongside the maps of debug information
+//! built for them. Streaming the opcodes to an [`OpcodeSink`] keeps only their indices in memory,
+//! which is all that the debug information and assertion messages refer to them by.
+use std::io::{self, Write};
+
+use acvm::acir::circuit::Opcode;
+
+/// Receives the opcodes of a circuit in order, so that the index of an opcode is the number of
+/// opcodes written before it.
+pub trait OpcodeSink: std::fmt::Debug {
+    fn write_opcode(&mut self, opcode: &Opcode) -> io::Result<()>;
+
+    /// Called once every opcode has been written.
+    fn flush(&mut self) -> io::Result<()>;
+}
+
+/// Writes each opcode as a line of JSON, in the serialization of `acvm`.
+#[derive(Debug)]
+pub struct JsonLinesSink<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> JsonLinesSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write + std::fmt::Debug> OpcodeSink for JsonLinesSink<W> {
+    fn write_opcode(&mut self, opcode: &Opcode) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, opcode)?;
+        self.writer.write_all(b"\n")
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Reads back the opcodes written by a [`JsonLinesSink`].
+pub fn read_json_lines(reader: impl io::BufRead) -> io::Result<Vec<Opcode>> {
+    reader.lines().map(|line| Ok(serde_json::from_str(&line?)?)).collect()
+}
@@ -0,0 +1,271 @@
+//! Lowers a [`Circuit`] into the standard PLONK arithmetization, as a table which halo2 and other
+//! PLONKish proving systems can prove with a single gate over three advice columns:
+//!
+//! ```text
+//! q_m * a * b + q_l * a + q_r * b + q_o * c + q_c = 0
+//! ```
+//!
+//! Each `AssertZero` opcode becomes one row of the table, with its coefficients in the fixed
+//! selector columns and its witnesses in the advice columns. The cells which hold the same
+//! witness, including those of the public inputs in the instance column, are linked by copy
+//! constraints. Only circuits compiled with an expression width of 3 fit in this gate.
+//!
+//! Directives and Brillig calls only compute hints for the prover, so they have no rows. Black box
+//! functions and memory opcodes have no lowering to this gate, so circuits using them are rejected.
+use std::collections::BTreeMap;
+
+use acvm::{
+    acir::{
+        circuit::{Circuit, Opcode},
+        native_types::{Expression, Witness},
+    },
+    FieldElement,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlonkishCircuit {
+    /// The largest index of a witness in the circuit.
+    pub current_witness_index: u32,
+    pub fixed: FixedColumns,
+    pub advice: AdviceColumns,
+    /// The witness of each row of the instance column, i.e. the public parameters and return
+    /// values in order of their witness index.
+    pub instance: Vec<Witness>,
+    /// Sets of cells which must hold equal values, one for each witness found in several cells.
+    pub copy_constraints: Vec<Vec<Cell>>,
+}
+
+/// The selectors of the gate, with one value per row.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixedColumns {
+    pub q_m: Vec<FieldElement>,
+    pub q_l: Vec<FieldElement>,
+    pub q_r: Vec<FieldElement>,
+    pub q_o: Vec<FieldElement>,
+    pub q_c: Vec<FieldElement>,
+}
+
+/// The witness held by each row of the advice columns, or `None` if the row does not use it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdviceColumns {
+    pub a: Vec<Option<Witness>>,
+    pub b: Vec<Option<Witness>>,
+    pub c: Vec<Option<Witness>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cell {
+    pub column: Column,
+    pub row: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+    A,
+    B,
+    C,
+    Instance,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Error)]
+pub enum PlonkishError {
+    #[error("Opcode {index} cannot be expressed as a PLONK gate: {opcode}")]
+    UnsupportedOpcode { index: usize, opcode: String },
+    #[error(
+        "Opcode {index} does not fit in a single PLONK gate, compile with an expression width of 3"
+    )]
+    ExpressionTooWide { index: usize },
+}
+
+/// Returns the PLONKish table of `circuit`.
+pub fn circuit_to_plonkish(circuit: &Circuit) -> Result<PlonkishCircuit, PlonkishError> {
+    let mut plonkish = PlonkishCircuit {
+        current_witness_index: circuit.current_witness_index,
+        ..Default::default()
+    };
+    let mut cells: BTreeMap<Witness, Vec<Cell>> = BTreeMap::new();
+
+    for (index, opcode) in circuit.opcodes.iter().enumerate() {
+        match opcode {
+            Opcode::AssertZero(expression) => {
+                let gate =
+                    Gate::new(expression).ok_or(PlonkishError::ExpressionTooWide { index })?;
+                let row = plonkish.fixed.q_m.len();
+                for (column, witness) in
+                    [(Column::A, gate.a), (Column::B, gate.b), (Column::C, gate.c)]
+                {
+                    if let Some(witness) = witness {
+                        cells.entry(witness).or_default().push(Cell { column, row });
+                    }
+                }
+                plonkish.push_gate(gate);
+            }
+            Opcode::Directive(_) | Opcode::Brillig(_) | Opcode::BrilligCall { .. } => (),
+            Opcode::BlackBoxFuncCall(_)
+            | Opcode::MemoryOp { .. }
+            | Opcode::MemoryInit { .. }
+            | Opcode::Call { .. } => {
+                return Err(PlonkishError::UnsupportedOpcode { index, opcode: opcode.to_string() });
+            }
+        }
+    }
+
+    for (row, witness) in circuit.public_inputs().0.into_iter().enumerate() {
+        cells.entry(witness).or_default().push(Cell { column: Column::Instance, row });
+        plonkish.instance.push(witness);
+    }
+    plonkish.copy_constraints = cells.into_values().filter(|cells| cells.len() > 1).collect();
+    Ok(plonkish)
+}
+
+impl PlonkishCircuit {
+    fn push_gate(&mut self, gate: Gate) {
+        self.fixed.q_m.push(gate.q_m);
+        self.fixed.q_l.push(gate.q_l);
+        self.fixed.q_r.push(gate.q_r);
+        self.fixed.q_o.push(gate.q_o);
+        self.fixed.q_c.push(gate.q_c);
+        self.advice.a.push(gate.a);
+        self.advice.b.push(gate.b);
+        self.advice.c.push(gate.c);
+    }
+}
+
+/// A single row of the table.
+struct Gate {
+    q_m: FieldElement,
+    q_l: FieldElement,
+    q_r: FieldElement,
+    q_o: FieldElement,
+    q_c: FieldElement,
+    a: Option<Witness>,
+    b: Option<Witness>,
+    c: Option<Witness>,
+}
+
+impl Gate {
+    /// Returns the gate asserting that `expression` is zero, if it fits in one.
+    fn new(expression: &Expression) -> Option<Gate> {
+        let zero = FieldElement::zero();
+        let mut gate = Gate {
+            q_m: zero,
+            q_l: zero,
+            q_r: zero,
+            q_o: zero,
+            q_c: expression.q_c,
+            a: None,
+            b: None,
+            c: None,
+        };
+        match expression.mul_terms.as_slice() {
+            [] => (),
+            [(q_m, lhs, rhs)] => {
+                gate.q_m = *q_m;
+                gate.a = Some(*lhs);
+                gate.b = Some(*rhs);
+            }
+            _ => return None,
+        }
+        for (coefficient, witness) in &expression.linear_combinations {
+            let witness = Some(*witness);
+            let slot = if gate.a.is_none() || gate.a == witness {
+                (&mut gate.a, &mut gate.q_l)
+            } else if gate.b.is_none() || gate.b == witness {
+                (&mut gate.b, &mut gate.q_r)
+            } else if gate.c.is_none() || gate.c == witness {
+                (&mut gate.c, &mut gate.q_o)
+            } else {
+                return None;
+            };
+            *slot.0 = witness;
+            *slot.1 += *coefficient;
+        }
+        Some(gate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::{
+        acir::{
+            circuit::{opcodes::BlockId, Circuit, Opcode, PublicInputs},
+            native_types::{Expression, Witness},
+        },
+        FieldElement,
+    };
+
+    use super::{circuit_to_plonkish, Cell, Column, PlonkishError};
+
+    fn cell(column: Column, row: usize) -> Cell {
+        Cell { column, row }
+    }
+
+    #[test]
+    fn lowers_each_assertion_to_a_gate_and_links_shared_witnesses() {
+        let one = FieldElement::one();
+        // w1 * w2 - w3 = 0 and w3 + w3 - w4 + 5 = 0, with w4 returned.
+        let circuit = Circuit {
+            current_witness_index: 4,
+            opcodes: vec![
+                Opcode::AssertZero(Expression {
+                    mul_terms: vec![(one, Witness(1), Witness(2))],
+                    linear_combinations: vec![(-one, Witness(3))],
+                    q_c: FieldElement::zero(),
+                }),
+                Opcode::AssertZero(Expression {
+                    mul_terms: Vec::new(),
+                    linear_combinations: vec![
+                        (one, Witness(3)),
+                        (one, Witness(3)),
+                        (-one, Witness(4)),
+                    ],
+                    q_c: FieldElement::from(5_u128),
+                }),
+            ],
+            return_values: PublicInputs([Witness(4)].into()),
+            ..Circuit::default()
+        };
+
+        let plonkish = circuit_to_plonkish(&circuit).unwrap();
+        assert_eq!(plonkish.fixed.q_m, [one, FieldElement::zero()]);
+        assert_eq!(plonkish.fixed.q_l, [FieldElement::zero(), FieldElement::from(2_u128)]);
+        assert_eq!(plonkish.fixed.q_r, [FieldElement::zero(), -one]);
+        assert_eq!(plonkish.fixed.q_o, [-one, FieldElement::zero()]);
+        assert_eq!(plonkish.fixed.q_c, [FieldElement::zero(), FieldElement::from(5_u128)]);
+        assert_eq!(plonkish.advice.a, [Some(Witness(1)), Some(Witness(3))]);
+        assert_eq!(plonkish.advice.b, [Some(Witness(2)), Some(Witness(4))]);
+        assert_eq!(plonkish.advice.c, [Some(Witness(3)), None]);
+        assert_eq!(plonkish.instance, [Witness(4)]);
+        assert_eq!(
+            plonkish.copy_constraints,
+            [
+                vec![cell(Column::C, 0), cell(Column::A, 1)],
+                vec![cell(Column::B, 1), cell(Column::Instance, 0)],
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_opcodes_without_a_gate() {
+        let too_wide = Expression {
+            mul_terms: Vec::new(),
+            linear_combinations: (1..=4).map(|i| (FieldElement::one(), Witness(i))).collect(),
+            q_c: FieldElement::zero(),
+        };
+        let circuit = Circuit { opcodes: vec![Opcode::AssertZero(too_wide)], ..Circuit::default() };
+        assert_eq!(
+            circuit_to_plonkish(&circuit),
+            Err(PlonkishError::ExpressionTooWide { index: 0 })
+        );
+
+        let memory = Opcode::MemoryInit { block_id: BlockId(0), init: Vec::new() };
+        let circuit = Circuit { opcodes: vec![memory], ..Circuit::default() };
+        assert!(matches!(
+            circuit_to_plonkish(&circuit),
+            Err(PlonkishError::UnsupportedOpcode { index: 0, .. })
+        ));
+    }
+}
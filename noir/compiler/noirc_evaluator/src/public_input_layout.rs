@@ -0,0 +1,400 @@
+//! By default the public inputs of a circuit are ordered by witness index, which follows the order
+//! in which ACIR generation happened to create them. Verifiers which consume public inputs
+//! directly (e.g. on-chain) often expect a specific order instead, so a [`PublicInputLayout`]
+//! lets the order be given in terms of the ABI, and small values to be packed into a single
+//! public input to reduce the number of them.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use acvm::{
+    acir::native_types::{Expression, Witness},
+    FieldElement,
+};
+use noirc_errors::Location;
+use noirc_frontend::monomorphization::ast::Type;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    errors::RuntimeError,
+    ssa::{ir::dfg::CallStack, GeneratedAcir},
+};
+
+/// The name by which a layout refers to the return value of `main`.
+pub const RETURN_VALUE_NAME: &str = "return";
+
+/// The order in which the public values of `main` should appear in the circuit's public inputs.
+///
+/// Public values which are not mentioned keep their relative order and are placed after those
+/// which are. For example `{ "order": ["root", { "pack": ["index", "flags"] }, "return"] }`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PublicInputLayout {
+    pub order: Vec<PublicInputEntry>,
+    /// The number of bits which may be packed into a single public input. Defaults to the number
+    /// of bits which always fit into a field element, but may be lowered for verifiers which
+    /// read public inputs into smaller words.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_packed_bits: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PublicInputEntry {
+    /// A public parameter of `main` by name, or its return value as [`RETURN_VALUE_NAME`].
+    /// Each field element of the value becomes a public input.
+    Value(String),
+    /// Values whose field elements are packed into as few public inputs as possible.
+    ///
+    /// Elements are packed least significant bits first and a new public input is started
+    /// whenever the next element does not fit, so only integers and booleans may be packed.
+    /// The return value may not be packed together with parameters.
+    Packed { pack: Vec<String> },
+}
+
+impl PublicInputLayout {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn from_file(path: &Path) -> std::io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(Self::from_json(&json)?)
+    }
+
+    fn packed_bits(&self) -> Result<u32, RuntimeError> {
+        let capacity = FieldElement::max_num_bits() - 1;
+        match self.max_packed_bits {
+            Some(max_packed_bits) if max_packed_bits > capacity => Err(layout_error(
+                format!("At most {capacity} bits can be packed into a public input"),
+                None,
+            )),
+            Some(max_packed_bits) => Ok(max_packed_bits),
+            None => Ok(capacity),
+        }
+    }
+}
+
+/// A parameter or the return value of `main`, as seen by a [`PublicInputLayout`].
+#[derive(Debug, Clone)]
+pub(crate) struct MainValue {
+    pub(crate) name: String,
+    pub(crate) is_public: bool,
+    pub(crate) is_return: bool,
+    pub(crate) location: Option<Location>,
+    /// The bit size of each field element of the value, or `None` for a `Field`.
+    pub(crate) bit_sizes: Vec<Option<u32>>,
+}
+
+impl MainValue {
+    pub(crate) fn new(
+        name: String,
+        typ: &Type,
+        is_public: bool,
+        is_return: bool,
+        location: Option<Location>,
+    ) -> Self {
+        let mut bit_sizes = Vec::new();
+        collect_bit_sizes(typ, &mut bit_sizes);
+        MainValue { name, is_public, is_return, location, bit_sizes }
+    }
+}
+
+fn collect_bit_sizes(typ: &Type, bit_sizes: &mut Vec<Option<u32>>) {
+    match typ {
+        Type::Field => bit_sizes.push(None),
+        Type::Integer(_, bit_size) => bit_sizes.push(Some(*bit_size)),
+        Type::Bool => bit_sizes.push(Some(1)),
+        Type::String(length) => bit_sizes.extend((0..*length).map(|_| Some(8))),
+        Type::Array(length, element) => {
+            for _ in 0..*length {
+                collect_bit_sizes(element, bit_sizes);
+            }
+        }
+        Type::Tuple(fields) => {
+            for field in fields {
+                collect_bit_sizes(field, bit_sizes);
+            }
+        }
+        Type::FmtString(..)
+        | Type::Unit
+        | Type::Slice(_)
+        | Type::MutableReference(_)
+        | Type::Function(..) => (),
+    }
+}
+
+/// A field element of a parameter or the return value of `main` which makes up (part of) a public
+/// input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PublicInputElement {
+    /// Index of the parameter of `main`, or the number of parameters for its return value.
+    pub value: usize,
+    /// Index of the field element within the value once it has been flattened as in the ABI.
+    pub element: usize,
+    /// The bit at which the element starts within the public input, which is only non-zero for
+    /// packed public inputs.
+    pub bit_offset: u32,
+}
+
+/// The elements making up each public input, keyed by the witness of the public input.
+pub type PublicInputElements = BTreeMap<Witness, Vec<PublicInputElement>>;
+
+/// The public inputs of a circuit once a [`PublicInputLayout`] has been applied.
+#[derive(Debug, Default)]
+pub(crate) struct LaidOutPublicInputs {
+    pub(crate) public_parameters: BTreeSet<Witness>,
+    pub(crate) return_values: BTreeSet<Witness>,
+    /// Witnesses of public parameters which are now only constrained against public inputs.
+    pub(crate) private_parameters: BTreeSet<Witness>,
+    pub(crate) elements: PublicInputElements,
+}
+
+impl LaidOutPublicInputs {
+    fn insert(
+        &mut self,
+        public_input: Witness,
+        is_return: bool,
+        elements: Vec<PublicInputElement>,
+    ) {
+        if is_return {
+            self.return_values.insert(public_input);
+        } else {
+            self.public_parameters.insert(public_input);
+        }
+        self.elements.insert(public_input, elements);
+    }
+}
+
+/// Creates a fresh witness for every public input, constrained to be equal to the value (or the
+/// packed values) it stands for.
+///
+/// As witnesses are created in the order given by `layout`, ordering the public inputs by
+/// witness index follows the layout. `witnesses` holds the witnesses of each of `values`.
+pub(crate) fn apply_layout(
+    layout: &PublicInputLayout,
+    acir: &mut GeneratedAcir,
+    values: &[MainValue],
+    witnesses: &[Vec<Witness>],
+) -> Result<LaidOutPublicInputs, RuntimeError> {
+    let max_packed_bits = layout.packed_bits()?;
+
+    let mut placed = vec![false; values.len()];
+    let mut groups = Vec::new();
+    for entry in &layout.order {
+        let (names, packed) = match entry {
+            PublicInputEntry::Value(name) => (std::slice::from_ref(name), false),
+            PublicInputEntry::Packed { pack } => (pack.as_slice(), true),
+        };
+        let mut group: Vec<usize> = Vec::with_capacity(names.len());
+        for name in names {
+            let Some(index) = values.iter().position(|value| &value.name == name) else {
+                return Err(layout_error(format!("`{name}` is not a parameter of main"), None));
+            };
+            let value = &values[index];
+            if !value.is_public {
+                let message = format!("`{name}` is private so cannot be a public input");
+                return Err(layout_error(message, value.location));
+            }
+            if std::mem::replace(&mut placed[index], true) {
+                let message = format!("`{name}` appears more than once in the public input layout");
+                return Err(layout_error(message, value.location));
+            }
+            // A public input is either part of the parameters or of the return value of a circuit.
+            if group.first().map_or(false, |first| values[*first].is_return != value.is_return) {
+                let message = "The return value cannot be packed together with parameters of main";
+                return Err(layout_error(message.to_string(), value.location));
+            }
+            group.push(index);
+        }
+        groups.push((group, packed));
+    }
+    let unplaced = (0..values.len()).filter(|index| values[*index].is_public && !placed[*index]);
+    groups.extend(unplaced.map(|index| (vec![index], false)));
+
+    // Any call stack left over from ACIR generation does not apply to these constraints.
+    acir.call_stack = CallStack::new();
+
+    let mut laid_out = LaidOutPublicInputs::default();
+    for (group, packed) in groups {
+        let is_return = group.iter().all(|index| values[*index].is_return);
+        if !packed {
+            for value in group {
+                for (element, witness) in witnesses[value].iter().enumerate() {
+                    let public_input = acir.create_witness_for_expression(&(*witness).into());
+                    let elements = vec![PublicInputElement { value, element, bit_offset: 0 }];
+                    laid_out.insert(public_input, is_return, elements);
+                }
+            }
+            continue;
+        }
+
+        let mut packed_value = Expression::default();
+        let mut packed_elements = Vec::new();
+        let mut offset = 0;
+        for value in group {
+            let main_value = &values[value];
+            let value_witnesses = witnesses[value].iter().zip(&main_value.bit_sizes);
+            for (element, (witness, bit_size)) in value_witnesses.enumerate() {
+                let bit_size = match bit_size {
+                    Some(bit_size) if *bit_size <= max_packed_bits => *bit_size,
+                    _ => {
+                        let message = format!(
+                            "`{}` does not fit into {max_packed_bits} bits so cannot be packed",
+                            main_value.name
+                        );
+                        return Err(layout_error(message, main_value.location));
+                    }
+                };
+                if offset + bit_size > max_packed_bits {
+                    let public_input = acir.create_witness_for_expression(&packed_value);
+                    laid_out.insert(public_input, is_return, std::mem::take(&mut packed_elements));
+                    packed_value = Expression::default();
+                    offset = 0;
+                }
+                let shift = FieldElement::from(2_u128).pow(&FieldElement::from(offset as u128));
+                packed_value.push_addition_term(shift, *witness);
+                packed_elements.push(PublicInputElement { value, element, bit_offset: offset });
+                offset += bit_size;
+            }
+        }
+        if !packed_elements.is_empty() {
+            let public_input = acir.create_witness_for_expression(&packed_value);
+            laid_out.insert(public_input, is_return, packed_elements);
+        }
+    }
+
+    for (value, witnesses) in values.iter().zip(witnesses) {
+        if value.is_public && !value.is_return {
+            laid_out.private_parameters.extend(witnesses);
+        }
+    }
+    Ok(laid_out)
+}
+
+fn layout_error(message: String, location: Option<Location>) -> RuntimeError {
+    RuntimeError::InvalidPublicInputLayout { message, call_stack: location.into_iter().collect() }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::{
+        circuit::Opcode,
+        native_types::{Expression, Witness},
+    };
+    use acvm::FieldElement;
+    use noirc_frontend::{monomorphization::ast::Type, Signedness};
+
+    use super::{
+        apply_layout, MainValue, PublicInputElement, PublicInputLayout, RETURN_VALUE_NAME,
+    };
+    use crate::{errors::RuntimeError, ssa::GeneratedAcir};
+
+    fn main_values() -> (Vec<MainValue>, Vec<Vec<Witness>>) {
+        let byte = Type::Integer(Signedness::Unsigned, 8);
+        let values = vec![
+            MainValue::new("a".to_string(), &Type::Field, true, false, None),
+            MainValue::new(
+                "b".to_string(),
+                &Type::Array(2, Box::new(byte.clone())),
+                true,
+                false,
+                None,
+            ),
+            MainValue::new("c".to_string(), &byte, false, false, None),
+            MainValue::new(RETURN_VALUE_NAME.to_string(), &Type::Bool, true, true, None),
+        ];
+        let witnesses = vec![
+            vec![Witness(1)],
+            vec![Witness(2), Witness(3)],
+            vec![Witness(4)],
+            vec![Witness(5)],
+        ];
+        (values, witnesses)
+    }
+
+    fn constrained_value(opcodes: &[Opcode], public_input: Witness) -> Expression {
+        opcodes
+            .iter()
+            .find_map(|opcode| match opcode {
+                Opcode::AssertZero(expr)
+                    if expr.linear_combinations.contains(&(-FieldElement::one(), public_input)) =>
+                {
+                    Some(expr + public_input)
+                }
+                _ => None,
+            })
+            .expect("public input should be constrained")
+    }
+
+    #[test]
+    fn reorders_and_packs_public_inputs() {
+        let (values, witnesses) = main_values();
+        let mut acir = GeneratedAcir::default();
+        acir.reserve_witnesses(Witness(5));
+        let layout = PublicInputLayout::from_json(
+            r#"{ "order": [{ "pack": ["b"] }, "a", { "pack": ["return"] }] }"#,
+        )
+        .unwrap();
+
+        let laid_out = apply_layout(&layout, &mut acir, &values, &witnesses).unwrap();
+
+        let public_parameters: Vec<_> = laid_out.public_parameters.iter().copied().collect();
+        assert_eq!(public_parameters, vec![Witness(6), Witness(7)]);
+        assert_eq!(laid_out.return_values, [Witness(8)].into());
+        assert_eq!(laid_out.private_parameters, [Witness(1), Witness(2), Witness(3)].into());
+        assert_eq!(
+            laid_out.elements[&Witness(6)],
+            vec![
+                PublicInputElement { value: 1, element: 0, bit_offset: 0 },
+                PublicInputElement { value: 1, element: 1, bit_offset: 8 },
+            ]
+        );
+        assert_eq!(
+            laid_out.elements[&Witness(8)],
+            vec![PublicInputElement { value: 3, element: 0, bit_offset: 0 }]
+        );
+
+        let mut packed = Expression::from(Witness(2));
+        packed.push_addition_term(FieldElement::from(256_u128), Witness(3));
+        let opcodes = acir.take_opcodes();
+        assert_eq!(constrained_value(&opcodes, Witness(6)), packed);
+        assert_eq!(constrained_value(&opcodes, Witness(7)), Expression::from(Witness(1)));
+        assert_eq!(constrained_value(&opcodes, Witness(8)), Expression::from(Witness(5)));
+    }
+
+    #[test]
+    fn starts_a_new_public_input_when_full() {
+        let (values, witnesses) = main_values();
+        let mut acir = GeneratedAcir::default();
+        acir.reserve_witnesses(Witness(5));
+        let layout = PublicInputLayout::from_json(
+            r#"{ "order": [{ "pack": ["b"] }], "max_packed_bits": 12 }"#,
+        )
+        .unwrap();
+
+        let laid_out = apply_layout(&layout, &mut acir, &values, &witnesses).unwrap();
+
+        // `b` is split over two public inputs, followed by `a` and then the return value.
+        let public_parameters: Vec<_> = laid_out.public_parameters.iter().copied().collect();
+        assert_eq!(public_parameters, vec![Witness(6), Witness(7), Witness(8)]);
+        assert_eq!(laid_out.return_values, [Witness(9)].into());
+    }
+
+    #[test]
+    fn rejects_invalid_layouts() {
+        let (values, witnesses) = main_values();
+        for layout in [
+            r#"{ "order": ["d"] }"#,
+            r#"{ "order": ["c"] }"#,
+            r#"{ "order": ["a", "a"] }"#,
+            r#"{ "order": [{ "pack": ["a"] }] }"#,
+            r#"{ "order": [{ "pack": ["b", "return"] }] }"#,
+        ] {
+            let layout = PublicInputLayout::from_json(layout).unwrap();
+            let mut acir = GeneratedAcir::default();
+            let result = apply_layout(&layout, &mut acir, &values, &witnesses);
+            assert!(matches!(result, Err(RuntimeError::InvalidPublicInputLayout { .. })));
+        }
+    }
+}
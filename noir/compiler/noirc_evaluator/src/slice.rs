@@ -0,0 +1,325 @@
+//! Proving a whole circuit to check a change to one part of it is slow. Slicing a circuit keeps
+//! only the opcodes which are needed to solve and check selected [`SliceTarget`]s, so that a much
+//! smaller circuit can be proven while iterating on them.
+//!
+//! The slice is taken backwards over the witness graph: each witness needs the opcode which
+//! solves for it, and each opcode needs the witnesses which it reads.
+use std::{
+    collections::{BTreeSet, HashMap},
+    convert::Infallible,
+    fmt::Display,
+    str::FromStr,
+};
+
+use acvm::acir::{
+    circuit::{
+        brillig::{Brillig, BrilligInputs, BrilligOutputs},
+        directives::Directive,
+        opcodes::{BlockId, MemOp},
+        Opcode, OpcodeLocation,
+    },
+    native_types::{Expression, Witness},
+};
+
+use crate::{errors::RuntimeError, public_input_layout::RETURN_VALUE_NAME, ssa::GeneratedAcir};
+
+/// A part of a circuit to keep when slicing it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SliceTarget {
+    /// The return value of `main`, given as [`RETURN_VALUE_NAME`].
+    ReturnValue,
+    /// Every assertion with this message.
+    Assertion(String),
+}
+
+impl FromStr for SliceTarget {
+    type Err = Infallible;
+
+    fn from_str(target: &str) -> Result<Self, Self::Err> {
+        if target == RETURN_VALUE_NAME {
+            Ok(SliceTarget::ReturnValue)
+        } else {
+            Ok(SliceTarget::Assertion(target.to_owned()))
+        }
+    }
+}
+
+impl Display for SliceTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SliceTarget::ReturnValue => write!(f, "{RETURN_VALUE_NAME}"),
+            SliceTarget::Assertion(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+/// The witnesses which an opcode reads and those which it solves for.
+#[derive(Debug, Default)]
+pub(crate) struct Dependencies {
+    reads: BTreeSet<Witness>,
+    pub(crate) solves: BTreeSet<Witness>,
+    /// Memory blocks whose contents the opcode reads.
+    blocks: Vec<BlockId>,
+}
+
+/// Removes every opcode of `acir` which is not needed to check the assertions selected by
+/// `targets`, or to solve for the `required` witnesses (and the return value, if targeted).
+///
+/// The opcodes are assumed to be in the order in which the ACVM solves them, starting from the
+/// input witnesses of `acir`.
+pub(crate) fn slice(
+    acir: &mut GeneratedAcir,
+    targets: &[SliceTarget],
+    required: impl IntoIterator<Item = Witness>,
+) -> Result<(), RuntimeError> {
+    let mut needed_opcodes = BTreeSet::new();
+    let mut needed_witnesses: Vec<Witness> = required.into_iter().collect();
+    for target in targets {
+        match target {
+            SliceTarget::ReturnValue => needed_witnesses.extend(&acir.return_witnesses),
+            SliceTarget::Assertion(message) => {
+                let assertions: Vec<_> = acir
+                    .assert_messages
+                    .iter()
+                    .filter(|(_, assert_message)| assert_message.to_string() == *message)
+                    .map(|(location, _)| match location {
+                        OpcodeLocation::Acir(index)
+                        | OpcodeLocation::Brillig { acir_index: index, .. } => *index,
+                    })
+                    .collect();
+                if assertions.is_empty() {
+                    return Err(RuntimeError::UnknownSliceTarget {
+                        target: target.to_string(),
+                        call_stack: Default::default(),
+                    });
+                }
+                needed_opcodes.extend(assertions);
+            }
+        }
+    }
+
+    let dependencies = dependencies(acir.opcodes(), &acir.input_witnesses);
+    let mut solved_by = HashMap::new();
+    let mut block_writes: HashMap<BlockId, Vec<usize>> = HashMap::new();
+    for (index, (opcode, dependencies)) in acir.opcodes().iter().zip(&dependencies).enumerate() {
+        solved_by.extend(dependencies.solves.iter().map(|witness| (*witness, index)));
+        let written_block = match opcode {
+            Opcode::MemoryInit { block_id, .. } => Some(block_id),
+            Opcode::MemoryOp { block_id, op, .. } if !is_read(op) => Some(block_id),
+            _ => None,
+        };
+        if let Some(block_id) = written_block {
+            block_writes.entry(*block_id).or_default().push(index);
+        }
+    }
+
+    // Opcodes which have been reached but whose own dependencies have not been added yet.
+    let mut pending: Vec<usize> = needed_opcodes.iter().copied().collect();
+    loop {
+        for witness in needed_witnesses.drain(..) {
+            if let Some(index) = solved_by.get(&witness) {
+                if needed_opcodes.insert(*index) {
+                    pending.push(*index);
+                }
+            }
+        }
+        let Some(index) = pending.pop() else { break };
+
+        needed_witnesses.extend(&dependencies[index].reads);
+        for block_id in &dependencies[index].blocks {
+            let writes = block_writes.get(block_id).into_iter().flatten();
+            let earlier_writes = writes.take_while(|write| **write < index);
+            for write in earlier_writes {
+                if needed_opcodes.insert(*write) {
+                    pending.push(*write);
+                }
+            }
+        }
+    }
+
+    // Constraints which solve for nothing are kept if they only involve witnesses of the slice,
+    // as they still restrict the values which those witnesses may take.
+    let reached: BTreeSet<Witness> = needed_opcodes
+        .iter()
+        .flat_map(|index| dependencies[*index].reads.iter().chain(&dependencies[*index].solves))
+        .copied()
+        .collect();
+    let constraints = acir.opcodes().iter().zip(&dependencies).enumerate().filter(
+        |(_, (opcode, dependencies))| {
+            matches!(opcode, Opcode::AssertZero(_) | Opcode::BlackBoxFuncCall(_))
+                && dependencies.solves.is_empty()
+                && dependencies.reads.is_subset(&reached)
+        },
+    );
+    let constraints: Vec<usize> = constraints.map(|(index, _)| index).collect();
+    needed_opcodes.extend(constraints);
+
+    acir.retain_opcodes(&needed_opcodes);
+    Ok(())
+}
+
+/// Returns the dependencies of each of `opcodes`, when solved in order from `inputs`.
+pub(crate) fn dependencies(opcodes: &[Opcode], inputs: &[Witness]) -> Vec<Dependencies> {
+    let mut solved: BTreeSet<Witness> = inputs.iter().copied().collect();
+    opcodes
+        .iter()
+        .map(|opcode| {
+            let mut dependencies = Dependencies::default();
+            match opcode {
+                Opcode::AssertZero(expression) => {
+                    // The ACVM solves for the only witness of the expression which is not known yet.
+                    for witness in expression_witnesses(expression) {
+                        if solved.contains(&witness) {
+                            dependencies.reads.insert(witness);
+                        } else {
+                            dependencies.solves.insert(witness);
+                        }
+                    }
+                }
+                Opcode::BlackBoxFuncCall(call) => {
+                    let inputs = call.get_inputs_vec();
+                    dependencies.reads.extend(inputs.iter().map(|input| input.witness));
+                    dependencies.solves.extend(call.get_outputs_vec());
+                }
+                Opcode::Directive(Directive::ToLeRadix { a, b, .. }) => {
+                    dependencies.reads.extend(expression_witnesses(a));
+                    dependencies.solves.extend(b);
+                }
+                Opcode::Directive(Directive::PermutationSort { inputs, bits, .. }) => {
+                    let inputs = inputs.iter().flatten().flat_map(expression_witnesses);
+                    dependencies.reads.extend(inputs);
+                    dependencies.solves.extend(bits);
+                }
+                Opcode::Brillig(Brillig { inputs, outputs, predicate, .. })
+                | Opcode::BrilligCall { inputs, outputs, predicate, .. } => {
+                    for input in inputs {
+                        match input {
+                            BrilligInputs::Single(expression) => {
+                                dependencies.reads.extend(expression_witnesses(expression));
+                            }
+                            BrilligInputs::Array(expressions) => {
+                                let inputs = expressions.iter().flat_map(expression_witnesses);
+                                dependencies.reads.extend(inputs);
+                            }
+                            BrilligInputs::MemoryArray(block_id) => {
+                                dependencies.blocks.push(*block_id);
+                            }
+                        }
+                    }
+                    for output in outputs {
+                        match output {
+                            BrilligOutputs::Simple(witness) => {
+                                dependencies.solves.insert(*witness);
+                            }
+                            BrilligOutputs::Array(witnesses) => {
+                                dependencies.solves.extend(witnesses);
+                            }
+                        }
+                    }
+                    let predicate = predicate.iter().flat_map(expression_witnesses);
+                    dependencies.reads.extend(predicate);
+                }
+                Opcode::MemoryInit { init, .. } => dependencies.reads.extend(init),
+                Opcode::Call { inputs, outputs, predicate, .. } => {
+                    dependencies.reads.extend(inputs);
+                    dependencies.reads.extend(predicate.iter().flat_map(expression_witnesses));
+                    dependencies.solves.extend(outputs);
+                }
+                Opcode::MemoryOp { block_id, op, predicate } => {
+                    dependencies.reads.extend(expression_witnesses(&op.index));
+                    let predicate = predicate.iter().flat_map(expression_witnesses);
+                    dependencies.reads.extend(predicate);
+                    if is_read(op) {
+                        dependencies.solves.extend(expression_witnesses(&op.value));
+                        dependencies.blocks.push(*block_id);
+                    } else {
+                        dependencies.reads.extend(expression_witnesses(&op.operation));
+                        dependencies.reads.extend(expression_witnesses(&op.value));
+                    }
+                }
+            }
+            solved.extend(&dependencies.solves);
+            dependencies
+        })
+        .collect()
+}
+
+fn is_read(op: &MemOp) -> bool {
+    op.operation.is_zero()
+}
+
+fn expression_witnesses(expression: &Expression) -> impl Iterator<Item = Witness> + '_ {
+    let mul_terms = expression.mul_terms.iter().flat_map(|(_, lhs, rhs)| [*lhs, *rhs]);
+    let linear_terms = expression.linear_combinations.iter().map(|(_, witness)| *witness);
+    mul_terms.chain(linear_terms)
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::{
+        acir::{
+            circuit::{Opcode, OpcodeLocation},
+            native_types::{Expression, Witness},
+        },
+        FieldElement,
+    };
+
+    use super::{slice, SliceTarget};
+    use crate::{errors::RuntimeError, ssa::GeneratedAcir};
+
+    /// Returns an expression constraining `output` to be the sum of `inputs`.
+    fn sum(inputs: &[Witness], output: Witness) -> Opcode {
+        let mut expression = Expression::default();
+        for input in inputs {
+            expression.push_addition_term(FieldElement::one(), *input);
+        }
+        expression.push_addition_term(-FieldElement::one(), output);
+        Opcode::AssertZero(expression)
+    }
+
+    /// Builds a circuit with inputs `w0` and `w1` where `w2 = w0 + w1` is checked by an assertion,
+    /// `w3 = w1 + w1` is returned and `w0` is range constrained.
+    fn circuit() -> GeneratedAcir {
+        let mut acir = GeneratedAcir::default();
+        acir.reserve_witnesses(Witness(3));
+        acir.input_witnesses = vec![Witness(0), Witness(1)];
+        acir.push_opcode(sum(&[Witness(0), Witness(1)], Witness(2)));
+        acir.push_opcode(sum(&[Witness(1), Witness(1)], Witness(3)));
+        acir.push_opcode(Opcode::AssertZero(Expression::from(Witness(2))));
+        acir.assert_messages.insert(OpcodeLocation::Acir(2), "sum is zero".into());
+        acir.range_constraint(Witness(0), 8).unwrap();
+        acir.return_witnesses = vec![Witness(3)];
+        acir
+    }
+
+    #[test]
+    fn keeps_the_opcodes_needed_by_an_assertion() {
+        let mut acir = circuit();
+        slice(&mut acir, &["sum is zero".parse().unwrap()], []).unwrap();
+
+        // The range constraint of `w0` is kept as `w0` is part of the slice.
+        let opcodes = acir.take_opcodes();
+        assert_eq!(opcodes.len(), 3);
+        assert_eq!(opcodes[0], sum(&[Witness(0), Witness(1)], Witness(2)));
+        assert_eq!(acir.assert_messages[&OpcodeLocation::Acir(1)], "sum is zero".into());
+    }
+
+    #[test]
+    fn keeps_the_opcodes_needed_by_the_return_value() {
+        let mut acir = circuit();
+        slice(&mut acir, &[SliceTarget::ReturnValue], []).unwrap();
+
+        let mut double = Expression::default();
+        double.push_addition_term(FieldElement::from(2_u128), Witness(1));
+        double.push_addition_term(-FieldElement::one(), Witness(3));
+        assert_eq!(acir.take_opcodes(), vec![Opcode::AssertZero(double)]);
+        assert!(acir.assert_messages.is_empty());
+    }
+
+    #[test]
+    fn rejects_unknown_assertions() {
+        let mut acir = circuit();
+        let result = slice(&mut acir, &["sum is one".parse().unwrap()], []);
+        assert!(matches!(result, Err(RuntimeError::UnknownSliceTarget { .. })));
+    }
+}
@@ -0,0 +1,413 @@
+//! Translates a [`Circuit`] into SMT-LIB, so that formal verification tools can reason about its
+//! constraints, e.g. to check that two versions of the compiler produce equivalent circuits for
+//! the same program.
+//!
+//! Witnesses are declared as constants of a finite field sort (as supported by cvc5), each
+//! `AssertZero` opcode becomes a polynomial equality and each black box function or call to another
+//! circuit becomes an uninterpreted function of its inputs. Memory blocks become arrays, with a new array for each
+//! write. Brillig calls and directives only compute hints for the solver, so the witnesses which
+//! they solve for are left unconstrained.
+//!
+//! No `(check-sat)` command is emitted, so that the output can be combined with other assertions.
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt::Write,
+};
+
+use acvm::{
+    acir::{
+        circuit::{
+            opcodes::{BlackBoxFuncCall, BlockId, FunctionInput},
+            Circuit, Opcode,
+        },
+        native_types::{Expression, Witness},
+    },
+    FieldElement,
+};
+use num_bigint::BigUint;
+
+/// Returns the SMT-LIB script asserting the constraints of `circuit`.
+pub fn circuit_to_smt_lib(circuit: &Circuit) -> String {
+    let mut script = SmtLibScript::default();
+    script.witnesses.extend(&circuit.private_parameters);
+    script.witnesses.extend(circuit.public_inputs().0);
+    for opcode in &circuit.opcodes {
+        script.push_opcode(opcode);
+    }
+    script.finish(circuit)
+}
+
+#[derive(Default)]
+struct SmtLibScript {
+    witnesses: BTreeSet<Witness>,
+    /// The declarations of uninterpreted functions and sorts, keyed by their name.
+    functions: BTreeMap<String, String>,
+    /// Array constants holding the contents of memory blocks.
+    arrays: Vec<String>,
+    /// The array holding the current contents of each memory block.
+    memory: HashMap<BlockId, String>,
+    /// The constant holding the current value of each big integer.
+    big_ints: HashMap<u32, String>,
+    /// Number of constants declared to hold big integers.
+    num_big_ints: usize,
+    assertions: Vec<String>,
+}
+
+impl SmtLibScript {
+    fn push_opcode(&mut self, opcode: &Opcode) {
+        match opcode {
+            Opcode::AssertZero(expression) => {
+                let expression = self.expression(expression);
+                self.assertions.push(format!("(= {expression} {})", field(FieldElement::zero())));
+            }
+            Opcode::BlackBoxFuncCall(call) => self.push_black_box_call(call),
+            Opcode::Directive(_) | Opcode::Brillig(_) | Opcode::BrilligCall { .. } => (),
+            Opcode::MemoryInit { block_id, init } => {
+                let array = self.new_array(*block_id);
+                for (index, value) in init.iter().enumerate() {
+                    let index = field(FieldElement::from(index as u128));
+                    let value = self.witness(*value);
+                    self.assertions.push(format!("(= (select {array} {index}) {value})"));
+                }
+            }
+            Opcode::MemoryOp { block_id, op, predicate } => {
+                let array = self.memory.get(block_id).cloned().unwrap_or_else(|| {
+                    // Blocks are always initialized before they are used, but an uninitialized
+                    // block is still allowed to hold anything.
+                    self.new_array(*block_id)
+                });
+                let index = self.expression(&op.index);
+                let value = self.expression(&op.value);
+                let predicate = predicate.as_ref().map(|predicate| self.expression(predicate));
+                let zero = field(FieldElement::zero());
+                if op.operation.is_zero() {
+                    let read = format!("(= {value} (select {array} {index}))");
+                    self.assertions.push(match predicate {
+                        Some(predicate) => format!("(=> (distinct {predicate} {zero}) {read})"),
+                        None => read,
+                    });
+                } else {
+                    let written = format!("(store {array} {index} {value})");
+                    let written = match predicate {
+                        Some(predicate) => {
+                            format!("(ite (= {predicate} {zero}) {array} {written})")
+                        }
+                        None => written,
+                    };
+                    let new_array = self.new_array(*block_id);
+                    self.assertions.push(format!("(= {new_array} {written})"));
+                }
+            }
+            Opcode::Call { id, inputs, outputs, predicate } => {
+                let sorts = vec!["F"; inputs.len()];
+                let arguments: String =
+                    inputs.iter().map(|input| format!(" {}", self.witness(*input))).collect();
+                let predicate = predicate.as_ref().map(|predicate| self.expression(predicate));
+                let zero = field(FieldElement::zero());
+                for (index, output) in outputs.iter().enumerate() {
+                    let name = format!("call{id}_out{index}");
+                    self.declare_function(&name, &sorts, "F");
+                    let result =
+                        if inputs.is_empty() { name } else { format!("({name}{arguments})") };
+                    let result = match &predicate {
+                        Some(predicate) => format!("(ite (= {predicate} {zero}) {zero} {result})"),
+                        None => result,
+                    };
+                    let output = self.witness(*output);
+                    self.assertions.push(format!("(= {output} {result})"));
+                }
+            }
+        }
+    }
+
+    fn push_black_box_call(&mut self, call: &BlackBoxFuncCall) {
+        match call {
+            BlackBoxFuncCall::BigIntAdd { lhs, rhs, output }
+            | BlackBoxFuncCall::BigIntSub { lhs, rhs, output }
+            | BlackBoxFuncCall::BigIntMul { lhs, rhs, output }
+            | BlackBoxFuncCall::BigIntDiv { lhs, rhs, output } => {
+                let name = call.name().to_owned();
+                self.declare_big_int_sort();
+                self.declare_function(&name, &["BigInt", "BigInt"], "BigInt");
+                let lhs = self.big_int(*lhs);
+                let rhs = self.big_int(*rhs);
+                let output = self.new_big_int(*output);
+                self.assertions.push(format!("(= {output} ({name} {lhs} {rhs}))"));
+            }
+            BlackBoxFuncCall::BigIntFromLeBytes { inputs, modulus, output } => {
+                let modulus = BigUint::from_bytes_le(modulus);
+                let name = format!("{}{}_mod{modulus}", call.name(), input_sizes(inputs));
+                self.declare_big_int_sort();
+                self.declare_function(&name, &vec!["F"; inputs.len()], "BigInt");
+                let inputs = self.inputs(inputs);
+                let output = self.new_big_int(*output);
+                self.assertions.push(format!("(= {output} ({name}{inputs}))"));
+            }
+            BlackBoxFuncCall::BigIntToLeBytes { input, outputs } => {
+                self.declare_big_int_sort();
+                let input = self.big_int(*input);
+                for (index, output) in outputs.iter().enumerate() {
+                    let name = format!("{}_out{index}", call.name());
+                    self.declare_function(&name, &["BigInt"], "F");
+                    let output = self.witness(*output);
+                    self.assertions.push(format!("(= {output} ({name} {input}))"));
+                }
+            }
+            _ => {
+                let inputs = call.get_inputs_vec();
+                let mut name = format!("{}{}", call.name(), input_sizes(&inputs));
+                if let BlackBoxFuncCall::PedersenCommitment { domain_separator, .. }
+                | BlackBoxFuncCall::PedersenHash { domain_separator, .. } = call
+                {
+                    write!(name, "_domain{domain_separator}").unwrap();
+                }
+                let sorts = vec!["F"; inputs.len()];
+                let arguments = self.inputs(&inputs);
+                let outputs = call.get_outputs_vec();
+                if outputs.is_empty() {
+                    // Opcodes without outputs, such as range constraints, are predicates which
+                    // must hold of their inputs.
+                    self.declare_function(&name, &sorts, "Bool");
+                    self.assertions.push(format!("({name}{arguments})"));
+                }
+                for (index, output) in outputs.iter().enumerate() {
+                    let name = format!("{name}_out{index}");
+                    self.declare_function(&name, &sorts, "F");
+                    let output = self.witness(*output);
+                    self.assertions.push(format!("(= {output} ({name}{arguments}))"));
+                }
+            }
+        }
+    }
+
+    fn declare_function(&mut self, name: &str, arguments: &[&str], result: &str) {
+        self.functions
+            .entry(name.to_owned())
+            .or_insert_with(|| format!("(declare-fun {name} ({}) {result})", arguments.join(" ")));
+    }
+
+    fn declare_big_int_sort(&mut self) {
+        self.functions
+            .entry("BigInt".to_owned())
+            .or_insert_with(|| "(declare-sort BigInt 0)".into());
+    }
+
+    fn new_array(&mut self, block_id: BlockId) -> String {
+        let array = format!("block{}_{}", block_id.0, self.arrays.len());
+        self.arrays.push(array.clone());
+        self.memory.insert(block_id, array.clone());
+        array
+    }
+
+    fn big_int(&mut self, id: u32) -> String {
+        match self.big_ints.get(&id) {
+            Some(constant) => constant.clone(),
+            None => self.new_big_int(id),
+        }
+    }
+
+    fn new_big_int(&mut self, id: u32) -> String {
+        let constant = format!("bigint{id}_{}", self.num_big_ints);
+        self.num_big_ints += 1;
+        self.declare_function(&constant, &[], "BigInt");
+        self.big_ints.insert(id, constant.clone());
+        constant
+    }
+
+    fn witness(&mut self, witness: Witness) -> String {
+        self.witnesses.insert(witness);
+        witness_name(witness)
+    }
+
+    /// Returns the arguments of an application of a function to `inputs`, each preceded by a space.
+    fn inputs(&mut self, inputs: &[FunctionInput]) -> String {
+        inputs.iter().map(|input| format!(" {}", self.witness(input.witness))).collect()
+    }
+
+    fn expression(&mut self, expression: &Expression) -> String {
+        let mut terms = Vec::new();
+        for (coefficient, lhs, rhs) in &expression.mul_terms {
+            let (lhs, rhs) = (self.witness(*lhs), self.witness(*rhs));
+            terms.push(if coefficient.is_one() {
+                format!("(ff.mul {lhs} {rhs})")
+            } else {
+                format!("(ff.mul {} {lhs} {rhs})", field(*coefficient))
+            });
+        }
+        for (coefficient, witness) in &expression.linear_combinations {
+            let witness = self.witness(*witness);
+            terms.push(if coefficient.is_one() {
+                witness
+            } else {
+                format!("(ff.mul {} {witness})", field(*coefficient))
+            });
+        }
+        if !expression.q_c.is_zero() || terms.is_empty() {
+            terms.push(field(expression.q_c));
+        }
+        match &terms[..] {
+            [term] => term.clone(),
+            _ => format!("(ff.add {})", terms.join(" ")),
+        }
+    }
+
+    fn finish(self, circuit: &Circuit) -> String {
+        let mut script = String::new();
+        let mut line = |line: String| {
+            script.push_str(&line);
+            script.push('\n');
+        };
+        line("(set-info :smt-lib-version 2.6)".into());
+        line("(set-logic ALL)".into());
+        line(format!("(define-sort F () (_ FiniteField {}))", FieldElement::modulus()));
+        for function in self.functions.into_values() {
+            line(function);
+        }
+
+        let names = |witnesses: &mut dyn Iterator<Item = &Witness>| -> String {
+            witnesses.map(|witness| format!(" {}", witness_name(*witness))).collect()
+        };
+        line(format!("; private parameters:{}", names(&mut circuit.private_parameters.iter())));
+        line(format!("; public parameters:{}", names(&mut circuit.public_parameters.0.iter())));
+        line(format!("; return values:{}", names(&mut circuit.return_values.0.iter())));
+        for witness in self.witnesses {
+            line(format!("(declare-const {} F)", witness_name(witness)));
+        }
+        for array in self.arrays {
+            line(format!("(declare-const {array} (Array F F))"));
+        }
+        for assertion in self.assertions {
+            line(format!("(assert {assertion})"));
+        }
+        script
+    }
+}
+
+fn witness_name(witness: Witness) -> String {
+    format!("w{}", witness.0)
+}
+
+fn field(value: FieldElement) -> String {
+    format!("(as ff{} F)", BigUint::from_bytes_be(&value.to_be_bytes()))
+}
+
+/// Returns the bit sizes of `inputs` as a suffix of the name of a black box function, so that
+/// calls are only identified with each other if their inputs are interpreted the same way.
+///
+/// Runs of inputs with the same bit size are written as `_{count}x{num_bits}`.
+fn input_sizes(inputs: &[FunctionInput]) -> String {
+    let mut suffix = String::new();
+    let mut inputs = inputs.iter().peekable();
+    while let Some(input) = inputs.next() {
+        let mut count = 1;
+        while inputs.next_if(|next| next.num_bits == input.num_bits).is_some() {
+            count += 1;
+        }
+        write!(suffix, "_{count}x{}", input.num_bits).unwrap();
+    }
+    suffix
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use acvm::{
+        acir::{
+            circuit::{
+                opcodes::{BlackBoxFuncCall, BlockId, FunctionInput, MemOp},
+                Circuit, Opcode, PublicInputs,
+            },
+            native_types::{Expression, Witness},
+        },
+        FieldElement,
+    };
+
+    use super::circuit_to_smt_lib;
+
+    fn assertions(script: &str) -> Vec<&str> {
+        script.lines().filter(|line| line.starts_with("(assert")).collect()
+    }
+
+    #[test]
+    fn translates_polynomial_constraints() {
+        let mut expression = Expression::default();
+        expression.push_multiplication_term(FieldElement::from(2_u128), Witness(0), Witness(1));
+        expression.push_addition_term(-FieldElement::one(), Witness(2));
+        expression.q_c = FieldElement::from(5_u128);
+        let circuit = Circuit {
+            current_witness_index: 2,
+            opcodes: vec![Opcode::AssertZero(expression)],
+            private_parameters: BTreeSet::from([Witness(0), Witness(1)]),
+            return_values: PublicInputs(BTreeSet::from([Witness(2)])),
+            ..Circuit::default()
+        };
+
+        let script = circuit_to_smt_lib(&circuit);
+        let minus_one = FieldElement::modulus() - 1_u32;
+        assert!(script.contains("(declare-const w2 F)"));
+        assert!(script.contains("; return values: w2"));
+        assert_eq!(
+            assertions(&script),
+            [format!(
+                "(assert (= (ff.add (ff.mul (as ff2 F) w0 w1) (ff.mul (as ff{minus_one} F) w2) \
+                 (as ff5 F)) (as ff0 F)))"
+            )]
+        );
+    }
+
+    #[test]
+    fn translates_black_boxes_into_uninterpreted_functions() {
+        let input = |witness| FunctionInput { witness: Witness(witness), num_bits: 8 };
+        let circuit = Circuit {
+            opcodes: vec![
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { input: input(0) }),
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::AND {
+                    lhs: input(0),
+                    rhs: input(1),
+                    output: Witness(2),
+                }),
+            ],
+            ..Circuit::default()
+        };
+
+        let script = circuit_to_smt_lib(&circuit);
+        assert!(script.contains("(declare-fun range_1x8 (F) Bool)"));
+        assert!(script.contains("(declare-fun and_2x8_out0 (F F) F)"));
+        assert_eq!(
+            assertions(&script),
+            ["(assert (range_1x8 w0))", "(assert (= w2 (and_2x8_out0 w0 w1)))"]
+        );
+    }
+
+    #[test]
+    fn writes_to_memory_create_new_arrays() {
+        let block_id = BlockId(0);
+        let circuit = Circuit {
+            opcodes: vec![
+                Opcode::MemoryInit { block_id, init: vec![Witness(0)] },
+                Opcode::MemoryOp {
+                    block_id,
+                    op: MemOp::write_to_mem_index(FieldElement::zero().into(), Witness(1).into()),
+                    predicate: None,
+                },
+                Opcode::MemoryOp {
+                    block_id,
+                    op: MemOp::read_at_mem_index(FieldElement::zero().into(), Witness(2)),
+                    predicate: Some(Witness(3).into()),
+                },
+            ],
+            ..Circuit::default()
+        };
+
+        let script = circuit_to_smt_lib(&circuit);
+        assert_eq!(
+            assertions(&script),
+            [
+                "(assert (= (select block0_0 (as ff0 F)) w0))",
+                "(assert (= block0_1 (store block0_0 (as ff0 F) w1)))",
+                "(assert (=> (distinct w3 (as ff0 F)) (= w2 (select block0_1 (as ff0 F)))))",
+            ]
+        );
+    }
+}
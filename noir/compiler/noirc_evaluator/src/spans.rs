@@ -0,0 +1,40 @@
+//! Names of the `tracing` spans and events emitted while lowering a program.
+//!
+//! These names, their levels and their fields are stable, so that tooling can rely on them when
+//! filtering or post-processing logs. They are only recorded once a subscriber is installed,
+//! which `nargo` does through the `NOIR_LOG` environment filter. The target of each is the module
+//! it is emitted from, and filter directives match targets by prefix, so that the verbosity can
+//! be chosen per module:
+//!
+//! ```text
+//! NOIR_LOG=noirc_evaluator::ssa=debug,noirc_evaluator::ssa::acir_gen=trace nargo compile
+//! ```
+//!
+//! | Name                 | Kind  | Level   | Target                                                   | Fields            |
+//! |----------------------|-------|---------|----------------------------------------------------------|-------------------|
+//! | [`SSA_GENERATION`]   | span  | `TRACE` | `noirc_evaluator::ssa`                                   |                   |
+//! | [`SSA_PASS`]         | span  | `DEBUG` | `noirc_evaluator::ssa`                                   | `pass`            |
+//! | [`ACIR_FUNCTION`]    | span  | `DEBUG` | `noirc_evaluator::ssa::acir_gen`                         | `function`        |
+//! | [`ACIR_OPCODE`]      | event | `TRACE` | `noirc_evaluator::ssa::acir_gen::acir_ir::generated_acir` | `index`, `opcode` |
+//! | [`BRILLIG_FUNCTION`] | span  | `DEBUG` | `noirc_evaluator::brillig::brillig_gen`                  | `function`        |
+//! | [`BRILLIG_OPCODE`]   | event | `TRACE` | `noirc_evaluator::brillig::brillig_ir`                   | `index`, `opcode` |
+
+/// Covers the generation and optimization of the SSA of the program, and its conversion into
+/// Brillig.
+pub const SSA_GENERATION: &str = "ssa_generation";
+
+/// Covers a single run of an SSA pass, whose name is given by the `pass` field.
+pub const SSA_PASS: &str = "ssa_pass";
+
+/// Covers the conversion of the SSA of a function, named by the `function` field, into ACIR.
+pub const ACIR_FUNCTION: &str = "acir_function";
+
+/// Emitted for each ACIR opcode, as it is pushed at position `index` of the circuit.
+pub const ACIR_OPCODE: &str = "acir_opcode";
+
+/// Covers the conversion of the SSA of a function, named by the `function` field, into Brillig.
+pub const BRILLIG_FUNCTION: &str = "brillig_function";
+
+/// Emitted for each Brillig opcode, as it is pushed at position `index` of the bytecode of the
+/// function being converted.
+pub const BRILLIG_OPCODE: &str = "brillig_opcode";
@@ -7,204 +7,945 @@
 //! This module heavily borrows from Cranelift
 #![allow(dead_code)]
 
-use std::collections::BTreeSet;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use crate::{
-    brillig::Brillig,
+    brillig::{brillig_ir::BrilligLimits, Brillig, BrilligOptions},
+    cost_model::CostModel,
     errors::{RuntimeError, SsaReport},
+    public_input_layout::{
+        apply_layout, MainValue, PublicInputElement, PublicInputElements, PublicInputLayout,
+        RETURN_VALUE_NAME,
+    },
+    slice::{slice, SliceTarget},
+    spans,
 };
 use acvm::acir::{
-    circuit::{Circuit, ExpressionWidth, PublicInputs},
+    circuit::{Circuit, ExpressionWidth, Opcode, PublicInputs},
     native_types::Witness,
 };
 
-use noirc_errors::debug_info::DebugInfo;
+use iter_extended::vecmap;
+use noirc_errors::{debug_info::DebugInfo, Location};
 
-use noirc_frontend::{
-    hir_def::function::FunctionSignature, monomorphization::ast::Program, Visibility,
-};
+use noirc_frontend::{hir_def::stmt::HirPattern, monomorphization::ast::Program, Type, Visibility};
+use serde::{Deserialize, Serialize};
 use tracing::{span, Level};
 
-use self::{acir_gen::GeneratedAcir, ssa_gen::Ssa};
+pub(crate) use self::acir_gen::{rename_opcode_witnesses, GeneratedAcir};
+use self::{
+    custom_passes::{CustomSsaPasses, PassPlacement},
+    ir::dfg::CallStack,
+    ssa_gen::Ssa,
+};
 
 mod acir_gen;
+pub mod custom_passes;
 pub(super) mod function_builder;
 pub mod ir;
 mod opt;
 pub mod ssa_gen;
 
+/// The ACIR of a program, before it is lowered into a circuit, along with what was reported about
+/// it while it was compiled.
+pub(crate) struct OptimizedAcir {
+    main: GeneratedAcir,
+    /// The ACIR of each `#[fold]` or `#[no_inline]` function, along with its name.
+    functions: Vec<(String, GeneratedAcir)>,
+    watermarks: Vec<PassWatermark>,
+    brillig_sizes: Vec<BrilligFunctionSize>,
+    loop_reports: Vec<String>,
+}
+
 /// Optimize the given program by converting it into SSA
 /// form and performing optimizations there. When finished,
 /// convert the final SSA into ACIR and return it.
 pub(crate) fn optimize_into_acir(
     program: Program,
-    print_ssa_passes: bool,
-    print_brillig_trace: bool,
-) -> Result<GeneratedAcir, RuntimeError> {
+    options: &CircuitOptions,
+) -> Result<OptimizedAcir, RuntimeError> {
     let abi_distinctness = program.return_distinctness;
+    let main_parameters = main_parameters(&program);
 
-    let ssa_gen_span = span!(Level::TRACE, "ssa_generation");
+    let ssa_gen_span = span!(Level::TRACE, spans::SSA_GENERATION);
     let ssa_gen_span_guard = ssa_gen_span.enter();
-    let ssa = SsaBuilder::new(program, print_ssa_passes)?
-        .run_pass(Ssa::defunctionalize, "After Defunctionalization:")
-        .run_pass(Ssa::inline_functions, "After Inlining:")
+    let dump_ssa_after = options.ssa_dump.map_or(&[][..], |dump| &dump.passes);
+    let builder = SsaBuilder::new(program, options.enable_ssa_logging, dump_ssa_after)?;
+    let builder = SsaBuilder {
+        collect_loop_reports: options.collect_loop_reports,
+        brillig_unroll_threshold: options.brillig_unroll_threshold,
+        custom_passes: options.custom_passes.clone(),
+        ..builder
+    };
+    let mut builder = run_ssa_passes(builder)?;
+    let loop_reports = std::mem::take(&mut builder.loop_reports);
+    let (ssa, watermarks, snapshots) = builder.finish();
+    if let Some(dump) = options.ssa_dump {
+        dump.write(&snapshots).map_err(|error| RuntimeError::WriteOutput {
+            path: dump.directory.display().to_string(),
+            message: error.to_string(),
+            call_stack: CallStack::new(),
+        })?;
+    }
+
+    let brillig = ssa.to_brillig(&BrilligOptions {
+        enable_debug_trace: options.enable_brillig_logging,
+        limits: BrilligLimits {
+            max_call_depth: options.brillig_max_call_depth,
+            max_memory: options.brillig_max_memory,
+        },
+        enable_profiling: options.profile_brillig,
+        memoized_foreign_calls: options.memoized_oracles.to_vec(),
+        check_overflows: options.check_brillig_overflows,
+    })?;
+    let brillig_sizes = brillig.function_sizes(&ssa);
+
+    drop(ssa_gen_span_guard);
+
+    let last_array_uses = ssa.find_last_array_uses();
+
+    let (main, functions) = ssa.into_acir(
+        brillig,
+        abi_distinctness,
+        main_parameters,
+        &last_array_uses,
+        options.cost_model.clone(),
+        options.max_reduction_witnesses,
+        options.warn_failing_range_constraints,
+        options.dedup_return_witnesses,
+    )?;
+    Ok(OptimizedAcir { main, functions, watermarks, brillig_sizes, loop_reports })
+}
+
+/// Names of the SSA passes, as used to select the passes after which to capture the SSA.
+pub const SSA_PASS_NAMES: &[&str] = &[
+    "defunctionalize",
+    "inline_functions",
+    "mem2reg",
+    "evaluate_assert_constant",
+    "unroll_loops",
+    "simplify_cfg",
+    "flatten_cfg",
+    "hoist_predicated_gadgets",
+    "fold_constants",
+    "precompute_commitments",
+    "dead_instruction_elimination",
+    "split_struct_arrays",
+    "sink_truncations",
+];
+
+fn run_ssa_passes(builder: SsaBuilder) -> Result<SsaBuilder, RuntimeError> {
+    let brillig_unroll_threshold = builder.brillig_unroll_threshold;
+    builder
+        .run_pass(Ssa::defunctionalize, "defunctionalize", "After Defunctionalization:")?
+        .run_pass(Ssa::inline_functions, "inline_functions", "After Inlining:")?
         // Run mem2reg with the CFG separated into blocks
-        .run_pass(Ssa::mem2reg, "After Mem2Reg:")
-        .try_run_pass(Ssa::evaluate_assert_constant, "After Assert Constant:")?
-        .try_run_pass(Ssa::unroll_loops, "After Unrolling:")?
-        .run_pass(Ssa::simplify_cfg, "After Simplifying:")
+        .run_pass(Ssa::mem2reg, "mem2reg", "After Mem2Reg:")?
+        .try_run_pass(
+            Ssa::evaluate_assert_constant,
+            "evaluate_assert_constant",
+            "After Assert Constant:",
+        )?
+        .collect_loop_reports()
+        .try_run_pass(
+            |ssa| ssa.unroll_loops(brillig_unroll_threshold),
+            "unroll_loops",
+            "After Unrolling:",
+        )?
+        .run_pass(Ssa::simplify_cfg, "simplify_cfg", "After Simplifying:")?
         // Run mem2reg before flattening to handle any promotion
         // of values that can be accessed after loop unrolling.
         // If there are slice mergers uncovered by loop unrolling
         // and this pass is missed, slice merging will fail inside of flattening.
-        .run_pass(Ssa::mem2reg, "After Mem2Reg:")
-        .run_pass(Ssa::flatten_cfg, "After Flattening:")
+        .run_pass(Ssa::mem2reg, "mem2reg", "After Mem2Reg:")?
+        .run_pass(Ssa::flatten_cfg, "flatten_cfg", "After Flattening:")?
         // Run mem2reg once more with the flattened CFG to catch any remaining loads/stores
-        .run_pass(Ssa::mem2reg, "After Mem2Reg:")
-        .run_pass(Ssa::fold_constants, "After Constant Folding:")
-        .run_pass(Ssa::dead_instruction_elimination, "After Dead Instruction Elimination:")
-        .finish();
+        .run_pass(Ssa::mem2reg, "mem2reg", "After Mem2Reg:")?
+        .run_pass(
+            Ssa::hoist_predicated_gadgets,
+            "hoist_predicated_gadgets",
+            "After Hoisting Predicated Gadgets:",
+        )?
+        .run_pass(Ssa::fold_constants, "fold_constants", "After Constant Folding:")?
+        .try_run_pass(
+            Ssa::precompute_commitments,
+            "precompute_commitments",
+            "After Precomputing Commitments:",
+        )?
+        .run_pass(
+            Ssa::dead_instruction_elimination,
+            "dead_instruction_elimination",
+            "After Dead Instruction Elimination:",
+        )?
+        .run_pass(
+            Ssa::split_struct_arrays,
+            "split_struct_arrays",
+            "After Splitting Struct Arrays:",
+        )?
+        .run_pass(Ssa::sink_truncations, "sink_truncations", "After Sinking Truncations:")?
+        .run_pass(
+            Ssa::dead_instruction_elimination,
+            "dead_instruction_elimination",
+            "After Dead Instruction Elimination:",
+        )
+}
 
-    let brillig = ssa.to_brillig(print_brillig_trace);
+/// Runs the SSA passes over `program`, returning the SSA of each function after each of the
+/// passes named in `passes`.
+pub fn capture_ssa_snapshots(
+    program: Program,
+    passes: &[String],
+) -> Result<Vec<SsaSnapshot>, RuntimeError> {
+    let builder = SsaBuilder::new(program, false, passes)?;
+    let (_, _, snapshots) = run_ssa_passes(builder)?.finish();
+    Ok(snapshots)
+}
 
-    drop(ssa_gen_span_guard);
+/// The SSA of a single function after a pass.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SsaSnapshot {
+    /// Name of the pass, suffixed with `_2`, `_3`, etc. if it has already run before.
+    pub pass: String,
+    /// Name of the function, prefixed with its id as names of functions need not be unique.
+    pub function: String,
+    pub ssa: String,
+}
 
-    let last_array_uses = ssa.find_last_array_uses();
+impl SsaSnapshot {
+    /// The path of the file which the snapshot is written to, relative to the dump directory.
+    pub fn path(&self) -> PathBuf {
+        Path::new(&self.pass).join(format!("{}.ssa", self.function))
+    }
+}
+
+/// Writes the SSA of each function to `directory` after each of the passes named in `passes`.
+#[derive(Debug, Clone)]
+pub struct SsaDump {
+    pub passes: Vec<String>,
+    pub directory: PathBuf,
+}
+
+impl SsaDump {
+    fn write(&self, snapshots: &[SsaSnapshot]) -> std::io::Result<()> {
+        for snapshot in snapshots {
+            let path = self.directory.join(snapshot.path());
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, &snapshot.ssa)?;
+        }
+        Ok(())
+    }
+}
+
+/// The size of the program after a single compilation stage.
+///
+/// These are recorded for every compilation so that changes in the size of a program between
+/// compiler versions can be attributed to the pass responsible for them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PassWatermark {
+    /// Name of the pass (or stage) after which the counts were taken.
+    pub pass: String,
+    /// Number of SSA instructions in reachable blocks or, once ACIR has been generated,
+    /// the number of ACIR opcodes.
+    pub instructions: usize,
+    /// Number of witnesses in the circuit. This is only known once ACIR has been generated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub witnesses: Option<u32>,
+    /// Cost of proving the circuit according to the cost model of the targeted backend, which is
+    /// closer to its gate count than the number of opcodes. This is only known once ACIR has been
+    /// generated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_cost: Option<u64>,
+}
+
+/// A named part of the return value of `main`, such as a field of a returned struct.
+///
+/// Each group has its own list of witnesses so that it can be read from the solved witnesses
+/// without knowing where it lies within the flattened return value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReturnGroup {
+    pub name: String,
+    /// The witnesses holding the flattened value of the group, in order.
+    pub witnesses: Vec<Witness>,
+}
+
+/// The size of the Brillig bytecode which an unconstrained function compiles to, before it is
+/// linked with the functions it calls into the bytecode executed by each call from ACIR.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BrilligFunctionSize {
+    pub name: String,
+    /// Number of opcodes of the function.
+    pub opcodes: usize,
+    /// Number of opcodes of each kind, keyed by their mnemonic in the Brillig disassembly.
+    pub opcode_counts: BTreeMap<String, usize>,
+}
+
+/// A function marked `#[fold]` or `#[no_inline]`, compiled into a separate circuit which the
+/// circuit of `main` and the other functions call into with `Call` opcodes.
+///
+/// The parameters of the circuit are the witnesses `0..n`, holding the flattened arguments of the
+/// function, and its return values are distinct witnesses in the order they are returned in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AcirFunction {
+    pub name: String,
+    #[serde(
+        serialize_with = "Circuit::serialize_circuit_base64",
+        deserialize_with = "Circuit::deserialize_circuit_base64"
+    )]
+    pub circuit: Circuit,
+    pub debug: DebugInfo,
+}
+
+/// A parameter of `main`, with the input witnesses holding its value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputParameter {
+    pub name: String,
+    pub visibility: Visibility,
+    /// The witnesses holding the flattened value of the parameter, in order.
+    pub witnesses: Vec<Witness>,
+}
+
+impl PassWatermark {
+    fn ssa(pass: &str, ssa: &Ssa) -> Self {
+        PassWatermark {
+            pass: pass.to_string(),
+            instructions: ssa.num_instructions(),
+            witnesses: None,
+            estimated_cost: None,
+        }
+    }
+
+    fn acir(pass: &str, circuit: &Circuit, cost_model: &dyn CostModel) -> Self {
+        PassWatermark {
+            pass: pass.to_string(),
+            instructions: circuit.opcodes.len(),
+            witnesses: Some(circuit.current_witness_index),
+            estimated_cost: Some(cost_model.circuit_cost(&circuit.opcodes)),
+        }
+    }
+}
+
+/// The options of the compilation of a [`Program`] by [`create_circuit`].
+#[derive(Clone)]
+pub struct CircuitOptions<'a> {
+    /// Whether the SSA is printed after each pass.
+    pub enable_ssa_logging: bool,
+    /// Whether the Brillig opcodes are printed as they are generated.
+    pub enable_brillig_logging: bool,
+    /// The cost model of the targeted backend, only used to choose between equivalent ways of
+    /// expressing an operation.
+    pub cost_model: Arc<dyn CostModel>,
+    /// If bounded, `AssertZero` opcodes are split to fit within this width.
+    pub expression_width: ExpressionWidth,
+    /// A warning is emitted for each expression which needs more than this many intermediate
+    /// witnesses.
+    pub max_reduction_witnesses: Option<usize>,
+    /// Each Brillig opcode may execute at most this many Brillig opcodes, or fewer if a smaller
+    /// bound can be derived from its loops.
+    pub max_brillig_steps: Option<usize>,
+    /// The order of the public inputs, which are otherwise ordered by witness index. The values
+    /// of `main` making up each public input are returned alongside the circuit.
+    pub public_input_layout: Option<&'a PublicInputLayout>,
+    /// The passes after which the SSA of each function is written out.
+    pub ssa_dump: Option<&'a SsaDump>,
+    /// Whether a summary of each loop is returned, as it is before unrolling.
+    pub collect_loop_reports: bool,
+    /// Loops of unconstrained functions are only unrolled if they unroll into at most this many
+    /// instructions.
+    pub brillig_unroll_threshold: Option<usize>,
+    /// Unconstrained functions trap once they are nested deeper than this many calls.
+    pub brillig_max_call_depth: Option<usize>,
+    /// Unconstrained functions trap once they use more than this many memory slots.
+    pub brillig_max_memory: Option<usize>,
+    /// Whether each block of unconstrained code counts its executions through the
+    /// [`BRILLIG_PROFILE_ORACLE`][crate::brillig::BRILLIG_PROFILE_ORACLE], under a counter whose
+    /// call stack is recorded in the [`DebugInfo`].
+    pub profile_brillig: bool,
+    /// The oracles whose calls from unconstrained code reuse the results of the earlier calls
+    /// with the same inputs, where these are values or arrays of them.
+    pub memoized_oracles: &'a [String],
+    /// Whether the unsigned arithmetic of unconstrained code traps instead of wrapping around
+    /// where it would fail its overflow checks in constrained code.
+    pub check_brillig_overflows: bool,
+    /// Whether range constraints which can never be satisfied are warnings rather than errors.
+    pub warn_failing_range_constraints: bool,
+    /// Unless empty, only the opcodes needed for these targets and the public parameters are
+    /// kept, and the circuit has no return value unless it is targeted.
+    pub slice_targets: &'a [SliceTarget],
+    /// Whether a warning is emitted for each group of opcodes which is not connected to any input
+    /// or return value of the program.
+    pub warn_disconnected_components: bool,
+    /// Whether repeated elements of a `distinct` return value of `main` share a single return
+    /// witness, so that they are only exposed once as public inputs.
+    pub dedup_return_witnesses: bool,
+    /// Passes run around the built-in SSA passes they are placed at.
+    pub custom_passes: &'a CustomSsaPasses,
+}
 
-    ssa.into_acir(brillig, abi_distinctness, &last_array_uses)
+/// A [`Program`] compiled by [`create_circuit`].
+pub struct CompiledCircuit {
+    pub circuit: Circuit,
+    pub debug: DebugInfo,
+    /// The input witnesses, as the parameters of `main` which they hold.
+    pub input_parameters: Vec<InputParameter>,
+    pub return_witnesses: Vec<Witness>,
+    pub warnings: Vec<SsaReport>,
+    pub pass_watermarks: Vec<PassWatermark>,
+    /// The size of the Brillig bytecode of each unconstrained function.
+    pub brillig_sizes: Vec<BrilligFunctionSize>,
+    /// The values of `main` making up each public input.
+    pub public_input_elements: PublicInputElements,
+    /// The witnesses of each field of the return value of `main`, if it is a struct or tuple.
+    pub return_groups: Vec<ReturnGroup>,
+    /// The functions marked `#[fold]` or `#[no_inline]`, in the order of the ids which `Call`
+    /// opcodes refer to them by, starting at `1`.
+    pub acir_functions: Vec<AcirFunction>,
+    /// A summary of each loop before unrolling, if they were collected.
+    pub loop_reports: Vec<String>,
 }
 
 /// Compiles the [`Program`] into [`ACIR`][acvm::acir::circuit::Circuit].
 ///
 /// The output ACIR is is backend-agnostic and so must go through a transformation pass before usage in proof generation.
-#[allow(clippy::type_complexity)]
 #[tracing::instrument(level = "trace", skip_all)]
 pub fn create_circuit(
     program: Program,
-    enable_ssa_logging: bool,
-    enable_brillig_logging: bool,
-) -> Result<(Circuit, DebugInfo, Vec<Witness>, Vec<Witness>, Vec<SsaReport>), RuntimeError> {
+    options: &CircuitOptions,
+) -> Result<CompiledCircuit, RuntimeError> {
     let func_sig = program.main_function_signature.clone();
     let recursive = program.recursive;
-    let mut generated_acir =
-        optimize_into_acir(program, enable_ssa_logging, enable_brillig_logging)?;
-    let opcodes = generated_acir.take_opcodes();
-    let current_witness_index = generated_acir.current_witness_index().0;
-    let GeneratedAcir {
-        return_witnesses,
-        locations,
-        input_witnesses,
-        assert_messages,
-        warnings,
-        ..
-    } = generated_acir;
+    let main_values = match options.public_input_layout {
+        Some(layout) => Some((layout, main_values(&program)?)),
+        None => None,
+    };
+    let OptimizedAcir {
+        main: mut generated_acir,
+        functions,
+        mut watermarks,
+        brillig_sizes,
+        loop_reports,
+    } = optimize_into_acir(program, options)?;
+    generated_acir.group_return_witnesses(return_groups(func_sig.1.as_ref()));
+    let slice_targets = options.slice_targets;
+    if !slice_targets.is_empty() && !slice_targets.contains(&SliceTarget::ReturnValue) {
+        generated_acir.return_witnesses.clear();
+        generated_acir.return_groups.clear();
+    }
 
-    let (public_parameter_witnesses, private_parameters) =
-        split_public_and_private_inputs(&func_sig, &input_witnesses);
+    let (public_parameter_witnesses, mut private_parameters) =
+        split_public_and_private_inputs(&generated_acir.input_parameters);
+    let (public_parameters, return_values, public_input_elements) = match main_values {
+        Some((layout, values)) => {
+            let mut witnesses =
+                vecmap(&generated_acir.input_parameters, |parameter| parameter.witnesses.clone());
+            witnesses.push(generated_acir.return_witnesses.clone());
+            let laid_out = apply_layout(layout, &mut generated_acir, &values, &witnesses)?;
+            private_parameters.extend(laid_out.private_parameters);
+            (
+                PublicInputs(laid_out.public_parameters),
+                PublicInputs(laid_out.return_values),
+                laid_out.elements,
+            )
+        }
+        None => (
+            PublicInputs(public_parameter_witnesses),
+            PublicInputs(generated_acir.return_witnesses.iter().copied().collect()),
+            public_input_elements(
+                &generated_acir.input_parameters,
+                &generated_acir.return_witnesses,
+            ),
+        ),
+    };
+
+    if !slice_targets.is_empty() {
+        let public_inputs = public_parameters.0.iter().chain(&return_values.0).copied();
+        slice(&mut generated_acir, slice_targets, public_inputs)?;
+    }
+
+    if options.warn_disconnected_components {
+        let connected =
+            public_parameters.0.iter().chain(&return_values.0).chain(&private_parameters);
+        generated_acir.warn_disconnected_components(connected.copied());
+    }
+
+    let expression_width = options.expression_width;
+    if let ExpressionWidth::Bounded { width } = expression_width {
+        generated_acir.reduce_expression_width(width);
+    }
 
-    let public_parameters = PublicInputs(public_parameter_witnesses);
-    let return_values = PublicInputs(return_witnesses.iter().copied().collect());
+    // Slicing and the removal of redundant constraints leave behind witnesses which are no longer
+    // referenced, which backends would otherwise still allocate.
+    let required = public_parameters.0.iter().chain(&return_values.0).chain(&private_parameters);
+    let renaming = generated_acir.compact_witnesses(required.copied());
+    let rename = |witness: &Witness| renaming[witness];
+    let public_parameters = PublicInputs(public_parameters.0.iter().map(rename).collect());
+    let return_values = PublicInputs(return_values.0.iter().map(rename).collect());
+    let private_parameters = private_parameters.iter().map(rename).collect();
+    let public_input_elements = public_input_elements
+        .into_iter()
+        .map(|(witness, elements)| (rename(&witness), elements))
+        .collect();
 
+    let opcodes = generated_acir.take_opcodes();
     let circuit = Circuit {
-        current_witness_index,
-        expression_width: ExpressionWidth::Unbounded,
+        current_witness_index: generated_acir.current_witness_index().0,
+        expression_width,
         opcodes,
         private_parameters,
         public_parameters,
         return_values,
-        assert_messages: assert_messages.into_iter().collect(),
+        assert_messages: Vec::new(),
         recursive,
+        brillig_bytecode: std::mem::take(&mut generated_acir.brillig_bytecode),
+    };
+    let cost_model = &*options.cost_model;
+    watermarks.push(PassWatermark::acir("ACIR Generation", &circuit, cost_model));
+
+    // Perform any ACIR-level optimizations, which may remove opcodes
+    let (mut optimized_circuit, transformation_map) = acvm::compiler::optimize(circuit);
+    generated_acir.remap_opcode_locations(|location| transformation_map.new_locations(location));
+    let max_brillig_steps = options.max_brillig_steps;
+    let debug = take_debug_info(&mut generated_acir, &mut optimized_circuit, max_brillig_steps);
+    watermarks.push(PassWatermark::acir("ACIR Optimization", &optimized_circuit, cost_model));
+
+    let acir_functions = vecmap(functions, |(name, generated_acir)| {
+        compile_acir_function(name, generated_acir, expression_width, max_brillig_steps)
+    });
+
+    let GeneratedAcir { return_witnesses, return_groups, input_parameters, warnings, .. } =
+        generated_acir;
+    Ok(CompiledCircuit {
+        circuit: optimized_circuit,
+        debug,
+        input_parameters,
+        return_witnesses,
+        warnings,
+        pass_watermarks: watermarks,
+        brillig_sizes,
+        public_input_elements,
+        return_groups,
+        acir_functions,
+        loop_reports,
+    })
+}
+
+/// Lowers the ACIR generated for a `#[fold]` or `#[no_inline]` function into the circuit of an
+/// [`AcirFunction`].
+fn compile_acir_function(
+    name: String,
+    mut generated_acir: GeneratedAcir,
+    expression_width: ExpressionWidth,
+    max_brillig_steps: Option<usize>,
+) -> AcirFunction {
+    if let ExpressionWidth::Bounded { width } = expression_width {
+        generated_acir.reduce_expression_width(width);
+    }
+
+    // The parameters and return values are always kept, and keep their order, so that the
+    // parameters remain the witnesses `0..n` which callers assign their arguments to.
+    generated_acir.compact_witnesses(std::iter::empty());
+    let circuit = Circuit {
+        current_witness_index: generated_acir.current_witness_index().0,
+        expression_width,
+        opcodes: generated_acir.take_opcodes(),
+        private_parameters: generated_acir.input_witnesses.iter().copied().collect(),
+        public_parameters: PublicInputs::default(),
+        return_values: PublicInputs(generated_acir.return_witnesses.iter().copied().collect()),
+        assert_messages: Vec::new(),
+        recursive: false,
+        brillig_bytecode: std::mem::take(&mut generated_acir.brillig_bytecode),
     };
 
-    // This converts each im::Vector in the BTreeMap to a Vec
-    let locations = locations
+    let (mut circuit, transformation_map) = acvm::compiler::optimize(circuit);
+    generated_acir.remap_opcode_locations(|location| transformation_map.new_locations(location));
+    let debug = take_debug_info(&mut generated_acir, &mut circuit, max_brillig_steps);
+    AcirFunction { name, circuit, debug }
+}
+
+/// Moves the locations, witness names and Brillig step bounds, variables and counters of
+/// `generated_acir` into the [`DebugInfo`] of `circuit`, the optimized circuit generated from it,
+/// and its assertion messages into `circuit` itself.
+fn take_debug_info(
+    generated_acir: &mut GeneratedAcir,
+    circuit: &mut Circuit,
+    max_brillig_steps: Option<usize>,
+) -> DebugInfo {
+    let assert_messages = std::mem::take(&mut generated_acir.assert_messages);
+    circuit.assert_messages = assert_messages.into_iter().collect();
+
+    // This resolves each interned call stack in the BTreeMap into a Vec
+    let call_stacks = &generated_acir.call_stacks;
+    let locations = std::mem::take(&mut generated_acir.locations)
         .into_iter()
-        .map(|(index, locations)| (index, locations.into_iter().collect()))
+        .map(|(index, call_stack)| (index, call_stacks.resolve(call_stack).into_iter().collect()))
         .collect();
 
     let mut debug_info = DebugInfo::new(locations);
-
-    // Perform any ACIR-level optimizations
-    let (optimized_circuit, transformation_map) = acvm::compiler::optimize(circuit);
-    debug_info.update_acir(transformation_map);
-
-    Ok((optimized_circuit, debug_info, input_witnesses, return_witnesses, warnings))
+    debug_info.witness_names = std::mem::take(&mut generated_acir.witness_names);
+    debug_info.brillig_variables = std::mem::take(&mut generated_acir.brillig_variables);
+    debug_info.brillig_counters = std::mem::take(&mut generated_acir.brillig_counters)
+        .into_iter()
+        .map(|(counter, call_stack)| (counter, call_stack.into_iter().collect()))
+        .collect();
+    let brillig_step_bounds = &generated_acir.brillig_step_bounds;
+    debug_info.brillig_step_bounds = circuit
+        .opcodes
+        .iter()
+        .enumerate()
+        .filter(|(_, opcode)| matches!(opcode, Opcode::Brillig(_) | Opcode::BrilligCall { .. }))
+        .filter_map(|(index, _)| {
+            let step_bound = match (brillig_step_bounds.get(&index), max_brillig_steps) {
+                (Some(derived), Some(max)) => (*derived).min(max),
+                (Some(derived), None) => *derived,
+                (None, max) => max?,
+            };
+            Some((index, step_bound))
+        })
+        .collect();
+    debug_info
 }
 
-// Takes each function argument and partitions the circuit's inputs witnesses according to its visibility.
+/// Partitions the witnesses of the parameters of `main` according to their visibility.
 fn split_public_and_private_inputs(
-    func_sig: &FunctionSignature,
-    input_witnesses: &[Witness],
+    input_parameters: &[InputParameter],
 ) -> (BTreeSet<Witness>, BTreeSet<Witness>) {
-    let mut idx = 0_usize;
-    if input_witnesses.is_empty() {
-        return (BTreeSet::new(), BTreeSet::new());
+    let mut public_parameters = BTreeSet::new();
+    let mut private_parameters = BTreeSet::new();
+    for parameter in input_parameters {
+        let witnesses = parameter.witnesses.iter().copied();
+        if parameter.visibility == Visibility::Public {
+            public_parameters.extend(witnesses);
+        } else {
+            private_parameters.extend(witnesses);
+        }
     }
+    (public_parameters, private_parameters)
+}
 
-    func_sig
-        .0
-        .iter()
-        .map(|(_, typ, visibility)| {
-            let num_field_elements_needed = typ.field_count() as usize;
-            let witnesses = input_witnesses[idx..idx + num_field_elements_needed].to_vec();
-            idx += num_field_elements_needed;
-            (visibility, witnesses)
-        })
-        .fold((BTreeSet::new(), BTreeSet::new()), |mut acc, (vis, witnesses)| {
-            // Split witnesses into sets based on their visibility.
-            if *vis == Visibility::Public {
-                for witness in witnesses {
-                    acc.0.insert(witness);
-                }
-            } else {
-                for witness in witnesses {
-                    acc.1.insert(witness);
-                }
+/// Maps each public input witness to the elements of the public parameters and return value of
+/// `main` which it holds. A witness may hold more than one of them if the return value reuses it.
+fn public_input_elements(
+    input_parameters: &[InputParameter],
+    return_witnesses: &[Witness],
+) -> PublicInputElements {
+    let mut elements = PublicInputElements::new();
+    for (value, parameter) in input_parameters.iter().enumerate() {
+        if parameter.visibility == Visibility::Public {
+            for (element, witness) in parameter.witnesses.iter().enumerate() {
+                let element = PublicInputElement { value, element, bit_offset: 0 };
+                elements.entry(*witness).or_default().push(element);
             }
-            (acc.0, acc.1)
-        })
+        }
+    }
+    let value = input_parameters.len();
+    for (element, witness) in return_witnesses.iter().enumerate() {
+        let element = PublicInputElement { value, element, bit_offset: 0 };
+        elements.entry(*witness).or_default().push(element);
+    }
+    elements
+}
+
+/// Returns the name, visibility and number of field elements of each parameter of `main`.
+fn main_parameters(program: &Program) -> Vec<(String, Visibility, usize)> {
+    let names = &program.main().parameters;
+    let signature = &program.main_function_signature.0;
+    vecmap(signature.iter().enumerate(), |(index, (_, typ, visibility))| {
+        // Monomorphization splits destructured parameters into one parameter per binding, which
+        // leaves them without a name of their own.
+        let name =
+            if names.len() == signature.len() { names[index].2.clone() } else { index.to_string() };
+        (name, *visibility, typ.field_count() as usize)
+    })
+}
+
+/// Returns the name and number of field elements of each part of a return value of type
+/// `return_type` which forms a [`ReturnGroup`]. Only the fields of structs and tuples do.
+fn return_groups(return_type: Option<&Type>) -> Vec<(String, usize)> {
+    let fields = match return_type {
+        Some(Type::Struct(struct_type, generics)) => struct_type.borrow().get_fields(generics),
+        Some(Type::Tuple(fields)) => {
+            fields.iter().enumerate().map(|(index, typ)| (index.to_string(), typ.clone())).collect()
+        }
+        _ => Vec::new(),
+    };
+    vecmap(fields, |(name, typ)| (name, typ.field_count() as usize))
+}
+
+/// Describes each parameter of `main`, followed by its return value, for a [`PublicInputLayout`].
+fn main_values(program: &Program) -> Result<Vec<MainValue>, RuntimeError> {
+    let main = program.main();
+    let signature = &program.main_function_signature.0;
+    // Monomorphization splits destructured parameters into one parameter per binding.
+    if main.parameters.len() != signature.len() {
+        return Err(RuntimeError::InvalidPublicInputLayout {
+            message: "parameters of main must not be destructured".to_string(),
+            call_stack: program.return_location.into_iter().collect(),
+        });
+    }
+
+    let mut values = vecmap(
+        main.parameters.iter().zip(signature),
+        |((_, _, name, typ), (pattern, _, visibility))| {
+            let is_public = *visibility == Visibility::Public;
+            MainValue::new(name.clone(), typ, is_public, false, pattern_location(pattern))
+        },
+    );
+    values.push(MainValue::new(
+        RETURN_VALUE_NAME.to_string(),
+        &main.return_type,
+        true,
+        true,
+        program.return_location,
+    ));
+    Ok(values)
+}
+
+fn pattern_location(pattern: &HirPattern) -> Option<Location> {
+    match pattern {
+        HirPattern::Identifier(ident) => Some(ident.location),
+        HirPattern::Mutable(pattern, _) => pattern_location(pattern),
+        HirPattern::Tuple(..) | HirPattern::Struct(..) => None,
+    }
 }
 
 // This is just a convenience object to bundle the ssa with `print_ssa_passes` for debug printing.
 struct SsaBuilder {
     ssa: Ssa,
     print_ssa_passes: bool,
+    collect_loop_reports: bool,
+    /// A summary of each loop before unrolling, if `collect_loop_reports` is true.
+    loop_reports: Vec<String>,
+    /// Loops of Brillig functions are unrolled if they unroll into at most this many instructions.
+    brillig_unroll_threshold: Option<usize>,
+    watermarks: Vec<PassWatermark>,
+    dump_ssa_after: Vec<String>,
+    snapshots: Vec<SsaSnapshot>,
+    /// The number of times each pass has run so far.
+    pass_runs: HashMap<&'static str, usize>,
+    custom_passes: CustomSsaPasses,
 }
 
 impl SsaBuilder {
-    fn new(program: Program, print_ssa_passes: bool) -> Result<SsaBuilder, RuntimeError> {
+    fn new(
+        program: Program,
+        print_ssa_passes: bool,
+        dump_ssa_after: &[String],
+    ) -> Result<SsaBuilder, RuntimeError> {
         let ssa = ssa_gen::generate_ssa(program)?;
-        Ok(SsaBuilder { print_ssa_passes, ssa }.print("Initial SSA:"))
+        let builder = SsaBuilder {
+            print_ssa_passes,
+            collect_loop_reports: false,
+            loop_reports: Vec::new(),
+            brillig_unroll_threshold: None,
+            ssa,
+            watermarks: Vec::new(),
+            dump_ssa_after: dump_ssa_after.to_vec(),
+            snapshots: Vec::new(),
+            pass_runs: HashMap::new(),
+            custom_passes: CustomSsaPasses::default(),
+        };
+        Ok(builder.print("Initial SSA:"))
     }
 
-    fn finish(self) -> Ssa {
-        self.ssa
+    fn finish(self) -> (Ssa, Vec<PassWatermark>, Vec<SsaSnapshot>) {
+        (self.ssa, self.watermarks, self.snapshots)
     }
 
     /// Runs the given SSA pass and prints the SSA afterward if `print_ssa_passes` is true.
-    fn run_pass(mut self, pass: fn(Ssa) -> Ssa, msg: &str) -> Self {
-        self.ssa = pass(self.ssa);
-        self.print(msg)
+    ///
+    /// The custom passes placed before or after this run of the pass are run around it.
+    fn run_pass(
+        self,
+        pass: fn(Ssa) -> Ssa,
+        name: &'static str,
+        msg: &str,
+    ) -> Result<Self, RuntimeError> {
+        self.try_run_pass(|ssa| Ok(pass(ssa)), name, msg)
     }
 
     /// The same as `run_pass` but for passes that may fail
     fn try_run_pass(
         mut self,
-        pass: fn(Ssa) -> Result<Ssa, RuntimeError>,
+        pass: impl FnOnce(Ssa) -> Result<Ssa, RuntimeError>,
+        name: &'static str,
         msg: &str,
     ) -> Result<Self, RuntimeError> {
-        self.ssa = pass(self.ssa)?;
-        Ok(self.print(msg))
+        let run = self.next_run(name);
+        self = self.run_custom_passes(&PassPlacement::Before(run.clone()))?;
+
+        let span = span!(Level::DEBUG, spans::SSA_PASS, pass = name);
+        self.ssa = span.in_scope(|| pass(self.ssa))?;
+        self.snapshot(&run);
+        self = self.print(msg);
+
+        self.run_custom_passes(&PassPlacement::After(run))
+    }
+
+    /// Runs the custom passes placed at `placement`, printing the SSA after each of them.
+    fn run_custom_passes(mut self, placement: &PassPlacement) -> Result<Self, RuntimeError> {
+        let custom_passes = self.custom_passes.clone();
+        for pass in custom_passes.placed_at(placement) {
+            let span = span!(Level::DEBUG, spans::SSA_PASS, pass = pass.name());
+            span.in_scope(|| pass.run(&mut self.ssa))?;
+            self = self.print(&format!("After {}:", pass.name()));
+        }
+        Ok(self)
+    }
+
+    /// Records a summary of each loop if `collect_loop_reports` is true.
+    fn collect_loop_reports(mut self) -> Self {
+        if self.collect_loop_reports {
+            self.loop_reports = vecmap(self.ssa.loop_reports(), |report| report.to_string());
+        }
+        self
+    }
+
+    fn to_brillig(&self, options: &BrilligOptions) -> Result<Brillig, RuntimeError> {
+        self.ssa.to_brillig(options)
+    }
+
+    /// Counts a new run of the pass `name`, returning the name of this run: `name` itself the
+    /// first time, then suffixed with `_2`, `_3`, etc.
+    fn next_run(&mut self, name: &'static str) -> String {
+        debug_assert!(SSA_PASS_NAMES.contains(&name), "{name} is missing from SSA_PASS_NAMES");
+        let runs = self.pass_runs.entry(name).or_default();
+        *runs += 1;
+        if *runs == 1 {
+            name.to_string()
+        } else {
+            format!("{name}_{runs}")
+        }
     }
 
-    fn to_brillig(&self, print_brillig_trace: bool) -> Brillig {
-        self.ssa.to_brillig(print_brillig_trace)
+    /// Records the SSA of each function if the pass of which `run` is a run is one of the passes
+    /// to capture.
+    fn snapshot(&mut self, run: &str) {
+        let name = match run.rsplit_once('_') {
+            Some((name, runs)) if runs.parse::<usize>().is_ok() => name,
+            _ => run,
+        };
+        if !self.dump_ssa_after.iter().any(|pass| pass == name) {
+            return;
+        }
+
+        let pass = run.to_string();
+        for function in self.ssa.functions.values() {
+            self.snapshots.push(SsaSnapshot {
+                pass: pass.clone(),
+                function: format!("{}_{}", function.id(), function.name()),
+                ssa: function.to_string(),
+            });
+        }
     }
 
-    fn print(self, msg: &str) -> Self {
+    fn print(mut self, msg: &str) -> Self {
+        // "After Mem2Reg:" is recorded as the watermark of the "Mem2Reg" pass.
+        let pass = msg.trim_start_matches("After ").trim_end_matches(':');
+        self.watermarks.push(PassWatermark::ssa(pass, &self.ssa));
+
         if self.print_ssa_passes {
             println!("{msg}\n{}", self.ssa);
         }
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, path::Path, sync::Arc};
+
+    use super::{
+        custom_passes::{CustomSsaPasses, PassPlacement, SsaPass},
+        SsaBuilder, SsaSnapshot,
+    };
+    use crate::{
+        errors::RuntimeError,
+        ssa::{
+            function_builder::FunctionBuilder,
+            ir::{function::RuntimeType, map::Id, types::Type},
+            ssa_gen::Ssa,
+        },
+    };
+
+    fn builder(dump_ssa_after: Vec<String>, custom_passes: CustomSsaPasses) -> SsaBuilder {
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::field());
+        builder.terminate_with_return(vec![v0]);
+
+        SsaBuilder {
+            ssa: builder.finish(),
+            print_ssa_passes: false,
+            collect_loop_reports: false,
+            loop_reports: Vec::new(),
+            brillig_unroll_threshold: None,
+            watermarks: Vec::new(),
+            dump_ssa_after,
+            snapshots: Vec::new(),
+            pass_runs: HashMap::new(),
+            custom_passes,
+        }
+    }
+
+    #[test]
+    fn snapshots_are_named_after_pass_and_function() {
+        let (_, _, snapshots) = builder(vec!["mem2reg".to_string()], CustomSsaPasses::default())
+            .run_pass(Ssa::mem2reg, "mem2reg", "After Mem2Reg:")
+            .and_then(|builder| {
+                builder.run_pass(Ssa::simplify_cfg, "simplify_cfg", "After Simplifying:")
+            })
+            .and_then(|builder| builder.run_pass(Ssa::mem2reg, "mem2reg", "After Mem2Reg:"))
+            .unwrap()
+            .finish();
+
+        let passes: Vec<_> = snapshots.iter().map(|snapshot| snapshot.pass.as_str()).collect();
+        assert_eq!(passes, vec!["mem2reg", "mem2reg_2"]);
+        let SsaSnapshot { function, ssa, .. } = &snapshots[1];
+        assert_eq!(function, "f0_main");
+        assert!(ssa.starts_with("acir fn main f0"));
+        assert_eq!(snapshots[1].path(), Path::new("mem2reg_2").join("f0_main.ssa"));
+    }
+
+    #[derive(Debug)]
+    struct NoopPass(PassPlacement);
+
+    impl SsaPass for NoopPass {
+        fn name(&self) -> &str {
+            "Noop"
+        }
+
+        fn placement(&self) -> PassPlacement {
+            self.0.clone()
+        }
+
+        fn run(&self, _ssa: &mut Ssa) -> Result<(), RuntimeError> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn custom_passes_run_around_a_single_run_of_a_pass() {
+        let mut custom_passes = CustomSsaPasses::default();
+        let placement = PassPlacement::After("mem2reg_2".to_string());
+        custom_passes.register(Arc::new(NoopPass(placement))).unwrap();
+        let unknown = PassPlacement::Before("gate_counts".to_string());
+        assert!(custom_passes.register(Arc::new(NoopPass(unknown))).is_err());
+
+        let (_, watermarks, _) = builder(Vec::new(), custom_passes)
+            .run_pass(Ssa::mem2reg, "mem2reg", "After Mem2Reg:")
+            .and_then(|builder| {
+                builder.run_pass(Ssa::simplify_cfg, "simplify_cfg", "After Simplifying:")
+            })
+            .and_then(|builder| builder.run_pass(Ssa::mem2reg, "mem2reg", "After Mem2Reg:"))
+            .unwrap()
+            .finish();
+
+        let passes: Vec<_> = watermarks.iter().map(|watermark| watermark.pass.as_str()).collect();
+        assert_eq!(passes, vec!["Mem2Reg", "Simplifying", "Mem2Reg", "Noop"]);
+    }
+}
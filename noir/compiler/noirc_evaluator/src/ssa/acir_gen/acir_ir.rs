@@ -1,4 +1,10 @@
 pub(crate) mod acir_variable;
 pub(crate) mod big_int;
+pub(crate) mod connectivity;
 pub(crate) mod generated_acir;
+pub(crate) mod peephole;
 pub(crate) mod sort;
+pub(crate) mod strategy;
+pub(crate) mod transcript;
+pub(crate) mod width_reduction;
+pub(crate) mod witness_renaming;
@@ -1,15 +1,21 @@
 use super::big_int::BigIntContext;
 use super::generated_acir::GeneratedAcir;
+use super::strategy::{BitwiseLowering, SortStrategy};
+use super::transcript::Transcript;
 use crate::brillig::brillig_gen::brillig_directive;
 use crate::brillig::brillig_ir::artifact::GeneratedBrillig;
+use crate::cost_model::CostModel;
 use crate::errors::{InternalError, RuntimeError, SsaReport};
 use crate::ssa::acir_gen::{AcirDynamicArray, AcirValue};
 use crate::ssa::ir::dfg::CallStack;
 use crate::ssa::ir::types::Type as SsaType;
-use crate::ssa::ir::{instruction::Endian, types::NumericType};
+use crate::ssa::ir::{
+    instruction::{quadratic_non_residue, Endian},
+    types::NumericType,
+};
 use acvm::acir::circuit::brillig::{BrilligInputs, BrilligOutputs};
 use acvm::acir::circuit::opcodes::{BlockId, MemOp};
-use acvm::acir::circuit::Opcode;
+use acvm::acir::circuit::{AssertionPayload, AssertionPayloadPart, Opcode};
 use acvm::blackbox_solver;
 use acvm::brillig_vm::{brillig::Value, VMStatus, VM};
 use acvm::{
@@ -24,7 +30,9 @@ use acvm::{
 use fxhash::FxHashMap as HashMap;
 use iter_extended::{try_vecmap, vecmap};
 use num_bigint::BigUint;
-use std::{borrow::Cow, hash::Hash};
+use std::{borrow::Cow, hash::Hash, sync::Arc};
+
+mod uint128;
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 /// High level Type descriptor for Variables.
@@ -88,6 +96,14 @@ impl<'a> From<&'a SsaType> for AcirType {
     }
 }
 
+/// A part of an assertion message built by [`AcirContext::assertion_payload`].
+#[derive(Debug, Clone)]
+pub(crate) enum AssertionPart {
+    Text(String),
+    /// The value of the variable at the time the assertion failed.
+    Var(AcirVar),
+}
+
 #[derive(Debug, Default)]
 /// Context object which holds the relationship between
 /// `Variables`(AcirVar) and types such as `Expression` and `Witness`
@@ -114,6 +130,23 @@ pub(crate) struct AcirContext {
 }
 
 impl AcirContext {
+    /// Sets the cost model consulted when choosing how to lower operations.
+    pub(crate) fn set_cost_model(&mut self, cost_model: Arc<dyn CostModel>) {
+        self.acir_ir.set_cost_model(cost_model);
+    }
+
+    /// Sets the number of intermediate witnesses a single expression may need before a warning
+    /// is emitted for it.
+    pub(crate) fn set_max_reduction_witnesses(&mut self, max_reduction_witnesses: Option<usize>) {
+        self.acir_ir.max_reduction_witnesses = max_reduction_witnesses;
+    }
+
+    /// Sets whether range constraints which can never be satisfied are reported as warnings
+    /// rather than errors.
+    pub(crate) fn set_warn_failing_range_constraints(&mut self, warn: bool) {
+        self.acir_ir.warn_failing_range_constraints = warn;
+    }
+
     pub(crate) fn current_witness_index(&self) -> Witness {
         self.acir_ir.current_witness_index()
     }
@@ -223,7 +256,10 @@ impl AcirContext {
         self.acir_ir.call_stack = call_stack;
     }
 
-    fn get_or_create_witness_var(&mut self, var: AcirVar) -> Result<AcirVar, InternalError> {
+    pub(crate) fn get_or_create_witness_var(
+        &mut self,
+        var: AcirVar,
+    ) -> Result<AcirVar, InternalError> {
         if self.var_to_expression(var)?.to_witness().is_some() {
             // If called with a variable which is already a witness then return the same variable.
             return Ok(var);
@@ -237,6 +273,15 @@ impl AcirContext {
         Ok(witness_var)
     }
 
+    /// Returns a witness variable equal to `var` so that it can be multiplied without exceeding
+    /// degree 2, recording any witness created for the expression currently being converted.
+    fn reduce_to_witness_var(&mut self, var: AcirVar) -> Result<AcirVar, InternalError> {
+        if self.var_to_expression(var)?.to_witness().is_none() {
+            self.acir_ir.record_reduction_witness();
+        }
+        self.get_or_create_witness_var(var)
+    }
+
     /// Converts an [`AcirVar`] to a [`Witness`]
     fn var_to_witness(&mut self, var: AcirVar) -> Result<Witness, InternalError> {
         let expression = self.var_to_expression(var)?;
@@ -263,6 +308,15 @@ impl AcirContext {
         Ok(var_data.to_expression().into_owned())
     }
 
+    /// Names the witness which `var` is, if it is one, after the variable of the source code
+    /// which it holds the value of.
+    pub(crate) fn name_var(&mut self, var: AcirVar, name: String) -> Result<(), InternalError> {
+        if let Some(witness) = self.var_to_expression(var)?.to_witness() {
+            self.acir_ir.witness_names.entry(witness).or_insert(name);
+        }
+        Ok(())
+    }
+
     /// True if the given AcirVar refers to a constant one value
     pub(crate) fn is_constant_one(&self, var: &AcirVar) -> bool {
         match self.vars[var] {
@@ -330,6 +384,101 @@ impl AcirContext {
         Ok(inverted_var)
     }
 
+    /// Returns a variable holding whether `var` is a square, along with a variable holding a square
+    /// root of `var` if so or of `var * quadratic_non_residue()` otherwise.
+    pub(crate) fn field_sqrt(
+        &mut self,
+        var: AcirVar,
+        predicate: AcirVar,
+    ) -> Result<(AcirVar, AcirVar), RuntimeError> {
+        let [is_square, root]: [AcirValue; 2] = self
+            .brillig(
+                predicate,
+                brillig_directive::directive_field_sqrt(),
+                vec![AcirValue::Var(var, AcirType::field())],
+                vec![AcirType::unsigned(1), AcirType::field()],
+                true,
+            )?
+            .try_into()
+            .expect("field_sqrt only returns two values");
+        let is_square = is_square.into_var()?;
+        let root = root.into_var()?;
+        self.range_constrain_var(is_square, &NumericType::Unsigned { bit_size: 1 }, None)?;
+
+        // root^2 == var * (is_square ? 1 : non_residue)
+        // As the non-residue times a non-square is a square, this certifies either answer.
+        let one = self.add_constant(FieldElement::one());
+        let non_residue = self.add_constant(quadratic_non_residue());
+        let is_not_square = self.sub_var(one, is_square)?;
+        let scaled_non_residue = self.mul_var(is_not_square, non_residue)?;
+        let factor = self.add_var(is_square, scaled_non_residue)?;
+        let expected_square = self.mul_var(var, factor)?;
+        let square = self.mul_var(root, root)?;
+        let difference = self.sub_var(square, expected_square)?;
+        let difference = self.mul_var(difference, predicate)?;
+        let zero = self.add_constant(FieldElement::zero());
+        self.assert_eq_var(difference, zero, None)?;
+
+        // Zero is a square, although zero times the non-residue also has a root.
+        let is_zero = self.eq_var(var, zero)?;
+        let claims_zero_is_not_square = self.mul_var(is_not_square, is_zero)?;
+        let claims_zero_is_not_square = self.mul_var(claims_zero_is_not_square, predicate)?;
+        self.assert_eq_var(claims_zero_is_not_square, zero, None)?;
+
+        Ok((is_square, root))
+    }
+
+    /// Returns a variable constrained to be `base^exponent`, where `exponent` is constrained to fit
+    /// in `max_bits_var` bits, which must be a constant.
+    pub(crate) fn pow_var(
+        &mut self,
+        base: AcirVar,
+        exponent: AcirVar,
+        max_bits_var: AcirVar,
+    ) -> Result<AcirVar, RuntimeError> {
+        let max_bits = match self.vars[&max_bits_var].as_constant() {
+            Some(max_bits) => max_bits.to_u128() as u32,
+            None => {
+                return Err(RuntimeError::InternalError(InternalError::NotAConstant {
+                    name: "max_bits".to_string(),
+                    call_stack: self.get_call_stack(),
+                }));
+            }
+        };
+
+        let base = self.var_to_expression(base)?;
+        let exponent = self.var_to_expression(exponent)?;
+        let power = self.acir_ir.pow(&base, &exponent, max_bits)?;
+        Ok(self.add_data(AcirVarData::from(power)))
+    }
+
+    /// Returns `value`, a `bit_size`-bit integer, with its bits rotated to the left by `amount`,
+    /// which must be smaller than `bit_size`.
+    ///
+    /// The value is split once into its top `amount` bits and the remaining ones, which are then
+    /// swapped, rather than shifted twice and recombined with a bitwise OR.
+    pub(crate) fn rotate_left_var(
+        &mut self,
+        value: AcirVar,
+        amount: u32,
+        bit_size: u32,
+    ) -> Result<AcirVar, RuntimeError> {
+        assert!(amount < bit_size, "ICE: rotations must be reduced modulo the bit size");
+        if amount == 0 {
+            return Ok(value);
+        }
+
+        let two = FieldElement::from(2_u128);
+
+        // value = high * 2^{bit_size - amount} + low
+        let divisor = self.add_constant(two.pow(&FieldElement::from((bit_size - amount) as u128)));
+        let one = self.add_constant(FieldElement::one());
+        let (high, low) = self.euclidean_division_var(value, divisor, bit_size, one)?;
+
+        // rotated = low * 2^{amount} + high
+        self.add_mul_var(high, two.pow(&FieldElement::from(amount as u128)), low)
+    }
+
     // Constrains `var` to be equal to predicate if the predicate is true
     // or to be equal to 0 if the predicate is false.
     //
@@ -404,9 +553,7 @@ impl AcirContext {
             let sum = self.add_var(lhs, rhs)?;
             self.add_mul_var(sum, -FieldElement::from(2_i128), prod)
         } else {
-            let inputs = vec![AcirValue::Var(lhs, typ.clone()), AcirValue::Var(rhs, typ)];
-            let outputs = self.black_box_function(BlackBoxFunc::XOR, inputs, 1)?;
-            Ok(outputs[0])
+            self.bitwise_var(BlackBoxFunc::XOR, lhs, rhs, typ)
         }
     }
 
@@ -434,9 +581,33 @@ impl AcirContext {
             // Operands are booleans.
             self.mul_var(lhs, rhs)
         } else {
-            let inputs = vec![AcirValue::Var(lhs, typ.clone()), AcirValue::Var(rhs, typ)];
-            let outputs = self.black_box_function(BlackBoxFunc::AND, inputs, 1)?;
-            Ok(outputs[0])
+            self.bitwise_var(BlackBoxFunc::AND, lhs, rhs, typ)
+        }
+    }
+
+    /// Returns an `AcirVar` that is the result of the bitwise operation `func` on `lhs` & `rhs`,
+    /// lowered in whichever way is cheapest for the backend.
+    fn bitwise_var(
+        &mut self,
+        func: BlackBoxFunc,
+        lhs: AcirVar,
+        rhs: AcirVar,
+        typ: AcirType,
+    ) -> Result<AcirVar, RuntimeError> {
+        let bit_size = typ.bit_size();
+        match BitwiseLowering::choose(self.acir_ir.cost_model(), func, bit_size) {
+            BitwiseLowering::BlackBox => {
+                let inputs = vec![AcirValue::Var(lhs, typ.clone()), AcirValue::Var(rhs, typ)];
+                let outputs = self.black_box_function(func, inputs, 1, None)?;
+                Ok(outputs[0])
+            }
+            BitwiseLowering::BitDecomposition => {
+                let lhs_expr = self.var_to_expression(lhs)?;
+                let rhs_expr = self.var_to_expression(rhs)?;
+                let result =
+                    self.acir_ir.bitwise_by_decomposition(func, &lhs_expr, &rhs_expr, bit_size)?;
+                Ok(self.add_data(AcirVarData::Expr(result)))
+            }
         }
     }
 
@@ -479,7 +650,7 @@ impl AcirContext {
         &mut self,
         lhs: AcirVar,
         rhs: AcirVar,
-        assert_message: Option<String>,
+        assert_message: Option<AssertionPayload>,
     ) -> Result<(), RuntimeError> {
         let lhs_expr = self.var_to_expression(lhs)?;
         let rhs_expr = self.var_to_expression(rhs)?;
@@ -496,13 +667,36 @@ impl AcirContext {
 
         self.acir_ir.assert_is_zero(diff_expr);
         if let Some(message) = assert_message {
-            self.acir_ir.assert_messages.insert(self.acir_ir.last_acir_opcode_location(), message);
+            self.acir_ir.set_last_assert_message(message);
         }
         self.mark_variables_equivalent(lhs, rhs)?;
 
         Ok(())
     }
 
+    /// Builds an assertion message out of text and the values of variables, which the solver
+    /// shows as they were when the assertion failed, e.g. `expected {rhs} got {lhs}`.
+    ///
+    /// Variables which are neither constants nor witnesses are assigned a witness, so this must
+    /// be called before emitting the opcode which the message is attached to.
+    pub(crate) fn assertion_payload(
+        &mut self,
+        parts: Vec<AssertionPart>,
+    ) -> Result<AssertionPayload, InternalError> {
+        let mut payload = AssertionPayload { parts: Vec::with_capacity(parts.len()) };
+        for part in parts {
+            let part = match part {
+                AssertionPart::Text(text) => AssertionPayloadPart::Text(text),
+                AssertionPart::Var(var) => match self.var_to_expression(var)?.to_const() {
+                    Some(constant) => AssertionPayloadPart::Text(constant.to_string()),
+                    None => AssertionPayloadPart::Witness(self.var_to_witness(var)?),
+                },
+            };
+            payload.parts.push(part);
+        }
+        Ok(payload)
+    }
+
     /// Adds a new Variable to context whose value will
     /// be constrained to be the division of `lhs` and `rhs`
     pub(crate) fn div_var(
@@ -530,7 +724,7 @@ impl AcirContext {
             }
             NumericType::Signed { bit_size } => {
                 let (quotient_var, _remainder_var) =
-                    self.signed_division_var(lhs, rhs, bit_size)?;
+                    self.signed_division_var(lhs, rhs, bit_size, predicate)?;
                 Ok(quotient_var)
             }
         }
@@ -575,8 +769,8 @@ impl AcirContext {
                 AcirVarData::Expr(_) | AcirVarData::Witness(_),
                 AcirVarData::Expr(_) | AcirVarData::Witness(_),
             ) => {
-                let lhs = self.get_or_create_witness_var(lhs)?;
-                let rhs = self.get_or_create_witness_var(rhs)?;
+                let lhs = self.reduce_to_witness_var(lhs)?;
+                let rhs = self.reduce_to_witness_var(rhs)?;
 
                 self.mul_var(lhs, rhs)?
             }
@@ -619,7 +813,10 @@ impl AcirContext {
     pub(crate) fn not_var(&mut self, x: AcirVar, typ: AcirType) -> Result<AcirVar, RuntimeError> {
         let bit_size = typ.bit_size();
         // Subtracting from max flips the bits
-        let max = self.add_constant((1_u128 << bit_size) - 1);
+        let max = self.add_constant(
+            FieldElement::from(2_u128).pow(&FieldElement::from(bit_size as u128))
+                - FieldElement::one(),
+        );
         self.sub_var(max, x)
     }
 
@@ -705,63 +902,30 @@ impl AcirContext {
             // q*b+r can overflow; we avoid this when b is constant
             if self.var_to_expression(rhs)?.is_const() {
                 avoid_overflow = true;
+            } else if bit_size <= 128 {
+                // `u128` operands are multiplied in limbs instead
+                return self.u128_euclidean_division_var(lhs, rhs, bit_size, predicate);
             } else {
                 // we do not support unbounded division
                 unreachable!("overflow in unbounded division");
             }
         }
 
-        let [q_value, r_value]: [AcirValue; 2] = self
-            .brillig(
-                predicate,
-                brillig_directive::directive_quotient(bit_size + 1),
-                vec![
-                    AcirValue::Var(lhs, AcirType::unsigned(bit_size)),
-                    AcirValue::Var(rhs, AcirType::unsigned(bit_size)),
-                ],
-                vec![AcirType::unsigned(max_q_bits), AcirType::unsigned(max_rhs_bits)],
-                true,
-            )?
-            .try_into()
-            .expect("quotient only returns two values");
-        let quotient_var = q_value.into_var()?;
-        let remainder_var = r_value.into_var()?;
-
-        // Constrain `q < 2^{max_q_bits}`.
-        self.range_constrain_var(
-            quotient_var,
-            &NumericType::Unsigned { bit_size: max_q_bits },
-            None,
-        )?;
-
-        // Constrain `r < 2^{max_rhs_bits}`.
-        //
-        // If `rhs` is a power of 2, then is just a looser version of the following bound constraint.
-        // In the case where `rhs` isn't a power of 2 then this range constraint is required
-        // as the bound constraint creates a new witness.
-        // This opcode will be optimized out if it is redundant so we always add it for safety.
-        self.range_constrain_var(
-            remainder_var,
-            &NumericType::Unsigned { bit_size: max_rhs_bits },
-            None,
+        let lhs_expr = self.var_to_expression(lhs)?;
+        let rhs_expr = self.var_to_expression(rhs)?;
+        let predicate_expr = self.var_to_expression(predicate)?;
+        let (quotient, remainder) = self.acir_ir.euclidean_division(
+            &lhs_expr,
+            &rhs_expr,
+            &predicate_expr,
+            bit_size,
+            max_q_bits,
+            max_rhs_bits,
         )?;
+        let quotient_var = self.add_data(AcirVarData::Witness(quotient));
+        let remainder_var = self.add_data(AcirVarData::Witness(remainder));
 
-        // Constrain `r < rhs`.
-        self.bound_constraint_with_offset(remainder_var, rhs, predicate, max_rhs_bits)?;
-
-        // a * predicate == (b * q + r) * predicate
-        // => predicate * (a - b * q - r) == 0
-        // When the predicate is 0, the equation always passes.
-        // When the predicate is 1, the euclidean division needs to be
-        // true.
-        let rhs_constraint = self.mul_var(rhs, quotient_var)?;
-        let rhs_constraint = self.add_var(rhs_constraint, remainder_var)?;
-        let rhs_constraint = self.mul_var(rhs_constraint, predicate)?;
-
-        let lhs_constraint = self.mul_var(lhs, predicate)?;
-        self.assert_eq_var(lhs_constraint, rhs_constraint, None)?;
-
-        if let Some(rhs_const) = self.var_to_expression(rhs)?.to_const() {
+        if let Some(rhs_const) = rhs_expr.to_const() {
             if avoid_overflow {
                 // we compute q0 = p/rhs
                 let rhs_big = BigUint::from_bytes_be(&rhs_const.to_be_bytes());
@@ -780,10 +944,13 @@ impl AcirContext {
                 let max_r_predicate = self.mul_var(predicate, max_r_var)?;
                 let r_predicate = self.mul_var(remainder_var, predicate)?;
                 // Bound the remainder to be <p-q0*b, if the predicate is true.
-                self.bound_constraint_with_offset(
-                    r_predicate,
-                    max_r_predicate,
-                    predicate,
+                let r_predicate = self.var_to_expression(r_predicate)?;
+                let max_r_predicate = self.var_to_expression(max_r_predicate)?;
+                let predicate = self.var_to_expression(predicate)?;
+                self.acir_ir.bound_constraint_with_offset(
+                    &r_predicate,
+                    &max_r_predicate,
+                    &predicate,
                     rhs_const.num_bits(),
                 )?;
             }
@@ -792,72 +959,6 @@ impl AcirContext {
         Ok((quotient_var, remainder_var))
     }
 
-    /// Generate constraints that are satisfied iff
-    /// lhs < rhs , when offset is 1, or
-    /// lhs <= rhs, when offset is 0
-    /// bits is the bit size of a and b (or an upper bound of the bit size)
-    ///
-    /// lhs<=rhs is done by constraining b-a to a bit size of 'bits':
-    /// if lhs<=rhs, 0 <= rhs-lhs <= b < 2^bits
-    /// if lhs>rhs, rhs-lhs = p+rhs-lhs > p-2^bits >= 2^bits  (if log(p) >= bits + 1)
-    /// n.b: we do NOT check here that lhs and rhs are indeed 'bits' size
-    /// lhs < rhs <=> a+1<=b
-    /// TODO: Consolidate this with bounds_check function.
-    pub(super) fn bound_constraint_with_offset(
-        &mut self,
-        lhs: AcirVar,
-        rhs: AcirVar,
-        offset: AcirVar,
-        bits: u32,
-    ) -> Result<(), RuntimeError> {
-        const fn num_bits<T>() -> usize {
-            std::mem::size_of::<T>() * 8
-        }
-
-        fn bit_size_u128(a: u128) -> u32 where {
-            num_bits::<u128>() as u32 - a.leading_zeros()
-        }
-
-        assert!(
-            bits < FieldElement::max_num_bits(),
-            "range check with bit size of the prime field is not implemented yet"
-        );
-
-        let mut lhs_offset = self.add_var(lhs, offset)?;
-
-        // Optimization when rhs is const and fits within a u128
-        let rhs_expr = self.var_to_expression(rhs)?;
-        if rhs_expr.is_const() && rhs_expr.q_c.fits_in_u128() {
-            // We try to move the offset to rhs
-            let rhs_offset = if self.is_constant_one(&offset) && rhs_expr.q_c.to_u128() >= 1 {
-                lhs_offset = lhs;
-                rhs_expr.q_c.to_u128() - 1
-            } else {
-                rhs_expr.q_c.to_u128()
-            };
-            // we now have lhs+offset <= rhs <=> lhs_offset <= rhs_offset
-
-            let bit_size = bit_size_u128(rhs_offset);
-            // r = 2^bit_size - rhs_offset -1, is of bit size  'bit_size' by construction
-            let r = (1_u128 << bit_size) - rhs_offset - 1;
-            // however, since it is a constant, we can compute it's actual bit size
-            let r_bit_size = bit_size_u128(r);
-            // witness = lhs_offset + r
-            assert!(bits + r_bit_size < FieldElement::max_num_bits()); //we need to ensure lhs_offset + r does not overflow
-
-            let r_var = self.add_constant(r);
-            let aor = self.add_var(lhs_offset, r_var)?;
-            // lhs_offset<=rhs_offset <=> lhs_offset + r < rhs_offset + r = 2^bit_size <=> witness < 2^bit_size
-            self.range_constrain_var(aor, &NumericType::Unsigned { bit_size }, None)?;
-            return Ok(());
-        }
-        // General case:  lhs_offset<=rhs <=> rhs-lhs_offset>=0 <=> rhs-lhs_offset is a 'bits' bit integer
-        let sub_expression = self.sub_var(rhs, lhs_offset)?; //rhs-lhs_offset
-        self.range_constrain_var(sub_expression, &NumericType::Unsigned { bit_size: bits }, None)?;
-
-        Ok(())
-    }
-
     // Returns the 2-complement of lhs, using the provided sign bit in 'leading'
     // if leading is zero, it returns lhs
     // if leading is one, it returns 2^bit_size-lhs
@@ -877,15 +978,37 @@ impl AcirContext {
         self.add_mul_var(lhs, FieldElement::from(2_i128), intermediate)
     }
 
+    /// Returns `-value`, in two's complement over `bit_size` bits, if `negate` is one and `value`
+    /// otherwise, where `value` is the magnitude of a signed integer.
+    ///
+    /// Unlike [`two_complement`][Self::two_complement], zero is left unchanged rather than being
+    /// mapped to `2^bit_size`.
+    fn negate_if(
+        &mut self,
+        value: AcirVar,
+        negate: AcirVar,
+        bit_size: u32,
+    ) -> Result<AcirVar, RuntimeError> {
+        let zero = self.add_constant(FieldElement::zero());
+        let value_is_zero = self.eq_var(value, zero)?;
+        let value_is_not_zero = self.not_var(value_is_zero, AcirType::unsigned(1))?;
+        let negate = self.mul_var(negate, value_is_not_zero)?;
+        self.two_complement(value, negate, bit_size)
+    }
+
     /// Returns the quotient and remainder such that lhs = rhs * quotient + remainder
     /// and |remainder| < |rhs|
     /// and remainder has the same sign than lhs
     /// Note that this is not the euclidean division, where we have instead remainder < |rhs|
+    ///
+    /// The quotient is rounded towards zero, and dividing the smallest value of the type by -1
+    /// wraps around to the smallest value.
     fn signed_division_var(
         &mut self,
         lhs: AcirVar,
         rhs: AcirVar,
         bit_size: u32,
+        predicate: AcirVar,
     ) -> Result<(AcirVar, AcirVar), RuntimeError> {
         // We derive the signed division from the unsigned euclidean division.
         // note that this is not euclidean division!
@@ -913,29 +1036,38 @@ impl AcirContext {
         let unsigned_lhs = self.two_complement(lhs, lhs_leading, bit_size)?;
         let unsigned_rhs = self.two_complement(rhs, rhs_leading, bit_size)?;
 
-        // Performs the division using the unsigned values of lhs and rhs
+        // Performs the division using the unsigned values of lhs and rhs.
+        // The magnitude of the smallest value of the type is 2^{bit_size-1}, which needs all of
+        // `bit_size` bits.
         let (q1, r1) =
-            self.euclidean_division_var(unsigned_lhs, unsigned_rhs, bit_size - 1, one)?;
+            self.euclidean_division_var(unsigned_lhs, unsigned_rhs, bit_size, predicate)?;
 
         // Unsigned to signed: derive q and r from q1,r1 and the signs of lhs and rhs
         // Quotient sign is lhs sign * rhs sign, whose resulting sign bit is the XOR of the sign bits
         let q_sign = self.xor_var(lhs_leading, rhs_leading, AcirType::unsigned(1))?;
 
-        let quotient = self.two_complement(q1, q_sign, bit_size)?;
-        let remainder = self.two_complement(r1, lhs_leading, bit_size)?;
+        let quotient = self.negate_if(q1, q_sign, bit_size)?;
+        let remainder = self.negate_if(r1, lhs_leading, bit_size)?;
 
         Ok((quotient, remainder))
     }
 
     /// Returns a variable which is constrained to be `lhs mod rhs`
+    ///
+    /// For signed integers the remainder has the sign of `lhs`, as with the `%` operator in Rust.
     pub(crate) fn modulo_var(
         &mut self,
         lhs: AcirVar,
         rhs: AcirVar,
-        bit_size: u32,
+        typ: AcirType,
         predicate: AcirVar,
     ) -> Result<AcirVar, RuntimeError> {
-        let (_, remainder) = self.euclidean_division_var(lhs, rhs, bit_size, predicate)?;
+        let (_, remainder) = match typ {
+            AcirType::NumericType(NumericType::Signed { bit_size }) => {
+                self.signed_division_var(lhs, rhs, bit_size, predicate)?
+            }
+            _ => self.euclidean_division_var(lhs, rhs, typ.bit_size(), predicate)?,
+        };
         Ok(remainder)
     }
 
@@ -953,25 +1085,29 @@ impl AcirContext {
         &mut self,
         variable: AcirVar,
         numeric_type: &NumericType,
-        message: Option<String>,
+        message: Option<AssertionPayload>,
     ) -> Result<AcirVar, RuntimeError> {
         match numeric_type {
             NumericType::Signed { bit_size } | NumericType::Unsigned { bit_size } => {
                 // If `variable` is constant then we don't need to add a constraint.
                 // We _do_ add a constraint if `variable` would fail the range check however so that we throw an error.
-                if let Some(constant) = self.var_to_expression(variable)?.to_const() {
+                let expression = self.var_to_expression(variable)?;
+                if let Some(constant) = expression.to_const() {
                     if constant.num_bits() <= *bit_size {
                         return Ok(variable);
                     }
                 }
+                self.acir_ir.check_range_can_be_satisfied(&expression, *bit_size)?;
 
                 let witness_var = self.get_or_create_witness_var(variable)?;
                 let witness = self.var_to_witness(witness_var)?;
+                if self.acir_ir.fits_in_bits(witness, *bit_size) {
+                    // No constraint would be emitted to attach `message` to.
+                    return Ok(variable);
+                }
                 self.acir_ir.range_constraint(witness, *bit_size)?;
                 if let Some(message) = message {
-                    self.acir_ir
-                        .assert_messages
-                        .insert(self.acir_ir.last_acir_opcode_location(), message);
+                    self.acir_ir.set_last_assert_message(message);
                 }
             }
             NumericType::NativeField => {
@@ -981,15 +1117,48 @@ impl AcirContext {
         Ok(variable)
     }
 
+    /// Requests that the `AcirVar` variable is constrained to be of type `NumericType`, alongside
+    /// other such requests up to the next call to
+    /// [`flush_range_constraints`][Self::flush_range_constraints].
+    pub(crate) fn range_constrain_var_batched(
+        &mut self,
+        variable: AcirVar,
+        numeric_type: &NumericType,
+    ) -> Result<(), RuntimeError> {
+        match numeric_type {
+            NumericType::Signed { bit_size } | NumericType::Unsigned { bit_size } => {
+                let witness_var = self.get_or_create_witness_var(variable)?;
+                let witness = self.var_to_witness(witness_var)?;
+                self.acir_ir.range_constraint_batch(witness, *bit_size)
+            }
+            NumericType::NativeField => Ok(()),
+        }
+    }
+
+    /// Emits the range constraints requested through
+    /// [`range_constrain_var_batched`][Self::range_constrain_var_batched].
+    pub(crate) fn flush_range_constraints(
+        &mut self,
+        new_block_id: impl FnMut() -> BlockId,
+    ) -> Result<(), RuntimeError> {
+        self.acir_ir.flush_range_constraints(new_block_id)
+    }
+
     /// Returns an `AcirVar` which will be constrained to be lhs mod 2^{rhs}
     /// In order to do this, we 'simply' perform euclidean division of lhs by 2^{rhs}
     /// The remainder of the division is then lhs mod 2^{rhs}
+    ///
+    /// No opcodes are emitted if `lhs` is already known to fit in `rhs` bits.
     pub(crate) fn truncate_var(
         &mut self,
         lhs: AcirVar,
         rhs: u32,
         max_bit_size: u32,
     ) -> Result<AcirVar, RuntimeError> {
+        if self.fits_in_bits(lhs, rhs)? {
+            return Ok(lhs);
+        }
+
         // 2^{rhs}
         let divisor =
             self.add_constant(FieldElement::from(2_u128).pow(&FieldElement::from(rhs as u128)));
@@ -1001,6 +1170,12 @@ impl AcirContext {
         Ok(remainder)
     }
 
+    /// Returns true if every value which `variable` can take is known to fit in `num_bits` bits.
+    fn fits_in_bits(&self, variable: AcirVar, num_bits: u32) -> Result<bool, InternalError> {
+        let expression = self.var_to_expression(variable)?;
+        Ok(self.acir_ir.bounds(&expression).is_some_and(|(_, upper)| upper.num_bits() <= num_bits))
+    }
+
     /// Returns an 'AcirVar' containing the boolean value lhs<rhs, assuming lhs and rhs are signed integers of size bit_count.
     /// Like in the unsigned case, we compute the difference diff = lhs-rhs+2^n (and we avoid underflow)
     /// The result depends on the diff and the signs of the inputs:
@@ -1140,11 +1315,15 @@ impl AcirContext {
 
     /// Calls a Blackbox function on the given inputs and returns a given set of outputs
     /// to represent the result of the blackbox function.
+    ///
+    /// If a `predicate` is given, the function is called on zeros instead of its inputs when the
+    /// predicate is zero.
     pub(crate) fn black_box_function(
         &mut self,
         name: BlackBoxFunc,
         mut inputs: Vec<AcirValue>,
         mut output_count: usize,
+        predicate: Option<AcirVar>,
     ) -> Result<Vec<AcirVar>, RuntimeError> {
         // Separate out any arguments that should be constants
         let (constant_inputs, constant_outputs) = match name {
@@ -1198,6 +1377,35 @@ impl AcirContext {
 
                 (vec![state_len], Vec::new())
             }
+            BlackBoxFunc::PoseidonPermutation => {
+                // The last two arguments are the state length and the rate, which must be constants
+                let mut parameters = Vec::with_capacity(2);
+                for name in ["rate", "length"] {
+                    let parameter = match inputs.pop() {
+                        Some(parameter) => parameter.into_var()?,
+                        None => {
+                            return Err(RuntimeError::InternalError(InternalError::MissingArg {
+                                name: "poseidon_permutation call".to_string(),
+                                arg: name.to_string(),
+                                call_stack: self.get_call_stack(),
+                            }))
+                        }
+                    };
+                    match self.vars[&parameter].as_constant() {
+                        Some(parameter) => parameters.push(parameter),
+                        None => {
+                            return Err(RuntimeError::InternalError(InternalError::NotAConstant {
+                                name: name.to_string(),
+                                call_stack: self.get_call_stack(),
+                            }))
+                        }
+                    }
+                }
+                // The length comes before the rate in the constant inputs of the opcode.
+                parameters.reverse();
+
+                (parameters, Vec::new())
+            }
             BlackBoxFunc::BigIntAdd
             | BlackBoxFunc::BigIntSub
             | BlackBoxFunc::BigIntMul
@@ -1282,11 +1490,49 @@ impl AcirContext {
                     self.big_int_ctx.new_big_int(FieldElement::from(modulus_id as u128));
                 (modulus, vec![result_id.bigint_id(), result_id.modulus_id()])
             }
+            BlackBoxFunc::Keccak256 => {
+                // When the whole message is hashed, the message size is not needed by the opcode,
+                // which can then be the cheaper fixed-length one.
+                let message_len = match inputs.first() {
+                    Some(AcirValue::Array(values)) => Some(values.len()),
+                    Some(AcirValue::DynamicArray(array)) => Some(array.len),
+                    _ => None,
+                };
+                let message_size = match inputs.last() {
+                    Some(AcirValue::Var(var, _)) => self.vars[var].as_constant(),
+                    _ => None,
+                };
+                if inputs.len() == 2
+                    && message_len.is_some()
+                    && message_size.map(|size| size.to_u128() as usize) == message_len
+                {
+                    inputs.pop();
+                }
+                (vec![], vec![])
+            }
+            BlackBoxFunc::AES128Encrypt => {
+                // The ciphertext is a slice which is padded to the next whole block, so we
+                // return its length ahead of its contents.
+                let input_size = match inputs.first() {
+                    Some(AcirValue::Array(values)) => values.len(),
+                    Some(AcirValue::DynamicArray(array)) => array.len,
+                    _ => {
+                        return Err(RuntimeError::InternalError(InternalError::MissingArg {
+                            name: "aes128_encrypt call".to_string(),
+                            arg: "plaintext".to_string(),
+                            call_stack: self.get_call_stack(),
+                        }))
+                    }
+                };
+                output_count = input_size + (16 - input_size % 16);
+                (vec![], vec![FieldElement::from(output_count as u128)])
+            }
             _ => (vec![], vec![]),
         };
 
         // Convert `AcirVar` to `FunctionInput`
         let inputs = self.prepare_inputs_for_black_box_func_call(inputs)?;
+        let predicate = predicate.map(|predicate| self.var_to_expression(predicate)).transpose()?;
         // Call Black box with `FunctionInput`
         let mut results = vecmap(&constant_outputs, |c| self.add_constant(*c));
         let outputs = self.acir_ir.call_black_box(
@@ -1295,6 +1541,7 @@ impl AcirContext {
             constant_inputs,
             constant_outputs,
             output_count,
+            predicate.as_ref(),
         )?;
 
         // Convert `Witness` values which are now constrained to be the output of the
@@ -1308,6 +1555,23 @@ impl AcirContext {
         Ok(results)
     }
 
+    /// Absorbs `inputs` into `transcript` and returns `count` challenges derived from them.
+    ///
+    /// Gadgets which batch their checks using random linear combinations should draw their
+    /// challenges from here.
+    pub(crate) fn transcript_challenges(
+        &mut self,
+        transcript: &mut Transcript,
+        inputs: &[AcirVar],
+        count: usize,
+    ) -> Result<Vec<AcirVar>, RuntimeError> {
+        let witnesses = try_vecmap(inputs, |input| self.var_to_witness(*input))?;
+        transcript.absorb(&witnesses);
+
+        let challenges = transcript.squeeze_challenges(&mut self.acir_ir, count)?;
+        Ok(vecmap(challenges, |challenge| self.add_data(AcirVarData::Witness(challenge))))
+    }
+
     /// Black box function calls expect their inputs to be in a specific data structure (FunctionInput).
     ///
     /// This function will convert `AcirVar` into `FunctionInput` for a blackbox function call.
@@ -1369,17 +1633,19 @@ impl AcirContext {
         let input_expr = self.var_to_expression(input_var)?;
 
         let bit_size = u32::BITS - (radix - 1).leading_zeros();
-        let limbs = self.acir_ir.radix_le_decompose(&input_expr, radix, limb_count, bit_size)?;
+        let limbs = self.acir_ir.radix_decompose_deferred(
+            endian,
+            &input_expr,
+            radix,
+            limb_count,
+            bit_size,
+        )?;
 
-        let mut limb_vars = vecmap(limbs, |witness| {
+        let limb_vars = vecmap(limbs, |witness| {
             let witness = self.add_data(AcirVarData::Witness(witness));
             AcirValue::Var(witness, result_element_type.clone())
         });
 
-        if endian == Endian::Big {
-            limb_vars.reverse();
-        }
-
         // `Intrinsic::ToRadix` returns slices which are represented
         // by tuples with the structure (length, slice contents)
         Ok(vec![
@@ -1418,10 +1684,11 @@ impl AcirContext {
         mut self,
         inputs: Vec<Witness>,
         warnings: Vec<SsaReport>,
-    ) -> GeneratedAcir {
+    ) -> Result<GeneratedAcir, RuntimeError> {
+        self.acir_ir.flush_deferred_byte_constraints()?;
         self.acir_ir.input_witnesses = inputs;
-        self.acir_ir.warnings = warnings;
-        self.acir_ir
+        self.acir_ir.warnings.extend(warnings);
+        Ok(self.acir_ir)
     }
 
     /// Adds `Data` into the context and assigns it a Variable.
@@ -1521,6 +1788,40 @@ impl AcirContext {
         Ok(outputs_var)
     }
 
+    /// Calls the circuit `id` of the program with `inputs` unless `predicate` is false, returning
+    /// values of the `outputs` types which hold its return values.
+    pub(crate) fn call_acir_function(
+        &mut self,
+        id: u32,
+        predicate: AcirVar,
+        inputs: Vec<AcirValue>,
+        outputs: Vec<AcirType>,
+    ) -> Result<Vec<AcirValue>, RuntimeError> {
+        let mut input_expressions = Vec::new();
+        for input in inputs {
+            self.brillig_array_input(&mut input_expressions, input)?;
+        }
+        let inputs = vecmap(input_expressions, |input| self.acir_ir.get_or_create_witness(&input));
+
+        let mut output_witnesses = Vec::new();
+        let output_values = vecmap(outputs, |output| match output {
+            AcirType::NumericType(_) => {
+                let witness_index = self.acir_ir.next_witness_index();
+                output_witnesses.push(witness_index);
+                let var = self.add_data(AcirVarData::Witness(witness_index));
+                AcirValue::Var(var, output.clone())
+            }
+            AcirType::Array(element_types, size) => {
+                let (acir_value, mut witnesses) = self.brillig_array_output(&element_types, size);
+                output_witnesses.append(&mut witnesses);
+                acir_value
+            }
+        });
+        let predicate = Some(self.var_to_expression(predicate)?);
+        self.acir_ir.push_opcode(Opcode::Call { id, inputs, outputs: output_witnesses, predicate });
+        Ok(output_values)
+    }
+
     fn brillig_array_input(
         &mut self,
         var_expressions: &mut Vec<Expression>,
@@ -1656,7 +1957,12 @@ impl AcirContext {
         });
 
         // Enforce the outputs to be a permutation of the inputs
-        self.acir_ir.permutation(&inputs_expr, &output_expr)?;
+        match SortStrategy::choose(self.acir_ir.cost_model(), len) {
+            SortStrategy::SortingNetwork => self.acir_ir.permutation(&inputs_expr, &output_expr)?,
+            SortStrategy::GrandProduct => {
+                self.acir_ir.grand_product_permutation(&inputs_expr, &outputs_witness, bit_size)?;
+            }
+        }
 
         // Enforce the outputs to be sorted
         for i in 0..(outputs_var.len() - 1) {
@@ -1700,6 +2006,20 @@ impl AcirContext {
         Ok(value_read_var)
     }
 
+    /// Returns a Variable that is constrained to be `values[index]`, reading it from a
+    /// read-only memory block which is allocated with `new_block_id` unless a block holding the
+    /// same values already exists.
+    pub(crate) fn lookup_table_var(
+        &mut self,
+        values: &[FieldElement],
+        index: AcirVar,
+        new_block_id: impl FnOnce() -> BlockId,
+    ) -> Result<AcirVar, RuntimeError> {
+        let index = self.var_to_expression(index)?;
+        let value = self.acir_ir.lookup_table(values, &index, new_block_id)?;
+        Ok(self.add_data(AcirVarData::Witness(value)))
+    }
+
     /// Constrains the Variable `value` to be the new value located at `index` in the memory `block_id`.
     pub(crate) fn write_to_memory(
         &mut self,
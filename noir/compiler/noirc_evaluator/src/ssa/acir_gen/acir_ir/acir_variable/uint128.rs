@@ -0,0 +1,126 @@
+//! Arithmetic over `u128` values, which are held in a single field element like any other integer.
+//!
+//! Sums, differences and comparisons of two `u128` values fit in the field, so the usual
+//! gadgets apply to them. Their products do not: a product of two 128-bit values may wrap around
+//! the field modulus, after which neither its truncation nor its range check says anything about
+//! the integer product. Multiplications, and the divisions which are constrained with one, split
+//! their operands into two 64-bit limbs so that every partial product fits in the field.
+use acvm::FieldElement;
+
+use crate::errors::RuntimeError;
+use crate::ssa::acir_gen::AcirValue;
+
+use super::{brillig_directive, AcirContext, AcirType, AcirVar};
+
+/// Number of bits in each of the two limbs of a `u128`.
+const LIMB_BITS: u32 = 64;
+
+impl AcirContext {
+    /// Returns the `(high, low)` 64-bit limbs of `value`, which must fit in 128 bits.
+    fn u128_limbs(&mut self, value: AcirVar) -> Result<(AcirVar, AcirVar), RuntimeError> {
+        let limb_base = self.add_constant(power_of_two(LIMB_BITS));
+        let one = self.add_constant(FieldElement::one());
+        self.euclidean_division_var(value, limb_base, 2 * LIMB_BITS, one)
+    }
+
+    /// Returns the product of two `u128` values, reduced modulo `2^128` when it does not fit in
+    /// 128 bits and offset by `2^128` in that case.
+    ///
+    /// The result is therefore congruent to the product modulo `2^128`, and fits in 128 bits
+    /// exactly when the product does, so it can be truncated or range checked as the result of
+    /// a multiplication of smaller integers would be.
+    pub(crate) fn u128_mul_var(
+        &mut self,
+        lhs: AcirVar,
+        rhs: AcirVar,
+    ) -> Result<AcirVar, RuntimeError> {
+        // Products of operands whose bounds are known to be small enough cannot wrap the field.
+        let max_product_bits = self.max_num_bits(lhs)? + self.max_num_bits(rhs)?;
+        if max_product_bits < FieldElement::max_num_bits() {
+            return self.mul_var(lhs, rhs);
+        }
+
+        let (lhs_hi, lhs_lo) = self.u128_limbs(lhs)?;
+        let (rhs_hi, rhs_lo) = self.u128_limbs(rhs)?;
+
+        // lhs * rhs = lo + mid * 2^64 + hi * 2^128
+        let lo = self.mul_var(lhs_lo, rhs_lo)?;
+        let mid_lhs = self.mul_var(lhs_lo, rhs_hi)?;
+        let mid_rhs = self.mul_var(lhs_hi, rhs_lo)?;
+        let mid = self.add_var(mid_lhs, mid_rhs)?;
+        let hi = self.mul_var(lhs_hi, rhs_hi)?;
+
+        // lo + mid * 2^64 < 2^194, whose bits above 128 carry into the high part.
+        let low = self.add_mul_var(lo, power_of_two(LIMB_BITS), mid)?;
+        let modulus = self.add_constant(power_of_two(2 * LIMB_BITS));
+        let one = self.add_constant(FieldElement::one());
+        let (carry, wrapped) = self.euclidean_division_var(low, modulus, 3 * LIMB_BITS + 2, one)?;
+
+        let overflow = self.add_var(carry, hi)?;
+        let zero = self.add_constant(FieldElement::zero());
+        let fits = self.eq_var(overflow, zero)?;
+        let overflowed = self.not_var(fits, AcirType::unsigned(1))?;
+        self.add_mul_var(wrapped, power_of_two(2 * LIMB_BITS), overflowed)
+    }
+
+    /// Returns the quotient and remainder of the division of two `u128` values, whose product
+    /// `rhs * quotient` is constrained without wrapping the field.
+    pub(super) fn u128_euclidean_division_var(
+        &mut self,
+        lhs: AcirVar,
+        rhs: AcirVar,
+        bit_size: u32,
+        predicate: AcirVar,
+    ) -> Result<(AcirVar, AcirVar), RuntimeError> {
+        let outputs = self.brillig(
+            predicate,
+            brillig_directive::directive_quotient(bit_size + 1),
+            vec![
+                AcirValue::Var(lhs, AcirType::unsigned(bit_size)),
+                AcirValue::Var(rhs, AcirType::unsigned(bit_size)),
+            ],
+            vec![AcirType::unsigned(bit_size), AcirType::unsigned(bit_size)],
+            true,
+        )?;
+        let [quotient, remainder]: [AcirValue; 2] =
+            outputs.try_into().expect("directive_quotient only returns two values");
+        let quotient = quotient.into_var()?;
+        let remainder = remainder.into_var()?;
+
+        // remainder < rhs
+        let remainder_expr = self.var_to_expression(remainder)?;
+        let rhs_expr = self.var_to_expression(rhs)?;
+        let predicate_expr = self.var_to_expression(predicate)?;
+        self.acir_ir.bound_constraint_with_offset(
+            &remainder_expr,
+            &rhs_expr,
+            &predicate_expr,
+            bit_size,
+        )?;
+
+        // lhs == rhs * quotient + remainder, where `rhs * quotient` only fits in 128 bits if the
+        // integer product does, as `lhs` must.
+        let product = self.u128_mul_var(quotient, rhs)?;
+        let rhs_times_quotient_plus_remainder = self.add_var(product, remainder)?;
+        let difference = self.sub_var(lhs, rhs_times_quotient_plus_remainder)?;
+        let predicated_difference = self.mul_var(difference, predicate)?;
+        let zero = self.add_constant(FieldElement::zero());
+        self.assert_eq_var(predicated_difference, zero, None)?;
+
+        Ok((quotient, remainder))
+    }
+
+    /// Returns the number of bits needed for the largest value which `variable` can take, or the
+    /// size of the field if its bounds are unknown.
+    fn max_num_bits(&self, variable: AcirVar) -> Result<u32, RuntimeError> {
+        let expression = self.var_to_expression(variable)?;
+        Ok(self
+            .acir_ir
+            .bounds(&expression)
+            .map_or(FieldElement::max_num_bits(), |(_, upper)| upper.num_bits()))
+    }
+}
+
+fn power_of_two(exponent: u32) -> FieldElement {
+    FieldElement::from(2_u128).pow(&FieldElement::from(exponent as u128))
+}
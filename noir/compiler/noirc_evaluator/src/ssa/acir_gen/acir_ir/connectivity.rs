@@ -0,0 +1,208 @@
+//! Finds the groups of opcodes which do not involve any input or return value of the program,
+//! even through other opcodes, as their constraints can then be satisfied independently of the
+//! rest of the circuit.
+use std::collections::{HashMap, HashSet};
+
+use acvm::acir::{
+    circuit::{
+        brillig::{Brillig, BrilligInputs},
+        opcodes::{BlackBoxFuncCall, BlockId},
+        Opcode, OpcodeLocation,
+    },
+    native_types::Witness,
+};
+
+use crate::errors::{InternalWarning, SsaReport};
+
+use super::{generated_acir::GeneratedAcir, witness_renaming::rename_opcode_witnesses};
+
+/// A value which opcodes may share, so that they belong to the same component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Node {
+    Witness(Witness),
+    Block(BlockId),
+    BigInt(u32),
+}
+
+#[derive(Default)]
+struct Components {
+    parents: HashMap<Node, Node>,
+}
+
+impl Components {
+    fn find(&mut self, node: Node) -> Node {
+        let mut root = node;
+        while let Some(&parent) = self.parents.get(&root) {
+            root = parent;
+        }
+        // Point every node on the path directly at the root, so that later lookups are short.
+        let mut current = node;
+        while current != root {
+            current = self.parents.insert(current, root).expect("node is on the path to the root");
+        }
+        root
+    }
+
+    fn union(&mut self, lhs: Node, rhs: Node) {
+        let (lhs, rhs) = (self.find(lhs), self.find(rhs));
+        if lhs != rhs {
+            self.parents.insert(lhs, rhs);
+        }
+    }
+}
+
+impl GeneratedAcir {
+    /// Warns about each group of opcodes which share no witness, memory block or big integer with
+    /// the `connected` witnesses, even through other opcodes.
+    ///
+    /// Opcodes which only involve constants are ignored, as are components none of whose opcodes
+    /// has a location.
+    pub(crate) fn warn_disconnected_components(
+        &mut self,
+        connected: impl IntoIterator<Item = Witness>,
+    ) {
+        let connected: Vec<_> = connected.into_iter().map(Node::Witness).collect();
+        if connected.is_empty() {
+            // Every opcode would be reported.
+            return;
+        }
+
+        let mut components = Components::default();
+        let opcode_nodes: Vec<_> = self.opcodes().iter().map(opcode_nodes).collect();
+        for nodes in &opcode_nodes {
+            for pair in nodes.windows(2) {
+                components.union(pair[0], pair[1]);
+            }
+        }
+        let connected: HashSet<_> =
+            connected.into_iter().map(|node| components.find(node)).collect();
+
+        // The indices of the opcodes of each disconnected component, in order of first opcode.
+        let mut disconnected: Vec<Vec<usize>> = Vec::new();
+        let mut component_indices: HashMap<Node, usize> = HashMap::new();
+        for (index, nodes) in opcode_nodes.iter().enumerate() {
+            let Some(&node) = nodes.first() else { continue };
+            let root = components.find(node);
+            if connected.contains(&root) {
+                continue;
+            }
+            let component = *component_indices.entry(root).or_insert_with(|| {
+                disconnected.push(Vec::new());
+                disconnected.len() - 1
+            });
+            disconnected[component].push(index);
+        }
+
+        for opcodes in disconnected {
+            let call_stack = opcodes
+                .iter()
+                .find_map(|index| self.locations.get(&OpcodeLocation::Acir(*index)))
+                .filter(|call_stack| !call_stack.is_root());
+            if let Some(call_stack) = call_stack {
+                self.warnings.push(SsaReport::Warning(InternalWarning::DisconnectedComponent {
+                    opcodes: opcodes.len(),
+                    call_stack: self.call_stacks.resolve(*call_stack),
+                }));
+            }
+        }
+    }
+}
+
+/// Returns the witnesses, memory blocks and big integers which `opcode` involves.
+fn opcode_nodes(opcode: &Opcode) -> Vec<Node> {
+    let mut nodes = Vec::new();
+    rename_opcode_witnesses(&mut opcode.clone(), &mut |witness| {
+        nodes.push(Node::Witness(witness));
+        witness
+    });
+    match opcode {
+        Opcode::MemoryInit { block_id, .. } | Opcode::MemoryOp { block_id, .. } => {
+            nodes.push(Node::Block(*block_id));
+        }
+        Opcode::Brillig(Brillig { inputs, .. }) | Opcode::BrilligCall { inputs, .. } => {
+            nodes.extend(inputs.iter().filter_map(|input| match input {
+                BrilligInputs::MemoryArray(block_id) => Some(Node::Block(*block_id)),
+                _ => None,
+            }));
+        }
+        Opcode::BlackBoxFuncCall(
+            BlackBoxFuncCall::BigIntAdd { lhs, rhs, output }
+            | BlackBoxFuncCall::BigIntSub { lhs, rhs, output }
+            | BlackBoxFuncCall::BigIntMul { lhs, rhs, output }
+            | BlackBoxFuncCall::BigIntDiv { lhs, rhs, output },
+        ) => {
+            nodes.extend([lhs, rhs, output].map(|id| Node::BigInt(*id)));
+        }
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::BigIntFromLeBytes { output, .. }) => {
+            nodes.push(Node::BigInt(*output));
+        }
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::BigIntToLeBytes { input, .. }) => {
+            nodes.push(Node::BigInt(*input));
+        }
+        _ => (),
+    }
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::{
+        acir::{
+            circuit::{
+                opcodes::{BlockId, MemOp},
+                Opcode,
+            },
+            native_types::{Expression, Witness},
+        },
+        FieldElement,
+    };
+    use noirc_errors::{Location, Span};
+
+    use crate::{
+        errors::{InternalWarning, SsaReport},
+        ssa::acir_gen::acir_ir::generated_acir::GeneratedAcir,
+    };
+
+    fn sum(witnesses: &[u32]) -> Opcode {
+        let mut expression = Expression::default();
+        for witness in witnesses {
+            expression.push_addition_term(FieldElement::one(), Witness(*witness));
+        }
+        Opcode::AssertZero(expression)
+    }
+
+    #[test]
+    fn reports_components_without_inputs_or_return_values() {
+        let mut acir = GeneratedAcir::default();
+        let block_id = BlockId(0);
+        let opcodes = vec![
+            // Connected to the input `w0` through the memory block.
+            sum(&[0, 1]),
+            Opcode::MemoryInit { block_id, init: vec![Witness(1)] },
+            Opcode::MemoryOp {
+                block_id,
+                op: MemOp::read_at_mem_index(Expression::zero(), Witness(2)),
+                predicate: None,
+            },
+            sum(&[2, 3]),
+            // Disconnected, as none of `w4`, `w5` and `w6` relates to `w0` or `w3`.
+            sum(&[4, 5]),
+            sum(&[5, 6]),
+            Opcode::AssertZero(Expression::one()),
+        ];
+        for (index, opcode) in opcodes.into_iter().enumerate() {
+            let location = Location::new(Span::single_char(index as u32), Default::default());
+            acir.call_stack = vec![location].into();
+            acir.push_opcode(opcode);
+        }
+
+        acir.warn_disconnected_components([Witness(0), Witness(3)]);
+        let [SsaReport::Warning(InternalWarning::DisconnectedComponent { opcodes, call_stack })] =
+            acir.warnings.as_slice()
+        else {
+            panic!("expected a single disconnected component, got {:?}", acir.warnings);
+        };
+        assert_eq!(*opcodes, 2);
+        assert_eq!(call_stack[0].span, Span::single_char(4));
+    }
+}
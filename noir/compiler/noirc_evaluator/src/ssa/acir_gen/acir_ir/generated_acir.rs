@@ -1,28 +1,48 @@
 //! `GeneratedAcir` is constructed as part of the `acir_gen` pass to accumulate all of the ACIR
 //! program as it is being converted from SSA form.
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    ops::Range,
+    sync::Arc,
+};
 
 use crate::{
     brillig::{brillig_gen::brillig_directive, brillig_ir::artifact::GeneratedBrillig},
-    errors::{InternalError, RuntimeError, SsaReport},
-    ssa::ir::dfg::CallStack,
+    cost_model::{CostModel, DefaultCostModel},
+    errors::{InternalError, InternalWarning, RuntimeError, SsaReport},
+    opcode_sink::OpcodeSink,
+    spans,
+    ssa::{
+        ir::{
+            call_stack::{CallStackId, CallStackTree},
+            dfg::CallStack,
+            instruction::Endian,
+            types::NumericType,
+        },
+        InputParameter, ReturnGroup,
+    },
+};
+
+use super::{
+    strategy::{IsZeroLowering, RangeBatchEncoding, RangeEncoding},
+    witness_renaming::{rename_assertion_payload, rename_expression, rename_opcode_witnesses},
 };
 
 use acvm::acir::{
+    brillig::Opcode as BrilligOpcode,
     circuit::{
-        brillig::{Brillig as AcvmBrillig, BrilligInputs, BrilligOutputs},
-        opcodes::{BlackBoxFuncCall, FunctionInput, Opcode as AcirOpcode},
-        OpcodeLocation,
+        brillig::{BrilligBytecode, BrilligInputs, BrilligOutputs},
+        opcodes::{BlackBoxFuncCall, BlockId, FunctionInput, MemOp, Opcode as AcirOpcode},
+        AssertionPayload, OpcodeLocation,
     },
     native_types::Witness,
     BlackBoxFunc,
 };
-use acvm::{
-    acir::{circuit::directives::Directive, native_types::Expression},
-    FieldElement,
-};
+use acvm::{acir::native_types::Expression, FieldElement};
 use iter_extended::vecmap;
-use num_bigint::BigUint;
+use noirc_errors::{debug_info::BrilligVariableInfo, Location};
+use noirc_frontend::Visibility;
+use num_bigint::{BigInt, BigUint, Sign};
 
 #[derive(Debug, Default)]
 /// The output of the Acir-gen pass
@@ -36,42 +56,305 @@ pub(crate) struct GeneratedAcir {
     current_witness_index: Option<u32>,
 
     /// The opcodes of which the compiled ACIR will comprise.
+    ///
+    /// If an `opcode_sink` is set, this only holds the opcodes which have not been streamed to it
+    /// yet.
     opcodes: Vec<AcirOpcode>,
 
+    /// The sink which opcodes are streamed to as they are pushed, if any.
+    opcode_sink: Option<Box<dyn OpcodeSink>>,
+
+    /// Number of opcodes streamed to `opcode_sink`, which come before those in `opcodes`.
+    streamed_opcodes: usize,
+
+    /// The first error returned by `opcode_sink`, after which no more opcodes are written to it.
+    opcode_sink_error: Option<std::io::Error>,
+
     /// All witness indices that comprise the final return value of the program
     ///
     /// Note: This may contain repeated indices, which is necessary for later mapping into the
     /// abi's return type.
     pub(crate) return_witnesses: Vec<Witness>,
 
+    /// The return witnesses split into named groups, if the return value of the program has
+    /// named parts which callers may want to address separately.
+    pub(crate) return_groups: Vec<ReturnGroup>,
+
     /// All witness indices which are inputs to the main function
     pub(crate) input_witnesses: Vec<Witness>,
 
+    /// The input witnesses split into the parameters of the main function which they hold, with
+    /// the visibility of each.
+    pub(crate) input_parameters: Vec<InputParameter>,
+
     /// Correspondence between an opcode index (in opcodes) and the source code call stack which generated it
-    pub(crate) locations: BTreeMap<OpcodeLocation, CallStack>,
+    ///
+    /// The call stacks are interned in `call_stacks`, and resolved with
+    /// [`opcode_call_stack`][Self::opcode_call_stack].
+    pub(crate) locations: BTreeMap<OpcodeLocation, CallStackId>,
+
+    pub(crate) call_stacks: CallStackTree,
 
     /// Source code location of the current instruction being processed
     /// None if we do not know the location
     pub(crate) call_stack: CallStack,
 
     /// Correspondence between an opcode index and the error message associated with it.
-    pub(crate) assert_messages: BTreeMap<OpcodeLocation, String>,
+    pub(crate) assert_messages: BTreeMap<OpcodeLocation, AssertionPayload>,
 
     pub(crate) warnings: Vec<SsaReport>,
+
+    /// The variable of the source code which each witness holds the value of, if any.
+    pub(crate) witness_names: BTreeMap<Witness, String>,
+
+    /// Witnesses known to be either `0` or `1`, because they have already been range constrained
+    /// to a single bit or because the constraints which define them imply it.
+    ///
+    /// Range constraints on these witnesses are redundant and so are not emitted.
+    boolean_witnesses: BTreeSet<Witness>,
+
+    /// Witnesses created by [`get_or_create_witness`][Self::get_or_create_witness], keyed by the
+    /// [canonical form][canonicalize] of the expression which they are constrained to equal.
+    expression_witnesses: HashMap<Expression, Witness>,
+
+    /// The smallest number of bits which each witness is known to fit within, because it has
+    /// already been range constrained to it.
+    ///
+    /// Range constraints of these witnesses to as many bits or more are redundant and so are not
+    /// emitted.
+    known_bit_sizes: BTreeMap<Witness, u32>,
+
+    /// Range constraints requested by [`range_constraint_batch`][Self::range_constraint_batch]
+    /// which have not been emitted yet, with the number of bits and the call stack of each.
+    pending_range_constraints: BTreeMap<Witness, (u32, CallStack)>,
+
+    /// Range constraints of the bytes unpacked by
+    /// [`radix_decompose_deferred`][Self::radix_decompose_deferred] which have been held
+    /// back, with the call stack of each.
+    ///
+    /// The bytes are known to fit in 8 bits straight away. A byte which goes on to be hashed with
+    /// Keccak needs no constraint of its own, as the black box function constrains its inputs
+    /// itself. The others are emitted by
+    /// [`flush_deferred_byte_constraints`][Self::flush_deferred_byte_constraints].
+    deferred_byte_constraints: BTreeMap<Witness, CallStack>,
+
+    /// Memory blocks holding every value which fits within a number of bits, shared by all of the
+    /// batched range constraints to that number of bits.
+    range_tables: BTreeMap<u32, BlockId>,
+
+    /// Read-only memory blocks created by [`lookup_table`][Self::lookup_table], keyed by the
+    /// values they hold so that lookups into equal tables share a block.
+    lookup_tables: HashMap<Vec<FieldElement>, BlockId>,
+
+    /// The witness indices reserved for this `GeneratedAcir` by
+    /// [`fork_with_witness_range`][Self::fork_with_witness_range], if any.
+    witness_range: Option<Range<u32>>,
+
+    /// Number of witnesses allocated after `witness_range` was used up, which are numbered down
+    /// from `u32::MAX` until they are relocated by [`merge`][Self::merge].
+    overflow_witnesses: u32,
+
+    /// Costs of the targeted backend, used to choose between alternative ways of lowering an
+    /// operation. The [`DefaultCostModel`] is used if this is `None`.
+    cost_model: Option<Arc<dyn CostModel>>,
+
+    /// Number of intermediate witnesses created to keep the degree of multiplications at most 2,
+    /// keyed by the source location of the expression which needed them.
+    reduction_witnesses: HashMap<Location, usize>,
+
+    /// A warning is emitted once an expression needs more than this many intermediate witnesses.
+    pub(crate) max_reduction_witnesses: Option<usize>,
+
+    /// Whether range constraints which can never be satisfied are reported as warnings rather
+    /// than errors.
+    pub(crate) warn_failing_range_constraints: bool,
+
+    /// Upper bounds on the number of opcodes executed by each Brillig opcode, keyed by its index.
+    pub(crate) brillig_step_bounds: BTreeMap<usize, usize>,
+
+    /// The variables of the source code held in the registers of each Brillig opcode, keyed by its
+    /// index.
+    pub(crate) brillig_variables: BTreeMap<usize, Vec<BrilligVariableInfo>>,
+
+    /// The call stack of the block of code counted by each profiling counter of the Brillig code,
+    /// keyed by the id of the counter.
+    pub(crate) brillig_counters: BTreeMap<usize, CallStack>,
+
+    /// The distinct Brillig bytecode executed by the [`BrilligCall`][AcirOpcode::BrilligCall]
+    /// opcodes, which refer to it by index.
+    pub(crate) brillig_bytecode: Vec<BrilligBytecode>,
 }
 
 impl GeneratedAcir {
+    pub(crate) fn cost_model(&self) -> &dyn CostModel {
+        self.cost_model.as_deref().unwrap_or(&DefaultCostModel)
+    }
+
+    pub(crate) fn set_cost_model(&mut self, cost_model: Arc<dyn CostModel>) {
+        self.cost_model = Some(cost_model);
+    }
+
+    /// Returns true if `witness` is known to be either `0` or `1`.
+    pub(crate) fn is_boolean(&self, witness: Witness) -> bool {
+        self.boolean_witnesses.contains(&witness)
+    }
+
+    /// Records that the existing constraints on `witness` imply that it is either `0` or `1`.
+    pub(crate) fn mark_boolean(&mut self, witness: Witness) {
+        self.boolean_witnesses.insert(witness);
+    }
+
+    /// Records that the existing constraints on `witness` imply that it fits within `num_bits`.
+    pub(crate) fn mark_bit_size(&mut self, witness: Witness, num_bits: u32) {
+        let bit_size = self.known_bit_sizes.entry(witness).or_insert(num_bits);
+        *bit_size = (*bit_size).min(num_bits);
+    }
+
+    /// Returns true if a range constraint of `witness` to `num_bits` would be redundant.
+    pub(crate) fn fits_in_bits(&self, witness: Witness, num_bits: u32) -> bool {
+        num_bits > 0 && self.is_boolean(witness)
+            || self.known_bit_sizes.get(&witness).is_some_and(|bit_size| *bit_size <= num_bits)
+    }
+
+    /// Records that an intermediate witness has been created to reduce the degree of the
+    /// expression currently being converted, warning if it now needs too many of them.
+    pub(crate) fn record_reduction_witness(&mut self) {
+        let (Some(max_reduction_witnesses), Some(location)) =
+            (self.max_reduction_witnesses, self.call_stack.last())
+        else {
+            return;
+        };
+        let count = self.reduction_witnesses.entry(*location).or_default();
+        *count += 1;
+        if *count == max_reduction_witnesses + 1 {
+            self.warnings.push(SsaReport::Warning(InternalWarning::ReductionWitnesses {
+                max_reduction_witnesses,
+                call_stack: self.call_stack.clone(),
+            }));
+        }
+    }
+
     /// Returns the current witness index.
     pub(crate) fn current_witness_index(&self) -> Witness {
         Witness(self.current_witness_index.unwrap_or(0))
     }
 
+    /// Marks every witness up to and including `witness` as declared, so that
+    /// [`next_witness_index`][Self::next_witness_index] only returns fresh witnesses after it.
+    pub(crate) fn reserve_witnesses(&mut self, witness: Witness) {
+        let current_index = self.current_witness_index.unwrap_or(0);
+        self.current_witness_index = Some(current_index.max(witness.0));
+    }
+
     /// Adds a new opcode into ACIR.
+    ///
+    /// `AssertZero` opcodes are put into [canonical form][canonicalize_constraint] first, so that
+    /// equal constraints are emitted identically however they were built.
     pub(crate) fn push_opcode(&mut self, opcode: AcirOpcode) {
+        let opcode = match opcode {
+            AcirOpcode::AssertZero(expr) => AcirOpcode::AssertZero(canonicalize_constraint(&expr)),
+            opcode => opcode,
+        };
+        tracing::trace!(
+            name: spans::ACIR_OPCODE,
+            index = self.num_opcodes(),
+            opcode = %opcode
+        );
         self.opcodes.push(opcode);
         if !self.call_stack.is_empty() {
-            self.locations.insert(self.last_acir_opcode_location(), self.call_stack.clone());
+            let call_stack = self.call_stacks.intern(&self.call_stack);
+            self.locations.insert(self.last_acir_opcode_location(), call_stack);
+        }
+        self.stream_opcodes();
+    }
+
+    /// Number of opcodes pushed so far, including those streamed to the opcode sink.
+    pub(crate) fn num_opcodes(&self) -> usize {
+        self.streamed_opcodes + self.opcodes.len()
+    }
+
+    /// Streams the opcodes pushed so far and from now on to `sink` rather than holding them in
+    /// memory.
+    ///
+    /// The passes which rewrite the opcodes after they are generated, such as witness compaction
+    /// or the peephole optimizer, only see the opcodes which are still held in memory and so must
+    /// not be run once a sink has been set.
+    pub(crate) fn set_opcode_sink(&mut self, sink: Box<dyn OpcodeSink>) {
+        self.opcode_sink = Some(sink);
+        self.stream_opcodes();
+    }
+
+    /// Flushes the opcode sink, returning the first error which it has returned if any.
+    pub(crate) fn close_opcode_sink(&mut self) -> std::io::Result<()> {
+        if let Some(error) = self.opcode_sink_error.take() {
+            return Err(error);
         }
+        match &mut self.opcode_sink {
+            Some(sink) => sink.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Moves the opcodes held in memory to the opcode sink, if one is set.
+    fn stream_opcodes(&mut self) {
+        let Some(sink) = &mut self.opcode_sink else {
+            return;
+        };
+        for opcode in self.opcodes.drain(..) {
+            if self.opcode_sink_error.is_none() {
+                if let Err(error) = sink.write_opcode(&opcode) {
+                    self.opcode_sink_error = Some(error);
+                }
+            }
+            self.streamed_opcodes += 1;
+        }
+    }
+
+    /// The call stack which the opcode at `location` was generated for, which is empty if it is
+    /// not known.
+    pub(crate) fn opcode_call_stack(&self, location: OpcodeLocation) -> CallStack {
+        self.locations
+            .get(&location)
+            .map_or_else(CallStack::new, |call_stack| self.call_stacks.resolve(*call_stack))
+    }
+
+    /// The opcodes held in memory, which are all of them unless they are streamed to a sink.
+    pub(crate) fn opcodes(&self) -> &[AcirOpcode] {
+        &self.opcodes
+    }
+
+    /// Estimated cost of proving the opcodes held in memory with the backend of `cost_model`.
+    pub(crate) fn estimate_cost(&self, cost_model: &(impl CostModel + ?Sized)) -> u64 {
+        cost_model.circuit_cost(&self.opcodes)
+    }
+
+    /// Replaces the opcode at `index`, which keeps its location and assertion message.
+    pub(crate) fn replace_opcode(&mut self, index: usize, opcode: AcirOpcode) {
+        self.opcodes[index] = opcode;
+    }
+
+    /// Removes every opcode whose index is not in `kept`, keeping the maps keyed by opcode
+    /// location in step with the remaining opcodes.
+    pub(crate) fn retain_opcodes(&mut self, kept: &BTreeSet<usize>) {
+        let new_indices: HashMap<usize, usize> =
+            kept.iter().enumerate().map(|(new_index, index)| (*index, new_index)).collect();
+        self.opcodes = std::mem::take(&mut self.opcodes)
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| kept.contains(index))
+            .map(|(_, opcode)| opcode)
+            .collect();
+        self.remap_opcode_locations(|location| match location {
+            OpcodeLocation::Acir(index) => {
+                new_indices.get(&index).map(|i| OpcodeLocation::Acir(*i))
+            }
+            OpcodeLocation::Brillig { acir_index, brillig_index } => {
+                new_indices.get(&acir_index).map(|acir_index| OpcodeLocation::Brillig {
+                    acir_index: *acir_index,
+                    brillig_index,
+                })
+            }
+        });
     }
 
     pub(crate) fn take_opcodes(&mut self) -> Vec<AcirOpcode> {
@@ -81,23 +364,39 @@ impl GeneratedAcir {
     /// Updates the witness index counter and returns
     /// the next witness index.
     pub(crate) fn next_witness_index(&mut self) -> Witness {
-        if let Some(current_index) = self.current_witness_index {
-            self.current_witness_index.replace(current_index + 1);
-        } else {
-            self.current_witness_index = Some(0);
+        let next_index = self.current_witness_index.map_or(0, |index| index + 1);
+        if self.witness_range.as_ref().is_some_and(|range| next_index >= range.end) {
+            // The reserved range is full, so the witness is taken from the top of the index space
+            // until it is relocated by `merge`.
+            self.overflow_witnesses += 1;
+            return Witness(u32::MAX - (self.overflow_witnesses - 1));
         }
-        Witness(self.current_witness_index.expect("ICE: current_witness_index should exist"))
+        self.current_witness_index = Some(next_index);
+        Witness(next_index)
     }
 
     /// Converts [`Expression`] `expr` into a [`Witness`].
     ///
     /// If `expr` can be represented as a `Witness` then this function will return it,
     /// else a new opcode will be added to create a `Witness` that is equal to `expr`.
+    /// The witness is reused if an equal expression has already been converted.
     pub(crate) fn get_or_create_witness(&mut self, expr: &Expression) -> Witness {
-        match expr.to_witness() {
-            Some(witness) => witness,
-            None => self.create_witness_for_expression(expr),
+        if let Some(witness) = expr.to_witness() {
+            return witness;
         }
+        let key = canonicalize(expr);
+        if let Some(witness) = self.expression_witnesses.get(&key) {
+            return *witness;
+        }
+        let witness = self.create_witness_for_expression(expr);
+        self.expression_witnesses.insert(key, witness);
+        witness
+    }
+
+    /// Returns the witness which an expression equal to `expr` has already been converted into by
+    /// [`get_or_create_witness`][Self::get_or_create_witness], if any.
+    pub(crate) fn existing_witness(&self, expr: &Expression) -> Option<Witness> {
+        self.expression_witnesses.get(&canonicalize(expr)).copied()
     }
 
     /// Creates a new [`Witness`] which is constrained to be equal to the passed [`Expression`].
@@ -129,11 +428,62 @@ impl GeneratedAcir {
     pub(crate) fn push_return_witness(&mut self, witness: Witness) {
         self.return_witnesses.push(witness);
     }
+
+    /// Replaces each return witness with a new witness equal to it, so that the return witnesses
+    /// are distinct from the input witnesses and from each other.
+    ///
+    /// If `dedup` is true, a return witness which is repeated is replaced with the same new
+    /// witness each time, so that each value is only exposed once as a public input. The return
+    /// witnesses then still give the witness holding each element of the return value, which is
+    /// all that the ABI needs to reconstruct it.
+    pub(crate) fn make_return_witnesses_distinct(&mut self, dedup: bool) {
+        let mut distinct_witnesses = HashMap::new();
+        let return_witnesses = std::mem::take(&mut self.return_witnesses);
+        self.return_witnesses = vecmap(return_witnesses, |witness| {
+            let expression = Expression::from(witness);
+            if !dedup {
+                return self.create_witness_for_expression(&expression);
+            }
+            *distinct_witnesses
+                .entry(witness)
+                .or_insert_with(|| self.create_witness_for_expression(&expression))
+        });
+    }
+
+    /// Splits the return witnesses into consecutive groups, each given by its name and the
+    /// number of witnesses it holds. The return witnesses are left ungrouped if `groups` is empty.
+    pub(crate) fn group_return_witnesses(&mut self, groups: Vec<(String, usize)>) {
+        if groups.is_empty() {
+            return;
+        }
+        let mut remaining = &self.return_witnesses[..];
+        self.return_groups = vecmap(groups, |(name, size)| {
+            let (witnesses, rest) = remaining.split_at(size);
+            remaining = rest;
+            ReturnGroup { name, witnesses: witnesses.to_vec() }
+        });
+        assert!(remaining.is_empty(), "ICE: return groups must cover every return witness");
+    }
+
+    /// Splits the input witnesses into the parameters of the main function which they hold,
+    /// given the name, visibility and number of field elements of each parameter in order.
+    pub(crate) fn group_input_witnesses(&mut self, parameters: Vec<(String, Visibility, usize)>) {
+        let mut remaining = &self.input_witnesses[..];
+        self.input_parameters = vecmap(parameters, |(name, visibility, size)| {
+            let (witnesses, rest) = remaining.split_at(size);
+            remaining = rest;
+            InputParameter { name, visibility, witnesses: witnesses.to_vec() }
+        });
+        assert!(remaining.is_empty(), "ICE: input parameters must cover every input witness");
+    }
 }
 
 impl GeneratedAcir {
     /// Calls a black box function and returns the output
     /// of said blackbox function.
+    ///
+    /// If a `predicate` is given, the inputs are multiplied by it so that the opcode is solved
+    /// over zeros rather than over whatever values a branch which is not taken left them with.
     pub(crate) fn call_black_box(
         &mut self,
         func_name: BlackBoxFunc,
@@ -141,10 +491,29 @@ impl GeneratedAcir {
         constant_inputs: Vec<FieldElement>,
         constant_outputs: Vec<FieldElement>,
         output_count: usize,
-    ) -> Result<Vec<Witness>, InternalError> {
+        predicate: Option<&Expression>,
+    ) -> Result<Vec<Witness>, RuntimeError> {
         let input_count = inputs.iter().fold(0usize, |sum, val| sum + val.len());
-        intrinsics_check_inputs(func_name, input_count);
-        intrinsics_check_outputs(func_name, output_count);
+        intrinsics_check_inputs(func_name, input_count, &self.call_stack)?;
+        intrinsics_check_outputs(func_name, output_count, &self.call_stack)?;
+
+        let predicated_inputs;
+        let inputs = match predicate {
+            Some(predicate) if predicate.to_const() != Some(FieldElement::one()) => {
+                predicated_inputs = vecmap(inputs, |inputs| {
+                    vecmap(inputs, |input| {
+                        let product = self.mul_with_witness(predicate, &input.witness.into());
+                        // The product is either zero or the input, so it fits in as many bits.
+                        FunctionInput {
+                            witness: self.get_or_create_witness(&product),
+                            num_bits: input.num_bits,
+                        }
+                    })
+                });
+                &predicated_inputs[..]
+            }
+            _ => inputs,
+        };
 
         let outputs = vecmap(0..output_count, |_| self.next_witness_index());
 
@@ -160,6 +529,7 @@ impl GeneratedAcir {
             }
             BlackBoxFunc::RANGE => BlackBoxFuncCall::RANGE { input: inputs[0][0] },
             BlackBoxFunc::SHA256 => BlackBoxFuncCall::SHA256 { inputs: inputs[0].clone(), outputs },
+            BlackBoxFunc::Sha512 => BlackBoxFuncCall::Sha512 { inputs: inputs[0].clone(), outputs },
             BlackBoxFunc::Blake2s => {
                 BlackBoxFuncCall::Blake2s { inputs: inputs[0].clone(), outputs }
             }
@@ -208,11 +578,84 @@ impl GeneratedAcir {
                     output: outputs[0],
                 }
             }
+            BlackBoxFunc::Ed25519 => BlackBoxFuncCall::Ed25519 {
+                // 32 bytes for the encoded public key
+                public_key: inputs[0].clone(),
+                // R and S are both 32 bytes each, so signature
+                // takes up 64 bytes
+                signature: inputs[1].clone(),
+                message: inputs[2].clone(),
+                output: outputs[0],
+            },
+            BlackBoxFunc::Bls12381G1Add => BlackBoxFuncCall::Bls12381G1Add {
+                // Both points are encoded as the 48 bytes of each coordinate
+                input1: inputs[0].clone(),
+                input2: inputs[1].clone(),
+                outputs,
+            },
+            BlackBoxFunc::Bls12381G1Mul => BlackBoxFuncCall::Bls12381G1Mul {
+                // 96 bytes for the encoded point and 32 bytes for the scalar
+                point: inputs[0].clone(),
+                scalar: inputs[1].clone(),
+                outputs,
+            },
             BlackBoxFunc::FixedBaseScalarMul => BlackBoxFuncCall::FixedBaseScalarMul {
                 low: inputs[0][0],
                 high: inputs[1][0],
                 outputs: (outputs[0], outputs[1]),
             },
+            BlackBoxFunc::MultiScalarMul => {
+                let (points, scalars) = (&inputs[0], &inputs[1]);
+                if points.len() % 2 != 0 || points.len() != scalars.len() {
+                    return Err(InternalError::Unexpected {
+                        expected: "two limbs of a scalar for each point's coordinates".to_string(),
+                        found: format!("{} coordinates and {} limbs", points.len(), scalars.len()),
+                        call_stack: self.call_stack.clone(),
+                    }
+                    .into());
+                }
+                BlackBoxFuncCall::MultiScalarMul {
+                    points: points.clone(),
+                    scalars: scalars.clone(),
+                    outputs: (outputs[0], outputs[1]),
+                }
+            }
+            BlackBoxFunc::AES128Encrypt => {
+                let [plaintext, iv, key] = inputs else {
+                    return Err(InternalError::MissingArg {
+                        name: "aes128_encrypt".to_string(),
+                        arg: "plaintext, iv and key".to_string(),
+                        call_stack: self.call_stack.clone(),
+                    }
+                    .into());
+                };
+                for (name, block) in [("iv", iv), ("key", key)] {
+                    if block.len() != 16 {
+                        return Err(InternalError::Unexpected {
+                            expected: format!("a {name} of 16 bytes"),
+                            found: format!("{} bytes", block.len()),
+                            call_stack: self.call_stack.clone(),
+                        }
+                        .into());
+                    }
+                }
+                // PKCS#7 padding always adds between 1 and 16 bytes to the plaintext.
+                let ciphertext_len = plaintext.len() + (16 - plaintext.len() % 16);
+                if outputs.len() != ciphertext_len {
+                    return Err(InternalError::Unexpected {
+                        expected: format!("a ciphertext of {ciphertext_len} bytes"),
+                        found: format!("{} bytes", outputs.len()),
+                        call_stack: self.call_stack.clone(),
+                    }
+                    .into());
+                }
+                BlackBoxFuncCall::AES128Encrypt {
+                    inputs: plaintext.clone(),
+                    iv: iv.clone(),
+                    key: key.clone(),
+                    outputs,
+                }
+            }
             BlackBoxFunc::EmbeddedCurveAdd => BlackBoxFuncCall::EmbeddedCurveAdd {
                 input1_x: inputs[0][0],
                 input1_y: inputs[1][0],
@@ -221,21 +664,30 @@ impl GeneratedAcir {
                 outputs: (outputs[0], outputs[1]),
             },
             BlackBoxFunc::Keccak256 => {
-                let var_message_size = match inputs.to_vec().pop() {
-                    Some(var_message_size) => var_message_size[0],
-                    None => {
+                // The inputs are constrained to their bit sizes by the black box function, which
+                // makes any held back constraints of bytes passed to it redundant.
+                for input in inputs.first().into_iter().flatten() {
+                    if input.num_bits <= 8 {
+                        self.deferred_byte_constraints.remove(&input.witness);
+                    }
+                }
+
+                match inputs {
+                    // The message size is omitted when it is known to be the message's length.
+                    [message] => BlackBoxFuncCall::Keccak256 { inputs: message.clone(), outputs },
+                    [message, var_message_size] => BlackBoxFuncCall::Keccak256VariableLength {
+                        inputs: message.clone(),
+                        var_message_size: var_message_size[0],
+                        outputs,
+                    },
+                    _ => {
                         return Err(InternalError::MissingArg {
-                            name: "".to_string(),
+                            name: "keccak256".to_string(),
                             arg: "message_size".to_string(),
                             call_stack: self.call_stack.clone(),
-                        });
+                        }
+                        .into());
                     }
-                };
-
-                BlackBoxFuncCall::Keccak256VariableLength {
-                    inputs: inputs[0].clone(),
-                    var_message_size,
-                    outputs,
                 }
             }
             BlackBoxFunc::Keccakf1600 => {
@@ -281,6 +733,35 @@ impl GeneratedAcir {
                 outputs,
                 len: constant_inputs[0].to_u128() as u32,
             },
+            BlackBoxFunc::PoseidonPermutation => {
+                let len = constant_inputs[0].to_u128() as u32;
+                let rate = constant_inputs[1].to_u128() as u32;
+                // The rate leaves at least one element of the state as capacity.
+                if rate == 0 || rate >= len {
+                    return Err(InternalError::Unexpected {
+                        expected: format!("a rate smaller than the state length {len}"),
+                        found: format!("a rate of {rate}"),
+                        call_stack: self.call_stack.clone(),
+                    }
+                    .into());
+                }
+                for (name, count) in [("inputs", inputs[0].len()), ("outputs", outputs.len())] {
+                    if count != len as usize {
+                        return Err(InternalError::Unexpected {
+                            expected: format!("{len} {name}"),
+                            found: format!("{count} {name}"),
+                            call_stack: self.call_stack.clone(),
+                        }
+                        .into());
+                    }
+                }
+                BlackBoxFuncCall::PoseidonPermutation {
+                    inputs: inputs[0].clone(),
+                    outputs,
+                    len,
+                    rate,
+                }
+            }
             BlackBoxFunc::Sha256Compression => BlackBoxFuncCall::Sha256Compression {
                 inputs: inputs[0].clone(),
                 hash_values: inputs[1].clone(),
@@ -296,33 +777,91 @@ impl GeneratedAcir {
     /// Takes an input expression and returns witnesses that are constrained to be limbs
     /// decomposed from the input for the given radix and limb count.
     ///
-    /// Only radix that are a power of two are supported
+    /// `bit_size` is the number of bits needed for a limb, i.e. the radix must not exceed
+    /// `2^bit_size`. Limbs of radices which are not a power of two are also constrained to be
+    /// less than the radix.
     pub(crate) fn radix_le_decompose(
         &mut self,
         input_expr: &Expression,
         radix: u32,
         limb_count: u32,
         bit_size: u32,
+    ) -> Result<Vec<Witness>, RuntimeError> {
+        self.decompose(Endian::Little, input_expr, radix, limb_count, bit_size, false)
+    }
+
+    /// Decomposes `input_expr` like [`radix_le_decompose`][Self::radix_le_decompose], but returns
+    /// the limbs in big-endian order.
+    pub(crate) fn radix_be_decompose(
+        &mut self,
+        input_expr: &Expression,
+        radix: u32,
+        limb_count: u32,
+        bit_size: u32,
+    ) -> Result<Vec<Witness>, RuntimeError> {
+        self.decompose(Endian::Big, input_expr, radix, limb_count, bit_size, false)
+    }
+
+    /// Decomposes `input_expr` like [`radix_le_decompose`][Self::radix_le_decompose], with the
+    /// limbs in `endian` order, but holds back the range constraints of the limbs if they are
+    /// bytes.
+    ///
+    /// The held back constraints are dropped if the bytes are hashed with Keccak, and the rest
+    /// must be emitted by [`flush_deferred_byte_constraints`][Self::flush_deferred_byte_constraints]
+    /// before the circuit is finished.
+    pub(crate) fn radix_decompose_deferred(
+        &mut self,
+        endian: Endian,
+        input_expr: &Expression,
+        radix: u32,
+        limb_count: u32,
+        bit_size: u32,
+    ) -> Result<Vec<Witness>, RuntimeError> {
+        self.decompose(endian, input_expr, radix, limb_count, bit_size, radix == 256)
+    }
+
+    fn decompose(
+        &mut self,
+        endian: Endian,
+        input_expr: &Expression,
+        radix: u32,
+        limb_count: u32,
+        bit_size: u32,
+        defer_byte_constraints: bool,
     ) -> Result<Vec<Witness>, RuntimeError> {
         let radix_big = BigUint::from(radix);
-        assert_eq!(
-            BigUint::from(2u128).pow(bit_size),
-            radix_big,
-            "ICE: Radix must be a power of 2"
-        );
+        let radix_bound = BigUint::from(2u128).pow(bit_size);
+        assert!(radix_big <= radix_bound, "ICE: Radix must fit in {bit_size} bits");
+        // limb < radix <=> limb + 2^bit_size - radix < 2^bit_size, for a limb of `bit_size` bits.
+        let bound_offset = (radix_big != radix_bound)
+            .then(|| FieldElement::from_be_bytes_reduce(&(radix_bound - &radix_big).to_bytes_be()));
 
         let limb_witnesses = vecmap(0..limb_count, |_| self.next_witness_index());
-        self.push_opcode(AcirOpcode::Directive(Directive::ToLeRadix {
-            a: input_expr.clone(),
-            b: limb_witnesses.clone(),
-            radix,
-        }));
+        // The limbs are solved and recomposed from the least significant one.
+        let le_limbs = match endian {
+            Endian::Little => limb_witnesses.clone(),
+            Endian::Big => limb_witnesses.iter().rev().copied().collect(),
+        };
+        let to_radix_code = brillig_directive::directive_to_radix(radix, limb_count as usize);
+        let inputs = vec![BrilligInputs::Single(input_expr.clone())];
+        let outputs = vecmap(&le_limbs, |witness| BrilligOutputs::Simple(*witness));
+        self.brillig(Some(Expression::one()), to_radix_code, inputs, outputs);
 
         let mut composed_limbs = Expression::default();
 
         let mut radix_pow = BigUint::from(1u128);
-        for limb_witness in &limb_witnesses {
-            self.range_constraint(*limb_witness, bit_size)?;
+        for limb_witness in &le_limbs {
+            if defer_byte_constraints {
+                self.deferred_byte_constraints.insert(*limb_witness, self.call_stack.clone());
+                self.mark_bit_size(*limb_witness, 8);
+            } else {
+                self.range_constraint(*limb_witness, bit_size)?;
+            }
+            if let Some(offset) = bound_offset {
+                let offset_limb =
+                    self.get_or_create_witness(&(Expression::from(*limb_witness) + offset));
+                self.range_constraint(offset_limb, bit_size)?;
+            }
 
             composed_limbs = composed_limbs.add_mul(
                 FieldElement::from_be_bytes_reduce(&radix_pow.to_bytes_be()),
@@ -337,6 +876,206 @@ impl GeneratedAcir {
         Ok(limb_witnesses)
     }
 
+    /// Returns the big-endian packing of `bytes` into a single field element.
+    ///
+    /// Each byte which is not constant is range constrained, unless it is already known to fit
+    /// in a byte (e.g. because it was unpacked by [`unpack_bytes`][Self::unpack_bytes]).
+    /// Constant bytes are folded into the constant term of the result.
+    pub(crate) fn pack_bytes(&mut self, bytes: &[Expression]) -> Result<Expression, RuntimeError> {
+        self.check_packed_byte_count(bytes.len())?;
+
+        let mut packed = Expression::default();
+        let mut shift = FieldElement::one();
+        for byte in bytes.iter().rev() {
+            match byte.to_const() {
+                Some(constant) if constant.num_bits() > 8 => {
+                    return Err(RuntimeError::IntegerOutOfBounds {
+                        value: constant,
+                        typ: NumericType::Unsigned { bit_size: 8 },
+                        call_stack: self.call_stack.clone(),
+                    });
+                }
+                Some(constant) => packed.q_c += constant * shift,
+                None => {
+                    let witness = self.get_or_create_witness(byte);
+                    self.range_constraint(witness, 8)?;
+                    packed = packed.add_mul(shift, &witness.into());
+                }
+            }
+            shift = shift * FieldElement::from(256_u128);
+        }
+        Ok(packed)
+    }
+
+    /// Returns the `num_bytes` big-endian bytes of `value`, constraining `value` to fit in them.
+    ///
+    /// The bytes are taken from a single decomposition of `value`, whose range constraints are
+    /// remembered so that packing the bytes again does not repeat them. If `value` is constant
+    /// then so are the bytes, and no opcodes are emitted.
+    pub(crate) fn unpack_bytes(
+        &mut self,
+        value: &Expression,
+        num_bytes: u32,
+    ) -> Result<Vec<Expression>, RuntimeError> {
+        self.check_packed_byte_count(num_bytes as usize)?;
+
+        if let Some(constant) = value.to_const() {
+            if constant.num_bits() > 8 * num_bytes {
+                return Err(RuntimeError::IntegerOutOfBounds {
+                    value: constant,
+                    typ: NumericType::Unsigned { bit_size: 8 * num_bytes },
+                    call_stack: self.call_stack.clone(),
+                });
+            }
+            let bytes = constant.to_be_bytes();
+            let bytes = &bytes[bytes.len() - num_bytes as usize..];
+            return Ok(vecmap(bytes, |byte| {
+                Expression::from_field(FieldElement::from(*byte as u128))
+            }));
+        }
+
+        let bytes = self.radix_be_decompose(value, 256, num_bytes, 8)?;
+        Ok(vecmap(bytes, Expression::from))
+    }
+
+    /// Returns the least and greatest values which `expression` can take, given the bit sizes
+    /// known for its witnesses.
+    ///
+    /// Returns `None` if the bit size of a witness is unknown, or if the values of `expression`
+    /// might wrap around the field modulus so that they do not form a single interval.
+    pub(crate) fn bounds(&self, expression: &Expression) -> Option<(FieldElement, FieldElement)> {
+        let modulus = BigInt::from(FieldElement::modulus());
+        let signed = |value: FieldElement| {
+            let value = BigInt::from_bytes_be(Sign::Plus, &value.to_be_bytes());
+            if value > &modulus / 2 {
+                value - &modulus
+            } else {
+                value
+            }
+        };
+        let max_value = |witness: &Witness| -> Option<BigInt> {
+            let num_bits =
+                if self.is_boolean(*witness) { 1 } else { *self.known_bit_sizes.get(witness)? };
+            Some((BigInt::from(1) << num_bits) - 1)
+        };
+
+        let mut lower = signed(expression.q_c);
+        let mut upper = lower.clone();
+        let terms = expression
+            .mul_terms
+            .iter()
+            .map(|(coefficient, lhs, rhs)| Some((coefficient, max_value(lhs)? * max_value(rhs)?)))
+            .chain(
+                expression
+                    .linear_combinations
+                    .iter()
+                    .map(|(coefficient, witness)| Some((coefficient, max_value(witness)?))),
+            );
+        for term in terms {
+            let (coefficient, max_value) = term?;
+            let extreme = signed(*coefficient) * max_value;
+            if extreme.sign() == Sign::Minus {
+                lower += extreme;
+            } else {
+                upper += extreme;
+            }
+        }
+
+        // Shift negative values, which are congruent to values just below the modulus, so that
+        // the bounds can be returned as field elements.
+        let shift = if upper.sign() == Sign::Minus { modulus.clone() } else { BigInt::from(0) };
+        let (lower, upper) = (lower + &shift, upper + &shift);
+        if lower.sign() == Sign::Minus || upper >= modulus {
+            return None;
+        }
+        let to_field = |value: BigInt| FieldElement::from_be_bytes_reduce(&value.to_bytes_be().1);
+        Some((to_field(lower), to_field(upper)))
+    }
+
+    /// Reports a range constraint of `expression` to `num_bits` which no value of `expression`
+    /// can satisfy, as an error or a warning according to `warn_failing_range_constraints`.
+    pub(crate) fn check_range_can_be_satisfied(
+        &mut self,
+        expression: &Expression,
+        num_bits: u32,
+    ) -> Result<(), RuntimeError> {
+        let Some((lower, upper)) = self.bounds(expression) else {
+            return Ok(());
+        };
+        if lower.num_bits() <= num_bits {
+            return Ok(());
+        }
+
+        let call_stack = self.call_stack.clone();
+        if self.warn_failing_range_constraints {
+            self.warnings.push(SsaReport::Warning(InternalWarning::FailingRangeConstraint {
+                lower,
+                upper,
+                num_bits,
+                call_stack,
+            }));
+            Ok(())
+        } else {
+            Err(RuntimeError::FailingRangeConstraint {
+                lower: Box::new(lower),
+                upper: Box::new(upper),
+                num_bits,
+                call_stack,
+            })
+        }
+    }
+
+    /// Returns an error if `num_bytes` bytes cannot be packed into a field element without
+    /// wrapping around the modulus.
+    fn check_packed_byte_count(&self, num_bytes: usize) -> Result<(), RuntimeError> {
+        let max_num_bits = FieldElement::max_num_bits() - 1;
+        if num_bytes > (max_num_bits / 8) as usize {
+            return Err(RuntimeError::UnsupportedIntegerSize {
+                num_bits: 8 * num_bytes as u32,
+                max_num_bits,
+                call_stack: self.call_stack.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns an expression which represents the bitwise AND or XOR of `lhs` and `rhs`,
+    /// computed by decomposing both into `bit_size` bits rather than calling `func`.
+    pub(crate) fn bitwise_by_decomposition(
+        &mut self,
+        func: BlackBoxFunc,
+        lhs: &Expression,
+        rhs: &Expression,
+        bit_size: u32,
+    ) -> Result<Expression, RuntimeError> {
+        let lhs_bits = self.radix_le_decompose(lhs, 2, bit_size, 1)?;
+        let rhs_bits = self.radix_le_decompose(rhs, 2, bit_size, 1)?;
+
+        let mut result = Expression::default();
+        let mut power_of_two = FieldElement::one();
+        for (lhs_bit, rhs_bit) in lhs_bits.into_iter().zip(rhs_bits) {
+            match func {
+                // a & b == a*b
+                BlackBoxFunc::AND => {
+                    result.push_multiplication_term(power_of_two, lhs_bit, rhs_bit);
+                }
+                // a ^ b == a + b - 2*a*b
+                BlackBoxFunc::XOR => {
+                    result.push_addition_term(power_of_two, lhs_bit);
+                    result.push_addition_term(power_of_two, rhs_bit);
+                    result.push_multiplication_term(
+                        -(power_of_two + power_of_two),
+                        lhs_bit,
+                        rhs_bit,
+                    );
+                }
+                _ => unreachable!("ICE: {func} is not a bitwise operation"),
+            }
+            power_of_two = power_of_two + power_of_two;
+        }
+        Ok(result)
+    }
+
     /// Returns an expression which represents `lhs * rhs`
     ///
     /// If one has multiplicative term and the other is of degree one or more,
@@ -367,7 +1106,7 @@ impl GeneratedAcir {
         let lhs_reduced = if lhs_is_linear {
             Cow::Borrowed(lhs)
         } else {
-            Cow::Owned(self.get_or_create_witness(lhs).into())
+            Cow::Owned(self.reduce_non_linear(lhs))
         };
 
         // If the lhs and rhs are the same, then we do not need to reduce
@@ -380,12 +1119,89 @@ impl GeneratedAcir {
         let rhs_reduced = if rhs_is_linear {
             Cow::Borrowed(rhs)
         } else {
-            Cow::Owned(self.get_or_create_witness(rhs).into())
+            Cow::Owned(self.reduce_non_linear(rhs))
         };
 
         (&*lhs_reduced * &*rhs_reduced).expect("Both expressions are reduced to be degree <= 1")
     }
 
+    /// Returns a degree-1 expression equal to the non-linear `expr`, in terms of a witness which
+    /// is constrained to equal it up to a constant factor and offset.
+    ///
+    /// The witness is shared by all expressions with the same [canonical form][canonicalize] once
+    /// their constant term is dropped and they are scaled to a leading coefficient of one, so that
+    /// a subexpression is only reduced once however many multiplications it appears in.
+    fn reduce_non_linear(&mut self, expr: &Expression) -> Expression {
+        let mut key = canonicalize(expr);
+        let offset = std::mem::replace(&mut key.q_c, FieldElement::zero());
+        let Some((scale, ..)) = key.mul_terms.first().copied() else {
+            // The multiplication terms cancel out.
+            return key + offset;
+        };
+        let key = &key * scale.inverse();
+        let witness = match self.existing_witness(&key) {
+            Some(witness) => witness,
+            None => {
+                self.record_reduction_witness();
+                self.get_or_create_witness(&key)
+            }
+        };
+        &Expression::from(witness) * scale + offset
+    }
+
+    /// Returns an expression equal to `base^exponent`, where `exponent` is constrained to fit in
+    /// `max_bits` bits.
+    ///
+    /// A constant exponent is applied by square-and-multiply. Any other exponent is decomposed into
+    /// bits, each of which selects whether the base is multiplied in at its step.
+    pub(crate) fn pow(
+        &mut self,
+        base: &Expression,
+        exponent: &Expression,
+        max_bits: u32,
+    ) -> Result<Expression, RuntimeError> {
+        self.check_range_can_be_satisfied(exponent, max_bits)?;
+
+        if let Some(exponent) = exponent.to_const() {
+            // Square and multiply, starting from the most significant bit of the exponent.
+            let mut result = Expression::one();
+            for bit in exponent.bits().into_iter().skip_while(|bit| !bit) {
+                result = self.mul_with_witness(&result, &result);
+                if bit {
+                    result = self.mul_with_witness(&result, base);
+                }
+            }
+            return Ok(result);
+        }
+
+        let bits = self.radix_le_decompose(exponent, 2, max_bits, 1)?;
+        if let Some(base) = base.to_const() {
+            // base^exponent is the product of base^(2^i) over the bits `i` which are set, so each
+            // factor `1 + bit * (base^(2^i) - 1)` is linear.
+            let mut result = Expression::one();
+            let mut power = base;
+            for bit in bits {
+                let factor = &Expression::from(bit) * (power - FieldElement::one());
+                let factor = factor + FieldElement::one();
+                result = self.mul_with_witness(&result, &factor);
+                power = power * power;
+            }
+            return Ok(result);
+        }
+
+        // Square and multiply, selecting with each bit whether `base` is multiplied in:
+        // result = result^2 * (1 + bit * (base - 1))
+        let base_minus_one = base.clone() - FieldElement::one();
+        let mut result = Expression::one();
+        for bit in bits.into_iter().rev() {
+            result = self.mul_with_witness(&result, &result);
+            let factor = self.mul_with_witness(&bit.into(), &base_minus_one);
+            let factor = factor + FieldElement::one();
+            result = self.mul_with_witness(&result, &factor);
+        }
+        Ok(result)
+    }
+
     /// Adds an inversion brillig opcode.
     ///
     /// This code will invert `expr` without applying constraints
@@ -407,6 +1223,23 @@ impl GeneratedAcir {
         inverted_witness
     }
 
+    /// Adds a single brillig opcode which inverts each of `exprs`, like
+    /// [`brillig_inverse`][Self::brillig_inverse] does for a single expression.
+    ///
+    /// Safety: It is the callers responsibility to ensure that each of the
+    /// resulting `Witness`es is constrained to be the inverse of its expression.
+    pub(crate) fn brillig_inverse_batch(&mut self, exprs: Vec<Expression>) -> Vec<Witness> {
+        let inverted_witnesses = vecmap(&exprs, |_| self.next_witness_index());
+
+        // Compute all of the inverses with a single inversion in brillig code
+        let inverse_code = brillig_directive::directive_invert_batch(exprs.len());
+        let inputs = vecmap(exprs, BrilligInputs::Single);
+        let outputs = vecmap(&inverted_witnesses, |witness| BrilligOutputs::Simple(*witness));
+        self.brillig(Some(Expression::one()), inverse_code, inputs, outputs);
+
+        inverted_witnesses
+    }
+
     /// Asserts `expr` to be zero.
     ///
     /// If `expr` is not zero, then the constraint system will
@@ -476,7 +1309,25 @@ impl GeneratedAcir {
     /// By setting `z` to be `0`, we can make `y` equal to `1`.
     /// This is easily observed: `y = 1 - t * 0`
     /// Now since `y` is one, this means that `t` needs to be zero, or else `y * t == 0` will fail.
+    ///
+    /// ---
+    /// When `t` is known to lie within `[0, 2^n)` for a small `n`, the backend's cost model may
+    /// instead favour [decomposing it into bits][Self::is_zero_from_bits].
     fn is_zero(&mut self, t_expr: &Expression) -> Witness {
+        let num_bits = self
+            .bounds(t_expr)
+            .and_then(|(lower, upper)| lower.is_zero().then(|| upper.num_bits()));
+        let is_witness = t_expr.to_witness().is_some();
+        match IsZeroLowering::choose(self.cost_model(), num_bits, is_witness) {
+            IsZeroLowering::Inverse => self.is_zero_from_inverse(t_expr),
+            IsZeroLowering::BitDecomposition => {
+                let num_bits = num_bits.expect("ICE: decomposition needs a known bit size");
+                self.is_zero_from_bits(t_expr, num_bits)
+            }
+        }
+    }
+
+    fn is_zero_from_inverse(&mut self, t_expr: &Expression) -> Witness {
         // We're checking for equality with zero so we can negate the expression without changing the result.
         // This is useful as it will sometimes allow us to simplify an expression down to a witness.
         let t_witness = if let Some(witness) = t_expr.to_witness() {
@@ -508,12 +1359,172 @@ impl GeneratedAcir {
         };
         self.assert_is_zero(ty_zero_constraint);
 
+        // If t == 0 then y == 1, otherwise y == 0 as y * t == 0.
+        self.mark_boolean(y);
         y
     }
 
-    /// Adds a constraint which ensure thats `witness` is an
-    /// integer within the range `[0, 2^{num_bits} - 1]`
-    pub(crate) fn range_constraint(
+    /// Returns a `Witness` that is `1` if `t == 0` and `0` otherwise, where `t` is known to lie
+    /// within `[0, 2^{num_bits})`.
+    ///
+    /// The bits of `t` are all zero exactly when `t` is, so the result is the product of
+    /// `1 - bit` over every bit. A `t` of a single bit is its own decomposition.
+    fn is_zero_from_bits(&mut self, t_expr: &Expression, num_bits: u32) -> Witness {
+        let bits = if num_bits == 1 {
+            vec![t_expr.clone()]
+        } else {
+            let bits = self
+                .radix_le_decompose(t_expr, 2, num_bits, 1)
+                .expect("ICE: bits always fit in a range constraint");
+            vecmap(bits, Expression::from)
+        };
+
+        let mut product = Expression::one();
+        for bit in bits {
+            product = self.mul_with_witness(&product, &(&Expression::one() - &bit));
+        }
+        let y = self.get_or_create_witness(&product);
+
+        self.mark_boolean(y);
+        y
+    }
+
+    /// Returns the witnesses `(q, r)` of the quotient and remainder of the euclidean division of
+    /// `lhs` by `rhs`, which are solved by a single brillig opcode.
+    ///
+    /// When `predicate` is one, they are constrained such that `lhs == rhs * q + r`, `r < rhs`,
+    /// `q < 2^{max_q_bits}` and `r < 2^{max_rhs_bits}`. When it is zero, both are zero.
+    ///
+    /// Safety: It is the callers responsibility to ensure that `lhs` and `rhs` fit in `bit_size`
+    /// bits, that `rhs` is not zero when `predicate` is one and that `rhs * q + r` cannot
+    /// overflow the field.
+    pub(crate) fn euclidean_division(
+        &mut self,
+        lhs: &Expression,
+        rhs: &Expression,
+        predicate: &Expression,
+        bit_size: u32,
+        max_q_bits: u32,
+        max_rhs_bits: u32,
+    ) -> Result<(Witness, Witness), RuntimeError> {
+        let quotient = self.next_witness_index();
+        let remainder = self.next_witness_index();
+
+        let quotient_code = brillig_directive::directive_quotient(bit_size + 1);
+        let inputs = vec![BrilligInputs::Single(lhs.clone()), BrilligInputs::Single(rhs.clone())];
+        let outputs = vec![BrilligOutputs::Simple(quotient), BrilligOutputs::Simple(remainder)];
+        self.brillig(Some(predicate.clone()), quotient_code, inputs, outputs);
+
+        // Constrain `q < 2^{max_q_bits}`.
+        self.range_constraint(quotient, max_q_bits)?;
+
+        // Constrain `r < 2^{max_rhs_bits}`.
+        //
+        // If `rhs` is a power of 2, then is just a looser version of the following bound constraint.
+        // In the case where `rhs` isn't a power of 2 then this range constraint is required
+        // as the bound constraint creates a new witness.
+        // This opcode will be optimized out if it is redundant so we always add it for safety.
+        self.range_constraint(remainder, max_rhs_bits)?;
+
+        // Constrain `r < rhs`.
+        self.bound_constraint_with_offset(&remainder.into(), rhs, predicate, max_rhs_bits)?;
+
+        // a * predicate == (b * q + r) * predicate
+        // => predicate * (a - b * q - r) == 0
+        // When the predicate is 0, the equation always passes.
+        // When the predicate is 1, the euclidean division needs to be
+        // true.
+        let rhs_times_quotient = self.mul_with_witness(rhs, &quotient.into());
+        let difference = &(lhs - &rhs_times_quotient) - remainder;
+        let constraint = self.mul_with_witness(&difference, predicate);
+        self.assert_is_zero(constraint);
+
+        Ok((quotient, remainder))
+    }
+
+    /// Generate constraints that are satisfied iff
+    /// lhs < rhs , when offset is 1, or
+    /// lhs <= rhs, when offset is 0
+    /// bits is the bit size of a and b (or an upper bound of the bit size)
+    ///
+    /// lhs<=rhs is done by constraining b-a to a bit size of 'bits':
+    /// if lhs<=rhs, 0 <= rhs-lhs <= b < 2^bits
+    /// if lhs>rhs, rhs-lhs = p+rhs-lhs > p-2^bits >= 2^bits  (if log(p) >= bits + 1)
+    /// n.b: we do NOT check here that lhs and rhs are indeed 'bits' size
+    /// lhs < rhs <=> a+1<=b
+    /// TODO: Consolidate this with bounds_check function.
+    pub(crate) fn bound_constraint_with_offset(
+        &mut self,
+        lhs: &Expression,
+        rhs: &Expression,
+        offset: &Expression,
+        bits: u32,
+    ) -> Result<(), RuntimeError> {
+        const fn num_bits<T>() -> usize {
+            std::mem::size_of::<T>() * 8
+        }
+
+        fn bit_size_u128(a: u128) -> u32 where {
+            num_bits::<u128>() as u32 - a.leading_zeros()
+        }
+
+        assert!(
+            bits < FieldElement::max_num_bits(),
+            "range check with bit size of the prime field is not implemented yet"
+        );
+
+        let mut lhs_offset = lhs + offset;
+
+        // Optimization when rhs is const and fits within a u128, with room for `2^bit_size` below
+        if rhs.is_const() && rhs.q_c.num_bits() < 128 {
+            // We try to move the offset to rhs
+            let offset_is_one = offset.to_const().map_or(false, |offset| offset.is_one());
+            let rhs_offset = if offset_is_one && rhs.q_c.to_u128() >= 1 {
+                lhs_offset = lhs.clone();
+                rhs.q_c.to_u128() - 1
+            } else {
+                rhs.q_c.to_u128()
+            };
+            // we now have lhs+offset <= rhs <=> lhs_offset <= rhs_offset
+
+            let bit_size = bit_size_u128(rhs_offset);
+            // r = 2^bit_size - rhs_offset -1, is of bit size  'bit_size' by construction
+            let r = (1_u128 << bit_size) - rhs_offset - 1;
+            // however, since it is a constant, we can compute it's actual bit size
+            let r_bit_size = bit_size_u128(r);
+            // witness = lhs_offset + r
+            assert!(bits + r_bit_size < FieldElement::max_num_bits()); //we need to ensure lhs_offset + r does not overflow
+
+            let aor = lhs_offset + FieldElement::from(r);
+            // lhs_offset<=rhs_offset <=> lhs_offset + r < rhs_offset + r = 2^bit_size <=> witness < 2^bit_size
+            return self.range_constrain_expression(&aor, bit_size);
+        }
+        // General case:  lhs_offset<=rhs <=> rhs-lhs_offset>=0 <=> rhs-lhs_offset is a 'bits' bit integer
+        let sub_expression = rhs - &lhs_offset; //rhs-lhs_offset
+        self.range_constrain_expression(&sub_expression, bits)
+    }
+
+    /// Constrains `expression` to be an integer within the range `[0, 2^{num_bits} - 1]`,
+    /// converting it into a witness if it is not a constant which is already known to fit.
+    fn range_constrain_expression(
+        &mut self,
+        expression: &Expression,
+        num_bits: u32,
+    ) -> Result<(), RuntimeError> {
+        if let Some(constant) = expression.to_const() {
+            if constant.num_bits() <= num_bits {
+                return Ok(());
+            }
+        }
+        self.check_range_can_be_satisfied(expression, num_bits)?;
+
+        let witness = self.get_or_create_witness(expression);
+        self.range_constraint(witness, num_bits)
+    }
+
+    /// Adds a constraint which ensure thats `witness` is an
+    /// integer within the range `[0, 2^{num_bits} - 1]`
+    pub(crate) fn range_constraint(
         &mut self,
         witness: Witness,
         num_bits: u32,
@@ -527,14 +1538,195 @@ impl GeneratedAcir {
             });
         };
 
-        let constraint = AcirOpcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
-            input: FunctionInput { witness, num_bits },
-        });
-        self.push_opcode(constraint);
+        if self.fits_in_bits(witness, num_bits) {
+            return Ok(());
+        }
+        self.emit_range_constraint(witness, num_bits)
+    }
+
+    /// Emits a range constraint of `witness` to `num_bits`, even if it is known to be redundant.
+    fn emit_range_constraint(
+        &mut self,
+        witness: Witness,
+        num_bits: u32,
+    ) -> Result<(), RuntimeError> {
+        match RangeEncoding::choose(self.cost_model(), num_bits) {
+            RangeEncoding::BlackBox => {
+                let constraint = AcirOpcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
+                    input: FunctionInput { witness, num_bits },
+                });
+                self.push_opcode(constraint);
+            }
+            RangeEncoding::Boolean => {
+                // witness * witness - witness == 0
+                let mut constraint = Expression::default();
+                constraint.push_multiplication_term(FieldElement::one(), witness, witness);
+                constraint.push_addition_term(-FieldElement::one(), witness);
+                self.assert_is_zero(constraint);
+            }
+            RangeEncoding::BitDecomposition => {
+                self.radix_le_decompose(&witness.into(), 2, num_bits, 1)?;
+            }
+        }
+        if num_bits == 1 {
+            self.mark_boolean(witness);
+        }
+        self.mark_bit_size(witness, num_bits);
 
         Ok(())
     }
 
+    /// Requests a range constraint of `witness` to `num_bits`, which is only emitted by the next
+    /// call to [`flush_range_constraints`][Self::flush_range_constraints].
+    ///
+    /// Deferring the constraints lets many witnesses which are constrained to the same small
+    /// number of bits share a single table rather than each needing its own constraint.
+    pub(crate) fn range_constraint_batch(
+        &mut self,
+        witness: Witness,
+        num_bits: u32,
+    ) -> Result<(), RuntimeError> {
+        if num_bits >= FieldElement::max_num_bits() {
+            return Err(RuntimeError::InvalidRangeConstraint {
+                num_bits: FieldElement::max_num_bits(),
+                call_stack: self.call_stack.clone(),
+            });
+        };
+
+        self.add_pending_range_constraint(witness, (num_bits, self.call_stack.clone()));
+        Ok(())
+    }
+
+    /// Records a pending range constraint, unless one to as few bits is already pending.
+    fn add_pending_range_constraint(&mut self, witness: Witness, pending: (u32, CallStack)) {
+        match self.pending_range_constraints.get(&witness) {
+            Some((num_bits, _)) if *num_bits <= pending.0 => (),
+            _ => {
+                self.pending_range_constraints.insert(witness, pending);
+            }
+        }
+    }
+
+    /// Emits the range constraints of the bytes unpacked by
+    /// [`radix_decompose_deferred`][Self::radix_decompose_deferred] which have not been
+    /// made redundant by a black box function since.
+    pub(crate) fn flush_deferred_byte_constraints(&mut self) -> Result<(), RuntimeError> {
+        let call_stack = std::mem::take(&mut self.call_stack);
+        for (witness, byte_call_stack) in std::mem::take(&mut self.deferred_byte_constraints) {
+            self.call_stack = byte_call_stack;
+            self.emit_range_constraint(witness, 8)?;
+        }
+        self.call_stack = call_stack;
+        Ok(())
+    }
+
+    /// Emits the range constraints requested by
+    /// [`range_constraint_batch`][Self::range_constraint_batch], using `new_block_id` to
+    /// allocate the memory blocks of any tables which are needed.
+    pub(crate) fn flush_range_constraints(
+        &mut self,
+        mut new_block_id: impl FnMut() -> BlockId,
+    ) -> Result<(), RuntimeError> {
+        let mut batches: BTreeMap<u32, Vec<(Witness, CallStack)>> = BTreeMap::new();
+        for (witness, (num_bits, call_stack)) in std::mem::take(&mut self.pending_range_constraints)
+        {
+            // Constraints emitted since the request may already imply it.
+            if !self.fits_in_bits(witness, num_bits) {
+                batches.entry(num_bits).or_default().push((witness, call_stack));
+            }
+        }
+
+        let call_stack = std::mem::take(&mut self.call_stack);
+        for (num_bits, batch) in batches {
+            let has_table = self.range_tables.contains_key(&num_bits);
+            match RangeBatchEncoding::choose(self.cost_model(), num_bits, batch.len(), has_table) {
+                RangeBatchEncoding::Individually => {
+                    for (witness, call_stack) in batch {
+                        self.call_stack = call_stack;
+                        self.range_constraint(witness, num_bits)?;
+                    }
+                }
+                RangeBatchEncoding::Table => {
+                    let block_id = match self.range_tables.get(&num_bits) {
+                        Some(block_id) => *block_id,
+                        None => self.range_table(num_bits, new_block_id()),
+                    };
+                    for (witness, call_stack) in batch {
+                        self.call_stack = call_stack;
+                        let value = self.next_witness_index();
+                        let op = MemOp::read_at_mem_index(witness.into(), value);
+                        self.push_opcode(AcirOpcode::MemoryOp { block_id, op, predicate: None });
+                        self.mark_bit_size(witness, num_bits);
+                    }
+                }
+            }
+        }
+        self.call_stack = call_stack;
+        Ok(())
+    }
+
+    /// Initializes `block_id` with every value which fits within `num_bits`, in increasing order,
+    /// so that reading it at an index checks that the index fits within `num_bits`.
+    fn range_table(&mut self, num_bits: u32, block_id: BlockId) -> BlockId {
+        let init = vecmap(0..1_u128 << num_bits, |value| {
+            self.get_or_create_witness(&FieldElement::from(value).into())
+        });
+        self.push_opcode(AcirOpcode::MemoryInit { block_id, init });
+        self.range_tables.insert(num_bits, block_id);
+        block_id
+    }
+
+    /// Returns a witness constrained to be `values[index]`, failing if `index` is not smaller
+    /// than the number of values.
+    ///
+    /// The values are written once to a memory block allocated with `new_block_id`, which is
+    /// shared by later lookups into the same values. Each lookup then only costs the bounds check
+    /// of its index and a single memory read, so any function over a small domain can be encoded
+    /// as a table of its results.
+    pub(crate) fn lookup_table(
+        &mut self,
+        values: &[FieldElement],
+        index: &Expression,
+        new_block_id: impl FnOnce() -> BlockId,
+    ) -> Result<Witness, RuntimeError> {
+        assert!(!values.is_empty(), "ICE: lookup tables must hold at least one value");
+
+        // index <= max_index, which is only implied by the range constraint for powers of two.
+        let max_index = values.len() as u128 - 1;
+        if max_index == 0 {
+            self.assert_is_zero(index.clone());
+        } else {
+            let index_bits = u128::BITS - max_index.leading_zeros();
+            self.range_constrain_expression(index, index_bits)?;
+            if !values.len().is_power_of_two() {
+                self.bound_constraint_with_offset(
+                    index,
+                    &FieldElement::from(max_index).into(),
+                    &Expression::zero(),
+                    index_bits,
+                )?;
+            }
+        }
+
+        let block_id = match self.lookup_tables.get(values) {
+            Some(block_id) => *block_id,
+            None => {
+                let block_id = new_block_id();
+                let init =
+                    vecmap(values, |value| self.get_or_create_witness(&Expression::from(*value)));
+                self.push_opcode(AcirOpcode::MemoryInit { block_id, init });
+                self.lookup_tables.insert(values.to_vec(), block_id);
+                block_id
+            }
+        };
+
+        let index = self.get_or_create_witness(index);
+        let value = self.next_witness_index();
+        let op = MemOp::read_at_mem_index(index.into(), value);
+        self.push_opcode(AcirOpcode::MemoryOp { block_id, op, predicate: None });
+        Ok(value)
+    }
+
     pub(crate) fn brillig(
         &mut self,
         predicate: Option<Expression>,
@@ -542,31 +1734,48 @@ impl GeneratedAcir {
         inputs: Vec<BrilligInputs>,
         outputs: Vec<BrilligOutputs>,
     ) {
-        let opcode = AcirOpcode::Brillig(AcvmBrillig {
-            inputs,
-            outputs,
-            bytecode: generated_brillig.byte_code,
-            predicate,
-        });
-        self.push_opcode(opcode);
+        let id = self.intern_brillig_bytecode(generated_brillig.byte_code);
+        self.push_opcode(AcirOpcode::BrilligCall { id, inputs, outputs, predicate });
+        if let Some(step_bound) = generated_brillig.step_bound {
+            self.brillig_step_bounds.insert(self.num_opcodes() - 1, step_bound);
+        }
+        if !generated_brillig.variables.is_empty() {
+            let variables =
+                vecmap(&generated_brillig.variables, |variable| variable.to_debug_info());
+            self.brillig_variables.insert(self.num_opcodes() - 1, variables);
+        }
+        self.brillig_counters.extend(generated_brillig.profile_counters);
         for (brillig_index, call_stack) in generated_brillig.locations {
             self.locations.insert(
-                OpcodeLocation::Brillig { acir_index: self.opcodes.len() - 1, brillig_index },
-                call_stack,
+                OpcodeLocation::Brillig { acir_index: self.num_opcodes() - 1, brillig_index },
+                self.call_stacks.intern(&call_stack),
             );
         }
         for (brillig_index, message) in generated_brillig.assert_messages {
             self.assert_messages.insert(
-                OpcodeLocation::Brillig { acir_index: self.opcodes.len() - 1, brillig_index },
-                message,
+                OpcodeLocation::Brillig { acir_index: self.num_opcodes() - 1, brillig_index },
+                message.into(),
             );
         }
     }
 
+    /// Returns the index of `bytecode` within [`brillig_bytecode`][Self::brillig_bytecode], adding
+    /// it if no equal bytecode has been added yet.
+    fn intern_brillig_bytecode(&mut self, bytecode: Vec<BrilligOpcode>) -> u32 {
+        let index = self
+            .brillig_bytecode
+            .iter()
+            .position(|existing| existing.bytecode == bytecode)
+            .unwrap_or_else(|| {
+                self.brillig_bytecode.push(BrilligBytecode { bytecode });
+                self.brillig_bytecode.len() - 1
+            });
+        index as u32
+    }
+
     /// Generate gates and control bits witnesses which ensure that out_expr is a permutation of in_expr
-    /// Add the control bits of the sorting network used to generate the constrains
-    /// into the PermutationSort directive for solving in ACVM.
-    /// The directive is solving the control bits so that the outputs are sorted in increasing order.
+    /// The control bits of the sorting network used to generate the constraints are solved by a
+    /// Brillig program, so that the outputs are sorted in increasing order.
     ///
     /// n.b. A sorting network is a predetermined set of switches,
     /// the control bits indicate the configuration of each switch: false for pass-through and true for cross-over
@@ -581,13 +1790,10 @@ impl GeneratedAcir {
         }
 
         let bits = vecmap(0..bits_len, |_| self.next_witness_index());
-        let inputs = in_expr.iter().map(|a| vec![a.clone()]).collect();
-        self.push_opcode(AcirOpcode::Directive(Directive::PermutationSort {
-            inputs,
-            tuple: 1,
-            bits: bits.clone(),
-            sort_by: vec![0],
-        }));
+        let routing_code = brillig_directive::directive_permutation_routing(in_expr.len());
+        let inputs = vecmap(in_expr, |input| BrilligInputs::Single(input.clone()));
+        let outputs = vecmap(&bits, |bit| BrilligOutputs::Simple(*bit));
+        self.brillig(Some(Expression::one()), routing_code, inputs, outputs);
         let (_, b) = self.permutation_layer(in_expr, &bits, false)?;
 
         // Constrain the network output to out_expr
@@ -598,7 +1804,313 @@ impl GeneratedAcir {
     }
 
     pub(crate) fn last_acir_opcode_location(&self) -> OpcodeLocation {
-        OpcodeLocation::Acir(self.opcodes.len() - 1)
+        OpcodeLocation::Acir(self.num_opcodes() - 1)
+    }
+
+    /// Attaches `message` to the last opcode, to be shown by the solver if it is not satisfied.
+    pub(crate) fn set_last_assert_message(&mut self, message: AssertionPayload) {
+        self.assert_messages.insert(self.last_acir_opcode_location(), message);
+    }
+
+    /// Rebuilds the maps keyed by opcode location after the opcodes have been transformed.
+    ///
+    /// `new_locations` returns the locations which an opcode has been moved or decomposed to. The
+    /// entries of opcodes which have been removed, for which it returns no location, are dropped.
+    pub(crate) fn remap_opcode_locations<I>(&mut self, new_locations: impl Fn(OpcodeLocation) -> I)
+    where
+        I: IntoIterator<Item = OpcodeLocation>,
+    {
+        fn remap<T: Clone, I: IntoIterator<Item = OpcodeLocation>>(
+            map: BTreeMap<OpcodeLocation, T>,
+            new_locations: &impl Fn(OpcodeLocation) -> I,
+        ) -> BTreeMap<OpcodeLocation, T> {
+            map.into_iter()
+                .flat_map(|(location, value)| {
+                    new_locations(location)
+                        .into_iter()
+                        .map(move |new_location| (new_location, value.clone()))
+                })
+                .collect()
+        }
+
+        fn remap_acir<T: Clone, I: IntoIterator<Item = OpcodeLocation>>(
+            map: BTreeMap<usize, T>,
+            new_locations: &impl Fn(OpcodeLocation) -> I,
+        ) -> BTreeMap<usize, T> {
+            let map = map.into_iter().map(|(index, value)| (OpcodeLocation::Acir(index), value));
+            remap(map.collect(), new_locations)
+                .into_iter()
+                .filter_map(|(location, value)| match location {
+                    OpcodeLocation::Acir(index) => Some((index, value)),
+                    OpcodeLocation::Brillig { .. } => None,
+                })
+                .collect()
+        }
+
+        self.locations = remap(std::mem::take(&mut self.locations), &new_locations);
+        self.assert_messages = remap(std::mem::take(&mut self.assert_messages), &new_locations);
+
+        self.brillig_step_bounds =
+            remap_acir(std::mem::take(&mut self.brillig_step_bounds), &new_locations);
+        self.brillig_variables =
+            remap_acir(std::mem::take(&mut self.brillig_variables), &new_locations);
+    }
+
+    /// Returns an empty `GeneratedAcir` which continues from this one, so that it can be built
+    /// separately and then [merged][Self::merge] back in.
+    ///
+    /// It allocates witnesses after those of `self`, and starts out with the same settings, call
+    /// stack and facts about existing witnesses.
+    pub(crate) fn fork(&self) -> GeneratedAcir {
+        GeneratedAcir {
+            current_witness_index: self.current_witness_index,
+            call_stack: self.call_stack.clone(),
+            boolean_witnesses: self.boolean_witnesses.clone(),
+            expression_witnesses: self.expression_witnesses.clone(),
+            known_bit_sizes: self.known_bit_sizes.clone(),
+            cost_model: self.cost_model.clone(),
+            max_reduction_witnesses: self.max_reduction_witnesses,
+            warn_failing_range_constraints: self.warn_failing_range_constraints,
+            ..GeneratedAcir::default()
+        }
+    }
+
+    /// Returns an empty `GeneratedAcir` like [`fork`][Self::fork], which allocates witnesses
+    /// from a range of `num_witnesses` indices reserved for it.
+    ///
+    /// Several of these forks can be built independently of each other and of `self`, each
+    /// numbering its witnesses the same way whatever order they are built in. `num_witnesses` is
+    /// only an estimate: any witnesses beyond it are relocated after the others when the fork is
+    /// merged back in.
+    pub(crate) fn fork_with_witness_range(&mut self, num_witnesses: u32) -> GeneratedAcir {
+        assert!(self.witness_range.is_none(), "ICE: cannot reserve witnesses from within a range");
+        let mut fork = self.fork();
+        let start = self.current_witness_index.map_or(0, |index| index + 1);
+        fork.witness_range = Some(start..start + num_witnesses);
+        if num_witnesses > 0 {
+            self.reserve_witnesses(Witness(start + num_witnesses - 1));
+        }
+        fork
+    }
+
+    /// Replaces every witness referenced by the opcodes, the assertion messages, the input and
+    /// return witnesses, the names of witnesses and the facts known about witnesses with
+    /// `rename(witness)`.
+    pub(crate) fn rename_witnesses(&mut self, mut rename: impl FnMut(Witness) -> Witness) {
+        for opcode in &mut self.opcodes {
+            rename_opcode_witnesses(opcode, &mut rename);
+            if let AcirOpcode::AssertZero(expression) = opcode {
+                *expression = canonicalize_constraint(expression);
+            }
+        }
+        let parameters =
+            self.input_parameters.iter_mut().flat_map(|parameter| &mut parameter.witnesses);
+        let groups = self.return_groups.iter_mut().flat_map(|group| &mut group.witnesses);
+        for witness in self
+            .input_witnesses
+            .iter_mut()
+            .chain(parameters)
+            .chain(&mut self.return_witnesses)
+            .chain(groups)
+        {
+            *witness = rename(*witness);
+        }
+        for message in self.assert_messages.values_mut() {
+            rename_assertion_payload(message, &mut rename);
+        }
+        for (witness, name) in std::mem::take(&mut self.witness_names) {
+            self.witness_names.entry(rename(witness)).or_insert(name);
+        }
+
+        self.boolean_witnesses =
+            std::mem::take(&mut self.boolean_witnesses).into_iter().map(&mut rename).collect();
+        self.expression_witnesses = std::mem::take(&mut self.expression_witnesses)
+            .into_iter()
+            .map(|(mut expression, witness)| {
+                rename_expression(&mut expression, &mut rename);
+                (canonicalize(&expression), rename(witness))
+            })
+            .collect();
+        // Witnesses may be renamed to the same witness, which then satisfies the facts of each.
+        for (witness, num_bits) in std::mem::take(&mut self.known_bit_sizes) {
+            self.mark_bit_size(rename(witness), num_bits);
+        }
+        for (witness, pending) in std::mem::take(&mut self.pending_range_constraints) {
+            self.add_pending_range_constraint(rename(witness), pending);
+        }
+        self.deferred_byte_constraints = std::mem::take(&mut self.deferred_byte_constraints)
+            .into_iter()
+            .map(|(witness, call_stack)| (rename(witness), call_stack))
+            .collect();
+    }
+
+    /// Renumbers the witnesses densely from zero, dropping those which are not referenced by an
+    /// opcode, an assertion message, the input or return witnesses or `required`, and returns the
+    /// new index of each witness which is kept.
+    ///
+    /// Witnesses keep their relative order, so that contiguous input witnesses stay contiguous.
+    pub(crate) fn compact_witnesses(
+        &mut self,
+        required: impl IntoIterator<Item = Witness>,
+    ) -> BTreeMap<Witness, Witness> {
+        assert_eq!(self.overflow_witnesses, 0, "ICE: cannot compact the witnesses of a fork");
+        let mut used: BTreeSet<Witness> = required.into_iter().collect();
+        used.extend(self.input_witnesses.iter().chain(&self.return_witnesses));
+        used.extend(self.pending_range_constraints.keys());
+        used.extend(self.deferred_byte_constraints.keys());
+        used.extend(self.assert_messages.values().flat_map(AssertionPayload::witnesses));
+        for opcode in &mut self.opcodes {
+            rename_opcode_witnesses(opcode, &mut |witness| {
+                used.insert(witness);
+                witness
+            });
+        }
+        let renaming: BTreeMap<Witness, Witness> =
+            used.into_iter().zip(0..).map(|(witness, index)| (witness, Witness(index))).collect();
+
+        // Facts about dropped witnesses would otherwise be taken to hold for the witnesses which
+        // are given their indices.
+        let is_kept = |witness: &Witness| renaming.contains_key(witness);
+        self.boolean_witnesses.retain(is_kept);
+        self.known_bit_sizes.retain(|witness, _| is_kept(witness));
+        self.witness_names.retain(|witness, _| is_kept(witness));
+        self.expression_witnesses.retain(|expression, witness| {
+            is_kept(witness)
+                && expression.mul_terms.iter().all(|(_, lhs, rhs)| is_kept(lhs) && is_kept(rhs))
+                && expression.linear_combinations.iter().all(|(_, witness)| is_kept(witness))
+        });
+
+        self.rename_witnesses(|witness| renaming[&witness]);
+        self.current_witness_index = renaming.values().next_back().map(|witness| witness.0);
+        renaming
+    }
+
+    /// Appends the opcodes of `other`, which must have been [forked][Self::fork] from `self`,
+    /// along with their locations, assertion messages and Brillig step bounds, variables and
+    /// counters.
+    ///
+    /// Witnesses are only renumbered if `other` outgrew its
+    /// [reserved range][Self::fork_with_witness_range]. Otherwise if several plain forks are
+    /// merged then each must have been forked after the previous one was merged back in.
+    pub(crate) fn merge(&mut self, mut other: GeneratedAcir) {
+        self.current_witness_index = self.current_witness_index.max(other.current_witness_index);
+        if other.overflow_witnesses > 0 {
+            let relocated = vecmap(0..other.overflow_witnesses, |_| self.next_witness_index());
+            let first_overflow = u32::MAX - (other.overflow_witnesses - 1);
+            other.rename_witnesses(|witness| {
+                if witness.0 >= first_overflow {
+                    relocated[(u32::MAX - witness.0) as usize]
+                } else {
+                    witness
+                }
+            });
+        }
+
+        let offset = self.num_opcodes();
+        other.remap_opcode_locations(|location| {
+            std::iter::once(match location {
+                OpcodeLocation::Acir(index) => OpcodeLocation::Acir(index + offset),
+                OpcodeLocation::Brillig { acir_index, brillig_index } => {
+                    OpcodeLocation::Brillig { acir_index: acir_index + offset, brillig_index }
+                }
+            })
+        });
+
+        let brillig_ids = vecmap(other.brillig_bytecode, |brillig_bytecode| {
+            self.intern_brillig_bytecode(brillig_bytecode.bytecode)
+        });
+        for opcode in &mut other.opcodes {
+            if let AcirOpcode::BrilligCall { id, .. } = opcode {
+                *id = brillig_ids[*id as usize];
+            }
+        }
+        self.opcodes.append(&mut other.opcodes);
+        self.stream_opcodes();
+        self.return_witnesses.append(&mut other.return_witnesses);
+        self.return_groups.append(&mut other.return_groups);
+        self.input_witnesses.append(&mut other.input_witnesses);
+        self.input_parameters.append(&mut other.input_parameters);
+        for (location, call_stack) in other.locations {
+            let call_stack = other.call_stacks.resolve(call_stack);
+            self.locations.insert(location, self.call_stacks.intern(&call_stack));
+        }
+        self.assert_messages.append(&mut other.assert_messages);
+        self.brillig_step_bounds.append(&mut other.brillig_step_bounds);
+        self.brillig_variables.append(&mut other.brillig_variables);
+        self.brillig_counters.append(&mut other.brillig_counters);
+        self.warnings.append(&mut other.warnings);
+        for (witness, name) in other.witness_names {
+            self.witness_names.entry(witness).or_insert(name);
+        }
+
+        self.boolean_witnesses.append(&mut other.boolean_witnesses);
+        self.expression_witnesses.extend(other.expression_witnesses);
+        for (witness, num_bits) in other.known_bit_sizes {
+            self.mark_bit_size(witness, num_bits);
+        }
+        for (location, count) in other.reduction_witnesses {
+            *self.reduction_witnesses.entry(location).or_default() += count;
+        }
+        for (witness, pending) in other.pending_range_constraints {
+            self.add_pending_range_constraint(witness, pending);
+        }
+        self.deferred_byte_constraints.extend(other.deferred_byte_constraints);
+        for (num_bits, block_id) in other.range_tables {
+            self.range_tables.entry(num_bits).or_insert(block_id);
+        }
+        for (values, block_id) in other.lookup_tables {
+            self.lookup_tables.entry(values).or_insert(block_id);
+        }
+    }
+}
+
+/// Returns `expression` with the terms on the same witnesses merged, the terms with a zero
+/// coefficient removed and the remaining terms sorted, so that equal expressions are equal.
+fn canonicalize(expression: &Expression) -> Expression {
+    let mut mul_terms = BTreeMap::new();
+    for (coefficient, lhs, rhs) in &expression.mul_terms {
+        let witnesses = if lhs <= rhs { (*lhs, *rhs) } else { (*rhs, *lhs) };
+        let sum = mul_terms.entry(witnesses).or_insert_with(FieldElement::zero);
+        *sum += *coefficient;
+    }
+    let mut linear_combinations = BTreeMap::new();
+    for (coefficient, witness) in &expression.linear_combinations {
+        let sum = linear_combinations.entry(*witness).or_insert_with(FieldElement::zero);
+        *sum += *coefficient;
+    }
+
+    Expression {
+        mul_terms: mul_terms
+            .into_iter()
+            .filter(|(_, coefficient)| !coefficient.is_zero())
+            .map(|((lhs, rhs), coefficient)| (coefficient, lhs, rhs))
+            .collect(),
+        linear_combinations: linear_combinations
+            .into_iter()
+            .filter(|(_, coefficient)| !coefficient.is_zero())
+            .map(|(witness, coefficient)| (coefficient, witness))
+            .collect(),
+        q_c: expression.q_c,
+    }
+}
+
+/// Returns the [canonical form][canonicalize] of `expression`, negated if needed so that its first
+/// coefficient is the smaller of itself and its negation.
+///
+/// Negating an expression which is asserted to be zero does not change the constraint, so
+/// constraints which only differ by sign are also made equal.
+pub(crate) fn canonicalize_constraint(expression: &Expression) -> Expression {
+    let expression = canonicalize(expression);
+    let first_coefficient = expression
+        .mul_terms
+        .first()
+        .map(|(coefficient, _, _)| *coefficient)
+        .or_else(|| expression.linear_combinations.first().map(|(coefficient, _)| *coefficient))
+        .unwrap_or(expression.q_c);
+    if -first_coefficient < first_coefficient {
+        -&expression
+    } else {
+        expression
     }
 }
 
@@ -613,6 +2125,7 @@ fn black_box_func_expected_input_size(name: BlackBoxFunc) -> Option<usize> {
         // variable number of inputs.
         BlackBoxFunc::Keccak256
         | BlackBoxFunc::SHA256
+        | BlackBoxFunc::Sha512
         | BlackBoxFunc::Blake2s
         | BlackBoxFunc::Blake3
         | BlackBoxFunc::PedersenCommitment
@@ -621,6 +2134,8 @@ fn black_box_func_expected_input_size(name: BlackBoxFunc) -> Option<usize> {
         BlackBoxFunc::Keccakf1600 => Some(25),
         // The permutation takes a fixed number of inputs, but the inputs length depends on the proving system implementation.
         BlackBoxFunc::Poseidon2Permutation => None,
+        // The permutation takes a state whose length is given by a constant argument.
+        BlackBoxFunc::PoseidonPermutation => None,
 
         // SHA256 compression requires 16 u32s as input message and 8 u32s for the hash state.
         BlackBoxFunc::Sha256Compression => Some(24),
@@ -632,15 +2147,29 @@ fn black_box_func_expected_input_size(name: BlackBoxFunc) -> Option<usize> {
         // number of inputs, since the message/hashed-message can vary in size.
         BlackBoxFunc::SchnorrVerify
         | BlackBoxFunc::EcdsaSecp256k1
-        | BlackBoxFunc::EcdsaSecp256r1 => None,
+        | BlackBoxFunc::EcdsaSecp256r1
+        | BlackBoxFunc::Ed25519 => None,
 
         // Inputs for fixed based scalar multiplication
         // is the low and high limbs of the scalar
         BlackBoxFunc::FixedBaseScalarMul => Some(2),
 
+        // Inputs for multi scalar multiplication are the coordinates of a variable number of
+        // points, followed by the low and high limbs of the scalar of each point
+        BlackBoxFunc::MultiScalarMul => None,
+
+        // AES encryption takes a variable length plaintext, followed by the 16 bytes of each
+        // of the initialization vector and the key
+        BlackBoxFunc::AES128Encrypt => None,
+
         // Recursive aggregation has a variable number of inputs
         BlackBoxFunc::RecursiveAggregation => None,
 
+        // Points over BLS12-381 are encoded as the 48 bytes of each of their coordinates,
+        // and scalars as 32 bytes.
+        BlackBoxFunc::Bls12381G1Add => Some(192),
+        BlackBoxFunc::Bls12381G1Mul => Some(128),
+
         // Addition over the embedded curve: input are coordinates (x1,y1) and (x2,y2) of the Grumpkin points
         BlackBoxFunc::EmbeddedCurveAdd => Some(4),
 
@@ -670,9 +2199,13 @@ fn black_box_expected_output_size(name: BlackBoxFunc) -> Option<usize> {
         | BlackBoxFunc::Blake2s
         | BlackBoxFunc::Blake3 => Some(32),
 
+        // 64 byte hash algorithms
+        BlackBoxFunc::Sha512 => Some(64),
+
         BlackBoxFunc::Keccakf1600 => Some(25),
         // The permutation returns a fixed number of outputs, equals to the inputs length which depends on the proving system implementation.
         BlackBoxFunc::Poseidon2Permutation => None,
+        BlackBoxFunc::PoseidonPermutation => None,
 
         BlackBoxFunc::Sha256Compression => Some(8),
         // Pedersen commitment returns a point
@@ -688,11 +2221,17 @@ fn black_box_expected_output_size(name: BlackBoxFunc) -> Option<usize> {
         // Signature verification algorithms will return a boolean
         BlackBoxFunc::SchnorrVerify
         | BlackBoxFunc::EcdsaSecp256k1
-        | BlackBoxFunc::EcdsaSecp256r1 => Some(1),
+        | BlackBoxFunc::EcdsaSecp256r1
+        | BlackBoxFunc::Ed25519 => Some(1),
 
         // Output of operations over the embedded curve
         // will be 2 field elements representing the point.
-        BlackBoxFunc::FixedBaseScalarMul | BlackBoxFunc::EmbeddedCurveAdd => Some(2),
+        BlackBoxFunc::FixedBaseScalarMul
+        | BlackBoxFunc::MultiScalarMul
+        | BlackBoxFunc::EmbeddedCurveAdd => Some(2),
+
+        // Operations over BLS12-381 return the 96 bytes of an encoded point.
+        BlackBoxFunc::Bls12381G1Add | BlackBoxFunc::Bls12381G1Mul => Some(96),
 
         // Big integer operations return a big integer
         BlackBoxFunc::BigIntAdd
@@ -704,6 +2243,9 @@ fn black_box_expected_output_size(name: BlackBoxFunc) -> Option<usize> {
         // ToLeBytes returns a variable array of bytes
         BlackBoxFunc::BigIntToLeBytes => None,
 
+        // AES encryption returns the padded ciphertext, whose length depends on the plaintext
+        BlackBoxFunc::AES128Encrypt => None,
+
         // Recursive aggregation has a variable number of outputs
         BlackBoxFunc::RecursiveAggregation => None,
     }
@@ -717,20 +2259,27 @@ fn black_box_expected_output_size(name: BlackBoxFunc) -> Option<usize> {
 /// In that case, this function will not check anything.
 ///
 /// Since we expect black box functions to be called behind a Noir shim function,
-/// we trigger a compiler error if the inputs do not match.
+/// we return a compiler error pointing at the call if the inputs do not match.
 ///
 /// An example of Noir shim function is the following:
 /// ``
 /// #[foreign(sha256)]
 /// fn sha256<N>(_input : [u8; N]) -> [u8; 32] {}
 /// ``
-fn intrinsics_check_inputs(name: BlackBoxFunc, input_count: usize) {
-    let expected_num_inputs = match black_box_func_expected_input_size(name) {
-        Some(expected_num_inputs) => expected_num_inputs,
-        None => return,
-    };
-
-    assert_eq!(expected_num_inputs,input_count,"Tried to call black box function {name} with {input_count} inputs, but this function's definition requires {expected_num_inputs} inputs");
+fn intrinsics_check_inputs(
+    name: BlackBoxFunc,
+    input_count: usize,
+    call_stack: &CallStack,
+) -> Result<(), RuntimeError> {
+    match black_box_func_expected_input_size(name) {
+        Some(expected) if expected != input_count => Err(RuntimeError::BlackBoxInputCount {
+            name,
+            expected,
+            found: input_count,
+            call_stack: call_stack.clone(),
+        }),
+        _ => Ok(()),
+    }
 }
 
 /// Checks that the number of outputs being used to call the blackbox function
@@ -741,7 +2290,7 @@ fn intrinsics_check_inputs(name: BlackBoxFunc, input_count: usize) {
 /// In that case, this function will not check anything.
 ///
 /// Since we expect black box functions to be called behind a Noir shim function,
-/// we trigger a compiler error if the inputs do not match.
+/// we return a compiler error pointing at the call if the outputs do not match.
 ///
 /// An example of Noir shim function is the following:
 /// ``
@@ -754,11 +2303,915 @@ fn intrinsics_check_inputs(name: BlackBoxFunc, input_count: usize) {
 ///     _input_aggregation_object : [Field; N]
 /// ) -> [Field; N] {}
 /// ``
-fn intrinsics_check_outputs(name: BlackBoxFunc, output_count: usize) {
-    let expected_num_outputs = match black_box_expected_output_size(name) {
-        Some(expected_num_inputs) => expected_num_inputs,
-        None => return,
+fn intrinsics_check_outputs(
+    name: BlackBoxFunc,
+    output_count: usize,
+    call_stack: &CallStack,
+) -> Result<(), RuntimeError> {
+    match black_box_expected_output_size(name) {
+        Some(expected) if expected != output_count => Err(RuntimeError::BlackBoxOutputCount {
+            name,
+            expected,
+            found: output_count,
+            call_stack: call_stack.clone(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use acvm::{
+        acir::{
+            circuit::{
+                opcodes::{BlackBoxFuncCall, BlockId, FunctionInput, Opcode},
+                AssertionPayload, AssertionPayloadPart, OpcodeLocation,
+            },
+            native_types::{Expression, Witness},
+            BlackBoxFunc,
+        },
+        FieldElement,
     };
+    use iter_extended::vecmap;
+    use noirc_errors::{Location, Span};
+    use noirc_frontend::Visibility;
+
+    use crate::{
+        cost_model::JsonCostModel,
+        errors::{InternalError, InternalWarning, RuntimeError, SsaReport},
+        ssa::ir::instruction::Endian,
+    };
+
+    use super::GeneratedAcir;
+
+    #[test]
+    fn booleanity_is_constrained_at_most_once() {
+        let mut acir = GeneratedAcir::default();
+        let bit = acir.next_witness_index();
+        acir.range_constraint(bit, 1).unwrap();
+        acir.range_constraint(bit, 1).unwrap();
+        acir.range_constraint(bit, 8).unwrap();
+        assert_eq!(acir.take_opcodes().len(), 1);
+
+        // The result of an equality check is boolean by construction.
+        let lhs = acir.next_witness_index();
+        let is_equal = acir.is_equal(&lhs.into(), &Expression::zero());
+        acir.take_opcodes();
+        acir.range_constraint(is_equal, 1).unwrap();
+        assert!(acir.take_opcodes().is_empty());
+    }
+
+    #[test]
+    fn boolean_values_are_checked_for_zero_by_negation() {
+        let mut acir = GeneratedAcir::default();
+        let bit = acir.next_witness_index();
+        acir.range_constraint(bit, 1).unwrap();
+        acir.take_opcodes();
+
+        let is_zero = acir.is_equal(&bit.into(), &Expression::zero());
+        let opcodes = acir.take_opcodes();
+        assert_eq!(opcodes.len(), 1);
+        assert!(!opcodes.iter().any(|opcode| matches!(opcode, Opcode::BrilligCall { .. })));
+        assert!(acir.is_boolean(is_zero));
+    }
+
+    #[test]
+    fn wider_range_constraints_are_not_repeated() {
+        let mut acir = GeneratedAcir::default();
+        let witness = acir.next_witness_index();
+        acir.range_constraint(witness, 8).unwrap();
+        acir.range_constraint(witness, 8).unwrap();
+        acir.range_constraint(witness, 32).unwrap();
+        assert_eq!(acir.take_opcodes().len(), 1);
+
+        // A narrower constraint is still needed, and then makes the first one redundant.
+        acir.range_constraint(witness, 4).unwrap();
+        acir.range_constraint(witness, 6).unwrap();
+        assert_eq!(acir.take_opcodes().len(), 1);
+    }
+
+    #[test]
+    fn range_constraints_of_other_witnesses_are_kept() {
+        let mut acir = GeneratedAcir::default();
+        let witness = acir.next_witness_index();
+        acir.range_constraint(witness, 8).unwrap();
+        acir.range_constraint(witness, 1).unwrap();
+
+        let opcodes = acir.take_opcodes();
+        assert_eq!(opcodes.len(), 2);
+        assert!(opcodes.iter().all(|opcode| matches!(
+            opcode,
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { .. })
+        )));
+    }
+
+    #[test]
+    fn batched_range_constraints_are_merged() {
+        let mut acir = GeneratedAcir::default();
+        let witness = acir.next_witness_index();
+        acir.range_constraint_batch(witness, 16).unwrap();
+        acir.range_constraint_batch(witness, 8).unwrap();
+        acir.range_constraint_batch(witness, 32).unwrap();
+        assert!(acir.take_opcodes().is_empty());
+
+        acir.flush_range_constraints(|| unreachable!("no table should be needed")).unwrap();
+        let opcodes = acir.take_opcodes();
+        assert_eq!(
+            opcodes,
+            vec![Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
+                input: FunctionInput { witness, num_bits: 8 }
+            })]
+        );
+    }
+
+    #[test]
+    fn batched_range_constraints_share_a_table() {
+        let mut acir = GeneratedAcir::default();
+        let model = JsonCostModel {
+            black_box_per_bit: [("range".to_string(), 4)].into_iter().collect(),
+            ..JsonCostModel::default()
+        };
+        acir.set_cost_model(Arc::new(model));
+
+        let witnesses = vecmap(0..10, |_| acir.next_witness_index());
+        for witness in &witnesses[..9] {
+            acir.range_constraint_batch(*witness, 4).unwrap();
+        }
+        acir.flush_range_constraints(|| BlockId(3)).unwrap();
+        let opcodes = acir.take_opcodes();
+        // A witness is constrained to each of the 16 values of the table before it is initialized.
+        assert!(
+            matches!(&opcodes[16], Opcode::MemoryInit { block_id: BlockId(3), init } if init.len() == 16)
+        );
+        assert_eq!(opcodes.len(), 16 + 1 + 9);
+        assert!(opcodes[17..].iter().all(|opcode| matches!(opcode, Opcode::MemoryOp { .. })));
+        assert!(witnesses[..9].iter().all(|witness| acir.fits_in_bits(*witness, 4)));
+
+        // A later batch reuses the table even if it is small.
+        acir.range_constraint_batch(witnesses[9], 4).unwrap();
+        acir.flush_range_constraints(|| unreachable!("the table should be reused")).unwrap();
+        let opcodes = acir.take_opcodes();
+        assert!(matches!(&opcodes[..], [Opcode::MemoryOp { block_id: BlockId(3), .. }]));
+    }
+
+    #[test]
+    fn lookups_into_equal_tables_share_a_memory_block() {
+        let mut acir = GeneratedAcir::default();
+        let squares = vecmap(0..5_u128, |value| FieldElement::from(value * value));
+        let index = acir.next_witness_index();
+        let other_index = acir.next_witness_index();
+
+        let value = acir.lookup_table(&squares, &index.into(), || BlockId(2)).unwrap();
+        let opcodes = acir.take_opcodes();
+        let Some(Opcode::MemoryInit { block_id: BlockId(2), init }) =
+            opcodes.iter().find(|opcode| matches!(opcode, Opcode::MemoryInit { .. }))
+        else {
+            panic!("expected the table to be initialized");
+        };
+        assert_eq!(init.len(), 5);
+        assert!(matches!(
+            opcodes.last(),
+            Some(Opcode::MemoryOp { block_id: BlockId(2), op, .. }) if op.value == value.into()
+        ));
+
+        acir.lookup_table(&squares, &other_index.into(), || unreachable!("the table is reused"))
+            .unwrap();
+        let opcodes = acir.take_opcodes();
+        assert!(opcodes.iter().all(|opcode| !matches!(opcode, Opcode::MemoryInit { .. })));
+        assert!(matches!(opcodes.last(), Some(Opcode::MemoryOp { block_id: BlockId(2), .. })));
+    }
+
+    #[test]
+    fn lookup_indices_are_range_constrained_to_the_table() {
+        let mut acir = GeneratedAcir::default();
+        let values = vecmap(0..4_u128, FieldElement::from);
+        let index = acir.next_witness_index();
+        acir.lookup_table(&values, &index.into(), || BlockId(0)).unwrap();
+        assert!(acir.fits_in_bits(index, 2));
+
+        // Indices into tables whose length is not a power of two are also bounded by it.
+        let mut acir = GeneratedAcir::default();
+        let values = vecmap(0..3_u128, FieldElement::from);
+        let index = acir.next_witness_index();
+        acir.lookup_table(&values, &index.into(), || BlockId(0)).unwrap();
+        let range_constraints = acir
+            .opcodes()
+            .iter()
+            .filter(|opcode| {
+                matches!(opcode, Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { .. }))
+            })
+            .count();
+        assert_eq!(range_constraints, 2);
+    }
+
+    #[test]
+    fn warns_once_per_expression_needing_many_reduction_witnesses() {
+        let mut acir = GeneratedAcir { max_reduction_witnesses: Some(2), ..Default::default() };
+        let x = Expression::from(acir.next_witness_index());
+        let square = acir.mul_with_witness(&x, &x);
+
+        let location = Location::new(Span::single_char(1), Default::default());
+        acir.call_stack.push_back(location);
+        let cube = acir.mul_with_witness(&square, &x);
+        let fourth_power = acir.mul_with_witness(&square, &square);
+        assert!(acir.warnings.is_empty());
+
+        // Both operands need to be reduced, crossing the limit.
+        let product = acir.mul_with_witness(&cube, &fourth_power);
+        acir.mul_with_witness(&product, &product);
+        assert_eq!(acir.warnings.len(), 1);
+        assert!(matches!(
+            &acir.warnings[0],
+            SsaReport::Warning(InternalWarning::ReductionWitnesses {
+                max_reduction_witnesses: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn return_witnesses_are_split_into_groups() {
+        let mut acir = GeneratedAcir::default();
+        let witnesses: Vec<_> = (0..3).map(|_| acir.next_witness_index()).collect();
+        for witness in &witnesses {
+            acir.push_return_witness(*witness);
+        }
+
+        acir.group_return_witnesses(vec![("x".to_string(), 1), ("y".to_string(), 2)]);
+        let groups: Vec<_> =
+            acir.return_groups.into_iter().map(|group| (group.name, group.witnesses)).collect();
+        assert_eq!(
+            groups,
+            vec![
+                ("x".to_string(), vec![witnesses[0]]),
+                ("y".to_string(), vec![witnesses[1], witnesses[2]])
+            ]
+        );
+    }
+
+    #[test]
+    fn input_witnesses_are_split_into_parameters() {
+        let mut acir = GeneratedAcir::default();
+        acir.input_witnesses = (0..3).map(|_| acir.next_witness_index()).collect();
+
+        acir.group_input_witnesses(vec![
+            ("x".to_string(), Visibility::Public, 1),
+            ("ys".to_string(), Visibility::Private, 2),
+        ]);
+        let parameters: Vec<_> = acir
+            .input_parameters
+            .into_iter()
+            .map(|parameter| (parameter.name, parameter.visibility, parameter.witnesses))
+            .collect();
+        assert_eq!(
+            parameters,
+            vec![
+                ("x".to_string(), Visibility::Public, vec![Witness(0)]),
+                ("ys".to_string(), Visibility::Private, vec![Witness(1), Witness(2)])
+            ]
+        );
+    }
+
+    #[test]
+    fn packing_unpacked_bytes_reuses_their_range_constraints() {
+        let mut acir = GeneratedAcir::default();
+        let value = Expression::from(acir.next_witness_index());
+        let bytes = acir.unpack_bytes(&value, 31).unwrap();
+        assert_eq!(bytes.len(), 31);
+        let num_opcodes = acir.take_opcodes().len();
+        assert!(num_opcodes > 31);
+
+        let packed = acir.pack_bytes(&bytes).unwrap();
+        assert!(acir.take_opcodes().is_empty());
+        assert!(packed.is_linear());
+        assert_eq!(packed.linear_combinations.len(), 31);
+
+        // A byte which was not unpacked is range constrained once.
+        let byte = Expression::from(acir.next_witness_index());
+        acir.pack_bytes(&[byte.clone(), byte]).unwrap();
+        assert_eq!(acir.take_opcodes().len(), 1);
+
+        assert!(matches!(
+            acir.unpack_bytes(&value, 32),
+            Err(RuntimeError::UnsupportedIntegerSize { num_bits: 256, .. })
+        ));
+    }
+
+    #[test]
+    fn hashed_bytes_are_not_range_constrained_twice() {
+        let mut acir = GeneratedAcir::default();
+        let value = Expression::from(acir.next_witness_index());
+        let bytes = acir.radix_decompose_deferred(Endian::Little, &value, 256, 4, 8).unwrap();
+        // Only the decomposition and the check that it recomposes `value` are emitted.
+        assert_eq!(acir.take_opcodes().len(), 2);
+        assert!(bytes.iter().all(|byte| acir.fits_in_bits(*byte, 8)));
+
+        let hashed = vecmap(&bytes[..2], |byte| FunctionInput { witness: *byte, num_bits: 8 });
+        let message_size = FunctionInput { witness: acir.next_witness_index(), num_bits: 32 };
+        let inputs = [hashed, vec![message_size]];
+        acir.call_black_box(BlackBoxFunc::Keccak256, &inputs, vec![], vec![], 32, None).unwrap();
+        assert_eq!(acir.take_opcodes().len(), 1);
+
+        // The bytes which were not hashed are still range constrained.
+        acir.flush_deferred_byte_constraints().unwrap();
+        let constrained = vecmap(acir.take_opcodes(), |opcode| match opcode {
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { input }) => input.witness,
+            opcode => panic!("expected a range constraint, found {opcode:?}"),
+        });
+        assert_eq!(constrained, &bytes[2..]);
+    }
+
+    #[test]
+    fn batched_inverses_are_computed_by_a_single_opcode() {
+        let mut acir = GeneratedAcir::default();
+        let exprs = vecmap(0..4, |_| Expression::from(acir.next_witness_index()));
+        let inverses = acir.brillig_inverse_batch(exprs.clone());
+        assert_eq!(inverses.len(), exprs.len());
+
+        let opcodes = acir.take_opcodes();
+        let [Opcode::BrilligCall { inputs, outputs, .. }] = opcodes.as_slice() else {
+            panic!("expected a single brillig opcode, found {opcodes:?}");
+        };
+        assert_eq!(inputs.len(), exprs.len());
+        assert_eq!(outputs.len(), exprs.len());
+    }
+
+    #[test]
+    fn identical_brillig_calls_share_their_bytecode() {
+        let mut acir = GeneratedAcir::default();
+        let witness = acir.next_witness_index();
+        acir.brillig_inverse(witness.into());
+        acir.brillig_inverse(witness.into());
+        assert_eq!(acir.brillig_bytecode.len(), 1);
+
+        let opcodes = acir.take_opcodes();
+        let ids = vecmap(&opcodes, |opcode| match opcode {
+            Opcode::BrilligCall { id, .. } => *id,
+            opcode => panic!("expected a brillig call, found {opcode:?}"),
+        });
+        assert_eq!(ids, vec![0, 0]);
+    }
+
+    #[test]
+    fn euclidean_division_is_solved_by_a_single_brillig_opcode() {
+        let mut acir = GeneratedAcir::default();
+        let lhs = acir.next_witness_index();
+        let rhs = Expression::from_field(FieldElement::from(10_u128));
+        let (quotient, remainder) =
+            acir.euclidean_division(&lhs.into(), &rhs, &Expression::one(), 8, 5, 4).unwrap();
+        assert!(acir.fits_in_bits(quotient, 5));
+        assert!(acir.fits_in_bits(remainder, 4));
+
+        let opcodes = acir.take_opcodes();
+        let brillig_count =
+            opcodes.iter().filter(|opcode| matches!(opcode, Opcode::BrilligCall { .. })).count();
+        assert_eq!(brillig_count, 1);
+
+        // lhs - 10 * q - r == 0
+        let Some(Opcode::AssertZero(division)) = opcodes.last() else {
+            panic!("expected the division to end with its recomposition");
+        };
+        let mut terms = division.linear_combinations.clone();
+        terms.sort_by_key(|(_, witness)| *witness);
+        assert_eq!(
+            terms,
+            vec![
+                (FieldElement::one(), lhs),
+                (-FieldElement::from(10_u128), quotient),
+                (-FieldElement::one(), remainder),
+            ]
+        );
+        assert!(division.mul_terms.is_empty());
+    }
+
+    #[test]
+    fn constant_exponents_are_applied_without_decomposition() {
+        let mut acir = GeneratedAcir::default();
+        let base = Expression::from(acir.next_witness_index());
+        let exponent = Expression::from_field(FieldElement::from(5_u128));
+        let power = acir.pow(&base, &exponent, 32).unwrap();
+        assert_eq!(power.mul_terms.len(), 1);
+
+        // x^5 = (x^2)^2 * x only needs x^2 and x^4 to be reduced to witnesses.
+        let opcodes = acir.take_opcodes();
+        assert_eq!(opcodes.len(), 2);
+        assert!(opcodes.iter().all(|opcode| matches!(opcode, Opcode::AssertZero(_))));
+    }
+
+    #[test]
+    fn exponents_known_at_runtime_are_decomposed_into_bits() {
+        let mut acir = GeneratedAcir::default();
+        let base = Expression::from(acir.next_witness_index());
+        let exponent = Expression::from(acir.next_witness_index());
+        acir.pow(&base, &exponent, 8).unwrap();
+
+        let opcodes = acir.take_opcodes();
+        let brillig_count =
+            opcodes.iter().filter(|opcode| matches!(opcode, Opcode::BrilligCall { .. })).count();
+        assert_eq!(brillig_count, 1);
+    }
+
+    #[test]
+    fn big_endian_limbs_are_recomposed_from_the_last() {
+        let mut acir = GeneratedAcir::default();
+        let value = Expression::from(acir.next_witness_index());
+        let bytes = acir.radix_be_decompose(&value, 256, 2, 8).unwrap();
+
+        let Some(Opcode::AssertZero(recomposition)) = acir.take_opcodes().pop() else {
+            panic!("expected the decomposition to end with its recomposition");
+        };
+        let coefficient = |byte: Witness| {
+            recomposition
+                .linear_combinations
+                .iter()
+                .find_map(|(coefficient, witness)| (*witness == byte).then_some(*coefficient))
+                .unwrap()
+        };
+        assert_eq!(coefficient(bytes[0]), coefficient(bytes[1]) * FieldElement::from(256_u128));
+    }
+
+    #[test]
+    fn limbs_are_constrained_to_be_less_than_the_radix() {
+        let mut acir = GeneratedAcir::default();
+        let value = Expression::from(acir.next_witness_index());
+        let digits = acir.radix_le_decompose(&value, 10, 3, 4).unwrap();
+
+        // Each digit and its sum with 16 - 10 are constrained to 4 bits.
+        let constrained: Vec<_> = acir
+            .take_opcodes()
+            .into_iter()
+            .filter_map(|opcode| match opcode {
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { input }) => {
+                    assert_eq!(input.num_bits, 4);
+                    Some(input.witness)
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(constrained.len(), 2 * digits.len());
+        for digit in digits {
+            let offset_digit = Expression::from(digit) + FieldElement::from(6_u128);
+            let offset_digit = acir.existing_witness(&offset_digit).unwrap();
+            assert!(constrained.contains(&digit));
+            assert!(constrained.contains(&offset_digit));
+        }
+    }
+
+    #[test]
+    fn constant_bytes_are_folded() {
+        let mut acir = GeneratedAcir::default();
+        let bytes = [1_u128, 2, 3].map(|byte| Expression::from_field(FieldElement::from(byte)));
+        let packed = acir.pack_bytes(&bytes).unwrap();
+        assert_eq!(packed.to_const(), Some(FieldElement::from(0x010203_u128)));
+
+        let unpacked = acir.unpack_bytes(&packed, 4).unwrap();
+        assert_eq!(unpacked[0], Expression::zero());
+        assert_eq!(&unpacked[1..], &bytes);
+        assert!(acir.take_opcodes().is_empty());
+
+        assert!(matches!(
+            acir.unpack_bytes(&packed, 2),
+            Err(RuntimeError::IntegerOutOfBounds { .. })
+        ));
+        let too_large = Expression::from_field(FieldElement::from(256_u128));
+        assert!(matches!(
+            acir.pack_bytes(&[too_large]),
+            Err(RuntimeError::IntegerOutOfBounds { .. })
+        ));
+    }
+
+    #[test]
+    fn witnesses_are_reused_for_equal_expressions() {
+        let mut acir = GeneratedAcir::default();
+        let x = acir.next_witness_index();
+        let y = acir.next_witness_index();
+        let sum = &Expression::from(x) + &Expression::from(y);
+        let witness = acir.get_or_create_witness(&sum);
+
+        // The same sum, written with its terms in another order and split up.
+        let mut reordered = Expression::default();
+        reordered.push_addition_term(FieldElement::one(), y);
+        reordered.push_addition_term(FieldElement::from(2_u128), x);
+        reordered.push_addition_term(-FieldElement::one(), x);
+        assert_eq!(acir.get_or_create_witness(&reordered), witness);
+        assert_eq!(acir.take_opcodes().len(), 1);
+
+        let difference = &Expression::from(x) - &Expression::from(y);
+        assert_ne!(acir.get_or_create_witness(&difference), witness);
+        assert_eq!(acir.take_opcodes().len(), 1);
+    }
+
+    #[test]
+    fn multiples_of_a_reduced_expression_reuse_its_witness() {
+        let mut acir = GeneratedAcir::default();
+        let [x, y, z, w] = [(); 4].map(|_| acir.next_witness_index());
+        let mut xy = Expression::default();
+        xy.push_multiplication_term(FieldElement::one(), x, y);
+
+        let first = acir.mul_with_witness(&xy, &Expression::from(z));
+        assert_eq!(acir.opcodes().len(), 1);
+        let Some((_, lhs, rhs)) = first.mul_terms.first().copied() else {
+            panic!("expected a multiplication by the reduced expression, got {first}");
+        };
+        let reduced = if lhs == z { rhs } else { lhs };
+
+        // `2xy + 3` is a multiple of `xy` plus a constant, so it is not reduced again.
+        let scaled = &xy * FieldElement::from(2_u128) + FieldElement::from(3_u128);
+        let second = acir.mul_with_witness(&scaled, &Expression::from(w));
+        assert_eq!(acir.opcodes().len(), 1);
+        let mut expected = Expression::default();
+        expected.push_multiplication_term(FieldElement::from(2_u128), reduced, w);
+        expected.push_addition_term(FieldElement::from(3_u128), w);
+        assert_eq!(super::canonicalize(&second), super::canonicalize(&expected));
+    }
+
+    #[test]
+    fn equal_constraints_are_emitted_identically() {
+        let mut acir = GeneratedAcir::default();
+        let x = acir.next_witness_index();
+        let y = acir.next_witness_index();
+
+        // x * y - x - 1 == 0, built up in two different ways.
+        let mut expr = Expression::default();
+        expr.push_addition_term(-FieldElement::one(), x);
+        expr.push_multiplication_term(FieldElement::one(), x, y);
+        expr.q_c = -FieldElement::one();
+        acir.assert_is_zero(expr);
+
+        let mut negated = Expression::default();
+        negated.push_multiplication_term(-FieldElement::from(2_u128), y, x);
+        negated.push_multiplication_term(FieldElement::one(), x, y);
+        negated.push_addition_term(FieldElement::one(), y);
+        negated.push_addition_term(FieldElement::one(), x);
+        negated.push_addition_term(-FieldElement::one(), y);
+        negated.q_c = FieldElement::one();
+        acir.assert_is_zero(negated);
+
+        let opcodes = acir.take_opcodes();
+        let mut canonical = Expression::default();
+        canonical.push_multiplication_term(FieldElement::one(), x, y);
+        canonical.push_addition_term(-FieldElement::one(), x);
+        canonical.q_c = -FieldElement::one();
+        assert_eq!(
+            opcodes,
+            vec![Opcode::AssertZero(canonical.clone()), Opcode::AssertZero(canonical)]
+        );
+    }
+
+    #[test]
+    fn repeated_return_witnesses_share_a_distinct_witness_when_deduplicated() {
+        let returning_an_input_twice = |dedup| {
+            let mut acir = GeneratedAcir::default();
+            let (x, y) = (acir.next_witness_index(), acir.next_witness_index());
+            acir.input_witnesses = vec![x, y];
+            acir.return_witnesses = vec![x, y, x];
+            acir.make_return_witnesses_distinct(dedup);
+            assert!(acir.return_witnesses.iter().all(|witness| ![x, y].contains(witness)));
+            acir
+        };
+
+        let distinct = returning_an_input_twice(false);
+        assert_eq!(distinct.opcodes().len(), 3);
+        assert_ne!(distinct.return_witnesses[0], distinct.return_witnesses[2]);
+
+        let deduplicated = returning_an_input_twice(true);
+        assert_eq!(deduplicated.opcodes().len(), 2);
+        let returned = &deduplicated.return_witnesses;
+        assert_eq!(returned[0], returned[2]);
+        assert_ne!(returned[0], returned[1]);
+    }
+
+    #[test]
+    fn forks_allocate_within_their_reserved_ranges() {
+        let mut acir = GeneratedAcir::default();
+        let x = acir.next_witness_index();
+        let mut first = acir.fork_with_witness_range(2);
+        let mut second = acir.fork_with_witness_range(1);
+        let after_forks = acir.next_witness_index();
+        assert_eq!(after_forks, Witness(4));
+
+        // The second fork is built first, and the first fork outgrows its range.
+        let y = second.next_witness_index();
+        let first_witnesses = vecmap(0..3, |_| first.next_witness_index());
+        assert_eq!(y, Witness(3));
+        assert_eq!(first_witnesses[..2], [Witness(1), Witness(2)]);
+        for witness in &first_witnesses {
+            first.assert_is_zero(&Expression::from(*witness) - &Expression::from(x));
+        }
+
+        acir.merge(second);
+        acir.merge(first);
+        assert_eq!(acir.current_witness_index(), Witness(5));
+        let Opcode::AssertZero(relocated) = &acir.opcodes()[2] else { unreachable!() };
+        assert!(relocated.linear_combinations.iter().any(|(_, witness)| *witness == Witness(5)));
+    }
+
+    #[test]
+    fn merging_rebases_opcode_locations() {
+        let location = |start| Location::new(Span::single_char(start), Default::default());
+        let mut acir = GeneratedAcir::default();
+        let x = acir.next_witness_index();
+        acir.call_stack.push_back(location(0));
+        acir.assert_is_zero(Expression::from(x));
+        acir.set_last_assert_message("first".into());
+
+        let mut fork = acir.fork();
+        let y = fork.next_witness_index();
+        assert_ne!(x, y);
+        fork.call_stack = vec![location(1)].into();
+        fork.range_constraint(y, 8).unwrap();
+        fork.assert_is_zero(Expression::from(y));
+        fork.set_last_assert_message("second".into());
+
+        acir.merge(fork);
+        assert_eq!(acir.current_witness_index(), y);
+        assert!(acir.fits_in_bits(y, 8));
+        assert_eq!(acir.opcodes().len(), 3);
+        assert_eq!(acir.opcode_call_stack(OpcodeLocation::Acir(1)), vec![location(1)].into());
+        assert_eq!(acir.assert_messages[&OpcodeLocation::Acir(0)], "first".into());
+        assert_eq!(acir.assert_messages[&OpcodeLocation::Acir(2)], "second".into());
+    }
+
+    #[test]
+    fn bounds_follow_known_bit_sizes() {
+        let mut acir = GeneratedAcir::default();
+        let byte = acir.next_witness_index();
+        acir.mark_bit_size(byte, 8);
+        let bit = acir.next_witness_index();
+        acir.mark_boolean(bit);
+        let unknown = acir.next_witness_index();
+
+        // 1000 + byte - 2 * bit * byte
+        let mut expression = Expression::from_field(FieldElement::from(1000_u128));
+        expression.push_addition_term(FieldElement::one(), byte);
+        expression.push_multiplication_term(-FieldElement::from(2_u128), bit, byte);
+        assert_eq!(
+            acir.bounds(&expression),
+            Some((FieldElement::from(490_u128), FieldElement::from(1255_u128)))
+        );
+        assert!(matches!(
+            acir.check_range_can_be_satisfied(&expression, 8),
+            Err(RuntimeError::FailingRangeConstraint { num_bits: 8, .. })
+        ));
+        assert!(acir.check_range_can_be_satisfied(&expression, 9).is_ok());
+
+        // Negative values lie just below the modulus.
+        let negated = &expression * -FieldElement::one();
+        assert_eq!(
+            acir.bounds(&negated),
+            Some((-FieldElement::from(1255_u128), -FieldElement::from(490_u128)))
+        );
+
+        acir.warn_failing_range_constraints = true;
+        assert!(acir.check_range_can_be_satisfied(&negated, 32).is_ok());
+        assert_eq!(acir.warnings.len(), 1);
+
+        // The values may wrap around to zero, or be anything at all.
+        assert_eq!(acir.bounds(&(&Expression::from(byte) - &Expression::one())), None);
+        assert_eq!(acir.bounds(&(&expression + &Expression::from(unknown))), None);
+    }
+
+    #[test]
+    fn remapping_drops_entries_of_removed_opcodes() {
+        let mut acir = GeneratedAcir::default();
+        acir.call_stack.push_back(Location::new(Span::single_char(1), Default::default()));
+        for message in ["first", "removed", "third"] {
+            let witness = acir.next_witness_index();
+            acir.assert_is_zero(witness.into());
+            acir.set_last_assert_message(message.into());
+        }
+        acir.brillig_step_bounds.insert(2, 10);
+
+        acir.remap_opcode_locations(|location| match location {
+            OpcodeLocation::Acir(0) => Some(OpcodeLocation::Acir(0)),
+            OpcodeLocation::Acir(2) => Some(OpcodeLocation::Acir(1)),
+            _ => None,
+        });
+
+        let messages: Vec<_> = acir.assert_messages.into_iter().collect();
+        assert_eq!(
+            messages,
+            vec![
+                (OpcodeLocation::Acir(0), "first".into()),
+                (OpcodeLocation::Acir(1), "third".into())
+            ]
+        );
+        let locations: Vec<_> = acir.locations.into_keys().collect();
+        assert_eq!(locations, vec![OpcodeLocation::Acir(0), OpcodeLocation::Acir(1)]);
+        assert_eq!(acir.brillig_step_bounds.into_iter().collect::<Vec<_>>(), vec![(1, 10)]);
+    }
+
+    #[test]
+    fn compaction_drops_unreferenced_witnesses() {
+        let mut acir = GeneratedAcir::default();
+        let [x, unused, y, z] = [(); 4].map(|_| acir.next_witness_index());
+        acir.input_witnesses = vec![x];
+        acir.range_constraint(unused, 8).unwrap();
+        acir.retain_opcodes(&Default::default());
+        acir.assert_is_zero(&(&Expression::from(x) + &Expression::from(y)) - &Expression::from(z));
+        acir.return_witnesses = vec![z];
+
+        let renaming = acir.compact_witnesses([]);
+
+        assert_eq!(renaming.keys().copied().collect::<Vec<_>>(), vec![x, y, z]);
+        assert_eq!(acir.current_witness_index(), Witness(2));
+        assert_eq!(acir.return_witnesses, vec![Witness(2)]);
+        // `y` is given the index of `unused`, but not the fact that it fits in 8 bits.
+        assert!(!acir.fits_in_bits(Witness(1), 8));
+        let mut expected = Expression::default();
+        for witness in [Witness(0), Witness(1)] {
+            expected.push_addition_term(FieldElement::one(), witness);
+        }
+        expected.push_addition_term(-FieldElement::one(), Witness(2));
+        assert_eq!(acir.opcodes(), [Opcode::AssertZero(expected)]);
+    }
+
+    #[test]
+    fn compaction_keeps_witnesses_shown_in_assertion_messages() {
+        let mut acir = GeneratedAcir::default();
+        let [unused, shown, x] = [(); 3].map(|_| acir.next_witness_index());
+        acir.range_constraint(unused, 8).unwrap();
+        acir.retain_opcodes(&Default::default());
+        acir.assert_is_zero(x.into());
+        acir.set_last_assert_message(AssertionPayload {
+            parts: vec![
+                AssertionPayloadPart::Text("x is not zero but ".to_string()),
+                AssertionPayloadPart::Witness(shown),
+            ],
+        });
+
+        let renaming = acir.compact_witnesses([]);
+
+        assert_eq!(renaming.keys().copied().collect::<Vec<_>>(), vec![shown, x]);
+        let message = &acir.assert_messages[&OpcodeLocation::Acir(0)];
+        assert_eq!(message.witnesses().collect::<Vec<_>>(), vec![Witness(0)]);
+    }
+
+    #[test]
+    fn compaction_renames_the_witnesses_of_variables() {
+        let mut acir = GeneratedAcir::default();
+        let [unused, x] = [(); 2].map(|_| acir.next_witness_index());
+        acir.witness_names.insert(unused, "unused".to_string());
+        acir.witness_names.insert(x, "x".to_string());
+        acir.assert_is_zero(x.into());
 
-    assert_eq!(expected_num_outputs,output_count,"Tried to call black box function {name} with {output_count} outputs, but this function's definition requires {expected_num_outputs} outputs");
+        acir.compact_witnesses([]);
+
+        assert_eq!(acir.witness_names, BTreeMap::from([(Witness(0), "x".to_string())]));
+    }
+
+    #[test]
+    fn multi_scalar_mul_requires_a_scalar_for_each_point() {
+        let mut acir = GeneratedAcir::default();
+        let mut inputs = |count| {
+            vecmap(0..count, |_| FunctionInput {
+                witness: acir.next_witness_index(),
+                num_bits: FieldElement::max_num_bits(),
+            })
+        };
+        let (points, scalars) = (inputs(4), inputs(4));
+
+        let inputs = [points.clone(), scalars.clone()];
+        let outputs = acir
+            .call_black_box(BlackBoxFunc::MultiScalarMul, &inputs, vec![], vec![], 2, None)
+            .unwrap();
+        let call = BlackBoxFuncCall::MultiScalarMul {
+            points: points.clone(),
+            scalars: scalars.clone(),
+            outputs: (outputs[0], outputs[1]),
+        };
+        assert_eq!(acir.opcodes(), [Opcode::BlackBoxFuncCall(call)]);
+
+        let inputs = [points, scalars[..2].to_vec()];
+        let result =
+            acir.call_black_box(BlackBoxFunc::MultiScalarMul, &inputs, vec![], vec![], 2, None);
+        assert!(matches!(
+            result,
+            Err(RuntimeError::InternalError(InternalError::Unexpected { .. }))
+        ));
+    }
+
+    #[test]
+    fn black_box_calls_with_the_wrong_arity_are_errors() {
+        let mut acir = GeneratedAcir::default();
+        let input = FunctionInput { witness: acir.next_witness_index(), num_bits: 8 };
+
+        let result =
+            acir.call_black_box(BlackBoxFunc::AND, &[vec![input]], vec![], vec![], 1, None);
+        assert!(matches!(
+            result,
+            Err(RuntimeError::BlackBoxInputCount { expected: 2, found: 1, .. })
+        ));
+
+        let inputs = [vec![input], vec![input]];
+        let result = acir.call_black_box(BlackBoxFunc::AND, &inputs, vec![], vec![], 2, None);
+        assert!(matches!(
+            result,
+            Err(RuntimeError::BlackBoxOutputCount { expected: 1, found: 2, .. })
+        ));
+        assert!(acir.opcodes().is_empty());
+    }
+
+    #[test]
+    fn predicated_black_box_calls_are_made_over_multiplied_inputs() {
+        let mut acir = GeneratedAcir::default();
+        let predicate = Expression::from(acir.next_witness_index());
+        let input = FunctionInput { witness: acir.next_witness_index(), num_bits: 8 };
+        let inputs = [vec![input]];
+
+        acir.call_black_box(BlackBoxFunc::RANGE, &inputs, vec![], vec![], 0, Some(&predicate))
+            .unwrap();
+        let [Opcode::AssertZero(product), Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE {
+            input: predicated,
+        })] = acir.opcodes()
+        else {
+            panic!("expected the input to be multiplied by the predicate");
+        };
+        assert_eq!(product.mul_terms.len(), 1);
+        assert!(product
+            .linear_combinations
+            .iter()
+            .any(|(_, witness)| *witness == predicated.witness));
+        assert_eq!(predicated.num_bits, 8);
+
+        // The inputs are left as they are when the predicate is known to be set.
+        let always = Expression::one();
+        acir.call_black_box(BlackBoxFunc::RANGE, &inputs, vec![], vec![], 0, Some(&always))
+            .unwrap();
+        let call = BlackBoxFuncCall::RANGE { input };
+        assert_eq!(acir.opcodes().last(), Some(&Opcode::BlackBoxFuncCall(call)));
+    }
+
+    #[test]
+    fn aes128_encrypt_outputs_the_padded_ciphertext() {
+        let mut acir = GeneratedAcir::default();
+        let mut inputs = |count| {
+            vecmap(0..count, |_| FunctionInput { witness: acir.next_witness_index(), num_bits: 8 })
+        };
+        let (plaintext, iv, key) = (inputs(20), inputs(16), inputs(16));
+
+        let inputs = [plaintext.clone(), iv.clone(), key.clone()];
+        let outputs = acir
+            .call_black_box(BlackBoxFunc::AES128Encrypt, &inputs, vec![], vec![], 32, None)
+            .unwrap();
+        let call = BlackBoxFuncCall::AES128Encrypt { inputs: plaintext.clone(), iv, key, outputs };
+        assert_eq!(acir.opcodes(), [Opcode::BlackBoxFuncCall(call)]);
+
+        let result =
+            acir.call_black_box(BlackBoxFunc::AES128Encrypt, &inputs, vec![], vec![], 20, None);
+        assert!(matches!(
+            result,
+            Err(RuntimeError::InternalError(InternalError::Unexpected { .. }))
+        ));
+
+        let inputs = [plaintext, inputs[1][..8].to_vec(), inputs[2].clone()];
+        let result =
+            acir.call_black_box(BlackBoxFunc::AES128Encrypt, &inputs, vec![], vec![], 32, None);
+        assert!(matches!(
+            result,
+            Err(RuntimeError::InternalError(InternalError::Unexpected { .. }))
+        ));
+    }
+
+    #[test]
+    fn poseidon_permutation_keeps_a_capacity() {
+        let mut acir = GeneratedAcir::default();
+        let state = vecmap(0..3, |_| FunctionInput {
+            witness: acir.next_witness_index(),
+            num_bits: FieldElement::max_num_bits(),
+        });
+        let parameters = |len: u128, rate: u128| vec![len.into(), rate.into()];
+
+        let inputs = [state.clone()];
+        let outputs = acir
+            .call_black_box(
+                BlackBoxFunc::PoseidonPermutation,
+                &inputs,
+                parameters(3, 2),
+                vec![],
+                3,
+                None,
+            )
+            .unwrap();
+        let call =
+            BlackBoxFuncCall::PoseidonPermutation { inputs: state, outputs, len: 3, rate: 2 };
+        assert_eq!(acir.opcodes(), [Opcode::BlackBoxFuncCall(call)]);
+
+        for (len, rate, output_count) in [(3, 3, 3), (3, 0, 3), (3, 2, 2), (4, 2, 4)] {
+            let result = acir.call_black_box(
+                BlackBoxFunc::PoseidonPermutation,
+                &inputs,
+                parameters(len, rate),
+                vec![],
+                output_count,
+                None,
+            );
+            assert!(matches!(
+                result,
+                Err(RuntimeError::InternalError(InternalError::Unexpected { .. }))
+            ));
+        }
+    }
 }
@@ -0,0 +1,419 @@
+//! A final pass over the opcodes of a [`GeneratedAcir`] which removes constraints made redundant
+//! by the way ACIR generation emits them one instruction at a time.
+//!
+//! Each clean-up is a [`Peephole`], which only decides how a single opcode is rewritten, usually
+//! by matching the [`Shape`] of an `AssertZero` opcode. Removing opcodes, replacing a witness
+//! throughout the circuit and keeping the locations and assertion messages in step with the
+//! remaining opcodes is shared between them, so that adding a clean-up only takes a new pattern.
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use acvm::{
+    acir::{
+        circuit::{
+            brillig::{Brillig, BrilligOutputs},
+            directives::Directive,
+            opcodes::Opcode as AcirOpcode,
+        },
+        native_types::{Expression, Witness},
+    },
+    FieldElement,
+};
+
+use super::{
+    generated_acir::{canonicalize_constraint, GeneratedAcir},
+    witness_renaming::rename_opcode_witnesses,
+};
+
+mod constants;
+mod copies;
+mod negations;
+mod redundant;
+
+use constants::ConstantSubstitution;
+use copies::CopyElimination;
+use negations::NegationElimination;
+use redundant::{DuplicateAssertions, TrivialAssertions};
+
+impl GeneratedAcir {
+    /// Removes `AssertZero` opcodes which are trivially redundant:
+    /// - those which only assert that two witnesses are equal, or opposite, after replacing every
+    ///   use of one of them with the other,
+    /// - those which only assert that a witness is a constant, after replacing every use of the
+    ///   witness with the constant,
+    /// - those asserting that an expression with no terms is zero,
+    /// - those which are equal to an earlier one.
+    ///
+    /// Input and return witnesses, and witnesses which are solved by opcodes other than
+    /// `AssertZero`, are never replaced.
+    pub(crate) fn peephole_optimize(&mut self) {
+        self.apply_peephole(&mut CopyElimination);
+        self.apply_peephole(&mut NegationElimination);
+        self.apply_peephole(&mut ConstantSubstitution);
+        self.apply_peephole(&mut TrivialAssertions);
+        self.apply_peephole(&mut DuplicateAssertions::default());
+    }
+
+    /// Rewrites each opcode in order as `peephole` decides, where each opcode already has the
+    /// substitutions of the opcodes before it applied.
+    fn apply_peephole(&mut self, peephole: &mut dyn Peephole) {
+        let mut context = PeepholeContext::new(self);
+        let mut substitutions = Substitutions::default();
+        let mut kept = BTreeSet::new();
+        for index in 0..self.opcodes().len() {
+            if let Some(opcode) = substitutions.apply(&self.opcodes()[index]) {
+                self.replace_opcode(index, opcode);
+            }
+            match peephole.rewrite(&context, &self.opcodes()[index]) {
+                Rewrite::Keep => {
+                    kept.insert(index);
+                }
+                Rewrite::Remove => (),
+                Rewrite::Substitute { witness, value } => {
+                    let value = substitutions.resolve(value);
+                    if value.witness == Some(witness) || !context.can_substitute(witness, &value) {
+                        kept.insert(index);
+                        continue;
+                    }
+                    if context.referenced.contains(&witness) {
+                        // `value` is a copy, whose witness takes over the references.
+                        context.referenced.extend(value.witness);
+                    }
+                    substitutions.0.insert(witness, value);
+                }
+            }
+        }
+        if substitutions.0.is_empty() && kept.len() == self.opcodes().len() {
+            return;
+        }
+
+        // Opcodes may reference witnesses which were only substituted after them.
+        for index in 0..self.opcodes().len() {
+            if let Some(opcode) = substitutions.apply(&self.opcodes()[index]) {
+                self.replace_opcode(index, opcode);
+            }
+        }
+        let copies: HashMap<Witness, Witness> = substitutions
+            .0
+            .keys()
+            .filter_map(|witness| {
+                let value = substitutions.resolve(Affine::witness(*witness));
+                value.as_copy().map(|copy| (*witness, copy))
+            })
+            .collect();
+        if !copies.is_empty() {
+            self.rename_witnesses(|witness| copies.get(&witness).copied().unwrap_or(witness));
+        }
+        if kept.len() < self.opcodes().len() {
+            self.retain_opcodes(&kept);
+        }
+    }
+}
+
+/// A clean-up of single opcodes.
+pub(super) trait Peephole {
+    /// Returns how `opcode` is rewritten.
+    fn rewrite(&mut self, context: &PeepholeContext, opcode: &AcirOpcode) -> Rewrite;
+}
+
+pub(super) enum Rewrite {
+    Keep,
+    Remove,
+    /// Removes the opcode, which asserts that `witness` is equal to `value`, and replaces every
+    /// use of `witness` with `value`. The opcode is kept if `witness` can't be replaced.
+    Substitute {
+        witness: Witness,
+        value: Affine,
+    },
+}
+
+/// The value `scale * witness + offset`, or `offset` if there is no witness.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Affine {
+    pub(super) scale: FieldElement,
+    pub(super) witness: Option<Witness>,
+    pub(super) offset: FieldElement,
+}
+
+impl Affine {
+    pub(super) fn witness(witness: Witness) -> Affine {
+        Affine { scale: FieldElement::one(), witness: Some(witness), offset: FieldElement::zero() }
+    }
+
+    pub(super) fn constant(offset: FieldElement) -> Affine {
+        Affine { scale: FieldElement::zero(), witness: None, offset }
+    }
+
+    /// Returns the witness which this value is a copy of, if any.
+    fn as_copy(&self) -> Option<Witness> {
+        let is_copy = self.scale.is_one() && self.offset.is_zero();
+        self.witness.filter(|_| is_copy)
+    }
+
+    /// Returns the linear term and the constant term of the value.
+    fn terms(&self) -> (Option<(FieldElement, Witness)>, FieldElement) {
+        (self.witness.map(|witness| (self.scale, witness)), self.offset)
+    }
+}
+
+/// The shapes of `AssertZero` opcodes which peepholes match on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Shape {
+    /// Asserts that `0 == 0`.
+    Trivial,
+    /// Asserts that `witness == value`.
+    Constant {
+        witness: Witness,
+        value: FieldElement,
+    },
+    /// Asserts that `lhs == rhs`.
+    Copy {
+        lhs: Witness,
+        rhs: Witness,
+    },
+    /// Asserts that `lhs == -rhs`.
+    Negation {
+        lhs: Witness,
+        rhs: Witness,
+    },
+    Other,
+}
+
+impl Shape {
+    /// Returns the shape of `opcode` if it is an `AssertZero` opcode.
+    pub(super) fn of(opcode: &AcirOpcode) -> Option<Shape> {
+        let AcirOpcode::AssertZero(expression) = opcode else { return None };
+        if !expression.mul_terms.is_empty() {
+            return Some(Shape::Other);
+        }
+        let shape = match expression.linear_combinations[..] {
+            [] if expression.q_c.is_zero() => Shape::Trivial,
+            [(coefficient, witness)] if !coefficient.is_zero() => {
+                Shape::Constant { witness, value: -expression.q_c / coefficient }
+            }
+            [(lhs_coefficient, lhs), (rhs_coefficient, rhs)] if expression.q_c.is_zero() => {
+                if lhs_coefficient == -rhs_coefficient {
+                    Shape::Copy { lhs, rhs }
+                } else if lhs_coefficient == rhs_coefficient {
+                    Shape::Negation { lhs, rhs }
+                } else {
+                    Shape::Other
+                }
+            }
+            _ => Shape::Other,
+        };
+        Some(shape)
+    }
+}
+
+/// What peepholes may rely on about the whole circuit.
+pub(super) struct PeepholeContext {
+    /// The input and return witnesses, and the witnesses solved by opcodes other than
+    /// `AssertZero`, which are never replaced.
+    fixed: HashSet<Witness>,
+    /// The witnesses referenced by opcodes other than `AssertZero`, which can only be replaced
+    /// with another witness.
+    referenced: HashSet<Witness>,
+}
+
+impl PeepholeContext {
+    fn new(acir: &GeneratedAcir) -> Self {
+        let mut fixed: HashSet<Witness> =
+            acir.input_witnesses.iter().chain(&acir.return_witnesses).copied().collect();
+        let mut referenced = HashSet::new();
+        for opcode in acir.opcodes() {
+            match opcode {
+                AcirOpcode::AssertZero(_) => continue,
+                AcirOpcode::MemoryInit { .. } => (),
+                AcirOpcode::BlackBoxFuncCall(call) => fixed.extend(call.get_outputs_vec()),
+                AcirOpcode::Directive(Directive::ToLeRadix { b: outputs, .. })
+                | AcirOpcode::Directive(Directive::PermutationSort { bits: outputs, .. }) => {
+                    fixed.extend(outputs);
+                }
+                AcirOpcode::Brillig(Brillig { outputs, .. })
+                | AcirOpcode::BrilligCall { outputs, .. } => {
+                    for output in outputs {
+                        match output {
+                            BrilligOutputs::Simple(witness) => {
+                                fixed.insert(*witness);
+                            }
+                            BrilligOutputs::Array(witnesses) => fixed.extend(witnesses),
+                        }
+                    }
+                }
+                AcirOpcode::MemoryOp { op, .. } => fixed.extend(witnesses(&op.value)),
+                AcirOpcode::Call { outputs, .. } => fixed.extend(outputs),
+            }
+            rename_opcode_witnesses(&mut opcode.clone(), &mut |witness| {
+                referenced.insert(witness);
+                witness
+            });
+        }
+        PeepholeContext { fixed, referenced }
+    }
+
+    /// Returns whether every use of `witness` can be replaced with `value`.
+    pub(super) fn can_substitute(&self, witness: Witness, value: &Affine) -> bool {
+        !self.fixed.contains(&witness)
+            && (value.as_copy().is_some() || !self.referenced.contains(&witness))
+    }
+
+    /// Returns the substitution of whichever of `lhs` and `rhs` can be replaced with `value` of
+    /// the other, preferring to keep the earliest witness.
+    pub(super) fn substitute_either(
+        &self,
+        lhs: Witness,
+        rhs: Witness,
+        value: impl Fn(Witness) -> Affine,
+    ) -> Rewrite {
+        let (first, last) = (lhs.min(rhs), lhs.max(rhs));
+        [(last, first), (first, last)]
+            .into_iter()
+            .find(|(replaced, kept)| self.can_substitute(*replaced, &value(*kept)))
+            .map_or(Rewrite::Keep, |(witness, kept)| Rewrite::Substitute {
+                witness,
+                value: value(kept),
+            })
+    }
+}
+
+/// The value which each replaced witness is replaced with, which may itself involve a replaced
+/// witness.
+#[derive(Default)]
+struct Substitutions(HashMap<Witness, Affine>);
+
+impl Substitutions {
+    /// Returns `value` in terms of witnesses which are not replaced.
+    fn resolve(&self, mut value: Affine) -> Affine {
+        while let Some(replacement) = value.witness.and_then(|witness| self.0.get(&witness)) {
+            value = Affine {
+                scale: value.scale * replacement.scale,
+                witness: replacement.witness,
+                offset: value.scale * replacement.offset + value.offset,
+            };
+        }
+        value
+    }
+
+    fn value(&self, witness: Witness) -> Affine {
+        self.resolve(Affine::witness(witness))
+    }
+
+    /// Returns `opcode` with every replaced witness replaced, or `None` if it references none.
+    fn apply(&self, opcode: &AcirOpcode) -> Option<AcirOpcode> {
+        if self.0.is_empty() {
+            return None;
+        }
+        let mut is_affected = false;
+        rename_opcode_witnesses(&mut opcode.clone(), &mut |witness| {
+            is_affected |= self.0.contains_key(&witness);
+            witness
+        });
+        if !is_affected {
+            return None;
+        }
+
+        let opcode = match opcode {
+            AcirOpcode::AssertZero(expression) => {
+                AcirOpcode::AssertZero(self.substitute(expression))
+            }
+            // Only copies may replace the witnesses of other opcodes.
+            opcode => {
+                let mut opcode = opcode.clone();
+                rename_opcode_witnesses(&mut opcode, &mut |witness| {
+                    self.value(witness).as_copy().unwrap_or(witness)
+                });
+                opcode
+            }
+        };
+        Some(opcode)
+    }
+
+    fn substitute(&self, expression: &Expression) -> Expression {
+        let mut result = Expression::from_field(expression.q_c);
+        for (coefficient, lhs, rhs) in &expression.mul_terms {
+            let (lhs_term, lhs_offset) = self.value(*lhs).terms();
+            let (rhs_term, rhs_offset) = self.value(*rhs).terms();
+            if let (Some((lhs_scale, lhs)), Some((rhs_scale, rhs))) = (lhs_term, rhs_term) {
+                result.push_multiplication_term(*coefficient * lhs_scale * rhs_scale, lhs, rhs);
+            }
+            if let Some((lhs_scale, lhs)) = lhs_term {
+                result.push_addition_term(*coefficient * lhs_scale * rhs_offset, lhs);
+            }
+            if let Some((rhs_scale, rhs)) = rhs_term {
+                result.push_addition_term(*coefficient * lhs_offset * rhs_scale, rhs);
+            }
+            result.q_c += *coefficient * lhs_offset * rhs_offset;
+        }
+        for (coefficient, witness) in &expression.linear_combinations {
+            let (term, offset) = self.value(*witness).terms();
+            if let Some((scale, witness)) = term {
+                result.push_addition_term(*coefficient * scale, witness);
+            }
+            result.q_c += *coefficient * offset;
+        }
+        canonicalize_constraint(&result)
+    }
+}
+
+fn witnesses(expression: &Expression) -> impl Iterator<Item = Witness> + '_ {
+    let mul_terms = expression.mul_terms.iter().flat_map(|(_, lhs, rhs)| [*lhs, *rhs]);
+    mul_terms.chain(expression.linear_combinations.iter().map(|(_, witness)| *witness))
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::{
+        acir::{
+            circuit::{opcodes::Opcode, OpcodeLocation},
+            native_types::{Expression, Witness},
+        },
+        FieldElement,
+    };
+    use noirc_errors::{Location, Span};
+
+    use super::GeneratedAcir;
+
+    pub(super) fn equality(lhs: Witness, rhs: Witness) -> Expression {
+        &Expression::from(lhs) - &Expression::from(rhs)
+    }
+
+    #[test]
+    fn collapses_chains_of_equal_witnesses() {
+        let mut acir = GeneratedAcir::default();
+        let x = acir.next_witness_index();
+        let [w1, w2, w3] = [(); 3].map(|_| acir.next_witness_index());
+        acir.input_witnesses = vec![x];
+
+        acir.assert_is_zero(equality(w1, x));
+        acir.assert_is_zero(equality(w2, w1));
+        let mut product = Expression::default();
+        product.push_multiplication_term(FieldElement::one(), w2, w2);
+        product.push_addition_term(-FieldElement::one(), w3);
+        acir.call_stack.push_back(Location::new(Span::single_char(3), Default::default()));
+        acir.assert_is_zero(product);
+        acir.return_witnesses = vec![w3];
+
+        acir.peephole_optimize();
+
+        let mut expected = Expression::default();
+        expected.push_multiplication_term(FieldElement::one(), x, x);
+        expected.push_addition_term(-FieldElement::one(), w3);
+        assert_eq!(acir.opcodes(), [Opcode::AssertZero(expected)]);
+        assert!(acir.locations.contains_key(&OpcodeLocation::Acir(0)));
+    }
+
+    #[test]
+    fn removes_duplicate_and_trivial_assertions() {
+        let mut acir = GeneratedAcir::default();
+        let x = acir.next_witness_index();
+        let y = acir.next_witness_index();
+        acir.input_witnesses = vec![x, y];
+
+        acir.assert_is_zero(equality(x, y));
+        acir.assert_is_zero(equality(y, x));
+        acir.assert_is_zero(Expression::default());
+
+        acir.peephole_optimize();
+        assert_eq!(acir.opcodes(), [Opcode::AssertZero(equality(x, y))]);
+    }
+}
@@ -0,0 +1,62 @@
+//! Replaces a witness which is asserted to be a constant with that constant, where the witness
+//! is only referenced by `AssertZero` opcodes.
+use acvm::acir::circuit::opcodes::Opcode as AcirOpcode;
+
+use super::{Affine, Peephole, PeepholeContext, Rewrite, Shape};
+
+pub(super) struct ConstantSubstitution;
+
+impl Peephole for ConstantSubstitution {
+    fn rewrite(&mut self, _context: &PeepholeContext, opcode: &AcirOpcode) -> Rewrite {
+        match Shape::of(opcode) {
+            Some(Shape::Constant { witness, value }) => {
+                Rewrite::Substitute { witness, value: Affine::constant(value) }
+            }
+            _ => Rewrite::Keep,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::{
+        acir::{
+            circuit::Opcode,
+            native_types::{Expression, Witness},
+        },
+        FieldElement,
+    };
+
+    use super::ConstantSubstitution;
+    use crate::ssa::acir_gen::acir_ir::generated_acir::GeneratedAcir;
+
+    fn is_constant(witness: Witness, value: u128) -> Expression {
+        &Expression::from(witness) - &Expression::from(FieldElement::from(value))
+    }
+
+    #[test]
+    fn substitutes_constants_into_assertions() {
+        let mut acir = GeneratedAcir::default();
+        let [x, y, z] = [(); 3].map(|_| acir.next_witness_index());
+        acir.input_witnesses = vec![x];
+        acir.return_witnesses = vec![z];
+
+        acir.assert_is_zero(is_constant(y, 3));
+        // `z` is a return value, so it keeps its assertion.
+        acir.assert_is_zero(is_constant(z, 5));
+        let mut product = Expression::default();
+        product.push_multiplication_term(FieldElement::one(), x, y);
+        product.push_addition_term(-FieldElement::one(), z);
+        acir.assert_is_zero(product);
+
+        acir.apply_peephole(&mut ConstantSubstitution);
+
+        let mut expected = Expression::default();
+        expected.push_addition_term(FieldElement::from(3_u128), x);
+        expected.push_addition_term(-FieldElement::one(), z);
+        assert_eq!(
+            acir.opcodes(),
+            [Opcode::AssertZero(is_constant(z, 5)), Opcode::AssertZero(expected)]
+        );
+    }
+}
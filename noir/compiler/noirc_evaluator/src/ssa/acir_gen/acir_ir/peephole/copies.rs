@@ -0,0 +1,43 @@
+//! Replaces a witness which is asserted to be equal to another with that other witness.
+use acvm::acir::circuit::opcodes::Opcode as AcirOpcode;
+
+use super::{Affine, Peephole, PeepholeContext, Rewrite, Shape};
+
+pub(super) struct CopyElimination;
+
+impl Peephole for CopyElimination {
+    fn rewrite(&mut self, context: &PeepholeContext, opcode: &AcirOpcode) -> Rewrite {
+        match Shape::of(opcode) {
+            Some(Shape::Copy { lhs, rhs }) => context.substitute_either(lhs, rhs, Affine::witness),
+            _ => Rewrite::Keep,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::{
+        circuit::{opcodes::Opcode, OpcodeLocation},
+        native_types::Expression,
+    };
+    use noirc_errors::{Location, Span};
+
+    use super::CopyElimination;
+    use crate::ssa::acir_gen::acir_ir::{generated_acir::GeneratedAcir, peephole::tests::equality};
+
+    #[test]
+    fn keeps_the_earliest_of_unfixed_witnesses() {
+        let mut acir = GeneratedAcir::default();
+        let [x, y, z] = [(); 3].map(|_| acir.next_witness_index());
+        acir.return_witnesses = vec![z];
+
+        acir.assert_is_zero(equality(y, x));
+        acir.call_stack.push_back(Location::new(Span::single_char(1), Default::default()));
+        acir.assert_is_zero(&Expression::from(y) + &Expression::from(z));
+
+        acir.apply_peephole(&mut CopyElimination);
+        let expected = &Expression::from(x) + &Expression::from(z);
+        assert_eq!(acir.opcodes(), [Opcode::AssertZero(expected)]);
+        assert!(acir.locations.contains_key(&OpcodeLocation::Acir(0)));
+    }
+}
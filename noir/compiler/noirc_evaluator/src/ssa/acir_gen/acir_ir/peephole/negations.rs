@@ -0,0 +1,65 @@
+//! Replaces a witness which is asserted to be the negation of another with the negation of that
+//! other witness, so that double negations cancel out.
+use acvm::{acir::circuit::opcodes::Opcode as AcirOpcode, FieldElement};
+
+use super::{Affine, Peephole, PeepholeContext, Rewrite, Shape};
+
+pub(super) struct NegationElimination;
+
+impl Peephole for NegationElimination {
+    fn rewrite(&mut self, context: &PeepholeContext, opcode: &AcirOpcode) -> Rewrite {
+        match Shape::of(opcode) {
+            Some(Shape::Negation { lhs, rhs }) => context.substitute_either(lhs, rhs, |witness| {
+                Affine { scale: -FieldElement::one(), ..Affine::witness(witness) }
+            }),
+            _ => Rewrite::Keep,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::{
+        circuit::{
+            opcodes::{BlackBoxFuncCall, FunctionInput},
+            Opcode,
+        },
+        native_types::{Expression, Witness},
+    };
+
+    use super::NegationElimination;
+    use crate::ssa::acir_gen::acir_ir::{generated_acir::GeneratedAcir, peephole::tests::equality};
+
+    fn negation(lhs: Witness, rhs: Witness) -> Expression {
+        &Expression::from(lhs) + &Expression::from(rhs)
+    }
+
+    #[test]
+    fn cancels_double_negations() {
+        let mut acir = GeneratedAcir::default();
+        let [x, y, z] = [(); 3].map(|_| acir.next_witness_index());
+        acir.input_witnesses = vec![x];
+        acir.return_witnesses = vec![z];
+
+        acir.assert_is_zero(negation(y, x));
+        acir.assert_is_zero(negation(z, y));
+
+        acir.apply_peephole(&mut NegationElimination);
+        assert_eq!(acir.opcodes(), [Opcode::AssertZero(equality(x, z))]);
+    }
+
+    #[test]
+    fn keeps_negations_of_witnesses_referenced_by_other_opcodes() {
+        let mut acir = GeneratedAcir::default();
+        let [x, y] = [(); 2].map(|_| acir.next_witness_index());
+        acir.input_witnesses = vec![x];
+
+        acir.assert_is_zero(negation(y, x));
+        let range = BlackBoxFuncCall::RANGE { input: FunctionInput { witness: y, num_bits: 8 } };
+        acir.push_opcode(Opcode::BlackBoxFuncCall(range));
+        let opcodes = acir.opcodes().to_vec();
+
+        acir.apply_peephole(&mut NegationElimination);
+        assert_eq!(acir.opcodes(), opcodes);
+    }
+}
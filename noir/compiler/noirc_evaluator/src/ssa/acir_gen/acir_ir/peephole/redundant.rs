@@ -0,0 +1,69 @@
+//! Removes assertions which add nothing to the constraints of the circuit.
+use std::collections::HashSet;
+
+use acvm::acir::{circuit::opcodes::Opcode as AcirOpcode, native_types::Expression};
+
+use super::{Peephole, PeepholeContext, Rewrite, Shape};
+use crate::ssa::acir_gen::acir_ir::generated_acir::canonicalize_constraint;
+
+/// Removes assertions that an expression with no terms is zero.
+pub(super) struct TrivialAssertions;
+
+impl Peephole for TrivialAssertions {
+    fn rewrite(&mut self, _context: &PeepholeContext, opcode: &AcirOpcode) -> Rewrite {
+        match Shape::of(opcode) {
+            Some(Shape::Trivial) => Rewrite::Remove,
+            _ => Rewrite::Keep,
+        }
+    }
+}
+
+/// Removes assertions which are equal to an earlier one, up to the order of their terms and
+/// their sign.
+#[derive(Default)]
+pub(super) struct DuplicateAssertions {
+    seen: HashSet<Expression>,
+}
+
+impl Peephole for DuplicateAssertions {
+    fn rewrite(&mut self, _context: &PeepholeContext, opcode: &AcirOpcode) -> Rewrite {
+        match opcode {
+            AcirOpcode::AssertZero(expression)
+                if !self.seen.insert(canonicalize_constraint(expression)) =>
+            {
+                Rewrite::Remove
+            }
+            _ => Rewrite::Keep,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::{circuit::Opcode, native_types::Expression};
+
+    use super::{DuplicateAssertions, TrivialAssertions};
+    use crate::ssa::acir_gen::acir_ir::{generated_acir::GeneratedAcir, peephole::tests::equality};
+
+    #[test]
+    fn removes_trivial_assertions() {
+        let mut acir = GeneratedAcir::default();
+        let [x, y] = [(); 2].map(|_| acir.next_witness_index());
+        acir.assert_is_zero(Expression::default());
+        acir.assert_is_zero(equality(x, y));
+
+        acir.apply_peephole(&mut TrivialAssertions);
+        assert_eq!(acir.opcodes(), [Opcode::AssertZero(equality(x, y))]);
+    }
+
+    #[test]
+    fn removes_assertions_equal_up_to_sign() {
+        let mut acir = GeneratedAcir::default();
+        let [x, y] = [(); 2].map(|_| acir.next_witness_index());
+        acir.assert_is_zero(equality(x, y));
+        acir.assert_is_zero(equality(y, x));
+
+        acir.apply_peephole(&mut DuplicateAssertions::default());
+        assert_eq!(acir.opcodes(), [Opcode::AssertZero(equality(x, y))]);
+    }
+}
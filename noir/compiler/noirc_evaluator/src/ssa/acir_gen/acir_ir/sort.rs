@@ -1,7 +1,14 @@
-use crate::errors::InternalError;
+use crate::{
+    brillig::brillig_gen::brillig_directive,
+    errors::{InternalError, RuntimeError},
+};
 
-use super::generated_acir::GeneratedAcir;
-use acvm::acir::native_types::{Expression, Witness};
+use super::{generated_acir::GeneratedAcir, transcript::Transcript};
+use acvm::acir::{
+    circuit::brillig::{BrilligInputs, BrilligOutputs},
+    native_types::{Expression, Witness},
+};
+use iter_extended::vecmap;
 
 impl GeneratedAcir {
     // Generates gates for a sorting network
@@ -85,4 +92,41 @@ impl GeneratedAcir {
         conf.extend(w2);
         Ok((conf, out_expr))
     }
+
+    /// Solves for `outputs` as the sorted `inputs` and constrains them to be a permutation of
+    /// `inputs` by checking that `inputs` and `outputs` are roots of the same polynomial.
+    ///
+    /// The polynomials are compared at a challenge derived from both `inputs` and `outputs`,
+    /// which a prover cannot predict when choosing the outputs.
+    /// The outputs are not constrained to be sorted.
+    pub(crate) fn grand_product_permutation(
+        &mut self,
+        inputs: &[Expression],
+        outputs: &[Witness],
+        bit_size: u32,
+    ) -> Result<(), RuntimeError> {
+        let sort_code = brillig_directive::directive_sort(inputs.len(), bit_size);
+        let brillig_inputs = vecmap(inputs, |input| BrilligInputs::Single(input.clone()));
+        let brillig_outputs = vecmap(outputs, |output| BrilligOutputs::Simple(*output));
+        self.brillig(Some(Expression::one()), sort_code, brillig_inputs, brillig_outputs);
+
+        let inputs = vecmap(inputs, |input| self.get_or_create_witness(input));
+        let mut transcript = Transcript::default();
+        transcript.absorb(&inputs);
+        transcript.absorb(outputs);
+        let challenge = transcript.squeeze_challenge(self)?;
+
+        let inputs_product = self.product_of_differences(&inputs, challenge);
+        let outputs_product = self.product_of_differences(outputs, challenge);
+        self.assert_is_zero(&inputs_product - &outputs_product);
+        Ok(())
+    }
+
+    /// Returns an expression for the product of `witness - challenge` over all `witnesses`.
+    fn product_of_differences(&mut self, witnesses: &[Witness], challenge: Witness) -> Expression {
+        witnesses.iter().fold(Expression::one(), |product, witness| {
+            let difference = &Expression::from(*witness) - challenge;
+            self.mul_with_witness(&product, &difference)
+        })
+    }
 }
@@ -0,0 +1,278 @@
+//! Operations which can be lowered into ACIR in more than one way.
+//!
+//! Each strategy is picked by estimating the cost of every alternative under the backend's
+//! [`CostModel`] and taking the cheapest one. Ties are broken in favour of the alternative
+//! listed first, which is the lowering used before cost models were introduced.
+use acvm::{acir::BlackBoxFunc, FieldElement};
+
+use crate::cost_model::CostModel;
+
+use super::transcript::RATE;
+
+/// Returns the cheapest of `candidates`, preferring earlier candidates on a tie.
+fn cheapest<T: Copy>(candidates: impl IntoIterator<Item = (T, u64)>) -> T {
+    candidates
+        .into_iter()
+        .min_by_key(|(_, cost)| *cost)
+        .map(|(candidate, _)| candidate)
+        .expect("ICE: there must be at least one candidate strategy")
+}
+
+/// How to constrain a witness to fit within a number of bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RangeEncoding {
+    /// A `RANGE` black box function call.
+    BlackBox,
+    /// An arithmetic constraint `w * w - w == 0`, only applicable to a single bit.
+    Boolean,
+    /// Decompose the witness into bits and constrain each one to be a boolean.
+    BitDecomposition,
+}
+
+impl RangeEncoding {
+    pub(crate) fn choose(cost_model: &dyn CostModel, num_bits: u32) -> Self {
+        let candidates = match num_bits {
+            0 => vec![RangeEncoding::BlackBox],
+            1 => vec![RangeEncoding::BlackBox, RangeEncoding::Boolean],
+            _ => vec![RangeEncoding::BlackBox, RangeEncoding::BitDecomposition],
+        };
+        cheapest(candidates.into_iter().map(|encoding| {
+            let cost = encoding.cost(cost_model, num_bits);
+            (encoding, cost)
+        }))
+    }
+
+    fn cost(self, cost_model: &dyn CostModel, num_bits: u32) -> u64 {
+        match self {
+            RangeEncoding::BlackBox => cost_model.black_box_cost(BlackBoxFunc::RANGE, num_bits),
+            RangeEncoding::Boolean => cost_model.assert_zero_cost(),
+            RangeEncoding::BitDecomposition => bit_decomposition_cost(cost_model, num_bits),
+        }
+    }
+}
+
+/// How to range constrain a batch of witnesses to the same number of bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RangeBatchEncoding {
+    /// Range constrain each witness on its own, using the cheapest [`RangeEncoding`].
+    Individually,
+    /// Read each witness as an index into a memory block holding every value which fits within
+    /// the number of bits, so that reading out of its bounds fails.
+    Table,
+}
+
+impl RangeBatchEncoding {
+    /// Tables are never used for more bits than this, as they hold a witness per value.
+    pub(crate) const MAX_TABLE_BITS: u32 = 16;
+
+    /// Chooses how to constrain `count` witnesses to `num_bits` bits, where `has_table` is
+    /// whether a table for that many bits has already been created and so comes for free.
+    pub(crate) fn choose(
+        cost_model: &dyn CostModel,
+        num_bits: u32,
+        count: usize,
+        has_table: bool,
+    ) -> Self {
+        let individually = count as u64 * range_cost(cost_model, num_bits);
+        if num_bits > Self::MAX_TABLE_BITS {
+            return RangeBatchEncoding::Individually;
+        }
+
+        // Each entry of a new table is a witness constrained to its value, which is then
+        // written into the memory block.
+        let table_size = 1_u64 << num_bits;
+        let table = if has_table {
+            0
+        } else {
+            table_size * (cost_model.assert_zero_cost() + cost_model.lookup_cost())
+        };
+        cheapest([
+            (RangeBatchEncoding::Individually, individually),
+            (RangeBatchEncoding::Table, table + count as u64 * cost_model.lookup_cost()),
+        ])
+    }
+}
+
+/// The cost of range constraining a witness to `num_bits` bits using the cheapest encoding.
+fn range_cost(cost_model: &dyn CostModel, num_bits: u32) -> u64 {
+    RangeEncoding::choose(cost_model, num_bits).cost(cost_model, num_bits)
+}
+
+/// The cost of decomposing a witness into `num_bits` bits, including constraining each bit.
+fn bit_decomposition_cost(cost_model: &dyn CostModel, num_bits: u32) -> u64 {
+    cost_model.unconstrained_cost()
+        + cost_model.assert_zero_cost()
+        + num_bits as u64 * range_cost(cost_model, 1)
+}
+
+/// How to compute the bitwise AND or XOR of two integers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BitwiseLowering {
+    /// An `AND` or `XOR` black box function call.
+    BlackBox,
+    /// Decompose both operands into bits and combine them arithmetically.
+    BitDecomposition,
+}
+
+impl BitwiseLowering {
+    pub(crate) fn choose(cost_model: &dyn CostModel, func: BlackBoxFunc, bit_size: u32) -> Self {
+        // Both operands are decomposed and the bits of the result are then combined
+        // into a single expression.
+        let decomposition =
+            2 * bit_decomposition_cost(cost_model, bit_size) + cost_model.assert_zero_cost();
+        cheapest([
+            (BitwiseLowering::BlackBox, cost_model.black_box_cost(func, bit_size)),
+            (BitwiseLowering::BitDecomposition, decomposition),
+        ])
+    }
+}
+
+/// How to compute whether an expression is zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum IsZeroLowering {
+    /// Solve for the inverse of the expression in Brillig and constrain the result with it.
+    Inverse,
+    /// Decompose the expression into bits, which are all zero exactly when it is, and multiply
+    /// together `1 - bit` over every bit. Only applicable to expressions known to be non-negative.
+    BitDecomposition,
+}
+
+impl IsZeroLowering {
+    /// Chooses how to check whether an expression is zero, where `num_bits` is the number of
+    /// bits which the expression is known to fit within, if any, and `is_witness` is whether it
+    /// is a single witness.
+    pub(crate) fn choose(
+        cost_model: &dyn CostModel,
+        num_bits: Option<u32>,
+        is_witness: bool,
+    ) -> Self {
+        // The inverse is taken of a witness, which must be created for any other expression.
+        let num_constraints = if is_witness { 2 } else { 3 };
+        let inverse =
+            cost_model.unconstrained_cost() + num_constraints * cost_model.assert_zero_cost();
+        let decomposition = match num_bits {
+            None | Some(0) => return IsZeroLowering::Inverse,
+            Some(num_bits) if num_bits >= FieldElement::max_num_bits() => {
+                return IsZeroLowering::Inverse
+            }
+            // A single bit is already its own decomposition, so only its negation is needed.
+            Some(1) => cost_model.assert_zero_cost(),
+            // Each product of the negated bits but the first needs a witness.
+            Some(num_bits) => {
+                bit_decomposition_cost(cost_model, num_bits)
+                    + (num_bits - 1) as u64 * cost_model.assert_zero_cost()
+            }
+        };
+        cheapest([
+            (IsZeroLowering::Inverse, inverse),
+            (IsZeroLowering::BitDecomposition, decomposition),
+        ])
+    }
+}
+
+/// How to constrain the outputs of a sort to be a permutation of its inputs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SortStrategy {
+    /// Route the inputs through a sorting network whose switches are solved in Brillig.
+    SortingNetwork,
+    /// Solve for the outputs in Brillig and check that the product of `input - challenge` over
+    /// all inputs equals that over all outputs, for a challenge derived from both.
+    GrandProduct,
+}
+
+impl SortStrategy {
+    pub(crate) fn choose(cost_model: &dyn CostModel, len: usize) -> Self {
+        // Each element passes through a switch for each level of the network.
+        let switches: u64 = (1..len).map(|i| (i as f64 + 1.0).log2().ceil() as u64).sum();
+        let network = cost_model.unconstrained_cost()
+            + (switches + len as u64) * cost_model.assert_zero_cost();
+
+        // Every input and output is absorbed into the transcript to derive the challenge.
+        let permutations = ((2 * len + RATE - 1) / RATE) as u64;
+        let poseidon2 = cost_model
+            .black_box_cost(BlackBoxFunc::Poseidon2Permutation, FieldElement::max_num_bits());
+        let grand_product = cost_model.unconstrained_cost()
+            + permutations * poseidon2
+            + (2 * len as u64 + 1) * cost_model.assert_zero_cost();
+
+        cheapest([
+            (SortStrategy::SortingNetwork, network),
+            (SortStrategy::GrandProduct, grand_product),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::BlackBoxFunc;
+
+    use crate::cost_model::{DefaultCostModel, JsonCostModel};
+
+    use super::{BitwiseLowering, IsZeroLowering, RangeBatchEncoding, RangeEncoding, SortStrategy};
+
+    #[test]
+    fn default_model_prefers_black_boxes() {
+        let model = DefaultCostModel;
+        assert_eq!(RangeEncoding::choose(&model, 1), RangeEncoding::BlackBox);
+        assert_eq!(RangeEncoding::choose(&model, 32), RangeEncoding::BlackBox);
+        assert_eq!(
+            BitwiseLowering::choose(&model, BlackBoxFunc::AND, 8),
+            BitwiseLowering::BlackBox
+        );
+    }
+
+    #[test]
+    fn expensive_range_checks_are_decomposed() {
+        let model = JsonCostModel {
+            black_box_per_bit: [("range".to_string(), 4)].into_iter().collect(),
+            ..JsonCostModel::default()
+        };
+        assert_eq!(RangeEncoding::choose(&model, 1), RangeEncoding::Boolean);
+        assert_eq!(RangeEncoding::choose(&model, 8), RangeEncoding::BitDecomposition);
+    }
+
+    #[test]
+    fn large_batches_of_expensive_range_checks_share_a_table() {
+        let individually = RangeBatchEncoding::Individually;
+        assert_eq!(RangeBatchEncoding::choose(&DefaultCostModel, 4, 1000, false), individually);
+
+        let model = JsonCostModel {
+            black_box_per_bit: [("range".to_string(), 4)].into_iter().collect(),
+            ..JsonCostModel::default()
+        };
+        // Each 4 bit range check costs 5, while the table costs 32 plus 1 per lookup.
+        assert_eq!(RangeBatchEncoding::choose(&model, 4, 8, false), individually);
+        assert_eq!(RangeBatchEncoding::choose(&model, 4, 9, false), RangeBatchEncoding::Table);
+        assert_eq!(RangeBatchEncoding::choose(&model, 4, 1, true), RangeBatchEncoding::Table);
+        assert_eq!(RangeBatchEncoding::choose(&model, 20, 1 << 20, false), individually);
+    }
+
+    #[test]
+    fn small_values_are_checked_for_zero_by_decomposition() {
+        let model = DefaultCostModel;
+        let decomposition = IsZeroLowering::BitDecomposition;
+        assert_eq!(IsZeroLowering::choose(&model, None, true), IsZeroLowering::Inverse);
+        assert_eq!(IsZeroLowering::choose(&model, Some(1), true), decomposition);
+        assert_eq!(IsZeroLowering::choose(&model, Some(8), false), IsZeroLowering::Inverse);
+
+        // With free range checks, two bits cost 1 to recompose and 1 to multiply, against 2 for
+        // the inverse of a witness and 3 for that of any other expression.
+        let model = JsonCostModel {
+            black_box: [("range".to_string(), 0)].into_iter().collect(),
+            ..JsonCostModel::default()
+        };
+        assert_eq!(IsZeroLowering::choose(&model, Some(2), true), IsZeroLowering::Inverse);
+        assert_eq!(IsZeroLowering::choose(&model, Some(2), false), decomposition);
+    }
+
+    #[test]
+    fn cheap_hashes_favour_grand_product_sorts() {
+        assert_eq!(SortStrategy::choose(&DefaultCostModel, 64), SortStrategy::SortingNetwork);
+
+        let model = JsonCostModel {
+            black_box: [("poseidon2_permutation".to_string(), 1)].into_iter().collect(),
+            ..JsonCostModel::default()
+        };
+        assert_eq!(SortStrategy::choose(&model, 64), SortStrategy::GrandProduct);
+    }
+}
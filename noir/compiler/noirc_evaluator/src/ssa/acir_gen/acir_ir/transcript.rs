@@ -0,0 +1,174 @@
+//! A Fiat-Shamir transcript for gadgets which need random challenges in-circuit, such as
+//! batched equality checks built from a random linear combination of their inputs.
+//!
+//! Gadgets should share this transcript rather than hashing their inputs themselves so that every
+//! challenge is derived in the same way.
+use acvm::{
+    acir::{
+        circuit::opcodes::FunctionInput,
+        native_types::{Expression, Witness},
+        BlackBoxFunc,
+    },
+    FieldElement,
+};
+use iter_extended::vecmap;
+
+use crate::errors::RuntimeError;
+
+use super::generated_acir::GeneratedAcir;
+
+/// Width of the Poseidon2 permutation used by the transcript.
+const STATE_WIDTH: usize = 4;
+
+/// Number of state elements which inputs are absorbed into.
+/// The remaining element is the capacity of the sponge.
+pub(super) const RATE: usize = STATE_WIDTH - 1;
+
+/// A duplex sponge over the Poseidon2 permutation.
+///
+/// Each challenge squeezed from the transcript depends on every witness absorbed before it,
+/// as well as on all previous challenges.
+#[derive(Debug, Default)]
+pub(crate) struct Transcript {
+    /// Witnesses absorbed since the last permutation.
+    pending: Vec<Witness>,
+    /// State of the sponge after the last permutation, `None` until the first permutation.
+    state: Option<Vec<Witness>>,
+    /// Witness constrained to zero, used to fill the initial state.
+    zero: Option<Witness>,
+}
+
+impl Transcript {
+    /// Adds `witnesses` to the transcript.
+    ///
+    /// They are only hashed once the next challenge is squeezed.
+    pub(crate) fn absorb(&mut self, witnesses: &[Witness]) {
+        self.pending.extend_from_slice(witnesses);
+    }
+
+    /// Returns a challenge witness derived from everything absorbed into the transcript so far.
+    pub(crate) fn squeeze_challenge(
+        &mut self,
+        acir: &mut GeneratedAcir,
+    ) -> Result<Witness, RuntimeError> {
+        let pending = std::mem::take(&mut self.pending);
+        if pending.is_empty() {
+            // Permute the state again so that consecutive challenges differ.
+            self.permute(acir, &[])?;
+        }
+        for chunk in pending.chunks(RATE) {
+            self.permute(acir, chunk)?;
+        }
+
+        let state = self.state.as_ref().expect("ICE: transcript state is set by a permutation");
+        Ok(state[0])
+    }
+
+    /// Returns `count` challenge witnesses, each one derived from the previous.
+    pub(crate) fn squeeze_challenges(
+        &mut self,
+        acir: &mut GeneratedAcir,
+        count: usize,
+    ) -> Result<Vec<Witness>, RuntimeError> {
+        (0..count).map(|_| self.squeeze_challenge(acir)).collect()
+    }
+
+    /// Adds `inputs` into the rate portion of the state before applying the permutation to it.
+    fn permute(
+        &mut self,
+        acir: &mut GeneratedAcir,
+        inputs: &[Witness],
+    ) -> Result<(), RuntimeError> {
+        assert!(inputs.len() <= RATE, "ICE: cannot absorb more than {RATE} witnesses at once");
+
+        let state = vecmap(0..STATE_WIDTH, |index| {
+            let witness = match (&self.state, inputs.get(index)) {
+                (Some(state), Some(input)) => {
+                    let element = Expression::from(state[index]);
+                    acir.get_or_create_witness(
+                        &element.add_mul(FieldElement::one(), &(*input).into()),
+                    )
+                }
+                (Some(state), None) => state[index],
+                (None, Some(input)) => *input,
+                (None, None) => *self
+                    .zero
+                    .get_or_insert_with(|| acir.get_or_create_witness(&Expression::zero())),
+            };
+            FunctionInput { witness, num_bits: FieldElement::max_num_bits() }
+        });
+
+        let outputs = acir.call_black_box(
+            BlackBoxFunc::Poseidon2Permutation,
+            &[state],
+            vec![FieldElement::from(STATE_WIDTH as u128)],
+            Vec::new(),
+            STATE_WIDTH,
+            None,
+        )?;
+        self.state = Some(outputs);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::acir::{
+        circuit::opcodes::{BlackBoxFuncCall, Opcode},
+        native_types::Witness,
+    };
+
+    use super::{GeneratedAcir, Transcript};
+
+    fn permutations(acir: &mut GeneratedAcir) -> Vec<(Vec<Witness>, Vec<Witness>)> {
+        acir.take_opcodes()
+            .into_iter()
+            .filter_map(|opcode| match opcode {
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::Poseidon2Permutation {
+                    inputs,
+                    outputs,
+                    ..
+                }) => Some((inputs.iter().map(|input| input.witness).collect(), outputs)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn absorbs_inputs_in_chunks() {
+        let mut acir = GeneratedAcir::default();
+        let inputs: Vec<_> = (0..5).map(|_| acir.next_witness_index()).collect();
+
+        let mut transcript = Transcript::default();
+        transcript.absorb(&inputs);
+        let challenge = transcript.squeeze_challenge(&mut acir).unwrap();
+
+        let permutations = permutations(&mut acir);
+        assert_eq!(permutations.len(), 2);
+
+        // The first permutation takes the first three inputs directly.
+        let (first_inputs, first_outputs) = &permutations[0];
+        assert_eq!(&first_inputs[..3], &inputs[..3]);
+
+        // The capacity element is carried over into the second permutation.
+        let (second_inputs, second_outputs) = &permutations[1];
+        assert_eq!(second_inputs[3], first_outputs[3]);
+        assert_eq!(challenge, second_outputs[0]);
+    }
+
+    #[test]
+    fn consecutive_challenges_differ() {
+        let mut acir = GeneratedAcir::default();
+        let input = acir.next_witness_index();
+
+        let mut transcript = Transcript::default();
+        transcript.absorb(&[input]);
+        let challenges = transcript.squeeze_challenges(&mut acir, 2).unwrap();
+        assert_ne!(challenges[0], challenges[1]);
+
+        // The second challenge is a permutation of the state which produced the first.
+        let permutations = permutations(&mut acir);
+        assert_eq!(permutations.len(), 2);
+        assert_eq!(permutations[1].0[0], challenges[0]);
+    }
+}
@@ -0,0 +1,245 @@
+//! Splits `AssertZero` opcodes so that each fits within the expression width of the targeted
+//! backend, instead of leaving this to the transformation applied by the ACVM compiler.
+use std::collections::{BTreeSet, HashSet};
+
+use acvm::acir::{
+    circuit::{opcodes::Opcode as AcirOpcode, OpcodeLocation},
+    native_types::{Expression, Witness},
+};
+
+use super::generated_acir::GeneratedAcir;
+use crate::slice::dependencies;
+
+impl GeneratedAcir {
+    /// Splits every `AssertZero` opcode which has more than one multiplication term or refers to
+    /// more than `width` witnesses, by moving some of its terms into intermediate witnesses.
+    ///
+    /// Witnesses which are already known to equal a group of terms are reused, as they cost no
+    /// extra opcode, and otherwise the group is chosen to fold as many terms as possible into a
+    /// single new opcode. Terms are only folded once the ACVM has solved for their witnesses, so
+    /// that each opcode is still solvable in order.
+    pub(crate) fn reduce_expression_width(&mut self, width: usize) {
+        assert!(width >= 3, "ICE: an expression width of {width} cannot hold a multiplication");
+        let dependencies = dependencies(self.opcodes(), &self.input_witnesses);
+        let mut solved: HashSet<Witness> = self.input_witnesses.iter().copied().collect();
+
+        let call_stack = std::mem::take(&mut self.call_stack);
+        let mut new_indices = Vec::new();
+        // The intermediate opcodes added for each opcode, which share its call stack.
+        let mut intermediates = Vec::new();
+        for (index, opcode) in self.take_opcodes().into_iter().enumerate() {
+            let first_intermediate = self.opcodes().len();
+            let opcode = match opcode {
+                AcirOpcode::AssertZero(expression) => {
+                    AcirOpcode::AssertZero(self.reduce_width(expression, width, &mut solved))
+                }
+                opcode => opcode,
+            };
+            self.push_opcode(opcode);
+            let new_index = self.opcodes().len() - 1;
+            new_indices.push(new_index);
+            intermediates.extend((first_intermediate..new_index).map(|i| (i, new_index)));
+            solved.extend(&dependencies[index].solves);
+        }
+        self.call_stack = call_stack;
+
+        self.remap_opcode_locations(|location| {
+            Some(match location {
+                OpcodeLocation::Acir(index) => OpcodeLocation::Acir(new_indices[index]),
+                OpcodeLocation::Brillig { acir_index, brillig_index } => {
+                    OpcodeLocation::Brillig { acir_index: new_indices[acir_index], brillig_index }
+                }
+            })
+        });
+        for (intermediate, owner) in intermediates {
+            if let Some(call_stack) = self.locations.get(&OpcodeLocation::Acir(owner)) {
+                self.locations.insert(OpcodeLocation::Acir(intermediate), *call_stack);
+            }
+        }
+    }
+
+    /// Returns `expression` with terms folded into intermediate witnesses until it fits within
+    /// `width`, or until no more of its terms can be folded.
+    fn reduce_width(
+        &mut self,
+        mut expression: Expression,
+        width: usize,
+        solved: &mut HashSet<Witness>,
+    ) -> Expression {
+        while !fits_width(&expression, width) {
+            let folded = if expression.mul_terms.len() > 1 {
+                self.fold_multiplication(&mut expression, width, solved)
+            } else {
+                // If too few linear terms can be folded, the multiplication is folded along with
+                // them instead.
+                fold_linear_terms(&mut expression, width, solved)
+                    .or_else(|| self.fold_multiplication(&mut expression, width, solved))
+            };
+            let Some(folded) = folded else { break };
+
+            let witness = match self.existing_witness(&folded) {
+                Some(witness) if solved.contains(&witness) => witness,
+                Some(_) => self.create_witness_for_expression(&folded),
+                None => self.get_or_create_witness(&folded),
+            };
+            solved.insert(witness);
+            expression.push_addition_term(acvm::FieldElement::one(), witness);
+        }
+        expression
+    }
+
+    /// Removes a multiplication term of `expression` whose witnesses are solved, along with as
+    /// many of its solved linear terms as fit alongside it, and returns the removed terms.
+    ///
+    /// A term which already has a solved witness costs nothing to fold, so it is preferred over
+    /// the others, which each cost a new `AssertZero` opcode.
+    fn fold_multiplication(
+        &self,
+        expression: &mut Expression,
+        width: usize,
+        solved: &HashSet<Witness>,
+    ) -> Option<Expression> {
+        let cost_model = self.cost_model();
+        let cost = |term: &Expression| match self.existing_witness(term) {
+            Some(witness) if solved.contains(&witness) => 0,
+            _ => cost_model.assert_zero_cost(),
+        };
+        let (index, _) = expression
+            .mul_terms
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, lhs, rhs))| solved.contains(lhs) && solved.contains(rhs))
+            .map(|(index, term)| {
+                let term = Expression { mul_terms: vec![*term], ..Expression::default() };
+                (index, cost(&term))
+            })
+            .min_by_key(|(_, cost)| *cost)?;
+
+        let term = expression.mul_terms.remove(index);
+        let mut folded = Expression { mul_terms: vec![term], ..Expression::default() };
+        if cost(&folded) > 0 {
+            let mut witnesses: BTreeSet<Witness> = BTreeSet::from([term.1, term.2]);
+            // Linear terms on the witnesses of the multiplication are folded for free, and the
+            // others while there is room for them, which leaves one witness for the result.
+            expression.linear_combinations.retain(|(coefficient, witness)| {
+                let fits = witnesses.contains(witness) || witnesses.len() < width - 1;
+                if fits && solved.contains(witness) {
+                    witnesses.insert(*witness);
+                    folded.push_addition_term(*coefficient, *witness);
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+        Some(folded)
+    }
+}
+
+/// Removes up to `width - 1` solved linear terms of `expression` on witnesses which are not part
+/// of its multiplication, and returns the removed terms, or `None` if folding them would not
+/// make `expression` any narrower.
+fn fold_linear_terms(
+    expression: &mut Expression,
+    width: usize,
+    solved: &HashSet<Witness>,
+) -> Option<Expression> {
+    let multiplied: Vec<Witness> =
+        expression.mul_terms.iter().flat_map(|(_, lhs, rhs)| [*lhs, *rhs]).collect();
+    let foldable: Vec<usize> = expression
+        .linear_combinations
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, witness))| solved.contains(witness) && !multiplied.contains(witness))
+        .map(|(index, _)| index)
+        .take(width - 1)
+        .collect();
+    if foldable.len() < 2 {
+        return None;
+    }
+
+    let mut folded = Expression::default();
+    for index in foldable.into_iter().rev() {
+        let (coefficient, witness) = expression.linear_combinations.remove(index);
+        folded.push_addition_term(coefficient, witness);
+    }
+    Some(folded)
+}
+
+/// Returns true if `expression` has at most one multiplication term and refers to at most
+/// `width` witnesses.
+fn fits_width(expression: &Expression, width: usize) -> bool {
+    let mul_witnesses = expression.mul_terms.iter().flat_map(|(_, lhs, rhs)| [lhs, rhs]);
+    let linear_witnesses = expression.linear_combinations.iter().map(|(_, witness)| witness);
+    let witnesses: BTreeSet<&Witness> = mul_witnesses.chain(linear_witnesses).collect();
+    expression.mul_terms.len() <= 1 && witnesses.len() <= width
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::{
+        acir::{
+            circuit::{opcodes::Opcode, OpcodeLocation},
+            native_types::{Expression, Witness},
+        },
+        FieldElement,
+    };
+    use noirc_errors::{Location, Span};
+
+    use super::{fits_width, GeneratedAcir};
+
+    #[test]
+    fn splits_wide_expressions() {
+        let mut acir = GeneratedAcir::default();
+        let inputs: Vec<Witness> = (0..6).map(|_| acir.next_witness_index()).collect();
+        let output = acir.next_witness_index();
+        acir.input_witnesses = inputs.clone();
+
+        let mut expression = Expression::default();
+        expression.push_multiplication_term(FieldElement::one(), inputs[0], inputs[1]);
+        expression.push_multiplication_term(FieldElement::from(2_u128), inputs[2], inputs[3]);
+        for input in &inputs[4..] {
+            expression.push_addition_term(FieldElement::one(), *input);
+        }
+        expression.push_addition_term(-FieldElement::one(), output);
+        acir.call_stack.push_back(Location::new(Span::single_char(1), Default::default()));
+        acir.assert_is_zero(expression);
+        acir.set_last_assert_message("message".into());
+
+        acir.reduce_expression_width(3);
+
+        let opcodes = acir.opcodes();
+        assert!(opcodes.len() > 1);
+        for opcode in opcodes {
+            let Opcode::AssertZero(expression) = opcode else { panic!("expected AssertZero") };
+            assert!(fits_width(expression, 3), "{expression} is too wide");
+        }
+        // The output is still solved for by the original opcode, which keeps its message.
+        let last = opcodes.len() - 1;
+        let Opcode::AssertZero(last_expression) = &opcodes[last] else { unreachable!() };
+        assert!(last_expression.linear_combinations.iter().any(|(_, witness)| *witness == output));
+        assert_eq!(acir.assert_messages[&OpcodeLocation::Acir(last)], "message".into());
+        assert_eq!(acir.locations.len(), opcodes.len());
+    }
+
+    #[test]
+    fn reuses_existing_witnesses() {
+        let mut acir = GeneratedAcir::default();
+        let [x, y, z] = [(); 3].map(|_| acir.next_witness_index());
+        acir.input_witnesses = vec![x, y, z];
+        let mut product = Expression::default();
+        product.push_multiplication_term(FieldElement::one(), x, y);
+        let xy = acir.get_or_create_witness(&product);
+
+        let mut expression = product.clone();
+        expression.push_multiplication_term(FieldElement::one(), y, z);
+        acir.assert_is_zero(expression);
+        acir.reduce_expression_width(4);
+
+        let mut expected = Expression::default();
+        expected.push_multiplication_term(FieldElement::one(), y, z);
+        expected.push_addition_term(FieldElement::one(), xy);
+        assert_eq!(acir.opcodes().len(), 2);
+        assert_eq!(acir.opcodes()[1], Opcode::AssertZero(expected));
+    }
+}
@@ -0,0 +1,239 @@
+//! Rewrites the witnesses referenced by ACIR opcodes, for passes which renumber them.
+use acvm::acir::{
+    circuit::{
+        brillig::{Brillig, BrilligInputs, BrilligOutputs},
+        directives::Directive,
+        opcodes::{BlackBoxFuncCall, FunctionInput},
+        AssertionPayload, AssertionPayloadPart, Opcode,
+    },
+    native_types::{Expression, Witness},
+};
+
+/// Replaces every witness referenced by `opcode` with `rename(witness)`.
+pub(crate) fn rename_opcode_witnesses(
+    opcode: &mut Opcode,
+    rename: &mut impl FnMut(Witness) -> Witness,
+) {
+    match opcode {
+        Opcode::AssertZero(expression) => rename_expression(expression, rename),
+        Opcode::BlackBoxFuncCall(call) => rename_black_box_call(call, rename),
+        Opcode::Directive(Directive::ToLeRadix { a, b, .. }) => {
+            rename_expression(a, rename);
+            rename_all(b, rename);
+        }
+        Opcode::Directive(Directive::PermutationSort { inputs, bits, .. }) => {
+            for expression in inputs.iter_mut().flatten() {
+                rename_expression(expression, rename);
+            }
+            rename_all(bits, rename);
+        }
+        Opcode::Brillig(Brillig { inputs, outputs, predicate, .. })
+        | Opcode::BrilligCall { inputs, outputs, predicate, .. } => {
+            for input in inputs {
+                match input {
+                    BrilligInputs::Single(expression) => rename_expression(expression, rename),
+                    BrilligInputs::Array(expressions) => {
+                        for expression in expressions {
+                            rename_expression(expression, rename);
+                        }
+                    }
+                    BrilligInputs::MemoryArray(_) => (),
+                }
+            }
+            for output in outputs {
+                match output {
+                    BrilligOutputs::Simple(witness) => *witness = rename(*witness),
+                    BrilligOutputs::Array(witnesses) => rename_all(witnesses, rename),
+                }
+            }
+            if let Some(predicate) = predicate {
+                rename_expression(predicate, rename);
+            }
+        }
+        Opcode::MemoryInit { init, .. } => rename_all(init, rename),
+        Opcode::Call { inputs, outputs, predicate, .. } => {
+            rename_all(inputs, rename);
+            rename_all(outputs, rename);
+            if let Some(predicate) = predicate {
+                rename_expression(predicate, rename);
+            }
+        }
+        Opcode::MemoryOp { op, predicate, .. } => {
+            rename_expression(&mut op.operation, rename);
+            rename_expression(&mut op.index, rename);
+            rename_expression(&mut op.value, rename);
+            if let Some(predicate) = predicate {
+                rename_expression(predicate, rename);
+            }
+        }
+    }
+}
+
+pub(crate) fn rename_expression(
+    expression: &mut Expression,
+    rename: &mut impl FnMut(Witness) -> Witness,
+) {
+    for (_, lhs, rhs) in &mut expression.mul_terms {
+        *lhs = rename(*lhs);
+        *rhs = rename(*rhs);
+    }
+    for (_, witness) in &mut expression.linear_combinations {
+        *witness = rename(*witness);
+    }
+}
+
+/// Replaces every witness whose value is shown in `payload` with `rename(witness)`.
+pub(crate) fn rename_assertion_payload(
+    payload: &mut AssertionPayload,
+    rename: &mut impl FnMut(Witness) -> Witness,
+) {
+    for part in &mut payload.parts {
+        if let AssertionPayloadPart::Witness(witness) = part {
+            *witness = rename(*witness);
+        }
+    }
+}
+
+fn rename_all(witnesses: &mut [Witness], rename: &mut impl FnMut(Witness) -> Witness) {
+    for witness in witnesses {
+        *witness = rename(*witness);
+    }
+}
+
+fn rename_inputs(inputs: &mut [FunctionInput], rename: &mut impl FnMut(Witness) -> Witness) {
+    for input in inputs {
+        input.witness = rename(input.witness);
+    }
+}
+
+fn rename_black_box_call(call: &mut BlackBoxFuncCall, rename: &mut impl FnMut(Witness) -> Witness) {
+    match call {
+        BlackBoxFuncCall::AND { lhs, rhs, output } | BlackBoxFuncCall::XOR { lhs, rhs, output } => {
+            rename_inputs(std::slice::from_mut(lhs), rename);
+            rename_inputs(std::slice::from_mut(rhs), rename);
+            *output = rename(*output);
+        }
+        BlackBoxFuncCall::RANGE { input } => rename_inputs(std::slice::from_mut(input), rename),
+        BlackBoxFuncCall::SHA256 { inputs, outputs }
+        | BlackBoxFuncCall::Sha512 { inputs, outputs }
+        | BlackBoxFuncCall::Blake2s { inputs, outputs }
+        | BlackBoxFuncCall::Blake3 { inputs, outputs }
+        | BlackBoxFuncCall::Keccak256 { inputs, outputs }
+        | BlackBoxFuncCall::Keccakf1600 { inputs, outputs }
+        | BlackBoxFuncCall::Poseidon2Permutation { inputs, outputs, .. }
+        | BlackBoxFuncCall::PoseidonPermutation { inputs, outputs, .. } => {
+            rename_inputs(inputs, rename);
+            rename_all(outputs, rename);
+        }
+        BlackBoxFuncCall::Keccak256VariableLength { inputs, var_message_size, outputs } => {
+            rename_inputs(inputs, rename);
+            rename_inputs(std::slice::from_mut(var_message_size), rename);
+            rename_all(outputs, rename);
+        }
+        BlackBoxFuncCall::Sha256Compression { inputs, hash_values, outputs } => {
+            rename_inputs(inputs, rename);
+            rename_inputs(hash_values, rename);
+            rename_all(outputs, rename);
+        }
+        BlackBoxFuncCall::SchnorrVerify {
+            public_key_x,
+            public_key_y,
+            signature,
+            message,
+            output,
+        } => {
+            rename_inputs(std::slice::from_mut(public_key_x), rename);
+            rename_inputs(std::slice::from_mut(public_key_y), rename);
+            rename_inputs(signature, rename);
+            rename_inputs(message, rename);
+            *output = rename(*output);
+        }
+        BlackBoxFuncCall::PedersenCommitment { inputs, outputs, .. } => {
+            rename_inputs(inputs, rename);
+            outputs.0 = rename(outputs.0);
+            outputs.1 = rename(outputs.1);
+        }
+        BlackBoxFuncCall::PedersenHash { inputs, output, .. } => {
+            rename_inputs(inputs, rename);
+            *output = rename(*output);
+        }
+        BlackBoxFuncCall::EcdsaSecp256k1 {
+            public_key_x,
+            public_key_y,
+            signature,
+            hashed_message,
+            output,
+        }
+        | BlackBoxFuncCall::EcdsaSecp256r1 {
+            public_key_x,
+            public_key_y,
+            signature,
+            hashed_message,
+            output,
+        } => {
+            rename_inputs(public_key_x, rename);
+            rename_inputs(public_key_y, rename);
+            rename_inputs(signature, rename);
+            rename_inputs(hashed_message, rename);
+            *output = rename(*output);
+        }
+        BlackBoxFuncCall::Ed25519 { public_key, signature, message, output } => {
+            rename_inputs(public_key, rename);
+            rename_inputs(signature, rename);
+            rename_inputs(message, rename);
+            *output = rename(*output);
+        }
+        BlackBoxFuncCall::Bls12381G1Add { input1, input2, outputs } => {
+            rename_inputs(input1, rename);
+            rename_inputs(input2, rename);
+            rename_all(outputs, rename);
+        }
+        BlackBoxFuncCall::Bls12381G1Mul { point, scalar, outputs } => {
+            rename_inputs(point, rename);
+            rename_inputs(scalar, rename);
+            rename_all(outputs, rename);
+        }
+        BlackBoxFuncCall::FixedBaseScalarMul { low, high, outputs } => {
+            rename_inputs(std::slice::from_mut(low), rename);
+            rename_inputs(std::slice::from_mut(high), rename);
+            outputs.0 = rename(outputs.0);
+            outputs.1 = rename(outputs.1);
+        }
+        BlackBoxFuncCall::MultiScalarMul { points, scalars, outputs } => {
+            rename_inputs(points, rename);
+            rename_inputs(scalars, rename);
+            outputs.0 = rename(outputs.0);
+            outputs.1 = rename(outputs.1);
+        }
+        BlackBoxFuncCall::AES128Encrypt { inputs, iv, key, outputs } => {
+            rename_inputs(inputs, rename);
+            rename_inputs(iv, rename);
+            rename_inputs(key, rename);
+            rename_all(outputs, rename);
+        }
+        BlackBoxFuncCall::EmbeddedCurveAdd { input1_x, input1_y, input2_x, input2_y, outputs } => {
+            for input in [input1_x, input1_y, input2_x, input2_y] {
+                rename_inputs(std::slice::from_mut(input), rename);
+            }
+            outputs.0 = rename(outputs.0);
+            outputs.1 = rename(outputs.1);
+        }
+        BlackBoxFuncCall::RecursiveAggregation {
+            verification_key,
+            proof,
+            public_inputs,
+            key_hash,
+        } => {
+            rename_inputs(verification_key, rename);
+            rename_inputs(proof, rename);
+            rename_inputs(public_inputs, rename);
+            rename_inputs(std::slice::from_mut(key_hash), rename);
+        }
+        BlackBoxFuncCall::BigIntFromLeBytes { inputs, .. } => rename_inputs(inputs, rename),
+        BlackBoxFuncCall::BigIntToLeBytes { outputs, .. } => rename_all(outputs, rename),
+        BlackBoxFuncCall::BigIntAdd { .. }
+        | BlackBoxFuncCall::BigIntSub { .. }
+        | BlackBoxFuncCall::BigIntMul { .. }
+        | BlackBoxFuncCall::BigIntDiv { .. } => (),
+    }
+}
@@ -1,18 +1,20 @@
 //! This file holds the pass to convert from Noir's SSA IR to ACIR.
 mod acir_ir;
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt::Debug;
+use std::sync::Arc;
 
-use self::acir_ir::acir_variable::{AcirContext, AcirType, AcirVar};
+use self::acir_ir::acir_variable::{AcirContext, AcirType, AcirVar, AssertionPart};
 use super::function_builder::data_bus::DataBus;
 use super::ir::dfg::CallStack;
 use super::{
     ir::{
         dfg::DataFlowGraph,
-        function::{Function, RuntimeType},
+        function::{Function, FunctionId, RuntimeType},
         instruction::{
-            Binary, BinaryOp, Instruction, InstructionId, Intrinsic, TerminatorInstruction,
+            left_rotation, AssertMessage, AssertMessagePart, Binary, BinaryOp, Instruction,
+            InstructionId, Intrinsic, TerminatorInstruction,
         },
         map::Id,
         types::{NumericType, Type},
@@ -23,19 +25,22 @@ use super::{
 use crate::brillig::brillig_ir::artifact::GeneratedBrillig;
 use crate::brillig::brillig_ir::BrilligContext;
 use crate::brillig::{brillig_gen::brillig_fn::FunctionContext as BrilligFunctionContext, Brillig};
+use crate::cost_model::CostModel;
 use crate::errors::{InternalError, InternalWarning, RuntimeError, SsaReport};
+use crate::spans;
 pub(crate) use acir_ir::generated_acir::GeneratedAcir;
+pub(crate) use acir_ir::witness_renaming::rename_opcode_witnesses;
 
 use acvm::acir::native_types::Witness;
 use acvm::acir::BlackBoxFunc;
 use acvm::{
-    acir::{circuit::opcodes::BlockId, native_types::Expression},
+    acir::circuit::{opcodes::BlockId, AssertionPayload},
     FieldElement,
 };
 use fxhash::FxHashMap as HashMap;
 use im::Vector;
 use iter_extended::{try_vecmap, vecmap};
-use noirc_frontend::Distinctness;
+use noirc_frontend::{Distinctness, Visibility};
 
 /// Context struct for the acir generation pass.
 /// May be similar to the Evaluator struct in the current SSA IR.
@@ -88,6 +93,20 @@ struct Context {
     /// a new BlockId
     max_block_id: u32,
 
+    /// Arrays which are the target of an `ArraySet` instruction in the function being converted.
+    ///
+    /// Constant arrays which are not in this set are only ever read, so reads from them at an
+    /// index which is only known at runtime are lookups into a read-only table of their values.
+    written_arrays: HashSet<ValueId>,
+
+    /// Memory blocks initialized with the values of constant arrays which are never written to,
+    /// keyed by their flattened values so that identical arrays share a single block.
+    constant_array_blocks: HashMap<Vec<FieldElement>, BlockId>,
+
+    /// The id which each `#[fold]` or `#[no_inline]` function is called by in `Call` opcodes, main
+    /// being `0`.
+    acir_function_ids: BTreeMap<FunctionId, u32>,
+
     data_bus: DataBus,
 }
 
@@ -152,41 +171,89 @@ impl AcirValue {
 }
 
 impl Ssa {
+    /// Converts the SSA into ACIR, whose input witnesses are split into `main_parameters`, given
+    /// as the name, visibility and number of field elements of each parameter of `main`.
+    /// Repeated elements of a `distinct` return value share a return witness if
+    /// `dedup_return_witnesses` is true.
+    ///
+    /// Each remaining constrained function is a `#[fold]` or `#[no_inline]` function, which is
+    /// converted into a separate circuit returned alongside its name. The circuits are ordered by
+    /// the id which `Call` opcodes refer to them by, starting at `1`.
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
     #[tracing::instrument(level = "trace", skip_all)]
     pub(crate) fn into_acir(
         self,
         brillig: Brillig,
         abi_distinctness: Distinctness,
+        main_parameters: Vec<(String, Visibility, usize)>,
         last_array_uses: &HashMap<ValueId, InstructionId>,
-    ) -> Result<GeneratedAcir, RuntimeError> {
-        let context = Context::new();
-        let mut generated_acir = context.convert_ssa(self, brillig, last_array_uses)?;
+        cost_model: Arc<dyn CostModel>,
+        max_reduction_witnesses: Option<usize>,
+        warn_failing_range_constraints: bool,
+        dedup_return_witnesses: bool,
+    ) -> Result<(GeneratedAcir, Vec<(String, GeneratedAcir)>), RuntimeError> {
+        let acir_function_ids = self.acir_function_ids();
+        let new_context = || {
+            Context::new(
+                cost_model.clone(),
+                max_reduction_witnesses,
+                warn_failing_range_constraints,
+                acir_function_ids.clone(),
+            )
+        };
+
+        let mut functions = Vec::new();
+        for function_id in acir_function_ids.keys() {
+            let function = &self.functions[function_id];
+            let _span =
+                tracing::debug_span!(spans::ACIR_FUNCTION, function = function.name()).entered();
+            let mut generated_acir =
+                new_context().convert_acir_function(function, &self, &brillig, last_array_uses)?;
+            if function.dfg[function.entry_block()].parameters().is_empty() {
+                generated_acir.input_witnesses.clear();
+            }
+            // Callers read the return values of the circuit in increasing witness order.
+            generated_acir.make_return_witnesses_distinct(false);
+            generated_acir.peephole_optimize();
+            functions.push((function.name().to_string(), generated_acir));
+        }
+
+        let mut generated_acir = new_context().convert_ssa(self, brillig, last_array_uses)?;
+        generated_acir.group_input_witnesses(main_parameters);
 
         match abi_distinctness {
             Distinctness::Distinct => {
-                // Create a witness for each return witness we have
-                // to guarantee that the return witnesses are distinct
-                let distinct_return_witness: Vec<_> = generated_acir
-                    .return_witnesses
-                    .clone()
-                    .into_iter()
-                    .map(|return_witness| {
-                        generated_acir
-                            .create_witness_for_expression(&Expression::from(return_witness))
-                    })
-                    .collect();
-
-                generated_acir.return_witnesses = distinct_return_witness;
-                Ok(generated_acir)
+                generated_acir.make_return_witnesses_distinct(dedup_return_witnesses);
             }
-            Distinctness::DuplicationAllowed => Ok(generated_acir),
+            Distinctness::DuplicationAllowed => (),
         }
+        generated_acir.peephole_optimize();
+        Ok((generated_acir, functions))
+    }
+
+    /// Numbers the constrained functions other than main, in the order of their [`FunctionId`]s.
+    fn acir_function_ids(&self) -> BTreeMap<FunctionId, u32> {
+        let main_id = self.main_id;
+        self.functions
+            .iter()
+            .filter(|(id, function)| **id != main_id && function.runtime() == RuntimeType::Acir)
+            .zip(1..)
+            .map(|((id, _), index)| (*id, index))
+            .collect()
     }
 }
 
 impl Context {
-    fn new() -> Context {
+    fn new(
+        cost_model: Arc<dyn CostModel>,
+        max_reduction_witnesses: Option<usize>,
+        warn_failing_range_constraints: bool,
+        acir_function_ids: BTreeMap<FunctionId, u32>,
+    ) -> Context {
         let mut acir_context = AcirContext::default();
+        acir_context.set_cost_model(cost_model);
+        acir_context.set_max_reduction_witnesses(max_reduction_witnesses);
+        acir_context.set_warn_failing_range_constraints(warn_failing_range_constraints);
         let current_side_effects_enabled_var = acir_context.add_constant(FieldElement::one());
 
         Context {
@@ -198,6 +265,9 @@ impl Context {
             internal_memory_blocks: HashMap::default(),
             internal_mem_block_lengths: HashMap::default(),
             max_block_id: 0,
+            written_arrays: HashSet::new(),
+            constant_array_blocks: HashMap::default(),
+            acir_function_ids,
             data_bus: DataBus::default(),
         }
     }
@@ -210,37 +280,100 @@ impl Context {
         last_array_uses: &HashMap<ValueId, InstructionId>,
     ) -> Result<GeneratedAcir, RuntimeError> {
         let main_func = ssa.main();
+        let _span =
+            tracing::debug_span!(spans::ACIR_FUNCTION, function = main_func.name()).entered();
         match main_func.runtime() {
-            RuntimeType::Acir => self.convert_acir_main(main_func, &ssa, brillig, last_array_uses),
+            RuntimeType::Acir => {
+                self.convert_acir_function(main_func, &ssa, &brillig, last_array_uses)
+            }
             RuntimeType::Brillig => self.convert_brillig_main(main_func, brillig),
         }
     }
 
-    fn convert_acir_main(
+    /// Converts `function`, which is either main or a `#[fold]` or `#[no_inline]` function, into a
+    /// circuit whose inputs are the parameters of the function.
+    fn convert_acir_function(
         mut self,
-        main_func: &Function,
+        function: &Function,
         ssa: &Ssa,
-        brillig: Brillig,
+        brillig: &Brillig,
         last_array_uses: &HashMap<ValueId, InstructionId>,
     ) -> Result<GeneratedAcir, RuntimeError> {
-        let dfg = &main_func.dfg;
-        let entry_block = &dfg[main_func.entry_block()];
+        let dfg = &function.dfg;
+        let entry_block = &dfg[function.entry_block()];
         let input_witness = self.convert_ssa_block_params(entry_block.parameters(), dfg)?;
+        let max_block_id = &mut self.max_block_id;
+        self.acir_context.flush_range_constraints(|| {
+            let block_id = BlockId(*max_block_id);
+            *max_block_id += 1;
+            block_id
+        })?;
 
         self.data_bus = dfg.data_bus.to_owned();
+        self.written_arrays = written_arrays(function);
         let mut warnings = Vec::new();
         for instruction_id in entry_block.instructions() {
             warnings.extend(self.convert_ssa_instruction(
                 *instruction_id,
                 dfg,
                 ssa,
-                &brillig,
+                brillig,
                 last_array_uses,
             )?);
         }
 
         warnings.extend(self.convert_ssa_return(entry_block.unwrap_terminator(), dfg)?);
-        Ok(self.acir_context.finish(input_witness, warnings))
+        self.name_witnesses(dfg)?;
+        self.acir_context.finish(input_witness, warnings)
+    }
+
+    /// Names each witness which holds the value of a variable of the source code after it.
+    ///
+    /// Values are named in the order they were created in, so a witness which holds the values
+    /// of several variables is named after the first of them.
+    fn name_witnesses(&mut self, dfg: &DataFlowGraph) -> Result<(), RuntimeError> {
+        let mut named_values: Vec<_> = self
+            .ssa_values
+            .iter()
+            .filter_map(|(value_id, acir_value)| {
+                let name = dfg.value_name(*value_id)?;
+                Some((*value_id, name.to_string(), acir_value.clone()))
+            })
+            .collect();
+        named_values.sort_by_key(|(value_id, ..)| *value_id);
+        for (value_id, name, acir_value) in named_values {
+            self.name_acir_value(name, &acir_value, &dfg.type_of_value(value_id))?;
+        }
+        Ok(())
+    }
+
+    /// Names the witnesses of `value`, of type `typ`, after the variable `name`, with the index
+    /// and field of each element of an array appended, e.g. `points[2].1`.
+    fn name_acir_value(
+        &mut self,
+        name: String,
+        value: &AcirValue,
+        typ: &Type,
+    ) -> Result<(), RuntimeError> {
+        match (value, typ) {
+            (AcirValue::Var(var, _), _) => self.acir_context.name_var(*var, name)?,
+            (
+                AcirValue::Array(elements),
+                Type::Array(element_types, _) | Type::Slice(element_types),
+            ) => {
+                for (index, element) in elements.iter().enumerate() {
+                    let field = index % element_types.len();
+                    let element_name = if element_types.len() == 1 {
+                        format!("{name}[{index}]")
+                    } else {
+                        format!("{name}[{}].{field}", index / element_types.len())
+                    };
+                    self.name_acir_value(element_name, element, &element_types[field])?;
+                }
+            }
+            _ => (),
+        }
+        Ok(())
     }
 
     fn convert_brillig_main(
@@ -279,7 +412,7 @@ impl Context {
         for acir_var in output_vars {
             self.acir_context.return_var(acir_var)?;
         }
-        Ok(self.acir_context.finish(witness_inputs, Vec::new()))
+        self.acir_context.finish(witness_inputs, Vec::new())
     }
 
     /// Adds and binds `AcirVar`s for each numeric block parameter or block parameter array element.
@@ -377,7 +510,8 @@ impl Context {
     }
 
     /// Creates an `AcirVar` corresponding to a parameter witness to appears in the abi. A range
-    /// constraint is added if the numeric type requires it.
+    /// constraint is requested if the numeric type requires it, to be emitted alongside those of
+    /// the other parameters once they have all been added.
     ///
     /// This function is used not only for adding numeric block parameters, but also for adding
     /// any array elements that belong to reference type block parameters.
@@ -387,7 +521,7 @@ impl Context {
     ) -> Result<AcirVar, RuntimeError> {
         let acir_var = self.acir_context.add_variable();
         if matches!(numeric_type, NumericType::Signed { .. } | NumericType::Unsigned { .. }) {
-            self.acir_context.range_constrain_var(acir_var, numeric_type, None)?;
+            self.acir_context.range_constrain_var_batched(acir_var, numeric_type)?;
         }
         Ok(acir_var)
     }
@@ -413,7 +547,11 @@ impl Context {
                 let lhs = self.convert_numeric_value(*lhs, dfg)?;
                 let rhs = self.convert_numeric_value(*rhs, dfg)?;
 
-                self.acir_context.assert_eq_var(lhs, rhs, assert_message.clone())?;
+                let assert_message = assert_message
+                    .as_ref()
+                    .map(|message| self.convert_assert_message(message, dfg))
+                    .transpose()?;
+                self.acir_context.assert_eq_var(lhs, rhs, assert_message)?;
             }
             Instruction::Cast(value_id, _) => {
                 let acir_var = self.convert_numeric_value(*value_id, dfg)?;
@@ -425,9 +563,16 @@ impl Context {
                     Value::Function(id) => {
                         let func = &ssa.functions[id];
                         match func.runtime() {
-                            RuntimeType::Acir => unimplemented!(
-                                "expected an intrinsic/brillig call, but found {func:?}. All ACIR methods should be inlined"
-                            ),
+                            RuntimeType::Acir => match self.acir_function_ids.get(id) {
+                                Some(acir_function_id) => self.convert_acir_call(*acir_function_id, arguments, result_ids, dfg)?,
+                                None => {
+                                    return Err(InternalError::General {
+                                        message: format!("Expected {id} to be inlined or compiled into a separate circuit"),
+                                        call_stack: self.acir_context.get_call_stack(),
+                                    }
+                                    .into());
+                                }
+                            },
                             RuntimeType::Brillig => {
                                 let inputs = vecmap(arguments, |arg| self.convert_value(*arg, dfg));
 
@@ -535,7 +680,7 @@ impl Context {
                 self.acir_context.range_constrain_var(
                     acir_var,
                     &NumericType::Unsigned { bit_size: *max_bit_size },
-                    assert_message.clone(),
+                    assert_message.clone().map(AssertionPayload::from),
                 )?;
             }
         }
@@ -543,17 +688,112 @@ impl Context {
         Ok(warnings)
     }
 
+    /// Converts the message of a constraint into the payload which the solver renders when the
+    /// constraint fails, with the values of any variables it shows read from their witnesses.
+    ///
+    /// This must be called before emitting the constraint, as variables which are not witnesses
+    /// yet are assigned one.
+    fn convert_assert_message(
+        &mut self,
+        message: &AssertMessage,
+        dfg: &DataFlowGraph,
+    ) -> Result<AssertionPayload, RuntimeError> {
+        let message_parts = match message {
+            AssertMessage::Static(message) => return Ok(message.clone().into()),
+            AssertMessage::Dynamic { parts, .. } => parts,
+        };
+        let mut parts = Vec::with_capacity(message_parts.len());
+        for part in message_parts {
+            match part {
+                AssertMessagePart::Text(text) => parts.push(AssertionPart::Text(text.clone())),
+                AssertMessagePart::Value(value) => {
+                    let value = self.convert_value(*value, dfg);
+                    self.push_assertion_parts(value, &mut parts)?;
+                }
+            }
+        }
+        Ok(self.acir_context.assertion_payload(parts)?)
+    }
+
+    /// Appends the parts showing `value` to an assertion message, with arrays shown as a
+    /// bracketed list of their flattened elements.
+    fn push_assertion_parts(
+        &mut self,
+        value: AcirValue,
+        parts: &mut Vec<AssertionPart>,
+    ) -> Result<(), RuntimeError> {
+        let elements = match value {
+            AcirValue::Var(var, _) => {
+                parts.push(AssertionPart::Var(var));
+                return Ok(());
+            }
+            AcirValue::Array(elements) => elements.into_iter().collect(),
+            AcirValue::DynamicArray(AcirDynamicArray { block_id, len, .. }) => {
+                try_vecmap(0..len, |index| {
+                    let index_var = self.acir_context.add_constant(index);
+                    let read = self.acir_context.read_from_memory(block_id, &index_var)?;
+                    Ok::<AcirValue, RuntimeError>(AcirValue::Var(read, AcirType::field()))
+                })?
+            }
+        };
+
+        parts.push(AssertionPart::Text("[".to_owned()));
+        for (index, element) in elements.into_iter().enumerate() {
+            if index > 0 {
+                parts.push(AssertionPart::Text(", ".to_owned()));
+            }
+            self.push_assertion_parts(element, parts)?;
+        }
+        parts.push(AssertionPart::Text("]".to_owned()));
+        Ok(())
+    }
+
+    /// Converts a call to the `#[fold]` or `#[no_inline]` function numbered `acir_function_id` into
+    /// a `Call` opcode.
+    fn convert_acir_call(
+        &mut self,
+        acir_function_id: u32,
+        arguments: &[ValueId],
+        result_ids: &[ValueId],
+        dfg: &DataFlowGraph,
+    ) -> Result<(), RuntimeError> {
+        let inputs = vecmap(arguments, |arg| self.convert_value(*arg, dfg));
+        let outputs = vecmap(result_ids, |result_id| dfg.type_of_value(*result_id).into());
+        let output_values = self.acir_context.call_acir_function(
+            acir_function_id,
+            self.current_side_effects_enabled_var,
+            inputs,
+            outputs,
+        )?;
+
+        for (result_id, output) in result_ids.iter().zip(output_values) {
+            if let AcirValue::Array(_) = &output {
+                let block_id = self.block_id(result_id);
+                let array_typ = dfg.type_of_value(*result_id);
+                self.initialize_array(block_id, array_typ.flattened_size(), Some(output.clone()))?;
+            }
+            self.ssa_values.insert(*result_id, output);
+        }
+        Ok(())
+    }
+
     fn gen_brillig_for(
         &self,
         func: &Function,
         brillig: &Brillig,
     ) -> Result<GeneratedBrillig, InternalError> {
         // Create the entry point artifact
+        let arguments = BrilligFunctionContext::parameters(func);
+        let return_values = BrilligFunctionContext::return_values(func);
+        let max_loop_iterations =
+            BrilligContext::entry_point_max_loop_iterations(&arguments, &return_values);
         let mut entry_point = BrilligContext::new_entry_point_artifact(
-            BrilligFunctionContext::parameters(func),
-            BrilligFunctionContext::return_values(func),
+            arguments,
+            return_values,
             BrilligFunctionContext::function_id_to_function_label(func.id()),
         );
+        let entry_point_steps =
+            entry_point.max_steps(0..entry_point.index_of_next_opcode(), max_loop_iterations);
         // Link the entry point with all dependencies
         while let Some(unresolved_fn_label) = entry_point.first_unresolved_function_call() {
             let artifact = &brillig.find_by_function_label(unresolved_fn_label.clone());
@@ -568,8 +808,14 @@ impl Context {
             };
             entry_point.link_with(artifact);
         }
+        entry_point.eliminate_dead_code();
         // Generate the final bytecode
-        Ok(entry_point.finish())
+        let mut generated_brillig = entry_point.finish();
+        generated_brillig.step_bound =
+            entry_point_steps.zip(brillig.step_bound(func.id())).and_then(
+                |(entry_point_steps, function_steps)| entry_point_steps.checked_add(function_steps),
+            );
+        Ok(generated_brillig)
     }
 
     /// Handles an ArrayGet or ArraySet instruction.
@@ -598,6 +844,9 @@ impl Context {
         if self.handle_constant_index(instruction, dfg, index, array, store_value)? {
             return Ok(());
         }
+        if store_value.is_none() && self.handle_read_only_array(instruction, dfg, index, array)? {
+            return Ok(());
+        }
 
         let (new_index, new_value) =
             self.convert_array_operation_inputs(array, dfg, index, store_value)?;
@@ -692,6 +941,50 @@ impl Context {
         Ok(false)
     }
 
+    /// Handle reads from constant arrays which are never written to: their values are written
+    /// once to a read-only memory block, which is shared by every array holding the same values,
+    /// rather than initializing a general memory block for each of them.
+    fn handle_read_only_array(
+        &mut self,
+        instruction: InstructionId,
+        dfg: &DataFlowGraph,
+        index: ValueId,
+        array: ValueId,
+    ) -> Result<bool, RuntimeError> {
+        let array = dfg.resolve(array);
+        if self.written_arrays.contains(&array) {
+            return Ok(false);
+        }
+        let Value::Array { array: elements, typ: Type::Array(element_types, _) } = &dfg[array]
+        else {
+            return Ok(false);
+        };
+        // Arrays of arrays are indexed through their element type sizes.
+        if element_types.iter().any(|typ| !matches!(typ, Type::Numeric(_))) {
+            return Ok(false);
+        }
+        let Some(values) =
+            elements.iter().map(|element| dfg.get_numeric_constant(*element)).collect::<Option<Vec<_>>>()
+        else {
+            return Ok(false);
+        };
+        if values.is_empty() {
+            return Ok(false);
+        }
+
+        let index_var = self.convert_numeric_value(index, dfg)?;
+        let predicate_index =
+            self.acir_context.mul_var(index_var, self.current_side_effects_enabled_var)?;
+        let max_block_id = &mut self.max_block_id;
+        let value = self.acir_context.lookup_table_var(&values, predicate_index, || {
+            let block_id = BlockId(*max_block_id);
+            *max_block_id += 1;
+            block_id
+        })?;
+        self.define_result_var(dfg, instruction, value);
+        Ok(true)
+    }
+
     /// We need to properly setup the inputs for array operations in ACIR.
     /// From the original SSA values we compute the following AcirVars:
     /// - new_index is the index of the array. ACIR memory operations work with a flat memory, so we fully flattened the specified index
@@ -1000,6 +1293,14 @@ impl Context {
 
         let array_typ = dfg.type_of_value(array_id);
 
+        // Constant arrays which are never written to can share the block of an identical array
+        let constant_values = self.shareable_constant_array(array_id, &array_typ, dfg);
+        if let Some(block_id) =
+            constant_values.as_ref().and_then(|values| self.constant_array_blocks.get(values))
+        {
+            self.memory_blocks.insert(array_id, *block_id);
+        }
+
         // Use the SSA ID to get or create its block ID
         let block_id = self.block_id(&array_id);
 
@@ -1017,6 +1318,9 @@ impl Context {
                         self.flattened_slice_size(array_id, dfg)
                     };
                     self.initialize_array(block_id, len, Some(value))?;
+                    if let Some(values) = constant_values {
+                        self.constant_array_blocks.insert(values, block_id);
+                    }
                 }
                 _ => {
                     return Err(InternalError::General {
@@ -1031,6 +1335,24 @@ impl Context {
         Ok((array_id, array_typ, block_id))
     }
 
+    /// Returns the flattened values of `array_id` if it is a constant array which has no memory
+    /// block yet and whose block can be shared with identical arrays, as it is never written to.
+    fn shareable_constant_array(
+        &self,
+        array_id: ValueId,
+        array_typ: &Type,
+        dfg: &DataFlowGraph,
+    ) -> Option<Vec<FieldElement>> {
+        if !matches!(array_typ, Type::Array(..))
+            || array_typ.contains_slice_element()
+            || self.written_arrays.contains(&array_id)
+            || self.memory_blocks.contains_key(&array_id)
+        {
+            return None;
+        }
+        flattened_constants(array_id, dfg)
+    }
+
     fn init_element_type_sizes_array(
         &mut self,
         array_typ: &Type,
@@ -1376,7 +1698,12 @@ impl Context {
                 // Conservative max bit size that is small enough such that two operands can be
                 // multiplied and still fit within the field modulus. This is necessary for the
                 // truncation technique: result % 2^bit_size to be valid.
-                let max_integer_bit_size = FieldElement::max_num_bits() / 2;
+                //
+                // `u128` products are the exception, as they are computed in limbs.
+                let max_integer_bit_size = match binary_type {
+                    Type::Numeric(NumericType::Unsigned { .. }) => 128,
+                    _ => FieldElement::max_num_bits() / 2,
+                };
                 if *bit_size > max_integer_bit_size {
                     return Err(RuntimeError::UnsupportedIntegerSize {
                         num_bits: *bit_size,
@@ -1394,7 +1721,14 @@ impl Context {
         match binary.operator {
             BinaryOp::Add => self.acir_context.add_var(lhs, rhs),
             BinaryOp::Sub => self.acir_context.sub_var(lhs, rhs),
-            BinaryOp::Mul => self.acir_context.mul_var(lhs, rhs),
+            BinaryOp::Mul => match binary_type {
+                AcirType::NumericType(NumericType::Unsigned { bit_size })
+                    if 2 * bit_size >= FieldElement::max_num_bits() =>
+                {
+                    self.acir_context.u128_mul_var(lhs, rhs)
+                }
+                _ => self.acir_context.mul_var(lhs, rhs),
+            },
             BinaryOp::Div => self.acir_context.div_var(
                 lhs,
                 rhs,
@@ -1416,7 +1750,7 @@ impl Context {
             BinaryOp::Mod => self.acir_context.modulo_var(
                 lhs,
                 rhs,
-                bit_count,
+                binary_type,
                 self.current_side_effects_enabled_var,
             ),
         }
@@ -1484,7 +1818,9 @@ impl Context {
                 ) {
                     // Subtractions must first have the integer modulus added before truncation can be
                     // applied. This is done in order to prevent underflow.
-                    let integer_modulus = self.acir_context.add_constant(2_u128.pow(bit_size));
+                    let integer_modulus = self.acir_context.add_constant(
+                        FieldElement::from(2_u128).pow(&FieldElement::from(bit_size as u128)),
+                    );
                     var = self.acir_context.add_var(var, integer_modulus)?;
                 }
             }
@@ -1539,7 +1875,14 @@ impl Context {
                     sum + dfg.try_get_array_length(*result_id).unwrap_or(1)
                 });
 
-                let vars = self.acir_context.black_box_function(black_box, inputs, output_count)?;
+                let predicate =
+                    is_predicated(black_box).then_some(self.current_side_effects_enabled_var);
+                let vars = self.acir_context.black_box_function(
+                    black_box,
+                    inputs,
+                    output_count,
+                    predicate,
+                )?;
 
                 Ok(self.convert_vars_to_values(vars, dfg, result_ids))
             }
@@ -1590,6 +1933,44 @@ impl Context {
 
                 Ok(self.convert_vars_to_values(out_vars, dfg, result_ids))
             }
+            Intrinsic::PrecomputedBlackBox(black_box) => {
+                unreachable!("ICE: `{black_box}` should have been computed at compile time")
+            }
+            Intrinsic::FieldSqrt => {
+                let value = self.convert_numeric_value(arguments[0], dfg)?;
+                let (is_square, root) =
+                    self.acir_context.field_sqrt(value, self.current_side_effects_enabled_var)?;
+                Ok(vec![
+                    AcirValue::Var(is_square, AcirType::unsigned(1)),
+                    AcirValue::Var(root, AcirType::field()),
+                ])
+            }
+            Intrinsic::FieldPow => {
+                let base = self.convert_numeric_value(arguments[0], dfg)?;
+                let exponent = self.convert_numeric_value(arguments[1], dfg)?;
+                let max_bits = self.convert_numeric_value(arguments[2], dfg)?;
+                let power = self.acir_context.pow_var(base, exponent, max_bits)?;
+                Ok(vec![AcirValue::Var(power, AcirType::field())])
+            }
+            Intrinsic::RotateLeft | Intrinsic::RotateRight => {
+                let value = self.convert_numeric_value(arguments[0], dfg)?;
+                let typ = AcirType::from(dfg.type_of_value(arguments[0]));
+                let bit_size = typ.bit_size();
+                let Some(amount) = dfg.get_numeric_constant(arguments[1]) else {
+                    return Err(RuntimeError::InternalError(InternalError::NotAConstant {
+                        name: "amount".to_string(),
+                        call_stack: self.acir_context.get_call_stack(),
+                    }));
+                };
+                let amount = left_rotation(intrinsic, amount, bit_size);
+                let rotated = self.acir_context.rotate_left_var(value, amount, bit_size)?;
+                Ok(vec![AcirValue::Var(rotated, typ)])
+            }
+            Intrinsic::AsWitness => {
+                let arg = self.convert_numeric_value(arguments[0], dfg)?;
+                self.acir_context.get_or_create_witness_var(arg)?;
+                Ok(Vec::new())
+            }
             Intrinsic::ArrayLen => {
                 let len = match self.convert_value(arguments[0], dfg) {
                     AcirValue::Var(_, _) => unreachable!("Non-array passed to array.len() method"),
@@ -2159,6 +2540,35 @@ impl Context {
     }
 }
 
+/// Returns the flattened values of `value` if it is a constant or an array of constants.
+fn flattened_constants(value: ValueId, dfg: &DataFlowGraph) -> Option<Vec<FieldElement>> {
+    match &dfg[dfg.resolve(value)] {
+        Value::NumericConstant { constant, .. } => Some(vec![*constant]),
+        Value::Array { array, .. } => {
+            let mut values = Vec::new();
+            for element in array {
+                values.extend(flattened_constants(*element, dfg)?);
+            }
+            Some(values)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the arrays which are the target of an `ArraySet` instruction in `function`.
+fn written_arrays(function: &Function) -> HashSet<ValueId> {
+    let dfg = &function.dfg;
+    function
+        .reachable_blocks()
+        .into_iter()
+        .flat_map(|block| dfg[block].instructions())
+        .filter_map(|instruction| match &dfg[*instruction] {
+            Instruction::ArraySet { array, .. } => Some(dfg.resolve(*array)),
+            _ => None,
+        })
+        .collect()
+}
+
 // We can omit the element size array for arrays which don't contain arrays or slices.
 fn can_omit_element_sizes_array(array_typ: &Type) -> bool {
     if array_typ.contains_slice_element() {
@@ -2170,3 +2580,17 @@ fn can_omit_element_sizes_array(array_typ: &Type) -> bool {
 
     !types.iter().any(|typ| typ.contains_an_array())
 }
+
+/// Returns true if calls to `func` are predicated by the side-effects condition.
+///
+/// The solvers of these black boxes reject some inputs, such as points which are not on the
+/// curve, which a branch that is not taken may well hold, but accept zeros.
+fn is_predicated(func: BlackBoxFunc) -> bool {
+    matches!(
+        func,
+        BlackBoxFunc::FixedBaseScalarMul
+            | BlackBoxFunc::MultiScalarMul
+            | BlackBoxFunc::Bls12381G1Add
+            | BlackBoxFunc::Bls12381G1Mul
+    )
+}
@@ -0,0 +1,106 @@
+//! Applications embedding the compiler can run passes of their own over the SSA, e.g. to annotate
+//! the program with gate counts or to apply rewrites specific to their domain, without patching
+//! the pipeline in [`run_ssa_passes`][super::run_ssa_passes].
+//!
+//! Each [`SsaPass`] is placed before or after a single run of one of the built-in passes, named
+//! as in [`SSA_PASS_NAMES`] and suffixed with `_2`, `_3`, etc. for its later runs, as the SSA
+//! snapshots are. Passes placed at the same point run in the order in which they were registered.
+use std::{fmt::Debug, sync::Arc};
+
+use thiserror::Error;
+
+use crate::errors::RuntimeError;
+
+use super::{ssa_gen::Ssa, SSA_PASS_NAMES};
+
+/// A pass over the SSA of the whole program which is not built into the compiler.
+pub trait SsaPass: Debug + Send + Sync {
+    /// Name of the pass, used when printing the SSA after it and in its [`PassWatermark`].
+    ///
+    /// [`PassWatermark`]: super::PassWatermark
+    fn name(&self) -> &str;
+
+    /// The run of a built-in pass which this pass is placed before or after.
+    fn placement(&self) -> PassPlacement;
+
+    /// Runs the pass, returning an error if the program should not be compiled.
+    fn run(&self, ssa: &mut Ssa) -> Result<(), RuntimeError>;
+}
+
+/// Where a custom pass runs, relative to a single run of a built-in pass such as `mem2reg_2`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PassPlacement {
+    Before(String),
+    After(String),
+}
+
+impl PassPlacement {
+    fn run(&self) -> &str {
+        match self {
+            PassPlacement::Before(run) | PassPlacement::After(run) => run,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("Cannot place SSA pass `{pass}` around `{run}`, which is not a built-in pass")]
+pub struct UnknownPassError {
+    pub pass: String,
+    pub run: String,
+}
+
+/// The custom passes to run during a compilation.
+#[derive(Debug, Clone, Default)]
+pub struct CustomSsaPasses {
+    passes: Vec<Arc<dyn SsaPass>>,
+}
+
+impl CustomSsaPasses {
+    /// Adds `pass` to the pipeline, after any pass already placed at the same point.
+    pub fn register(&mut self, pass: Arc<dyn SsaPass>) -> Result<(), UnknownPassError> {
+        let placement = pass.placement();
+        if !is_built_in_run(placement.run()) {
+            return Err(UnknownPassError {
+                pass: pass.name().to_string(),
+                run: placement.run().to_string(),
+            });
+        }
+        self.passes.push(pass);
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.passes.is_empty()
+    }
+
+    /// Returns the passes placed at `placement`, in order.
+    pub(super) fn placed_at<'a>(
+        &'a self,
+        placement: &'a PassPlacement,
+    ) -> impl Iterator<Item = &'a Arc<dyn SsaPass>> + 'a {
+        self.passes.iter().filter(move |pass| pass.placement() == *placement)
+    }
+}
+
+/// Returns true if `run` names a run of a built-in pass, e.g. `mem2reg` or `mem2reg_2`.
+fn is_built_in_run(run: &str) -> bool {
+    let pass = match run.rsplit_once('_') {
+        Some((pass, count)) if count.parse::<usize>().map_or(false, |count| count >= 2) => pass,
+        _ => run,
+    };
+    SSA_PASS_NAMES.contains(&pass)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_built_in_run;
+
+    #[test]
+    fn recognizes_later_runs_of_built_in_passes() {
+        assert!(is_built_in_run("mem2reg"));
+        assert!(is_built_in_run("mem2reg_3"));
+        assert!(is_built_in_run("dead_instruction_elimination_2"));
+        assert!(!is_built_in_run("mem2reg_1"));
+        assert!(!is_built_in_run("gate_counts"));
+    }
+}
@@ -7,8 +7,8 @@ use noirc_errors::Location;
 
 use crate::ssa::ir::{
     basic_block::BasicBlockId,
-    function::{Function, FunctionId},
-    instruction::{Binary, BinaryOp, Instruction, TerminatorInstruction},
+    function::{Function, FunctionId, InlineType},
+    instruction::{AssertMessage, Binary, BinaryOp, Instruction, TerminatorInstruction},
     types::Type,
     value::{Value, ValueId},
 };
@@ -89,6 +89,16 @@ impl FunctionBuilder {
         self.new_function_with_type(name, function_id, RuntimeType::Brillig);
     }
 
+    /// Set whether calls to the current function may be inlined into their callers.
+    pub(crate) fn set_inline_type(&mut self, inline_type: InlineType) {
+        self.current_function.set_inline_type(inline_type);
+    }
+
+    /// Set whether commitments made by the current function should be computed at compile time.
+    pub(crate) fn set_precompute_commitments(&mut self, precompute_commitments: bool) {
+        self.current_function.set_precompute_commitments(precompute_commitments);
+    }
+
     /// Consume the FunctionBuilder returning all the functions it has generated.
     pub(crate) fn finish(mut self) -> Ssa {
         self.finished_functions.push(self.current_function);
@@ -126,6 +136,12 @@ impl FunctionBuilder {
         self.current_function.dfg.type_of_value(value)
     }
 
+    /// Names `value` after the variable of the source code which it was bound to, unless it
+    /// already has a name.
+    pub(crate) fn set_value_name(&mut self, value: ValueId, name: String) {
+        self.current_function.dfg.set_value_name(value, name);
+    }
+
     /// Insert a new block into the current function and return it.
     /// Note that this block is unreachable until another block is set to jump to it.
     pub(crate) fn insert_block(&mut self) -> BasicBlockId {
@@ -250,7 +266,7 @@ impl FunctionBuilder {
         &mut self,
         lhs: ValueId,
         rhs: ValueId,
-        assert_message: Option<String>,
+        assert_message: Option<AssertMessage>,
     ) {
         self.insert_instruction(Instruction::Constrain(lhs, rhs, assert_message), None);
     }
@@ -289,37 +305,36 @@ impl FunctionBuilder {
     ) -> ValueId {
         let base = self.field_constant(FieldElement::from(2_u128));
         let typ = self.current_function.dfg.type_of_value(lhs);
-        let (max_bit, pow) =
-            if let Some(rhs_constant) = self.current_function.dfg.get_numeric_constant(rhs) {
-                // Happy case is that we know precisely by how many bits the the integer will
-                // increase: lhs_bit_size + rhs
-                let bit_shift_size = rhs_constant.to_u128() as u32;
-
-                let (rhs_bit_size_pow_2, overflows) = 2_u128.overflowing_pow(bit_shift_size);
-                if overflows {
-                    assert!(bit_size < 128, "ICE - shift left with big integers are not supported");
-                    if bit_size < 128 {
-                        let zero = self.numeric_constant(FieldElement::zero(), typ);
-                        return InsertInstructionResult::SimplifiedTo(zero).first();
-                    }
-                }
-                let pow = self.numeric_constant(FieldElement::from(rhs_bit_size_pow_2), typ);
-
-                let max_lhs_bits = self.current_function.dfg.get_value_max_num_bits(lhs);
-
-                (max_lhs_bits + bit_shift_size, pow)
-            } else {
-                // we use a predicate to nullify the result in case of overflow
-                let bit_size_var =
-                    self.numeric_constant(FieldElement::from(bit_size as u128), typ.clone());
-                let overflow = self.insert_binary(rhs, BinaryOp::Lt, bit_size_var);
-                let predicate = self.insert_cast(overflow, typ.clone());
-                // we can safely cast to unsigned because overflow_checks prevent bit-shift with a negative value
-                let rhs_unsigned = self.insert_cast(rhs, Type::unsigned(bit_size));
-                let pow = self.pow(base, rhs_unsigned);
-                let pow = self.insert_cast(pow, typ);
-                (FieldElement::max_num_bits(), self.insert_binary(predicate, BinaryOp::Mul, pow))
-            };
+        let (max_bit, pow) = if let Some(rhs_constant) =
+            self.current_function.dfg.get_numeric_constant(rhs)
+        {
+            // Happy case is that we know precisely by how many bits the the integer will
+            // increase: lhs_bit_size + rhs
+            let bit_shift_size = rhs_constant.to_u128() as u32;
+
+            let (rhs_bit_size_pow_2, overflows) = 2_u128.overflowing_pow(bit_shift_size);
+            if overflows {
+                assert!(bit_size <= 128, "ICE - shift left with big integers are not supported");
+                let zero = self.numeric_constant(FieldElement::zero(), typ);
+                return InsertInstructionResult::SimplifiedTo(zero).first();
+            }
+            let pow = self.numeric_constant(FieldElement::from(rhs_bit_size_pow_2), typ);
+
+            let max_lhs_bits = self.current_function.dfg.get_value_max_num_bits(lhs);
+
+            (max_lhs_bits + bit_shift_size, pow)
+        } else {
+            // we use a predicate to nullify the result in case of overflow
+            let bit_size_var =
+                self.numeric_constant(FieldElement::from(bit_size as u128), typ.clone());
+            let overflow = self.insert_binary(rhs, BinaryOp::Lt, bit_size_var);
+            let predicate = self.insert_cast(overflow, typ.clone());
+            // we can safely cast to unsigned because overflow_checks prevent bit-shift with a negative value
+            let rhs_unsigned = self.insert_cast(rhs, Type::unsigned(bit_size));
+            let pow = self.pow(base, rhs_unsigned);
+            let pow = self.insert_cast(pow, typ);
+            (FieldElement::max_num_bits(), self.insert_binary(predicate, BinaryOp::Mul, pow))
+        };
 
         if max_bit <= bit_size {
             self.insert_binary(lhs, BinaryOp::Mul, pow)
@@ -24,6 +24,11 @@ pub(crate) struct BasicBlock {
     /// This will be a control flow instruction. This is only
     /// None if the block is still being constructed.
     terminator: Option<TerminatorInstruction>,
+
+    /// Incremented whenever the instructions of this block may have been removed or reordered,
+    /// which invalidates the pure instructions cached for it by the
+    /// [`DataFlowGraph`][super::dfg::DataFlowGraph].
+    generation: u32,
 }
 
 /// An identifier for a Basic Block.
@@ -33,7 +38,7 @@ impl BasicBlock {
     /// Create a new BasicBlock with the given parameters.
     /// Parameters can also be added later via BasicBlock::add_parameter
     pub(crate) fn new() -> Self {
-        Self { parameters: Vec::new(), instructions: Vec::new(), terminator: None }
+        Self { parameters: Vec::new(), instructions: Vec::new(), terminator: None, generation: 0 }
     }
 
     /// Returns the parameters of this block
@@ -71,14 +76,22 @@ impl BasicBlock {
 
     /// Retrieve a mutable reference to all instructions in this block.
     pub(crate) fn instructions_mut(&mut self) -> &mut Vec<InstructionId> {
+        self.generation = self.generation.wrapping_add(1);
         &mut self.instructions
     }
 
     /// Take the instructions in this block, replacing it with an empty Vec
     pub(crate) fn take_instructions(&mut self) -> Vec<InstructionId> {
+        self.generation = self.generation.wrapping_add(1);
         std::mem::take(&mut self.instructions)
     }
 
+    /// Returns the generation of this block's instructions, which changes whenever instructions
+    /// may have been removed from the block or reordered within it.
+    pub(crate) fn generation(&self) -> u32 {
+        self.generation
+    }
+
     /// Sets the terminator instruction of this block.
     ///
     /// A properly-constructed block will always terminate with a TerminatorInstruction -
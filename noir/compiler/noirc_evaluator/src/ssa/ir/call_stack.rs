@@ -0,0 +1,87 @@
+use fxhash::FxHashMap as HashMap;
+use noirc_errors::Location;
+
+use super::dfg::CallStack;
+
+/// A call stack interned in a [`CallStackTree`], which it must be resolved by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) struct CallStackId(u32);
+
+impl CallStackId {
+    /// The empty call stack, which every tree holds.
+    pub(crate) fn root() -> Self {
+        CallStackId(0)
+    }
+
+    pub(crate) fn is_root(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+/// Interns call stacks as the paths from the root of a tree of locations, with the outermost
+/// location first.
+///
+/// Instructions and opcodes generated for the same function share the outer frames of their call
+/// stacks, so that interning them stores each of those frames only once. Call stacks are resolved
+/// back into a [`CallStack`] when needed, such as when reporting an error.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CallStackTree {
+    /// The parent and innermost location of each call stack other than the root, which is
+    /// `nodes[id - 1]`.
+    nodes: Vec<(CallStackId, Location)>,
+    children: HashMap<(CallStackId, Location), CallStackId>,
+}
+
+impl CallStackTree {
+    /// Returns the call stack made of `parent` with `location` pushed onto it.
+    pub(crate) fn push(&mut self, parent: CallStackId, location: Location) -> CallStackId {
+        *self.children.entry((parent, location)).or_insert_with(|| {
+            self.nodes.push((parent, location));
+            CallStackId(self.nodes.len() as u32)
+        })
+    }
+
+    pub(crate) fn intern(&mut self, call_stack: &CallStack) -> CallStackId {
+        call_stack.iter().fold(CallStackId::root(), |parent, location| self.push(parent, *location))
+    }
+
+    pub(crate) fn resolve(&self, mut id: CallStackId) -> CallStack {
+        let mut call_stack = CallStack::new();
+        while !id.is_root() {
+            let (parent, location) = self.nodes[id.0 as usize - 1];
+            call_stack.push_front(location);
+            id = parent;
+        }
+        call_stack
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use noirc_errors::{Location, Span};
+
+    use super::{CallStackId, CallStackTree};
+    use crate::ssa::ir::dfg::CallStack;
+
+    fn at(position: u32) -> Location {
+        Location::new(Span::single_char(position), Default::default())
+    }
+
+    #[test]
+    fn call_stacks_share_their_outer_frames() {
+        let mut tree = CallStackTree::default();
+        let first: CallStack = vec![at(1), at(2), at(3)].into();
+        let second: CallStack = vec![at(1), at(2), at(4)].into();
+
+        let first_id = tree.intern(&first);
+        let second_id = tree.intern(&second);
+        assert_ne!(first_id, second_id);
+        assert_eq!(tree.intern(&first), first_id);
+        assert_eq!(tree.nodes.len(), 4);
+
+        assert_eq!(tree.resolve(first_id), first);
+        assert_eq!(tree.resolve(second_id), second);
+        assert_eq!(tree.intern(&CallStack::new()), CallStackId::root());
+        assert!(tree.resolve(CallStackId::root()).is_empty());
+    }
+}
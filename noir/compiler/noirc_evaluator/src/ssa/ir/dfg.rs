@@ -4,6 +4,7 @@ use crate::ssa::{function_builder::data_bus::DataBus, ir::instruction::SimplifyR
 
 use super::{
     basic_block::{BasicBlock, BasicBlockId},
+    call_stack::{CallStackId, CallStackTree},
     function::FunctionId,
     instruction::{
         Instruction, InstructionId, InstructionResultType, Intrinsic, TerminatorInstruction,
@@ -46,6 +47,13 @@ pub(crate) struct DataFlowGraph {
     /// twice will return the same ValueId.
     constants: HashMap<(FieldElement, Type), ValueId>,
 
+    /// Each [pure][Instruction::is_pure] instruction is unique within a block, attempting to
+    /// insert the same instruction twice into a block will return the results of the first.
+    ///
+    /// The instructions of a block are only cached for the [generation][BasicBlock::generation]
+    /// which they were inserted in, as they may since have been removed from the block.
+    pure_instructions: HashMap<BasicBlockId, (u32, HashMap<Instruction, InstructionId>)>,
+
     /// Contains each function that has been imported into the current function.
     /// A unique `ValueId` for each function's [`Value::Function`] is stored so any given FunctionId
     /// will always have the same ValueId within this function.
@@ -69,6 +77,11 @@ pub(crate) struct DataFlowGraph {
     /// material effect on the SSA itself.
     replaced_value_ids: HashMap<ValueId, ValueId>,
 
+    /// Names of the variables of the source code which values were bound to. This information is
+    /// purely used for naming the witnesses of the generated ACIR when debugging, and has no
+    /// material effect on the SSA itself.
+    value_names: HashMap<ValueId, String>,
+
     /// Source location of each instruction for debugging and issuing errors.
     ///
     /// The `CallStack` here corresponds to the entire callstack of locations. Initially this
@@ -79,7 +92,11 @@ pub(crate) struct DataFlowGraph {
     ///
     /// Instructions inserted by internal SSA passes that don't correspond to user code
     /// may not have a corresponding location.
-    locations: HashMap<InstructionId, CallStack>,
+    locations: HashMap<InstructionId, CallStackId>,
+
+    /// The call stacks of `locations`, which are interned as most instructions of a function share
+    /// the outer frames of their call stacks once it has been inlined.
+    call_stacks: CallStackTree,
 
     pub(crate) data_bus: DataBus,
 }
@@ -184,9 +201,17 @@ impl DataFlowGraph {
                 let mut last_id = None;
 
                 for instruction in instructions {
+                    if let Some(id) = self.cached_pure_instruction(&instruction, block) {
+                        last_id = Some(id);
+                        continue;
+                    }
+                    let is_pure = instruction.is_pure(self);
                     let id = self.make_instruction(instruction, ctrl_typevars.clone());
                     self.blocks[block].insert_instruction(id);
-                    self.locations.insert(id, call_stack.clone());
+                    self.locations.insert(id, self.call_stacks.intern(&call_stack));
+                    if is_pure {
+                        self.cache_pure_instruction(id, block);
+                    }
                     last_id = Some(id);
                 }
 
@@ -196,6 +221,34 @@ impl DataFlowGraph {
         }
     }
 
+    /// Returns the id of an instruction equal to `instruction` which has already been inserted into
+    /// `block` and is still part of it, if there is one.
+    fn cached_pure_instruction(
+        &self,
+        instruction: &Instruction,
+        block: BasicBlockId,
+    ) -> Option<InstructionId> {
+        let (generation, instructions) = self.pure_instructions.get(&block)?;
+        if *generation != self.blocks[block].generation() {
+            return None;
+        }
+        let id = *instructions.get(instruction)?;
+        // Instructions may have been replaced in place since they were cached.
+        (self.instructions[id] == *instruction).then_some(id)
+    }
+
+    /// Caches the pure instruction `id`, which has just been inserted at the end of `block`, so
+    /// that inserting an equal instruction later in the block reuses its results.
+    fn cache_pure_instruction(&mut self, id: InstructionId, block: BasicBlockId) {
+        let generation = self.blocks[block].generation();
+        let (cached_generation, instructions) = self.pure_instructions.entry(block).or_default();
+        if *cached_generation != generation {
+            *cached_generation = generation;
+            instructions.clear();
+        }
+        instructions.insert(self.instructions[id].clone(), id);
+    }
+
     /// Insert a value into the dfg's storage and return an id to reference it.
     /// Until the value is used in an instruction it is unreachable.
     pub(crate) fn make_value(&mut self, value: Value) -> ValueId {
@@ -210,11 +263,25 @@ impl DataFlowGraph {
     pub(crate) fn set_value_from_id(&mut self, value_to_replace: ValueId, new_value: ValueId) {
         if value_to_replace != new_value {
             self.replaced_value_ids.insert(value_to_replace, self.resolve(new_value));
+            if let Some(name) = self.value_names.get(&value_to_replace).cloned() {
+                self.set_value_name(new_value, name);
+            }
             let new_value = self.values[new_value].clone();
             self.values[value_to_replace] = new_value;
         }
     }
 
+    /// Names `value` after the variable of the source code which it was bound to, unless it
+    /// already has a name.
+    pub(crate) fn set_value_name(&mut self, value: ValueId, name: String) {
+        self.value_names.entry(value).or_insert(name);
+    }
+
+    /// Returns the name of the variable of the source code which `value` was bound to, if any.
+    pub(crate) fn value_name(&self, value: ValueId) -> Option<&str> {
+        self.value_names.get(&value).map(String::as_str)
+    }
+
     /// Set the type of value_id to the target_type.
     pub(crate) fn set_type_of_value(&mut self, value_id: ValueId, target_type: Type) {
         let value = &mut self.values[value_id];
@@ -459,11 +526,15 @@ impl DataFlowGraph {
     }
 
     pub(crate) fn get_call_stack(&self, instruction: InstructionId) -> CallStack {
-        self.locations.get(&instruction).cloned().unwrap_or_default()
+        self.locations
+            .get(&instruction)
+            .map_or_else(CallStack::new, |call_stack| self.call_stacks.resolve(*call_stack))
     }
 
     pub(crate) fn add_location(&mut self, instruction: InstructionId, location: Location) {
-        self.locations.entry(instruction).or_default().push_back(location);
+        let call_stack = self.locations.get(&instruction).copied();
+        let call_stack = self.call_stacks.push(call_stack.unwrap_or(CallStackId::root()), location);
+        self.locations.insert(instruction, call_stack);
     }
 
     pub(crate) fn get_value_call_stack(&self, value: ValueId) -> CallStack {
@@ -562,7 +633,7 @@ impl<'dfg> InsertInstructionResult<'dfg> {
 #[cfg(test)]
 mod tests {
     use super::DataFlowGraph;
-    use crate::ssa::ir::{instruction::Instruction, types::Type};
+    use crate::ssa::ir::{dfg::CallStack, instruction::Instruction, types::Type};
 
     #[test]
     fn make_instruction() {
@@ -573,4 +644,25 @@ mod tests {
         let results = dfg.instruction_results(ins_id);
         assert_eq!(results.len(), 1);
     }
+
+    #[test]
+    fn pure_instructions_are_hash_consed() {
+        let mut dfg = DataFlowGraph::default();
+        let block = dfg.make_block();
+        let param = dfg.add_block_parameter(block, Type::unsigned(16));
+        let cast = Instruction::Cast(param, Type::unsigned(32));
+
+        let first =
+            dfg.insert_instruction_and_results(cast.clone(), block, None, CallStack::new()).first();
+        let second =
+            dfg.insert_instruction_and_results(cast.clone(), block, None, CallStack::new()).first();
+        assert_eq!(first, second);
+        assert_eq!(dfg[block].instructions().len(), 1);
+
+        // Once the instruction has been removed from the block, it is no longer reused.
+        dfg[block].instructions_mut().clear();
+        let third = dfg.insert_instruction_and_results(cast, block, None, CallStack::new()).first();
+        assert_ne!(first, third);
+        assert_eq!(dfg[block].instructions().len(), 1);
+    }
 }
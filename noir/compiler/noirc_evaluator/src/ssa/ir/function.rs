@@ -1,6 +1,7 @@
 use std::collections::BTreeSet;
 
 use iter_extended::vecmap;
+pub(crate) use noirc_frontend::monomorphization::ast::InlineType;
 
 use super::basic_block::BasicBlockId;
 use super::dfg::DataFlowGraph;
@@ -35,6 +36,13 @@ pub(crate) struct Function {
 
     runtime: RuntimeType,
 
+    /// Whether calls to this function may be inlined into their callers.
+    inline_type: InlineType,
+
+    /// Whether commitments made by this function, including through the functions it calls,
+    /// should be computed at compile time.
+    precompute_commitments: bool,
+
     /// The DataFlowGraph holds the majority of data pertaining to the function
     /// including its blocks, instructions, and values.
     pub(crate) dfg: DataFlowGraph,
@@ -47,7 +55,15 @@ impl Function {
     pub(crate) fn new(name: String, id: FunctionId) -> Self {
         let mut dfg = DataFlowGraph::default();
         let entry_block = dfg.make_block();
-        Self { name, id, entry_block, dfg, runtime: RuntimeType::Acir }
+        Self {
+            name,
+            id,
+            entry_block,
+            dfg,
+            runtime: RuntimeType::Acir,
+            inline_type: InlineType::default(),
+            precompute_commitments: false,
+        }
     }
 
     /// The name of the function.
@@ -71,6 +87,26 @@ impl Function {
         self.runtime = runtime;
     }
 
+    /// Whether calls to this function may be inlined into their callers.
+    pub(crate) fn inline_type(&self) -> InlineType {
+        self.inline_type
+    }
+
+    /// Set whether calls to this function may be inlined into their callers.
+    pub(crate) fn set_inline_type(&mut self, inline_type: InlineType) {
+        self.inline_type = inline_type;
+    }
+
+    /// Whether commitments made by this function should be computed at compile time.
+    pub(crate) fn precompute_commitments(&self) -> bool {
+        self.precompute_commitments
+    }
+
+    /// Set whether commitments made by this function should be computed at compile time.
+    pub(crate) fn set_precompute_commitments(&mut self, precompute_commitments: bool) {
+        self.precompute_commitments = precompute_commitments;
+    }
+
     /// Retrieves the entry block of a function.
     ///
     /// A function's entry block contains the instructions
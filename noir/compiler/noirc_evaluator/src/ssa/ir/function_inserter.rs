@@ -116,7 +116,30 @@ impl<'f> FunctionInserter<'f> {
         );
 
         Self::insert_new_instruction_results(&mut self.values, &results, &new_results);
-        new_results
+
+        // The results of a new instruction borrow the dfg, so they are looked up again once the
+        // names of the old results have been copied over.
+        let new_results = match new_results {
+            InsertInstructionResult::Results(new_id, _) => Err(new_id),
+            InsertInstructionResult::SimplifiedTo(value) => {
+                Ok(InsertInstructionResult::SimplifiedTo(value))
+            }
+            InsertInstructionResult::SimplifiedToMultiple(values) => {
+                Ok(InsertInstructionResult::SimplifiedToMultiple(values))
+            }
+            InsertInstructionResult::InstructionRemoved => {
+                Ok(InsertInstructionResult::InstructionRemoved)
+            }
+        };
+        for result in &results {
+            let name = self.function.dfg.value_name(*result).map(str::to_string);
+            if let (Some(name), Some(new_result)) = (name, self.values.get(result)) {
+                self.function.dfg.set_value_name(*new_result, name);
+            }
+        }
+        new_results.unwrap_or_else(|new_id| {
+            InsertInstructionResult::Results(new_id, self.function.dfg.instruction_results(new_id))
+        })
     }
 
     /// Modify the values HashMap to remember the mapping between an instruction result's previous
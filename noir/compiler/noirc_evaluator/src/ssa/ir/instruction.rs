@@ -16,6 +16,7 @@ mod constrain;
 
 pub(crate) use binary::{Binary, BinaryOp};
 use call::simplify_call;
+pub(crate) use call::{left_rotation, quadratic_non_residue};
 use cast::simplify_cast;
 use constrain::decompose_constrain;
 
@@ -50,8 +51,16 @@ pub(crate) enum Intrinsic {
     ToBits(Endian),
     ToRadix(Endian),
     BlackBox(BlackBoxFunc),
+    /// A black box function whose results must be computed at compile time, as its call was
+    /// made from a function marked `#[precompute_commitments]`.
+    PrecomputedBlackBox(BlackBoxFunc),
     FromField,
     AsField,
+    AsWitness,
+    FieldSqrt,
+    FieldPow,
+    RotateLeft,
+    RotateRight,
 }
 
 impl std::fmt::Display for Intrinsic {
@@ -73,8 +82,14 @@ impl std::fmt::Display for Intrinsic {
             Intrinsic::ToRadix(Endian::Big) => write!(f, "to_be_radix"),
             Intrinsic::ToRadix(Endian::Little) => write!(f, "to_le_radix"),
             Intrinsic::BlackBox(function) => write!(f, "{function}"),
+            Intrinsic::PrecomputedBlackBox(function) => write!(f, "precomputed_{function}"),
             Intrinsic::FromField => write!(f, "from_field"),
             Intrinsic::AsField => write!(f, "as_field"),
+            Intrinsic::AsWitness => write!(f, "as_witness"),
+            Intrinsic::FieldSqrt => write!(f, "field_sqrt"),
+            Intrinsic::FieldPow => write!(f, "field_pow"),
+            Intrinsic::RotateLeft => write!(f, "rotate_left"),
+            Intrinsic::RotateRight => write!(f, "rotate_right"),
         }
     }
 }
@@ -85,11 +100,16 @@ impl Intrinsic {
     /// If there are no side effects then the `Intrinsic` can be removed if the result is unused.
     pub(crate) fn has_side_effects(&self) -> bool {
         match self {
-            Intrinsic::AssertConstant | Intrinsic::ApplyRangeConstraint => true,
+            Intrinsic::AssertConstant | Intrinsic::ApplyRangeConstraint | Intrinsic::AsWitness => {
+                true
+            }
 
             // These apply a constraint that the input must fit into a specified number of limbs.
             Intrinsic::ToBits(_) | Intrinsic::ToRadix(_) => true,
 
+            // This applies a constraint that the exponent must fit into a specified number of bits.
+            Intrinsic::FieldPow => true,
+
             Intrinsic::Sort
             | Intrinsic::ArrayLen
             | Intrinsic::SlicePushBack
@@ -100,7 +120,11 @@ impl Intrinsic {
             | Intrinsic::SliceRemove
             | Intrinsic::StrAsBytes
             | Intrinsic::FromField
-            | Intrinsic::AsField => false,
+            | Intrinsic::AsField
+            | Intrinsic::FieldSqrt
+            | Intrinsic::RotateLeft
+            | Intrinsic::RotateRight
+            | Intrinsic::PrecomputedBlackBox(_) => false,
 
             // Some black box functions have side-effects
             Intrinsic::BlackBox(func) => matches!(func, BlackBoxFunc::RecursiveAggregation),
@@ -128,6 +152,11 @@ impl Intrinsic {
             "to_be_bits" => Some(Intrinsic::ToBits(Endian::Big)),
             "from_field" => Some(Intrinsic::FromField),
             "as_field" => Some(Intrinsic::AsField),
+            "as_witness" => Some(Intrinsic::AsWitness),
+            "field_sqrt" => Some(Intrinsic::FieldSqrt),
+            "field_pow" => Some(Intrinsic::FieldPow),
+            "rotate_left" => Some(Intrinsic::RotateLeft),
+            "rotate_right" => Some(Intrinsic::RotateRight),
             other => BlackBoxFunc::lookup(other).map(Intrinsic::BlackBox),
         }
     }
@@ -140,6 +169,66 @@ pub(crate) enum Endian {
     Little,
 }
 
+/// The message of a [`Instruction::Constrain`], shown when the constraint fails.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub(crate) enum AssertMessage {
+    Static(String),
+    /// A format string, whose placeholders are filled in with the values of the variables they
+    /// name at the time the constraint fails.
+    Dynamic {
+        /// The format string as written, e.g. `mismatch: {x} vs {y}`.
+        template: String,
+        /// The text of `template` between its placeholders, and the values which replace them.
+        parts: Vec<AssertMessagePart>,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub(crate) enum AssertMessagePart {
+    Text(String),
+    Value(ValueId),
+}
+
+impl AssertMessage {
+    /// The message as written, with the placeholders of a format string left in.
+    pub(crate) fn template(&self) -> &str {
+        match self {
+            AssertMessage::Static(message) => message,
+            AssertMessage::Dynamic { template, .. } => template,
+        }
+    }
+
+    fn values(&self) -> impl Iterator<Item = ValueId> + '_ {
+        let parts = match self {
+            AssertMessage::Static(_) => &[][..],
+            AssertMessage::Dynamic { parts, .. } => parts,
+        };
+        parts.iter().filter_map(|part| match part {
+            AssertMessagePart::Text(_) => None,
+            AssertMessagePart::Value(value) => Some(*value),
+        })
+    }
+
+    fn map_values(&self, mut f: impl FnMut(ValueId) -> ValueId) -> AssertMessage {
+        match self {
+            AssertMessage::Static(message) => AssertMessage::Static(message.clone()),
+            AssertMessage::Dynamic { template, parts } => AssertMessage::Dynamic {
+                template: template.clone(),
+                parts: vecmap(parts, |part| match part {
+                    AssertMessagePart::Text(text) => AssertMessagePart::Text(text.clone()),
+                    AssertMessagePart::Value(value) => AssertMessagePart::Value(f(*value)),
+                }),
+            },
+        }
+    }
+}
+
+impl From<String> for AssertMessage {
+    fn from(message: String) -> Self {
+        AssertMessage::Static(message)
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 /// Instructions are used to perform tasks.
 /// The instructions that the IR is able to specify are listed below.
@@ -157,7 +246,7 @@ pub(crate) enum Instruction {
     Truncate { value: ValueId, bit_size: u32, max_bit_size: u32 },
 
     /// Constrains two values to be equal to one another.
-    Constrain(ValueId, ValueId, Option<String>),
+    Constrain(ValueId, ValueId, Option<AssertMessage>),
 
     /// Range constrain `value` to `max_bit_size`
     RangeCheck { value: ValueId, max_bit_size: u32, assert_message: Option<String> },
@@ -326,7 +415,9 @@ impl Instruction {
                 max_bit_size: *max_bit_size,
             },
             Instruction::Constrain(lhs, rhs, assert_message) => {
-                Instruction::Constrain(f(*lhs), f(*rhs), assert_message.clone())
+                let assert_message =
+                    assert_message.as_ref().map(|message| message.map_values(&mut f));
+                Instruction::Constrain(f(*lhs), f(*rhs), assert_message)
             }
             Instruction::Call { func, arguments } => Instruction::Call {
                 func: f(*func),
@@ -376,9 +467,12 @@ impl Instruction {
             | Instruction::Load { address: value } => {
                 f(*value);
             }
-            Instruction::Constrain(lhs, rhs, _) => {
+            Instruction::Constrain(lhs, rhs, assert_message) => {
                 f(*lhs);
                 f(*rhs);
+                for value in assert_message.iter().flat_map(AssertMessage::values) {
+                    f(value);
+                }
             }
 
             Instruction::Store { address, value } => {
@@ -476,8 +570,11 @@ impl Instruction {
             }
             Instruction::Truncate { value, bit_size, max_bit_size } => {
                 if let Some((numeric_constant, typ)) = dfg.get_numeric_constant_with_type(*value) {
-                    let integer_modulus = 2_u128.pow(*bit_size);
-                    let truncated = numeric_constant.to_u128() % integer_modulus;
+                    // `to_u128` already keeps the low 128 bits.
+                    let value = numeric_constant.to_u128();
+                    let truncated = 2_u128
+                        .checked_pow(*bit_size)
+                        .map_or(value, |integer_modulus| value % integer_modulus);
                     SimplifiedTo(dfg.make_constant(truncated.into(), typ))
                 } else if let Value::Instruction { instruction, .. } = &dfg[dfg.resolve(*value)] {
                     match &dfg[*instruction] {
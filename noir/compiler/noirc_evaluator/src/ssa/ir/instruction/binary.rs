@@ -252,8 +252,8 @@ fn eval_constant_binary_op(
                 return None;
             }
             let result = function(lhs, rhs)?;
-            // Check for overflow
-            if result >= 2u128.pow(*bit_size) {
+            // Check for overflow, which the checked operations have already done for 128 bits.
+            if 2u128.checked_pow(*bit_size).is_some_and(|modulus| result >= modulus) {
                 return None;
             }
             result.into()
@@ -296,8 +296,10 @@ fn eval_constant_binary_op(
 }
 
 fn truncate(int: u128, bit_size: u32) -> u128 {
-    let max = 2u128.pow(bit_size);
-    int % max
+    match 2u128.checked_pow(bit_size) {
+        Some(max) => int % max,
+        None => int,
+    }
 }
 
 impl BinaryOp {
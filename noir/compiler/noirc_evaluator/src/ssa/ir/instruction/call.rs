@@ -10,7 +10,7 @@ use crate::ssa::{
         dfg::{CallStack, DataFlowGraph},
         instruction::Intrinsic,
         map::Id,
-        types::Type,
+        types::{NumericType, Type},
         value::{Value, ValueId},
     },
     opt::flatten_cfg::value_merger::ValueMerger,
@@ -238,7 +238,17 @@ pub(super) fn simplify_call(
             }
         }
         Intrinsic::BlackBox(bb_func) => simplify_black_box_func(bb_func, arguments, dfg),
+        // These are computed by the `precompute_commitments` pass, which needs a solver.
+        Intrinsic::PrecomputedBlackBox(_) => SimplifyResult::None,
         Intrinsic::Sort => simplify_sort(dfg, arguments),
+        Intrinsic::AsWitness => {
+            // Constants are never assigned to witnesses.
+            if dfg.is_constant(arguments[0]) {
+                SimplifyResult::Remove
+            } else {
+                SimplifyResult::None
+            }
+        }
         Intrinsic::AsField => {
             let instruction = Instruction::Cast(
                 arguments[0],
@@ -267,6 +277,90 @@ pub(super) fn simplify_call(
             let instruction = Instruction::Cast(truncated_value, target_type);
             SimplifyResult::SimplifiedToInstruction(instruction)
         }
+        Intrinsic::FieldSqrt => match dfg.get_numeric_constant(arguments[0]) {
+            Some(value) => {
+                let (is_square, root) = field_sqrt(value);
+                let is_square = dfg.make_constant(is_square.into(), Type::bool());
+                let root = dfg.make_constant(root, Type::field());
+                SimplifyResult::SimplifiedToMultiple(vec![is_square, root])
+            }
+            None => SimplifyResult::None,
+        },
+        Intrinsic::FieldPow => {
+            let base = dfg.get_numeric_constant(arguments[0]);
+            let exponent = dfg.get_numeric_constant(arguments[1]);
+            let max_bits = dfg.get_numeric_constant(arguments[2]);
+            match (base, exponent, max_bits) {
+                // An exponent which does not fit is left for ACIR generation to report.
+                (Some(base), Some(exponent), Some(max_bits))
+                    if exponent.num_bits() as u128 <= max_bits.to_u128() =>
+                {
+                    SimplifyResult::SimplifiedTo(
+                        dfg.make_constant(base.pow(&exponent), Type::field()),
+                    )
+                }
+                _ => SimplifyResult::None,
+            }
+        }
+        Intrinsic::RotateLeft | Intrinsic::RotateRight => {
+            let typ = dfg.type_of_value(arguments[0]);
+            let bit_size = match typ {
+                Type::Numeric(NumericType::Unsigned { bit_size })
+                | Type::Numeric(NumericType::Signed { bit_size }) => bit_size,
+                _ => return SimplifyResult::None,
+            };
+            let Some(amount) = dfg.get_numeric_constant(arguments[1]) else {
+                return SimplifyResult::None;
+            };
+            let amount = left_rotation(intrinsic, amount, bit_size);
+            match dfg.get_numeric_constant(arguments[0]) {
+                _ if amount == 0 => SimplifyResult::SimplifiedTo(arguments[0]),
+                Some(value) => {
+                    let value = value.to_u128();
+                    let low_bits = value & (u128::MAX >> (128 - (bit_size - amount)));
+                    let rotated = (low_bits << amount) | (value >> (bit_size - amount));
+                    SimplifyResult::SimplifiedTo(dfg.make_constant(rotated.into(), typ))
+                }
+                None => SimplifyResult::None,
+            }
+        }
+    }
+}
+
+/// Returns the number of bits by which a rotation of `bit_size`-bit integers by `amount` bits,
+/// in the direction of `intrinsic`, rotates them to the left.
+///
+/// The result is always smaller than `bit_size`, as rotating by a multiple of it is a no-op.
+pub(crate) fn left_rotation(intrinsic: Intrinsic, amount: FieldElement, bit_size: u32) -> u32 {
+    let amount = (amount.to_u128() % bit_size as u128) as u32;
+    match intrinsic {
+        Intrinsic::RotateLeft => amount,
+        Intrinsic::RotateRight => (bit_size - amount) % bit_size,
+        _ => unreachable!("ICE: {intrinsic} is not a rotation"),
+    }
+}
+
+/// Returns the smallest quadratic non-residue of the native field.
+///
+/// A value is a non-residue exactly when its product with this one is a residue, so a square
+/// root of that product proves that the value has no square root.
+pub(crate) fn quadratic_non_residue() -> FieldElement {
+    let mut candidate = FieldElement::from(2_u128);
+    while candidate.sqrt().is_some() {
+        candidate += FieldElement::one();
+    }
+    candidate
+}
+
+/// Returns whether `value` is a square, along with a square root of `value` if so
+/// or of `value * quadratic_non_residue()` otherwise.
+fn field_sqrt(value: FieldElement) -> (bool, FieldElement) {
+    match value.sqrt() {
+        Some(root) => (true, root),
+        None => {
+            let certificate = (value * quadratic_non_residue()).sqrt();
+            (false, certificate.expect("ICE: product of two non-residues must be a square"))
+        }
     }
 }
 
@@ -395,6 +489,7 @@ fn simplify_black_box_func(
 ) -> SimplifyResult {
     match bb_func {
         BlackBoxFunc::SHA256 => simplify_hash(dfg, arguments, acvm::blackbox_solver::sha256),
+        BlackBoxFunc::Sha512 => simplify_hash(dfg, arguments, acvm::blackbox_solver::sha512),
         BlackBoxFunc::Blake2s => simplify_hash(dfg, arguments, acvm::blackbox_solver::blake2s),
         BlackBoxFunc::Blake3 => simplify_hash(dfg, arguments, acvm::blackbox_solver::blake3),
         BlackBoxFunc::Keccakf1600 => SimplifyResult::None, //TODO(Guillaume)
@@ -418,14 +513,20 @@ fn simplify_black_box_func(
             }
         }
         BlackBoxFunc::Poseidon2Permutation => SimplifyResult::None, //TODO(Guillaume)
+        BlackBoxFunc::PoseidonPermutation => SimplifyResult::None,
+        BlackBoxFunc::AES128Encrypt => SimplifyResult::None,
+        // Constant points may be invalid, which must be reported when solving rather than here.
+        BlackBoxFunc::Bls12381G1Add | BlackBoxFunc::Bls12381G1Mul => SimplifyResult::None,
         BlackBoxFunc::EcdsaSecp256k1 => {
             simplify_signature(dfg, arguments, acvm::blackbox_solver::ecdsa_secp256k1_verify)
         }
         BlackBoxFunc::EcdsaSecp256r1 => {
             simplify_signature(dfg, arguments, acvm::blackbox_solver::ecdsa_secp256r1_verify)
         }
+        BlackBoxFunc::Ed25519 => simplify_ed25519_signature(dfg, arguments),
 
         BlackBoxFunc::FixedBaseScalarMul
+        | BlackBoxFunc::MultiScalarMul
         | BlackBoxFunc::SchnorrVerify
         | BlackBoxFunc::PedersenCommitment
         | BlackBoxFunc::PedersenHash
@@ -484,8 +585,6 @@ fn constant_to_radix(
     dfg: &mut DataFlowGraph,
 ) -> (ValueId, ValueId) {
     let bit_size = u32::BITS - (radix - 1).leading_zeros();
-    let radix_big = BigUint::from(radix);
-    assert_eq!(BigUint::from(2u128).pow(bit_size), radix_big, "ICE: Radix must be a power of 2");
     let big_integer = BigUint::from_bytes_be(&field.to_be_bytes());
 
     // Decompose the integer into its radix digits in little endian form.
@@ -516,10 +615,10 @@ fn array_is_constant(dfg: &DataFlowGraph, values: &im::Vector<Id<Value>>) -> boo
     values.iter().all(|value| dfg.get_numeric_constant(*value).is_some())
 }
 
-fn simplify_hash(
+fn simplify_hash<const N: usize>(
     dfg: &mut DataFlowGraph,
     arguments: &[ValueId],
-    hash_function: fn(&[u8]) -> Result<[u8; 32], BlackBoxResolutionError>,
+    hash_function: fn(&[u8]) -> Result<[u8; N], BlackBoxResolutionError>,
 ) -> SimplifyResult {
     match dfg.get_array_constant(arguments[0]) {
         Some((input, _)) if array_is_constant(dfg, &input) => {
@@ -585,6 +684,34 @@ fn simplify_signature(
     }
 }
 
+fn simplify_ed25519_signature(dfg: &mut DataFlowGraph, arguments: &[ValueId]) -> SimplifyResult {
+    match (
+        dfg.get_array_constant(arguments[0]),
+        dfg.get_array_constant(arguments[1]),
+        dfg.get_array_constant(arguments[2]),
+    ) {
+        (Some((public_key, _)), Some((signature, _)), Some((message, _)))
+            if array_is_constant(dfg, &public_key)
+                && array_is_constant(dfg, &signature)
+                && array_is_constant(dfg, &message) =>
+        {
+            let public_key: [u8; 32] =
+                to_u8_vec(dfg, public_key).try_into().expect("Ed25519 public keys are 32 bytes");
+            let signature: [u8; 64] =
+                to_u8_vec(dfg, signature).try_into().expect("Ed25519 signatures are 64 bytes");
+            let message: Vec<u8> = to_u8_vec(dfg, message);
+
+            let valid_signature =
+                acvm::blackbox_solver::ed25519_verify(&message, &public_key, &signature)
+                    .expect("Rust solvable black box function should not fail");
+
+            let valid_signature = dfg.make_constant(valid_signature.into(), Type::bool());
+            SimplifyResult::SimplifiedTo(valid_signature)
+        }
+        _ => SimplifyResult::None,
+    }
+}
+
 fn simplify_sort(dfg: &mut DataFlowGraph, arguments: &[ValueId]) -> SimplifyResult {
     match dfg.get_array_constant(arguments[0]) {
         Some((input, _)) => {
@@ -1,13 +1,13 @@
 use acvm::FieldElement;
 
-use super::{Binary, BinaryOp, DataFlowGraph, Instruction, Type, Value, ValueId};
+use super::{AssertMessage, Binary, BinaryOp, DataFlowGraph, Instruction, Type, Value, ValueId};
 
 /// Try to decompose this constrain instruction. This constraint will be broken down such that it instead constrains
 /// all the values which are used to compute the values which were being constrained.
 pub(super) fn decompose_constrain(
     lhs: ValueId,
     rhs: ValueId,
-    msg: Option<String>,
+    msg: Option<AssertMessage>,
     dfg: &mut DataFlowGraph,
 ) -> Vec<Instruction> {
     let lhs = dfg.resolve(lhs);
@@ -9,7 +9,9 @@ use iter_extended::vecmap;
 use super::{
     basic_block::BasicBlockId,
     function::Function,
-    instruction::{Instruction, InstructionId, TerminatorInstruction},
+    instruction::{
+        AssertMessage, AssertMessagePart, Instruction, InstructionId, TerminatorInstruction,
+    },
     value::ValueId,
 };
 
@@ -119,6 +121,20 @@ pub(crate) fn display_terminator(
     }
 }
 
+/// Display the message of a constraint, with the values which fill in a format string in braces.
+fn assert_message(function: &Function, message: &AssertMessage) -> String {
+    match message {
+        AssertMessage::Static(message) => message.clone(),
+        AssertMessage::Dynamic { parts, .. } => parts
+            .iter()
+            .map(|part| match part {
+                AssertMessagePart::Text(text) => text.clone(),
+                AssertMessagePart::Value(id) => format!("{{{}}}", value(function, *id)),
+            })
+            .collect(),
+    }
+}
+
 /// Display an arbitrary instruction
 pub(crate) fn display_instruction(
     function: &Function,
@@ -146,7 +162,10 @@ pub(crate) fn display_instruction(
             writeln!(f, "truncate {value} to {bit_size} bits, max_bit_size: {max_bit_size}",)
         }
         Instruction::Constrain(lhs, rhs, message) => match message {
-            Some(message) => writeln!(f, "constrain {} == {} '{message}'", show(*lhs), show(*rhs)),
+            Some(message) => {
+                let message = assert_message(function, message);
+                writeln!(f, "constrain {} == {} '{message}'", show(*lhs), show(*rhs))
+            }
             None => writeln!(f, "constrain {} == {}", show(*lhs), show(*rhs)),
         },
         Instruction::Call { func, arguments } => {
@@ -32,7 +32,7 @@ impl NumericType {
     pub(crate) fn value_is_within_limits(self, field: FieldElement) -> bool {
         match self {
             NumericType::Signed { bit_size } | NumericType::Unsigned { bit_size } => {
-                let max = 2u128.pow(bit_size) - 1;
+                let max = 2u128.checked_pow(bit_size).map_or(u128::MAX, |modulus| modulus - 1);
                 field <= max.into()
             }
             NumericType::NativeField => true,
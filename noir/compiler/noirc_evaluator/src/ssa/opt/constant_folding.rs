@@ -18,8 +18,9 @@
 //! [`DataFlowGraph::set_value_from_id`] are used on a value which enables instructions dependent on the value to
 //! now be simplified.
 //!
-//! This is the only pass which removes duplicated pure [`Instruction`]s however and so is needed when
-//! different blocks are merged, i.e. after the [`flatten_cfg`][super::flatten_cfg] pass.
+//! The [`DataFlowGraph`] never inserts a pure [`Instruction`] which duplicates one earlier in the same
+//! block, but this is the only pass which removes duplicates that arise afterwards and so is needed
+//! when different blocks are merged, i.e. after the [`flatten_cfg`][super::flatten_cfg] pass.
 use std::collections::HashSet;
 
 use iter_extended::vecmap;
@@ -422,12 +423,20 @@ mod test {
         let v0 = builder.add_parameter(Type::unsigned(16));
 
         let v1 = builder.insert_cast(v0, Type::unsigned(32));
-        let v2 = builder.insert_cast(v0, Type::unsigned(32));
-        builder.insert_constrain(v1, v2, None);
 
         let mut ssa = builder.finish();
         let main = ssa.main_mut();
-        let instructions = main.dfg[main.entry_block()].instructions();
+        let entry = main.entry_block();
+
+        // Inserting the duplicate cast through the builder would reuse `v1`, as it does for pure
+        // instructions within a block, so it is added to the block directly.
+        let cast = main.dfg.make_instruction(Instruction::Cast(v0, Type::unsigned(32)), None);
+        main.dfg[entry].insert_instruction(cast);
+        let v2 = main.dfg.instruction_results(cast)[0];
+        let constrain = main.dfg.make_instruction(Instruction::Constrain(v1, v2, None), None);
+        main.dfg[entry].insert_instruction(constrain);
+
+        let instructions = main.dfg[entry].instructions();
         assert_eq!(instructions.len(), 3);
 
         // Expected output:
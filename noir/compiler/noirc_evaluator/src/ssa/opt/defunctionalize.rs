@@ -4,7 +4,14 @@
 //! with a non-literal target can be replaced with a call to an apply function.
 //! The apply function is a dispatch function that takes the function id as a parameter
 //! and dispatches to the correct target.
-use std::collections::{BTreeMap, BTreeSet, HashSet};
+//!
+//! Calls to a function read out of a constant array of functions (e.g. a dispatch table
+//! indexed by a runtime value) are given their own apply function which only dispatches to
+//! the functions held in that array, rather than to every function sharing its signature.
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    rc::Rc,
+};
 
 use acvm::FieldElement;
 use iter_extended::vecmap;
@@ -13,6 +20,7 @@ use crate::ssa::{
     function_builder::FunctionBuilder,
     ir::{
         basic_block::BasicBlockId,
+        dfg::DataFlowGraph,
         function::{Function, FunctionId, RuntimeType, Signature},
         instruction::{BinaryOp, Instruction},
         types::{NumericType, Type},
@@ -49,6 +57,9 @@ struct ApplyFunction {
 #[derive(Debug, Clone)]
 struct DefunctionalizationContext {
     apply_functions: HashMap<Signature, ApplyFunction>,
+    /// Apply functions for calls to an element of a constant array of functions,
+    /// keyed by the distinct functions held in the array.
+    array_apply_functions: HashMap<(Signature, Vec<FunctionId>), ApplyFunction>,
 }
 
 impl Ssa {
@@ -56,10 +67,18 @@ impl Ssa {
     pub(crate) fn defunctionalize(mut self) -> Ssa {
         // Find all functions used as value that share the same signature
         let variants = find_variants(&self);
+        let array_variants = find_array_variants(&self);
 
         let apply_functions = create_apply_functions(&mut self, variants);
+        let array_apply_functions = array_variants
+            .into_iter()
+            .map(|(signature, variants)| {
+                let apply_function = create_apply_function_for(&mut self, &signature, &variants);
+                ((signature, variants), apply_function)
+            })
+            .collect();
 
-        let context = DefunctionalizationContext { apply_functions };
+        let context = DefunctionalizationContext { apply_functions, array_apply_functions };
 
         context.defunctionalize_all(&mut self);
         self
@@ -103,7 +122,11 @@ impl DefunctionalizationContext {
                         };
 
                         // Find the correct apply function
-                        let apply_function = self.get_apply_function(&signature);
+                        let apply_function = match array_dispatch_targets(&func.dfg, target_func_id)
+                        {
+                            Some(variants) => self.array_apply_functions[&(signature, variants)],
+                            None => self.get_apply_function(&signature),
+                        };
 
                         // Replace the instruction with a call to apply
                         let apply_function_value_id = func.dfg.import_function(apply_function.id);
@@ -147,6 +170,35 @@ impl DefunctionalizationContext {
                 }
             }
         }
+
+        // Arrays holding functions now hold their ids instead
+        let value_ids = vecmap(func.dfg.values_iter(), |(id, _)| id);
+        for value_id in value_ids {
+            let typ = func.dfg[value_id].get_type().clone();
+            let new_type = defunctionalize_type(&typ);
+            if new_type == typ {
+                continue;
+            }
+            match func.dfg[value_id].clone() {
+                Value::Array { array, .. } => {
+                    let array = array
+                        .into_iter()
+                        .map(|element| match func.dfg[element] {
+                            Value::Function(id) => {
+                                func.dfg.make_constant(function_id_to_field(id), Type::field())
+                            }
+                            _ => func.dfg.resolve(element),
+                        })
+                        .collect();
+                    let new_array = func.dfg.make_array(array, new_type);
+                    func.dfg.set_value_from_id(value_id, new_array);
+                }
+                Value::Instruction { .. } | Value::Param { .. } => {
+                    func.dfg.set_type_of_value(value_id, new_type);
+                }
+                _ => {}
+            }
+        }
     }
 
     /// Returns the apply function for the given signature
@@ -191,11 +243,8 @@ fn find_variants(ssa: &Ssa) -> BTreeMap<Signature, Vec<FunctionId>> {
 fn find_functions_as_values(func: &Function) -> BTreeSet<FunctionId> {
     let mut functions_as_values: BTreeSet<FunctionId> = BTreeSet::new();
 
-    let mut process_value = |value_id: ValueId| {
-        if let Value::Function(id) = func.dfg[value_id] {
-            functions_as_values.insert(id);
-        }
-    };
+    let mut process_value =
+        |value_id: ValueId| collect_functions(&func.dfg, value_id, &mut functions_as_values);
 
     for block_id in func.reachable_blocks() {
         let block = &func.dfg[block_id];
@@ -208,6 +257,14 @@ fn find_functions_as_values(func: &Function) -> BTreeSet<FunctionId> {
                 Instruction::Store { value, .. } => {
                     process_value(*value);
                 }
+                // Functions may also be used as values by being read out of an array
+                Instruction::ArrayGet { array, .. } => {
+                    process_value(*array);
+                }
+                Instruction::ArraySet { array, value, .. } => {
+                    process_value(*array);
+                    process_value(*value);
+                }
                 _ => continue,
             };
         }
@@ -218,6 +275,83 @@ fn find_functions_as_values(func: &Function) -> BTreeSet<FunctionId> {
     functions_as_values
 }
 
+/// Collects the functions held by `value_id`, either directly or as elements of a constant array.
+fn collect_functions(dfg: &DataFlowGraph, value_id: ValueId, functions: &mut BTreeSet<FunctionId>) {
+    match &dfg[value_id] {
+        Value::Function(id) => {
+            functions.insert(*id);
+        }
+        Value::Array { array, .. } => {
+            for element in array {
+                collect_functions(dfg, *element, functions);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// If `target` is read from a constant array whose elements are all functions, returns the
+/// distinct functions it may be, bounding the dispatch to the length of the array.
+fn array_dispatch_targets(dfg: &DataFlowGraph, target: ValueId) -> Option<Vec<FunctionId>> {
+    let Value::Instruction { instruction, .. } = &dfg[target] else {
+        return None;
+    };
+    let Instruction::ArrayGet { array, .. } = &dfg[*instruction] else {
+        return None;
+    };
+    let (array, _) = dfg.get_array_constant(*array)?;
+
+    let targets = array
+        .iter()
+        .map(|element| match &dfg[dfg.resolve(*element)] {
+            Value::Function(id) => Some(*id),
+            _ => None,
+        })
+        .collect::<Option<BTreeSet<_>>>()?;
+    Some(targets.into_iter().collect())
+}
+
+/// Collects the signatures of calls to an element of a constant array of functions
+/// along with the functions held in each array.
+fn find_array_variants(ssa: &Ssa) -> BTreeSet<(Signature, Vec<FunctionId>)> {
+    let mut variants = BTreeSet::new();
+
+    for function in ssa.functions.values() {
+        for block_id in function.reachable_blocks() {
+            for instruction_id in function.dfg[block_id].instructions() {
+                let Instruction::Call { func: target, arguments } = &function.dfg[*instruction_id]
+                else {
+                    continue;
+                };
+                if let Some(targets) = array_dispatch_targets(&function.dfg, *target) {
+                    let results = function.dfg.instruction_results(*instruction_id);
+                    let signature = Signature {
+                        params: vecmap(arguments, |param| function.dfg.type_of_value(*param)),
+                        returns: vecmap(results, |result| function.dfg.type_of_value(*result)),
+                    };
+                    variants.insert((signature, targets));
+                }
+            }
+        }
+    }
+    variants
+}
+
+/// Replaces any function types within `typ` with the field type used for function ids.
+fn defunctionalize_type(typ: &Type) -> Type {
+    match typ {
+        Type::Function => Type::field(),
+        Type::Array(elements, len) => {
+            Type::Array(Rc::new(vecmap(elements.iter(), defunctionalize_type)), *len)
+        }
+        Type::Slice(elements) => {
+            Type::Slice(Rc::new(vecmap(elements.iter(), defunctionalize_type)))
+        }
+        Type::Reference(element) => Type::Reference(Rc::new(defunctionalize_type(element))),
+        Type::Numeric(_) => typ.clone(),
+    }
+}
+
 /// Finds all dynamic dispatch signatures in the given function
 fn find_dynamic_dispatches(func: &Function) -> BTreeSet<Signature> {
     let mut dispatches = BTreeSet::new();
@@ -229,6 +363,10 @@ fn find_dynamic_dispatches(func: &Function) -> BTreeSet<Signature> {
             match instruction {
                 Instruction::Call { func: target, arguments } => {
                     if let Value::Param { .. } | Value::Instruction { .. } = &func.dfg[*target] {
+                        // These are dispatched to by their own apply functions
+                        if array_dispatch_targets(&func.dfg, *target).is_some() {
+                            continue;
+                        }
                         let results = func.dfg.instruction_results(*instruction_id);
                         dispatches.insert(Signature {
                             params: vecmap(arguments, |param| func.dfg.type_of_value(*param)),
@@ -249,22 +387,33 @@ fn create_apply_functions(
 ) -> HashMap<Signature, ApplyFunction> {
     let mut apply_functions = HashMap::default();
     for (signature, variants) in variants_map.into_iter() {
-        assert!(
-            !variants.is_empty(),
-            "ICE: at least one variant should exist for a dynamic call {signature:?}"
-        );
-        let dispatches_to_multiple_functions = variants.len() > 1;
-
-        let id = if dispatches_to_multiple_functions {
-            create_apply_function(ssa, signature.clone(), variants)
-        } else {
-            variants[0]
-        };
-        apply_functions.insert(signature, ApplyFunction { id, dispatches_to_multiple_functions });
+        let apply_function = create_apply_function_for(ssa, &signature, &variants);
+        apply_functions.insert(signature, apply_function);
     }
     apply_functions
 }
 
+/// Creates an apply function dispatching to `variants`, unless there is only a single variant
+/// in which case calls are made to it directly.
+fn create_apply_function_for(
+    ssa: &mut Ssa,
+    signature: &Signature,
+    variants: &[FunctionId],
+) -> ApplyFunction {
+    assert!(
+        !variants.is_empty(),
+        "ICE: at least one variant should exist for a dynamic call {signature:?}"
+    );
+    let dispatches_to_multiple_functions = variants.len() > 1;
+
+    let id = if dispatches_to_multiple_functions {
+        create_apply_function(ssa, signature.clone(), variants.to_vec())
+    } else {
+        variants[0]
+    };
+    ApplyFunction { id, dispatches_to_multiple_functions }
+}
+
 fn function_id_to_field(function_id: FunctionId) -> FieldElement {
     (function_id.to_usize() as u128).into()
 }
@@ -356,3 +505,95 @@ fn build_return_block(
     builder.switch_to_block(previous_block);
     return_block
 }
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use crate::ssa::{
+        function_builder::FunctionBuilder,
+        ir::{
+            function::RuntimeType,
+            instruction::{BinaryOp, Instruction},
+            map::Id,
+            types::Type,
+            value::Value,
+        },
+    };
+
+    #[test]
+    fn dispatches_only_to_functions_in_array() {
+        // fn main f0 {
+        //   b0(v0: u32, v1: Field):
+        //     v2 = array_get [f1, f2, f1], index v0
+        //     v3 = call v2(v1)
+        //     return v3
+        // }
+        // fn double f1 {
+        //   b0(v0: Field):
+        //     v1 = mul v0, Field 2
+        //     return v1
+        // }
+        // fn triple f2 {
+        //   b0(v0: Field):
+        //     v1 = mul v0, Field 3
+        //     return v1
+        // }
+        // fn quadruple f3 {
+        //   b0(v0: Field):
+        //     v1 = mul v0, Field 4
+        //     return v1
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::unsigned(32));
+        let v1 = builder.add_parameter(Type::field());
+
+        let double = builder.import_function(Id::test_new(1));
+        let triple = builder.import_function(Id::test_new(2));
+        let functions_type = Type::Array(Rc::new(vec![Type::Function]), 3);
+        let functions = builder.array_constant(im::vector![double, triple, double], functions_type);
+        let v2 = builder.insert_array_get(functions, v0, Type::Function);
+        let v3 = builder.insert_call(v2, vec![v1], vec![Type::field()])[0];
+        builder.terminate_with_return(vec![v3]);
+
+        // `quadruple` shares the signature of the functions in the array but is not one of them.
+        for (id, name, factor) in [(1, "double", 2u128), (2, "triple", 3), (3, "quadruple", 4)] {
+            builder.new_function(name.into(), Id::test_new(id));
+            let v0 = builder.add_parameter(Type::field());
+            let factor = builder.field_constant(factor);
+            let v1 = builder.insert_binary(v0, BinaryOp::Mul, factor);
+            builder.terminate_with_return(vec![v1]);
+        }
+
+        let ssa = builder.finish().defunctionalize();
+        let main = ssa.main();
+
+        // The array now holds the ids of the functions
+        let array_type = Type::Array(Rc::new(vec![Type::field()]), 3);
+        assert_eq!(main.dfg.type_of_value(functions), array_type);
+
+        let instructions = main.dfg[main.entry_block()].instructions();
+        let Instruction::Call { func, arguments } = &main.dfg[instructions[1]] else {
+            panic!("Expected a call instruction");
+        };
+        let Value::Function(apply_id) = main.dfg[*func] else {
+            panic!("Expected a call to an apply function");
+        };
+        assert_eq!(arguments, &vec![v2, v1]);
+
+        let apply = &ssa.functions[&apply_id];
+        let mut targets = Vec::new();
+        for block in apply.reachable_blocks() {
+            for instruction in apply.dfg[block].instructions() {
+                if let Instruction::Call { func, .. } = &apply.dfg[*instruction] {
+                    if let Value::Function(target) = apply.dfg[*func] {
+                        targets.push(target);
+                    }
+                }
+            }
+        }
+        targets.sort();
+        assert_eq!(targets, vec![Id::test_new(1), Id::test_new(2)]);
+    }
+}
@@ -157,15 +157,17 @@ pub(crate) mod value_merger;
 use value_merger::ValueMerger;
 
 impl Ssa {
-    /// Flattens the control flow graph of main such that the function is left with a
-    /// single block containing all instructions and no more control-flow.
+    /// Flattens the control flow graph of each constrained function such that the function is
+    /// left with a single block containing all instructions and no more control-flow.
     ///
     /// This pass will modify any instructions with side effects in particular, often multiplying
     /// them by jump conditions to maintain correctness even when all branches of a jmpif are inlined.
     /// For more information, see the module-level comment at the top of this file.
     #[tracing::instrument(level = "trace", skip(self))]
     pub(crate) fn flatten_cfg(mut self) -> Ssa {
-        flatten_function_cfg(self.main_mut());
+        for function in self.functions.values_mut() {
+            flatten_function_cfg(function);
+        }
         self
     }
 }
@@ -713,7 +715,10 @@ impl<'f> Context<'f> {
     }
 
     fn undo_stores_in_then_branch(&mut self, then_branch: &Branch) {
-        for (address, store) in &then_branch.store_values {
+        // The stores are undone in order of address rather than in the order of the hash map, so
+        // that the instructions inserted do not depend on how the addresses are hashed.
+        let store_values: BTreeMap<_, _> = then_branch.store_values.iter().collect();
+        for (address, store) in store_values {
             let address = *address;
             let value = store.old_value;
             self.insert_instruction_with_typevars(Instruction::Store { address, value }, None);
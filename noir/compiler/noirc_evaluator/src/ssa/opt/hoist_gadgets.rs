@@ -0,0 +1,537 @@
+//! This pass merges pure gadget calls which [`flatten_cfg`][super::flatten_cfg] has duplicated
+//! across the branches of an `if` expression into a single call over the selected inputs.
+//!
+//! Black box calls are not predicated, so after flattening `if c { sha256(a) } else { sha256(b) }`
+//! both calls end up in the circuit even though only one of their results is ever used:
+//!
+//! ```text
+//! enable_side_effects v0
+//! v1 = call sha256(a)
+//! v2 = not v0
+//! enable_side_effects v2
+//! v3 = call sha256(b)
+//! ```
+//!
+//! As the two calls execute under mutually exclusive conditions and the gadget is pure,
+//! both can be replaced by a single call `sha256(select(v0, a, b))` placed where the second call was.
+//! The merged call is made under either condition, as some black boxes are predicated on it:
+//!
+//! ```text
+//! enable_side_effects v0
+//! v2 = not v0
+//! enable_side_effects v2
+//! v4 = <merge of a and b on v0>
+//! v5 = or v0, v2
+//! enable_side_effects v5
+//! v6 = call sha256(v4)
+//! enable_side_effects v2
+//! ```
+//!
+//! The first call can only be removed if none of its results are used before the second call,
+//! since the replacement results only become available from that point onwards.
+use std::collections::HashSet;
+
+use acvm::acir::BlackBoxFunc;
+use fxhash::FxHashMap as HashMap;
+use iter_extended::vecmap;
+
+use crate::ssa::{
+    ir::{
+        basic_block::BasicBlockId,
+        dfg::DataFlowGraph,
+        function::{Function, RuntimeType},
+        instruction::{Binary, BinaryOp, Instruction, InstructionId, Intrinsic},
+        types::Type,
+        value::{Value, ValueId},
+    },
+    ssa_gen::Ssa,
+};
+
+use super::flatten_cfg::value_merger::ValueMerger;
+
+impl Ssa {
+    /// Merges pairs of identical pure gadget calls executed under mutually exclusive
+    /// side-effects conditions into a single call over the selected inputs.
+    ///
+    /// See [`hoist_gadgets`][self] module for more information.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub(crate) fn hoist_predicated_gadgets(mut self) -> Ssa {
+        for function in self.functions.values_mut() {
+            // Only ACIR functions are flattened into a single block of predicated instructions.
+            if function.runtime() == RuntimeType::Acir {
+                hoist_predicated_gadgets(function);
+            }
+        }
+        self
+    }
+}
+
+/// A gadget call encountered earlier in the block which has not been paired yet.
+struct GadgetCall {
+    instruction: InstructionId,
+    /// Index of the call within the block's instructions.
+    position: usize,
+    func: BlackBoxFunc,
+    /// The side-effects condition active when the call was made.
+    condition: ValueId,
+}
+
+fn hoist_predicated_gadgets(function: &mut Function) {
+    let block = function.entry_block();
+    let instructions = function.dfg[block].instructions().to_vec();
+
+    let mut candidates: Vec<GadgetCall> = Vec::new();
+    // Maps the second call of each pair to the first call and the conditions each was made under.
+    let mut pairs: HashMap<InstructionId, (InstructionId, ValueId, ValueId)> = HashMap::default();
+    let mut side_effects_condition = None;
+
+    for (position, instruction_id) in instructions.iter().copied().enumerate() {
+        let func = match &function.dfg[instruction_id] {
+            Instruction::EnableSideEffects { condition } => {
+                side_effects_condition = Some(*condition);
+                continue;
+            }
+            Instruction::Call { func, .. } => match &function.dfg[*func] {
+                Value::Intrinsic(Intrinsic::BlackBox(func)) if is_hoistable(*func) => *func,
+                _ => continue,
+            },
+            _ => continue,
+        };
+        // Calls made before any condition is set are not within a branch.
+        let Some(condition) = side_effects_condition else {
+            continue;
+        };
+
+        let call = GadgetCall { instruction: instruction_id, position, func, condition };
+        let paired = candidates
+            .iter()
+            .position(|first| can_pair(&function.dfg, &instructions, first, &call));
+
+        if let Some(index) = paired {
+            let first = candidates.remove(index);
+            pairs.insert(instruction_id, (first.instruction, first.condition, condition));
+        } else {
+            candidates.push(call);
+        }
+    }
+
+    if pairs.is_empty() {
+        return;
+    }
+
+    let removed: HashSet<InstructionId> = pairs.values().map(|(first, ..)| *first).collect();
+    function.dfg[block].take_instructions();
+
+    for instruction_id in instructions {
+        if removed.contains(&instruction_id) {
+            continue;
+        }
+        match pairs.get(&instruction_id) {
+            Some((first, condition, second_condition)) => {
+                let conditions = (*condition, *second_condition);
+                hoist_pair(&mut function.dfg, block, *first, instruction_id, conditions);
+            }
+            None => function.dfg[block].insert_instruction(instruction_id),
+        }
+    }
+}
+
+/// Returns true if calls to `func` can be safely evaluated on inputs selected from two calls.
+///
+/// Black boxes with side-effects are excluded, as are the big integer operations which
+/// refer to state held by acir_gen rather than to values.
+fn is_hoistable(func: BlackBoxFunc) -> bool {
+    match func {
+        BlackBoxFunc::SHA256
+        | BlackBoxFunc::Sha512
+        | BlackBoxFunc::Blake2s
+        | BlackBoxFunc::Blake3
+        | BlackBoxFunc::Keccak256
+        | BlackBoxFunc::Keccakf1600
+        | BlackBoxFunc::SchnorrVerify
+        | BlackBoxFunc::PedersenCommitment
+        | BlackBoxFunc::PedersenHash
+        | BlackBoxFunc::EcdsaSecp256k1
+        | BlackBoxFunc::EcdsaSecp256r1
+        | BlackBoxFunc::Ed25519
+        | BlackBoxFunc::Bls12381G1Add
+        | BlackBoxFunc::Bls12381G1Mul
+        | BlackBoxFunc::FixedBaseScalarMul
+        | BlackBoxFunc::MultiScalarMul
+        | BlackBoxFunc::EmbeddedCurveAdd
+        | BlackBoxFunc::Poseidon2Permutation
+        | BlackBoxFunc::PoseidonPermutation
+        | BlackBoxFunc::Sha256Compression
+        | BlackBoxFunc::AES128Encrypt => true,
+
+        BlackBoxFunc::AND
+        | BlackBoxFunc::XOR
+        | BlackBoxFunc::RANGE
+        | BlackBoxFunc::RecursiveAggregation
+        | BlackBoxFunc::BigIntAdd
+        | BlackBoxFunc::BigIntSub
+        | BlackBoxFunc::BigIntMul
+        | BlackBoxFunc::BigIntDiv
+        | BlackBoxFunc::BigIntFromLeBytes
+        | BlackBoxFunc::BigIntToLeBytes => false,
+    }
+}
+
+/// Returns true if the argument at `index` must be a compile-time constant,
+/// in which case both calls must agree on it for them to be merged.
+fn requires_constant_argument(func: BlackBoxFunc, index: usize, argument_count: usize) -> bool {
+    match func {
+        // The domain separator and the state length respectively are passed as the last argument.
+        BlackBoxFunc::PedersenCommitment
+        | BlackBoxFunc::PedersenHash
+        | BlackBoxFunc::Poseidon2Permutation => index + 1 == argument_count,
+        // The state length and the rate of the original Poseidon are its last two arguments.
+        BlackBoxFunc::PoseidonPermutation => index + 2 >= argument_count,
+        _ => false,
+    }
+}
+
+fn can_pair(
+    dfg: &DataFlowGraph,
+    instructions: &[InstructionId],
+    first: &GadgetCall,
+    second: &GadgetCall,
+) -> bool {
+    if first.func != second.func
+        || !conditions_are_exclusive(dfg, first.condition, second.condition)
+    {
+        return false;
+    }
+
+    let first_results = dfg.instruction_results(first.instruction);
+    let second_results = dfg.instruction_results(second.instruction);
+    let results_match = first_results.len() == second_results.len()
+        && first_results
+            .iter()
+            .zip(second_results)
+            .all(|(lhs, rhs)| dfg.type_of_value(*lhs) == dfg.type_of_value(*rhs));
+
+    results_match
+        && arguments_can_be_merged(dfg, first.func, first.instruction, second.instruction)
+        && !instructions[first.position + 1..second.position].iter().any(|instruction| {
+            let mut uses_results = false;
+            dfg[*instruction].for_each_value(|value| {
+                uses_results |= value_uses_any(dfg, value, first_results);
+            });
+            uses_results
+        })
+}
+
+fn arguments_can_be_merged(
+    dfg: &DataFlowGraph,
+    func: BlackBoxFunc,
+    first: InstructionId,
+    second: InstructionId,
+) -> bool {
+    let (Instruction::Call { arguments: lhs, .. }, Instruction::Call { arguments: rhs, .. }) =
+        (&dfg[first], &dfg[second])
+    else {
+        unreachable!("ICE: Expected gadget instructions to be calls");
+    };
+
+    let argument_count = lhs.len();
+    argument_count == rhs.len()
+        && lhs.iter().zip(rhs).enumerate().all(|(index, (lhs, rhs))| {
+            let (lhs, rhs) = (dfg.resolve(*lhs), dfg.resolve(*rhs));
+            if lhs == rhs {
+                return true;
+            }
+            if requires_constant_argument(func, index, argument_count) {
+                return false;
+            }
+            let typ = dfg.type_of_value(lhs);
+            typ == dfg.type_of_value(rhs) && matches!(typ, Type::Numeric(_) | Type::Array(..))
+        })
+}
+
+/// Returns true if `value` is, or is an array containing, one of `results`.
+fn value_uses_any(dfg: &DataFlowGraph, value: ValueId, results: &[ValueId]) -> bool {
+    let value = dfg.resolve(value);
+    match &dfg[value] {
+        Value::Array { array, .. } => {
+            array.iter().any(|element| value_uses_any(dfg, *element, results))
+        }
+        _ => results.contains(&value),
+    }
+}
+
+/// Two side-effects conditions are mutually exclusive if one implies some value is true
+/// while the other implies it is false.
+fn conditions_are_exclusive(dfg: &DataFlowGraph, lhs: ValueId, rhs: ValueId) -> bool {
+    let rhs = condition_literals(dfg, rhs);
+    condition_literals(dfg, lhs)
+        .into_iter()
+        .any(|(value, negated)| rhs.contains(&(value, !negated)))
+}
+
+/// Decomposes a condition built by [`flatten_cfg`][super::flatten_cfg] out of `and` and `not`
+/// instructions into the literals it implies. A literal `(v, false)` states that `v` is true
+/// whenever the condition holds, and `(v, true)` that `v` is false.
+fn condition_literals(dfg: &DataFlowGraph, condition: ValueId) -> Vec<(ValueId, bool)> {
+    let mut literals = Vec::new();
+    let mut queue = vec![dfg.resolve(condition)];
+
+    while let Some(value) = queue.pop() {
+        if let Value::Instruction { instruction, .. } = &dfg[value] {
+            match &dfg[*instruction] {
+                Instruction::Binary(Binary { lhs, operator: BinaryOp::And, rhs }) => {
+                    queue.push(dfg.resolve(*lhs));
+                    queue.push(dfg.resolve(*rhs));
+                    continue;
+                }
+                Instruction::Not(negated) => {
+                    literals.push((dfg.resolve(*negated), true));
+                    continue;
+                }
+                _ => (),
+            }
+        }
+        literals.push((value, false));
+    }
+    literals
+}
+
+/// Replaces the `first` and `second` calls with a single call over inputs selected by the
+/// side-effects condition of the first call, made under the condition of either call.
+fn hoist_pair(
+    dfg: &mut DataFlowGraph,
+    block: BasicBlockId,
+    first: InstructionId,
+    second: InstructionId,
+    (condition, second_condition): (ValueId, ValueId),
+) {
+    let (
+        Instruction::Call { func, arguments: then_arguments },
+        Instruction::Call { arguments: else_arguments, .. },
+    ) = (dfg[first].clone(), dfg[second].clone())
+    else {
+        unreachable!("ICE: Expected gadget instructions to be calls");
+    };
+    let then_arguments = vecmap(then_arguments, |argument| dfg.resolve(argument));
+    let else_arguments = vecmap(else_arguments, |argument| dfg.resolve(argument));
+
+    let call_stack = dfg.get_call_stack(second);
+    let else_condition = dfg
+        .insert_instruction_and_results(
+            Instruction::Not(condition),
+            block,
+            None,
+            call_stack.clone(),
+        )
+        .first();
+
+    let mut value_merger = ValueMerger::new(dfg, block, None, None);
+    let arguments =
+        vecmap(then_arguments.into_iter().zip(else_arguments), |(then_arg, else_arg)| {
+            if then_arg == else_arg {
+                then_arg
+            } else {
+                value_merger.merge_values(condition, else_condition, then_arg, else_arg)
+            }
+        });
+
+    let first_results = dfg.instruction_results(first).to_vec();
+    let second_results = dfg.instruction_results(second).to_vec();
+    let result_types = vecmap(&second_results, |result| dfg.type_of_value(*result));
+
+    let either_condition = Instruction::Binary(Binary {
+        lhs: condition,
+        operator: BinaryOp::Or,
+        rhs: second_condition,
+    });
+    let either_condition = dfg
+        .insert_instruction_and_results(either_condition, block, None, call_stack.clone())
+        .first();
+    let enable_side_effects = Instruction::EnableSideEffects { condition: either_condition };
+    dfg.insert_instruction_and_results(enable_side_effects, block, None, call_stack.clone());
+
+    let call = Instruction::Call { func, arguments };
+    let new_results = dfg
+        .insert_instruction_and_results(call, block, Some(result_types), call_stack.clone())
+        .results()
+        .to_vec();
+    assert_eq!(new_results.len(), second_results.len());
+
+    let enable_side_effects = Instruction::EnableSideEffects { condition: second_condition };
+    dfg.insert_instruction_and_results(enable_side_effects, block, None, call_stack);
+
+    for ((first_result, second_result), new_result) in
+        first_results.into_iter().zip(second_results).zip(new_results)
+    {
+        dfg.set_value_from_id(first_result, new_result);
+        dfg.set_value_from_id(second_result, new_result);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use acvm::acir::BlackBoxFunc;
+
+    use crate::ssa::{
+        function_builder::FunctionBuilder,
+        ir::{
+            function::RuntimeType,
+            instruction::{Binary, BinaryOp, Instruction, Intrinsic},
+            map::Id,
+            types::Type,
+            value::Value,
+        },
+        ssa_gen::Ssa,
+    };
+
+    fn count_gadget_calls(ssa: &Ssa) -> usize {
+        let main = ssa.main();
+        main.dfg[main.entry_block()]
+            .instructions()
+            .iter()
+            .filter(|instruction| match &main.dfg[**instruction] {
+                Instruction::Call { func, .. } => {
+                    matches!(main.dfg[*func], Value::Intrinsic(Intrinsic::BlackBox(_)))
+                }
+                _ => false,
+            })
+            .count()
+    }
+
+    /// Builds:
+    /// ```text
+    /// fn main f0 {
+    ///   b0(v0: u1, v1: [u8; 2], v2: [u8; 2]):
+    ///     jmpif v0, then: b1, else: b2
+    ///   b1():
+    ///     v3 = call sha256(v1)
+    ///     jmp b3(v3)
+    ///   b2():
+    ///     v4 = call sha256(v2)
+    ///     jmp b3(v4)
+    ///   b3(v5: [u8; 32]):
+    ///     return v5
+    /// }
+    /// ```
+    fn sha256_in_both_branches() -> Ssa {
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+
+        let b1 = builder.insert_block();
+        let b2 = builder.insert_block();
+        let b3 = builder.insert_block();
+
+        let input_type = Type::Array(Rc::new(vec![Type::unsigned(8)]), 2);
+        let output_type = Type::Array(Rc::new(vec![Type::unsigned(8)]), 32);
+
+        let v0 = builder.add_parameter(Type::bool());
+        let v1 = builder.add_parameter(input_type.clone());
+        let v2 = builder.add_parameter(input_type);
+        let v5 = builder.add_block_parameter(b3, output_type.clone());
+
+        let sha256 = builder.import_intrinsic_id(Intrinsic::BlackBox(BlackBoxFunc::SHA256));
+        builder.terminate_with_jmpif(v0, b1, b2);
+
+        builder.switch_to_block(b1);
+        let v3 = builder.insert_call(sha256, vec![v1], vec![output_type.clone()])[0];
+        builder.terminate_with_jmp(b3, vec![v3]);
+
+        builder.switch_to_block(b2);
+        let v4 = builder.insert_call(sha256, vec![v2], vec![output_type])[0];
+        builder.terminate_with_jmp(b3, vec![v4]);
+
+        builder.switch_to_block(b3);
+        builder.terminate_with_return(vec![v5]);
+
+        builder.finish()
+    }
+
+    #[test]
+    fn merges_calls_from_both_branches() {
+        let ssa = sha256_in_both_branches().flatten_cfg();
+        assert_eq!(count_gadget_calls(&ssa), 2);
+
+        let ssa = ssa.hoist_predicated_gadgets();
+        assert_eq!(count_gadget_calls(&ssa), 1);
+
+        // The results of the removed call must now refer to the remaining one.
+        let ssa = ssa.fold_constants().dead_instruction_elimination();
+        assert_eq!(count_gadget_calls(&ssa), 1);
+    }
+
+    #[test]
+    fn merged_call_is_made_under_either_condition() {
+        let ssa = sha256_in_both_branches().flatten_cfg().hoist_predicated_gadgets();
+
+        let main = ssa.main();
+        let instructions = main.dfg[main.entry_block()].instructions();
+        let call = instructions
+            .iter()
+            .position(|instruction| matches!(main.dfg[*instruction], Instruction::Call { .. }))
+            .unwrap();
+        let Instruction::EnableSideEffects { condition } = main.dfg[instructions[call - 1]] else {
+            panic!("expected the side-effects condition to be set before the merged call");
+        };
+        let Value::Instruction { instruction, .. } = main.dfg[condition] else {
+            panic!("expected the condition to be computed");
+        };
+        assert!(matches!(
+            main.dfg[instruction],
+            Instruction::Binary(Binary { operator: BinaryOp::Or, .. })
+        ));
+        assert!(matches!(main.dfg[instructions[call + 1]], Instruction::EnableSideEffects { .. }));
+    }
+
+    #[test]
+    fn does_not_merge_calls_whose_results_are_used_in_their_branch() {
+        // fn main f0 {
+        //   b0(v0: u1, v1: Field, v2: Field):
+        //     jmpif v0, then: b1, else: b2
+        //   b1():
+        //     v3 = call pedersen_hash([v1], u32 0)
+        //     constrain v3 == Field 0
+        //     jmp b3(v3)
+        //   b2():
+        //     v4 = call pedersen_hash([v2], u32 0)
+        //     jmp b3(v4)
+        //   b3(v5: Field):
+        //     return v5
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+
+        let b1 = builder.insert_block();
+        let b2 = builder.insert_block();
+        let b3 = builder.insert_block();
+
+        let v0 = builder.add_parameter(Type::bool());
+        let v1 = builder.add_parameter(Type::field());
+        let v2 = builder.add_parameter(Type::field());
+        let v5 = builder.add_block_parameter(b3, Type::field());
+
+        let pedersen = builder.import_intrinsic_id(Intrinsic::BlackBox(BlackBoxFunc::PedersenHash));
+        let separator = builder.numeric_constant(0u128, Type::unsigned(32));
+        let zero = builder.field_constant(0u128);
+        let array_type = Type::Array(Rc::new(vec![Type::field()]), 1);
+        builder.terminate_with_jmpif(v0, b1, b2);
+
+        builder.switch_to_block(b1);
+        let input = builder.array_constant(im::vector![v1], array_type.clone());
+        let v3 = builder.insert_call(pedersen, vec![input, separator], vec![Type::field()])[0];
+        builder.insert_constrain(v3, zero, None);
+        builder.terminate_with_jmp(b3, vec![v3]);
+
+        builder.switch_to_block(b2);
+        let input = builder.array_constant(im::vector![v2], array_type);
+        let v4 = builder.insert_call(pedersen, vec![input, separator], vec![Type::field()])[0];
+        builder.terminate_with_jmp(b3, vec![v4]);
+
+        builder.switch_to_block(b3);
+        builder.terminate_with_return(vec![v5]);
+
+        let ssa = builder.finish().flatten_cfg().hoist_predicated_gadgets();
+        assert_eq!(count_gadget_calls(&ssa), 2);
+    }
+}
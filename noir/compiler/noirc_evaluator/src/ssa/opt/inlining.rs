@@ -2,17 +2,18 @@
 //! The purpose of this pass is to inline the instructions of each function call
 //! within the function caller. If all function calls are known, there will only
 //! be a single function remaining when the pass finishes.
-use std::collections::{BTreeSet, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 
-use iter_extended::{btree_map, vecmap};
+use acvm::acir::BlackBoxFunc;
+use iter_extended::vecmap;
 
 use crate::ssa::{
     function_builder::FunctionBuilder,
     ir::{
         basic_block::BasicBlockId,
         dfg::{CallStack, InsertInstructionResult},
-        function::{Function, FunctionId, RuntimeType},
-        instruction::{Instruction, InstructionId, TerminatorInstruction},
+        function::{Function, FunctionId, InlineType, RuntimeType},
+        instruction::{Instruction, InstructionId, Intrinsic, TerminatorInstruction},
         value::{Value, ValueId},
     },
     ssa_gen::Ssa,
@@ -23,6 +24,10 @@ use fxhash::FxHashMap as HashMap;
 /// frames at any point in time.
 const RECURSION_LIMIT: u32 = 1000;
 
+/// An estimate of the number of Brillig opcodes a call takes besides moving its arguments and
+/// results: saving and restoring the registers of the caller, the call itself and the return.
+const BRILLIG_CALL_OVERHEAD: usize = 4;
+
 impl Ssa {
     /// Inline all functions within the IR.
     ///
@@ -36,13 +41,69 @@ impl Ssa {
     /// changes. This is because if the function's id later becomes known by a later
     /// pass, we would need to re-run all of inlining anyway to inline it, so we might
     /// as well save the work for later instead of performing it twice.
+    ///
+    /// Commitments made by constrained functions marked `#[precompute_commitments]`, including
+    /// through the functions they call, are replaced by calls to
+    /// [`Intrinsic::PrecomputedBlackBox`] so that they can still be told apart once inlined.
+    ///
+    /// Calls to functions marked `#[no_inline]` are kept as well. Each such function becomes an
+    /// entry point of its own, which is compiled into a separate circuit for its constrained
+    /// callers and once as a shared Brillig function for its unconstrained callers. A function
+    /// called from both is compiled twice, the second time under a new id.
+    ///
+    /// Unconstrained functions are inlined into their unconstrained callers if they are marked
+    /// `#[inline_always]`, or are small enough that inlining them does not grow the program by
+    /// more than the overhead of the calls this removes. Other calls to them are kept.
+    ///
+    /// Likewise, calls from constrained code to functions marked `#[fold]` are kept, and each such
+    /// function becomes an entry point of its own which is compiled into a separate circuit.
+    /// Unconstrained callers inline these functions.
     #[tracing::instrument(level = "trace", skip(self))]
     pub(crate) fn inline_functions(mut self) -> Ssa {
-        self.functions = btree_map(get_entry_point_functions(&self), |entry_point| {
-            let new_function = InlineContext::new(&self, entry_point).inline_all(&self);
-            (entry_point, new_function)
-        });
+        let mut entry_points: Vec<_> = get_entry_point_functions(&self)
+            .into_iter()
+            .map(|entry_point| (entry_point, self.functions[&entry_point].runtime()))
+            .collect();
+
+        // The id of the function compiled from each entry point for each runtime.
+        let mut compiled_ids: HashMap<(FunctionId, RuntimeType), FunctionId> =
+            entry_points.iter().map(|entry_point| (*entry_point, entry_point.0)).collect();
+
+        let brillig_functions_to_inline = brillig_functions_to_inline(&self);
+        let mut functions = BTreeMap::new();
+        while let Some((entry_point, runtime)) = entry_points.pop() {
+            let id = compiled_ids[&(entry_point, runtime)];
+            if functions.contains_key(&id) {
+                continue;
+            }
+            let mut context = InlineContext::new(&self, entry_point, id, runtime);
+            context.brillig_functions_to_inline = brillig_functions_to_inline.clone();
+            let (mut new_function, kept_callees) = context.inline_all(&self);
+
+            // Kept calls are to functions compiled with the runtime of their caller, as `#[fold]`
+            // functions are only kept in constrained code.
+            for callee in kept_callees {
+                let callee_id = match compiled_ids.get(&(callee, runtime)).copied() {
+                    Some(callee_id) => callee_id,
+                    None if compiled_ids.keys().any(|(source, _)| *source == callee) => {
+                        let callee_id = self.next_id.next();
+                        compiled_ids.insert((callee, runtime), callee_id);
+                        callee_id
+                    }
+                    None => {
+                        compiled_ids.insert((callee, runtime), callee);
+                        callee
+                    }
+                };
+                if callee_id != callee {
+                    redirect_calls(&mut new_function, callee, callee_id);
+                }
+                entry_points.push((callee, runtime));
+            }
+            functions.insert(id, new_function);
+        }
 
+        self.functions = functions;
         self
     }
 }
@@ -60,6 +121,20 @@ struct InlineContext {
 
     // The FunctionId of the entry point function we're inlining into in the old, unmodified Ssa.
     entry_point: FunctionId,
+
+    // Functions marked `#[no_inline]` or `#[fold]` whose calls were kept rather than inlined.
+    kept_callees: BTreeSet<FunctionId>,
+
+    // Whether the function being inlined is, or is called from, a constrained function marked
+    // `#[precompute_commitments]`.
+    precompute_commitments: bool,
+
+    // Unconstrained functions which are inlined into their unconstrained callers.
+    brillig_functions_to_inline: BTreeSet<FunctionId>,
+
+    // The functions being inlined, innermost last, so that recursive calls to unconstrained
+    // functions are kept rather than inlined forever.
+    inlining: Vec<FunctionId>,
 }
 
 /// The per-function inlining context contains information that is only valid for one function.
@@ -93,6 +168,20 @@ struct PerFunctionContext<'function> {
     inlining_entry: bool,
 }
 
+/// Makes the calls of `function` to `from` call `to` instead.
+fn redirect_calls(function: &mut Function, from: FunctionId, to: FunctionId) {
+    let callees: Vec<_> = function
+        .dfg
+        .values_iter()
+        .filter(|(_, value)| matches!(value, Value::Function(id) if *id == from))
+        .map(|(value, _)| value)
+        .collect();
+    let new_callee = function.dfg.import_function(to);
+    for callee in callees {
+        function.dfg.set_value_from_id(callee, new_callee);
+    }
+}
+
 /// The entry point functions are each function we should inline into - and each function that
 /// should be left in the final program. This is usually just `main` but also includes any
 /// brillig functions used.
@@ -107,20 +196,86 @@ fn get_entry_point_functions(ssa: &Ssa) -> BTreeSet<FunctionId> {
     entry_points
 }
 
+/// Returns the unconstrained functions which are inlined into their unconstrained callers: those
+/// marked `#[inline_always]`, and those not marked `#[no_inline]` which are small enough that
+/// inlining them at each of their call sites grows the program by no more than the overhead of the
+/// calls this removes.
+fn brillig_functions_to_inline(ssa: &Ssa) -> BTreeSet<FunctionId> {
+    let mut call_sites: HashMap<FunctionId, usize> = HashMap::default();
+    for function in ssa.functions.values() {
+        for block in function.reachable_blocks() {
+            for instruction in function.dfg[block].instructions() {
+                if let Instruction::Call { func, .. } = &function.dfg[*instruction] {
+                    if let Value::Function(callee) = function.dfg[*func] {
+                        *call_sites.entry(callee).or_default() += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    let functions = ssa.functions.iter();
+    let brillig_functions = functions.filter(|(_, function)| {
+        function.runtime() == RuntimeType::Brillig && function.id() != ssa.main_id
+    });
+    brillig_functions
+        .filter(|(id, function)| match function.inline_type() {
+            InlineType::InlineAlways => true,
+            InlineType::NoInline | InlineType::Fold => false,
+            InlineType::Inline => {
+                let call_sites = call_sites.get(id).copied().unwrap_or_default();
+                let size: usize = function
+                    .reachable_blocks()
+                    .iter()
+                    .map(|block| function.dfg[*block].instructions().len() + 1)
+                    .sum();
+                let call_overhead =
+                    BRILLIG_CALL_OVERHEAD + function.parameters().len() + function.returns().len();
+                size * call_sites.saturating_sub(1) <= call_overhead * call_sites
+            }
+        })
+        .map(|(id, _)| *id)
+        .collect()
+}
+
 impl InlineContext {
     /// Create a new context object for the function inlining pass.
     /// This starts off with an empty mapping of instructions for main's parameters.
     /// The function being inlined into will always be the main function, although it is
     /// actually a copy that is created in case the original main is still needed from a function
     /// that could not be inlined calling it.
-    fn new(ssa: &Ssa, entry_point: FunctionId) -> InlineContext {
+    ///
+    /// The new function is built under the given `id` and with the given `runtime`, which may
+    /// differ from the source function's for `#[no_inline]` functions compiled as shared
+    /// Brillig functions.
+    fn new(
+        ssa: &Ssa,
+        entry_point: FunctionId,
+        id: FunctionId,
+        runtime: RuntimeType,
+    ) -> InlineContext {
         let source = &ssa.functions[&entry_point];
-        let builder = FunctionBuilder::new(source.name().to_owned(), entry_point, source.runtime());
-        Self { builder, recursion_level: 0, entry_point, call_stack: CallStack::new() }
+        let mut builder = FunctionBuilder::new(source.name().to_owned(), id, runtime);
+        builder.set_inline_type(source.inline_type());
+        let precompute_commitments =
+            runtime == RuntimeType::Acir && source.precompute_commitments();
+        Self {
+            builder,
+            recursion_level: 0,
+            entry_point,
+            call_stack: CallStack::new(),
+            kept_callees: BTreeSet::new(),
+            precompute_commitments,
+            brillig_functions_to_inline: BTreeSet::new(),
+            inlining: Vec::new(),
+        }
     }
 
     /// Start inlining the entry point function and all functions reachable from it.
-    fn inline_all(mut self, ssa: &Ssa) -> Function {
+    ///
+    /// Returns the new function along with the `#[no_inline]` and `#[fold]` functions it still
+    /// calls.
+    fn inline_all(mut self, ssa: &Ssa) -> (Function, BTreeSet<FunctionId>) {
         let entry_point = &ssa.functions[&self.entry_point];
 
         let mut context = PerFunctionContext::new(&mut self, entry_point);
@@ -137,6 +292,7 @@ impl InlineContext {
             let new_parameter = context.context.builder.add_block_parameter(entry_block, typ);
             context.values.insert(*parameter, new_parameter);
         }
+        context.copy_value_names(original_parameters);
 
         context.blocks.insert(context.source_function.entry_block(), entry_block);
         context.inline_blocks(ssa);
@@ -148,7 +304,7 @@ impl InlineContext {
         assert_eq!(new_ssa.functions.len(), 1);
         let mut new_func = new_ssa.functions.pop_first().unwrap().1;
         new_func.dfg.data_bus = databus;
-        new_func
+        (new_func, self.kept_callees)
     }
 
     /// Inlines a function into the current function and returns the translated return values
@@ -168,19 +324,39 @@ impl InlineContext {
         }
 
         let source_function = &ssa.functions[&id];
+        let precompute_commitments = self.precompute_commitments;
+        if self.builder.current_function.runtime() == RuntimeType::Acir {
+            self.precompute_commitments |= source_function.precompute_commitments();
+        }
+        self.inlining.push(id);
         let mut context = PerFunctionContext::new(self, source_function);
 
         let parameters = source_function.parameters();
         assert_eq!(parameters.len(), arguments.len());
         context.values = parameters.iter().copied().zip(arguments.iter().copied()).collect();
+        context.copy_value_names(parameters);
 
         let current_block = context.context.builder.current_block();
         context.blocks.insert(source_function.entry_block(), current_block);
 
         let return_values = context.inline_blocks(ssa);
+        self.inlining.pop();
         self.recursion_level -= 1;
+        self.precompute_commitments = precompute_commitments;
         return_values
     }
+
+    /// Imports `intrinsic` into the function being built, marking commitments to be computed at
+    /// compile time if requested by the function being inlined or one of its callers.
+    fn import_intrinsic(&mut self, intrinsic: Intrinsic) -> ValueId {
+        let intrinsic = match intrinsic {
+            Intrinsic::BlackBox(
+                func @ (BlackBoxFunc::PedersenCommitment | BlackBoxFunc::PedersenHash),
+            ) if self.precompute_commitments => Intrinsic::PrecomputedBlackBox(func),
+            intrinsic => intrinsic,
+        };
+        self.builder.import_intrinsic_id(intrinsic)
+    }
 }
 
 impl<'function> PerFunctionContext<'function> {
@@ -219,7 +395,7 @@ impl<'function> PerFunctionContext<'function> {
                 self.context.builder.numeric_constant(*constant, typ.clone())
             }
             Value::Function(function) => self.context.builder.import_function(*function),
-            Value::Intrinsic(intrinsic) => self.context.builder.import_intrinsic_id(*intrinsic),
+            Value::Intrinsic(intrinsic) => self.context.import_intrinsic(*intrinsic),
             Value::ForeignFunction(function) => {
                 self.context.builder.import_foreign_function(function)
             }
@@ -261,6 +437,7 @@ impl<'function> PerFunctionContext<'function> {
             let new_parameter = self.context.builder.add_block_parameter(new_block, typ);
             self.values.insert(*parameter, new_parameter);
         }
+        self.copy_value_names(original_parameters);
 
         self.blocks.insert(source_block, new_block);
         new_block
@@ -350,7 +527,14 @@ impl<'function> PerFunctionContext<'function> {
             match &self.source_function.dfg[*id] {
                 Instruction::Call { func, arguments } => match self.get_function(*func) {
                     Some(function) => match ssa.functions[&function].runtime() {
+                        RuntimeType::Acir if self.keeps_call_to(&ssa.functions[&function]) => {
+                            self.context.kept_callees.insert(function);
+                            self.push_instruction(*id);
+                        }
                         RuntimeType::Acir => self.inline_function(ssa, *id, function, arguments),
+                        RuntimeType::Brillig if self.inlines_brillig_call_to(function) => {
+                            self.inline_function(ssa, *id, function, arguments);
+                        }
                         RuntimeType::Brillig => self.push_instruction(*id),
                     },
                     None => self.push_instruction(*id),
@@ -360,6 +544,29 @@ impl<'function> PerFunctionContext<'function> {
         }
     }
 
+    /// Returns true if a call to the constrained function `callee` should be kept, which is the
+    /// case for all calls to `#[no_inline]` functions and for calls from constrained code to
+    /// `#[fold]` functions.
+    fn keeps_call_to(&self, callee: &Function) -> bool {
+        let caller_runtime = self.context.builder.current_function.runtime();
+        match callee.inline_type() {
+            InlineType::NoInline => true,
+            InlineType::Fold => caller_runtime == RuntimeType::Acir,
+            InlineType::Inline | InlineType::InlineAlways => false,
+        }
+    }
+
+    /// Returns true if a call to the unconstrained function `callee` should be inlined, which is
+    /// only the case for calls from unconstrained code to functions chosen to be inlined, unless
+    /// the call is recursive.
+    fn inlines_brillig_call_to(&self, callee: FunctionId) -> bool {
+        let context = &self.context;
+        context.builder.current_function.runtime() == RuntimeType::Brillig
+            && context.brillig_functions_to_inline.contains(&callee)
+            && callee != context.entry_point
+            && !context.inlining.contains(&callee)
+    }
+
     /// Inline a function call and remember the inlined return values in the values map
     fn inline_function(
         &mut self,
@@ -387,6 +594,7 @@ impl<'function> PerFunctionContext<'function> {
 
         let new_results = InsertInstructionResult::Results(call_id, &new_results);
         Self::insert_new_instruction_results(&mut self.values, old_results, new_results);
+        self.copy_value_names(old_results);
     }
 
     /// Push the given instruction from the source_function into the current block of the
@@ -408,6 +616,18 @@ impl<'function> PerFunctionContext<'function> {
 
         let new_results = self.context.builder.insert_instruction(instruction, ctrl_typevars);
         Self::insert_new_instruction_results(&mut self.values, &results, new_results);
+        self.copy_value_names(&results);
+    }
+
+    /// Names the values which `source_values` were translated to after the variables which
+    /// `source_values` were bound to, so that the names survive inlining.
+    fn copy_value_names(&mut self, source_values: &[ValueId]) {
+        for source_value in source_values {
+            let name = self.source_function.dfg.value_name(*source_value);
+            if let (Some(name), Some(value)) = (name, self.values.get(source_value)) {
+                self.context.builder.set_value_name(*value, name.to_string());
+            }
+        }
     }
 
     /// Modify the values HashMap to remember the mapping between an instruction result's previous
@@ -514,10 +734,11 @@ mod test {
         function_builder::FunctionBuilder,
         ir::{
             basic_block::BasicBlockId,
-            function::RuntimeType,
-            instruction::{BinaryOp, Intrinsic, TerminatorInstruction},
+            function::{InlineType, RuntimeType},
+            instruction::{BinaryOp, Instruction, Intrinsic, TerminatorInstruction},
             map::Id,
             types::Type,
+            value::Value,
         },
     };
 
@@ -787,4 +1008,248 @@ mod test {
         let main = ssa.main();
         assert_eq!(main.reachable_blocks().len(), 4);
     }
+
+    #[test]
+    fn compiles_no_inline_functions_once_for_each_runtime() {
+        // fn main f0 {
+        //   b0(v0: Field):
+        //     v1 = call f1(v0)
+        //     v2 = call f2(v1)
+        //     return v2
+        // }
+        // brillig fn foo f1 {
+        //   b0(v0: Field):
+        //     v1 = call f2(v0)
+        //     return v1
+        // }
+        // #[no_inline]
+        // fn double f2 {
+        //   b0(v0: Field):
+        //     v1 = add v0, v0
+        //     return v1
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let main_v0 = builder.add_parameter(Type::field());
+        let foo_id = Id::test_new(1);
+        let double_id = Id::test_new(2);
+        let foo = builder.import_function(foo_id);
+        let double = builder.import_function(double_id);
+        let main_v1 = builder.insert_call(foo, vec![main_v0], vec![Type::field()])[0];
+        let main_v2 = builder.insert_call(double, vec![main_v1], vec![Type::field()])[0];
+        builder.terminate_with_return(vec![main_v2]);
+
+        builder.new_brillig_function("foo".into(), foo_id);
+        let foo_v0 = builder.add_parameter(Type::field());
+        let double = builder.import_function(double_id);
+        let foo_v1 = builder.insert_call(double, vec![foo_v0], vec![Type::field()])[0];
+        builder.terminate_with_return(vec![foo_v1]);
+
+        builder.new_function("double".into(), double_id);
+        builder.set_inline_type(InlineType::NoInline);
+        let double_v0 = builder.add_parameter(Type::field());
+        let double_v1 = builder.insert_binary(double_v0, BinaryOp::Add, double_v0);
+        builder.terminate_with_return(vec![double_v1]);
+
+        let ssa = builder.finish().inline_functions();
+
+        // `double` is kept as a shared Brillig function for `foo`, which is processed first, and as
+        // a separate circuit under a new id for main.
+        assert_eq!(ssa.functions.len(), 4);
+        let double = &ssa.functions[&double_id];
+        assert_eq!(double.runtime(), RuntimeType::Brillig);
+        assert_eq!(double.inline_type(), InlineType::NoInline);
+
+        let acir_double_id = Id::test_new(3);
+        let acir_double = &ssa.functions[&acir_double_id];
+        assert_eq!(acir_double.runtime(), RuntimeType::Acir);
+        assert_eq!(acir_double.inline_type(), InlineType::NoInline);
+
+        let main = ssa.main();
+        let callees: Vec<_> = main.dfg[main.entry_block()]
+            .instructions()
+            .iter()
+            .map(|id| match main.dfg[*id] {
+                Instruction::Call { func, .. } => main.dfg[func].clone(),
+                ref instruction => panic!("expected only calls in main, got {instruction:?}"),
+            })
+            .collect();
+        assert!(matches!(
+            callees.as_slice(),
+            [Value::Function(first), Value::Function(second)]
+                if *first == foo_id && *second == acir_double_id
+        ));
+
+        let foo = &ssa.functions[&foo_id];
+        let foo_instructions = foo.dfg[foo.entry_block()].instructions();
+        assert_eq!(foo_instructions.len(), 1);
+        assert!(matches!(foo.dfg[foo_instructions[0]], Instruction::Call { .. }));
+    }
+
+    #[test]
+    fn inlines_small_unconstrained_functions_into_unconstrained_callers() {
+        // fn main f0 {
+        //   b0(v0: Field):
+        //     v1 = call f1(v0)
+        //     return v1
+        // }
+        // brillig fn foo f1 {
+        //   b0(v0: Field):
+        //     v1 = call f2(v0)
+        //     v2 = call f2(v1)
+        //     v3 = call f3(v2)
+        //     v4 = call f3(v3)
+        //     v5 = call f4(v4)
+        //     v6 = call f4(v5)
+        //     return v6
+        // }
+        // brillig fn double f2 {
+        //   b0(v0: Field):
+        //     v1 = add v0, v0
+        //     return v1
+        // }
+        // brillig fn big f3 {
+        //   b0(v0: Field):
+        //     v1 = mul v0, v0
+        //     ... 16 multiplications in total
+        //     return v16
+        // }
+        // #[inline_always]
+        // brillig fn big_always f4 { ... same as big }
+        let main_id = Id::test_new(0);
+        let foo_id = Id::test_new(1);
+        let double_id = Id::test_new(2);
+        let big_id = Id::test_new(3);
+        let big_always_id = Id::test_new(4);
+
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let main_v0 = builder.add_parameter(Type::field());
+        let foo = builder.import_function(foo_id);
+        let main_v1 = builder.insert_call(foo, vec![main_v0], vec![Type::field()])[0];
+        builder.terminate_with_return(vec![main_v1]);
+
+        builder.new_brillig_function("foo".into(), foo_id);
+        let mut value = builder.add_parameter(Type::field());
+        for callee in [double_id, double_id, big_id, big_id, big_always_id, big_always_id] {
+            let callee = builder.import_function(callee);
+            value = builder.insert_call(callee, vec![value], vec![Type::field()])[0];
+        }
+        builder.terminate_with_return(vec![value]);
+
+        builder.new_brillig_function("double".into(), double_id);
+        let double_v0 = builder.add_parameter(Type::field());
+        let double_v1 = builder.insert_binary(double_v0, BinaryOp::Add, double_v0);
+        builder.terminate_with_return(vec![double_v1]);
+
+        for (name, id) in [("big", big_id), ("big_always", big_always_id)] {
+            builder.new_brillig_function(name.into(), id);
+            if id == big_always_id {
+                builder.set_inline_type(InlineType::InlineAlways);
+            }
+            let mut value = builder.add_parameter(Type::field());
+            for _ in 0..16 {
+                value = builder.insert_binary(value, BinaryOp::Mul, value);
+            }
+            builder.terminate_with_return(vec![value]);
+        }
+
+        let ssa = builder.finish().inline_functions();
+
+        // Only the calls to `big` are kept, which would grow `foo` by more than they cost.
+        let foo = &ssa.functions[&foo_id];
+        let callees: Vec<_> = foo.dfg[foo.entry_block()]
+            .instructions()
+            .iter()
+            .filter_map(|id| match &foo.dfg[*id] {
+                Instruction::Call { func, .. } => Some(foo.dfg[*func].clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(callees, vec![Value::Function(big_id), Value::Function(big_id)]);
+
+        // Calls from constrained code are always kept.
+        let main = ssa.main();
+        let main_instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(main_instructions.len(), 1);
+    }
+
+    #[test]
+    fn keeps_no_inline_calls_from_constrained_code() {
+        // fn main f0 {
+        //   b0(v0: Field):
+        //     v1 = call f1(v0)
+        //     return v1
+        // }
+        // #[no_inline]
+        // fn double f1 {
+        //   b0(v0: Field):
+        //     v1 = add v0, v0
+        //     return v1
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let main_v0 = builder.add_parameter(Type::field());
+        let double_id = Id::test_new(1);
+        let double = builder.import_function(double_id);
+        let main_v1 = builder.insert_call(double, vec![main_v0], vec![Type::field()])[0];
+        builder.terminate_with_return(vec![main_v1]);
+
+        builder.new_function("double".into(), double_id);
+        builder.set_inline_type(InlineType::NoInline);
+        let double_v0 = builder.add_parameter(Type::field());
+        let double_v1 = builder.insert_binary(double_v0, BinaryOp::Add, double_v0);
+        builder.terminate_with_return(vec![double_v1]);
+
+        let ssa = builder.finish().inline_functions();
+
+        assert_eq!(ssa.functions.len(), 2);
+        let main = ssa.main();
+        let main_instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(main_instructions.len(), 1);
+        assert!(matches!(main.dfg[main_instructions[0]], Instruction::Call { .. }));
+
+        let double = &ssa.functions[&double_id];
+        assert_eq!(double.runtime(), RuntimeType::Acir);
+        assert_eq!(double.inline_type(), InlineType::NoInline);
+    }
+
+    #[test]
+    fn keeps_fold_calls_from_constrained_code() {
+        // fn main f0 {
+        //   b0(v0: Field):
+        //     v1 = call f1(v0)
+        //     return v1
+        // }
+        // #[fold]
+        // fn double f1 {
+        //   b0(v0: Field):
+        //     v1 = add v0, v0
+        //     return v1
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let main_v0 = builder.add_parameter(Type::field());
+        let double_id = Id::test_new(1);
+        let double = builder.import_function(double_id);
+        let main_v1 = builder.insert_call(double, vec![main_v0], vec![Type::field()])[0];
+        builder.terminate_with_return(vec![main_v1]);
+
+        builder.new_function("double".into(), double_id);
+        builder.set_inline_type(InlineType::Fold);
+        let double_v0 = builder.add_parameter(Type::field());
+        let double_v1 = builder.insert_binary(double_v0, BinaryOp::Add, double_v0);
+        builder.terminate_with_return(vec![double_v1]);
+
+        let ssa = builder.finish().inline_functions();
+
+        assert_eq!(ssa.functions.len(), 2);
+        let main = ssa.main();
+        let main_instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(main_instructions.len(), 1);
+        assert!(matches!(main.dfg[main_instructions[0]], Instruction::Call { .. }));
+
+        let double = &ssa.functions[&double_id];
+        assert_eq!(double.runtime(), RuntimeType::Acir);
+        assert_eq!(double.inline_type(), InlineType::Fold);
+    }
 }
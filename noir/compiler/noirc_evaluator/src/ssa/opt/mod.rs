@@ -10,7 +10,11 @@ mod constant_folding;
 mod defunctionalize;
 mod die;
 pub(crate) mod flatten_cfg;
+mod hoist_gadgets;
 mod inlining;
 mod mem2reg;
+mod precompute_commitments;
 mod simplify_cfg;
+mod sink_truncations;
+mod split_struct_arrays;
 mod unrolling;
@@ -0,0 +1,330 @@
+//! This pass computes at compile time the commitments made by functions marked
+//! `#[precompute_commitments]`, so that only their results are embedded in the circuit.
+//!
+//! Protocol circuits often commit to large constant structures, such as trees of verification
+//! keys, whose commitments would otherwise be recomputed by every proof. Inlining marks the
+//! commitments made by these functions, including through the functions they call, with
+//! [`Intrinsic::PrecomputedBlackBox`]. Once loops are unrolled and constants are folded, each
+//! such call must only have constant arguments and is replaced by its results:
+//!
+//! ```text
+//! v1 = call precomputed_pedersen_hash([Field 1, Field 2], u32 0)
+//! ```
+//!
+//! becomes the constant hash of `[1, 2]`. A call whose arguments are not all constants is an
+//! error, as the author asked for the commitment not to be part of the circuit.
+use acvm::{acir::BlackBoxFunc, BlackBoxFunctionSolver, FieldElement};
+use iter_extended::vecmap;
+
+use crate::{
+    errors::RuntimeError,
+    ssa::{
+        ir::{
+            function::Function,
+            instruction::{Instruction, InstructionId, Intrinsic},
+            types::Type,
+            value::{Value, ValueId},
+        },
+        ssa_gen::Ssa,
+    },
+};
+
+impl Ssa {
+    /// Replaces the commitments marked by inlining as to be computed at compile time with their
+    /// results, returning an error if any of them is made over values which are not constants.
+    ///
+    /// See [`precompute_commitments`][self] module for more information.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub(crate) fn precompute_commitments(self) -> Result<Ssa, RuntimeError> {
+        // Only start a solver when it is needed, as doing so is not free.
+        if !self.functions.values().any(has_precomputed_calls) {
+            return Ok(self);
+        }
+        match commitment_solver() {
+            Some(solver) => self.precompute_commitments_with(&solver),
+            None => {
+                let (function, instruction) = self
+                    .functions
+                    .values()
+                    .find_map(|function| {
+                        precomputed_calls(function).next().map(|call| (function, call))
+                    })
+                    .expect("a function has precomputed calls");
+                Err(RuntimeError::CommitmentNotPrecomputed {
+                    reason: "this compiler has no solver for commitments".to_string(),
+                    call_stack: function.dfg.get_call_stack(instruction),
+                })
+            }
+        }
+    }
+
+    fn precompute_commitments_with(
+        mut self,
+        solver: &impl BlackBoxFunctionSolver,
+    ) -> Result<Ssa, RuntimeError> {
+        for function in self.functions.values_mut() {
+            for block in function.reachable_blocks() {
+                let instructions = function.dfg[block].take_instructions();
+                let mut remaining_instructions = Vec::with_capacity(instructions.len());
+                for instruction in instructions {
+                    if !precompute_call(function, instruction, solver)? {
+                        remaining_instructions.push(instruction);
+                    }
+                }
+                *function.dfg[block].instructions_mut() = remaining_instructions;
+            }
+        }
+        Ok(self)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn commitment_solver() -> Option<bn254_blackbox_solver::Bn254BlackBoxSolver> {
+    Some(bn254_blackbox_solver::Bn254BlackBoxSolver::new())
+}
+
+/// The solver can only be started asynchronously on wasm, which compilation does not support.
+#[cfg(target_arch = "wasm32")]
+fn commitment_solver() -> Option<bn254_blackbox_solver::Bn254BlackBoxSolver> {
+    None
+}
+
+fn has_precomputed_calls(function: &Function) -> bool {
+    precomputed_calls(function).next().is_some()
+}
+
+/// Returns the calls of `function` to [`Intrinsic::PrecomputedBlackBox`].
+fn precomputed_calls(function: &Function) -> impl Iterator<Item = InstructionId> + '_ {
+    function.reachable_blocks().into_iter().flat_map(move |block| {
+        function.dfg[block]
+            .instructions()
+            .iter()
+            .copied()
+            .filter(move |instruction| precomputed_black_box(function, *instruction).is_some())
+    })
+}
+
+fn precomputed_black_box(function: &Function, instruction: InstructionId) -> Option<BlackBoxFunc> {
+    match &function.dfg[instruction] {
+        Instruction::Call { func, .. } => match function.dfg[*func] {
+            Value::Intrinsic(Intrinsic::PrecomputedBlackBox(func)) => Some(func),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Replaces the results of `instruction` with constants if it is a precomputed commitment.
+///
+/// Returns true if the instruction has been replaced and should be removed from its block.
+fn precompute_call(
+    function: &mut Function,
+    instruction: InstructionId,
+    solver: &impl BlackBoxFunctionSolver,
+) -> Result<bool, RuntimeError> {
+    let Some(func) = precomputed_black_box(function, instruction) else {
+        return Ok(false);
+    };
+    let Instruction::Call { arguments, .. } = &function.dfg[instruction] else {
+        unreachable!("precomputed black boxes are calls")
+    };
+    let call_stack = function.dfg.get_call_stack(instruction);
+    let not_precomputed = |reason: String| RuntimeError::CommitmentNotPrecomputed {
+        reason,
+        call_stack: call_stack.clone(),
+    };
+
+    let (inputs, domain_separator) = constant_arguments(function, arguments).ok_or_else(|| {
+        not_precomputed("its inputs are not all known at compile time".to_string())
+    })?;
+    let outputs = match func {
+        BlackBoxFunc::PedersenCommitment => {
+            solver.pedersen_commitment(&inputs, domain_separator).map(|(x, y)| vec![x, y])
+        }
+        BlackBoxFunc::PedersenHash => {
+            solver.pedersen_hash(&inputs, domain_separator).map(|hash| vec![hash])
+        }
+        _ => unreachable!("ICE: `{func}` is not a commitment"),
+    }
+    .map_err(|error| not_precomputed(error.to_string()))?;
+
+    let result = function.dfg.instruction_results(instruction)[0];
+    let result_type = function.dfg.type_of_value(result);
+    let new_result = match outputs.as_slice() {
+        [hash] => function.dfg.make_constant(*hash, result_type),
+        _ => {
+            let Type::Array(element_types, _) = &result_type else {
+                unreachable!("ICE: `{func}` returns a single value or an array")
+            };
+            let element_type = element_types[0].clone();
+            let elements =
+                vecmap(outputs, |output| function.dfg.make_constant(output, element_type.clone()));
+            function.dfg.make_array(elements.into(), result_type)
+        }
+    };
+    function.dfg.set_value_from_id(result, new_result);
+    Ok(true)
+}
+
+/// Returns the constant inputs and domain separator of a commitment, if all of them are known.
+fn constant_arguments(
+    function: &Function,
+    arguments: &[ValueId],
+) -> Option<(Vec<FieldElement>, u32)> {
+    let (inputs, _) = function.dfg.get_array_constant(arguments[0])?;
+    let inputs: Option<Vec<FieldElement>> =
+        inputs.iter().map(|input| function.dfg.get_numeric_constant(*input)).collect();
+    let inputs = inputs?;
+    let domain_separator = function.dfg.get_numeric_constant(arguments[1])?;
+    Some((inputs, domain_separator.to_u128() as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use acvm::{
+        acir::BlackBoxFunc, blackbox_solver::StubbedBlackBoxSolver, BlackBoxFunctionSolver,
+        BlackBoxResolutionError, FieldElement,
+    };
+
+    use crate::{
+        errors::RuntimeError,
+        ssa::{
+            function_builder::FunctionBuilder,
+            ir::{
+                function::RuntimeType,
+                instruction::{Instruction, Intrinsic, TerminatorInstruction},
+                map::Id,
+                types::Type,
+            },
+            ssa_gen::Ssa,
+        },
+    };
+
+    /// Hashes to the sum of the inputs and the domain separator.
+    struct SumSolver;
+
+    impl BlackBoxFunctionSolver for SumSolver {
+        fn pedersen_hash(
+            &self,
+            inputs: &[FieldElement],
+            domain_separator: u32,
+        ) -> Result<FieldElement, BlackBoxResolutionError> {
+            Ok(inputs
+                .iter()
+                .fold(FieldElement::from(domain_separator as u128), |sum, input| sum + *input))
+        }
+
+        fn schnorr_verify(
+            &self,
+            public_key_x: &FieldElement,
+            public_key_y: &FieldElement,
+            signature: &[u8],
+            message: &[u8],
+        ) -> Result<bool, BlackBoxResolutionError> {
+            StubbedBlackBoxSolver.schnorr_verify(public_key_x, public_key_y, signature, message)
+        }
+
+        fn pedersen_commitment(
+            &self,
+            inputs: &[FieldElement],
+            domain_separator: u32,
+        ) -> Result<(FieldElement, FieldElement), BlackBoxResolutionError> {
+            StubbedBlackBoxSolver.pedersen_commitment(inputs, domain_separator)
+        }
+
+        fn fixed_base_scalar_mul(
+            &self,
+            low: &FieldElement,
+            high: &FieldElement,
+        ) -> Result<(FieldElement, FieldElement), BlackBoxResolutionError> {
+            StubbedBlackBoxSolver.fixed_base_scalar_mul(low, high)
+        }
+
+        fn multi_scalar_mul(
+            &self,
+            points: &[FieldElement],
+            scalars: &[FieldElement],
+        ) -> Result<(FieldElement, FieldElement), BlackBoxResolutionError> {
+            StubbedBlackBoxSolver.multi_scalar_mul(points, scalars)
+        }
+
+        fn ec_add(
+            &self,
+            input1_x: &FieldElement,
+            input1_y: &FieldElement,
+            input2_x: &FieldElement,
+            input2_y: &FieldElement,
+        ) -> Result<(FieldElement, FieldElement), BlackBoxResolutionError> {
+            StubbedBlackBoxSolver.ec_add(input1_x, input1_y, input2_x, input2_y)
+        }
+
+        fn poseidon_permutation(
+            &self,
+            inputs: &[FieldElement],
+            rate: u32,
+        ) -> Result<Vec<FieldElement>, BlackBoxResolutionError> {
+            StubbedBlackBoxSolver.poseidon_permutation(inputs, rate)
+        }
+    }
+
+    /// Builds:
+    /// ```text
+    /// acir fn main f0 {
+    ///   b0(v0: Field):
+    ///     v1 = call precomputed_pedersen_hash([Field 1, Field 2], u32 3)
+    ///     v2 = call precomputed_pedersen_hash([Field 1, v0], u32 3)
+    ///     return v1
+    /// }
+    /// ```
+    /// where the second call is only made if `hash_parameter` is set.
+    fn precomputed_hashes(hash_parameter: bool) -> Ssa {
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::field());
+
+        let pedersen_hash =
+            builder.import_intrinsic_id(Intrinsic::PrecomputedBlackBox(BlackBoxFunc::PedersenHash));
+        let array_type = Type::Array(Rc::new(vec![Type::field()]), 2);
+        let one = builder.field_constant(1_u128);
+        let two = builder.field_constant(2_u128);
+        let separator = builder.numeric_constant(3_u128, Type::unsigned(32));
+
+        let constants = builder.array_constant(vec![one, two].into(), array_type.clone());
+        let v1 =
+            builder.insert_call(pedersen_hash, vec![constants, separator], vec![Type::field()])[0];
+        if hash_parameter {
+            let inputs = builder.array_constant(vec![one, v0].into(), array_type);
+            builder.insert_call(pedersen_hash, vec![inputs, separator], vec![Type::field()]);
+        }
+        builder.terminate_with_return(vec![v1]);
+        builder.finish()
+    }
+
+    #[test]
+    fn replaces_commitments_over_constants_with_their_results() {
+        let ssa = precomputed_hashes(false).precompute_commitments_with(&SumSolver).unwrap();
+
+        let main = ssa.main();
+        let entry_block = &main.dfg[main.entry_block()];
+        assert!(entry_block
+            .instructions()
+            .iter()
+            .all(|instruction| !matches!(main.dfg[*instruction], Instruction::Call { .. })));
+        let Some(TerminatorInstruction::Return { return_values, .. }) = entry_block.terminator()
+        else {
+            panic!("expected the entry block to return");
+        };
+        assert_eq!(
+            main.dfg.get_numeric_constant(return_values[0]),
+            Some(FieldElement::from(6_u128))
+        );
+    }
+
+    #[test]
+    fn rejects_commitments_over_unknown_values() {
+        let result = precomputed_hashes(true).precompute_commitments_with(&SumSolver);
+        assert!(matches!(result, Err(RuntimeError::CommitmentNotPrecomputed { .. })));
+    }
+}
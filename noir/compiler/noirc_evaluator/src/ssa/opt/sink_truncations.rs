@@ -0,0 +1,330 @@
+//! This pass merges chains of truncations on sized integers into a single truncation at the end
+//! of the chain.
+//!
+//! Every wrapping arithmetic operation is followed by a truncation, each of which is lowered into
+//! a bit decomposition of its input. When the only use of a truncated value is another operation
+//! which is itself truncated to the same or a smaller bit size, the first truncation is redundant:
+//! truncating to `n` bits commutes with addition, subtraction and multiplication as long as the
+//! operations do not overflow the field.
+//!
+//! ```text
+//! v2 = add v0, v1
+//! v3 = truncate v2 to 32 bits, max_bit_size: 254
+//! v5 = add v3, v4
+//! v6 = truncate v5 to 32 bits, max_bit_size: 254
+//! ```
+//!
+//! becomes
+//!
+//! ```text
+//! v2 = add v0, v1
+//! v5 = add v2, v4
+//! v6 = truncate v5 to 32 bits, max_bit_size: 34
+//! ```
+//!
+//! The maximum bit size of the remaining truncation is recomputed from bounds on its input so that
+//! the decomposition stays sound. A chain is only merged while this bound stays small enough for
+//! the arithmetic to be exact, so long chains are split into several truncations.
+use acvm::FieldElement;
+use fxhash::FxHashMap as HashMap;
+
+use crate::ssa::{
+    ir::{
+        dfg::DataFlowGraph,
+        function::{Function, RuntimeType},
+        instruction::{Binary, BinaryOp, Instruction, InstructionId},
+        types::Type,
+        value::{Value, ValueId},
+    },
+    ssa_gen::Ssa,
+};
+
+impl Ssa {
+    /// Removes truncations whose only use is an arithmetic operation which is truncated again.
+    ///
+    /// See [`sink_truncations`][self] module for more information.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub(crate) fn sink_truncations(mut self) -> Ssa {
+        for function in self.functions.values_mut() {
+            // Brillig integers wrap natively so there is nothing to gain there.
+            if function.runtime() == RuntimeType::Acir {
+                sink_truncations(function);
+            }
+        }
+        self
+    }
+}
+
+/// Truncations are only merged while their input is bounded below this many bits, so that the
+/// decomposition into quotient and remainder cannot overflow the field.
+fn max_bound() -> u32 {
+    FieldElement::max_num_bits() - 2
+}
+
+fn sink_truncations(function: &mut Function) {
+    let block = function.entry_block();
+    let instructions = function.dfg[block].instructions().to_vec();
+
+    let mut uses: HashMap<ValueId, usize> = HashMap::default();
+    for instruction in &instructions {
+        function.dfg[*instruction].for_each_value(|value| *uses.entry(value).or_default() += 1);
+    }
+    if let Some(terminator) = function.dfg[block].terminator() {
+        terminator.for_each_value(|value| *uses.entry(value).or_default() += 1);
+    }
+
+    let mut bounds = Bounds::default();
+    for instruction_id in instructions {
+        if let Instruction::Truncate { value, bit_size, .. } = function.dfg[instruction_id] {
+            if let Some((binary_id, binary, max_bit_size)) =
+                sink_into(&function.dfg, &bounds, &uses, value, bit_size)
+            {
+                function.dfg[binary_id] = Instruction::Binary(binary);
+                function.dfg[instruction_id] =
+                    Instruction::Truncate { value, bit_size, max_bit_size };
+                bounds.insert(value, max_bit_size);
+            }
+        }
+        bounds.record(&function.dfg, instruction_id);
+    }
+}
+
+/// Attempts to bypass the truncations of the operands of the binary operation producing `value`,
+/// which is about to be truncated to `bit_size` bits.
+///
+/// Returns the binary instruction to replace, its replacement and the bound on its result.
+fn sink_into(
+    dfg: &DataFlowGraph,
+    bounds: &Bounds,
+    uses: &HashMap<ValueId, usize>,
+    value: ValueId,
+    bit_size: u32,
+) -> Option<(InstructionId, Binary, u32)> {
+    let Value::Instruction { instruction, .. } = &dfg[value] else {
+        return None;
+    };
+    let Instruction::Binary(binary) = &dfg[*instruction] else {
+        return None;
+    };
+    if uses.get(&value) != Some(&1) {
+        return None;
+    }
+
+    // Only the value being subtracted from can be replaced, as acir-gen relies on the subtrahend
+    // fitting in `bit_size` bits to avoid underflow.
+    let can_sink_rhs = match binary.operator {
+        BinaryOp::Add | BinaryOp::Mul => true,
+        BinaryOp::Sub => false,
+        _ => return None,
+    };
+
+    let untruncated = |operand: ValueId| {
+        let Value::Instruction { instruction, .. } = &dfg[operand] else {
+            return operand;
+        };
+        match &dfg[*instruction] {
+            Instruction::Truncate { value, bit_size: inner_bit_size, .. }
+                if *inner_bit_size >= bit_size && uses.get(&operand) == Some(&1) =>
+            {
+                *value
+            }
+            _ => operand,
+        }
+    };
+    let lhs = untruncated(binary.lhs);
+    let rhs = if can_sink_rhs { untruncated(binary.rhs) } else { binary.rhs };
+    if lhs == binary.lhs && rhs == binary.rhs {
+        return None;
+    }
+
+    let new_binary = Binary { lhs, rhs, operator: binary.operator };
+    let bound = match binary.operator {
+        BinaryOp::Sub if bounds.get(dfg, rhs) > bit_size => return None,
+        // acir-gen adds `2^bit_size` to the difference before truncating it.
+        BinaryOp::Sub => bounds.get(dfg, lhs).max(bit_size) + 1,
+        _ => bounds.of_binary(dfg, &new_binary)?,
+    };
+    if bound >= max_bound() {
+        return None;
+    }
+    // The decomposition needs room for at least one bit of quotient.
+    Some((*instruction, new_binary, bound.max(bit_size + 1)))
+}
+
+/// Upper bounds on the number of bits of the values computed so far.
+#[derive(Default)]
+struct Bounds(HashMap<ValueId, u32>);
+
+impl Bounds {
+    fn get(&self, dfg: &DataFlowGraph, value: ValueId) -> u32 {
+        if let Some(bound) = self.0.get(&value) {
+            return *bound;
+        }
+        if let Some(constant) = dfg.get_numeric_constant(value) {
+            return constant.num_bits();
+        }
+        match dfg.type_of_value(value) {
+            Type::Numeric(numeric_type) => numeric_type.bit_size(),
+            _ => FieldElement::max_num_bits(),
+        }
+    }
+
+    fn insert(&mut self, value: ValueId, bound: u32) {
+        self.0.insert(value, bound);
+    }
+
+    fn of_binary(&self, dfg: &DataFlowGraph, binary: &Binary) -> Option<u32> {
+        let lhs = self.get(dfg, binary.lhs);
+        let rhs = self.get(dfg, binary.rhs);
+        match binary.operator {
+            BinaryOp::Add => Some(lhs.max(rhs) + 1),
+            BinaryOp::Mul => Some(lhs + rhs),
+            _ => None,
+        }
+    }
+
+    /// Records a bound on the result of `instruction_id`.
+    ///
+    /// Results of sized integer type are usually assumed to fit in their type, however the
+    /// results of arithmetic are only brought back into range by a later truncation or range
+    /// check so their bounds are derived from their operands instead.
+    fn record(&mut self, dfg: &DataFlowGraph, instruction_id: InstructionId) {
+        let [result] = dfg.instruction_results(instruction_id) else {
+            return;
+        };
+        let bound = match &dfg[instruction_id] {
+            Instruction::Truncate { bit_size, .. } => *bit_size,
+            // Casts do not change the underlying field element.
+            Instruction::Cast(value, _) => self.get(dfg, *value),
+            Instruction::Binary(binary) => match binary.operator {
+                BinaryOp::Add | BinaryOp::Mul => self
+                    .of_binary(dfg, binary)
+                    .expect("ICE: additions and multiplications are bounded")
+                    .min(FieldElement::max_num_bits()),
+                BinaryOp::Sub => FieldElement::max_num_bits(),
+                _ => return,
+            },
+            _ => return,
+        };
+        self.insert(*result, bound);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::ssa::{
+        function_builder::FunctionBuilder,
+        ir::{
+            function::RuntimeType,
+            instruction::{Binary, BinaryOp, Instruction},
+            map::Id,
+            types::Type,
+        },
+    };
+
+    #[test]
+    fn merges_wrapping_additions() {
+        // fn main f0 {
+        //   b0(v0: u32, v1: u32, v2: u32):
+        //     v3 = cast v0 as Field
+        //     v4 = cast v1 as Field
+        //     v5 = add v3, v4
+        //     v6 = truncate v5 to 32 bits, max_bit_size: 254
+        //     v7 = cast v2 as Field
+        //     v8 = add v6, v7
+        //     v9 = truncate v8 to 32 bits, max_bit_size: 254
+        //     return v9
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::unsigned(32));
+        let v1 = builder.add_parameter(Type::unsigned(32));
+        let v2 = builder.add_parameter(Type::unsigned(32));
+
+        let v3 = builder.insert_cast(v0, Type::field());
+        let v4 = builder.insert_cast(v1, Type::field());
+        let v5 = builder.insert_binary(v3, BinaryOp::Add, v4);
+        let v6 = builder.insert_truncate(v5, 32, 254);
+        let v7 = builder.insert_cast(v2, Type::field());
+        let v8 = builder.insert_binary(v6, BinaryOp::Add, v7);
+        let v9 = builder.insert_truncate(v8, 32, 254);
+        builder.terminate_with_return(vec![v9]);
+
+        let ssa = builder.finish().sink_truncations().dead_instruction_elimination();
+        let main = ssa.main();
+        let instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(instructions.len(), 6);
+
+        let truncations: Vec<_> = instructions
+            .iter()
+            .filter_map(|instruction| match &main.dfg[*instruction] {
+                Instruction::Truncate { value, bit_size, max_bit_size } => {
+                    Some((*value, *bit_size, *max_bit_size))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(truncations, vec![(v8, 32, 34)]);
+
+        let Instruction::Binary(binary) = &main.dfg[instructions[4]] else {
+            panic!("Expected the second addition");
+        };
+        assert_eq!(binary, &Binary { lhs: v5, rhs: v7, operator: BinaryOp::Add });
+    }
+
+    #[test]
+    fn keeps_truncations_with_other_uses() {
+        // fn main f0 {
+        //   b0(v0: Field, v1: u8):
+        //     v2 = truncate v0 to 8 bits, max_bit_size: 16
+        //     v3 = mul v2, v1
+        //     v4 = truncate v3 to 8 bits, max_bit_size: 16
+        //     return v2, v4
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::field());
+        let v1 = builder.add_parameter(Type::unsigned(8));
+
+        let v2 = builder.insert_truncate(v0, 8, 16);
+        let v3 = builder.insert_binary(v2, BinaryOp::Mul, v1);
+        let v4 = builder.insert_truncate(v3, 8, 16);
+        builder.terminate_with_return(vec![v2, v4]);
+
+        let ssa = builder.finish().sink_truncations();
+        let main = ssa.main();
+        let instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(
+            main.dfg[instructions[1]],
+            Instruction::Binary(Binary { lhs: v2, rhs: v1, operator: BinaryOp::Mul })
+        );
+    }
+
+    #[test]
+    fn does_not_sink_into_unbounded_values() {
+        // fn main f0 {
+        //   b0(v0: Field, v1: Field):
+        //     v2 = truncate v0 to 32 bits, max_bit_size: 254
+        //     v3 = add v2, v1
+        //     v4 = truncate v3 to 32 bits, max_bit_size: 254
+        //     return v4
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(Type::field());
+        let v1 = builder.add_parameter(Type::field());
+
+        let v2 = builder.insert_truncate(v0, 32, 254);
+        let v3 = builder.insert_binary(v2, BinaryOp::Add, v1);
+        let v4 = builder.insert_truncate(v3, 32, 254);
+        builder.terminate_with_return(vec![v4]);
+
+        let ssa = builder.finish().sink_truncations();
+        let main = ssa.main();
+        let instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(
+            main.dfg[instructions[1]],
+            Instruction::Binary(Binary { lhs: v2, rhs: v1, operator: BinaryOp::Add })
+        );
+    }
+}
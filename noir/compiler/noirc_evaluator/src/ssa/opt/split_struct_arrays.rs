@@ -0,0 +1,429 @@
+//! This pass changes the layout of arrays of structs from an array of structs into a
+//! struct of arrays, when this avoids copying fields which are never accessed dynamically
+//! into ACIR memory.
+//!
+//! Arrays of structs are stored with the fields of each element interleaved, so that reading
+//! `array[i].y` from an array of `(x, y)` pairs reads the flattened index `i * 2 + 1`:
+//!
+//! ```text
+//! v2 = mul v1, Field 2
+//! v3 = add v2, Field 1
+//! v4 = array_get v0, index v3
+//! ```
+//!
+//! As soon as an array is accessed at a dynamic index all of its flattened elements are copied
+//! into a memory block, including every `x` even though these are never read dynamically.
+//! If each field is instead held in a separate array then only the arrays of fields which are
+//! accessed dynamically need to be placed in memory:
+//!
+//! ```text
+//! v5 = make_array [<each y of v0>]
+//! v4 = array_get v5, index v1
+//! ```
+//!
+//! Only arrays of numeric fields whose every use, along with the uses of every array derived
+//! from them by `array_set`, is a read or a write of a single field are split in this way.
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use acvm::FieldElement;
+use im::Vector;
+use iter_extended::vecmap;
+
+use crate::ssa::{
+    ir::{
+        basic_block::BasicBlockId,
+        dfg::{CallStack, DataFlowGraph},
+        function::{Function, RuntimeType},
+        instruction::{Binary, BinaryOp, Instruction, InstructionId},
+        types::Type,
+        value::{Value, ValueId},
+    },
+    ssa_gen::Ssa,
+};
+
+impl Ssa {
+    /// Splits arrays of structs whose fields are only partly accessed at dynamic indices into
+    /// one array per field.
+    ///
+    /// See [`split_struct_arrays`][self] module for more information.
+    #[tracing::instrument(level = "trace", skip(self))]
+    pub(crate) fn split_struct_arrays(mut self) -> Ssa {
+        for function in self.functions.values_mut() {
+            // Brillig arrays are not copied into memory blocks.
+            if function.runtime() == RuntimeType::Acir {
+                split_struct_arrays(function);
+            }
+        }
+        self
+    }
+}
+
+/// An array of structs along with every array derived from it by `array_set`.
+struct Family {
+    /// The types of the fields of each struct.
+    field_types: Vec<Type>,
+    /// The number of structs in the array.
+    len: usize,
+    members: Vec<ValueId>,
+    /// Whether every access to the family reads or writes a single field.
+    splittable: bool,
+    /// The fields which are accessed at a dynamic index.
+    dynamic_fields: BTreeSet<usize>,
+}
+
+/// An index into an array of structs, in terms of the index of the struct and of the field.
+enum FieldIndex {
+    Constant(u128, usize),
+    Dynamic(ValueId, usize),
+}
+
+impl FieldIndex {
+    fn field(&self) -> usize {
+        match self {
+            FieldIndex::Constant(_, field) | FieldIndex::Dynamic(_, field) => *field,
+        }
+    }
+}
+
+fn split_struct_arrays(function: &mut Function) {
+    let block = function.entry_block();
+    let instructions = function.dfg[block].instructions().to_vec();
+
+    let families = find_families(&function.dfg, block, &instructions);
+    let families: Vec<Family> = families
+        .into_values()
+        .filter(|family| {
+            family.splittable
+                && !family.dynamic_fields.is_empty()
+                && family.dynamic_fields.len() < family.field_types.len()
+        })
+        .collect();
+    if families.is_empty() {
+        return;
+    }
+
+    let dfg = &mut function.dfg;
+    dfg[block].take_instructions();
+
+    // The arrays holding each field of each member of the families being split.
+    let mut split_arrays: HashMap<ValueId, Vec<ValueId>> = HashMap::new();
+    for family in &families {
+        let root = family.members[0];
+        split_arrays.insert(root, split_root(dfg, block, root, family));
+    }
+
+    for instruction_id in instructions {
+        match dfg[instruction_id].clone() {
+            Instruction::ArrayGet { array, index } => {
+                if let Some(fields) = split_arrays.get(&dfg.resolve(array)) {
+                    let field_index = decompose_index(dfg, index, fields.len())
+                        .expect("ICE: split arrays are only accessed at a single field");
+                    let array = fields[field_index.field()];
+                    let index = struct_index(dfg, index, field_index);
+                    dfg[instruction_id] = Instruction::ArrayGet { array, index };
+                }
+                dfg[block].insert_instruction(instruction_id);
+            }
+            Instruction::ArraySet { array, index, value } => {
+                let Some(fields) = split_arrays.get(&dfg.resolve(array)).cloned() else {
+                    dfg[block].insert_instruction(instruction_id);
+                    continue;
+                };
+                let field_index = decompose_index(dfg, index, fields.len())
+                    .expect("ICE: split arrays are only accessed at a single field");
+                let field = field_index.field();
+                let index = struct_index(dfg, index, field_index);
+                let set = Instruction::ArraySet { array: fields[field], index, value };
+                let call_stack = dfg.get_call_stack(instruction_id);
+                let new_array =
+                    dfg.insert_instruction_and_results(set, block, None, call_stack).first();
+
+                let mut new_fields = fields;
+                new_fields[field] = new_array;
+                let result = dfg.instruction_results(instruction_id)[0];
+                split_arrays.insert(result, new_fields);
+            }
+            _ => dfg[block].insert_instruction(instruction_id),
+        }
+    }
+}
+
+/// Groups the arrays of structs used in `instructions` into families, recording how each is used.
+fn find_families(
+    dfg: &DataFlowGraph,
+    block: BasicBlockId,
+    instructions: &[InstructionId],
+) -> BTreeMap<ValueId, Family> {
+    let parameters: HashSet<ValueId> = dfg.block_parameters(block).iter().copied().collect();
+    let mut families: BTreeMap<ValueId, Family> = BTreeMap::new();
+    let mut roots: HashMap<ValueId, ValueId> = HashMap::new();
+    let mut escaped = HashSet::new();
+
+    for instruction_id in instructions {
+        let (array, index) = match &dfg[*instruction_id] {
+            Instruction::ArrayGet { array, index } => (*array, *index),
+            Instruction::ArraySet { array, index, value } => {
+                mark_escaped(dfg, *value, &mut escaped);
+                (*array, *index)
+            }
+            instruction => {
+                instruction.for_each_value(|value| mark_escaped(dfg, value, &mut escaped));
+                continue;
+            }
+        };
+        mark_escaped(dfg, index, &mut escaped);
+
+        let array = dfg.resolve(array);
+        let root = match roots.get(&array) {
+            Some(root) => *root,
+            None => {
+                let Some(family) = new_family(dfg, &parameters, array) else {
+                    mark_escaped(dfg, array, &mut escaped);
+                    continue;
+                };
+                families.insert(array, family);
+                roots.insert(array, array);
+                array
+            }
+        };
+
+        let family = families.get_mut(&root).expect("ICE: every root has a family");
+        match decompose_index(dfg, index, family.field_types.len()) {
+            Some(FieldIndex::Dynamic(_, field)) => {
+                family.dynamic_fields.insert(field);
+            }
+            Some(FieldIndex::Constant(..)) => (),
+            None => family.splittable = false,
+        }
+
+        if matches!(dfg[*instruction_id], Instruction::ArraySet { .. }) {
+            let result = dfg.instruction_results(*instruction_id)[0];
+            family.members.push(result);
+            roots.insert(result, root);
+        }
+    }
+
+    if let Some(terminator) = dfg[block].terminator() {
+        terminator.for_each_value(|value| mark_escaped(dfg, value, &mut escaped));
+    }
+
+    for family in families.values_mut() {
+        if family.members.iter().any(|member| escaped.contains(member)) {
+            family.splittable = false;
+        }
+    }
+    families
+}
+
+/// Returns a new family rooted at `array` if it is an array of structs which can be split.
+fn new_family(
+    dfg: &DataFlowGraph,
+    parameters: &HashSet<ValueId>,
+    array: ValueId,
+) -> Option<Family> {
+    let is_root = parameters.contains(&array) || dfg.get_array_constant(array).is_some();
+    let Type::Array(field_types, len) = dfg.type_of_value(array) else {
+        return None;
+    };
+    let is_struct_of_numbers =
+        field_types.len() > 1 && field_types.iter().all(|typ| matches!(typ, Type::Numeric(_)));
+    (is_root && is_struct_of_numbers).then(|| Family {
+        field_types: field_types.to_vec(),
+        len,
+        members: vec![array],
+        splittable: true,
+        dynamic_fields: BTreeSet::new(),
+    })
+}
+
+/// Records that `value`, along with any array it is an element of, is used other than by
+/// reading or writing one of its fields.
+fn mark_escaped(dfg: &DataFlowGraph, value: ValueId, escaped: &mut HashSet<ValueId>) {
+    let value = dfg.resolve(value);
+    if let Value::Array { array, .. } = &dfg[value] {
+        for element in array {
+            mark_escaped(dfg, *element, escaped);
+        }
+    }
+    escaped.insert(value);
+}
+
+/// Splits `index` into the index of a struct and of one of its `field_count` fields, if it is
+/// either a constant or of the form `i * field_count + field`.
+fn decompose_index(dfg: &DataFlowGraph, index: ValueId, field_count: usize) -> Option<FieldIndex> {
+    let index = dfg.resolve(index);
+    if let Some(constant) = dfg.get_numeric_constant(index) {
+        let constant = constant.try_to_u64()? as u128;
+        let field_count = field_count as u128;
+        return Some(FieldIndex::Constant(
+            constant / field_count,
+            (constant % field_count) as usize,
+        ));
+    }
+
+    let (base, field) = match binary(dfg, index) {
+        Some(Binary { lhs, rhs, operator: BinaryOp::Add }) => {
+            let (base, offset) = if dfg.is_constant(*rhs) { (*lhs, *rhs) } else { (*rhs, *lhs) };
+            let offset = dfg.get_numeric_constant(offset)?.try_to_u64()? as usize;
+            (base, offset)
+        }
+        _ => (index, 0),
+    };
+    if field >= field_count {
+        return None;
+    }
+
+    let Some(Binary { lhs, rhs, operator: BinaryOp::Mul }) = binary(dfg, base) else {
+        return None;
+    };
+    let stride = FieldElement::from(field_count as u128);
+    if dfg.get_numeric_constant(*rhs) == Some(stride) {
+        Some(FieldIndex::Dynamic(*lhs, field))
+    } else if dfg.get_numeric_constant(*lhs) == Some(stride) {
+        Some(FieldIndex::Dynamic(*rhs, field))
+    } else {
+        None
+    }
+}
+
+fn binary(dfg: &DataFlowGraph, value: ValueId) -> Option<&Binary> {
+    match &dfg[dfg.resolve(value)] {
+        Value::Instruction { instruction, .. } => match &dfg[*instruction] {
+            Instruction::Binary(binary) => Some(binary),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns the index of the struct addressed by `field_index`, which was decomposed from the
+/// flattened `index`.
+fn struct_index(dfg: &mut DataFlowGraph, index: ValueId, field_index: FieldIndex) -> ValueId {
+    match field_index {
+        FieldIndex::Constant(struct_index, _) => {
+            let typ = dfg.type_of_value(index);
+            dfg.make_constant(struct_index.into(), typ)
+        }
+        FieldIndex::Dynamic(struct_index, _) => struct_index,
+    }
+}
+
+/// Creates an array for each field of the root of `family`.
+fn split_root(
+    dfg: &mut DataFlowGraph,
+    block: BasicBlockId,
+    root: ValueId,
+    family: &Family,
+) -> Vec<ValueId> {
+    let field_count = family.field_types.len();
+    let elements: Vec<ValueId> = match dfg.get_array_constant(root) {
+        Some((elements, _)) => elements.into_iter().collect(),
+        // Parameters are read one element at a time at the start of the function.
+        None => (0..family.len * field_count)
+            .map(|flat_index| {
+                let typ = family.field_types[flat_index % field_count].clone();
+                let index = dfg.make_constant((flat_index as u128).into(), Type::field());
+                let get = Instruction::ArrayGet { array: root, index };
+                dfg.insert_instruction_and_results(get, block, Some(vec![typ]), CallStack::new())
+                    .first()
+            })
+            .collect(),
+    };
+
+    vecmap(family.field_types.iter().enumerate(), |(field, typ)| {
+        let field_elements: Vector<ValueId> =
+            elements.iter().skip(field).step_by(field_count).copied().collect();
+        dfg.make_array(field_elements, Type::Array(vec![typ.clone()].into(), family.len))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::rc::Rc;
+
+    use crate::ssa::{
+        function_builder::FunctionBuilder,
+        ir::{
+            function::RuntimeType,
+            instruction::{BinaryOp, Instruction},
+            map::Id,
+            types::Type,
+        },
+    };
+
+    fn pair_array() -> Type {
+        Type::Array(Rc::new(vec![Type::field(), Type::unsigned(32)]), 3)
+    }
+
+    #[test]
+    fn splits_arrays_of_partly_accessed_structs() {
+        // fn main f0 {
+        //   b0(v0: [(Field, u32); 3], v1: u32):
+        //     v2 = mul v1, Field 2
+        //     v3 = add v2, Field 1
+        //     v4 = array_get v0, index v3
+        //     return v4
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(pair_array());
+        let v1 = builder.add_parameter(Type::unsigned(32));
+
+        let two = builder.field_constant(2u128);
+        let one = builder.field_constant(1u128);
+        let v2 = builder.insert_binary(v1, BinaryOp::Mul, two);
+        let v3 = builder.insert_binary(v2, BinaryOp::Add, one);
+        let v4 = builder.insert_array_get(v0, v3, Type::unsigned(32));
+        builder.terminate_with_return(vec![v4]);
+
+        // Only the `u32` fields are read from the parameter before the dynamic read:
+        //
+        // fn main f0 {
+        //   b0(v0: [(Field, u32); 3], v1: u32):
+        //     v5 = array_get v0, index Field 1
+        //     v6 = array_get v0, index Field 3
+        //     v7 = array_get v0, index Field 5
+        //     v4 = array_get [v5, v6, v7], index v1
+        //     return v4
+        // }
+        let ssa = builder.finish().split_struct_arrays().dead_instruction_elimination();
+        let main = ssa.main();
+        let instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(instructions.len(), 4);
+
+        let Instruction::ArrayGet { array, index } = &main.dfg[instructions[3]] else {
+            panic!("Expected the dynamic read to be kept");
+        };
+        assert_eq!(*index, v1);
+        assert_eq!(
+            main.dfg.type_of_value(*array),
+            Type::Array(Rc::new(vec![Type::unsigned(32)]), 3)
+        );
+        assert_eq!(main.dfg.instruction_results(instructions[3]), &[v4]);
+    }
+
+    #[test]
+    fn keeps_arrays_which_are_used_whole() {
+        // fn main f0 {
+        //   b0(v0: [(Field, u32); 3], v1: u32):
+        //     v2 = mul v1, Field 2
+        //     v3 = array_get v0, index v2
+        //     return v3, v0
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+        let v0 = builder.add_parameter(pair_array());
+        let v1 = builder.add_parameter(Type::unsigned(32));
+
+        let two = builder.field_constant(2u128);
+        let v2 = builder.insert_binary(v1, BinaryOp::Mul, two);
+        let v3 = builder.insert_array_get(v0, v2, Type::field());
+        builder.terminate_with_return(vec![v3, v0]);
+
+        let ssa = builder.finish().split_struct_arrays();
+        let main = ssa.main();
+        let instructions = main.dfg[main.entry_block()].instructions();
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(main.dfg[instructions[1]], Instruction::ArrayGet { array: v0, index: v2 });
+    }
+}
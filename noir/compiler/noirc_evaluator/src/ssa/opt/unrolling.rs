@@ -12,6 +12,12 @@
 //!
 //! Note that this pass also often creates superfluous jmp instructions in the
 //! program that will need to be removed by a later simplify cfg pass.
+//!
+//! Loops in Brillig functions are only unrolled if their bounds are constant and they unroll into
+//! few enough instructions, as larger loops are cheaper to execute than to store unrolled.
+//!
+//! Before unrolling, [`Ssa::loop_reports`] can summarize each loop to show how much it will
+//! grow the program by.
 use std::collections::HashSet;
 
 use crate::{
@@ -24,32 +30,48 @@ use crate::{
             dom::DominatorTree,
             function::{Function, RuntimeType},
             function_inserter::FunctionInserter,
-            instruction::TerminatorInstruction,
+            instruction::{Binary, BinaryOp, Instruction, TerminatorInstruction},
             post_order::PostOrder,
-            value::ValueId,
+            value::{Value, ValueId},
         },
         ssa_gen::Ssa,
     },
 };
 use fxhash::FxHashMap as HashMap;
+use iter_extended::vecmap;
 
 impl Ssa {
-    /// Unroll all loops in each SSA function.
+    /// Unroll all loops in each ACIR function, and the loops of Brillig functions which unroll
+    /// into at most `brillig_unroll_threshold` instructions.
     /// If any loop cannot be unrolled, it is left as-is or in a partially unrolled state.
     #[tracing::instrument(level = "trace", skip(self))]
-    pub(crate) fn unroll_loops(mut self) -> Result<Ssa, RuntimeError> {
+    pub(crate) fn unroll_loops(
+        mut self,
+        brillig_unroll_threshold: Option<usize>,
+    ) -> Result<Ssa, RuntimeError> {
         for function in self.functions.values_mut() {
-            // Loop unrolling in brillig can lead to a code explosion currently. This can
-            // also be true for ACIR, but we have no alternative to unrolling in ACIR.
-            // Brillig also generally prefers smaller code rather than faster code.
+            let mut loops = find_all_loops(function);
+
+            // Loop unrolling in brillig can lead to a code explosion. This can also be true for
+            // ACIR, but we have no alternative to unrolling in ACIR. Brillig also generally
+            // prefers smaller code rather than faster code, so only small loops are unrolled.
             if function.runtime() == RuntimeType::Brillig {
-                continue;
+                let Some(max_unrolled_instructions) = brillig_unroll_threshold else {
+                    continue;
+                };
+                loops.skipped = loop_reports(function)
+                    .into_iter()
+                    .filter(|report| {
+                        report
+                            .unrolled_instructions
+                            .map_or(true, |unrolled| unrolled > max_unrolled_instructions as u128)
+                    })
+                    .map(|report| report.header)
+                    .collect();
             }
 
-            // This check is always true with the addition of the above guard, but I'm
-            // keeping it in case the guard on brillig functions is ever removed.
             let abort_on_error = function.runtime() == RuntimeType::Acir;
-            find_all_loops(function).unroll_each_loop(function, abort_on_error)?;
+            loops.unroll_each_loop(function, abort_on_error)?;
         }
         Ok(self)
     }
@@ -72,12 +94,216 @@ struct Loops {
     /// The loops that failed to be unrolled so that we do not try to unroll them again.
     /// Each loop is identified by its header block id.
     failed_to_unroll: HashSet<BasicBlockId>,
+    /// The loops which are left as they are, identified by their header block ids.
+    skipped: HashSet<BasicBlockId>,
 
     yet_to_unroll: Vec<Loop>,
     modified_blocks: HashSet<BasicBlockId>,
     cfg: ControlFlowGraph,
 }
 
+/// A summary of a loop before it is unrolled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LoopReport {
+    /// The function containing the loop, e.g. `main f0`.
+    pub(crate) function: String,
+    pub(crate) header: BasicBlockId,
+    /// Values passed from one iteration to the next: the parameters of the loop header and any
+    /// references allocated outside of the loop which it both loads from and stores to.
+    pub(crate) carried_values: Vec<ValueId>,
+    /// Number of instructions in the blocks of the loop, including those of nested loops.
+    pub(crate) body_instructions: usize,
+    /// Number of those instructions which compute the same result in every iteration.
+    pub(crate) iteration_independent_instructions: usize,
+    /// The number of iterations, if the bounds of the loop are constant.
+    pub(crate) iterations: Option<u128>,
+    /// The number of instructions the loop will be unrolled into, if it and all nested loops
+    /// have constant bounds.
+    pub(crate) unrolled_instructions: Option<u128>,
+}
+
+impl std::fmt::Display for LoopReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let unknown = || "unknown".to_string();
+        writeln!(f, "loop {} in fn {}:", self.header, self.function)?;
+        writeln!(f, "  iterations: {}", self.iterations.map_or_else(unknown, |n| n.to_string()))?;
+        writeln!(
+            f,
+            "  body instructions: {} ({} iteration-independent)",
+            self.body_instructions, self.iteration_independent_instructions
+        )?;
+        let unrolled = self.unrolled_instructions.map_or_else(unknown, |n| n.to_string());
+        writeln!(f, "  unrolled instructions: {unrolled}")?;
+        let carried_values = vecmap(&self.carried_values, ToString::to_string);
+        write!(f, "  carried values: {}", carried_values.join(", "))
+    }
+}
+
+impl Ssa {
+    /// Summarizes each loop which will be unrolled, outermost loops first.
+    pub(crate) fn loop_reports(&self) -> Vec<LoopReport> {
+        let functions = self.functions.values();
+        let acir_functions = functions.filter(|function| function.runtime() == RuntimeType::Acir);
+        acir_functions.flat_map(loop_reports).collect()
+    }
+}
+
+fn loop_reports(function: &Function) -> Vec<LoopReport> {
+    let loops = find_all_loops(function);
+    let mut reports: Vec<LoopReport> = Vec::with_capacity(loops.yet_to_unroll.len());
+
+    // Loops are sorted from smallest to largest, so nested loops are reported on before the loops
+    // containing them.
+    for (index, loop_) in loops.yet_to_unroll.iter().enumerate() {
+        let nested_loops = loops.yet_to_unroll[..index]
+            .iter()
+            .zip(&reports)
+            .filter(|(nested, _)| nested.blocks.is_subset(&loop_.blocks));
+        // Only loops which aren't nested in another nested loop contribute to the unrolled size
+        // directly.
+        let directly_nested = nested_loops.clone().filter(|(nested, _)| {
+            !loops.yet_to_unroll[..index].iter().any(|other| {
+                other.header != nested.header
+                    && other.blocks.is_subset(&loop_.blocks)
+                    && nested.blocks.is_subset(&other.blocks)
+            })
+        });
+
+        let block_instructions = |block: &BasicBlockId| function.dfg[*block].instructions().len();
+        let body_instructions = loop_.blocks.iter().map(block_instructions).sum();
+        let nested_blocks: HashSet<_> =
+            nested_loops.flat_map(|(nested, _)| nested.blocks.iter().copied()).collect();
+        let own_instructions =
+            loop_.blocks.difference(&nested_blocks).map(block_instructions).sum::<usize>();
+
+        let iterations = loop_iterations(function, &loops.cfg, loop_);
+        let unrolled_instructions = directly_nested
+            .map(|(_, report)| report.unrolled_instructions)
+            .try_fold(own_instructions as u128, |total, nested| Some(total + nested?))
+            .zip(iterations)
+            .map(|(instructions, iterations)| instructions * iterations);
+
+        reports.push(LoopReport {
+            function: format!("{} {}", function.name(), function.id()),
+            header: loop_.header,
+            carried_values: carried_values(function, loop_),
+            body_instructions,
+            iteration_independent_instructions: iteration_independent_instructions(function, loop_),
+            iterations,
+            unrolled_instructions,
+        });
+    }
+    reports.reverse();
+    reports
+}
+
+/// Returns the number of iterations of a loop of the form `for i in start..end` where both
+/// `start` and `end` are constants.
+fn loop_iterations(function: &Function, cfg: &ControlFlowGraph, loop_: &Loop) -> Option<u128> {
+    let dfg = &function.dfg;
+    let mut pre_headers =
+        cfg.predecessors(loop_.header).filter(|block| *block != loop_.back_edge_start);
+    let (Some(pre_header), None) = (pre_headers.next(), pre_headers.next()) else {
+        return None;
+    };
+    let start = match dfg[pre_header].terminator() {
+        Some(TerminatorInstruction::Jmp { arguments, .. }) if arguments.len() == 1 => {
+            dfg.get_numeric_constant(arguments[0])?
+        }
+        _ => return None,
+    };
+
+    let Some(TerminatorInstruction::JmpIf { condition, .. }) = dfg[loop_.header].terminator()
+    else {
+        return None;
+    };
+    let Value::Instruction { instruction, .. } = &dfg[*condition] else {
+        return None;
+    };
+    let end = match &dfg[*instruction] {
+        Instruction::Binary(Binary { lhs, rhs, operator: BinaryOp::Lt })
+            if dfg[loop_.header].parameters().first() == Some(lhs) =>
+        {
+            dfg.get_numeric_constant(*rhs)?
+        }
+        _ => return None,
+    };
+    Some(end.to_u128().saturating_sub(start.to_u128()))
+}
+
+fn carried_values(function: &Function, loop_: &Loop) -> Vec<ValueId> {
+    let dfg = &function.dfg;
+    let mut carried_values = dfg[loop_.header].parameters().to_vec();
+
+    let mut loaded = HashSet::new();
+    let mut stored = Vec::new();
+    let mut allocated = HashSet::new();
+    for block in &loop_.blocks {
+        for instruction in dfg[*block].instructions() {
+            match &dfg[*instruction] {
+                Instruction::Load { address } => {
+                    loaded.insert(dfg.resolve(*address));
+                }
+                Instruction::Store { address, .. } => stored.push(dfg.resolve(*address)),
+                Instruction::Allocate => {
+                    allocated.extend(dfg.instruction_results(*instruction).iter().copied());
+                }
+                _ => (),
+            }
+        }
+    }
+    for address in stored {
+        if loaded.contains(&address)
+            && !allocated.contains(&address)
+            && !carried_values.contains(&address)
+        {
+            carried_values.push(address);
+        }
+    }
+    carried_values
+}
+
+fn iteration_independent_instructions(function: &Function, loop_: &Loop) -> usize {
+    let dfg = &function.dfg;
+    let instructions: Vec<_> =
+        loop_.blocks.iter().flat_map(|block| dfg[*block].instructions().iter().copied()).collect();
+
+    // Values are assumed to vary between iterations until they are found to only depend on values
+    // from outside of the loop.
+    let mut varying: HashSet<ValueId> =
+        loop_.blocks.iter().flat_map(|block| dfg[*block].parameters().iter().copied()).collect();
+    varying.extend(instructions.iter().flat_map(|id| dfg.instruction_results(*id).iter().copied()));
+
+    let mut independent = HashSet::new();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for id in &instructions {
+            if independent.contains(id) {
+                continue;
+            }
+            let instruction = &dfg[*id];
+            if instruction.has_side_effects(dfg)
+                || matches!(instruction, Instruction::Load { .. } | Instruction::Allocate)
+            {
+                continue;
+            }
+            let mut uses_varying_value = false;
+            instruction.for_each_value(|value| {
+                uses_varying_value |= varying.contains(&dfg.resolve(value));
+            });
+            if !uses_varying_value {
+                independent.insert(*id);
+                for result in dfg.instruction_results(*id) {
+                    varying.remove(result);
+                }
+                changed = true;
+            }
+        }
+    }
+    independent.len()
+}
+
 /// Find a loop in the program by finding a node that dominates any predecessor node.
 /// The edge where this happens will be the back-edge of the loop.
 fn find_all_loops(function: &Function) -> Loops {
@@ -106,6 +332,7 @@ fn find_all_loops(function: &Function) -> Loops {
 
     Loops {
         failed_to_unroll: HashSet::new(),
+        skipped: HashSet::new(),
         yet_to_unroll: loops,
         modified_blocks: HashSet::new(),
         cfg,
@@ -126,11 +353,14 @@ impl Loops {
             if next_loop.blocks.iter().any(|block| self.modified_blocks.contains(block)) {
                 let mut new_context = find_all_loops(function);
                 new_context.failed_to_unroll = self.failed_to_unroll;
+                new_context.skipped = self.skipped;
                 return new_context.unroll_each_loop(function, abort_on_error);
             }
 
             // Don't try to unroll the loop again if it is known to fail
-            if !self.failed_to_unroll.contains(&next_loop.header) {
+            if !self.failed_to_unroll.contains(&next_loop.header)
+                && !self.skipped.contains(&next_loop.header)
+            {
                 match unroll_loop(function, &self.cfg, &next_loop) {
                     Ok(_) => self.modified_blocks.extend(next_loop.blocks),
                     Err(call_stack) if abort_on_error => {
@@ -462,9 +692,16 @@ impl<'f> LoopIteration<'f> {
 
 #[cfg(test)]
 mod tests {
+    use iter_extended::vecmap;
+
     use crate::ssa::{
         function_builder::FunctionBuilder,
-        ir::{function::RuntimeType, instruction::BinaryOp, map::Id, types::Type},
+        ir::{
+            function::RuntimeType,
+            instruction::{BinaryOp, TerminatorInstruction},
+            map::Id,
+            types::Type,
+        },
     };
 
     #[test]
@@ -557,6 +794,12 @@ mod tests {
         let ssa = builder.finish();
         assert_eq!(ssa.main().reachable_blocks().len(), 7);
 
+        // The outer loop runs the 2 instructions outside of the inner loop and the 5 instructions
+        // of the inner loop 4 times on each of its 3 iterations.
+        let reports = ssa.loop_reports();
+        let unrolled_sizes = vecmap(&reports, |report| report.unrolled_instructions);
+        assert_eq!(unrolled_sizes, vec![Some(3 * (2 + 4 * 5)), Some(4 * 5)]);
+
         // Expected output:
         //
         // fn main f0 {
@@ -585,7 +828,7 @@ mod tests {
         // }
         // The final block count is not 1 because unrolling creates some unnecessary jmps.
         // If a simplify cfg pass is ran afterward, the expected block count will be 1.
-        let ssa = ssa.unroll_loops().expect("All loops should be unrolled");
+        let ssa = ssa.unroll_loops(None).expect("All loops should be unrolled");
         assert_eq!(ssa.main().reachable_blocks().len(), 5);
     }
 
@@ -634,6 +877,128 @@ mod tests {
         assert_eq!(ssa.main().reachable_blocks().len(), 4);
 
         // Expected that we failed to unroll the loop
-        assert!(ssa.unroll_loops().is_err());
+        assert!(ssa.unroll_loops(None).is_err());
+    }
+
+    #[test]
+    fn unroll_small_brillig_loops() {
+        // unconstrained fn main f0 {
+        //   b0():
+        //     jmp b1(Field 0)
+        //   b1(v0: Field):
+        //     v1 = lt v0, Field 5
+        //     jmpif v1, then: b2, else: b3
+        //   b2():
+        //     v2 = add v0, Field 1
+        //     jmp b1(v2)
+        //   b3():
+        //     return
+        // }
+        let brillig_loop = || {
+            let main_id = Id::test_new(0);
+            let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Brillig);
+
+            let b1 = builder.insert_block();
+            let b2 = builder.insert_block();
+            let b3 = builder.insert_block();
+
+            let zero = builder.field_constant(0u128);
+            builder.terminate_with_jmp(b1, vec![zero]);
+
+            builder.switch_to_block(b1);
+            let v0 = builder.add_block_parameter(b1, Type::field());
+            let five = builder.field_constant(5u128);
+            let v1 = builder.insert_binary(v0, BinaryOp::Lt, five);
+            builder.terminate_with_jmpif(v1, b2, b3);
+
+            builder.switch_to_block(b2);
+            let one = builder.field_constant(1u128);
+            let v2 = builder.insert_binary(v0, BinaryOp::Add, one);
+            builder.terminate_with_jmp(b1, vec![v2]);
+
+            builder.switch_to_block(b3);
+            builder.terminate_with_return(vec![]);
+            builder.finish()
+        };
+
+        // Loops of Brillig functions are kept unless a threshold is given.
+        let ssa = brillig_loop().unroll_loops(None).unwrap();
+        assert_eq!(ssa.main().reachable_blocks().len(), 4);
+
+        // The loop unrolls into 5 iterations of 2 instructions.
+        let ssa = brillig_loop().unroll_loops(Some(9)).unwrap();
+        assert_eq!(ssa.main().reachable_blocks().len(), 4);
+
+        let ssa = brillig_loop().unroll_loops(Some(10)).unwrap();
+        let main = ssa.main();
+        let reachable_blocks = main.reachable_blocks();
+        assert!(reachable_blocks.iter().all(|block| {
+            !matches!(main.dfg[*block].terminator(), Some(TerminatorInstruction::JmpIf { .. }))
+        }));
+    }
+
+    #[test]
+    fn report_loop_carried_and_independent_values() {
+        // fn main f0 {
+        //   b0(v0: Field):
+        //     v1 = allocate
+        //     store Field 0 at v1
+        //     jmp b1(Field 0)
+        //   b1(v2: Field):
+        //     v3 = lt v2, Field 5
+        //     jmpif v3, then: b2, else: b3
+        //   b2():
+        //     v4 = mul v0, v0
+        //     v5 = load v1
+        //     v6 = add v5, v4
+        //     store v6 at v1
+        //     v7 = add v2, Field 1
+        //     jmp b1(v7)
+        //   b3():
+        //     return
+        // }
+        let main_id = Id::test_new(0);
+        let mut builder = FunctionBuilder::new("main".into(), main_id, RuntimeType::Acir);
+
+        let b1 = builder.insert_block();
+        let b2 = builder.insert_block();
+        let b3 = builder.insert_block();
+
+        let v0 = builder.add_parameter(Type::field());
+        let v1 = builder.insert_allocate(Type::field());
+        let zero = builder.field_constant(0u128);
+        builder.insert_store(v1, zero);
+        builder.terminate_with_jmp(b1, vec![zero]);
+
+        builder.switch_to_block(b1);
+        let v2 = builder.add_block_parameter(b1, Type::field());
+        let five = builder.field_constant(5u128);
+        let v3 = builder.insert_binary(v2, BinaryOp::Lt, five);
+        builder.terminate_with_jmpif(v3, b2, b3);
+
+        builder.switch_to_block(b2);
+        let v4 = builder.insert_binary(v0, BinaryOp::Mul, v0);
+        let v5 = builder.insert_load(v1, Type::field());
+        let v6 = builder.insert_binary(v5, BinaryOp::Add, v4);
+        builder.insert_store(v1, v6);
+        let one = builder.field_constant(1u128);
+        let v7 = builder.insert_binary(v2, BinaryOp::Add, one);
+        builder.terminate_with_jmp(b1, vec![v7]);
+
+        builder.switch_to_block(b3);
+        builder.terminate_with_return(vec![]);
+
+        let ssa = builder.finish();
+        let reports = ssa.loop_reports();
+        assert_eq!(reports.len(), 1);
+        let report = &reports[0];
+
+        assert_eq!(report.header, b1);
+        assert_eq!(report.carried_values, vec![v2, v1]);
+        assert_eq!(report.body_instructions, 6);
+        // Only `v4 = mul v0, v0` is the same in every iteration.
+        assert_eq!(report.iteration_independent_instructions, 1);
+        assert_eq!(report.iterations, Some(5));
+        assert_eq!(report.unrolled_instructions, Some(30));
     }
 }
@@ -113,6 +113,8 @@ impl<'a> FunctionContext<'a> {
         } else {
             self.builder.new_function(func.name.clone(), id);
         }
+        self.builder.set_inline_type(func.inline_type);
+        self.builder.set_precompute_commitments(func.precompute_commitments);
         self.add_parameters_to_scope(&func.parameters);
     }
 
@@ -121,8 +123,8 @@ impl<'a> FunctionContext<'a> {
     /// The returned parameter type list will be flattened, so any struct parameters will
     /// be returned as one entry for each field (recursively).
     fn add_parameters_to_scope(&mut self, parameters: &Parameters) {
-        for (id, mutable, _, typ) in parameters {
-            self.add_parameter_to_scope(*id, typ, *mutable);
+        for (id, mutable, name, typ) in parameters {
+            self.add_parameter_to_scope(*id, name, typ, *mutable);
         }
     }
 
@@ -133,22 +135,35 @@ impl<'a> FunctionContext<'a> {
     fn add_parameter_to_scope(
         &mut self,
         parameter_id: LocalId,
+        name: &str,
         parameter_type: &ast::Type,
         mutable: bool,
     ) {
         // Add a separate parameter for each field type in 'parameter_type'
-        let parameter_value = Self::map_type(parameter_type, |typ| {
-            let value = self.builder.add_parameter(typ);
-            if mutable {
-                self.new_mutable_variable(value)
-            } else {
-                value.into()
-            }
+        let parameters = Self::map_type(parameter_type, |typ| self.builder.add_parameter(typ));
+        self.name_values(&parameters, name.to_string());
+        let parameter_value = parameters.map(|value| {
+            Tree::Leaf(if mutable { self.new_mutable_variable(value) } else { value.into() })
         });
 
         self.definitions.insert(parameter_id, parameter_value);
     }
 
+    /// Names each of `values` after the variable `name` of the source code which it is bound to,
+    /// with the index of the field of a tuple which it holds appended, e.g. `point.1`.
+    pub(super) fn name_values(&mut self, values: &Tree<ValueId>, name: String) {
+        match values {
+            // Patterns which are destructured are bound to a variable named `_` first.
+            _ if name == "_" => (),
+            Tree::Branch(fields) => {
+                for (index, field) in fields.iter().enumerate() {
+                    self.name_values(field, format!("{name}.{index}"));
+                }
+            }
+            Tree::Leaf(value) => self.builder.set_value_name(*value, name),
+        }
+    }
+
     /// Allocate a single slot of memory and store into it the given initial value of the variable.
     /// Always returns a Value::Mutable wrapping the allocate instruction.
     pub(super) fn new_mutable_variable(&mut self, value_to_store: ValueId) -> Value {
@@ -435,7 +450,7 @@ impl<'a> FunctionContext<'a> {
             self.builder.set_location(location).insert_constrain(
                 sign,
                 one,
-                Some("attempt to bit-shift with overflow".to_string()),
+                Some("attempt to bit-shift with overflow".to_owned().into()),
             );
         }
 
@@ -446,7 +461,7 @@ impl<'a> FunctionContext<'a> {
         self.builder.set_location(location).insert_constrain(
             overflow,
             one,
-            Some("attempt to bit-shift with overflow".to_owned()),
+            Some("attempt to bit-shift with overflow".to_owned().into()),
         );
         self.builder.insert_truncate(result, bit_size, bit_size + 1)
     }
@@ -498,8 +513,11 @@ impl<'a> FunctionContext<'a> {
                 let sign_diff = self.builder.insert_binary(result_sign, BinaryOp::Eq, lhs_sign);
                 let sign_diff_with_predicate =
                     self.builder.insert_binary(sign_diff, BinaryOp::Mul, same_sign);
-                let overflow_check =
-                    Instruction::Constrain(sign_diff_with_predicate, same_sign, Some(message));
+                let overflow_check = Instruction::Constrain(
+                    sign_diff_with_predicate,
+                    same_sign,
+                    Some(message.into()),
+                );
                 self.builder.set_location(location).insert_instruction(overflow_check, None);
             }
             BinaryOpKind::Multiply => {
@@ -530,7 +548,7 @@ impl<'a> FunctionContext<'a> {
                 self.builder.set_location(location).insert_constrain(
                     product_overflow_check,
                     one,
-                    Some(message),
+                    Some(message.into()),
                 );
             }
             _ => unreachable!("operator {} should not overflow", operator),
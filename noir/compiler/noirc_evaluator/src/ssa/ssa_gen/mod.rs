@@ -2,7 +2,7 @@ pub(crate) mod context;
 mod program;
 mod value;
 
-pub(crate) use program::Ssa;
+pub use program::Ssa;
 
 use context::SharedContext;
 use iter_extended::{try_vecmap, vecmap};
@@ -29,7 +29,7 @@ use super::{
     function_builder::data_bus::DataBus,
     ir::{
         function::RuntimeType,
-        instruction::{BinaryOp, TerminatorInstruction},
+        instruction::{AssertMessage, AssertMessagePart, BinaryOp, TerminatorInstruction},
         types::Type,
         value::ValueId,
     },
@@ -141,7 +141,7 @@ impl<'a> FunctionContext<'a> {
             Expression::Call(call) => self.codegen_call(call),
             Expression::Let(let_expr) => self.codegen_let(let_expr),
             Expression::Constrain(expr, location, assert_message) => {
-                self.codegen_constrain(expr, *location, assert_message.clone())
+                self.codegen_constrain(expr, *location, assert_message.as_deref())
             }
             Expression::Assign(assign) => self.codegen_assign(assign),
             Expression::Semi(semi) => self.codegen_semi(semi),
@@ -441,7 +441,7 @@ impl<'a> FunctionContext<'a> {
         self.builder.insert_constrain(
             is_offset_out_of_bounds,
             true_const,
-            Some("Index out of bounds".to_owned()),
+            Some("Index out of bounds".to_owned().into()),
         );
     }
 
@@ -642,11 +642,11 @@ impl<'a> FunctionContext<'a> {
     /// ValueId directly. If it is mutable, we'll need to allocate space for the value and store
     /// the initial value before returning the allocate instruction.
     fn codegen_let(&mut self, let_expr: &ast::Let) -> Result<Values, RuntimeError> {
-        let mut values = self.codegen_expression(&let_expr.expression)?;
-
-        values = values.map(|value| {
-            let value = value.eval(self);
+        let values = self.codegen_expression(&let_expr.expression)?;
+        let values = values.map(|value| Tree::Leaf(value.eval(self)));
+        self.name_values(&values, let_expr.name.clone());
 
+        let values = values.map(|value| {
             // Make sure to increment array reference counts on each let binding
             self.builder.increment_array_reference_count(value);
 
@@ -665,15 +665,64 @@ impl<'a> FunctionContext<'a> {
         &mut self,
         expr: &Expression,
         location: Location,
-        assert_message: Option<String>,
+        assert_message: Option<&Expression>,
     ) -> Result<Values, RuntimeError> {
         let expr = self.codegen_non_tuple_expression(expr)?;
         let true_literal = self.builder.numeric_constant(true, Type::bool());
+        let assert_message =
+            assert_message.map(|message| self.codegen_assert_message(message)).transpose()?;
         self.builder.set_location(location).insert_constrain(expr, true_literal, assert_message);
 
         Ok(Self::unit_value())
     }
 
+    /// Generates the message of a constraint from a string literal, or from a format string
+    /// whose placeholders are filled in with the values of the variables which it captures.
+    ///
+    /// A value made of several fields, such as a struct, is shown as a parenthesized list.
+    fn codegen_assert_message(
+        &mut self,
+        message: &Expression,
+    ) -> Result<AssertMessage, RuntimeError> {
+        let (template, fields) = match message {
+            Expression::Literal(ast::Literal::Str(message)) => return Ok(message.clone().into()),
+            Expression::Literal(ast::Literal::FmtStr(template, _, fields)) => (template, fields),
+            _ => unreachable!("ICE: assert messages are string or format string literals"),
+        };
+        let mut fields = match self.codegen_expression(fields)? {
+            Tree::Branch(fields) => fields.into_iter(),
+            leaf => vec![leaf].into_iter(),
+        };
+
+        let mut texts = split_format_string(template).into_iter();
+        let mut parts = Vec::new();
+        let push_text = |parts: &mut Vec<_>, text: &str| {
+            if !text.is_empty() {
+                parts.push(AssertMessagePart::Text(text.to_owned()));
+            }
+        };
+        push_text(&mut parts, texts.next().unwrap_or_default());
+        for text in texts {
+            let field = fields.next().expect("ICE: format string should capture every placeholder");
+            match field.into_value_list(self).as_slice() {
+                [value] => parts.push(AssertMessagePart::Value(*value)),
+                values => {
+                    push_text(&mut parts, "(");
+                    for (index, value) in values.iter().enumerate() {
+                        if index > 0 {
+                            push_text(&mut parts, ", ");
+                        }
+                        parts.push(AssertMessagePart::Value(*value));
+                    }
+                    push_text(&mut parts, ")");
+                }
+            }
+            push_text(&mut parts, text);
+        }
+
+        Ok(AssertMessage::Dynamic { template: template.clone(), parts })
+    }
+
     fn codegen_assign(&mut self, assign: &ast::Assign) -> Result<Values, RuntimeError> {
         let lhs = self.extract_current_value(&assign.lvalue)?;
         let rhs = self.codegen_expression(&assign.expression)?;
@@ -687,3 +736,26 @@ impl<'a> FunctionContext<'a> {
         Ok(Self::unit_value())
     }
 }
+
+/// Splits a format string into the text around its `{name}` placeholders, which are found in the
+/// same way as when the variables they capture are resolved.
+fn split_format_string(template: &str) -> Vec<&str> {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+
+    let mut texts = Vec::new();
+    let mut text_start = 0;
+    let mut search_start = 0;
+    while let Some(open) = template[search_start..].find('{').map(|index| search_start + index) {
+        let name = &template[open + 1..];
+        let name_len = name.find(|c| !is_name_char(c)).unwrap_or(name.len());
+        if name_len > 0 && name[name_len..].starts_with('}') {
+            texts.push(&template[text_start..open]);
+            text_start = open + name_len + 2;
+            search_start = text_start;
+        } else {
+            search_start = open + 1;
+        }
+    }
+    texts.push(&template[text_start..]);
+    texts
+}
@@ -8,7 +8,7 @@ use crate::ssa::ir::{
 };
 
 /// Contains the entire SSA representation of the program.
-pub(crate) struct Ssa {
+pub struct Ssa {
     pub(crate) functions: BTreeMap<FunctionId, Function>,
     pub(crate) main_id: FunctionId,
     pub(crate) next_id: AtomicCounter<Function>,
@@ -39,6 +39,19 @@ impl Ssa {
         self.functions.get_mut(&self.main_id).expect("ICE: Ssa should have a main function")
     }
 
+    /// Returns the number of instructions in the reachable blocks of all functions
+    pub fn num_instructions(&self) -> usize {
+        self.functions
+            .values()
+            .flat_map(|function| {
+                function
+                    .reachable_blocks()
+                    .into_iter()
+                    .map(|block| function.dfg[block].instructions().len())
+            })
+            .sum()
+    }
+
     /// Adds a new function to the program
     pub(crate) fn add_fn(
         &mut self,
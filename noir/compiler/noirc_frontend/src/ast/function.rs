@@ -108,6 +108,9 @@ impl From<FunctionDefinition> for NoirFunction {
             Some(FunctionAttribute::Test { .. }) => FunctionKind::Normal,
             Some(FunctionAttribute::Oracle(_)) => FunctionKind::Oracle,
             Some(FunctionAttribute::Recursive) => FunctionKind::Recursive,
+            Some(FunctionAttribute::NoInline) => FunctionKind::Normal,
+            Some(FunctionAttribute::InlineAlways) => FunctionKind::Normal,
+            Some(FunctionAttribute::Fold) => FunctionKind::Normal,
             None => FunctionKind::Normal,
         };
 
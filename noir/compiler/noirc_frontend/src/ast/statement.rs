@@ -415,8 +415,10 @@ pub enum LValue {
     Dereference(Box<LValue>),
 }
 
+/// A constraint, with the message to show if it fails: either a string literal or a format
+/// string whose placeholders are filled in with the values of the variables they name.
 #[derive(Debug, PartialEq, Eq, Clone)]
-pub struct ConstrainStatement(pub Expression, pub Option<String>, pub ConstrainKind);
+pub struct ConstrainStatement(pub Expression, pub Option<Expression>, pub ConstrainKind);
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum ConstrainKind {
@@ -1140,7 +1140,8 @@ impl<'a> Resolver<'a> {
             }
             StatementKind::Constrain(constrain_stmt) => {
                 let expr_id = self.resolve_expression(constrain_stmt.0);
-                let assert_message = constrain_stmt.1;
+                let assert_message =
+                    constrain_stmt.1.map(|message| self.resolve_expression(message));
                 HirStatement::Constrain(HirConstrainStatement(expr_id, self.file, assert_message))
             }
             StatementKind::Expression(expr) => {
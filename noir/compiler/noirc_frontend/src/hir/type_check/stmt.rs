@@ -308,6 +308,12 @@ impl<'interner> TypeChecker<'interner> {
             expected_typ: Type::Bool.to_string(),
             expr_span,
         });
+
+        // The message is a string or format string literal, whose captured variables still need
+        // to be typed.
+        if let Some(message) = stmt.2 {
+            self.check_expression(&message);
+        }
     }
 
     /// All declaration statements check that the user specified type(UST) is equal to the
@@ -345,14 +351,14 @@ impl<'interner> TypeChecker<'interner> {
         let span = self.interner.expr_span(rhs_expr);
         match expr {
             HirExpression::Literal(HirLiteral::Integer(value, false)) => {
-                let v = value.to_u128();
                 if let Type::Integer(_, bit_count) = annotated_type {
-                    let max = 1 << bit_count;
-                    if v >= max {
+                    // Computed without shifting past the end of a u128 for 128-bit integers.
+                    let max = u128::MAX.checked_shr(128 - bit_count).unwrap_or(0);
+                    if value.num_bits() > *bit_count {
                         self.errors.push(TypeCheckError::OverflowingAssignment {
                             expr: value,
                             ty: annotated_type.clone(),
-                            range: format!("0..={}", max - 1),
+                            range: format!("0..={max}"),
                             span,
                         });
                     };
@@ -324,7 +324,9 @@ impl IntType {
             Err(_) => return Ok(None),
         };
 
-        let max_bits = FieldElement::max_num_bits() / 2;
+        // Products of unsigned integers of up to 128 bits are computed on two limbs where they
+        // would not fit in a field element, which signed integers do not support.
+        let max_bits = if is_signed { FieldElement::max_num_bits() / 2 } else { 128 };
 
         if str_as_u32 > max_bits {
             return Err(LexerErrorKind::TooManyBits { span, max: max_bits, got: str_as_u32 });
@@ -417,6 +419,11 @@ impl Attributes {
         !self.has_contract_library_method() && !self.is_test_function()
     }
 
+    /// Returns true if one of the secondary attributes is `precompute_commitments`
+    pub fn has_precompute_commitments(&self) -> bool {
+        self.secondary.contains(&SecondaryAttribute::PrecomputeCommitments)
+    }
+
     /// Returns note if a deprecated secondary attribute is found
     pub fn get_deprecated_note(&self) -> Option<Option<String>> {
         self.secondary.iter().find_map(|attr| match attr {
@@ -492,6 +499,9 @@ impl Attribute {
             }
             ["test"] => Attribute::Function(FunctionAttribute::Test(TestScope::None)),
             ["recursive"] => Attribute::Function(FunctionAttribute::Recursive),
+            ["no_inline"] => Attribute::Function(FunctionAttribute::NoInline),
+            ["inline_always"] => Attribute::Function(FunctionAttribute::InlineAlways),
+            ["fold"] => Attribute::Function(FunctionAttribute::Fold),
             ["test", name] => {
                 validate(name)?;
                 let malformed_scope =
@@ -512,6 +522,9 @@ impl Attribute {
             }
             ["event"] => Attribute::Secondary(SecondaryAttribute::Event),
             ["export"] => Attribute::Secondary(SecondaryAttribute::Export),
+            ["precompute_commitments"] => {
+                Attribute::Secondary(SecondaryAttribute::PrecomputeCommitments)
+            }
             ["deprecated", name] => {
                 if !name.starts_with('"') && !name.ends_with('"') {
                     return Err(LexerErrorKind::MalformedFuncAttribute {
@@ -543,6 +556,9 @@ pub enum FunctionAttribute {
     Oracle(String),
     Test(TestScope),
     Recursive,
+    NoInline,
+    InlineAlways,
+    Fold,
 }
 
 impl FunctionAttribute {
@@ -571,6 +587,18 @@ impl FunctionAttribute {
     pub fn is_low_level(&self) -> bool {
         matches!(self, FunctionAttribute::Foreign(_) | FunctionAttribute::Builtin(_))
     }
+
+    pub fn is_no_inline(&self) -> bool {
+        matches!(self, FunctionAttribute::NoInline)
+    }
+
+    pub fn is_inline_always(&self) -> bool {
+        matches!(self, FunctionAttribute::InlineAlways)
+    }
+
+    pub fn is_fold(&self) -> bool {
+        matches!(self, FunctionAttribute::Fold)
+    }
 }
 
 impl fmt::Display for FunctionAttribute {
@@ -581,6 +609,9 @@ impl fmt::Display for FunctionAttribute {
             FunctionAttribute::Builtin(ref k) => write!(f, "#[builtin({k})]"),
             FunctionAttribute::Oracle(ref k) => write!(f, "#[oracle({k})]"),
             FunctionAttribute::Recursive => write!(f, "#[recursive]"),
+            FunctionAttribute::NoInline => write!(f, "#[no_inline]"),
+            FunctionAttribute::InlineAlways => write!(f, "#[inline_always]"),
+            FunctionAttribute::Fold => write!(f, "#[fold]"),
         }
     }
 }
@@ -597,6 +628,9 @@ pub enum SecondaryAttribute {
     ContractLibraryMethod,
     Event,
     Export,
+    // Commitments over constant data made by the function, including through the functions it
+    // calls, are computed at compile time rather than in the circuit.
+    PrecomputeCommitments,
     Field(String),
     Custom(String),
 }
@@ -612,6 +646,7 @@ impl fmt::Display for SecondaryAttribute {
             SecondaryAttribute::ContractLibraryMethod => write!(f, "#[contract_library_method]"),
             SecondaryAttribute::Event => write!(f, "#[event]"),
             SecondaryAttribute::Export => write!(f, "#[export]"),
+            SecondaryAttribute::PrecomputeCommitments => write!(f, "#[precompute_commitments]"),
             SecondaryAttribute::Field(ref k) => write!(f, "#[field({k})]"),
         }
     }
@@ -625,6 +660,9 @@ impl AsRef<str> for FunctionAttribute {
             FunctionAttribute::Oracle(string) => string,
             FunctionAttribute::Test { .. } => "",
             FunctionAttribute::Recursive => "",
+            FunctionAttribute::NoInline => "",
+            FunctionAttribute::InlineAlways => "",
+            FunctionAttribute::Fold => "",
         }
     }
 }
@@ -636,7 +674,9 @@ impl AsRef<str> for SecondaryAttribute {
             SecondaryAttribute::Deprecated(None) => "",
             SecondaryAttribute::Custom(string) | SecondaryAttribute::Field(string) => string,
             SecondaryAttribute::ContractLibraryMethod => "",
-            SecondaryAttribute::Event | SecondaryAttribute::Export => "",
+            SecondaryAttribute::Event
+            | SecondaryAttribute::Export
+            | SecondaryAttribute::PrecomputeCommitments => "",
         }
     }
 }
@@ -31,7 +31,7 @@ pub enum Expression {
     ExtractTupleField(Box<Expression>, usize),
     Call(Call),
     Let(Let),
-    Constrain(Box<Expression>, Location, Option<String>),
+    Constrain(Box<Expression>, Location, Option<Box<Expression>>),
     Assign(Assign),
     Semi(Box<Expression>),
 }
@@ -204,6 +204,38 @@ pub struct Function {
 
     pub return_type: Type,
     pub unconstrained: bool,
+    pub inline_type: InlineType,
+    /// Whether commitments over constant data made by this function should be computed at
+    /// compile time, as requested by `#[precompute_commitments]`.
+    pub precompute_commitments: bool,
+}
+
+/// Whether calls to a function may be inlined into their callers.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InlineType {
+    /// The function may be inlined. This is the default for all functions.
+    #[default]
+    Inline,
+    /// The function was marked `#[no_inline]` and should be compiled once as a separate
+    /// function which is called into.
+    NoInline,
+    /// The function was marked `#[inline_always]` and should be inlined into its unconstrained
+    /// callers even if it is an unconstrained function too large to be inlined otherwise.
+    InlineAlways,
+    /// The function was marked `#[fold]` and should be compiled into a circuit of its own which
+    /// its constrained callers call into.
+    Fold,
+}
+
+impl std::fmt::Display for InlineType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InlineType::Inline => write!(f, "inline"),
+            InlineType::NoInline => write!(f, "no_inline"),
+            InlineType::InlineAlways => write!(f, "inline_always"),
+            InlineType::Fold => write!(f, "fold"),
+        }
+    }
 }
 
 /// Compared to hir_def::types::Type, this monomorphized Type has:
@@ -30,7 +30,7 @@ use crate::{
     TypeVariableKind, UnaryOp, Visibility,
 };
 
-use self::ast::{Definition, FuncId, Function, LocalId, Program};
+use self::ast::{Definition, FuncId, Function, InlineType, LocalId, Program};
 
 pub mod ast;
 pub mod printer;
@@ -250,8 +250,25 @@ impl<'interner> Monomorphizer<'interner> {
         let body = self.expr(body_expr_id);
         let unconstrained = modifiers.is_unconstrained
             || matches!(modifiers.contract_function_type, Some(ContractFunctionType::Open));
+        let inline_type = match &modifiers.attributes.function {
+            Some(attribute) if attribute.is_no_inline() => InlineType::NoInline,
+            Some(attribute) if attribute.is_inline_always() => InlineType::InlineAlways,
+            Some(attribute) if attribute.is_fold() => InlineType::Fold,
+            _ => InlineType::Inline,
+        };
+
+        let precompute_commitments = modifiers.attributes.has_precompute_commitments();
 
-        let function = ast::Function { id, name, parameters, body, return_type, unconstrained };
+        let function = ast::Function {
+            id,
+            name,
+            parameters,
+            body,
+            return_type,
+            unconstrained,
+            inline_type,
+            precompute_commitments,
+        };
         self.push_function(id, function);
     }
 
@@ -335,8 +352,9 @@ impl<'interner> Monomorphizer<'interner> {
                     match typ {
                         ast::Type::Field => Literal(Integer(-value, typ, location)),
                         ast::Type::Integer(_, bit_size) => {
-                            let base = 1_u128 << bit_size;
-                            Literal(Integer(FieldElement::from(base) - value, typ, location))
+                            let base = FieldElement::from(2_u128)
+                                .pow(&FieldElement::from(bit_size as u128));
+                            Literal(Integer(base - value, typ, location))
                         }
                         _ => unreachable!("Integer literal must be numeric"),
                     }
@@ -483,7 +501,8 @@ impl<'interner> Monomorphizer<'interner> {
             HirStatement::Constrain(constrain) => {
                 let expr = self.expr(constrain.0);
                 let location = self.interner.expr_location(&constrain.0);
-                ast::Expression::Constrain(Box::new(expr), location, constrain.2)
+                let message = constrain.2.map(|message| Box::new(self.expr(message)));
+                ast::Expression::Constrain(Box::new(expr), location, message)
             }
             HirStatement::Assign(assign) => self.assign(assign),
             HirStatement::For(for_loop) => {
@@ -932,7 +951,7 @@ impl<'interner> Monomorphizer<'interner> {
                     // Oracle calls are required to be wrapped in an unconstrained function
                     // The first argument to the `print` oracle is a bool, indicating a newline to be inserted at the end of the input
                     // The second argument is expected to always be an ident
-                    self.append_printable_type_info(&hir_arguments[1], &mut arguments);
+                    self.append_printable_type_info(&hir_arguments[1], location, &mut arguments);
                 }
             }
         }
@@ -991,13 +1010,15 @@ impl<'interner> Monomorphizer<'interner> {
     /// thus requiring extra metadata to correctly decode this list of elements.
     ///
     /// The Noir compiler has a `PrintableType` that handles encoding/decoding a list
-    /// of field elements to/from JSON. The type metadata attached in this method
-    /// is the serialized `PrintableType` for the argument passed to the function.
-    /// The caller that is running a Noir program should then deserialize the `PrintableType`,
+    /// of field elements. The type metadata attached in this method is the `PrintableType`
+    /// for the argument passed to the function, encoded as an array of fields made of type tags
+    /// and their parameters, which is much shorter than serializing it as a string.
+    /// The caller that is running a Noir program should then decode the `PrintableType`,
     /// and accurately decode the list of field elements passed to the foreign call.
     fn append_printable_type_info(
         &mut self,
         hir_argument: &HirExpression,
+        location: Location,
         arguments: &mut Vec<ast::Expression>,
     ) {
         match hir_argument {
@@ -1011,7 +1032,9 @@ impl<'interner> Monomorphizer<'interner> {
                         match *elements {
                             Type::Tuple(element_types) => {
                                 for typ in element_types {
-                                    Self::append_printable_type_info_inner(&typ, arguments);
+                                    Self::append_printable_type_info_inner(
+                                        &typ, location, arguments,
+                                    );
                                 }
                             }
                             _ => unreachable!(
@@ -1021,7 +1044,7 @@ impl<'interner> Monomorphizer<'interner> {
                         true
                     }
                     _ => {
-                        Self::append_printable_type_info_inner(&typ, arguments);
+                        Self::append_printable_type_info_inner(&typ, location, arguments);
                         false
                     }
                 };
@@ -1032,7 +1055,11 @@ impl<'interner> Monomorphizer<'interner> {
         }
     }
 
-    fn append_printable_type_info_inner(typ: &Type, arguments: &mut Vec<ast::Expression>) {
+    fn append_printable_type_info_inner(
+        typ: &Type,
+        location: Location,
+        arguments: &mut Vec<ast::Expression>,
+    ) {
         // Disallow printing slices and mutable references for consistency,
         // since they cannot be passed from ACIR into Brillig
         if let HirType::Array(size, _) = typ {
@@ -1044,10 +1071,15 @@ impl<'interner> Monomorphizer<'interner> {
         }
 
         let printable_type: PrintableType = typ.into();
-        let abi_as_string = serde_json::to_string(&printable_type)
-            .expect("ICE: expected PrintableType to serialize");
+        let contents = vecmap(printable_type.to_fields(), |field| {
+            ast::Expression::Literal(ast::Literal::Integer(field, ast::Type::Field, location))
+        });
+        let typ = ast::Type::Array(contents.len() as u64, Box::new(ast::Type::Field));
 
-        arguments.push(ast::Expression::Literal(ast::Literal::Str(abi_as_string)));
+        arguments.push(ast::Expression::Literal(ast::Literal::Array(ast::ArrayLiteral {
+            contents,
+            typ,
+        })));
     }
 
     /// Try to evaluate certain builtin functions (currently only 'array_len' and field modulus methods)
@@ -1208,7 +1240,18 @@ impl<'interner> Monomorphizer<'interner> {
         let name = lambda_name.to_owned();
         let unconstrained = false;
 
-        let function = ast::Function { id, name, parameters, body, return_type, unconstrained };
+        let inline_type = InlineType::Inline;
+        let precompute_commitments = false;
+        let function = ast::Function {
+            id,
+            name,
+            parameters,
+            body,
+            return_type,
+            unconstrained,
+            inline_type,
+            precompute_commitments,
+        };
         self.push_function(id, function);
 
         let typ =
@@ -1322,7 +1365,18 @@ impl<'interner> Monomorphizer<'interner> {
         parameters.append(&mut converted_parameters);
 
         let unconstrained = false;
-        let function = ast::Function { id, name, parameters, body, return_type, unconstrained };
+        let inline_type = InlineType::Inline;
+        let precompute_commitments = false;
+        let function = ast::Function {
+            id,
+            name,
+            parameters,
+            body,
+            return_type,
+            unconstrained,
+            inline_type,
+            precompute_commitments,
+        };
         self.push_function(id, function);
 
         let lambda_value =
@@ -1440,7 +1494,18 @@ impl<'interner> Monomorphizer<'interner> {
         let name = lambda_name.to_owned();
 
         let unconstrained = false;
-        let function = ast::Function { id, name, parameters, body, return_type, unconstrained };
+        let inline_type = InlineType::Inline;
+        let precompute_commitments = false;
+        let function = ast::Function {
+            id,
+            name,
+            parameters,
+            body,
+            return_type,
+            unconstrained,
+            inline_type,
+            precompute_commitments,
+        };
         self.push_function(id, function);
 
         ast::Expression::Ident(ast::Ident {
@@ -659,6 +659,11 @@ impl NodeInterner {
         *func = hir_func;
     }
 
+    /// Returns the ids of every function which has been resolved.
+    pub fn function_ids(&self) -> impl Iterator<Item = FuncId> + '_ {
+        self.func_meta.keys().copied()
+    }
+
     pub fn find_function(&self, function_name: &str) -> Option<FuncId> {
         self.func_meta
             .iter()
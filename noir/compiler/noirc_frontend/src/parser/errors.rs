@@ -38,7 +38,7 @@ pub enum ParserErrorReason {
     MultipleFunctionAttributesFound,
     #[error("A function attribute cannot be placed on a struct")]
     NoFunctionAttributesAllowedOnStruct,
-    #[error("Assert statements can only accept string literals")]
+    #[error("Assert statements can only accept string or format string literals")]
     AssertMessageNotString,
     #[error("{0}")]
     Lexer(LexerErrorKind),
@@ -837,7 +837,7 @@ where
             let mut message_str = None;
 
             if let Some(message) = expressions.get(1) {
-                if let ExpressionKind::Literal(Literal::Str(message)) = &message.kind {
+                if is_assert_message(message) {
                     message_str = Some(message.clone());
                 } else {
                     emit(ParserError::with_reason(ParserErrorReason::AssertMessageNotString, span));
@@ -873,7 +873,7 @@ where
             let mut message_str = None;
 
             if let Some(message) = exprs.get(2) {
-                if let ExpressionKind::Literal(Literal::Str(message)) = &message.kind {
+                if is_assert_message(message) {
                     message_str = Some(message.clone());
                 } else {
                     emit(ParserError::with_reason(ParserErrorReason::AssertMessageNotString, span));
@@ -887,6 +887,12 @@ where
         })
 }
 
+/// Assertion messages are either string literals or format strings, whose placeholders are
+/// filled in when the assertion fails.
+fn is_assert_message(message: &Expression) -> bool {
+    matches!(&message.kind, ExpressionKind::Literal(Literal::Str(_) | Literal::FmtStr(_)))
+}
+
 fn declaration<'a, P>(expr_parser: P) -> impl NoirParser<StatementKind> + 'a
 where
     P: ExprParser + 'a,
@@ -2092,10 +2098,22 @@ mod test {
         match parse_with(assertion(expression()), "assert(x == y, \"assertion message\")").unwrap()
         {
             StatementKind::Constrain(ConstrainStatement(_, message, _)) => {
-                assert_eq!(message, Some("assertion message".to_owned()));
+                let message = message.map(|message| message.kind);
+                assert_eq!(message, Some(ExpressionKind::string("assertion message".to_owned())));
+            }
+            _ => unreachable!(),
+        }
+
+        match parse_with(assertion(expression()), "assert(x == y, f\"{x} is not {y}\")").unwrap() {
+            StatementKind::Constrain(ConstrainStatement(_, message, _)) => {
+                let message = message.map(|message| message.kind);
+                let expected = ExpressionKind::format_string("{x} is not {y}".to_owned());
+                assert_eq!(message, Some(expected));
             }
             _ => unreachable!(),
         }
+
+        parse_with(assertion(expression()), "assert(x == y, y)").unwrap_err();
     }
 
     /// This is the standard way to assert that two expressions are equivalent
@@ -2116,7 +2134,8 @@ mod test {
             .unwrap()
         {
             StatementKind::Constrain(ConstrainStatement(_, message, _)) => {
-                assert_eq!(message, Some("assertion message".to_owned()));
+                let message = message.map(|message| message.kind);
+                assert_eq!(message, Some(ExpressionKind::string("assertion message".to_owned())));
             }
             _ => unreachable!(),
         }
@@ -0,0 +1,209 @@
+//! A compact encoding of [`PrintableType`]s into field elements, which is how the types of the
+//! values passed to the `print` oracle are sent along with them.
+//!
+//! Each type starts with a tag telling its kind, followed by its parameters and the encodings of
+//! the types it contains. Strings such as the names of structs and their fields are encoded as
+//! their length in bytes followed by the bytes packed into fields, [`BYTES_PER_FIELD`] at a time.
+use acvm::FieldElement;
+
+use crate::PrintableType;
+
+/// The number of bytes of a string packed into each field, which is the most that always fit.
+const BYTES_PER_FIELD: usize = 31;
+
+const FIELD: u128 = 0;
+const ARRAY: u128 = 1;
+const SLICE: u128 = 2;
+const TUPLE: u128 = 3;
+const SIGNED_INTEGER: u128 = 4;
+const UNSIGNED_INTEGER: u128 = 5;
+const BOOLEAN: u128 = 6;
+const STRUCT: u128 = 7;
+const STRING: u128 = 8;
+const FUNCTION: u128 = 9;
+const MUTABLE_REFERENCE: u128 = 10;
+const UNIT: u128 = 11;
+
+impl PrintableType {
+    /// Encodes the type into field elements, which [`PrintableType::from_fields`] decodes.
+    pub fn to_fields(&self) -> Vec<FieldElement> {
+        let mut fields = Vec::new();
+        self.encode(&mut fields);
+        fields
+    }
+
+    /// Decodes a type from the field elements produced by [`PrintableType::to_fields`], returning
+    /// `None` if they do not encode one.
+    pub fn from_fields(fields: &[FieldElement]) -> Option<Self> {
+        let mut fields = fields.iter().copied();
+        let typ = Self::decode(&mut fields)?;
+        fields.next().is_none().then_some(typ)
+    }
+
+    fn encode(&self, fields: &mut Vec<FieldElement>) {
+        match self {
+            PrintableType::Field => fields.push(FIELD.into()),
+            PrintableType::Array { length: Some(length), typ } => {
+                fields.extend([ARRAY, u128::from(*length)].map(FieldElement::from));
+                typ.encode(fields);
+            }
+            PrintableType::Array { length: None, typ } => {
+                fields.push(SLICE.into());
+                typ.encode(fields);
+            }
+            PrintableType::Tuple { types } => {
+                fields.extend([TUPLE, types.len() as u128].map(FieldElement::from));
+                for typ in types {
+                    typ.encode(fields);
+                }
+            }
+            PrintableType::SignedInteger { width } => {
+                fields.extend([SIGNED_INTEGER, u128::from(*width)].map(FieldElement::from));
+            }
+            PrintableType::UnsignedInteger { width } => {
+                fields.extend([UNSIGNED_INTEGER, u128::from(*width)].map(FieldElement::from));
+            }
+            PrintableType::Boolean => fields.push(BOOLEAN.into()),
+            PrintableType::Struct { name, fields: struct_fields } => {
+                fields.push(STRUCT.into());
+                encode_string(name, fields);
+                fields.push(struct_fields.len().into());
+                for (field_name, typ) in struct_fields {
+                    encode_string(field_name, fields);
+                    typ.encode(fields);
+                }
+            }
+            PrintableType::String { length } => {
+                fields.extend([STRING, u128::from(*length)].map(FieldElement::from));
+            }
+            PrintableType::Function { env } => {
+                fields.push(FUNCTION.into());
+                env.encode(fields);
+            }
+            PrintableType::MutableReference { typ } => {
+                fields.push(MUTABLE_REFERENCE.into());
+                typ.encode(fields);
+            }
+            PrintableType::Unit => fields.push(UNIT.into()),
+        }
+    }
+
+    fn decode(fields: &mut impl Iterator<Item = FieldElement>) -> Option<Self> {
+        let typ = match decode_integer::<u128>(fields)? {
+            FIELD => PrintableType::Field,
+            ARRAY => {
+                let length = decode_integer(fields)?;
+                PrintableType::Array { length: Some(length), typ: Box::new(Self::decode(fields)?) }
+            }
+            SLICE => PrintableType::Array { length: None, typ: Box::new(Self::decode(fields)?) },
+            TUPLE => {
+                let count: usize = decode_integer(fields)?;
+                let types = (0..count).map(|_| Self::decode(fields)).collect::<Option<_>>()?;
+                PrintableType::Tuple { types }
+            }
+            SIGNED_INTEGER => PrintableType::SignedInteger { width: decode_integer(fields)? },
+            UNSIGNED_INTEGER => PrintableType::UnsignedInteger { width: decode_integer(fields)? },
+            BOOLEAN => PrintableType::Boolean,
+            STRUCT => {
+                let name = decode_string(fields)?;
+                let count: usize = decode_integer(fields)?;
+                let struct_fields = (0..count)
+                    .map(|_| Some((decode_string(fields)?, Self::decode(fields)?)))
+                    .collect::<Option<_>>()?;
+                PrintableType::Struct { name, fields: struct_fields }
+            }
+            STRING => PrintableType::String { length: decode_integer(fields)? },
+            FUNCTION => PrintableType::Function { env: Box::new(Self::decode(fields)?) },
+            MUTABLE_REFERENCE => {
+                PrintableType::MutableReference { typ: Box::new(Self::decode(fields)?) }
+            }
+            UNIT => PrintableType::Unit,
+            _ => return None,
+        };
+        Some(typ)
+    }
+}
+
+fn encode_string(string: &str, fields: &mut Vec<FieldElement>) {
+    fields.push(string.len().into());
+    fields
+        .extend(string.as_bytes().chunks(BYTES_PER_FIELD).map(FieldElement::from_be_bytes_reduce));
+}
+
+fn decode_string(fields: &mut impl Iterator<Item = FieldElement>) -> Option<String> {
+    let length: usize = decode_integer(fields)?;
+    let mut bytes = Vec::with_capacity(length);
+    while bytes.len() < length {
+        let chunk_length = (length - bytes.len()).min(BYTES_PER_FIELD);
+        let chunk = fields.next()?.to_be_bytes();
+        let (padding, chunk) = chunk.split_at(chunk.len() - chunk_length);
+        if padding.iter().any(|byte| *byte != 0) {
+            return None;
+        }
+        bytes.extend_from_slice(chunk);
+    }
+    String::from_utf8(bytes).ok()
+}
+
+fn decode_integer<T: TryFrom<u128>>(fields: &mut impl Iterator<Item = FieldElement>) -> Option<T> {
+    let field = fields.next()?;
+    if field.num_bits() > 128 {
+        return None;
+    }
+    field.to_u128().try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use acvm::brillig_vm::brillig::{ForeignCallParam, Value};
+    use iter_extended::vecmap;
+
+    use crate::{fetch_printable_type, PrintableType};
+
+    #[test]
+    fn types_round_trip_through_fields() {
+        let typ = PrintableType::Struct {
+            name: "AStructWithANameLongerThanThirtyOneBytes".to_string(),
+            fields: vec![
+                ("x".to_string(), PrintableType::SignedInteger { width: 8 }),
+                (
+                    "y".to_string(),
+                    PrintableType::Array {
+                        length: Some(3),
+                        typ: Box::new(PrintableType::Tuple {
+                            types: vec![
+                                PrintableType::Boolean,
+                                PrintableType::String { length: 5 },
+                                PrintableType::Unit,
+                            ],
+                        }),
+                    },
+                ),
+                (
+                    "z".to_string(),
+                    PrintableType::Function {
+                        env: Box::new(PrintableType::Array {
+                            length: None,
+                            typ: Box::new(PrintableType::Field),
+                        }),
+                    },
+                ),
+            ],
+        };
+        assert_eq!(PrintableType::from_fields(&typ.to_fields()), Some(typ));
+    }
+
+    #[test]
+    fn integers_take_two_fields() {
+        let typ = PrintableType::UnsignedInteger { width: 64 };
+        assert_eq!(typ.to_fields(), vec![5_u128.into(), 64_u128.into()]);
+    }
+
+    #[test]
+    fn types_sent_as_json_strings_are_still_decoded() {
+        let json = r#"{"kind":"unsignedinteger","width":64}"#;
+        let fields = vecmap(json.bytes(), |byte| Value::from(u128::from(byte)));
+        let typ = fetch_printable_type(&ForeignCallParam::Array(fields)).unwrap();
+        assert_eq!(typ, PrintableType::UnsignedInteger { width: 64 });
+    }
+}
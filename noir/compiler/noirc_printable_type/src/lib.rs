@@ -6,6 +6,8 @@ use regex::{Captures, Regex};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+mod encoding;
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "kind", rename_all = "lowercase")]
 pub enum PrintableType {
@@ -64,6 +66,9 @@ pub enum ForeignCallError {
     #[error("Foreign call inputs needed for execution are missing")]
     MissingForeignCallInputs,
 
+    #[error("Could not decode PrintableType argument")]
+    InvalidPrintableType,
+
     #[error("Could not parse PrintableType argument. {0}")]
     ParsingError(#[from] serde_json::Error),
 
@@ -138,6 +143,15 @@ fn fetch_printable_type(
     printable_type: &ForeignCallParam,
 ) -> Result<PrintableType, ForeignCallError> {
     let printable_type_as_fields = vecmap(printable_type.values(), |value| value.to_field());
+    if let Some(printable_type) = PrintableType::from_fields(&printable_type_as_fields) {
+        return Ok(printable_type);
+    }
+
+    // Programs compiled before the compact encoding pass the type as a JSON string instead, which
+    // cannot be mistaken for it as no type tag is the byte of its opening `{`.
+    if printable_type_as_fields.first() != Some(&FieldElement::from(u128::from(b'{'))) {
+        return Err(ForeignCallError::InvalidPrintableType);
+    }
     let printable_type_as_string = decode_string_value(&printable_type_as_fields);
     let printable_type: PrintableType = serde_json::from_str(&printable_type_as_string)?;
 
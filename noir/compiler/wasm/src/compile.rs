@@ -43,6 +43,8 @@ export type ProgramArtifact = {
     bytecode: string;
     debug_symbols: any;
     file_map: Record<number, any>;
+    public_inputs?: Array<any>;
+    return_groups?: Array<any>;
 }
 
 type WarningsCompileResult = { warnings: Array<any>; };
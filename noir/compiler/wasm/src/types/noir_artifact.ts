@@ -119,14 +119,34 @@ export interface SourceCodeLocation {
  */
 export type OpcodeLocation = string;
 
+/**
+ * The id of a call stack, which is the index of its innermost frame in the call stack tree plus one.
+ * The empty call stack has id `0`.
+ */
+export type CallStackId = number;
+
+/**
+ * The source code locations of the opcodes of a function, with their call stacks interned.
+ */
+export interface CompressedLocations {
+  /**
+   * The frames of every call stack, as the id of the call stack of its caller and its own location.
+   */
+  call_stacks: [CallStackId, SourceCodeLocation][];
+  /**
+   * A map of the opcode location to the id of its call stack.
+   */
+  opcodes: Record<OpcodeLocation, CallStackId>;
+}
+
 /**
  * The debug information for a given function.
  */
 export interface DebugInfo {
   /**
-   * A map of the opcode location to the source code location.
+   * The source code locations of each opcode.
    */
-  locations: Record<OpcodeLocation, SourceCodeLocation[]>;
+  locations: CompressedLocations;
 }
 
 /**
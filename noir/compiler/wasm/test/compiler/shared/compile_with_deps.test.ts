@@ -37,7 +37,7 @@ export function shouldCompileIdentically(
 
     // Compare the debug symbol information, ignoring the actual ids used for file identifiers.
     // Debug symbol info looks like the following, what we need is to ignore the 'file' identifiers
-    // {"locations":{"0":[{"span":{"start":141,"end":156},"file":39},{"span":{"start":38,"end":76},"file":38},{"span":{"start":824,"end":862},"file":23}]}}
+    // {"locations":{"call_stacks":[[0,{"span":{"start":141,"end":156},"file":39}],[1,{"span":{"start":38,"end":76},"file":38}]],"opcodes":{"0":2}}}
     expect(nargoDebugInfos).to.deep.eq(noirWasmDebugInfos);
   }).timeout(timeout);
 }
@@ -67,10 +67,8 @@ function deleteDebugMetadata(contract: ContractArtifact) {
 
 /** Clears file identifiers from a set of debug symbols. */
 function clearFileIdentifiers(debugSymbols: DebugInfo) {
-  for (const loc of Object.values(debugSymbols.locations)) {
-    for (const span of loc) {
-      span.file = 0;
-    }
+  for (const [, location] of debugSymbols.locations.call_stacks) {
+    location.file = 0;
   }
 }
 
@@ -58,6 +58,7 @@ mod tests {
             return_values: PublicInputs::default(),
             assert_messages: Default::default(),
             recursive: false,
+            brillig_bytecode: Vec::new(),
         };
 
         let contract = get_mock_backend()?.eth_contract(&circuit)?;
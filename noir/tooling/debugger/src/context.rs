@@ -1,6 +1,6 @@
 use acvm::acir::circuit::{Circuit, Opcode, OpcodeLocation};
 use acvm::acir::native_types::{Witness, WitnessMap};
-use acvm::brillig_vm::brillig::Value;
+use acvm::brillig_vm::brillig::{Opcode as BrilligOpcode, Value};
 use acvm::pwg::{
     ACVMStatus, BrilligSolver, BrilligSolverStatus, ForeignCallWaitInfo, StepResult, ACVM,
 };
@@ -10,6 +10,7 @@ use nargo::artifacts::debug::DebugArtifact;
 use nargo::errors::{ExecutionError, Location};
 use nargo::ops::ForeignCallExecutor;
 use nargo::NargoError;
+use noirc_errors::debug_info::BrilligValueInfo;
 
 use std::collections::{hash_set::Iter, HashSet};
 
@@ -23,6 +24,7 @@ pub(super) enum DebugCommandResult {
 
 pub(super) struct DebugContext<'a, B: BlackBoxFunctionSolver> {
     acvm: ACVM<'a, B>,
+    circuit: &'a Circuit,
     brillig_solver: Option<BrilligSolver<'a, B>>,
     foreign_call_executor: Box<dyn ForeignCallExecutor + 'a>,
     debug_artifact: &'a DebugArtifact,
@@ -38,7 +40,13 @@ impl<'a, B: BlackBoxFunctionSolver> DebugContext<'a, B> {
         foreign_call_executor: Box<dyn ForeignCallExecutor + 'a>,
     ) -> Self {
         Self {
-            acvm: ACVM::new(blackbox_solver, &circuit.opcodes, initial_witness),
+            acvm: ACVM::new(
+                blackbox_solver,
+                &circuit.opcodes,
+                initial_witness,
+                &circuit.brillig_bytecode,
+            ),
+            circuit,
             brillig_solver: None,
             foreign_call_executor,
             debug_artifact,
@@ -50,6 +58,12 @@ impl<'a, B: BlackBoxFunctionSolver> DebugContext<'a, B> {
         self.acvm.opcodes()
     }
 
+    /// Returns the Brillig bytecode executed by the opcode at `acir_index`, if it executes any.
+    pub(super) fn get_brillig_bytecode(&self, acir_index: usize) -> Option<&[BrilligOpcode]> {
+        let opcode = self.get_opcodes().get(acir_index)?;
+        self.circuit.brillig_bytecode_of(opcode)
+    }
+
     pub(super) fn get_witness_map(&self) -> &WitnessMap {
         self.acvm.witness_map()
     }
@@ -90,9 +104,8 @@ impl<'a, B: BlackBoxFunctionSolver> DebugContext<'a, B> {
     fn get_opcodes_sizes(&self) -> Vec<usize> {
         self.get_opcodes()
             .iter()
-            .map(|opcode| match opcode {
-                Opcode::Brillig(brillig_block) => brillig_block.bytecode.len(),
-                _ => 1,
+            .map(|opcode| {
+                self.circuit.brillig_bytecode_of(opcode).map_or(1, |bytecode| bytecode.len())
             })
             .collect()
     }
@@ -171,17 +184,16 @@ impl<'a, B: BlackBoxFunctionSolver> DebugContext<'a, B> {
         match location {
             None => String::from("invalid"),
             Some(OpcodeLocation::Acir(acir_index)) => {
-                let opcode = &opcodes[*acir_index];
-                if let Opcode::Brillig(ref brillig) = opcode {
-                    let first_opcode = &brillig.bytecode[0];
+                if let Some(bytecode) = self.get_brillig_bytecode(*acir_index) {
+                    let first_opcode = &bytecode[0];
                     format!("BRILLIG {first_opcode:?}")
                 } else {
-                    format!("{opcode:?}")
+                    format!("{:?}", opcodes[*acir_index])
                 }
             }
             Some(OpcodeLocation::Brillig { acir_index, brillig_index }) => {
-                if let Opcode::Brillig(ref brillig) = opcodes[*acir_index] {
-                    let opcode = &brillig.bytecode[*brillig_index];
+                if let Some(bytecode) = self.get_brillig_bytecode(*acir_index) {
+                    let opcode = &bytecode[*brillig_index];
                     format!("      | {opcode:?}")
                 } else {
                     String::from("      | invalid")
@@ -260,6 +272,9 @@ impl<'a, B: BlackBoxFunctionSolver> DebugContext<'a, B> {
             ACVMStatus::RequiresForeignCall(_) => {
                 unreachable!("Unexpected pending foreign call resolution");
             }
+            ACVMStatus::RequiresAcirCall(_) => DebugCommandResult::Error(
+                NargoError::ExecutionError(ExecutionError::UnsupportedAcirCall),
+            ),
         }
     }
 
@@ -285,7 +300,7 @@ impl<'a, B: BlackBoxFunctionSolver> DebugContext<'a, B> {
         match self.get_current_opcode_location() {
             Some(OpcodeLocation::Brillig { .. }) => true,
             Some(OpcodeLocation::Acir(acir_index)) => {
-                matches!(self.get_opcodes()[acir_index], Opcode::Brillig(_))
+                self.get_brillig_bytecode(acir_index).is_some()
             }
             _ => false,
         }
@@ -347,15 +362,56 @@ impl<'a, B: BlackBoxFunctionSolver> DebugContext<'a, B> {
     }
 
     pub(super) fn is_executing_brillig(&self) -> bool {
-        let opcodes = self.get_opcodes();
-        let acir_index = self.acvm.instruction_pointer();
-        acir_index < opcodes.len() && matches!(opcodes[acir_index], Opcode::Brillig(..))
+        self.get_brillig_bytecode(self.acvm.instruction_pointer()).is_some()
     }
 
     pub(super) fn get_brillig_memory(&self) -> Option<&[Value]> {
         self.brillig_solver.as_ref().map(|solver| solver.get_memory())
     }
 
+    /// Returns the name and value of each variable of the source code which the registers of the
+    /// Brillig VM hold at the current opcode, or nothing if no Brillig opcode is executing.
+    pub(super) fn get_brillig_variables(&self) -> Vec<(String, String)> {
+        let (Some(OpcodeLocation::Brillig { acir_index, brillig_index }), Some(memory)) =
+            (self.get_current_opcode_location(), self.get_brillig_memory())
+        else {
+            return Vec::new();
+        };
+        let Some(variables) = self
+            .debug_artifact
+            .debug_symbols
+            .first()
+            .and_then(|debug_info| debug_info.brillig_variables.get(&acir_index))
+        else {
+            return Vec::new();
+        };
+
+        let read = |address: usize| memory.get(address).map(|value| value.to_field().to_string());
+        // Registers which are about to be written may hold anything, so they are not trusted to
+        // hold a valid address or length.
+        let read_usize =
+            |address: usize| memory.get(address)?.to_field().try_to_u64().map(|n| n as usize);
+        let read_elements = |pointer: usize, size: usize| {
+            let start = read_usize(pointer)?;
+            let elements: Option<Vec<_>> = (start..start.saturating_add(size)).map(read).collect();
+            Some(format!("[{}]", elements?.join(", ")))
+        };
+        variables
+            .iter()
+            .filter(|variable| variable.opcodes.contains(&brillig_index))
+            .filter_map(|variable| {
+                let value = match variable.value {
+                    BrilligValueInfo::Register(register) => read(register)?,
+                    BrilligValueInfo::Array { pointer, size } => read_elements(pointer, size)?,
+                    BrilligValueInfo::Vector { pointer, size } => {
+                        read_elements(pointer, read_usize(size)?)?
+                    }
+                };
+                Some((variable.name.clone(), value))
+            })
+            .collect()
+    }
+
     pub(super) fn write_brillig_memory(&mut self, ptr: usize, value: FieldElement) {
         if let Some(solver) = self.brillig_solver.as_mut() {
             solver.write_memory_at(ptr, value.into());
@@ -374,17 +430,9 @@ impl<'a, B: BlackBoxFunctionSolver> DebugContext<'a, B> {
         let opcodes = self.get_opcodes();
         match *location {
             OpcodeLocation::Acir(acir_index) => acir_index < opcodes.len(),
-            OpcodeLocation::Brillig { acir_index, brillig_index } => {
-                acir_index < opcodes.len()
-                    && matches!(opcodes[acir_index], Opcode::Brillig(..))
-                    && {
-                        if let Opcode::Brillig(ref brillig) = opcodes[acir_index] {
-                            brillig_index < brillig.bytecode.len()
-                        } else {
-                            false
-                        }
-                    }
-            }
+            OpcodeLocation::Brillig { acir_index, brillig_index } => self
+                .get_brillig_bytecode(acir_index)
+                .is_some_and(|bytecode| brillig_index < bytecode.len()),
         }
     }
 
@@ -17,12 +17,12 @@ use dap::requests::{Command, Request, SetBreakpointsArguments};
 use dap::responses::{
     ContinueResponse, DisassembleResponse, ResponseBody, ScopesResponse, SetBreakpointsResponse,
     SetExceptionBreakpointsResponse, SetInstructionBreakpointsResponse, StackTraceResponse,
-    ThreadsResponse,
+    ThreadsResponse, VariablesResponse,
 };
 use dap::server::Server;
 use dap::types::{
-    Breakpoint, DisassembledInstruction, Source, StackFrame, SteppingGranularity,
-    StoppedEventReason, Thread,
+    Breakpoint, DisassembledInstruction, Scope, Source, StackFrame, SteppingGranularity,
+    StoppedEventReason, Thread, Variable,
 };
 use nargo::artifacts::debug::DebugArtifact;
 use nargo::ops::DefaultForeignCallExecutor;
@@ -43,6 +43,10 @@ pub struct DapSession<'a, R: Read, W: Write, B: BlackBoxFunctionSolver> {
 
 // BTreeMap<FileId, Vec<(usize, OpcodeLocation)>
 
+/// The reference by which the client requests the variables of the only scope, the local
+/// variables of the Brillig code being executed.
+const LOCALS_REFERENCE: i64 = 1;
+
 impl<'a, R: Read, W: Write, B: BlackBoxFunctionSolver> DapSession<'a, R, W, B> {
     pub fn new(
         server: Server<R, W>,
@@ -199,11 +203,10 @@ impl<'a, R: Read, W: Write, B: BlackBoxFunctionSolver> DapSession<'a, R, W, B> {
                     self.handle_continue(req)?;
                 }
                 Command::Scopes(_) => {
-                    // FIXME: this needs a proper implementation when we can
-                    // show the parameters and variables
-                    self.server.respond(
-                        req.success(ResponseBody::Scopes(ScopesResponse { scopes: vec![] })),
-                    )?;
+                    self.handle_scopes(req)?;
+                }
+                Command::Variables(_) => {
+                    self.handle_variables(req)?;
                 }
                 _ => {
                     eprintln!("ERROR: unhandled command: {:?}", req.command);
@@ -252,6 +255,39 @@ impl<'a, R: Read, W: Write, B: BlackBoxFunctionSolver> DapSession<'a, R, W, B> {
         Ok(())
     }
 
+    fn handle_scopes(&mut self, req: Request) -> Result<(), ServerError> {
+        // Only the variables held in the registers of Brillig code are known
+        let scopes = if self.context.is_executing_brillig() {
+            vec![Scope {
+                name: "Locals".to_string(),
+                variables_reference: LOCALS_REFERENCE,
+                ..Scope::default()
+            }]
+        } else {
+            vec![]
+        };
+        self.server.respond(req.success(ResponseBody::Scopes(ScopesResponse { scopes })))?;
+        Ok(())
+    }
+
+    fn handle_variables(&mut self, req: Request) -> Result<(), ServerError> {
+        let Command::Variables(ref args) = req.command else {
+            unreachable!("handle_variables called on a non variables request");
+        };
+        let variables = if args.variables_reference == LOCALS_REFERENCE {
+            self.context
+                .get_brillig_variables()
+                .into_iter()
+                .map(|(name, value)| Variable { name, value, ..Variable::default() })
+                .collect()
+        } else {
+            vec![]
+        };
+        self.server
+            .respond(req.success(ResponseBody::Variables(VariablesResponse { variables })))?;
+        Ok(())
+    }
+
     fn handle_disassemble(&mut self, req: Request) -> Result<(), ServerError> {
         let Command::Disassemble(ref args) = req.command else {
             unreachable!("handle_disassemble called on a non disassemble request");
@@ -1,6 +1,6 @@
 use crate::context::{DebugCommandResult, DebugContext};
 
-use acvm::acir::circuit::{Circuit, Opcode, OpcodeLocation};
+use acvm::acir::circuit::{brillig::Brillig, Circuit, Opcode, OpcodeLocation};
 use acvm::acir::native_types::{Witness, WitnessMap};
 use acvm::{BlackBoxFunctionSolver, FieldElement};
 
@@ -57,19 +57,19 @@ impl<'a, B: BlackBoxFunctionSolver> ReplDebugger<'a, B> {
                         // so we limit it to denoting it's the start of a Brillig
                         // block. The user can still use the `opcodes` command to
                         // take a look at the whole block.
-                        let opcode_summary = match opcodes[ip] {
-                            Opcode::Brillig(..) => "BRILLIG: ...".into(),
-                            _ => format!("{}", opcodes[ip]),
+                        let opcode_summary = match self.context.get_brillig_bytecode(ip) {
+                            Some(_) => "BRILLIG: ...".into(),
+                            None => format!("{}", opcodes[ip]),
                         };
                         println!("At opcode {}: {}", ip, opcode_summary);
                     }
                     OpcodeLocation::Brillig { acir_index, brillig_index } => {
-                        let Opcode::Brillig(ref brillig) = opcodes[acir_index] else {
+                        let Some(bytecode) = self.context.get_brillig_bytecode(acir_index) else {
                             unreachable!("Brillig location does not contain a Brillig block");
                         };
                         println!(
                             "At opcode {}.{}: {:?}",
-                            acir_index, brillig_index, brillig.bytecode[brillig_index]
+                            acir_index, brillig_index, bytecode[brillig_index]
                         );
                     }
                 }
@@ -114,10 +114,17 @@ impl<'a, B: BlackBoxFunctionSolver> ReplDebugger<'a, B> {
         };
         for (acir_index, opcode) in opcodes.iter().enumerate() {
             let marker = outer_marker(acir_index);
-            if let Opcode::Brillig(brillig) = opcode {
-                println!("{:>3} {:2} BRILLIG inputs={:?}", acir_index, marker, brillig.inputs);
-                println!("       |       outputs={:?}", brillig.outputs);
-                for (brillig_index, brillig_opcode) in brillig.bytecode.iter().enumerate() {
+            let brillig_call = match opcode {
+                Opcode::Brillig(Brillig { inputs, outputs, .. })
+                | Opcode::BrilligCall { inputs, outputs, .. } => Some((inputs, outputs)),
+                _ => None,
+            };
+            if let (Some((inputs, outputs)), Some(bytecode)) =
+                (brillig_call, self.context.get_brillig_bytecode(acir_index))
+            {
+                println!("{:>3} {:2} BRILLIG inputs={:?}", acir_index, marker, inputs);
+                println!("       |       outputs={:?}", outputs);
+                for (brillig_index, brillig_opcode) in bytecode.iter().enumerate() {
                     println!(
                         "{:>3}.{:<2} |{:2} {:?}",
                         acir_index,
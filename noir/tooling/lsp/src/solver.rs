@@ -32,6 +32,14 @@ impl BlackBoxFunctionSolver for WrapperSolver {
         self.0.fixed_base_scalar_mul(low, high)
     }
 
+    fn multi_scalar_mul(
+        &self,
+        points: &[acvm::FieldElement],
+        scalars: &[acvm::FieldElement],
+    ) -> Result<(acvm::FieldElement, acvm::FieldElement), acvm::BlackBoxResolutionError> {
+        self.0.multi_scalar_mul(points, scalars)
+    }
+
     fn pedersen_hash(
         &self,
         inputs: &[acvm::FieldElement],
@@ -49,4 +57,12 @@ impl BlackBoxFunctionSolver for WrapperSolver {
     ) -> Result<(acvm::FieldElement, acvm::FieldElement), acvm::BlackBoxResolutionError> {
         self.0.ec_add(input1_x, input1_y, input2_x, input2_y)
     }
+
+    fn poseidon_permutation(
+        &self,
+        inputs: &[acvm::FieldElement],
+        rate: u32,
+    ) -> Result<Vec<acvm::FieldElement>, acvm::BlackBoxResolutionError> {
+        self.0.poseidon_permutation(inputs, rate)
+    }
 }
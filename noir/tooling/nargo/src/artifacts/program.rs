@@ -2,10 +2,12 @@ use std::collections::BTreeMap;
 
 use acvm::acir::circuit::Circuit;
 use fm::FileId;
-use noirc_abi::Abi;
+use noirc_abi::{Abi, PublicInputTable};
 use noirc_driver::CompiledProgram;
 use noirc_driver::DebugFile;
 use noirc_errors::debug_info::DebugInfo;
+use noirc_evaluator::gate_hints::GateHint;
+use noirc_evaluator::ssa::{AcirFunction, BrilligFunctionSize, PassWatermark, ReturnGroup};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -34,6 +36,36 @@ pub struct ProgramArtifact {
 
     /// Map of file Id to the source code so locations in debug info can be mapped to source code they point to.
     pub file_map: BTreeMap<FileId, DebugFile>,
+
+    /// Instruction and witness counts after each compilation pass, used to track down which pass
+    /// is responsible for a change in the size of the program between compiler versions.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pass_watermarks: Vec<PassWatermark>,
+
+    /// The number of opcodes of each unconstrained function and of each kind within it, used to
+    /// find which functions make up most of the Brillig bytecode.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub brillig_sizes: Vec<BrilligFunctionSize>,
+
+    /// The position of each public input alongside the path within the ABI and the encoding of
+    /// the values it holds, for generating the glue code of verifiers.
+    #[serde(default, skip_serializing_if = "PublicInputTable::is_empty")]
+    pub public_inputs: PublicInputTable,
+
+    /// The name and witnesses of each field of the return value, so that each of them can be
+    /// read from the solved witnesses without relying on its position in the flattened value.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub return_groups: Vec<ReturnGroup>,
+
+    /// Groups of opcodes which the backend may lay out together, e.g. as a single wide
+    /// arithmetic gate. Only emitted for backends whose cost model makes use of them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub gate_hints: Vec<GateHint>,
+
+    /// The circuits of the functions marked `#[fold]` or `#[no_inline]`, which the `Call` opcodes
+    /// of the program refer to by their position in this list plus one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub acir_functions: Vec<AcirFunction>,
 }
 
 impl From<CompiledProgram> for ProgramArtifact {
@@ -45,6 +77,12 @@ impl From<CompiledProgram> for ProgramArtifact {
             bytecode: program.circuit,
             debug_symbols: program.debug,
             file_map: program.file_map,
+            pass_watermarks: program.pass_watermarks,
+            brillig_sizes: program.brillig_sizes,
+            public_inputs: program.public_inputs,
+            return_groups: program.return_groups,
+            gate_hints: program.gate_hints,
+            acir_functions: program.acir_functions,
         }
     }
 }
@@ -59,6 +97,12 @@ impl From<ProgramArtifact> for CompiledProgram {
             debug: program.debug_symbols,
             file_map: program.file_map,
             warnings: vec![],
+            pass_watermarks: program.pass_watermarks,
+            brillig_sizes: program.brillig_sizes,
+            public_inputs: program.public_inputs,
+            return_groups: program.return_groups,
+            gate_hints: program.gate_hints,
+            acir_functions: program.acir_functions,
         }
     }
 }
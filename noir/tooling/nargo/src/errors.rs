@@ -64,10 +64,14 @@ impl NargoError {
             ExecutionError::SolvingError(error) => match error {
                 OpcodeResolutionError::IndexOutOfBounds { .. }
                 | OpcodeResolutionError::OpcodeNotSolvable(_)
-                | OpcodeResolutionError::UnsatisfiedConstrain { .. } => None,
+                | OpcodeResolutionError::UnsatisfiedConstrain { .. }
+                | OpcodeResolutionError::BrilligStepLimitExceeded { .. }
+                | OpcodeResolutionError::AcirCallOutputsMismatch { .. }
+                | OpcodeResolutionError::UnknownBrilligBytecode { .. } => None,
                 OpcodeResolutionError::BrilligFunctionFailed { message, .. } => Some(message),
                 OpcodeResolutionError::BlackBoxFunctionFailed(_, reason) => Some(reason),
             },
+            ExecutionError::MissingAcirFunction(_) | ExecutionError::UnsupportedAcirCall => None,
         }
     }
 }
@@ -79,6 +83,12 @@ pub enum ExecutionError {
 
     #[error(transparent)]
     SolvingError(#[from] OpcodeResolutionError),
+
+    #[error("Circuit {0} is called but is not part of the program")]
+    MissingAcirFunction(u32),
+
+    #[error("Calls to other circuits are not supported")]
+    UnsupportedAcirCall,
 }
 
 /// Extracts the opcode locations from a nargo error.
@@ -91,6 +101,10 @@ fn extract_locations_from_error(
             call_stack,
             ..
         })
+        | ExecutionError::SolvingError(OpcodeResolutionError::BrilligStepLimitExceeded {
+            call_stack,
+            ..
+        })
         | ExecutionError::AssertionFailed(_, call_stack) => Some(call_stack.clone()),
         ExecutionError::SolvingError(OpcodeResolutionError::IndexOutOfBounds {
             opcode_location: error_location,
@@ -98,6 +112,14 @@ fn extract_locations_from_error(
         })
         | ExecutionError::SolvingError(OpcodeResolutionError::UnsatisfiedConstrain {
             opcode_location: error_location,
+        })
+        | ExecutionError::SolvingError(OpcodeResolutionError::AcirCallOutputsMismatch {
+            opcode_location: error_location,
+            ..
+        })
+        | ExecutionError::SolvingError(OpcodeResolutionError::UnknownBrilligBytecode {
+            opcode_location: error_location,
+            ..
         }) => match error_location {
             ErrorLocation::Unresolved => {
                 unreachable!("Cannot resolve index for unsatisfied constraint")
@@ -1,6 +1,13 @@
+use std::collections::BTreeMap;
+
+use acvm::acir::circuit::OpcodeLocation;
 use acvm::pwg::{ACVMStatus, ErrorLocation, OpcodeResolutionError, ACVM};
 use acvm::BlackBoxFunctionSolver;
-use acvm::{acir::circuit::Circuit, acir::native_types::WitnessMap};
+use acvm::{
+    acir::circuit::Circuit,
+    acir::native_types::{Witness, WitnessMap},
+};
+use noirc_evaluator::ssa::AcirFunction;
 
 use crate::errors::ExecutionError;
 use crate::NargoError;
@@ -11,10 +18,38 @@ use super::foreign_calls::ForeignCallExecutor;
 pub fn execute_circuit<B: BlackBoxFunctionSolver, F: ForeignCallExecutor>(
     circuit: &Circuit,
     initial_witness: WitnessMap,
+    brillig_step_limits: BTreeMap<usize, usize>,
+    blackbox_solver: &B,
+    foreign_call_executor: &mut F,
+) -> Result<WitnessMap, NargoError> {
+    execute_program(
+        circuit,
+        &[],
+        initial_witness,
+        brillig_step_limits,
+        blackbox_solver,
+        foreign_call_executor,
+    )
+}
+
+/// Executes `circuit` as [`execute_circuit`] does, along with the circuits of `acir_functions`
+/// which it calls, each with the Brillig step limits of its own debug information.
+///
+/// `circuit` is the entry point of the program, so a `Call` opcode with id `n` calls the circuit
+/// at index `n - 1` of `acir_functions`. An error raised by a called circuit is reported at the
+/// `Call` opcode, as the locations of its own opcodes have no meaning to the caller.
+#[tracing::instrument(level = "trace", skip_all)]
+pub fn execute_program<B: BlackBoxFunctionSolver, F: ForeignCallExecutor>(
+    circuit: &Circuit,
+    acir_functions: &[AcirFunction],
+    initial_witness: WitnessMap,
+    brillig_step_limits: BTreeMap<usize, usize>,
     blackbox_solver: &B,
     foreign_call_executor: &mut F,
 ) -> Result<WitnessMap, NargoError> {
-    let mut acvm = ACVM::new(blackbox_solver, &circuit.opcodes, initial_witness);
+    let mut acvm =
+        ACVM::new(blackbox_solver, &circuit.opcodes, initial_witness, &circuit.brillig_bytecode)
+            .with_brillig_step_limits(brillig_step_limits);
 
     loop {
         let solver_status = acvm.solve();
@@ -40,7 +75,8 @@ pub fn execute_circuit<B: BlackBoxFunctionSolver, F: ForeignCallExecutor>(
                         if let Some(assert_message) = circuit.get_assert_message(
                             *call_stack.last().expect("Call stacks should not be empty"),
                         ) {
-                            ExecutionError::AssertionFailed(assert_message.to_owned(), call_stack)
+                            let assert_message = assert_message.render(acvm.witness_map());
+                            ExecutionError::AssertionFailed(assert_message, call_stack)
                         } else {
                             ExecutionError::SolvingError(error)
                         }
@@ -52,8 +88,73 @@ pub fn execute_circuit<B: BlackBoxFunctionSolver, F: ForeignCallExecutor>(
                 let foreign_call_result = foreign_call_executor.execute(&foreign_call)?;
                 acvm.resolve_pending_foreign_call(foreign_call_result);
             }
+            ACVMStatus::RequiresAcirCall(acir_call) => {
+                let callee = (acir_call.id as usize)
+                    .checked_sub(1)
+                    .and_then(|index| acir_functions.get(index))
+                    .ok_or(ExecutionError::MissingAcirFunction(acir_call.id))?;
+                let call_location = OpcodeLocation::Acir(acvm.instruction_pointer());
+                let solved_witness = execute_program(
+                    &callee.circuit,
+                    acir_functions,
+                    acir_call.initial_witness,
+                    callee.debug.brillig_step_bounds.clone(),
+                    blackbox_solver,
+                    foreign_call_executor,
+                )
+                .map_err(|error| at_call_location(error, call_location))?;
+                let call_results = callee
+                    .circuit
+                    .return_values
+                    .indices()
+                    .into_iter()
+                    .map(|index| solved_witness[&Witness(index)])
+                    .collect();
+                acvm.resolve_pending_acir_call(call_results);
+            }
         }
     }
 
     Ok(acvm.finalize())
 }
+
+/// Attributes an error raised by a called circuit to the `Call` opcode at `call_location`.
+fn at_call_location(error: NargoError, call_location: OpcodeLocation) -> NargoError {
+    let NargoError::ExecutionError(error) = error else {
+        return error;
+    };
+    let opcode_location = ErrorLocation::Resolved(call_location);
+    let call_stack = vec![call_location];
+    let error = match error {
+        ExecutionError::AssertionFailed(message, _) => {
+            ExecutionError::AssertionFailed(message, call_stack)
+        }
+        ExecutionError::SolvingError(error) => ExecutionError::SolvingError(match error {
+            OpcodeResolutionError::UnsatisfiedConstrain { .. } => {
+                OpcodeResolutionError::UnsatisfiedConstrain { opcode_location }
+            }
+            OpcodeResolutionError::IndexOutOfBounds { index, array_size, .. } => {
+                OpcodeResolutionError::IndexOutOfBounds { opcode_location, index, array_size }
+            }
+            OpcodeResolutionError::AcirCallOutputsMismatch {
+                results_size, outputs_size, ..
+            } => OpcodeResolutionError::AcirCallOutputsMismatch {
+                opcode_location,
+                results_size,
+                outputs_size,
+            },
+            OpcodeResolutionError::UnknownBrilligBytecode { id, .. } => {
+                OpcodeResolutionError::UnknownBrilligBytecode { opcode_location, id }
+            }
+            OpcodeResolutionError::BrilligFunctionFailed { message, .. } => {
+                OpcodeResolutionError::BrilligFunctionFailed { message, call_stack }
+            }
+            OpcodeResolutionError::BrilligStepLimitExceeded { step_limit, .. } => {
+                OpcodeResolutionError::BrilligStepLimitExceeded { step_limit, call_stack }
+            }
+            error => error,
+        }),
+        error => error,
+    };
+    NargoError::ExecutionError(error)
+}
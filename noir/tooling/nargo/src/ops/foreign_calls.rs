@@ -3,7 +3,9 @@ use acvm::{
     pwg::ForeignCallWaitInfo,
 };
 use jsonrpc::{arg as build_json_rpc_arg, minreq_http::Builder, Client};
+use noirc_evaluator::brillig::BRILLIG_PROFILE_ORACLE;
 use noirc_printable_type::{decode_string_value, ForeignCallError, PrintableValueDisplay};
+use std::collections::BTreeMap;
 
 pub trait ForeignCallExecutor {
     fn execute(
@@ -21,6 +23,7 @@ pub(crate) enum ForeignCall {
     SetMockReturns,
     SetMockTimes,
     ClearMock,
+    ProfileBlock,
 }
 
 impl std::fmt::Display for ForeignCall {
@@ -38,6 +41,7 @@ impl ForeignCall {
             ForeignCall::SetMockReturns => "set_mock_returns",
             ForeignCall::SetMockTimes => "set_mock_times",
             ForeignCall::ClearMock => "clear_mock",
+            ForeignCall::ProfileBlock => BRILLIG_PROFILE_ORACLE,
         }
     }
 
@@ -49,6 +53,7 @@ impl ForeignCall {
             "set_mock_returns" => Some(ForeignCall::SetMockReturns),
             "set_mock_times" => Some(ForeignCall::SetMockTimes),
             "clear_mock" => Some(ForeignCall::ClearMock),
+            BRILLIG_PROFILE_ORACLE => Some(ForeignCall::ProfileBlock),
             _ => None,
         }
     }
@@ -97,6 +102,8 @@ pub struct DefaultForeignCallExecutor {
     show_output: bool,
     /// JSON RPC client to resolve foreign calls
     external_resolver: Option<Client>,
+    /// The number of times each counter of profiled Brillig code was reached.
+    brillig_profile: BTreeMap<usize, u64>,
 }
 
 impl DefaultForeignCallExecutor {
@@ -115,6 +122,12 @@ impl DefaultForeignCallExecutor {
 }
 
 impl DefaultForeignCallExecutor {
+    /// Returns the number of times each counter of profiled Brillig code was reached by the
+    /// foreign calls executed so far, keyed by the id of the counter.
+    pub fn brillig_profile(&self) -> &BTreeMap<usize, u64> {
+        &self.brillig_profile
+    }
+
     fn extract_mock_id(
         foreign_call_inputs: &[ForeignCallParam],
     ) -> Result<(usize, &[ForeignCallParam]), ForeignCallError> {
@@ -201,6 +214,16 @@ impl ForeignCallExecutor for DefaultForeignCallExecutor {
                 self.mocked_responses.retain(|response| response.id != id);
                 Ok(ForeignCallResult { values: vec![] })
             }
+            Some(ForeignCall::ProfileBlock) => {
+                let counter = foreign_call
+                    .inputs
+                    .first()
+                    .ok_or(ForeignCallError::MissingForeignCallInputs)?
+                    .unwrap_value()
+                    .to_usize();
+                *self.brillig_profile.entry(counter).or_default() += 1;
+                Ok(ForeignCallResult { values: vec![] })
+            }
             None => {
                 let mock_response_position = self
                     .mocked_responses
@@ -255,7 +278,9 @@ mod tests {
     use jsonrpc_core::Result as RpcResult;
     use jsonrpc_derive::rpc;
     use jsonrpc_http_server::{Server, ServerBuilder};
+    use noirc_evaluator::brillig::BRILLIG_PROFILE_ORACLE;
     use serial_test::serial;
+    use std::collections::BTreeMap;
 
     use crate::ops::{DefaultForeignCallExecutor, ForeignCallExecutor};
 
@@ -334,4 +359,20 @@ mod tests {
 
         server.close();
     }
+
+    #[test]
+    fn counts_the_executions_of_profiled_blocks() {
+        let mut executor = DefaultForeignCallExecutor::new(false, None);
+
+        for counter in [3_usize, 0, 3] {
+            let foreign_call = ForeignCallWaitInfo {
+                function: BRILLIG_PROFILE_ORACLE.to_string(),
+                inputs: vec![ForeignCallParam::Single(counter.into())],
+            };
+            let result = executor.execute(&foreign_call);
+            assert_eq!(result.unwrap(), ForeignCallResult { values: vec![] });
+        }
+
+        assert_eq!(executor.brillig_profile(), &BTreeMap::from([(0, 1), (3, 2)]));
+    }
 }
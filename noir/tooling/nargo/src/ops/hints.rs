@@ -0,0 +1,178 @@
+use std::collections::BTreeSet;
+
+use acvm::{
+    acir::{
+        circuit::{
+            brillig::{Brillig, BrilligOutputs},
+            Circuit, Opcode, OpcodeLocation,
+        },
+        native_types::{Expression, Witness, WitnessMap},
+    },
+    pwg::get_value,
+};
+use noirc_errors::{debug_info::DebugInfo, CustomDiagnostic, FileDiagnostic};
+
+/// Returns a warning for each call to an unconstrained function which was executed while
+/// solving `witness_map`, but which has outputs that no executed constraint refers to.
+///
+/// Unconstrained functions only act as hints for the prover, so their outputs must be checked
+/// by constraints for the circuit to be sound. Only the paths taken by a test are covered, so
+/// a hint which is left unconstrained by these may still be constrained under other inputs.
+pub fn find_uncovered_hints(
+    circuit: &Circuit,
+    debug: &DebugInfo,
+    witness_map: &WitnessMap,
+) -> Vec<FileDiagnostic> {
+    let constrained = constrained_witnesses(circuit, witness_map);
+
+    let mut warnings = Vec::new();
+    for (index, opcode) in circuit.opcodes.iter().enumerate() {
+        let (Opcode::Brillig(Brillig { outputs, predicate, .. })
+        | Opcode::BrilligCall { outputs, predicate, .. }) = opcode
+        else {
+            continue;
+        };
+        if !is_executed(predicate.as_ref(), witness_map) {
+            continue;
+        }
+
+        let uncovered = outputs
+            .iter()
+            .flat_map(|output| match output {
+                BrilligOutputs::Simple(witness) => vec![*witness],
+                BrilligOutputs::Array(witnesses) => witnesses.clone(),
+            })
+            .filter(|witness| !constrained.contains(witness))
+            .count();
+        if uncovered == 0 {
+            continue;
+        }
+
+        let Some(call_stack) = debug.opcode_location(&OpcodeLocation::Acir(index)) else {
+            continue;
+        };
+        let Some(location) = call_stack.last() else {
+            continue;
+        };
+        let message = if uncovered == 1 {
+            "An output of this unconstrained call is never constrained".to_string()
+        } else {
+            format!("{uncovered} outputs of this unconstrained call are never constrained")
+        };
+        let diagnostic = CustomDiagnostic::simple_warning(
+            message,
+            "These outputs were computed while running this test but no constraint refers to them, so a prover could replace them with any value".to_string(),
+            location.span,
+        );
+        warnings.push(diagnostic.in_file(location.file).with_call_stack(call_stack));
+    }
+    warnings
+}
+
+/// Returns the witnesses referred to by the constraints which were executed while solving
+/// `witness_map`.
+fn constrained_witnesses(circuit: &Circuit, witness_map: &WitnessMap) -> BTreeSet<Witness> {
+    let mut constrained = BTreeSet::new();
+    for opcode in &circuit.opcodes {
+        match opcode {
+            Opcode::AssertZero(expression) => insert_witnesses(expression, &mut constrained),
+            Opcode::BlackBoxFuncCall(call) => {
+                constrained.extend(call.get_inputs_vec().iter().map(|input| input.witness));
+                constrained.extend(call.get_outputs_vec());
+            }
+            Opcode::MemoryOp { op, predicate, .. } => {
+                if is_executed(predicate.as_ref(), witness_map) {
+                    insert_witnesses(&op.index, &mut constrained);
+                    insert_witnesses(&op.value, &mut constrained);
+                }
+            }
+            Opcode::MemoryInit { init, .. } => constrained.extend(init),
+            Opcode::Call { inputs, outputs, predicate, .. } => {
+                if is_executed(predicate.as_ref(), witness_map) {
+                    constrained.extend(inputs.iter().chain(outputs));
+                }
+            }
+            // These only solve for witnesses without constraining them.
+            Opcode::Directive(_) | Opcode::Brillig(_) | Opcode::BrilligCall { .. } => (),
+        }
+    }
+    constrained
+}
+
+fn insert_witnesses(expression: &Expression, witnesses: &mut BTreeSet<Witness>) {
+    for (_, lhs, rhs) in &expression.mul_terms {
+        witnesses.insert(*lhs);
+        witnesses.insert(*rhs);
+    }
+    for (_, witness) in &expression.linear_combinations {
+        witnesses.insert(*witness);
+    }
+}
+
+/// Returns whether an opcode with the given `predicate` was executed.
+fn is_executed(predicate: Option<&Expression>, witness_map: &WitnessMap) -> bool {
+    match predicate {
+        Some(predicate) => get_value(predicate, witness_map).map_or(true, |value| !value.is_zero()),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use acvm::{
+        acir::{
+            circuit::{
+                brillig::{Brillig, BrilligOutputs},
+                Circuit, Opcode, OpcodeLocation,
+            },
+            native_types::{Expression, Witness, WitnessMap},
+        },
+        FieldElement,
+    };
+    use noirc_errors::{debug_info::DebugInfo, Location, Span};
+
+    use super::find_uncovered_hints;
+
+    fn hint(outputs: Vec<Witness>, predicate: Option<Expression>) -> Opcode {
+        Opcode::Brillig(Brillig {
+            inputs: Vec::new(),
+            outputs: outputs.into_iter().map(BrilligOutputs::Simple).collect(),
+            bytecode: Vec::new(),
+            predicate,
+        })
+    }
+
+    #[test]
+    fn reports_executed_hints_with_unconstrained_outputs() {
+        let location = Location::new(Span::single_char(0), Default::default());
+        let circuit = Circuit {
+            opcodes: vec![
+                // w1 is constrained, w2 is not.
+                hint(vec![Witness(1), Witness(2)], None),
+                Opcode::AssertZero(Witness(1).into()),
+                // This hint is skipped as its predicate is zero.
+                hint(vec![Witness(3)], Some(Witness(4).into())),
+            ],
+            ..Circuit::default()
+        };
+        let debug = DebugInfo::new(BTreeMap::from([
+            (OpcodeLocation::Acir(0), vec![location]),
+            (OpcodeLocation::Acir(2), vec![location]),
+        ]));
+        let witness_map = WitnessMap::from(BTreeMap::from([
+            (Witness(1), FieldElement::zero()),
+            (Witness(2), FieldElement::one()),
+            (Witness(3), FieldElement::zero()),
+            (Witness(4), FieldElement::zero()),
+        ]));
+
+        let warnings = find_uncovered_hints(&circuit, &debug, &witness_map);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].diagnostic.message,
+            "An output of this unconstrained call is never constrained"
+        );
+    }
+}
@@ -1,14 +1,16 @@
 pub use self::compile::{compile_contract, compile_program, compile_workspace};
-pub use self::execute::execute_circuit;
+pub use self::execute::{execute_circuit, execute_program};
 pub use self::foreign_calls::{DefaultForeignCallExecutor, ForeignCallExecutor};
+pub use self::hints::find_uncovered_hints;
 pub use self::optimize::{optimize_contract, optimize_program};
-pub use self::transform::{transform_contract, transform_program};
+pub use self::transform::{annotate_program, transform_contract, transform_program};
 
-pub use self::test::{run_test, TestStatus};
+pub use self::test::{run_test, run_test_checking_hints, TestStatus};
 
 mod compile;
 mod execute;
 mod foreign_calls;
+mod hints;
 mod optimize;
 mod test;
 mod transform;
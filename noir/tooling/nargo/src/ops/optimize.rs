@@ -5,6 +5,12 @@ pub fn optimize_program(mut program: CompiledProgram) -> CompiledProgram {
     let (optimized_circuit, location_map) = acvm::compiler::optimize(program.circuit);
     program.circuit = optimized_circuit;
     program.debug.update_acir(location_map);
+    for function in &mut program.acir_functions {
+        let (optimized_circuit, location_map) =
+            acvm::compiler::optimize(std::mem::take(&mut function.circuit));
+        function.circuit = optimized_circuit;
+        function.debug.update_acir(location_map);
+    }
     program
 }
 
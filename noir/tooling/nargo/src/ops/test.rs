@@ -6,7 +6,7 @@ use noirc_frontend::hir::{def_map::TestFunction, Context};
 
 use crate::{errors::try_to_diagnose_runtime_error, NargoError};
 
-use super::{execute_circuit, DefaultForeignCallExecutor};
+use super::{execute_program, hints::find_uncovered_hints, DefaultForeignCallExecutor};
 
 pub enum TestStatus {
     Pass,
@@ -22,20 +22,74 @@ pub fn run_test<B: BlackBoxFunctionSolver>(
     foreign_call_resolver_url: Option<&str>,
     config: &CompileOptions,
 ) -> TestStatus {
+    let (status, _) = run_test_with_hints(
+        blackbox_solver,
+        context,
+        test_function,
+        show_output,
+        foreign_call_resolver_url,
+        config,
+        false,
+    );
+    status
+}
+
+/// Runs a test as [`run_test`] does, also returning a warning for each unconstrained function
+/// called by the test whose outputs are not referred to by any constraint which was executed.
+///
+/// This can only point out under-constrained hints on the paths exercised by the test.
+pub fn run_test_checking_hints<B: BlackBoxFunctionSolver>(
+    blackbox_solver: &B,
+    context: &Context,
+    test_function: TestFunction,
+    show_output: bool,
+    foreign_call_resolver_url: Option<&str>,
+    config: &CompileOptions,
+) -> (TestStatus, Vec<FileDiagnostic>) {
+    run_test_with_hints(
+        blackbox_solver,
+        context,
+        test_function,
+        show_output,
+        foreign_call_resolver_url,
+        config,
+        true,
+    )
+}
+
+fn run_test_with_hints<B: BlackBoxFunctionSolver>(
+    blackbox_solver: &B,
+    context: &Context,
+    test_function: TestFunction,
+    show_output: bool,
+    foreign_call_resolver_url: Option<&str>,
+    config: &CompileOptions,
+    check_hints: bool,
+) -> (TestStatus, Vec<FileDiagnostic>) {
     let program = compile_no_check(context, config, test_function.get_id(), None, false);
     match program {
         Ok(program) => {
             // Run the backend to ensure the PWG evaluates functions like std::hash::pedersen,
             // otherwise constraints involving these expressions will not error.
-            let circuit_execution = execute_circuit(
+            let circuit_execution = execute_program(
                 &program.circuit,
+                &program.acir_functions,
                 WitnessMap::new(),
+                program.debug.brillig_step_bounds.clone(),
                 blackbox_solver,
                 &mut DefaultForeignCallExecutor::new(show_output, foreign_call_resolver_url),
             );
-            test_status_program_compile_pass(test_function, program.debug, circuit_execution)
+            let hint_warnings = match &circuit_execution {
+                Ok(witness_map) if check_hints => {
+                    find_uncovered_hints(&program.circuit, &program.debug, witness_map)
+                }
+                _ => Vec::new(),
+            };
+            let status =
+                test_status_program_compile_pass(test_function, program.debug, circuit_execution);
+            (status, hint_warnings)
         }
-        Err(err) => test_status_program_compile_fail(err, test_function),
+        Err(err) => (test_status_program_compile_fail(err, test_function), Vec::new()),
     }
 }
 
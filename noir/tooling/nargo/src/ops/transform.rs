@@ -1,6 +1,7 @@
 use acvm::acir::circuit::ExpressionWidth;
 use iter_extended::vecmap;
 use noirc_driver::{CompiledContract, CompiledProgram};
+use noirc_evaluator::cost_model::CostModel;
 
 pub fn transform_program(
     mut program: CompiledProgram,
@@ -11,6 +12,23 @@ pub fn transform_program(
 
     program.circuit = optimized_circuit;
     program.debug.update_acir(location_map);
+    for function in &mut program.acir_functions {
+        let circuit = std::mem::take(&mut function.circuit);
+        let (optimized_circuit, location_map) = acvm::compiler::compile(circuit, expression_width);
+        function.circuit = optimized_circuit;
+        function.debug.update_acir(location_map);
+    }
+    // The opcodes which the hints refer to have been moved around.
+    program.gate_hints.clear();
+    program
+}
+
+/// Annotates the transformed circuit of `program` with the gate layout hints of `cost_model`.
+pub fn annotate_program(
+    mut program: CompiledProgram,
+    cost_model: &dyn CostModel,
+) -> CompiledProgram {
+    program.gate_hints = cost_model.gate_hints(&program.circuit);
     program
 }
 
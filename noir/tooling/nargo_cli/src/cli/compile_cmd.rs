@@ -21,7 +21,10 @@ use crate::backends::Backend;
 use crate::errors::CliError;
 
 use super::fs::program::only_acir;
-use super::fs::program::{read_program_from_file, save_contract_to_file, save_program_to_file};
+use super::fs::program::{
+    read_program_from_file, save_acir_json_to_file, save_contract_to_file, save_plonkish_to_file,
+    save_program_to_file, save_smt_lib_to_file,
+};
 use super::NargoConfig;
 use rayon::prelude::*;
 
@@ -80,9 +83,24 @@ pub(crate) fn run(
 
     // Save build artifacts to disk.
     let only_acir = args.compile_options.only_acir;
+    let cost_model = args.compile_options.cost_model();
     for (package, program) in binary_packages.into_iter().zip(compiled_program) {
         let program = nargo::ops::transform_program(program, expression_width);
+        let program = nargo::ops::annotate_program(program, &*cost_model);
         save_program(program.clone(), &package, &workspace.target_directory_path(), only_acir);
+        if args.compile_options.emit_smt_lib {
+            save_smt_lib_to_file(&program.circuit, &package.name, &circuit_dir);
+        }
+        if args.compile_options.emit_acir_json {
+            save_acir_json_to_file(&program, &package.name, &circuit_dir);
+        }
+        if args.compile_options.emit_plonkish {
+            if let Err(error) = save_plonkish_to_file(&program.circuit, &package.name, &circuit_dir)
+            {
+                let message = format!("Failed to export {} as PLONKish: {error}", package.name);
+                return Err(CliError::Generic(message));
+            }
+        }
     }
     for (package, contract) in contract_packages.into_iter().zip(compiled_contracts) {
         let contract = nargo::ops::transform_contract(contract, expression_width);
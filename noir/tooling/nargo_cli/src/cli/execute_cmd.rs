@@ -1,22 +1,27 @@
 use acvm::acir::native_types::WitnessMap;
 use bn254_blackbox_solver::Bn254BlackBoxSolver;
 use clap::Args;
+use std::path::PathBuf;
 
 use nargo::artifacts::debug::DebugArtifact;
 use nargo::constants::PROVER_INPUT_FILE;
 use nargo::errors::try_to_diagnose_runtime_error;
-use nargo::ops::{compile_program, DefaultForeignCallExecutor};
+use nargo::ops::DefaultForeignCallExecutor;
 use nargo::package::Package;
-use nargo::{insert_all_files_for_workspace_into_file_manager, parse_all};
+use nargo::{insert_all_files_for_workspace_into_file_manager, parse_all, prepare_package};
 use nargo_toml::{get_package_manifest, resolve_workspace_from_toml, PackageSelection};
 use noirc_abi::input_parser::{Format, InputValue};
 use noirc_abi::InputMap;
 use noirc_driver::{
-    file_manager_with_stdlib, CompileOptions, CompiledProgram, NOIR_ARTIFACT_VERSION_STRING,
+    brillig_profile, compile_main, file_manager_with_stdlib, CompileOptions, CompiledProgram,
+    NOIR_ARTIFACT_VERSION_STRING,
 };
 use noirc_frontend::graph::CrateName;
 
-use super::fs::{inputs::read_inputs_from_file, witness::save_witness_to_dir};
+use super::fs::{
+    inputs::read_inputs_from_file, program::save_brillig_profile_to_file,
+    witness::save_witness_to_dir,
+};
 use super::NargoConfig;
 use crate::backends::Backend;
 use crate::cli::compile_cmd::report_errors;
@@ -46,13 +51,22 @@ pub(crate) struct ExecuteCommand {
     /// JSON RPC url to solve oracle calls
     #[clap(long)]
     oracle_resolver: Option<String>,
+
+    /// Profile the unconstrained functions of each package, writing how often each of their
+    /// blocks was executed as folded stacks to `<package>.brillig.folded` in this directory
+    #[clap(long)]
+    brillig_profile_dir: Option<PathBuf>,
 }
 
 pub(crate) fn run(
     backend: &Backend,
-    args: ExecuteCommand,
+    mut args: ExecuteCommand,
     config: NargoConfig,
 ) -> Result<(), CliError> {
+    if args.brillig_profile_dir.is_some() {
+        args.compile_options.profile_brillig = true;
+    }
+
     let toml_path = get_package_manifest(&config.program_dir)?;
     let default_selection =
         if args.workspace { PackageSelection::All } else { PackageSelection::DefaultOrAll };
@@ -74,13 +88,10 @@ pub(crate) fn run(
         .unwrap_or_else(|| backend.get_backend_info_or_default());
     let binary_packages = workspace.into_iter().filter(|package| package.is_binary());
     for package in binary_packages {
-        let compilation_result = compile_program(
-            &workspace_file_manager,
-            &parsed_files,
-            package,
-            &args.compile_options,
-            None,
-        );
+        // The context is kept around to name the functions of the Brillig profile.
+        let (mut context, crate_id) =
+            prepare_package(&workspace_file_manager, &parsed_files, package);
+        let compilation_result = compile_main(&mut context, crate_id, &args.compile_options, None);
 
         let compiled_program = report_errors(
             compilation_result,
@@ -91,11 +102,13 @@ pub(crate) fn run(
 
         let compiled_program = nargo::ops::transform_program(compiled_program, expression_width);
 
+        let mut foreign_call_executor =
+            DefaultForeignCallExecutor::new(true, args.oracle_resolver.as_deref());
         let (return_value, solved_witness) = execute_program_and_decode(
-            compiled_program,
+            &compiled_program,
             package,
             &args.prover_name,
-            args.oracle_resolver.as_deref(),
+            &mut foreign_call_executor,
         )?;
 
         println!("[{}] Circuit witness successfully solved", package.name);
@@ -107,21 +120,31 @@ pub(crate) fn run(
 
             println!("[{}] Witness saved to {}", package.name, witness_path.display());
         }
+        if let Some(profile_dir) = &args.brillig_profile_dir {
+            let profile = brillig_profile(
+                &context,
+                &compiled_program,
+                foreign_call_executor.brillig_profile(),
+            );
+            let profile_path = save_brillig_profile_to_file(&profile, &package.name, profile_dir);
+
+            println!("[{}] Brillig profile saved to {}", package.name, profile_path.display());
+        }
     }
     Ok(())
 }
 
 fn execute_program_and_decode(
-    program: CompiledProgram,
+    program: &CompiledProgram,
     package: &Package,
     prover_name: &str,
-    foreign_call_resolver_url: Option<&str>,
+    foreign_call_executor: &mut DefaultForeignCallExecutor,
 ) -> Result<(Option<InputValue>, WitnessMap), CliError> {
     // Parse the initial witness values from Prover.toml
     let (inputs_map, _) =
         read_inputs_from_file(&package.root_dir, prover_name, Format::Toml, &program.abi)?;
-    let solved_witness = execute_program(&program, &inputs_map, foreign_call_resolver_url)?;
-    let public_abi = program.abi.public_abi();
+    let solved_witness = execute_program(program, &inputs_map, foreign_call_executor)?;
+    let public_abi = program.abi.clone().public_abi();
     let (_, return_value) = public_abi.decode(&solved_witness)?;
 
     Ok((return_value, solved_witness))
@@ -130,17 +153,19 @@ fn execute_program_and_decode(
 pub(crate) fn execute_program(
     compiled_program: &CompiledProgram,
     inputs_map: &InputMap,
-    foreign_call_resolver_url: Option<&str>,
+    foreign_call_executor: &mut DefaultForeignCallExecutor,
 ) -> Result<WitnessMap, CliError> {
     let blackbox_solver = Bn254BlackBoxSolver::new();
 
     let initial_witness = compiled_program.abi.encode(inputs_map, None)?;
 
-    let solved_witness_err = nargo::ops::execute_circuit(
+    let solved_witness_err = nargo::ops::execute_program(
         &compiled_program.circuit,
+        &compiled_program.acir_functions,
         initial_witness,
+        compiled_program.debug.brillig_step_bounds.clone(),
         &blackbox_solver,
-        &mut DefaultForeignCallExecutor::new(true, foreign_call_resolver_url),
+        foreign_call_executor,
     );
     match solved_witness_err {
         Ok(solved_witness) => Ok(solved_witness),
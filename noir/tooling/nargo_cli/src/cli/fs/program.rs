@@ -2,6 +2,9 @@ use std::path::{Path, PathBuf};
 
 use acvm::acir::circuit::Circuit;
 use nargo::artifacts::{contract::ContractArtifact, program::ProgramArtifact};
+use noirc_driver::{
+    circuit_to_plonkish, circuit_to_smt_lib, AcirJson, CompiledProgram, PlonkishError,
+};
 use noirc_frontend::graph::CrateName;
 
 use crate::errors::FilesystemError;
@@ -30,6 +33,64 @@ pub(crate) fn only_acir<P: AsRef<Path>>(
     circuit_path
 }
 
+/// Writes the constraints of the circuit as SMT-LIB to `<crate_name>.smt2`
+pub(crate) fn save_smt_lib_to_file<P: AsRef<Path>>(
+    circuit: &Circuit,
+    crate_name: &CrateName,
+    circuit_dir: P,
+) -> PathBuf {
+    create_named_dir(circuit_dir.as_ref(), "target");
+    let circuit_name: String = crate_name.into();
+    let smt_lib_path = circuit_dir.as_ref().join(circuit_name).with_extension("smt2");
+    write_to_file(circuit_to_smt_lib(circuit).as_bytes(), &smt_lib_path);
+
+    smt_lib_path
+}
+
+/// Writes the executions of profiled Brillig blocks as folded stacks to `<crate_name>.brillig.folded`
+pub(crate) fn save_brillig_profile_to_file<P: AsRef<Path>>(
+    profile: &str,
+    crate_name: &CrateName,
+    profile_dir: P,
+) -> PathBuf {
+    create_named_dir(profile_dir.as_ref(), "profile");
+    let circuit_name: String = crate_name.into();
+    let profile_path = profile_dir.as_ref().join(format!("{circuit_name}.brillig.folded"));
+    write_to_file(profile.as_bytes(), &profile_path);
+
+    profile_path
+}
+
+/// Writes the circuit with its debug information and warnings as JSON to `<crate_name>.acir.json`
+pub(crate) fn save_acir_json_to_file<P: AsRef<Path>>(
+    program: &CompiledProgram,
+    crate_name: &CrateName,
+    circuit_dir: P,
+) -> PathBuf {
+    create_named_dir(circuit_dir.as_ref(), "target");
+    let circuit_name: String = crate_name.into();
+    let acir_json_path = circuit_dir.as_ref().join(format!("{circuit_name}.acir.json"));
+    let acir_json = AcirJson::new(&program.circuit, &program.debug, &program.warnings);
+    write_to_file(&serde_json::to_vec(&acir_json).unwrap(), &acir_json_path);
+
+    acir_json_path
+}
+
+/// Writes the constraints of the circuit as a PLONKish table to `<crate_name>.plonkish.json`
+pub(crate) fn save_plonkish_to_file<P: AsRef<Path>>(
+    circuit: &Circuit,
+    crate_name: &CrateName,
+    circuit_dir: P,
+) -> Result<PathBuf, PlonkishError> {
+    let plonkish = circuit_to_plonkish(circuit)?;
+    create_named_dir(circuit_dir.as_ref(), "target");
+    let circuit_name: String = crate_name.into();
+    let plonkish_path = circuit_dir.as_ref().join(format!("{circuit_name}.plonkish.json"));
+    write_to_file(&serde_json::to_vec(&plonkish).unwrap(), &plonkish_path);
+
+    Ok(plonkish_path)
+}
+
 pub(crate) fn save_contract_to_file<P: AsRef<Path>>(
     compiled_contract: &ContractArtifact,
     circuit_name: &str,
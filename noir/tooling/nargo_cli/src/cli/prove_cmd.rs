@@ -1,6 +1,6 @@
 use clap::Args;
 use nargo::constants::{PROVER_INPUT_FILE, VERIFIER_INPUT_FILE};
-use nargo::ops::compile_program;
+use nargo::ops::{compile_program, DefaultForeignCallExecutor};
 use nargo::package::Package;
 use nargo::workspace::Workspace;
 use nargo::{insert_all_files_for_workspace_into_file_manager, parse_all};
@@ -122,8 +122,11 @@ pub(crate) fn prove_package(
     let (inputs_map, _) =
         read_inputs_from_file(&package.root_dir, prover_name, Format::Toml, &compiled_program.abi)?;
 
-    let solved_witness =
-        execute_program(&compiled_program, &inputs_map, foreign_call_resolver_url)?;
+    let solved_witness = execute_program(
+        &compiled_program,
+        &inputs_map,
+        &mut DefaultForeignCallExecutor::new(true, foreign_call_resolver_url),
+    )?;
 
     // Write public inputs into Verifier.toml
     let public_abi = compiled_program.abi.public_abi();
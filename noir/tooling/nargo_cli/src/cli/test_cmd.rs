@@ -6,7 +6,7 @@ use clap::Args;
 use fm::FileManager;
 use nargo::{
     insert_all_files_for_workspace_into_file_manager,
-    ops::{run_test, TestStatus},
+    ops::{run_test, run_test_checking_hints, TestStatus},
     package::Package,
     parse_all, prepare_package,
 };
@@ -50,6 +50,11 @@ pub(crate) struct TestCommand {
     /// JSON RPC url to solve oracle calls
     #[clap(long)]
     oracle_resolver: Option<String>,
+
+    /// Warn about outputs of unconstrained functions which are not constrained by any
+    /// constraint executed during the test
+    #[arg(long)]
+    check_hints: bool,
 }
 
 pub(crate) fn run(
@@ -95,6 +100,7 @@ pub(crate) fn run(
                 pattern,
                 args.show_output,
                 args.oracle_resolver.as_deref(),
+                args.check_hints,
                 &args.compile_options,
             )
         })
@@ -132,6 +138,7 @@ fn run_tests<S: BlackBoxFunctionSolver>(
     fn_name: FunctionNameMatch,
     show_output: bool,
     foreign_call_resolver_url: Option<&str>,
+    check_hints: bool,
     compile_options: &CompileOptions,
 ) -> Result<Vec<(String, TestStatus)>, CliError> {
     let (mut context, crate_id) = prepare_package(file_manager, parsed_files, package);
@@ -158,14 +165,26 @@ fn run_tests<S: BlackBoxFunctionSolver>(
             .expect("Failed to write to stderr");
         writer.flush().expect("Failed to flush writer");
 
-        let test_status = run_test(
-            blackbox_solver,
-            &context,
-            test_function,
-            show_output,
-            foreign_call_resolver_url,
-            compile_options,
-        );
+        let (test_status, hint_warnings) = if check_hints {
+            run_test_checking_hints(
+                blackbox_solver,
+                &context,
+                test_function,
+                show_output,
+                foreign_call_resolver_url,
+                compile_options,
+            )
+        } else {
+            let test_status = run_test(
+                blackbox_solver,
+                &context,
+                test_function,
+                show_output,
+                foreign_call_resolver_url,
+                compile_options,
+            );
+            (test_status, Vec::new())
+        };
 
         match &test_status {
             TestStatus::Pass { .. } => {
@@ -201,6 +220,15 @@ fn run_tests<S: BlackBoxFunctionSolver>(
         test_report.push((test_name, test_status));
 
         writer.reset().expect("Failed to reset writer");
+
+        if !hint_warnings.is_empty() {
+            noirc_errors::reporter::report_all(
+                context.file_manager.as_file_map(),
+                &hint_warnings,
+                compile_options.deny_warnings,
+                compile_options.silence_warnings,
+            );
+        }
     }
 
     write!(writer, "[{}] ", package.name).expect("Failed to write to stderr");
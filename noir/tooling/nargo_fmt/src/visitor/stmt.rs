@@ -38,8 +38,9 @@ impl super::FmtVisitor<'_> {
 
                     nested_shape.indent.block_indent(self.config);
 
-                    let message =
-                        message.map_or(String::new(), |message| format!(", \"{message}\""));
+                    let message = message.map_or(String::new(), |message| {
+                        format!(", {}", rewrite::sub_expr(self, nested_shape, message))
+                    });
 
                     let (callee, args) = match kind {
                         ConstrainKind::Assert => {
@@ -58,43 +58,8 @@ it('allows passing a custom foreign call handler', async () => {
     ['0x0000000000000000000000000000000000000000000000000000000000000002'],
     // Type metadata
     [
-      '0x000000000000000000000000000000000000000000000000000000000000007b',
-      '0x0000000000000000000000000000000000000000000000000000000000000022',
-      '0x000000000000000000000000000000000000000000000000000000000000006b',
-      '0x0000000000000000000000000000000000000000000000000000000000000069',
-      '0x000000000000000000000000000000000000000000000000000000000000006e',
-      '0x0000000000000000000000000000000000000000000000000000000000000064',
-      '0x0000000000000000000000000000000000000000000000000000000000000022',
-      '0x000000000000000000000000000000000000000000000000000000000000003a',
-      '0x0000000000000000000000000000000000000000000000000000000000000022',
-      '0x0000000000000000000000000000000000000000000000000000000000000075',
-      '0x000000000000000000000000000000000000000000000000000000000000006e',
-      '0x0000000000000000000000000000000000000000000000000000000000000073',
-      '0x0000000000000000000000000000000000000000000000000000000000000069',
-      '0x0000000000000000000000000000000000000000000000000000000000000067',
-      '0x000000000000000000000000000000000000000000000000000000000000006e',
-      '0x0000000000000000000000000000000000000000000000000000000000000065',
-      '0x0000000000000000000000000000000000000000000000000000000000000064',
-      '0x0000000000000000000000000000000000000000000000000000000000000069',
-      '0x000000000000000000000000000000000000000000000000000000000000006e',
-      '0x0000000000000000000000000000000000000000000000000000000000000074',
-      '0x0000000000000000000000000000000000000000000000000000000000000065',
-      '0x0000000000000000000000000000000000000000000000000000000000000067',
-      '0x0000000000000000000000000000000000000000000000000000000000000065',
-      '0x0000000000000000000000000000000000000000000000000000000000000072',
-      '0x0000000000000000000000000000000000000000000000000000000000000022',
-      '0x000000000000000000000000000000000000000000000000000000000000002c',
-      '0x0000000000000000000000000000000000000000000000000000000000000022',
-      '0x0000000000000000000000000000000000000000000000000000000000000077',
-      '0x0000000000000000000000000000000000000000000000000000000000000069',
-      '0x0000000000000000000000000000000000000000000000000000000000000064',
-      '0x0000000000000000000000000000000000000000000000000000000000000074',
-      '0x0000000000000000000000000000000000000000000000000000000000000068',
-      '0x0000000000000000000000000000000000000000000000000000000000000022',
-      '0x000000000000000000000000000000000000000000000000000000000000003a',
-      '0x0000000000000000000000000000000000000000000000000000000000000036',
-      '0x0000000000000000000000000000000000000000000000000000000000000034',
-      '0x000000000000000000000000000000000000000000000000000000000000007d',
+      '0x0000000000000000000000000000000000000000000000000000000000000005',
+      '0x0000000000000000000000000000000000000000000000000000000000000040',
     ],
     // format string?
     ['0x0000000000000000000000000000000000000000000000000000000000000000'],
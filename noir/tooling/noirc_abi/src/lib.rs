@@ -23,8 +23,11 @@ use std::{collections::BTreeMap, str};
 
 pub mod errors;
 pub mod input_parser;
+mod public_inputs;
 mod serialization;
 
+pub use public_inputs::{ElementEncoding, PublicInput, PublicInputTable, PublicInputValue};
+
 /// A map from the fields in an TOML/JSON file which correspond to some ABI to their values
 pub type InputMap = BTreeMap<String, InputValue>;
 
@@ -0,0 +1,144 @@
+//! Verifiers receive the public inputs of a circuit as a flat list of field elements. A
+//! [`PublicInputTable`] describes which values of the ABI make up each of them, so that glue code
+//! for a verifier (e.g. a Solidity contract) can be generated without reimplementing how the ABI
+//! flattens values into field elements.
+use serde::{Deserialize, Serialize};
+
+use crate::{AbiType, Sign};
+
+/// The public inputs of a circuit, in the order in which they are passed to the verifier.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PublicInputTable(pub Vec<PublicInput>);
+
+impl PublicInputTable {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicInput {
+    /// Index of the public input in the list passed to the verifier.
+    pub position: usize,
+    /// The values held by the public input. There is more than one if several values were packed
+    /// into it, or if the return value of `main` reuses a public parameter.
+    pub values: Vec<PublicInputValue>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicInputValue {
+    /// Path to the value within the ABI, e.g. `pubkey.x`, `hash[3]` or `return.0`.
+    pub path: String,
+    #[serde(flatten)]
+    pub encoding: ElementEncoding,
+    /// The bit of the public input at which the value starts.
+    pub bit_offset: u32,
+}
+
+/// How a single field element of a flattened ABI value is to be interpreted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "encoding", rename_all = "lowercase")]
+pub enum ElementEncoding {
+    Field,
+    /// An integer of `width` bits, which is stored in two's complement if signed.
+    Integer {
+        sign: Sign,
+        width: u32,
+    },
+    Boolean,
+}
+
+impl AbiType {
+    /// Returns the path and encoding of each field element of a value of this type, in the order
+    /// in which they are encoded, where `path` is the path to the value itself.
+    pub fn flattened_elements(&self, path: &str) -> Vec<(String, ElementEncoding)> {
+        let mut elements = Vec::with_capacity(self.field_count() as usize);
+        self.flatten_elements(path.to_owned(), &mut elements);
+        elements
+    }
+
+    fn flatten_elements(&self, path: String, elements: &mut Vec<(String, ElementEncoding)>) {
+        match self {
+            AbiType::Field => elements.push((path, ElementEncoding::Field)),
+            AbiType::Integer { sign, width } => {
+                elements.push((path, ElementEncoding::Integer { sign: *sign, width: *width }));
+            }
+            AbiType::Boolean => elements.push((path, ElementEncoding::Boolean)),
+            AbiType::String { length } => {
+                let encoding = ElementEncoding::Integer { sign: Sign::Unsigned, width: 8 };
+                elements.extend((0..*length).map(|index| (format!("{path}[{index}]"), encoding)));
+            }
+            AbiType::Array { length, typ } => {
+                for index in 0..*length {
+                    typ.flatten_elements(format!("{path}[{index}]"), elements);
+                }
+            }
+            AbiType::Struct { fields, .. } => {
+                for (name, typ) in fields {
+                    typ.flatten_elements(format!("{path}.{name}"), elements);
+                }
+            }
+            AbiType::Tuple { fields } => {
+                for (index, typ) in fields.iter().enumerate() {
+                    typ.flatten_elements(format!("{path}.{index}"), elements);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{AbiType, Sign};
+
+    use super::{ElementEncoding, PublicInput, PublicInputTable, PublicInputValue};
+
+    #[test]
+    fn flattens_nested_values_into_paths() {
+        let typ = AbiType::Struct {
+            path: "Foo".to_string(),
+            fields: vec![
+                ("x".to_string(), AbiType::Field),
+                (
+                    "y".to_string(),
+                    AbiType::Array {
+                        length: 2,
+                        typ: Box::new(AbiType::Tuple {
+                            fields: vec![AbiType::Boolean, AbiType::String { length: 1 }],
+                        }),
+                    },
+                ),
+            ],
+        };
+
+        let byte = ElementEncoding::Integer { sign: Sign::Unsigned, width: 8 };
+        let expected = vec![
+            ("foo.x".to_string(), ElementEncoding::Field),
+            ("foo.y[0].0".to_string(), ElementEncoding::Boolean),
+            ("foo.y[0].1[0]".to_string(), byte),
+            ("foo.y[1].0".to_string(), ElementEncoding::Boolean),
+            ("foo.y[1].1[0]".to_string(), byte),
+        ];
+        assert_eq!(typ.flattened_elements("foo"), expected);
+    }
+
+    #[test]
+    fn serializes_encoding_alongside_path() {
+        let table = PublicInputTable(vec![PublicInput {
+            position: 0,
+            values: vec![PublicInputValue {
+                path: "x".to_string(),
+                encoding: ElementEncoding::Integer { sign: Sign::Signed, width: 8 },
+                bit_offset: 8,
+            }],
+        }]);
+
+        let json = serde_json::to_string(&table).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"position":0,"values":[{"path":"x","encoding":"integer","sign":"signed","width":8,"bit_offset":8}]}]"#
+        );
+        assert_eq!(serde_json::from_str::<PublicInputTable>(&json).unwrap(), table);
+    }
+}